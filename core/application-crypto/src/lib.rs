@@ -34,6 +34,8 @@ pub use codec;
 pub use serde;
 #[doc(hidden)]
 pub use rstd::{ops::Deref, vec::Vec};
+#[doc(hidden)]
+pub use srml_metadata as metadata;
 
 pub mod ed25519;
 pub mod sr25519;
@@ -205,6 +207,19 @@ macro_rules! app_crypto {
 			type Generic = $public;
 		}
 
+		impl $crate::metadata::TypeInfo for Public {
+			fn path() -> $crate::Vec<&'static str> {
+				module_path!().split("::").chain(::core::iter::once("Public")).collect()
+			}
+
+			fn type_def(registry: &mut $crate::metadata::Registry) -> $crate::metadata::TypeDef {
+				$crate::metadata::TypeDef::Composite(<[_]>::to_vec(&[$crate::metadata::Field {
+					name: None,
+					ty: registry.register::<$public>(),
+				}]))
+			}
+		}
+
 		impl $crate::RuntimeAppPublic for Public where $public: $crate::RuntimePublic<Signature=$sig> {
 			const ID: $crate::KeyTypeId = $key_type;
 			type Signature = Signature;
@@ -268,6 +283,19 @@ macro_rules! app_crypto {
 		impl $crate::AppSignature for Signature {
 			type Generic = $sig;
 		}
+
+		impl $crate::metadata::TypeInfo for Signature {
+			fn path() -> $crate::Vec<&'static str> {
+				module_path!().split("::").chain(::core::iter::once("Signature")).collect()
+			}
+
+			fn type_def(registry: &mut $crate::metadata::Registry) -> $crate::metadata::TypeDef {
+				$crate::metadata::TypeDef::Composite(<[_]>::to_vec(&[$crate::metadata::Field {
+					name: None,
+					ty: registry.register::<$sig>(),
+				}]))
+			}
+		}
 	}
 }
 
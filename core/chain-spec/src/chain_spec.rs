@@ -33,6 +33,7 @@ enum GenesisSource<G> {
 	File(PathBuf),
 	Binary(Cow<'static, [u8]>),
 	Factory(Rc<dyn Fn() -> G>),
+	Storage((StorageOverlay, ChildrenStorageOverlay)),
 }
 
 impl<G> Clone for GenesisSource<G> {
@@ -41,6 +42,7 @@ impl<G> Clone for GenesisSource<G> {
 			GenesisSource::File(ref path) => GenesisSource::File(path.clone()),
 			GenesisSource::Binary(ref d) => GenesisSource::Binary(d.clone()),
 			GenesisSource::Factory(ref f) => GenesisSource::Factory(f.clone()),
+			GenesisSource::Storage(ref s) => GenesisSource::Storage(s.clone()),
 		}
 	}
 }
@@ -66,6 +68,7 @@ impl<G: RuntimeGenesis> GenesisSource<G> {
 				Ok(genesis.genesis)
 			},
 			GenesisSource::Factory(f) => Ok(Genesis::Runtime(f())),
+			GenesisSource::Storage(storage) => Ok(raw_genesis(storage.clone())),
 		}
 	}
 }
@@ -103,6 +106,22 @@ enum Genesis<G> {
 	),
 }
 
+fn raw_genesis<G>(storage: (StorageOverlay, ChildrenStorageOverlay)) -> Genesis<G> {
+	let top = storage.0.into_iter()
+		.map(|(k, v)| (StorageKey(k), StorageData(v)))
+		.collect();
+	let children = storage.1.into_iter()
+		.map(|(sk, child)| (
+				StorageKey(sk),
+				child.into_iter()
+					.map(|(k, v)| (StorageKey(k), StorageData(v)))
+					.collect(),
+		))
+		.collect();
+
+	Genesis::Raw(top, children)
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 #[serde(deny_unknown_fields)]
@@ -187,6 +206,13 @@ impl<G, E> ChainSpec<G, E> {
 		&self.spec.extensions
 	}
 
+	/// Replace the genesis of the chain with the given raw storage.
+	///
+	/// This is used to start a new chain from the state of an existing one.
+	pub fn set_storage(&mut self, storage: (StorageOverlay, ChildrenStorageOverlay)) {
+		self.genesis = GenesisSource::Storage(storage);
+	}
+
 	/// Create hardcoded spec.
 	pub fn from_genesis<F: Fn() -> G + 'static>(
 		name: &str,
@@ -253,22 +279,7 @@ impl<G: RuntimeGenesis, E: serde::Serialize> ChainSpec<G, E> {
 
 		};
 		let genesis = match (raw, self.genesis.resolve()?) {
-			(true, Genesis::Runtime(g)) => {
-				let storage = g.build_storage()?;
-				let top = storage.0.into_iter()
-					.map(|(k, v)| (StorageKey(k), StorageData(v)))
-					.collect();
-				let children = storage.1.into_iter()
-					.map(|(sk, child)| (
-							StorageKey(sk),
-							child.into_iter()
-								.map(|(k, v)| (StorageKey(k), StorageData(v)))
-								.collect(),
-					))
-					.collect();
-
-				Genesis::Raw(top, children)
-			},
+			(true, Genesis::Runtime(g)) => raw_genesis(g.build_storage()?),
 			(_, genesis) => genesis,
 		};
 		let spec = Container {
@@ -329,4 +340,24 @@ mod tests {
 
 		assert_eq!(spec.extensions().my_property, "Test Extension");
 	}
+
+	#[test]
+	fn should_use_storage_as_raw_genesis() {
+		let mut spec = TestSpec::from_json_file(
+			PathBuf::from("./res/chain_spec.json")
+		).unwrap();
+		let top = vec![(b"key".to_vec(), b"value".to_vec())].into_iter().collect();
+		let child = vec![(b"child key".to_vec(), b"child value".to_vec())].into_iter().collect();
+		let children = vec![(b":child_storage:default:test".to_vec(), child)].into_iter().collect();
+		spec.set_storage((top, children));
+
+		let storage = (&spec).build_storage().unwrap();
+		assert_eq!(storage.0.get(&b"key"[..]), Some(&b"value".to_vec()));
+		let child = storage.1.get(&b":child_storage:default:test"[..]).unwrap();
+		assert_eq!(child.get(&b"child key"[..]), Some(&b"child value".to_vec()));
+
+		let json = spec.to_json(false).unwrap();
+		let spec = TestSpec::from_json_bytes(json.into_bytes()).unwrap();
+		assert_eq!((&spec).build_storage().unwrap(), storage);
+	}
 }
@@ -29,8 +29,9 @@ pub mod informant;
 use client::ExecutionStrategies;
 use service::{
 	config::{Configuration, DatabaseConfig},
-	ServiceBuilderExport, ServiceBuilderImport, ServiceBuilderRevert,
-	RuntimeGenesis, ChainSpecExtension, PruningMode, ChainSpec,
+	ServiceBuilderExport, ServiceBuilderImport, ServiceBuilderRevert, ServiceBuilderCheckDb,
+	ServiceBuilderExportState,
+	RuntimeGenesis, ChainSpecExtension, PruningMode, BlocksPruning, ChainSpec,
 };
 use network::{
 	self,
@@ -52,7 +53,8 @@ use structopt::{StructOpt, clap::AppSettings};
 #[doc(hidden)]
 pub use structopt::clap::App;
 use params::{
-	RunCmd, PurgeChainCmd, RevertCmd, ImportBlocksCmd, ExportBlocksCmd, BuildSpecCmd,
+	RunCmd, PurgeChainCmd, RevertCmd, CheckDbCmd, ImportBlocksCmd, ExportBlocksCmd, ExportStateCmd,
+	BuildSpecCmd,
 	NetworkConfigurationParams, MergeParameters, TransactionPoolParams,
 	NodeKeyParams, NodeKeyType, Cors,
 };
@@ -226,12 +228,18 @@ where
 		params::CoreParams::ImportBlocks(params) => ParseAndPrepare::ImportBlocks(
 			ParseAndPrepareImport { params, version }
 		),
+		params::CoreParams::ExportState(params) => ParseAndPrepare::ExportState(
+			ParseAndPrepareExportState { params, version }
+		),
 		params::CoreParams::PurgeChain(params) => ParseAndPrepare::PurgeChain(
 			ParseAndPreparePurge { params, version }
 		),
 		params::CoreParams::Revert(params) => ParseAndPrepare::RevertChain(
 			ParseAndPrepareRevert { params, version }
 		),
+		params::CoreParams::CheckDb(params) => ParseAndPrepare::CheckDb(
+			ParseAndPrepareCheckDb { params, version }
+		),
 		params::CoreParams::Custom(params) => ParseAndPrepare::CustomCommand(params),
 	}
 }
@@ -258,10 +266,14 @@ pub enum ParseAndPrepare<'a, CC, RP> {
 	ExportBlocks(ParseAndPrepareExport<'a>),
 	/// Command ready to import the chain.
 	ImportBlocks(ParseAndPrepareImport<'a>),
+	/// Command ready to export the state.
+	ExportState(ParseAndPrepareExportState<'a>),
 	/// Command ready to purge the chain.
 	PurgeChain(ParseAndPreparePurge<'a>),
 	/// Command ready to revert the chain.
 	RevertChain(ParseAndPrepareRevert<'a>),
+	/// Command ready to check the database.
+	CheckDb(ParseAndPrepareCheckDb<'a>),
 	/// An additional custom command passed to `parse_and_prepare`.
 	CustomCommand(CC),
 }
@@ -351,7 +363,7 @@ impl<'a> ParseAndPrepareExport<'a> {
 	{
 		let config = create_config_with_db_path(spec_factory, &self.params.shared_params, self.version)?;
 
-		if let DatabaseConfig::Path { ref path, .. } = &config.database {
+		if let Some(path) = config.database.path() {
 			info!("DB path: {}", path.display());
 		}
 		let from = self.params.from.unwrap_or(1);
@@ -413,6 +425,45 @@ impl<'a> ParseAndPrepareImport<'a> {
 	}
 }
 
+/// Command ready to export the state.
+pub struct ParseAndPrepareExportState<'a> {
+	params: ExportStateCmd,
+	version: &'a VersionInfo,
+}
+
+impl<'a> ParseAndPrepareExportState<'a> {
+	/// Runs the command and exports the state into a chain spec.
+	pub fn run_with_builder<C, G, E, F, B, S>(
+		self,
+		builder: F,
+		spec_factory: S,
+	) -> error::Result<()> where
+		S: FnOnce(&str) -> Result<Option<ChainSpec<G, E>>, String>,
+		F: FnOnce(Configuration<C, G, E>) -> Result<B, error::Error>,
+		B: ServiceBuilderExportState,
+		C: Default,
+		G: RuntimeGenesis,
+		E: ChainSpecExtension,
+	{
+		let config = create_config_with_db_path(spec_factory, &self.params.shared_params, self.version)?;
+
+		if let Some(path) = config.database.path() {
+			info!("DB path: {}", path.display());
+		}
+		let mut spec = config.chain_spec.clone();
+		let storage = builder(config)?.export_raw_state(self.params.at.map(Into::into))?;
+		spec.set_storage(storage);
+		let json = service::chain_ops::build_spec(spec, true)?;
+
+		let mut file: Box<dyn Write> = match self.params.output {
+			Some(filename) => Box::new(File::create(filename)?),
+			None => Box::new(stdout()),
+		};
+		file.write_all(json.as_bytes())?;
+		Ok(())
+	}
+}
+
 /// Command ready to purge the chain.
 pub struct ParseAndPreparePurge<'a> {
 	params: PurgeChainCmd,
@@ -432,9 +483,9 @@ impl<'a> ParseAndPreparePurge<'a> {
 		let config = create_config_with_db_path::<(), _, _, _>(
 			spec_factory, &self.params.shared_params, self.version
 		)?;
-		let db_path = match config.database {
-			DatabaseConfig::Path { path, .. } => path,
-			_ => {
+		let db_path = match config.database.path() {
+			Some(path) => path.to_owned(),
+			None => {
 				eprintln!("Cannot purge custom database implementation");
 				return Ok(());
 			}
@@ -500,6 +551,34 @@ impl<'a> ParseAndPrepareRevert<'a> {
 	}
 }
 
+/// Command ready to check the database.
+pub struct ParseAndPrepareCheckDb<'a> {
+	params: CheckDbCmd,
+	version: &'a VersionInfo,
+}
+
+impl<'a> ParseAndPrepareCheckDb<'a> {
+	/// Runs the command and checks the database.
+	pub fn run_with_builder<C, G, E, F, B, S>(
+		self,
+		builder: F,
+		spec_factory: S
+	) -> error::Result<()> where
+		S: FnOnce(&str) -> Result<Option<ChainSpec<G, E>>, String>,
+		F: FnOnce(Configuration<C, G, E>) -> Result<B, error::Error>,
+		B: ServiceBuilderCheckDb,
+		C: Default,
+		G: RuntimeGenesis,
+		E: ChainSpecExtension,
+	{
+		let config = create_config_with_db_path(
+			spec_factory, &self.params.shared_params, self.version
+		)?;
+		builder(config)?.check_db(self.params.repair)?;
+		Ok(())
+	}
+}
+
 /// Create a `NodeKeyConfig` from the given `NodeKeyParams` in the context
 /// of an optional network config storage directory.
 fn node_key_config<P>(params: NodeKeyParams, net_config_dir: &Option<P>)
@@ -605,6 +684,7 @@ fn fill_network_configuration(
 		enable_mdns: !is_dev && !cli.no_mdns,
 		wasm_external_transport: None,
 	};
+	config.sync_mode = cli.sync.into();
 
 	Ok(())
 }
@@ -672,10 +752,12 @@ where
 		|| keystore_path(&base_path, config.chain_spec.id())
 	);
 
-	config.database = DatabaseConfig::Path {
-		path: db_path(&base_path, config.chain_spec.id()),
-		cache_size: cli.database_cache_size,
-	};
+	config.database = database_config(
+		&cli.shared_params,
+		&base_path,
+		config.chain_spec.id(),
+		cli.database_cache_size,
+	);
 	config.state_cache_size = cli.state_cache_size;
 
 	let is_dev = cli.shared_params.dev;
@@ -715,6 +797,31 @@ where
 		},
 	};
 
+	config.blocks_pruning = match cli.blocks_pruning {
+		None => {
+			if cli.prune_justifications {
+				return Err(error::Error::Input(
+					"Pruning justifications requires `--blocks-pruning`.".to_string()
+				));
+			}
+			BlocksPruning::KeepAll
+		},
+		Some(ref s) if s == "archive" => BlocksPruning::KeepAll,
+		Some(s) => BlocksPruning::KeepFinalized {
+			blocks: s.parse().map_err(|_|
+				error::Error::Input("Invalid blocks pruning mode specified".to_string())
+			)?,
+			justifications: cli.prune_justifications,
+		},
+	};
+
+	if cli.storage_index && !config.pruning.is_archive() {
+		return Err(error::Error::Input(
+			"The storage index requires state pruning to be disabled (i.e. archive).".to_string()
+		));
+	}
+	config.storage_index = cli.storage_index;
+
 	config.wasm_method = cli.wasm_method.into();
 
 	let exec = cli.execution_strategies;
@@ -760,10 +867,16 @@ where
 
 	let rpc_interface: &str = if cli.rpc_external { "0.0.0.0" } else { "127.0.0.1" };
 	let ws_interface: &str = if cli.ws_external { "0.0.0.0" } else { "127.0.0.1" };
+	let prometheus_interface: &str = if cli.prometheus_external { "0.0.0.0" } else { "127.0.0.1" };
 
 	config.rpc_http = Some(parse_address(&format!("{}:{}", rpc_interface, 9933), cli.rpc_port)?);
 	config.rpc_ws = Some(parse_address(&format!("{}:{}", ws_interface, 9944), cli.ws_port)?);
 
+	config.prometheus_port = match cli.prometheus_port {
+		Some(port) => Some(parse_address(&format!("{}:{}", prometheus_interface, port), None)?),
+		None => None,
+	};
+
 	config.rpc_ws_max_connections = cli.ws_max_connections;
 	config.rpc_cors = cli.rpc_cors.unwrap_or_else(|| if is_dev {
 		log::warn!("Running in --dev mode, RPC CORS has been disabled.");
@@ -839,10 +952,7 @@ where
 	let base_path = base_path(cli, version);
 
 	let mut config = service::Configuration::default_with_spec(spec.clone());
-	config.database = DatabaseConfig::Path {
-		path: db_path(&base_path, spec.id()),
-		cache_size: None,
-	};
+	config.database = database_config(cli, &base_path, spec.id(), None);
 
 	Ok(config)
 }
@@ -882,6 +992,33 @@ fn db_path(base_path: &Path, chain_id: &str) -> PathBuf {
 	path
 }
 
+fn sled_path(base_path: &Path, chain_id: &str) -> PathBuf {
+	let mut path = base_path.to_owned();
+	path.push("chains");
+	path.push(chain_id);
+	path.push("sled");
+	path
+}
+
+/// Returns the configuration of the database selected with `--db`.
+fn database_config(
+	cli: &SharedParams,
+	base_path: &Path,
+	chain_id: &str,
+	cache_size: Option<u32>,
+) -> DatabaseConfig {
+	match cli.database {
+		params::Database::RocksDb => DatabaseConfig::Path {
+			path: db_path(base_path, chain_id),
+			cache_size,
+		},
+		params::Database::Sled => DatabaseConfig::Sled {
+			path: sled_path(base_path, chain_id),
+			flush_on_commit: !cli.db_no_flush,
+		},
+	}
+}
+
 fn network_path(base_path: &Path, chain_id: &str) -> PathBuf {
 	let mut path = base_path.to_owned();
 	path.push("chains");
@@ -91,6 +91,37 @@ arg_enum! {
 	}
 }
 
+arg_enum! {
+	/// How to sync the chain.
+	#[allow(missing_docs)]
+	#[derive(Debug, Clone, Copy)]
+	pub enum SyncMode {
+		Full,
+		Warp,
+	}
+}
+
+impl Into<network::config::SyncMode> for SyncMode {
+	fn into(self) -> network::config::SyncMode {
+		match self {
+			SyncMode::Full => network::config::SyncMode::Full,
+			SyncMode::Warp => network::config::SyncMode::Warp,
+		}
+	}
+}
+
+arg_enum! {
+	/// Database backend
+	#[allow(missing_docs)]
+	#[derive(Debug, Clone, Copy)]
+	pub enum Database {
+		// Facebook's RocksDB
+		RocksDb,
+		// Pure-Rust embedded store
+		Sled,
+	}
+}
+
 /// Shared parameters used by all `CoreParams`.
 #[derive(Debug, StructOpt, Clone)]
 pub struct SharedParams {
@@ -109,6 +140,24 @@ pub struct SharedParams {
 	/// Sets a custom logging filter.
 	#[structopt(short = "l", long = "log", value_name = "LOG_PATTERN")]
 	pub log: Option<String>,
+
+	/// Select the database backend to use.
+	///
+	/// `RocksDb` is stored in the `db` directory of the chain, `Sled` in the `sled` directory.
+	#[structopt(
+		long = "db",
+		value_name = "DB",
+		possible_values = &Database::variants(),
+		case_insensitive = true,
+		default_value = "RocksDb"
+	)]
+	pub database: Database,
+
+	/// Do not wait for the sled database to be flushed to disk on every commit.
+	///
+	/// Sled then flushes in the background, so the latest blocks may be lost on a crash.
+	#[structopt(long = "db-no-flush")]
+	pub db_no_flush: bool,
 }
 
 impl GetLogFilter for SharedParams {
@@ -160,6 +209,20 @@ pub struct NetworkConfigurationParams {
 	#[structopt(long = "no-mdns")]
 	pub no_mdns: bool,
 
+	/// Blockchain syncing mode.
+	///
+	/// `Full` downloads and executes all the blocks. `Warp` downloads the block headers and
+	/// justifications, then the state of the latest finalized block, and only executes the
+	/// blocks after it. Warp sync is only performed when the node starts from genesis.
+	#[structopt(
+		long = "sync",
+		value_name = "SYNC_MODE",
+		possible_values = &SyncMode::variants(),
+		case_insensitive = true,
+		default_value = "Full"
+	)]
+	pub sync: SyncMode,
+
 	#[allow(missing_docs)]
 	#[structopt(flatten)]
 	pub node_key_params: NodeKeyParams
@@ -373,6 +436,12 @@ pub struct RunCmd {
 	#[structopt(long = "ws-external")]
 	pub ws_external: bool,
 
+	/// Listen to all Prometheus endpoint interfaces.
+	///
+	/// Default is local.
+	#[structopt(long = "prometheus-external")]
+	pub prometheus_external: bool,
+
 	/// Specify HTTP RPC server TCP port.
 	#[structopt(long = "rpc-port", value_name = "PORT")]
 	pub rpc_port: Option<u16>,
@@ -381,6 +450,12 @@ pub struct RunCmd {
 	#[structopt(long = "ws-port", value_name = "PORT")]
 	pub ws_port: Option<u16>,
 
+	/// Expose Prometheus metrics on the `/metrics` path of this TCP port.
+	///
+	/// The endpoint is disabled when not specified.
+	#[structopt(long = "prometheus-port", value_name = "PORT")]
+	pub prometheus_port: Option<u16>,
+
 	/// Maximum number of WS RPC server connections.
 	#[structopt(long = "ws-max-connections", value_name = "COUNT")]
 	pub ws_max_connections: Option<usize>,
@@ -411,6 +486,26 @@ pub struct RunCmd {
 	#[structopt(long = "unsafe-pruning")]
 	pub unsafe_pruning: bool,
 
+	/// Specify the block bodies pruning mode, a number of finalized blocks to keep or 'archive'.
+	///
+	/// Headers are always kept. Default is to keep all block bodies (i.e. 'archive').
+	#[structopt(long = "blocks-pruning", value_name = "PRUNING_MODE")]
+	pub blocks_pruning: Option<String>,
+
+	/// Prune the justifications along with the block bodies.
+	///
+	/// The justifications the consensus engines still need, such as those of
+	/// authority set changes, are kept.
+	#[structopt(long = "prune-justifications")]
+	pub prune_justifications: bool,
+
+	/// Keep an index of the storage changes to speed up historical storage queries.
+	///
+	/// Requires state pruning to be disabled (i.e. 'archive'). The index is built
+	/// while importing blocks, so it can only be enabled on a new database.
+	#[structopt(long = "storage-index")]
+	pub storage_index: bool,
+
 	/// The human-readable name for this node.
 	///
 	/// The node name will be reported to the telemetry server, if enabled.
@@ -691,6 +786,26 @@ pub struct ExportBlocksCmd {
 
 impl_get_log_filter!(ExportBlocksCmd);
 
+/// The `export-state` command used to export the state of the chain as a chain spec.
+#[derive(Debug, StructOpt, Clone)]
+pub struct ExportStateCmd {
+	/// Output file name or stdout if unspecified.
+	#[structopt(parse(from_os_str))]
+	pub output: Option<PathBuf>,
+
+	/// Specify the block number to export the state at.
+	///
+	/// Default is best block.
+	#[structopt(long = "at", value_name = "BLOCK")]
+	pub at: Option<u32>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl_get_log_filter!(ExportStateCmd);
+
 /// The `import-blocks` command used to import blocks.
 #[derive(Debug, StructOpt, Clone)]
 pub struct ImportBlocksCmd {
@@ -759,6 +874,20 @@ pub struct PurgeChainCmd {
 
 impl_get_log_filter!(PurgeChainCmd);
 
+/// The `check-db` command used to check the integrity of the database.
+#[derive(Debug, StructOpt, Clone)]
+pub struct CheckDbCmd {
+	/// Repair the issues that can be fixed without losing data, such as dangling leaves.
+	#[structopt(long = "repair")]
+	pub repair: bool,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl_get_log_filter!(CheckDbCmd);
+
 /// All core commands that are provided by default.
 ///
 /// The core commands are split into multiple subcommands and `Run` is the default subcommand. From
@@ -778,12 +907,18 @@ pub enum CoreParams<CC, RP> {
 	/// Import blocks from file.
 	ImportBlocks(ImportBlocksCmd),
 
+	/// Export the state of a given block into a chain spec.
+	ExportState(ExportStateCmd),
+
 	/// Revert chain to the previous state.
 	Revert(RevertCmd),
 
 	/// Remove the whole chain data.
 	PurgeChain(PurgeChainCmd),
 
+	/// Check the integrity of the database.
+	CheckDb(CheckDbCmd),
+
 	/// Further custom subcommands.
 	Custom(CC),
 }
@@ -811,6 +946,12 @@ impl<CC, RP> StructOpt for CoreParams<CC, RP> where
 			ImportBlocksCmd::augment_clap(SubCommand::with_name("import-blocks"))
 				.about("Import blocks from file.")
 		)
+		.subcommand(
+			ExportStateCmd::augment_clap(SubCommand::with_name("export-state"))
+				.about("Export the state of a given block into a chain spec with a raw genesis, \
+						which can be used to start a new chain from that state."
+					)
+		)
 		.subcommand(
 			RevertCmd::augment_clap(SubCommand::with_name("revert"))
 				.about("Revert chain to the previous state.")
@@ -819,6 +960,10 @@ impl<CC, RP> StructOpt for CoreParams<CC, RP> where
 			PurgeChainCmd::augment_clap(SubCommand::with_name("purge-chain"))
 				.about("Remove the whole chain data.")
 		)
+		.subcommand(
+			CheckDbCmd::augment_clap(SubCommand::with_name("check-db"))
+				.about("Check the integrity of the database, optionally repairing it.")
+		)
 	}
 
 	fn from_clap(matches: &::structopt::clap::ArgMatches) -> Self {
@@ -829,9 +974,12 @@ impl<CC, RP> StructOpt for CoreParams<CC, RP> where
 				CoreParams::ExportBlocks(ExportBlocksCmd::from_clap(matches)),
 			("import-blocks", Some(matches)) =>
 				CoreParams::ImportBlocks(ImportBlocksCmd::from_clap(matches)),
+			("export-state", Some(matches)) =>
+				CoreParams::ExportState(ExportStateCmd::from_clap(matches)),
 			("revert", Some(matches)) => CoreParams::Revert(RevertCmd::from_clap(matches)),
 			("purge-chain", Some(matches)) =>
 				CoreParams::PurgeChain(PurgeChainCmd::from_clap(matches)),
+			("check-db", Some(matches)) => CoreParams::CheckDb(CheckDbCmd::from_clap(matches)),
 			(_, None) => CoreParams::Run(MergeParameters::from_clap(matches)),
 			_ => CoreParams::Custom(CC::from_clap(matches)),
 		}
@@ -845,8 +993,10 @@ impl<CC, RP> GetLogFilter for CoreParams<CC, RP> where CC: GetLogFilter {
 			CoreParams::BuildSpec(c) => c.get_log_filter(),
 			CoreParams::ExportBlocks(c) => c.get_log_filter(),
 			CoreParams::ImportBlocks(c) => c.get_log_filter(),
+			CoreParams::ExportState(c) => c.get_log_filter(),
 			CoreParams::PurgeChain(c) => c.get_log_filter(),
 			CoreParams::Revert(c) => c.get_log_filter(),
+			CoreParams::CheckDb(c) => c.get_log_filter(),
 			CoreParams::Custom(c) => c.get_log_filter(),
 		}
 	}
@@ -16,19 +16,20 @@
 
 //! DB-backed cache of blockchain data.
 
-use std::{sync::Arc, collections::HashMap};
+use std::{sync::Arc, collections::{HashMap, HashSet}};
 use parking_lot::RwLock;
 
-use kvdb::{KeyValueDB, DBTransaction};
+use database::{Database, Transaction};
 
 use client::blockchain::{well_known_cache_keys::{self, Id as CacheKeyId}, Cache as BlockchainCache};
 use client::error::Result as ClientResult;
 use codec::{Encode, Decode};
 use sr_primitives::generic::BlockId;
 use sr_primitives::traits::{Block as BlockT, Header as HeaderT, NumberFor, Zero};
-use crate::utils::{self, COLUMN_META, db_err};
+use crate::utils::{self, COLUMN_META, db_err, meta_keys};
 
 use self::list_cache::{ListCache, PruningStrategy};
+use self::list_storage::Storage;
 
 mod list_cache;
 mod list_entry;
@@ -76,7 +77,7 @@ impl<T> CacheItemT for T where T: Clone + Decode + Encode + PartialEq {}
 /// Database-backed blockchain data cache.
 pub struct DbCache<Block: BlockT> {
 	cache_at: HashMap<CacheKeyId, ListCache<Block, Vec<u8>, self::list_storage::DbStorage>>,
-	db: Arc<dyn KeyValueDB>,
+	db: Arc<dyn Database>,
 	key_lookup_column: Option<u32>,
 	header_column: Option<u32>,
 	authorities_column: Option<u32>,
@@ -87,7 +88,7 @@ pub struct DbCache<Block: BlockT> {
 impl<Block: BlockT> DbCache<Block> {
 	/// Create new cache.
 	pub fn new(
-		db: Arc<dyn KeyValueDB>,
+		db: Arc<dyn Database>,
 		key_lookup_column: Option<u32>,
 		header_column: Option<u32>,
 		authorities_column: Option<u32>,
@@ -111,7 +112,7 @@ impl<Block: BlockT> DbCache<Block> {
 	}
 
 	/// Begin cache transaction.
-	pub fn transaction<'a>(&'a mut self, tx: &'a mut DBTransaction) -> DbCacheTransaction<'a, Block> {
+	pub fn transaction<'a>(&'a mut self, tx: &'a mut Transaction) -> DbCacheTransaction<'a, Block> {
 		DbCacheTransaction {
 			cache: self,
 			tx,
@@ -149,7 +150,7 @@ impl<Block: BlockT> DbCache<Block> {
 fn get_cache_helper<'a, Block: BlockT>(
 	cache_at: &'a mut HashMap<CacheKeyId, ListCache<Block, Vec<u8>, self::list_storage::DbStorage>>,
 	name: CacheKeyId,
-	db: &Arc<dyn KeyValueDB>,
+	db: &Arc<dyn Database>,
 	key_lookup: Option<u32>,
 	header: Option<u32>,
 	cache: Option<u32>,
@@ -171,6 +172,55 @@ fn get_cache_helper<'a, Block: BlockT>(
 	})
 }
 
+/// Check that the entries of every list-cache stored in the database can be read back, following
+/// each chain of entries from the best finalized and unfinalized entries.
+///
+/// Returns the name of every cache with a broken entry, along with the block of that entry, or
+/// `None` if the metadata of the cache can't be decoded.
+pub fn check_caches<Block: BlockT>(
+	db: &Arc<dyn Database>,
+	key_lookup: Option<u32>,
+	header: Option<u32>,
+	cache: Option<u32>,
+) -> ClientResult<Vec<(Vec<u8>, Option<(NumberFor<Block>, Block::Hash)>)>> {
+	let mut broken = Vec::new();
+	for pair in db.iter_from_prefix(COLUMN_META, meta_keys::CACHE_META_PREFIX) {
+		let (key, _) = pair.map_err(db_err)?;
+		let name = key[meta_keys::CACHE_META_PREFIX.len()..].to_vec();
+		let storage = self::list_storage::DbStorage::new(name.clone(), db.clone(),
+			self::list_storage::DbColumns {
+				meta: COLUMN_META,
+				key_lookup,
+				header,
+				cache,
+			},
+		);
+		let meta = match Storage::<Block, Vec<u8>>::read_meta(&storage) {
+			Ok(meta) => meta,
+			Err(_) => {
+				broken.push((name, None));
+				continue;
+			},
+		};
+
+		// forks share their oldest entries, which only need to be checked once.
+		let mut checked = HashSet::new();
+		for head in meta.finalized.into_iter().chain(meta.unfinalized) {
+			let mut at = Some(head);
+			while let Some(block) = at.take() {
+				if !checked.insert(block.hash) {
+					break;
+				}
+				match Storage::<Block, Vec<u8>>::read_entry(&storage, &block) {
+					Ok(Some(entry)) => at = entry.prev_valid_from,
+					_ => broken.push((name.clone(), Some((block.number, block.hash)))),
+				}
+			}
+		}
+	}
+	Ok(broken)
+}
+
 /// Cache operations that are to be committed after database transaction is committed.
 pub struct DbCacheTransactionOps<Block: BlockT> {
 	cache_at_op: HashMap<CacheKeyId, self::list_cache::CommitOperation<Block, Vec<u8>>>,
@@ -180,7 +230,7 @@ pub struct DbCacheTransactionOps<Block: BlockT> {
 /// Database-backed blockchain data cache transaction valid for single block import.
 pub struct DbCacheTransaction<'a, Block: BlockT> {
 	cache: &'a mut DbCache<Block>,
-	tx: &'a mut DBTransaction,
+	tx: &'a mut Transaction,
 	cache_at_op: HashMap<CacheKeyId, self::list_cache::CommitOperation<Block, Vec<u8>>>,
 	best_finalized_block: Option<ComplexBlockId<Block>>,
 }
@@ -300,7 +350,7 @@ impl<Block: BlockT> BlockchainCache<Block> for DbCacheSync<Block> {
 		let genesis_hash = cache.genesis_hash;
 		let cache_contents = vec![(*key, data)].into_iter().collect();
 		let db = cache.db.clone();
-		let mut dbtx = DBTransaction::new();
+		let mut dbtx = Transaction::new();
 		let tx = cache.transaction(&mut dbtx);
 		let tx = tx.on_block_insert(
 			ComplexBlockId::new(Default::default(), Zero::zero()),
@@ -309,7 +359,7 @@ impl<Block: BlockT> BlockchainCache<Block> for DbCacheSync<Block> {
 			EntryType::Genesis,
 		)?;
 		let tx_ops = tx.into_ops();
-		db.write(dbtx).map_err(db_err)?;
+		db.commit(dbtx).map_err(db_err)?;
 		cache.commit(tx_ops);
 		Ok(())
 	}
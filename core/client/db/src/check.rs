@@ -0,0 +1,585 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Database integrity checks.
+//!
+//! The checks walk the columns of the database and report the inconsistencies that a crash in
+//! the middle of a write may leave behind: headers that can't be found by number or hash, bodies
+//! and justifications that don't match their header, states that don't resolve from their root,
+//! leaves that don't exist and broken list-cache entries. Issues that only concern unreachable
+//! data, such as dangling leaves, can be repaired.
+
+use std::fmt;
+
+use codec::{Decode, Encode};
+use hash_db::{HashDBRef, PlainDBRef, Prefix};
+use primitives::{H256, Blake2Hasher, storage::well_known_keys};
+use sr_primitives::Justification;
+use sr_primitives::generic::BlockId;
+use sr_primitives::traits::{
+	Block as BlockT, Header as HeaderT, Hash as HashT, NumberFor, Zero, One, SaturatedConversion,
+};
+use state_machine::DBValue;
+use trie::{Trie, TrieConfiguration, EMPTY_PREFIX, trie_types::{Layout, TrieDB}};
+
+use client::children;
+use client::error::Result as ClientResult;
+use client::leaves::LeafSet;
+use database::{Database, Transaction};
+use crate::{Backend, StorageDb, columns};
+use crate::utils::{self, db_err, meta_keys, Meta};
+
+/// An inconsistency found in the database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue<N, H> {
+	/// A header, stored under the given key, can't be decoded.
+	UndecodableHeader(Vec<u8>),
+	/// A header is stored under a key that doesn't match its number and hash.
+	MisplacedHeader(N, H),
+	/// The best or finalized block recorded in the meta column has no header.
+	MissingMetaBlock(&'static str, H),
+	/// There is no header for this number of the canonical chain.
+	MissingCanonicalHeader(N),
+	/// The canonical block is not a child of the previous canonical block.
+	BrokenCanonicalChain(N, H),
+	/// The body of the block can't be decoded.
+	UndecodableBody(N, H),
+	/// The body of the block doesn't match the extrinsics root of its header.
+	BodyRootMismatch(N, H),
+	/// The justification of the block can't be decoded.
+	UndecodableJustification(N, H),
+	/// A body is stored under the given key, but there is no header for it.
+	OrphanBody(Vec<u8>),
+	/// A justification is stored under the given key, but there is no header for it.
+	OrphanJustification(Vec<u8>),
+	/// The state of a non-pruned block doesn't fully resolve from its root.
+	IncompleteState(N, H, String),
+	/// A leaf has no header.
+	DanglingLeaf(N, H),
+	/// A leaf has children, so it's not a leaf anymore.
+	StaleLeaf(N, H),
+	/// An entry of the named list-cache is missing or can't be decoded. `None` if it's the
+	/// metadata of the cache that can't be decoded.
+	BrokenCacheEntry(Vec<u8>, Option<(N, H)>),
+}
+
+impl<N, H> Issue<N, H> {
+	/// Whether the issue is repaired by `check_database` when repairs are enabled.
+	pub fn is_repairable(&self) -> bool {
+		match self {
+			Issue::OrphanBody(_) | Issue::OrphanJustification(_) |
+			Issue::DanglingLeaf(..) | Issue::StaleLeaf(..) => true,
+			_ => false,
+		}
+	}
+}
+
+impl<N: fmt::Debug, H: fmt::Debug> fmt::Display for Issue<N, H> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Issue::UndecodableHeader(key) => write!(f, "Undecodable header at key {:?}", key),
+			Issue::MisplacedHeader(n, h) =>
+				write!(f, "Header #{:?} ({:?}) stored under a wrong key", n, h),
+			Issue::MissingMetaBlock(what, h) =>
+				write!(f, "No header for the {} block {:?}", what, h),
+			Issue::MissingCanonicalHeader(n) => write!(f, "No header for canonical block #{:?}", n),
+			Issue::BrokenCanonicalChain(n, h) =>
+				write!(f, "Canonical block #{:?} ({:?}) is not a child of the previous one", n, h),
+			Issue::UndecodableBody(n, h) =>
+				write!(f, "Undecodable body of block #{:?} ({:?})", n, h),
+			Issue::BodyRootMismatch(n, h) =>
+				write!(f, "Body of block #{:?} ({:?}) doesn't match its extrinsics root", n, h),
+			Issue::UndecodableJustification(n, h) =>
+				write!(f, "Undecodable justification of block #{:?} ({:?})", n, h),
+			Issue::OrphanBody(key) => write!(f, "Body without header at key {:?}", key),
+			Issue::OrphanJustification(key) =>
+				write!(f, "Justification without header at key {:?}", key),
+			Issue::IncompleteState(n, h, e) =>
+				write!(f, "Incomplete state of block #{:?} ({:?}): {}", n, h, e),
+			Issue::DanglingLeaf(n, h) => write!(f, "Leaf #{:?} ({:?}) has no header", n, h),
+			Issue::StaleLeaf(n, h) => write!(f, "Leaf #{:?} ({:?}) has children", n, h),
+			Issue::BrokenCacheEntry(name, Some((n, h))) =>
+				write!(f, "Broken entry of cache {:?} at block #{:?} ({:?})", name, n, h),
+			Issue::BrokenCacheEntry(name, None) =>
+				write!(f, "Undecodable metadata of cache {:?}", name),
+		}
+	}
+}
+
+/// The outcome of a database check.
+#[derive(Debug, Default)]
+pub struct CheckReport<N, H> {
+	/// Number of headers that were checked.
+	pub headers: u64,
+	/// Number of headers of reverted blocks, which can no longer be looked up by hash.
+	pub reverted_headers: u64,
+	/// Number of states that were walked.
+	pub states: u64,
+	/// The inconsistencies that were found.
+	pub issues: Vec<Issue<N, H>>,
+	/// Number of issues that were repaired.
+	pub repaired: usize,
+}
+
+impl<N, H> CheckReport<N, H> {
+	/// Whether no inconsistency was found.
+	pub fn is_ok(&self) -> bool {
+		self.issues.is_empty()
+	}
+}
+
+/// The report of a check of the database of `Block`.
+pub type BlockCheckReport<Block> = CheckReport<NumberFor<Block>, <Block as BlockT>::Hash>;
+
+/// Trie nodes of the state database, as seen by the trie.
+struct StateNodes<'a, Block: BlockT>(&'a StorageDb<Block>);
+
+impl<'a, Block: BlockT> HashDBRef<Blake2Hasher, DBValue> for StateNodes<'a, Block> {
+	fn get(&self, key: &H256, prefix: Prefix) -> Option<DBValue> {
+		state_machine::Storage::<Blake2Hasher>::get(self.0, key, prefix).ok().and_then(|v| v)
+	}
+
+	fn contains(&self, key: &H256, prefix: Prefix) -> bool {
+		HashDBRef::get(self, key, prefix).is_some()
+	}
+}
+
+impl<'a, Block: BlockT> PlainDBRef<H256, DBValue> for StateNodes<'a, Block> {
+	fn get(&self, key: &H256) -> Option<DBValue> {
+		HashDBRef::get(self, key, EMPTY_PREFIX)
+	}
+
+	fn contains(&self, key: &H256) -> bool {
+		HashDBRef::contains(self, key, EMPTY_PREFIX)
+	}
+}
+
+/// Walk the whole trie under `root`, including the child tries, and return the number of keys.
+fn walk_state<Block: BlockT>(nodes: &StateNodes<Block>, root: &H256) -> Result<u64, String> {
+	// the node of an empty trie is never stored.
+	let empty_root = Layout::<Blake2Hasher>::trie_root(Vec::<(Vec<u8>, Vec<u8>)>::new());
+	if *root == empty_root {
+		return Ok(0);
+	}
+
+	let trie = TrieDB::<Blake2Hasher>::new(nodes, root).map_err(|e| format!("{}", e))?;
+	let mut keys = 0;
+	for item in trie.iter().map_err(|e| format!("{}", e))? {
+		let (key, value) = item.map_err(|e| format!("{}", e))?;
+		keys += 1;
+		if key.starts_with(well_known_keys::CHILD_STORAGE_KEY_PREFIX) {
+			if value.len() != std::mem::size_of::<H256>() {
+				return Err(format!("Invalid root of child trie {:?}", key));
+			}
+			if &value[..] == empty_root.as_ref() {
+				continue;
+			}
+			trie::for_keys_in_child_trie::<Layout<Blake2Hasher>, _, _>(
+				&key,
+				nodes,
+				&value,
+				|_| keys += 1,
+			).map_err(|e| format!("{}", e))?;
+		}
+	}
+	Ok(keys)
+}
+
+/// Read a header, treating undecodable headers, which `check_headers` reports, as missing.
+fn read_header<Block: BlockT>(
+	db: &dyn Database,
+	key_lookup_col: Option<u32>,
+	header_col: Option<u32>,
+	id: BlockId<Block>,
+) -> ClientResult<Option<Block::Header>> {
+	match utils::read_header::<Block>(db, key_lookup_col, header_col, id) {
+		Err(client::error::Error::Backend(_)) => Ok(None),
+		result => result,
+	}
+}
+
+/// Check the headers and their lookup entries, calling `f` with the lookup key of every header
+/// that can be looked up by hash.
+pub(crate) fn check_headers<Block: BlockT>(
+	db: &dyn Database,
+	key_lookup_col: Option<u32>,
+	header_col: Option<u32>,
+	report: &mut BlockCheckReport<Block>,
+	mut f: impl FnMut(&[u8], &Block::Header, &mut BlockCheckReport<Block>) -> ClientResult<()>,
+) -> ClientResult<()> {
+	for pair in db.iter(header_col) {
+		let (key, value) = pair.map_err(db_err)?;
+		report.headers += 1;
+		let header = match Block::Header::decode(&mut &value[..]) {
+			Ok(header) => header,
+			Err(_) => {
+				report.issues.push(Issue::UndecodableHeader(key.to_vec()));
+				continue;
+			},
+		};
+		let (number, hash) = (*header.number(), header.hash());
+		if &utils::number_and_hash_to_lookup_key(number, hash)?[..] != &key[..] {
+			report.issues.push(Issue::MisplacedHeader(number, hash));
+			continue;
+		}
+
+		// reverting a block only removes its hash lookup entry.
+		let lookup_key = db.get(key_lookup_col, hash.as_ref()).map_err(db_err)?;
+		if lookup_key.as_ref().map(|k| &k[..]) != Some(&key[..]) {
+			report.reverted_headers += 1;
+			continue;
+		}
+		f(&key, &header, report)?;
+	}
+	Ok(())
+}
+
+/// Check the best and finalized blocks and the canonical chain up to the best block. Numbers that
+/// have no canonical entry are skipped if `allow_gaps` is set.
+pub(crate) fn check_canonical_chain<Block: BlockT>(
+	db: &dyn Database,
+	key_lookup_col: Option<u32>,
+	header_col: Option<u32>,
+	meta: &Meta<NumberFor<Block>, Block::Hash>,
+	allow_gaps: bool,
+	report: &mut BlockCheckReport<Block>,
+) -> ClientResult<()> {
+	let header = |id| read_header::<Block>(db, key_lookup_col, header_col, id);
+
+	for (what, hash) in &[("best", meta.best_hash), ("finalized", meta.finalized_hash)] {
+		if header(BlockId::Hash(*hash))?.is_none() {
+			report.issues.push(Issue::MissingMetaBlock(*what, *hash));
+		}
+	}
+
+	let mut parent_hash = None;
+	let mut number = Zero::zero();
+	while number <= meta.best_number {
+		match header(BlockId::Number(number))? {
+			Some(header) => {
+				if parent_hash.map_or(false, |parent_hash| parent_hash != *header.parent_hash()) {
+					report.issues.push(Issue::BrokenCanonicalChain(number, header.hash()));
+				}
+				parent_hash = Some(header.hash());
+			},
+			None => {
+				if !allow_gaps {
+					report.issues.push(Issue::MissingCanonicalHeader(number));
+				}
+				parent_hash = None;
+			},
+		}
+		number += One::one();
+	}
+	Ok(())
+}
+
+/// Report the entries of `col` that have no header, deleting them if a transaction is given.
+fn check_orphans(
+	db: &dyn Database,
+	col: Option<u32>,
+	header_col: Option<u32>,
+	mut transaction: Option<&mut Transaction>,
+	mut issue: impl FnMut(Vec<u8>),
+) -> ClientResult<()> {
+	for pair in db.iter(col) {
+		let (key, _) = pair.map_err(db_err)?;
+		if db.get(header_col, &key).map_err(db_err)?.is_none() {
+			if let Some(transaction) = transaction.as_mut() {
+				transaction.delete(col, &key);
+			}
+			issue(key.to_vec());
+		}
+	}
+	Ok(())
+}
+
+impl<Block: BlockT<Hash=H256>> Backend<Block> {
+	/// Check the integrity of the database.
+	///
+	/// If `repair` is set, the issues that are repairable are fixed once the check is done.
+	pub fn check_database(&self, repair: bool) -> ClientResult<BlockCheckReport<Block>> {
+		type Hashing<Block> = <<Block as BlockT>::Header as HeaderT>::Hashing;
+		let db = &*self.storage.db;
+		let meta = utils::read_meta::<Block>(db, columns::META, columns::HEADER)?;
+		let mut report = CheckReport::default();
+		let mut transaction = Transaction::new();
+
+		let read = |id| read_header::<Block>(db, columns::KEY_LOOKUP, columns::HEADER, id);
+
+		// headers, bodies and justifications
+		let mut states = Vec::new();
+		let check_block = |
+			key: &[u8],
+			header: &Block::Header,
+			report: &mut BlockCheckReport<Block>,
+		| -> ClientResult<()> {
+			let (number, hash) = (*header.number(), header.hash());
+			if let Some(body) = db.get(columns::BODY, key).map_err(db_err)? {
+				match Vec::<Block::Extrinsic>::decode(&mut &body[..]) {
+					Ok(extrinsics) => {
+						let root = <Hashing<Block> as HashT>::ordered_trie_root(
+							extrinsics.iter().map(Encode::encode).collect(),
+						);
+						if root != *header.extrinsics_root() {
+							report.issues.push(Issue::BodyRootMismatch(number, hash));
+						}
+					},
+					Err(_) => report.issues.push(Issue::UndecodableBody(number, hash)),
+				}
+			}
+			if let Some(justification) = db.get(columns::JUSTIFICATION, key).map_err(db_err)? {
+				if Justification::decode(&mut &justification[..]).is_err() {
+					report.issues.push(Issue::UndecodableJustification(number, hash));
+				}
+			}
+
+			// the state of the blocks discarded by canonicalization is gone even in archive mode.
+			let canonical = read(BlockId::Number(number))?
+				.map_or(false, |canonical| canonical.hash() == hash);
+			let pruned = self.storage.state_db.is_pruned(&hash, number.saturated_into::<u64>());
+			if !pruned && (canonical || number > meta.finalized_number) {
+				states.push((number, hash, *header.state_root()));
+			}
+			Ok(())
+		};
+		check_headers::<Block>(db, columns::KEY_LOOKUP, columns::HEADER, &mut report, check_block)?;
+		check_orphans(
+			db,
+			columns::BODY,
+			columns::HEADER,
+			if repair { Some(&mut transaction) } else { None },
+			|key| report.issues.push(Issue::OrphanBody(key)),
+		)?;
+		check_orphans(
+			db,
+			columns::JUSTIFICATION,
+			columns::HEADER,
+			if repair { Some(&mut transaction) } else { None },
+			|key| report.issues.push(Issue::OrphanJustification(key)),
+		)?;
+		check_canonical_chain::<Block>(
+			db,
+			columns::KEY_LOOKUP,
+			columns::HEADER,
+			&meta,
+			false,
+			&mut report,
+		)?;
+
+		// states
+		let nodes = StateNodes(&*self.storage);
+		for (number, hash, root) in states {
+			report.states += 1;
+			if let Err(e) = walk_state(&nodes, &root) {
+				report.issues.push(Issue::IncompleteState(number, hash, e));
+			}
+		}
+
+		// leaves
+		for pair in db.iter_from_prefix(columns::META, meta_keys::LEAF_PREFIX) {
+			let (key, value) = pair.map_err(db_err)?;
+			if !key.starts_with(meta_keys::LEAF_PREFIX) { break }
+			let hash = Block::Hash::decode(&mut &key[meta_keys::LEAF_PREFIX.len()..]);
+			let number = NumberFor::<Block>::decode(&mut &value[..]);
+			let (hash, number) = match (hash, number) {
+				(Ok(hash), Ok(number)) => (hash, number),
+				_ => return Err(client::error::Error::Backend("Error decoding leaf".into())),
+			};
+			let issue = match read(BlockId::Hash(hash))? {
+				Some(ref header) if *header.number() == number => {
+					let children: Vec<Block::Hash> = children::read_children(
+						db,
+						columns::META,
+						meta_keys::CHILDREN_PREFIX,
+						hash,
+					)?;
+					let mut has_children = false;
+					for child in children {
+						has_children |= read(BlockId::Hash(child))?.is_some();
+					}
+					if !has_children { continue }
+					Issue::StaleLeaf(number, hash)
+				},
+				_ => Issue::DanglingLeaf(number, hash),
+			};
+			if repair {
+				transaction.delete(columns::META, &key);
+			}
+			report.issues.push(issue);
+		}
+
+		if repair && !transaction.is_empty() {
+			db.commit(transaction).map_err(db_err)?;
+			*self.blockchain.leaves.write() =
+				LeafSet::read_from_db(db, columns::META, meta_keys::LEAF_PREFIX)?;
+			report.repaired = report.issues.iter().filter(|issue| issue.is_repairable()).count();
+		}
+
+		Ok(report)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use client::backend::{Backend as BTrait, BlockImportOperation as Op, NewBlockState};
+	use client::blockchain::Backend as BLBTrait;
+	use sr_primitives::testing::{Header, Block as RawBlock, ExtrinsicWrapper};
+	use sr_primitives::traits::BlakeTwo256;
+	use state_machine::Backend as StateBackend;
+	use std::{io, sync::Arc};
+	use database::{ColumnId, DatabaseIter};
+	use super::*;
+	use crate::{DatabaseSettings, DatabaseSettingsSrc, BlocksPruning, PruningMode};
+	use crate::utils::for_each_test_database;
+
+	type Block = RawBlock<ExtrinsicWrapper<u64>>;
+
+	/// A database that, like RocksDB, carries on past the prefix it iterates from.
+	struct UnboundedPrefixDb(Arc<dyn Database>);
+
+	impl Database for UnboundedPrefixDb {
+		fn commit(&self, transaction: Transaction) -> io::Result<()> {
+			self.0.commit(transaction)
+		}
+
+		fn get(&self, col: ColumnId, key: &[u8]) -> io::Result<Option<Vec<u8>>> {
+			self.0.get(col, key)
+		}
+
+		fn iter<'a>(&'a self, col: ColumnId) -> DatabaseIter<'a> {
+			self.0.iter(col)
+		}
+
+		fn iter_from_prefix<'a>(&'a self, col: ColumnId, prefix: &'a [u8]) -> DatabaseIter<'a> {
+			self.0.iter_from_prefix_at(col, &[], prefix)
+		}
+	}
+
+	fn insert_block(
+		backend: &Backend<Block>,
+		number: u64,
+		parent_hash: H256,
+		storage: Vec<(Vec<u8>, Vec<u8>)>,
+	) -> H256 {
+		let id = if number == 0 {
+			BlockId::Hash(Default::default())
+		} else {
+			BlockId::Number(number - 1)
+		};
+		let mut op = backend.begin_operation().unwrap();
+		backend.begin_state_operation(&mut op, id).unwrap();
+		let delta = storage.into_iter().map(|(k, v)| (k, Some(v)));
+		let (root, overlay) = op.old_state.storage_root(delta);
+		op.update_db_storage(overlay).unwrap();
+		let header = Header {
+			number,
+			parent_hash,
+			state_root: root.into(),
+			digest: Default::default(),
+			extrinsics_root: BlakeTwo256::ordered_trie_root(Vec::new()),
+		};
+		let hash = header.hash();
+		op.set_block_data(header, Some(vec![]), None, NewBlockState::Best).unwrap();
+		backend.commit_operation(op).unwrap();
+		hash
+	}
+
+	#[test]
+	fn consistent_database_passes() {
+		for_each_test_database(|| {
+			let backend = Backend::<Block>::new_test(10, 0);
+			let block0 = insert_block(&backend, 0, Default::default(), vec![(vec![1], vec![2])]);
+			insert_block(&backend, 1, block0, vec![(vec![3], vec![4])]);
+
+			let report = backend.check_database(false).unwrap();
+			assert!(report.is_ok(), "{:?}", report.issues);
+			assert_eq!(report.headers, 2);
+			assert_eq!(report.states, 2);
+		});
+	}
+
+	#[test]
+	fn dangling_leaf_is_repaired() {
+		for_each_test_database(|| {
+			let backend = Backend::<Block>::new_test(10, 0);
+			let block0 = insert_block(&backend, 0, Default::default(), Vec::new());
+			let block1 = insert_block(&backend, 1, block0, Vec::new());
+
+			let dangling = H256::repeat_byte(42);
+			let mut transaction = Transaction::new();
+			let mut key = meta_keys::LEAF_PREFIX.to_vec();
+			dangling.using_encoded(|hash| key.extend(hash));
+			transaction.put(columns::META, &key, &2u64.encode());
+			backend.storage.db.commit(transaction).unwrap();
+
+			let report = backend.check_database(false).unwrap();
+			assert_eq!(report.issues, vec![Issue::DanglingLeaf(2, dangling)]);
+			assert_eq!(report.repaired, 0);
+
+			let report = backend.check_database(true).unwrap();
+			assert_eq!(report.repaired, 1);
+			assert_eq!(backend.blockchain().leaves().unwrap(), vec![block1]);
+			assert!(backend.check_database(false).unwrap().is_ok());
+		});
+	}
+
+	#[test]
+	fn missing_state_nodes_are_reported() {
+		for_each_test_database(|| {
+			let backend = Backend::<Block>::new_test(10, 0);
+			let block0 = insert_block(&backend, 0, Default::default(), vec![(vec![1], vec![2])]);
+
+			let mut transaction = Transaction::new();
+			for pair in backend.storage.db.iter(columns::STATE) {
+				transaction.delete(columns::STATE, &pair.unwrap().0);
+			}
+			backend.storage.db.commit(transaction).unwrap();
+
+			let report = backend.check_database(true).unwrap();
+			match &report.issues[..] {
+				[Issue::IncompleteState(0, hash, _)] => assert_eq!(*hash, block0),
+				issues => panic!("Unexpected issues: {:?}", issues),
+			}
+			assert_eq!(report.repaired, 0);
+		});
+	}
+
+	#[test]
+	fn meta_keys_after_the_leaves_are_not_checked_as_leaves() {
+		for_each_test_database(|| {
+			let db = Arc::new(UnboundedPrefixDb(crate::utils::new_test_database()));
+			let backend = Backend::<Block>::new(DatabaseSettings {
+				state_cache_size: 16777216,
+				state_cache_child_ratio: Some((50, 100)),
+				pruning: PruningMode::keep_blocks(10),
+				source: DatabaseSettingsSrc::Custom(db),
+				storage_index: false,
+				blocks_pruning: BlocksPruning::KeepAll,
+			}, 0).unwrap();
+			let block0 = insert_block(&backend, 0, Default::default(), Vec::new());
+
+			// sorted right after the leaves, but not a leaf.
+			let mut transaction = Transaction::new();
+			transaction.put(columns::META, b"lease", &[1]);
+			backend.storage.db.commit(transaction).unwrap();
+
+			let report = backend.check_database(false).unwrap();
+			assert!(report.is_ok(), "{:?}", report.issues);
+			assert_eq!(backend.blockchain().leaves().unwrap(), vec![block0]);
+		});
+	}
+}
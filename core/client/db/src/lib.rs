@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
-//! Client backend that uses RocksDB database as storage.
+//! Client backend that uses a key-value database, RocksDB by default, as storage.
 //!
 //! # Canonicality vs. Finality
 //!
@@ -26,11 +26,13 @@
 
 #![warn(missing_docs)]
 
+pub mod check;
 pub mod light;
 pub mod offchain;
 
 mod cache;
 mod storage_cache;
+mod storage_index;
 mod utils;
 
 use std::sync::Arc;
@@ -41,11 +43,10 @@ use std::collections::{HashMap, HashSet};
 use client::backend::NewBlockState;
 use client::blockchain::{well_known_cache_keys, HeaderBackend};
 use client::{ForkBlocks, ExecutionStrategies};
-use client::backend::{StorageCollection, ChildStorageCollection};
+use client::backend::{StorageCollection, ChildStorageCollection, JustificationRetention};
 use client::error::{Result as ClientResult, Error as ClientError};
 use codec::{Decode, Encode};
 use hash_db::{Hasher, Prefix};
-use kvdb::{KeyValueDB, DBTransaction};
 use trie::{MemoryDB, PrefixedMemoryDB, prefixed_key};
 use parking_lot::{Mutex, RwLock};
 use primitives::{H256, Blake2Hasher, ChangesTrieConfiguration, convert_hash, traits::CodeExecutor};
@@ -83,8 +84,8 @@ const DEFAULT_CHILD_RATIO: (usize, usize) = (1, 10);
 /// DB-backed patricia trie state, transaction type is an overlay of changes to commit.
 pub type DbState = state_machine::TrieBackend<Arc<dyn state_machine::Storage<Blake2Hasher>>, Blake2Hasher>;
 
-/// Re-export the KVDB trait so that one can pass an implementation of it.
-pub use kvdb;
+/// Re-export the database trait so that one can pass an implementation of it.
+pub use database::{self, Database, Transaction, as_database, as_seekable_database};
 
 /// A reference tracking state.
 ///
@@ -202,11 +203,42 @@ pub struct DatabaseSettings {
 	pub pruning: PruningMode,
 	/// Where to find the database.
 	pub source: DatabaseSettingsSrc,
+	/// Keep an index of the storage changes for fast historical queries.
+	///
+	/// The index can only be enabled on a new database, since it has to be built from genesis.
+	pub storage_index: bool,
+	/// Block bodies and justifications pruning mode.
+	pub blocks_pruning: BlocksPruning,
+}
+
+/// Pruning of block bodies and justifications, independent of the state pruning.
+///
+/// Headers are always kept. Blocks are pruned as they go out of the kept range, so the blocks
+/// finalized before the pruning was enabled are left untouched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlocksPruning {
+	/// Keep the bodies and justifications of all blocks.
+	KeepAll,
+	/// Only keep the bodies of the latest finalized blocks. The bodies of forks are pruned as
+	/// soon as they are displaced by finality.
+	KeepFinalized {
+		/// Number of finalized blocks to keep the bodies of.
+		blocks: u32,
+		/// Prune the justifications along with the bodies, except for those a consensus engine
+		/// registered with `register_justification_retention` still needs.
+		justifications: bool,
+	},
+}
+
+impl Default for BlocksPruning {
+	fn default() -> Self {
+		BlocksPruning::KeepAll
+	}
 }
 
 /// Where to find the database..
 pub enum DatabaseSettingsSrc {
-	/// Load a database from a given path. Recommended for most uses.
+	/// Load a RocksDB database from a given path. Recommended for most uses.
 	Path {
 		/// Path to the database.
 		path: PathBuf,
@@ -214,8 +246,16 @@ pub enum DatabaseSettingsSrc {
 		cache_size: Option<usize>,
 	},
 
+	/// Load a sled database from a given path. Requires the `sled` feature.
+	Sled {
+		/// Path to the database.
+		path: PathBuf,
+		/// Whether every commit waits for the database to be flushed to disk.
+		flush_on_commit: bool,
+	},
+
 	/// Use a custom already-open database.
-	Custom(Arc<dyn KeyValueDB>),
+	Custom(Arc<dyn Database>),
 }
 
 /// Create an instance of db-backed client.
@@ -263,6 +303,8 @@ pub(crate) mod columns {
 	pub const AUX: Option<u32> = Some(8);
 	/// Offchain workers local storage
 	pub const OFFCHAIN: Option<u32> = Some(9);
+	/// Index of the storage changes, see `storage_index`.
+	pub const STORAGE_INDEX: Option<u32> = Some(10);
 }
 
 struct PendingBlock<Block: BlockT> {
@@ -273,7 +315,7 @@ struct PendingBlock<Block: BlockT> {
 }
 
 // wrapper that implements trait required for state_db
-struct StateMetaDb<'a>(&'a dyn KeyValueDB);
+struct StateMetaDb<'a>(&'a dyn Database);
 
 impl<'a> state_db::MetaDb for StateMetaDb<'a> {
 	type Error = io::Error;
@@ -285,14 +327,15 @@ impl<'a> state_db::MetaDb for StateMetaDb<'a> {
 
 /// Block database
 pub struct BlockchainDb<Block: BlockT> {
-	db: Arc<dyn KeyValueDB>,
+	db: Arc<dyn Database>,
 	meta: Arc<RwLock<Meta<NumberFor<Block>, Block::Hash>>>,
 	leaves: RwLock<LeafSet<Block::Hash, NumberFor<Block>>>,
 	header_metadata_cache: HeaderMetadataCache<Block>,
+	blocks_pruning: BlocksPruning,
 }
 
 impl<Block: BlockT> BlockchainDb<Block> {
-	fn new(db: Arc<dyn KeyValueDB>) -> ClientResult<Self> {
+	fn new(db: Arc<dyn Database>, blocks_pruning: BlocksPruning) -> ClientResult<Self> {
 		let meta = read_meta::<Block>(&*db, columns::META, columns::HEADER)?;
 		let leaves = LeafSet::read_from_db(&*db, columns::META, meta_keys::LEAF_PREFIX)?;
 		Ok(BlockchainDb {
@@ -300,9 +343,21 @@ impl<Block: BlockT> BlockchainDb<Block> {
 			leaves: RwLock::new(leaves),
 			meta: Arc::new(RwLock::new(meta)),
 			header_metadata_cache: HeaderMetadataCache::default(),
+			blocks_pruning,
 		})
 	}
 
+	/// Returns the number of the latest finalized block whose body has been pruned, if any.
+	fn last_pruned(&self) -> Option<NumberFor<Block>> {
+		match self.blocks_pruning {
+			BlocksPruning::KeepAll => None,
+			BlocksPruning::KeepFinalized { blocks, .. } => {
+				let finalized = self.meta.read().finalized_number.saturated_into::<u64>();
+				finalized.checked_sub(u64::from(blocks)).map(|number| number.saturated_into())
+			},
+		}
+	}
+
 	fn update_meta(
 		&self,
 		hash: Block::Hash,
@@ -381,7 +436,17 @@ impl<Block: BlockT> client::blockchain::Backend<Block> for BlockchainDb<Block> {
 					format!("Error decoding body: {}", err)
 				)),
 			}
-			None => Ok(None),
+			None => {
+				let number = match id {
+					BlockId::Hash(hash) => self.number(hash)?,
+					BlockId::Number(number) => Some(number),
+				};
+				match (number, self.last_pruned()) {
+					(Some(number), Some(last_pruned)) if number <= last_pruned =>
+						Err(client::error::Error::BlockBodyPruned(format!("{:?}", id))),
+					_ => Ok(None),
+				}
+			},
 		}
 	}
 
@@ -460,7 +525,7 @@ pub struct BlockImportOperation<Block: BlockT, H: Hasher> {
 }
 
 impl<Block: BlockT, H: Hasher> BlockImportOperation<Block, H> {
-	fn apply_aux(&mut self, transaction: &mut DBTransaction) {
+	fn apply_aux(&mut self, transaction: &mut Transaction) {
 		for (key, maybe_val) in self.aux_ops.drain(..) {
 			match maybe_val {
 				Some(val) => transaction.put_vec(columns::AUX, &key, val),
@@ -526,11 +591,12 @@ impl<Block> client::backend::BlockImportOperation<Block, Blake2Hasher>
 				(storage_key, child_overlay.into_iter().map(|(k, v)| (k, Some(v)))));
 
 		let (root, transaction) = self.old_state.full_storage_root(
-			top.into_iter().map(|(k, v)| (k, Some(v))),
+			top.iter().map(|(k, v)| (k.clone(), Some(v.clone()))),
 			child_delta
 		);
 
 		self.db_updates = transaction;
+		self.storage_updates = top.into_iter().map(|(k, v)| (k, Some(v))).collect();
 		Ok(root)
 	}
 
@@ -573,7 +639,7 @@ impl<Block> client::backend::BlockImportOperation<Block, Blake2Hasher>
 }
 
 struct StorageDb<Block: BlockT> {
-	pub db: Arc<dyn KeyValueDB>,
+	pub db: Arc<dyn Database>,
 	pub state_db: StateDb<Block::Hash, Vec<u8>>,
 }
 
@@ -590,7 +656,7 @@ impl<Block: BlockT> state_db::NodeDb for StorageDb<Block> {
 	type Key = [u8];
 
 	fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
-		self.db.get(columns::STATE, key).map(|r| r.map(|v| v.to_vec()))
+		self.db.get(columns::STATE, key)
 	}
 }
 
@@ -613,7 +679,7 @@ impl state_machine::Storage<Blake2Hasher> for DbGenesisStorage {
 
 /// A database wrapper for changes tries.
 pub struct DbChangesTrieStorage<Block: BlockT> {
-	db: Arc<dyn KeyValueDB>,
+	db: Arc<dyn Database>,
 	meta: Arc<RwLock<Meta<NumberFor<Block>, Block::Hash>>>,
 	min_blocks_to_keep: Option<u32>,
 	cache: RwLock<ChangesTrieBuildCache<Block::Hash, NumberFor<Block>>>,
@@ -622,7 +688,7 @@ pub struct DbChangesTrieStorage<Block: BlockT> {
 
 impl<Block: BlockT<Hash=H256>> DbChangesTrieStorage<Block> {
 	/// Commit new changes trie.
-	pub fn commit(&self, tx: &mut DBTransaction, mut changes_trie: MemoryDB<Blake2Hasher>) {
+	pub fn commit(&self, tx: &mut Transaction, mut changes_trie: MemoryDB<Blake2Hasher>) {
 		for (key, (val, _)) in changes_trie.drain() {
 			tx.put(columns::CHANGES_TRIE, &key[..], &val);
 		}
@@ -637,7 +703,7 @@ impl<Block: BlockT<Hash=H256>> DbChangesTrieStorage<Block> {
 	pub fn prune(
 		&self,
 		config: &ChangesTrieConfiguration,
-		tx: &mut DBTransaction,
+		tx: &mut Transaction,
 		block_hash: Block::Hash,
 		block_num: NumberFor<Block>,
 	) {
@@ -766,6 +832,7 @@ where
 
 	fn get(&self, key: &H256, _prefix: Prefix) -> Result<Option<DBValue>, String> {
 		self.db.get(columns::CHANGES_TRIE, &key[..])
+			.map(|r| r.map(|v| DBValue::from_slice(&v)))
 			.map_err(|err| format!("{}", err))
 	}
 }
@@ -783,6 +850,8 @@ pub struct Backend<Block: BlockT> {
 	canonicalization_delay: u64,
 	shared_cache: SharedCache<Block, Blake2Hasher>,
 	import_lock: Mutex<()>,
+	storage_index: bool,
+	justification_retention: RwLock<Vec<Arc<dyn JustificationRetention<Block>>>>,
 }
 
 impl<Block: BlockT<Hash=H256>> Backend<Block> {
@@ -791,30 +860,32 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 	/// The pruning window is how old a block must be before the state is pruned.
 	pub fn new(config: DatabaseSettings, canonicalization_delay: u64) -> ClientResult<Self> {
 		let db = crate::utils::open_database(&config, columns::META, "full")?;
-		Self::from_kvdb(db as Arc<_>, canonicalization_delay, &config)
+		Self::from_database(db, canonicalization_delay, &config)
 	}
 
 	/// Create new memory-backed client backend for tests.
 	#[cfg(any(test, feature = "test-helpers"))]
 	pub fn new_test(keep_blocks: u32, canonicalization_delay: u64) -> Self {
-		let db = Arc::new(kvdb_memorydb::create(crate::utils::NUM_COLUMNS));
+		let db = crate::utils::new_test_database();
 		let db_setting = DatabaseSettings {
 			state_cache_size: 16777216,
 			state_cache_child_ratio: Some((50, 100)),
 			pruning: PruningMode::keep_blocks(keep_blocks),
 			source: DatabaseSettingsSrc::Custom(db),
+			storage_index: false,
+			blocks_pruning: BlocksPruning::KeepAll,
 		};
 
 		Self::new(db_setting, canonicalization_delay).expect("failed to create test-db")
 	}
 
-	fn from_kvdb(
-		db: Arc<dyn KeyValueDB>,
+	fn from_database(
+		db: Arc<dyn Database>,
 		canonicalization_delay: u64,
 		config: &DatabaseSettings
 	) -> ClientResult<Self> {
 		let is_archive_pruning = config.pruning.is_archive();
-		let blockchain = BlockchainDb::new(db.clone())?;
+		let blockchain = BlockchainDb::new(db.clone(), config.blocks_pruning)?;
 		let meta = blockchain.meta.clone();
 		let map_e = |e: state_db::Error<io::Error>| ::client::error::Error::from(format!("State database error: {:?}", e));
 		let state_db: StateDb<_, _> = StateDb::new(config.pruning.clone(), &StateMetaDb(&*db)).map_err(map_e)?;
@@ -823,6 +894,7 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 			state_db,
 		};
 		let offchain_storage = offchain::LocalStorage::new(db.clone());
+		let storage_index = Self::open_storage_index(&*db, config.storage_index)?;
 		let changes_tries_storage = DbChangesTrieStorage {
 			db,
 			meta,
@@ -843,9 +915,42 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 				config.state_cache_child_ratio.unwrap_or(DEFAULT_CHILD_RATIO),
 			),
 			import_lock: Default::default(),
+			storage_index,
+			justification_retention: Default::default(),
 		})
 	}
 
+	/// Returns whether the storage index should be kept, updating its marker.
+	///
+	/// The index must cover every block since genesis, so it is only started on an empty database
+	/// and it is dropped for good once disabled.
+	fn open_storage_index(db: &dyn Database, enabled: bool) -> ClientResult<bool> {
+		let marked = db.get(columns::META, meta_keys::STORAGE_INDEX).map_err(db_err)?.is_some();
+		let mut transaction = Transaction::new();
+		let enabled = match (enabled, marked) {
+			(true, true) => true,
+			(true, false) => {
+				if db.get(columns::META, meta_keys::GENESIS_HASH).map_err(db_err)?.is_some() {
+					warn!("Storage index can only be enabled on a new database, ignoring it");
+					false
+				} else {
+					transaction.put(columns::META, meta_keys::STORAGE_INDEX, &[]);
+					true
+				}
+			},
+			(false, true) => {
+				for pair in db.iter(columns::STORAGE_INDEX) {
+					transaction.delete(columns::STORAGE_INDEX, &pair.map_err(db_err)?.0);
+				}
+				transaction.delete(columns::META, meta_keys::STORAGE_INDEX);
+				false
+			},
+			(false, false) => false,
+		};
+		db.commit(transaction).map_err(db_err)?;
+		Ok(enabled)
+	}
+
 	/// Returns in-memory blockchain that contains the same set of blocks that the self.
 	#[cfg(feature = "test-helpers")]
 	pub fn as_in_memory(&self) -> InMemoryBackend<Block, Blake2Hasher> {
@@ -856,7 +961,8 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 
 		// get all headers hashes && sort them by number (could be duplicate)
 		let mut headers: Vec<(NumberFor<Block>, Block::Hash, Block::Header)> = Vec::new();
-		for (_, header) in self.blockchain.db.iter(columns::HEADER) {
+		for pair in self.blockchain.db.iter(columns::HEADER) {
+			let (_, header) = pair.unwrap();
 			let header = Block::Header::decode(&mut &header[..]).unwrap();
 			let hash = header.hash();
 			let number = *header.number();
@@ -923,7 +1029,7 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 	/// to be best, `route_to` should equal to `best_to`.
 	fn set_head_with_transaction(
 		&self,
-		transaction: &mut DBTransaction,
+		transaction: &mut Transaction,
 		route_to: Block::Hash,
 		best_to: (NumberFor<Block>, Block::Hash),
 	) -> ClientResult<(Vec<Block::Hash>, Vec<Block::Hash>)> {
@@ -1000,7 +1106,7 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 
 	fn finalize_block_with_transaction(
 		&self,
-		transaction: &mut DBTransaction,
+		transaction: &mut Transaction,
 		hash: &Block::Hash,
 		header: &Block::Header,
 		last_finalized: Option<Block::Hash>,
@@ -1030,8 +1136,9 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 	// performs forced canonicaliziation with a delay after importing a non-finalized block.
 	fn force_delayed_canonicalize(
 		&self,
-		transaction: &mut DBTransaction,
+		transaction: &mut Transaction,
 		hash: Block::Hash,
+		parent_hash: Block::Hash,
 		number: NumberFor<Block>,
 	)
 		-> ClientResult<()>
@@ -1044,18 +1151,23 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 				return Ok(())
 			}
 
-			let hash = if new_canonical == number_u64 {
-				hash
+			let (hash, parent_hash) = if new_canonical == number_u64 {
+				(hash, parent_hash)
 			} else {
-				::client::blockchain::HeaderBackend::hash(&self.blockchain, new_canonical.saturated_into())?
+				let hash = ::client::blockchain::HeaderBackend::hash(&self.blockchain, new_canonical.saturated_into())?
 					.expect("existence of block with number `new_canonical` \
-						implies existence of blocks with all numbers before it; qed")
+						implies existence of blocks with all numbers before it; qed");
+				let parent_hash = *::client::blockchain::HeaderBackend::header(&self.blockchain, BlockId::Hash(hash))?
+					.expect("the hash of the block was just read from the database; qed")
+					.parent_hash();
+				(hash, parent_hash)
 			};
 
 			trace!(target: "db", "Canonicalize block #{} ({:?})", new_canonical, hash);
 			let commit = self.storage.state_db.canonicalize_block(&hash)
 				.map_err(|e: state_db::Error<io::Error>| client::error::Error::from(format!("State database error: {:?}", e)))?;
 			apply_state_commit(transaction, commit);
+			self.offchain_storage.canonicalize(transaction, hash.as_ref(), parent_hash.as_ref()).map_err(db_err)?;
 		};
 
 		Ok(())
@@ -1064,7 +1176,7 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 	fn try_commit_operation(&self, mut operation: BlockImportOperation<Block, Blake2Hasher>)
 		-> ClientResult<()>
 	{
-		let mut transaction = DBTransaction::new();
+		let mut transaction = Transaction::new();
 		let mut finalization_displaced_leaves = None;
 
 		operation.apply_aux(&mut transaction);
@@ -1139,6 +1251,19 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 			let commit = self.storage.state_db.insert_block(&hash, number_u64, &pending_block.header.parent_hash(), changeset)
 				.map_err(|e: state_db::Error<io::Error>| client::error::Error::from(format!("State database error: {:?}", e)))?;
 			apply_state_commit(&mut transaction, commit);
+			offchain::LocalStorage::note_block(
+				&mut transaction,
+				hash.as_ref(),
+				pending_block.header.parent_hash().as_ref(),
+			);
+			if self.storage_index {
+				storage_index::note_changes::<Block>(
+					&mut transaction,
+					number,
+					&hash,
+					&operation.storage_updates,
+				);
+			}
 
 			// Check if need to finalize. Genesis is always finalized instantly.
 			let finalized = number_u64 == 0 || pending_block.leaf_state.is_final();
@@ -1161,7 +1286,7 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 				)?;
 			} else {
 				// canonicalize blocks which are old enough, regardless of finality.
-				self.force_delayed_canonicalize(&mut transaction, hash, *header.number())?
+				self.force_delayed_canonicalize(&mut transaction, hash, *header.parent_hash(), *header.number())?
 			}
 
 			debug!(target: "db", "DB Commit {:?} ({}), best = {}", hash, number, is_best);
@@ -1204,7 +1329,7 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 			None
 		};
 
-		let write_result = self.storage.db.write(transaction).map_err(db_err);
+		let write_result = self.storage.db.commit(transaction).map_err(db_err);
 
 		if let Some(changes_trie_cache_update) = operation.changes_trie_cache_update {
 			self.changes_tries_storage.commit_cache(changes_trie_cache_update);
@@ -1253,7 +1378,7 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 	// was not a child of the last finalized block.
 	fn note_finalized(
 		&self,
-		transaction: &mut DBTransaction,
+		transaction: &mut Transaction,
 		f_header: &Block::Header,
 		f_hash: Block::Hash,
 		displaced: &mut Option<FinalizationDisplaced<Block::Hash, NumberFor<Block>>>
@@ -1271,6 +1396,7 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 			let commit = self.storage.state_db.canonicalize_block(&f_hash)
 				.map_err(|e: state_db::Error<io::Error>| client::error::Error::from(format!("State database error: {:?}", e)))?;
 			apply_state_commit(transaction, commit);
+			self.offchain_storage.canonicalize(transaction, f_hash.as_ref(), parent_hash.as_ref()).map_err(db_err)?;
 
 			let changes_trie_config = self.changes_trie_config(parent_hash)?;
 			if let Some(changes_trie_config) = changes_trie_config {
@@ -1279,6 +1405,7 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 		}
 
 		let new_displaced = self.blockchain.leaves.write().finalize_height(f_num);
+		self.prune_blocks(transaction, f_num, &new_displaced)?;
 		match displaced {
 			x @ &mut None => *x = Some(new_displaced),
 			&mut Some(ref mut displaced) => displaced.merge(new_displaced),
@@ -1286,9 +1413,59 @@ impl<Block: BlockT<Hash=H256>> Backend<Block> {
 
 		Ok(())
 	}
+
+	// prunes the body and justification of the canonical block that went out of the kept range
+	// with the finalization of `f_num`, and the bodies of the forks displaced by it.
+	fn prune_blocks(
+		&self,
+		transaction: &mut Transaction,
+		f_num: NumberFor<Block>,
+		displaced: &FinalizationDisplaced<Block::Hash, NumberFor<Block>>,
+	) -> ClientResult<()> {
+		let (blocks, justifications) = match self.blockchain.blocks_pruning {
+			BlocksPruning::KeepAll => return Ok(()),
+			BlocksPruning::KeepFinalized { blocks, justifications } => (blocks, justifications),
+		};
+
+		// forks can't be finalized anymore, so their bodies are pruned right away. Displaced
+		// leaves are below the finalized block and the chains are followed down to the canonical
+		// one.
+		for leaf in displaced.leaves() {
+			let mut hash = leaf.clone();
+			while let Some(header) = self.blockchain.header(BlockId::Hash(hash))? {
+				let number = *header.number();
+				if self.blockchain.hash(number)? == Some(hash) {
+					break;
+				}
+				transaction.delete(columns::BODY, &utils::number_and_hash_to_lookup_key(number, hash)?);
+				trace!(target: "db", "Pruned body of fork block #{} ({})", number, hash);
+				hash = *header.parent_hash();
+			}
+		}
+
+		let number = match f_num.saturated_into::<u64>().checked_sub(u64::from(blocks)) {
+			Some(number) => number.saturated_into::<NumberFor<Block>>(),
+			None => return Ok(()),
+		};
+		let header = match self.blockchain.header(BlockId::Number(number))? {
+			Some(header) => header,
+			None => return Ok(()),
+		};
+
+		let lookup_key = utils::number_and_hash_to_lookup_key(number, header.hash())?;
+		transaction.delete(columns::BODY, &lookup_key);
+		let keep_justification = self.justification_retention.read().iter()
+			.any(|retention| retention.keep_justification(&header));
+		if justifications && !keep_justification {
+			transaction.delete(columns::JUSTIFICATION, &lookup_key);
+		}
+		trace!(target: "db", "Pruned body of block #{} ({})", number, header.hash());
+
+		Ok(())
+	}
 }
 
-fn apply_state_commit(transaction: &mut DBTransaction, commit: state_db::CommitSet<Vec<u8>>) {
+fn apply_state_commit(transaction: &mut Transaction, commit: state_db::CommitSet<Vec<u8>>) {
 	for (key, val) in commit.data.inserted.into_iter() {
 		transaction.put(columns::STATE, &key[..], &val);
 	}
@@ -1311,19 +1488,19 @@ impl<Block> client::backend::AuxStore for Backend<Block> where Block: BlockT<Has
 		I: IntoIterator<Item=&'a(&'c [u8], &'c [u8])>,
 		D: IntoIterator<Item=&'a &'b [u8]>,
 	>(&self, insert: I, delete: D) -> ClientResult<()> {
-		let mut transaction = DBTransaction::new();
+		let mut transaction = Transaction::new();
 		for (k, v) in insert {
 			transaction.put(columns::AUX, k, v);
 		}
 		for k in delete {
 			transaction.delete(columns::AUX, k);
 		}
-		self.storage.db.write(transaction).map_err(db_err)?;
+		self.storage.db.commit(transaction).map_err(db_err)?;
 		Ok(())
 	}
 
 	fn get_aux(&self, key: &[u8]) -> ClientResult<Option<Vec<u8>>> {
-		Ok(self.storage.db.get(columns::AUX, key).map(|r| r.map(|v| v.to_vec())).map_err(db_err)?)
+		Ok(self.storage.db.get(columns::AUX, key).map_err(db_err)?)
 	}
 }
 
@@ -1377,7 +1554,7 @@ impl<Block> client::backend::Backend<Block, Blake2Hasher> for Backend<Block> whe
 	fn finalize_block(&self, block: BlockId<Block>, justification: Option<Justification>)
 		-> ClientResult<()>
 	{
-		let mut transaction = DBTransaction::new();
+		let mut transaction = Transaction::new();
 		let hash = self.blockchain.expect_block_hash_from_id(&block)?;
 		let header = self.blockchain.expect_header(block)?;
 		let mut displaced = None;
@@ -1390,7 +1567,7 @@ impl<Block> client::backend::Backend<Block, Blake2Hasher> for Backend<Block> whe
 				justification,
 				displaced,
 			)?;
-			self.storage.db.write(transaction).map_err(db_err)?;
+			self.storage.db.commit(transaction).map_err(db_err)?;
 			self.blockchain.update_meta(hash, number, is_best, is_finalized);
 			Ok(())
 		};
@@ -1425,7 +1602,7 @@ impl<Block> client::backend::Backend<Block, Blake2Hasher> for Backend<Block> whe
 			if best.is_zero() {
 				return Ok(c.saturated_into::<NumberFor<Block>>())
 			}
-			let mut transaction = DBTransaction::new();
+			let mut transaction = Transaction::new();
 			match self.storage.state_db.revert_one() {
 				Some(commit) => {
 					apply_state_commit(&mut transaction, commit);
@@ -1441,7 +1618,7 @@ impl<Block> client::backend::Backend<Block, Blake2Hasher> for Backend<Block> whe
 					transaction.put(columns::META, meta_keys::BEST_BLOCK, &key);
 					transaction.delete(columns::KEY_LOOKUP, removed.hash().as_ref());
 					children::remove_children(&mut transaction, columns::META, meta_keys::CHILDREN_PREFIX, hash);
-					self.storage.db.write(transaction).map_err(db_err)?;
+					self.storage.db.commit(transaction).map_err(db_err)?;
 					self.blockchain.update_meta(hash, best, true, false);
 					self.blockchain.leaves.write().revert(removed.hash().clone(), removed.number().clone(), removed.parent_hash().clone());
 				}
@@ -1451,6 +1628,36 @@ impl<Block> client::backend::Backend<Block, Blake2Hasher> for Backend<Block> whe
 		Ok(n)
 	}
 
+	fn import_state(
+		&self,
+		block: BlockId<Block>,
+		top: StorageOverlay,
+		children: ChildrenStorageOverlay,
+	) -> ClientResult<()> {
+		let header = self.blockchain.expect_header(block)?;
+		// the state is written straight to the database, bypassing the non-canonical overlay of
+		// the state db, which is only valid for blocks which have already been canonicalized.
+		if *header.number() > self.blockchain.info().finalized_number {
+			return Err(client::error::Error::Backend(
+				format!("Cannot import state of non-finalized block {:?}", block)
+			));
+		}
+
+		let mut operation = self.begin_operation()?;
+		let root = client::backend::BlockImportOperation::reset_storage(&mut operation, top, children)?;
+		if root != *header.state_root() {
+			return Err(client::error::Error::InvalidStateRoot);
+		}
+
+		let mut transaction = Transaction::new();
+		for (key, (val, rc)) in operation.db_updates.drain() {
+			if rc > 0 {
+				transaction.put(columns::STATE, &key[..], &val);
+			}
+		}
+		self.storage.db.commit(transaction).map_err(db_err)
+	}
+
 	fn blockchain(&self) -> &BlockchainDb<Block> {
 		&self.blockchain
 	}
@@ -1460,6 +1667,10 @@ impl<Block> client::backend::Backend<Block, Blake2Hasher> for Backend<Block> whe
 		Some(used)
 	}
 
+	fn state_cache_hit_stats(&self) -> Option<(u64, u64)> {
+		Some(self.shared_cache.lock().hit_stats())
+	}
+
 	fn state_at(&self, block: BlockId<Block>) -> ClientResult<Self::State> {
 		use client::blockchain::HeaderBackend as BcHeaderBackend;
 
@@ -1507,6 +1718,29 @@ impl<Block> client::backend::Backend<Block, Blake2Hasher> for Backend<Block> whe
 	fn get_import_lock(&self) -> &Mutex<()> {
 		&self.import_lock
 	}
+
+	fn indexed_storage_changes(
+		&self,
+		key: &[u8],
+		first: NumberFor<Block>,
+		last: NumberFor<Block>,
+	) -> ClientResult<Option<Vec<(NumberFor<Block>, Option<H256>)>>> {
+		if !self.storage_index {
+			return Ok(None);
+		}
+
+		storage_index::read_changes::<Block, _>(
+			&*self.storage.db,
+			key,
+			first,
+			last,
+			|number| self.blockchain.hash(number),
+		).map(Some)
+	}
+
+	fn register_justification_retention(&self, retention: Arc<dyn JustificationRetention<Block>>) {
+		self.justification_retention.write().push(retention);
+	}
 }
 
 impl<Block> client::backend::LocalBackend<Block, Blake2Hasher> for Backend<Block>
@@ -1523,6 +1757,7 @@ mod tests {
 	use hash_db::{HashDB, EMPTY_PREFIX};
 	use super::*;
 	use crate::columns;
+	use crate::utils::for_each_test_database;
 	use client::backend::Backend as BTrait;
 	use client::blockchain::Backend as BLBTrait;
 	use client::backend::BlockImportOperation as Op;
@@ -1590,774 +1825,980 @@ mod tests {
 
 	#[test]
 	fn block_hash_inserted_correctly() {
-		let backing = {
-			let db = Backend::<Block>::new_test(1, 0);
-			for i in 0..10 {
-				assert!(db.blockchain().hash(i).unwrap().is_none());
-
-				{
-					let id = if i == 0 {
-						BlockId::Hash(Default::default())
-					} else {
-						BlockId::Number(i - 1)
-					};
-
-					let mut op = db.begin_operation().unwrap();
-					db.begin_state_operation(&mut op, id).unwrap();
-					let header = Header {
-						number: i,
-						parent_hash: if i == 0 {
-							Default::default()
+		for_each_test_database(|| {
+			let backing = {
+				let db = Backend::<Block>::new_test(1, 0);
+				for i in 0..10 {
+					assert!(db.blockchain().hash(i).unwrap().is_none());
+
+					{
+						let id = if i == 0 {
+							BlockId::Hash(Default::default())
 						} else {
-							db.blockchain.hash(i - 1).unwrap().unwrap()
-						},
-						state_root: Default::default(),
-						digest: Default::default(),
-						extrinsics_root: Default::default(),
-					};
-
-					op.set_block_data(
-						header,
-						Some(vec![]),
-						None,
-						NewBlockState::Best,
-					).unwrap();
-					db.commit_operation(op).unwrap();
+							BlockId::Number(i - 1)
+						};
+
+						let mut op = db.begin_operation().unwrap();
+						db.begin_state_operation(&mut op, id).unwrap();
+						let header = Header {
+							number: i,
+							parent_hash: if i == 0 {
+								Default::default()
+							} else {
+								db.blockchain.hash(i - 1).unwrap().unwrap()
+							},
+							state_root: Default::default(),
+							digest: Default::default(),
+							extrinsics_root: Default::default(),
+						};
+
+						op.set_block_data(
+							header,
+							Some(vec![]),
+							None,
+							NewBlockState::Best,
+						).unwrap();
+						db.commit_operation(op).unwrap();
+					}
+
+					assert!(db.blockchain().hash(i).unwrap().is_some())
 				}
+				db.storage.db.clone()
+			};
 
-				assert!(db.blockchain().hash(i).unwrap().is_some())
+			let backend = Backend::<Block>::new(DatabaseSettings {
+				state_cache_size: 16777216,
+				state_cache_child_ratio: Some((50, 100)),
+				pruning: PruningMode::keep_blocks(1),
+				source: DatabaseSettingsSrc::Custom(backing),
+				storage_index: false,
+				blocks_pruning: BlocksPruning::KeepAll,
+			}, 0).unwrap();
+			assert_eq!(backend.blockchain().info().best_number, 9);
+			for i in 0..10 {
+				assert!(backend.blockchain().hash(i).unwrap().is_some())
 			}
-			db.storage.db.clone()
-		};
-
-		let backend = Backend::<Block>::new(DatabaseSettings {
-			state_cache_size: 16777216,
-			state_cache_child_ratio: Some((50, 100)),
-			pruning: PruningMode::keep_blocks(1),
-			source: DatabaseSettingsSrc::Custom(backing),
-		}, 0).unwrap();
-		assert_eq!(backend.blockchain().info().best_number, 9);
-		for i in 0..10 {
-			assert!(backend.blockchain().hash(i).unwrap().is_some())
-		}
+		});
 	}
 
 	#[test]
 	fn set_state_data() {
-		let db = Backend::<Block>::new_test(2, 0);
-		let hash = {
-			let mut op = db.begin_operation().unwrap();
-			db.begin_state_operation(&mut op, BlockId::Hash(Default::default())).unwrap();
-			let mut header = Header {
-				number: 0,
-				parent_hash: Default::default(),
-				state_root: Default::default(),
-				digest: Default::default(),
-				extrinsics_root: Default::default(),
-			};
-
-			let storage = vec![
-				(vec![1, 3, 5], vec![2, 4, 6]),
-				(vec![1, 2, 3], vec![9, 9, 9]),
-			];
-
-			header.state_root = op.old_state.storage_root(storage
-				.iter()
-				.cloned()
-				.map(|(x, y)| (x, Some(y)))
-			).0.into();
-			let hash = header.hash();
-
-			op.reset_storage(storage.iter().cloned().collect(), Default::default()).unwrap();
-			op.set_block_data(
-				header.clone(),
-				Some(vec![]),
-				None,
-				NewBlockState::Best,
-			).unwrap();
+		for_each_test_database(|| {
+			let db = Backend::<Block>::new_test(2, 0);
+			let hash = {
+				let mut op = db.begin_operation().unwrap();
+				db.begin_state_operation(&mut op, BlockId::Hash(Default::default())).unwrap();
+				let mut header = Header {
+					number: 0,
+					parent_hash: Default::default(),
+					state_root: Default::default(),
+					digest: Default::default(),
+					extrinsics_root: Default::default(),
+				};
+
+				let storage = vec![
+					(vec![1, 3, 5], vec![2, 4, 6]),
+					(vec![1, 2, 3], vec![9, 9, 9]),
+				];
+
+				header.state_root = op.old_state.storage_root(storage
+					.iter()
+					.cloned()
+					.map(|(x, y)| (x, Some(y)))
+				).0.into();
+				let hash = header.hash();
 
-			db.commit_operation(op).unwrap();
+				op.reset_storage(storage.iter().cloned().collect(), Default::default()).unwrap();
+				op.set_block_data(
+					header.clone(),
+					Some(vec![]),
+					None,
+					NewBlockState::Best,
+				).unwrap();
 
-			let state = db.state_at(BlockId::Number(0)).unwrap();
+				db.commit_operation(op).unwrap();
 
-			assert_eq!(state.storage(&[1, 3, 5]).unwrap(), Some(vec![2, 4, 6]));
-			assert_eq!(state.storage(&[1, 2, 3]).unwrap(), Some(vec![9, 9, 9]));
-			assert_eq!(state.storage(&[5, 5, 5]).unwrap(), None);
+				let state = db.state_at(BlockId::Number(0)).unwrap();
 
-			hash
-		};
+				assert_eq!(state.storage(&[1, 3, 5]).unwrap(), Some(vec![2, 4, 6]));
+				assert_eq!(state.storage(&[1, 2, 3]).unwrap(), Some(vec![9, 9, 9]));
+				assert_eq!(state.storage(&[5, 5, 5]).unwrap(), None);
 
-		{
-			let mut op = db.begin_operation().unwrap();
-			db.begin_state_operation(&mut op, BlockId::Number(0)).unwrap();
-			let mut header = Header {
-				number: 1,
-				parent_hash: hash,
-				state_root: Default::default(),
-				digest: Default::default(),
-				extrinsics_root: Default::default(),
+				hash
 			};
 
-			let storage = vec![
-				(vec![1, 3, 5], None),
-				(vec![5, 5, 5], Some(vec![4, 5, 6])),
-			];
-
-			let (root, overlay) = op.old_state.storage_root(storage.iter().cloned());
-			op.update_db_storage(overlay).unwrap();
-			header.state_root = root.into();
-
-			op.set_block_data(
-				header,
-				Some(vec![]),
-				None,
-				NewBlockState::Best,
-			).unwrap();
+			{
+				let mut op = db.begin_operation().unwrap();
+				db.begin_state_operation(&mut op, BlockId::Number(0)).unwrap();
+				let mut header = Header {
+					number: 1,
+					parent_hash: hash,
+					state_root: Default::default(),
+					digest: Default::default(),
+					extrinsics_root: Default::default(),
+				};
+
+				let storage = vec![
+					(vec![1, 3, 5], None),
+					(vec![5, 5, 5], Some(vec![4, 5, 6])),
+				];
+
+				let (root, overlay) = op.old_state.storage_root(storage.iter().cloned());
+				op.update_db_storage(overlay).unwrap();
+				header.state_root = root.into();
+
+				op.set_block_data(
+					header,
+					Some(vec![]),
+					None,
+					NewBlockState::Best,
+				).unwrap();
 
-			db.commit_operation(op).unwrap();
+				db.commit_operation(op).unwrap();
 
-			let state = db.state_at(BlockId::Number(1)).unwrap();
+				let state = db.state_at(BlockId::Number(1)).unwrap();
 
-			assert_eq!(state.storage(&[1, 3, 5]).unwrap(), None);
-			assert_eq!(state.storage(&[1, 2, 3]).unwrap(), Some(vec![9, 9, 9]));
-			assert_eq!(state.storage(&[5, 5, 5]).unwrap(), Some(vec![4, 5, 6]));
-		}
+				assert_eq!(state.storage(&[1, 3, 5]).unwrap(), None);
+				assert_eq!(state.storage(&[1, 2, 3]).unwrap(), Some(vec![9, 9, 9]));
+				assert_eq!(state.storage(&[5, 5, 5]).unwrap(), Some(vec![4, 5, 6]));
+			}
+		});
 	}
 
 	#[test]
-	fn delete_only_when_negative_rc() {
-		let _ = ::env_logger::try_init();
-		let key;
-		let backend = Backend::<Block>::new_test(1, 0);
-
-		let hash = {
-			let mut op = backend.begin_operation().unwrap();
-			backend.begin_state_operation(&mut op, BlockId::Hash(Default::default())).unwrap();
-			let mut header = Header {
-				number: 0,
-				parent_hash: Default::default(),
-				state_root: Default::default(),
-				digest: Default::default(),
-				extrinsics_root: Default::default(),
+	fn storage_index_tracks_canonical_changes() {
+		for_each_test_database(|| {
+			let db = Backend::<Block>::new(DatabaseSettings {
+				state_cache_size: 16777216,
+				state_cache_child_ratio: Some((50, 100)),
+				pruning: PruningMode::ArchiveAll,
+				source: DatabaseSettingsSrc::Custom(crate::utils::new_test_database()),
+				storage_index: true,
+				blocks_pruning: BlocksPruning::KeepAll,
+			}, 0).unwrap();
+
+			let import = |number: u64, parent_hash: H256, value: Option<u8>, state: NewBlockState| {
+				let header = Header {
+					number,
+					parent_hash,
+					state_root: BlakeTwo256::trie_root(Vec::new()),
+					digest: Default::default(),
+					extrinsics_root: H256::from_low_u64_be(value.unwrap_or(0).into()),
+				};
+				let mut op = db.begin_operation().unwrap();
+				if number == 0 {
+					db.begin_state_operation(&mut op, BlockId::Hash(Default::default())).unwrap();
+					let storage = vec![(vec![1], vec![value.unwrap()])];
+					op.reset_storage(storage.into_iter().collect(), Default::default()).unwrap();
+				} else {
+					db.begin_state_operation(&mut op, BlockId::Hash(parent_hash)).unwrap();
+					op.update_storage(vec![(vec![1], value.map(|value| vec![value]))], Vec::new())
+						.unwrap();
+				}
+				op.set_block_data(header.clone(), Some(vec![]), None, state).unwrap();
+				db.commit_operation(op).unwrap();
+				header.hash()
 			};
+			let value_hash = |value: u8| Some(Blake2Hasher::hash(&[value]));
 
-			let storage: Vec<(_, _)> = vec![];
-
-			header.state_root = op.old_state.storage_root(storage
-				.iter()
-				.cloned()
-				.map(|(x, y)| (x, Some(y)))
-			).0.into();
-			let hash = header.hash();
-
-			op.reset_storage(storage.iter().cloned().collect(), Default::default()).unwrap();
+			let genesis = import(0, Default::default(), Some(1), NewBlockState::Final);
+			let block1 = import(1, genesis, Some(2), NewBlockState::Best);
+			import(1, genesis, Some(3), NewBlockState::Normal);
+			import(2, block1, None, NewBlockState::Best);
 
-			key = op.db_updates.insert(EMPTY_PREFIX, b"hello");
-			op.set_block_data(
-				header,
-				Some(vec![]),
+			assert_eq!(
+				db.indexed_storage_changes(&[1], 0, 2).unwrap(),
+				Some(vec![(0, value_hash(1)), (1, value_hash(2)), (2, None)]),
+			);
+			assert_eq!(
+				db.indexed_storage_changes(&[1], 1, 1).unwrap(),
+				Some(vec![(1, value_hash(2))]),
+			);
+			assert_eq!(db.indexed_storage_changes(&[1], 5, 5).unwrap(), Some(vec![(2, None)]));
+			assert_eq!(db.indexed_storage_changes(&[2], 0, 2).unwrap(), Some(vec![]));
+			assert_eq!(
+				Backend::<Block>::new_test(2, 0).indexed_storage_changes(&[1], 0, 2).unwrap(),
 				None,
-				NewBlockState::Best,
-			).unwrap();
-
-			backend.commit_operation(op).unwrap();
-			assert_eq!(backend.storage.db.get(
-				columns::STATE,
-				&trie::prefixed_key::<Blake2Hasher>(&key, EMPTY_PREFIX)
-			).unwrap().unwrap(), &b"hello"[..]);
-			hash
-		};
+			);
+		});
+	}
 
-		let hash = {
-			let mut op = backend.begin_operation().unwrap();
-			backend.begin_state_operation(&mut op, BlockId::Number(0)).unwrap();
-			let mut header = Header {
-				number: 1,
-				parent_hash: hash,
-				state_root: Default::default(),
-				digest: Default::default(),
-				extrinsics_root: Default::default(),
+	#[test]
+	fn prunes_finalized_block_bodies() {
+		for_each_test_database(|| {
+			let db = Backend::<Block>::new(DatabaseSettings {
+				state_cache_size: 16777216,
+				state_cache_child_ratio: Some((50, 100)),
+				pruning: PruningMode::keep_blocks(2),
+				source: DatabaseSettingsSrc::Custom(crate::utils::new_test_database()),
+				storage_index: false,
+				blocks_pruning: BlocksPruning::KeepFinalized { blocks: 2, justifications: true },
+			}, 0).unwrap();
+
+			struct TestRetention;
+			impl JustificationRetention<Block> for TestRetention {
+				fn keep_justification(&self, header: &Header) -> bool {
+					header.digest.logs().iter().any(|log| log.as_consensus().map_or(false, |(id, _)| id == *b"test"))
+				}
+			}
+			db.register_justification_retention(Arc::new(TestRetention));
+
+			let import = |number, parent_hash, extrinsics_root, state| {
+				let mut header = Header {
+					number,
+					parent_hash,
+					state_root: BlakeTwo256::trie_root(Vec::new()),
+					digest: Default::default(),
+					extrinsics_root,
+				};
+				match number {
+					1 => header.digest.push(DigestItem::Consensus(*b"babe", Vec::new())),
+					2 => header.digest.push(DigestItem::Consensus(*b"test", Vec::new())),
+					_ => (),
+				}
+				let mut op = db.begin_operation().unwrap();
+				db.begin_state_operation(&mut op, BlockId::Hash(parent_hash)).unwrap();
+				let justification = Some(vec![number as u8]);
+				op.set_block_data(header.clone(), Some(vec![]), justification, state).unwrap();
+				db.commit_operation(op).unwrap();
+				header.hash()
 			};
 
-			let storage: Vec<(_, _)> = vec![];
-
-			header.state_root = op.old_state.storage_root(storage
-				.iter()
-				.cloned()
-				.map(|(x, y)| (x, Some(y)))
-			).0.into();
-			let hash = header.hash();
+			let genesis_hash = import(0, Default::default(), Default::default(), NewBlockState::Final);
+			let fork_hash = import(1, genesis_hash, H256::from_low_u64_be(1), NewBlockState::Normal);
+			let mut parent_hash = genesis_hash;
+			for number in 1..5 {
+				parent_hash = import(number, parent_hash, Default::default(), NewBlockState::Final);
+			}
 
-			op.db_updates.insert(EMPTY_PREFIX, b"hello");
-			op.db_updates.remove(&key, EMPTY_PREFIX);
-			op.set_block_data(
-				header,
-				Some(vec![]),
+			let blockchain = db.blockchain();
+			assert!(blockchain.header(BlockId::Hash(fork_hash)).unwrap().is_some());
+			assert_eq!(
+				read_db(&*db.storage.db, columns::KEY_LOOKUP, columns::BODY, BlockId::Hash(fork_hash)).unwrap(),
 				None,
-				NewBlockState::Best,
-			).unwrap();
+			);
+			for number in 0..3 {
+				match blockchain.body(BlockId::Number(number)) {
+					Err(client::error::Error::BlockBodyPruned(_)) => (),
+					body => panic!("Unexpected body of block #{}: {:?}", number, body),
+				}
+				assert!(blockchain.header(BlockId::Number(number)).unwrap().is_some());
+			}
+			assert_eq!(blockchain.body(BlockId::Number(3)).unwrap(), Some(vec![]));
+			assert_eq!(blockchain.body(BlockId::Number(4)).unwrap(), Some(vec![]));
+			assert_eq!(blockchain.justification(BlockId::Number(1)).unwrap(), None);
+			assert_eq!(blockchain.justification(BlockId::Number(2)).unwrap(), Some(vec![2]));
+			assert_eq!(blockchain.justification(BlockId::Number(3)).unwrap(), Some(vec![3]));
+		});
+	}
 
-			backend.commit_operation(op).unwrap();
-			assert_eq!(backend.storage.db.get(
-				columns::STATE,
-				&trie::prefixed_key::<Blake2Hasher>(&key, EMPTY_PREFIX)
-			).unwrap().unwrap(), &b"hello"[..]);
-			hash
-		};
+	#[test]
+	fn delete_only_when_negative_rc() {
+		for_each_test_database(|| {
+			let _ = ::env_logger::try_init();
+			let key;
+			let backend = Backend::<Block>::new_test(1, 0);
+
+			let hash = {
+				let mut op = backend.begin_operation().unwrap();
+				backend.begin_state_operation(&mut op, BlockId::Hash(Default::default())).unwrap();
+				let mut header = Header {
+					number: 0,
+					parent_hash: Default::default(),
+					state_root: Default::default(),
+					digest: Default::default(),
+					extrinsics_root: Default::default(),
+				};
+
+				let storage: Vec<(_, _)> = vec![];
+
+				header.state_root = op.old_state.storage_root(storage
+					.iter()
+					.cloned()
+					.map(|(x, y)| (x, Some(y)))
+				).0.into();
+				let hash = header.hash();
 
-		let hash = {
-			let mut op = backend.begin_operation().unwrap();
-			backend.begin_state_operation(&mut op, BlockId::Number(1)).unwrap();
-			let mut header = Header {
-				number: 2,
-				parent_hash: hash,
-				state_root: Default::default(),
-				digest: Default::default(),
-				extrinsics_root: Default::default(),
+				op.reset_storage(storage.iter().cloned().collect(), Default::default()).unwrap();
+
+				key = op.db_updates.insert(EMPTY_PREFIX, b"hello");
+				op.set_block_data(
+					header,
+					Some(vec![]),
+					None,
+					NewBlockState::Best,
+				).unwrap();
+
+				backend.commit_operation(op).unwrap();
+				assert_eq!(backend.storage.db.get(
+					columns::STATE,
+					&trie::prefixed_key::<Blake2Hasher>(&key, EMPTY_PREFIX)
+				).unwrap().unwrap(), &b"hello"[..]);
+				hash
 			};
 
-			let storage: Vec<(_, _)> = vec![];
+			let hash = {
+				let mut op = backend.begin_operation().unwrap();
+				backend.begin_state_operation(&mut op, BlockId::Number(0)).unwrap();
+				let mut header = Header {
+					number: 1,
+					parent_hash: hash,
+					state_root: Default::default(),
+					digest: Default::default(),
+					extrinsics_root: Default::default(),
+				};
+
+				let storage: Vec<(_, _)> = vec![];
+
+				header.state_root = op.old_state.storage_root(storage
+					.iter()
+					.cloned()
+					.map(|(x, y)| (x, Some(y)))
+				).0.into();
+				let hash = header.hash();
 
-			header.state_root = op.old_state.storage_root(storage
-				.iter()
-				.cloned()
-				.map(|(x, y)| (x, Some(y)))
-			).0.into();
-			let hash = header.hash();
+				op.db_updates.insert(EMPTY_PREFIX, b"hello");
+				op.db_updates.remove(&key, EMPTY_PREFIX);
+				op.set_block_data(
+					header,
+					Some(vec![]),
+					None,
+					NewBlockState::Best,
+				).unwrap();
+
+				backend.commit_operation(op).unwrap();
+				assert_eq!(backend.storage.db.get(
+					columns::STATE,
+					&trie::prefixed_key::<Blake2Hasher>(&key, EMPTY_PREFIX)
+				).unwrap().unwrap(), &b"hello"[..]);
+				hash
+			};
 
-			op.db_updates.remove(&key, EMPTY_PREFIX);
-			op.set_block_data(
-				header,
-				Some(vec![]),
-				None,
-				NewBlockState::Best,
-			).unwrap();
+			let hash = {
+				let mut op = backend.begin_operation().unwrap();
+				backend.begin_state_operation(&mut op, BlockId::Number(1)).unwrap();
+				let mut header = Header {
+					number: 2,
+					parent_hash: hash,
+					state_root: Default::default(),
+					digest: Default::default(),
+					extrinsics_root: Default::default(),
+				};
+
+				let storage: Vec<(_, _)> = vec![];
+
+				header.state_root = op.old_state.storage_root(storage
+					.iter()
+					.cloned()
+					.map(|(x, y)| (x, Some(y)))
+				).0.into();
+				let hash = header.hash();
 
-			backend.commit_operation(op).unwrap();
+				op.db_updates.remove(&key, EMPTY_PREFIX);
+				op.set_block_data(
+					header,
+					Some(vec![]),
+					None,
+					NewBlockState::Best,
+				).unwrap();
 
+				backend.commit_operation(op).unwrap();
 
-			assert!(backend.storage.db.get(
-				columns::STATE,
-				&trie::prefixed_key::<Blake2Hasher>(&key, EMPTY_PREFIX)
-			).unwrap().is_some());
-			hash
-		};
 
-		{
-			let mut op = backend.begin_operation().unwrap();
-			backend.begin_state_operation(&mut op, BlockId::Number(2)).unwrap();
-			let mut header = Header {
-				number: 3,
-				parent_hash: hash,
-				state_root: Default::default(),
-				digest: Default::default(),
-				extrinsics_root: Default::default(),
+				assert!(backend.storage.db.get(
+					columns::STATE,
+					&trie::prefixed_key::<Blake2Hasher>(&key, EMPTY_PREFIX)
+				).unwrap().is_some());
+				hash
 			};
 
-			let storage: Vec<(_, _)> = vec![];
-
-			header.state_root = op.old_state.storage_root(storage
-				.iter()
-				.cloned()
-				.map(|(x, y)| (x, Some(y)))
-			).0.into();
-
-			op.set_block_data(
-				header,
-				Some(vec![]),
-				None,
-				NewBlockState::Best,
-			).unwrap();
+			{
+				let mut op = backend.begin_operation().unwrap();
+				backend.begin_state_operation(&mut op, BlockId::Number(2)).unwrap();
+				let mut header = Header {
+					number: 3,
+					parent_hash: hash,
+					state_root: Default::default(),
+					digest: Default::default(),
+					extrinsics_root: Default::default(),
+				};
+
+				let storage: Vec<(_, _)> = vec![];
+
+				header.state_root = op.old_state.storage_root(storage
+					.iter()
+					.cloned()
+					.map(|(x, y)| (x, Some(y)))
+				).0.into();
+
+				op.set_block_data(
+					header,
+					Some(vec![]),
+					None,
+					NewBlockState::Best,
+				).unwrap();
+
+				backend.commit_operation(op).unwrap();
+				assert!(backend.storage.db.get(
+					columns::STATE,
+					&trie::prefixed_key::<Blake2Hasher>(&key, EMPTY_PREFIX)
+				).unwrap().is_none());
+			}
 
-			backend.commit_operation(op).unwrap();
+			backend.finalize_block(BlockId::Number(1), None).unwrap();
+			backend.finalize_block(BlockId::Number(2), None).unwrap();
+			backend.finalize_block(BlockId::Number(3), None).unwrap();
 			assert!(backend.storage.db.get(
 				columns::STATE,
 				&trie::prefixed_key::<Blake2Hasher>(&key, EMPTY_PREFIX)
 			).unwrap().is_none());
-		}
-
-		backend.finalize_block(BlockId::Number(1), None).unwrap();
-		backend.finalize_block(BlockId::Number(2), None).unwrap();
-		backend.finalize_block(BlockId::Number(3), None).unwrap();
-		assert!(backend.storage.db.get(
-			columns::STATE,
-			&trie::prefixed_key::<Blake2Hasher>(&key, EMPTY_PREFIX)
-		).unwrap().is_none());
+		});
 	}
 
 	#[test]
 	fn changes_trie_storage_works() {
-		let backend = Backend::<Block>::new_test(1000, 100);
-		backend.changes_tries_storage.meta.write().finalized_number = 1000;
+		for_each_test_database(|| {
+			let backend = Backend::<Block>::new_test(1000, 100);
+			backend.changes_tries_storage.meta.write().finalized_number = 1000;
 
 
-		let check_changes = |backend: &Backend<Block>, block: u64, changes: Vec<(Vec<u8>, Vec<u8>)>| {
-			let (changes_root, mut changes_trie_update) = prepare_changes(changes);
-			let anchor = state_machine::ChangesTrieAnchorBlockId {
-				hash: backend.blockchain().header(BlockId::Number(block)).unwrap().unwrap().hash(),
-				number: block
-			};
-			assert_eq!(backend.changes_tries_storage.root(&anchor, block), Ok(Some(changes_root)));
+			let check_changes = |backend: &Backend<Block>, block: u64, changes: Vec<(Vec<u8>, Vec<u8>)>| {
+				let (changes_root, mut changes_trie_update) = prepare_changes(changes);
+				let anchor = state_machine::ChangesTrieAnchorBlockId {
+					hash: backend.blockchain().header(BlockId::Number(block)).unwrap().unwrap().hash(),
+					number: block
+				};
+				assert_eq!(backend.changes_tries_storage.root(&anchor, block), Ok(Some(changes_root)));
 
-			for (key, (val, _)) in changes_trie_update.drain() {
-				assert_eq!(backend.changes_trie_storage().unwrap().get(&key, EMPTY_PREFIX), Ok(Some(val)));
-			}
-		};
+				for (key, (val, _)) in changes_trie_update.drain() {
+					assert_eq!(backend.changes_trie_storage().unwrap().get(&key, EMPTY_PREFIX), Ok(Some(val)));
+				}
+			};
 
-		let changes0 = vec![(b"key_at_0".to_vec(), b"val_at_0".to_vec())];
-		let changes1 = vec![
-			(b"key_at_1".to_vec(), b"val_at_1".to_vec()),
-			(b"another_key_at_1".to_vec(), b"another_val_at_1".to_vec()),
-		];
-		let changes2 = vec![(b"key_at_2".to_vec(), b"val_at_2".to_vec())];
+			let changes0 = vec![(b"key_at_0".to_vec(), b"val_at_0".to_vec())];
+			let changes1 = vec![
+				(b"key_at_1".to_vec(), b"val_at_1".to_vec()),
+				(b"another_key_at_1".to_vec(), b"another_val_at_1".to_vec()),
+			];
+			let changes2 = vec![(b"key_at_2".to_vec(), b"val_at_2".to_vec())];
 
-		let block0 = insert_header(&backend, 0, Default::default(), changes0.clone(), Default::default());
-		let block1 = insert_header(&backend, 1, block0, changes1.clone(), Default::default());
-		let _ = insert_header(&backend, 2, block1, changes2.clone(), Default::default());
+			let block0 = insert_header(&backend, 0, Default::default(), changes0.clone(), Default::default());
+			let block1 = insert_header(&backend, 1, block0, changes1.clone(), Default::default());
+			let _ = insert_header(&backend, 2, block1, changes2.clone(), Default::default());
 
-		// check that the storage contains tries for all blocks
-		check_changes(&backend, 0, changes0);
-		check_changes(&backend, 1, changes1);
-		check_changes(&backend, 2, changes2);
+			// check that the storage contains tries for all blocks
+			check_changes(&backend, 0, changes0);
+			check_changes(&backend, 1, changes1);
+			check_changes(&backend, 2, changes2);
+		});
 	}
 
 	#[test]
 	fn changes_trie_storage_works_with_forks() {
-		let backend = Backend::<Block>::new_test(1000, 100);
-
-		let changes0 = vec![(b"k0".to_vec(), b"v0".to_vec())];
-		let changes1 = vec![(b"k1".to_vec(), b"v1".to_vec())];
-		let changes2 = vec![(b"k2".to_vec(), b"v2".to_vec())];
-		let block0 = insert_header(&backend, 0, Default::default(), changes0.clone(), Default::default());
-		let block1 = insert_header(&backend, 1, block0, changes1.clone(), Default::default());
-		let block2 = insert_header(&backend, 2, block1, changes2.clone(), Default::default());
-
-		let changes2_1_0 = vec![(b"k3".to_vec(), b"v3".to_vec())];
-		let changes2_1_1 = vec![(b"k4".to_vec(), b"v4".to_vec())];
-		let block2_1_0 = insert_header(&backend, 3, block2, changes2_1_0.clone(), Default::default());
-		let block2_1_1 = insert_header(&backend, 4, block2_1_0, changes2_1_1.clone(), Default::default());
-
-		let changes2_2_0 = vec![(b"k5".to_vec(), b"v5".to_vec())];
-		let changes2_2_1 = vec![(b"k6".to_vec(), b"v6".to_vec())];
-		let block2_2_0 = insert_header(&backend, 3, block2, changes2_2_0.clone(), Default::default());
-		let block2_2_1 = insert_header(&backend, 4, block2_2_0, changes2_2_1.clone(), Default::default());
-
-		// finalize block1
-		backend.changes_tries_storage.meta.write().finalized_number = 1;
-
-		// branch1: when asking for finalized block hash
-		let (changes1_root, _) = prepare_changes(changes1);
-		let anchor = state_machine::ChangesTrieAnchorBlockId { hash: block2_1_1, number: 4 };
-		assert_eq!(backend.changes_tries_storage.root(&anchor, 1), Ok(Some(changes1_root)));
-
-		// branch2: when asking for finalized block hash
-		let anchor = state_machine::ChangesTrieAnchorBlockId { hash: block2_2_1, number: 4 };
-		assert_eq!(backend.changes_tries_storage.root(&anchor, 1), Ok(Some(changes1_root)));
-
-		// branch1: when asking for non-finalized block hash (search by traversal)
-		let (changes2_1_0_root, _) = prepare_changes(changes2_1_0);
-		let anchor = state_machine::ChangesTrieAnchorBlockId { hash: block2_1_1, number: 4 };
-		assert_eq!(backend.changes_tries_storage.root(&anchor, 3), Ok(Some(changes2_1_0_root)));
-
-		// branch2: when asking for non-finalized block hash (search using canonicalized hint)
-		let (changes2_2_0_root, _) = prepare_changes(changes2_2_0);
-		let anchor = state_machine::ChangesTrieAnchorBlockId { hash: block2_2_1, number: 4 };
-		assert_eq!(backend.changes_tries_storage.root(&anchor, 3), Ok(Some(changes2_2_0_root)));
-
-		// finalize first block of branch2 (block2_2_0)
-		backend.changes_tries_storage.meta.write().finalized_number = 3;
-
-		// branch2: when asking for finalized block of this branch
-		assert_eq!(backend.changes_tries_storage.root(&anchor, 3), Ok(Some(changes2_2_0_root)));
-
-		// branch1: when asking for finalized block of other branch
-		// => result is incorrect (returned for the block of branch1), but this is expected,
-		// because the other fork is abandoned (forked before finalized header)
-		let anchor = state_machine::ChangesTrieAnchorBlockId { hash: block2_1_1, number: 4 };
-		assert_eq!(backend.changes_tries_storage.root(&anchor, 3), Ok(Some(changes2_2_0_root)));
+		for_each_test_database(|| {
+			let backend = Backend::<Block>::new_test(1000, 100);
+
+			let changes0 = vec![(b"k0".to_vec(), b"v0".to_vec())];
+			let changes1 = vec![(b"k1".to_vec(), b"v1".to_vec())];
+			let changes2 = vec![(b"k2".to_vec(), b"v2".to_vec())];
+			let block0 = insert_header(&backend, 0, Default::default(), changes0.clone(), Default::default());
+			let block1 = insert_header(&backend, 1, block0, changes1.clone(), Default::default());
+			let block2 = insert_header(&backend, 2, block1, changes2.clone(), Default::default());
+
+			let changes2_1_0 = vec![(b"k3".to_vec(), b"v3".to_vec())];
+			let changes2_1_1 = vec![(b"k4".to_vec(), b"v4".to_vec())];
+			let block2_1_0 = insert_header(&backend, 3, block2, changes2_1_0.clone(), Default::default());
+			let block2_1_1 = insert_header(&backend, 4, block2_1_0, changes2_1_1.clone(), Default::default());
+
+			let changes2_2_0 = vec![(b"k5".to_vec(), b"v5".to_vec())];
+			let changes2_2_1 = vec![(b"k6".to_vec(), b"v6".to_vec())];
+			let block2_2_0 = insert_header(&backend, 3, block2, changes2_2_0.clone(), Default::default());
+			let block2_2_1 = insert_header(&backend, 4, block2_2_0, changes2_2_1.clone(), Default::default());
+
+			// finalize block1
+			backend.changes_tries_storage.meta.write().finalized_number = 1;
+
+			// branch1: when asking for finalized block hash
+			let (changes1_root, _) = prepare_changes(changes1);
+			let anchor = state_machine::ChangesTrieAnchorBlockId { hash: block2_1_1, number: 4 };
+			assert_eq!(backend.changes_tries_storage.root(&anchor, 1), Ok(Some(changes1_root)));
+
+			// branch2: when asking for finalized block hash
+			let anchor = state_machine::ChangesTrieAnchorBlockId { hash: block2_2_1, number: 4 };
+			assert_eq!(backend.changes_tries_storage.root(&anchor, 1), Ok(Some(changes1_root)));
+
+			// branch1: when asking for non-finalized block hash (search by traversal)
+			let (changes2_1_0_root, _) = prepare_changes(changes2_1_0);
+			let anchor = state_machine::ChangesTrieAnchorBlockId { hash: block2_1_1, number: 4 };
+			assert_eq!(backend.changes_tries_storage.root(&anchor, 3), Ok(Some(changes2_1_0_root)));
+
+			// branch2: when asking for non-finalized block hash (search using canonicalized hint)
+			let (changes2_2_0_root, _) = prepare_changes(changes2_2_0);
+			let anchor = state_machine::ChangesTrieAnchorBlockId { hash: block2_2_1, number: 4 };
+			assert_eq!(backend.changes_tries_storage.root(&anchor, 3), Ok(Some(changes2_2_0_root)));
+
+			// finalize first block of branch2 (block2_2_0)
+			backend.changes_tries_storage.meta.write().finalized_number = 3;
+
+			// branch2: when asking for finalized block of this branch
+			assert_eq!(backend.changes_tries_storage.root(&anchor, 3), Ok(Some(changes2_2_0_root)));
+
+			// branch1: when asking for finalized block of other branch
+			// => result is incorrect (returned for the block of branch1), but this is expected,
+			// because the other fork is abandoned (forked before finalized header)
+			let anchor = state_machine::ChangesTrieAnchorBlockId { hash: block2_1_1, number: 4 };
+			assert_eq!(backend.changes_tries_storage.root(&anchor, 3), Ok(Some(changes2_2_0_root)));
+		});
 	}
 
 	#[test]
 	fn changes_tries_with_digest_are_pruned_on_finalization() {
-		let mut backend = Backend::<Block>::new_test(1000, 100);
-		backend.changes_tries_storage.min_blocks_to_keep = Some(8);
-		let config = ChangesTrieConfiguration {
-			digest_interval: 2,
-			digest_levels: 2,
-		};
+		for_each_test_database(|| {
+			let mut backend = Backend::<Block>::new_test(1000, 100);
+			backend.changes_tries_storage.min_blocks_to_keep = Some(8);
+			let config = ChangesTrieConfiguration {
+				digest_interval: 2,
+				digest_levels: 2,
+			};
 
-		// insert some blocks
-		let block0 = insert_header(&backend, 0, Default::default(), vec![(b"key_at_0".to_vec(), b"val_at_0".to_vec())], Default::default());
-		let block1 = insert_header(&backend, 1, block0, vec![(b"key_at_1".to_vec(), b"val_at_1".to_vec())], Default::default());
-		let block2 = insert_header(&backend, 2, block1, vec![(b"key_at_2".to_vec(), b"val_at_2".to_vec())], Default::default());
-		let block3 = insert_header(&backend, 3, block2, vec![(b"key_at_3".to_vec(), b"val_at_3".to_vec())], Default::default());
-		let block4 = insert_header(&backend, 4, block3, vec![(b"key_at_4".to_vec(), b"val_at_4".to_vec())], Default::default());
-		let block5 = insert_header(&backend, 5, block4, vec![(b"key_at_5".to_vec(), b"val_at_5".to_vec())], Default::default());
-		let block6 = insert_header(&backend, 6, block5, vec![(b"key_at_6".to_vec(), b"val_at_6".to_vec())], Default::default());
-		let block7 = insert_header(&backend, 7, block6, vec![(b"key_at_7".to_vec(), b"val_at_7".to_vec())], Default::default());
-		let block8 = insert_header(&backend, 8, block7, vec![(b"key_at_8".to_vec(), b"val_at_8".to_vec())], Default::default());
-		let block9 = insert_header(&backend, 9, block8, vec![(b"key_at_9".to_vec(), b"val_at_9".to_vec())], Default::default());
-		let block10 = insert_header(&backend, 10, block9, vec![(b"key_at_10".to_vec(), b"val_at_10".to_vec())], Default::default());
-		let block11 = insert_header(&backend, 11, block10, vec![(b"key_at_11".to_vec(), b"val_at_11".to_vec())], Default::default());
-		let block12 = insert_header(&backend, 12, block11, vec![(b"key_at_12".to_vec(), b"val_at_12".to_vec())], Default::default());
-		let block13 = insert_header(&backend, 13, block12, vec![(b"key_at_13".to_vec(), b"val_at_13".to_vec())], Default::default());
-		backend.changes_tries_storage.meta.write().finalized_number = 13;
-
-		// check that roots of all tries are in the columns::CHANGES_TRIE
-		let anchor = state_machine::ChangesTrieAnchorBlockId { hash: block13, number: 13 };
-		fn read_changes_trie_root(backend: &Backend<Block>, num: u64) -> H256 {
-			backend.blockchain().header(BlockId::Number(num)).unwrap().unwrap().digest().logs().iter()
-				.find(|i| i.as_changes_trie_root().is_some()).unwrap().as_changes_trie_root().unwrap().clone()
-		}
-		let root1 = read_changes_trie_root(&backend, 1); assert_eq!(backend.changes_tries_storage.root(&anchor, 1).unwrap(), Some(root1));
-		let root2 = read_changes_trie_root(&backend, 2); assert_eq!(backend.changes_tries_storage.root(&anchor, 2).unwrap(), Some(root2));
-		let root3 = read_changes_trie_root(&backend, 3); assert_eq!(backend.changes_tries_storage.root(&anchor, 3).unwrap(), Some(root3));
-		let root4 = read_changes_trie_root(&backend, 4); assert_eq!(backend.changes_tries_storage.root(&anchor, 4).unwrap(), Some(root4));
-		let root5 = read_changes_trie_root(&backend, 5); assert_eq!(backend.changes_tries_storage.root(&anchor, 5).unwrap(), Some(root5));
-		let root6 = read_changes_trie_root(&backend, 6); assert_eq!(backend.changes_tries_storage.root(&anchor, 6).unwrap(), Some(root6));
-		let root7 = read_changes_trie_root(&backend, 7); assert_eq!(backend.changes_tries_storage.root(&anchor, 7).unwrap(), Some(root7));
-		let root8 = read_changes_trie_root(&backend, 8); assert_eq!(backend.changes_tries_storage.root(&anchor, 8).unwrap(), Some(root8));
-		let root9 = read_changes_trie_root(&backend, 9); assert_eq!(backend.changes_tries_storage.root(&anchor, 9).unwrap(), Some(root9));
-		let root10 = read_changes_trie_root(&backend, 10); assert_eq!(backend.changes_tries_storage.root(&anchor, 10).unwrap(), Some(root10));
-		let root11 = read_changes_trie_root(&backend, 11); assert_eq!(backend.changes_tries_storage.root(&anchor, 11).unwrap(), Some(root11));
-		let root12 = read_changes_trie_root(&backend, 12); assert_eq!(backend.changes_tries_storage.root(&anchor, 12).unwrap(), Some(root12));
-
-		// now simulate finalization of block#12, causing prune of tries at #1..#4
-		let mut tx = DBTransaction::new();
-		backend.changes_tries_storage.prune(&config, &mut tx, Default::default(), 12);
-		backend.storage.db.write(tx).unwrap();
-		assert!(backend.changes_tries_storage.get(&root1, EMPTY_PREFIX).unwrap().is_none());
-		assert!(backend.changes_tries_storage.get(&root2, EMPTY_PREFIX).unwrap().is_none());
-		assert!(backend.changes_tries_storage.get(&root3, EMPTY_PREFIX).unwrap().is_none());
-		assert!(backend.changes_tries_storage.get(&root4, EMPTY_PREFIX).unwrap().is_none());
-		assert!(backend.changes_tries_storage.get(&root5, EMPTY_PREFIX).unwrap().is_some());
-		assert!(backend.changes_tries_storage.get(&root6, EMPTY_PREFIX).unwrap().is_some());
-		assert!(backend.changes_tries_storage.get(&root7, EMPTY_PREFIX).unwrap().is_some());
-		assert!(backend.changes_tries_storage.get(&root8, EMPTY_PREFIX).unwrap().is_some());
-
-		// now simulate finalization of block#16, causing prune of tries at #5..#8
-		let mut tx = DBTransaction::new();
-		backend.changes_tries_storage.prune(&config, &mut tx, Default::default(), 16);
-		backend.storage.db.write(tx).unwrap();
-		assert!(backend.changes_tries_storage.get(&root5, EMPTY_PREFIX).unwrap().is_none());
-		assert!(backend.changes_tries_storage.get(&root6, EMPTY_PREFIX).unwrap().is_none());
-		assert!(backend.changes_tries_storage.get(&root7, EMPTY_PREFIX).unwrap().is_none());
-		assert!(backend.changes_tries_storage.get(&root8, EMPTY_PREFIX).unwrap().is_none());
-
-		// now "change" pruning mode to archive && simulate finalization of block#20
-		// => no changes tries are pruned, because we never prune in archive mode
-		backend.changes_tries_storage.min_blocks_to_keep = None;
-		let mut tx = DBTransaction::new();
-		backend.changes_tries_storage.prune(&config, &mut tx, Default::default(), 20);
-		backend.storage.db.write(tx).unwrap();
-		assert!(backend.changes_tries_storage.get(&root9, EMPTY_PREFIX).unwrap().is_some());
-		assert!(backend.changes_tries_storage.get(&root10, EMPTY_PREFIX).unwrap().is_some());
-		assert!(backend.changes_tries_storage.get(&root11, EMPTY_PREFIX).unwrap().is_some());
-		assert!(backend.changes_tries_storage.get(&root12, EMPTY_PREFIX).unwrap().is_some());
+			// insert some blocks
+			let block0 = insert_header(&backend, 0, Default::default(), vec![(b"key_at_0".to_vec(), b"val_at_0".to_vec())], Default::default());
+			let block1 = insert_header(&backend, 1, block0, vec![(b"key_at_1".to_vec(), b"val_at_1".to_vec())], Default::default());
+			let block2 = insert_header(&backend, 2, block1, vec![(b"key_at_2".to_vec(), b"val_at_2".to_vec())], Default::default());
+			let block3 = insert_header(&backend, 3, block2, vec![(b"key_at_3".to_vec(), b"val_at_3".to_vec())], Default::default());
+			let block4 = insert_header(&backend, 4, block3, vec![(b"key_at_4".to_vec(), b"val_at_4".to_vec())], Default::default());
+			let block5 = insert_header(&backend, 5, block4, vec![(b"key_at_5".to_vec(), b"val_at_5".to_vec())], Default::default());
+			let block6 = insert_header(&backend, 6, block5, vec![(b"key_at_6".to_vec(), b"val_at_6".to_vec())], Default::default());
+			let block7 = insert_header(&backend, 7, block6, vec![(b"key_at_7".to_vec(), b"val_at_7".to_vec())], Default::default());
+			let block8 = insert_header(&backend, 8, block7, vec![(b"key_at_8".to_vec(), b"val_at_8".to_vec())], Default::default());
+			let block9 = insert_header(&backend, 9, block8, vec![(b"key_at_9".to_vec(), b"val_at_9".to_vec())], Default::default());
+			let block10 = insert_header(&backend, 10, block9, vec![(b"key_at_10".to_vec(), b"val_at_10".to_vec())], Default::default());
+			let block11 = insert_header(&backend, 11, block10, vec![(b"key_at_11".to_vec(), b"val_at_11".to_vec())], Default::default());
+			let block12 = insert_header(&backend, 12, block11, vec![(b"key_at_12".to_vec(), b"val_at_12".to_vec())], Default::default());
+			let block13 = insert_header(&backend, 13, block12, vec![(b"key_at_13".to_vec(), b"val_at_13".to_vec())], Default::default());
+			backend.changes_tries_storage.meta.write().finalized_number = 13;
+
+			// check that roots of all tries are in the columns::CHANGES_TRIE
+			let anchor = state_machine::ChangesTrieAnchorBlockId { hash: block13, number: 13 };
+			fn read_changes_trie_root(backend: &Backend<Block>, num: u64) -> H256 {
+				backend.blockchain().header(BlockId::Number(num)).unwrap().unwrap().digest().logs().iter()
+					.find(|i| i.as_changes_trie_root().is_some()).unwrap().as_changes_trie_root().unwrap().clone()
+			}
+			let root1 = read_changes_trie_root(&backend, 1); assert_eq!(backend.changes_tries_storage.root(&anchor, 1).unwrap(), Some(root1));
+			let root2 = read_changes_trie_root(&backend, 2); assert_eq!(backend.changes_tries_storage.root(&anchor, 2).unwrap(), Some(root2));
+			let root3 = read_changes_trie_root(&backend, 3); assert_eq!(backend.changes_tries_storage.root(&anchor, 3).unwrap(), Some(root3));
+			let root4 = read_changes_trie_root(&backend, 4); assert_eq!(backend.changes_tries_storage.root(&anchor, 4).unwrap(), Some(root4));
+			let root5 = read_changes_trie_root(&backend, 5); assert_eq!(backend.changes_tries_storage.root(&anchor, 5).unwrap(), Some(root5));
+			let root6 = read_changes_trie_root(&backend, 6); assert_eq!(backend.changes_tries_storage.root(&anchor, 6).unwrap(), Some(root6));
+			let root7 = read_changes_trie_root(&backend, 7); assert_eq!(backend.changes_tries_storage.root(&anchor, 7).unwrap(), Some(root7));
+			let root8 = read_changes_trie_root(&backend, 8); assert_eq!(backend.changes_tries_storage.root(&anchor, 8).unwrap(), Some(root8));
+			let root9 = read_changes_trie_root(&backend, 9); assert_eq!(backend.changes_tries_storage.root(&anchor, 9).unwrap(), Some(root9));
+			let root10 = read_changes_trie_root(&backend, 10); assert_eq!(backend.changes_tries_storage.root(&anchor, 10).unwrap(), Some(root10));
+			let root11 = read_changes_trie_root(&backend, 11); assert_eq!(backend.changes_tries_storage.root(&anchor, 11).unwrap(), Some(root11));
+			let root12 = read_changes_trie_root(&backend, 12); assert_eq!(backend.changes_tries_storage.root(&anchor, 12).unwrap(), Some(root12));
+
+			// now simulate finalization of block#12, causing prune of tries at #1..#4
+			let mut tx = Transaction::new();
+			backend.changes_tries_storage.prune(&config, &mut tx, Default::default(), 12);
+			backend.storage.db.commit(tx).unwrap();
+			assert!(backend.changes_tries_storage.get(&root1, EMPTY_PREFIX).unwrap().is_none());
+			assert!(backend.changes_tries_storage.get(&root2, EMPTY_PREFIX).unwrap().is_none());
+			assert!(backend.changes_tries_storage.get(&root3, EMPTY_PREFIX).unwrap().is_none());
+			assert!(backend.changes_tries_storage.get(&root4, EMPTY_PREFIX).unwrap().is_none());
+			assert!(backend.changes_tries_storage.get(&root5, EMPTY_PREFIX).unwrap().is_some());
+			assert!(backend.changes_tries_storage.get(&root6, EMPTY_PREFIX).unwrap().is_some());
+			assert!(backend.changes_tries_storage.get(&root7, EMPTY_PREFIX).unwrap().is_some());
+			assert!(backend.changes_tries_storage.get(&root8, EMPTY_PREFIX).unwrap().is_some());
+
+			// now simulate finalization of block#16, causing prune of tries at #5..#8
+			let mut tx = Transaction::new();
+			backend.changes_tries_storage.prune(&config, &mut tx, Default::default(), 16);
+			backend.storage.db.commit(tx).unwrap();
+			assert!(backend.changes_tries_storage.get(&root5, EMPTY_PREFIX).unwrap().is_none());
+			assert!(backend.changes_tries_storage.get(&root6, EMPTY_PREFIX).unwrap().is_none());
+			assert!(backend.changes_tries_storage.get(&root7, EMPTY_PREFIX).unwrap().is_none());
+			assert!(backend.changes_tries_storage.get(&root8, EMPTY_PREFIX).unwrap().is_none());
+
+			// now "change" pruning mode to archive && simulate finalization of block#20
+			// => no changes tries are pruned, because we never prune in archive mode
+			backend.changes_tries_storage.min_blocks_to_keep = None;
+			let mut tx = Transaction::new();
+			backend.changes_tries_storage.prune(&config, &mut tx, Default::default(), 20);
+			backend.storage.db.commit(tx).unwrap();
+			assert!(backend.changes_tries_storage.get(&root9, EMPTY_PREFIX).unwrap().is_some());
+			assert!(backend.changes_tries_storage.get(&root10, EMPTY_PREFIX).unwrap().is_some());
+			assert!(backend.changes_tries_storage.get(&root11, EMPTY_PREFIX).unwrap().is_some());
+			assert!(backend.changes_tries_storage.get(&root12, EMPTY_PREFIX).unwrap().is_some());
+		});
 	}
 
 	#[test]
 	fn changes_tries_without_digest_are_pruned_on_finalization() {
-		let mut backend = Backend::<Block>::new_test(1000, 100);
-		backend.changes_tries_storage.min_blocks_to_keep = Some(4);
-		let config = ChangesTrieConfiguration {
-			digest_interval: 0,
-			digest_levels: 0,
-		};
-
-		// insert some blocks
-		let block0 = insert_header(&backend, 0, Default::default(), vec![(b"key_at_0".to_vec(), b"val_at_0".to_vec())], Default::default());
-		let block1 = insert_header(&backend, 1, block0, vec![(b"key_at_1".to_vec(), b"val_at_1".to_vec())], Default::default());
-		let block2 = insert_header(&backend, 2, block1, vec![(b"key_at_2".to_vec(), b"val_at_2".to_vec())], Default::default());
-		let block3 = insert_header(&backend, 3, block2, vec![(b"key_at_3".to_vec(), b"val_at_3".to_vec())], Default::default());
-		let block4 = insert_header(&backend, 4, block3, vec![(b"key_at_4".to_vec(), b"val_at_4".to_vec())], Default::default());
-		let block5 = insert_header(&backend, 5, block4, vec![(b"key_at_5".to_vec(), b"val_at_5".to_vec())], Default::default());
-		let block6 = insert_header(&backend, 6, block5, vec![(b"key_at_6".to_vec(), b"val_at_6".to_vec())], Default::default());
-
-		// check that roots of all tries are in the columns::CHANGES_TRIE
-		let anchor = state_machine::ChangesTrieAnchorBlockId { hash: block6, number: 6 };
-		fn read_changes_trie_root(backend: &Backend<Block>, num: u64) -> H256 {
-			backend.blockchain().header(BlockId::Number(num)).unwrap().unwrap().digest().logs().iter()
-				.find(|i| i.as_changes_trie_root().is_some()).unwrap().as_changes_trie_root().unwrap().clone()
-		}
-
-		let root1 = read_changes_trie_root(&backend, 1); assert_eq!(backend.changes_tries_storage.root(&anchor, 1).unwrap(), Some(root1));
-		let root2 = read_changes_trie_root(&backend, 2); assert_eq!(backend.changes_tries_storage.root(&anchor, 2).unwrap(), Some(root2));
-		let root3 = read_changes_trie_root(&backend, 3); assert_eq!(backend.changes_tries_storage.root(&anchor, 3).unwrap(), Some(root3));
-		let root4 = read_changes_trie_root(&backend, 4); assert_eq!(backend.changes_tries_storage.root(&anchor, 4).unwrap(), Some(root4));
-		let root5 = read_changes_trie_root(&backend, 5); assert_eq!(backend.changes_tries_storage.root(&anchor, 5).unwrap(), Some(root5));
-		let root6 = read_changes_trie_root(&backend, 6); assert_eq!(backend.changes_tries_storage.root(&anchor, 6).unwrap(), Some(root6));
+		for_each_test_database(|| {
+			let mut backend = Backend::<Block>::new_test(1000, 100);
+			backend.changes_tries_storage.min_blocks_to_keep = Some(4);
+			let config = ChangesTrieConfiguration {
+				digest_interval: 0,
+				digest_levels: 0,
+			};
 
-		// now simulate finalization of block#5, causing prune of trie at #1
-		let mut tx = DBTransaction::new();
-		backend.changes_tries_storage.prune(&config, &mut tx, block5, 5);
-		backend.storage.db.write(tx).unwrap();
-		assert!(backend.changes_tries_storage.get(&root1, EMPTY_PREFIX).unwrap().is_none());
-		assert!(backend.changes_tries_storage.get(&root2, EMPTY_PREFIX).unwrap().is_some());
+			// insert some blocks
+			let block0 = insert_header(&backend, 0, Default::default(), vec![(b"key_at_0".to_vec(), b"val_at_0".to_vec())], Default::default());
+			let block1 = insert_header(&backend, 1, block0, vec![(b"key_at_1".to_vec(), b"val_at_1".to_vec())], Default::default());
+			let block2 = insert_header(&backend, 2, block1, vec![(b"key_at_2".to_vec(), b"val_at_2".to_vec())], Default::default());
+			let block3 = insert_header(&backend, 3, block2, vec![(b"key_at_3".to_vec(), b"val_at_3".to_vec())], Default::default());
+			let block4 = insert_header(&backend, 4, block3, vec![(b"key_at_4".to_vec(), b"val_at_4".to_vec())], Default::default());
+			let block5 = insert_header(&backend, 5, block4, vec![(b"key_at_5".to_vec(), b"val_at_5".to_vec())], Default::default());
+			let block6 = insert_header(&backend, 6, block5, vec![(b"key_at_6".to_vec(), b"val_at_6".to_vec())], Default::default());
+
+			// check that roots of all tries are in the columns::CHANGES_TRIE
+			let anchor = state_machine::ChangesTrieAnchorBlockId { hash: block6, number: 6 };
+			fn read_changes_trie_root(backend: &Backend<Block>, num: u64) -> H256 {
+				backend.blockchain().header(BlockId::Number(num)).unwrap().unwrap().digest().logs().iter()
+					.find(|i| i.as_changes_trie_root().is_some()).unwrap().as_changes_trie_root().unwrap().clone()
+			}
 
-		// now simulate finalization of block#6, causing prune of tries at #2
-		let mut tx = DBTransaction::new();
-		backend.changes_tries_storage.prune(&config, &mut tx, block6, 6);
-		backend.storage.db.write(tx).unwrap();
-		assert!(backend.changes_tries_storage.get(&root2, EMPTY_PREFIX).unwrap().is_none());
-		assert!(backend.changes_tries_storage.get(&root3, EMPTY_PREFIX).unwrap().is_some());
+			let root1 = read_changes_trie_root(&backend, 1); assert_eq!(backend.changes_tries_storage.root(&anchor, 1).unwrap(), Some(root1));
+			let root2 = read_changes_trie_root(&backend, 2); assert_eq!(backend.changes_tries_storage.root(&anchor, 2).unwrap(), Some(root2));
+			let root3 = read_changes_trie_root(&backend, 3); assert_eq!(backend.changes_tries_storage.root(&anchor, 3).unwrap(), Some(root3));
+			let root4 = read_changes_trie_root(&backend, 4); assert_eq!(backend.changes_tries_storage.root(&anchor, 4).unwrap(), Some(root4));
+			let root5 = read_changes_trie_root(&backend, 5); assert_eq!(backend.changes_tries_storage.root(&anchor, 5).unwrap(), Some(root5));
+			let root6 = read_changes_trie_root(&backend, 6); assert_eq!(backend.changes_tries_storage.root(&anchor, 6).unwrap(), Some(root6));
+
+			// now simulate finalization of block#5, causing prune of trie at #1
+			let mut tx = Transaction::new();
+			backend.changes_tries_storage.prune(&config, &mut tx, block5, 5);
+			backend.storage.db.commit(tx).unwrap();
+			assert!(backend.changes_tries_storage.get(&root1, EMPTY_PREFIX).unwrap().is_none());
+			assert!(backend.changes_tries_storage.get(&root2, EMPTY_PREFIX).unwrap().is_some());
+
+			// now simulate finalization of block#6, causing prune of tries at #2
+			let mut tx = Transaction::new();
+			backend.changes_tries_storage.prune(&config, &mut tx, block6, 6);
+			backend.storage.db.commit(tx).unwrap();
+			assert!(backend.changes_tries_storage.get(&root2, EMPTY_PREFIX).unwrap().is_none());
+			assert!(backend.changes_tries_storage.get(&root3, EMPTY_PREFIX).unwrap().is_some());
+		});
 	}
 
 	#[test]
 	fn tree_route_works() {
-		let backend = Backend::<Block>::new_test(1000, 100);
-		let blockchain = backend.blockchain();
-		let block0 = insert_header(&backend, 0, Default::default(), Vec::new(), Default::default());
-
-		// fork from genesis: 3 prong.
-		let a1 = insert_header(&backend, 1, block0, Vec::new(), Default::default());
-		let a2 = insert_header(&backend, 2, a1, Vec::new(), Default::default());
-		let a3 = insert_header(&backend, 3, a2, Vec::new(), Default::default());
-
-		// fork from genesis: 2 prong.
-		let b1 = insert_header(&backend, 1, block0, Vec::new(), H256::from([1; 32]));
-		let b2 = insert_header(&backend, 2, b1, Vec::new(), Default::default());
-
-		{
-			let tree_route = tree_route(blockchain, a3, b2).unwrap();
+		for_each_test_database(|| {
+			let backend = Backend::<Block>::new_test(1000, 100);
+			let blockchain = backend.blockchain();
+			let block0 = insert_header(&backend, 0, Default::default(), Vec::new(), Default::default());
+
+			// fork from genesis: 3 prong.
+			let a1 = insert_header(&backend, 1, block0, Vec::new(), Default::default());
+			let a2 = insert_header(&backend, 2, a1, Vec::new(), Default::default());
+			let a3 = insert_header(&backend, 3, a2, Vec::new(), Default::default());
+
+			// fork from genesis: 2 prong.
+			let b1 = insert_header(&backend, 1, block0, Vec::new(), H256::from([1; 32]));
+			let b2 = insert_header(&backend, 2, b1, Vec::new(), Default::default());
+
+			{
+				let tree_route = tree_route(blockchain, a3, b2).unwrap();
+
+				assert_eq!(tree_route.common_block().hash, block0);
+				assert_eq!(tree_route.retracted().iter().map(|r| r.hash).collect::<Vec<_>>(), vec![a3, a2, a1]);
+				assert_eq!(tree_route.enacted().iter().map(|r| r.hash).collect::<Vec<_>>(), vec![b1, b2]);
+			}
 
-			assert_eq!(tree_route.common_block().hash, block0);
-			assert_eq!(tree_route.retracted().iter().map(|r| r.hash).collect::<Vec<_>>(), vec![a3, a2, a1]);
-			assert_eq!(tree_route.enacted().iter().map(|r| r.hash).collect::<Vec<_>>(), vec![b1, b2]);
-		}
+			{
+				let tree_route = tree_route(blockchain, a1, a3).unwrap();
 
-		{
-			let tree_route = tree_route(blockchain, a1, a3).unwrap();
-
-			assert_eq!(tree_route.common_block().hash, a1);
-			assert!(tree_route.retracted().is_empty());
-			assert_eq!(tree_route.enacted().iter().map(|r| r.hash).collect::<Vec<_>>(), vec![a2, a3]);
-		}
+				assert_eq!(tree_route.common_block().hash, a1);
+				assert!(tree_route.retracted().is_empty());
+				assert_eq!(tree_route.enacted().iter().map(|r| r.hash).collect::<Vec<_>>(), vec![a2, a3]);
+			}
 
-		{
-			let tree_route = tree_route(blockchain, a3, a1).unwrap();
+			{
+				let tree_route = tree_route(blockchain, a3, a1).unwrap();
 
-			assert_eq!(tree_route.common_block().hash, a1);
-			assert_eq!(tree_route.retracted().iter().map(|r| r.hash).collect::<Vec<_>>(), vec![a3, a2]);
-			assert!(tree_route.enacted().is_empty());
-		}
+				assert_eq!(tree_route.common_block().hash, a1);
+				assert_eq!(tree_route.retracted().iter().map(|r| r.hash).collect::<Vec<_>>(), vec![a3, a2]);
+				assert!(tree_route.enacted().is_empty());
+			}
 
-		{
-			let tree_route = tree_route(blockchain, a2, a2).unwrap();
+			{
+				let tree_route = tree_route(blockchain, a2, a2).unwrap();
 
-			assert_eq!(tree_route.common_block().hash, a2);
-			assert!(tree_route.retracted().is_empty());
-			assert!(tree_route.enacted().is_empty());
-		}
+				assert_eq!(tree_route.common_block().hash, a2);
+				assert!(tree_route.retracted().is_empty());
+				assert!(tree_route.enacted().is_empty());
+			}
+		});
 	}
 
 	#[test]
 	fn tree_route_child() {
-		let backend = Backend::<Block>::new_test(1000, 100);
-		let blockchain = backend.blockchain();
+		for_each_test_database(|| {
+			let backend = Backend::<Block>::new_test(1000, 100);
+			let blockchain = backend.blockchain();
 
-		let block0 = insert_header(&backend, 0, Default::default(), Vec::new(), Default::default());
-		let block1 = insert_header(&backend, 1, block0, Vec::new(), Default::default());
+			let block0 = insert_header(&backend, 0, Default::default(), Vec::new(), Default::default());
+			let block1 = insert_header(&backend, 1, block0, Vec::new(), Default::default());
 
-		{
-			let tree_route = tree_route(blockchain, block0, block1).unwrap();
+			{
+				let tree_route = tree_route(blockchain, block0, block1).unwrap();
 
-			assert_eq!(tree_route.common_block().hash, block0);
-			assert!(tree_route.retracted().is_empty());
-			assert_eq!(tree_route.enacted().iter().map(|r| r.hash).collect::<Vec<_>>(), vec![block1]);
-		}
+				assert_eq!(tree_route.common_block().hash, block0);
+				assert!(tree_route.retracted().is_empty());
+				assert_eq!(tree_route.enacted().iter().map(|r| r.hash).collect::<Vec<_>>(), vec![block1]);
+			}
+		});
 	}
 
 	#[test]
 	fn lowest_common_ancestor_works() {
-		let backend = Backend::<Block>::new_test(1000, 100);
-		let blockchain = backend.blockchain();
-		let block0 = insert_header(&backend, 0, Default::default(), Vec::new(), Default::default());
+		for_each_test_database(|| {
+			let backend = Backend::<Block>::new_test(1000, 100);
+			let blockchain = backend.blockchain();
+			let block0 = insert_header(&backend, 0, Default::default(), Vec::new(), Default::default());
 
-		// fork from genesis: 3 prong.
-		let a1 = insert_header(&backend, 1, block0, Vec::new(), Default::default());
-		let a2 = insert_header(&backend, 2, a1, Vec::new(), Default::default());
-		let a3 = insert_header(&backend, 3, a2, Vec::new(), Default::default());
+			// fork from genesis: 3 prong.
+			let a1 = insert_header(&backend, 1, block0, Vec::new(), Default::default());
+			let a2 = insert_header(&backend, 2, a1, Vec::new(), Default::default());
+			let a3 = insert_header(&backend, 3, a2, Vec::new(), Default::default());
 
-		// fork from genesis: 2 prong.
-		let b1 = insert_header(&backend, 1, block0, Vec::new(), H256::from([1; 32]));
-		let b2 = insert_header(&backend, 2, b1, Vec::new(), Default::default());
+			// fork from genesis: 2 prong.
+			let b1 = insert_header(&backend, 1, block0, Vec::new(), H256::from([1; 32]));
+			let b2 = insert_header(&backend, 2, b1, Vec::new(), Default::default());
 
-		{
-			let lca = lowest_common_ancestor(blockchain, a3, b2).unwrap();
+			{
+				let lca = lowest_common_ancestor(blockchain, a3, b2).unwrap();
 
-			assert_eq!(lca.hash, block0);
-			assert_eq!(lca.number, 0);
-		}
+				assert_eq!(lca.hash, block0);
+				assert_eq!(lca.number, 0);
+			}
 
-		{
-			let lca = lowest_common_ancestor(blockchain, a1, a3).unwrap();
+			{
+				let lca = lowest_common_ancestor(blockchain, a1, a3).unwrap();
 
-			assert_eq!(lca.hash, a1);
-			assert_eq!(lca.number, 1);
-		}
+				assert_eq!(lca.hash, a1);
+				assert_eq!(lca.number, 1);
+			}
 
-		{
-			let lca = lowest_common_ancestor(blockchain, a3, a1).unwrap();
+			{
+				let lca = lowest_common_ancestor(blockchain, a3, a1).unwrap();
 
-			assert_eq!(lca.hash, a1);
-			assert_eq!(lca.number, 1);
-		}
+				assert_eq!(lca.hash, a1);
+				assert_eq!(lca.number, 1);
+			}
 
-		{
-			let lca = lowest_common_ancestor(blockchain, a2, a3).unwrap();
+			{
+				let lca = lowest_common_ancestor(blockchain, a2, a3).unwrap();
 
-			assert_eq!(lca.hash, a2);
-			assert_eq!(lca.number, 2);
-		}
+				assert_eq!(lca.hash, a2);
+				assert_eq!(lca.number, 2);
+			}
 
-		{
-			let lca = lowest_common_ancestor(blockchain, a2, a1).unwrap();
+			{
+				let lca = lowest_common_ancestor(blockchain, a2, a1).unwrap();
 
-			assert_eq!(lca.hash, a1);
-			assert_eq!(lca.number, 1);
-		}
+				assert_eq!(lca.hash, a1);
+				assert_eq!(lca.number, 1);
+			}
 
-		{
-			let lca = lowest_common_ancestor(blockchain, a2, a2).unwrap();
+			{
+				let lca = lowest_common_ancestor(blockchain, a2, a2).unwrap();
 
-			assert_eq!(lca.hash, a2);
-			assert_eq!(lca.number, 2);
-		}
+				assert_eq!(lca.hash, a2);
+				assert_eq!(lca.number, 2);
+			}
+		});
 	}
 
 	#[test]
 	fn test_tree_route_regression() {
-		// NOTE: this is a test for a regression introduced in #3665, the result
-		// of tree_route would be erroneously computed, since it was taking into
-		// account the `ancestor` in `CachedHeaderMetadata` for the comparison.
-		// in this test we simulate the same behavior with the side-effect
-		// triggering the issue being eviction of a previously fetched record
-		// from the cache, therefore this test is dependent on the LRU cache
-		// size for header metadata, which is currently set to 5000 elements.
-		let backend = Backend::<Block>::new_test(10000, 10000);
-		let blockchain = backend.blockchain();
-
-		let genesis = insert_header(&backend, 0, Default::default(), Vec::new(), Default::default());
-
-		let block100 = (1..=100).fold(genesis, |parent, n| {
-			insert_header(&backend, n, parent, Vec::new(), Default::default())
-		});
+		for_each_test_database(|| {
+			// NOTE: this is a test for a regression introduced in #3665, the result
+			// of tree_route would be erroneously computed, since it was taking into
+			// account the `ancestor` in `CachedHeaderMetadata` for the comparison.
+			// in this test we simulate the same behavior with the side-effect
+			// triggering the issue being eviction of a previously fetched record
+			// from the cache, therefore this test is dependent on the LRU cache
+			// size for header metadata, which is currently set to 5000 elements.
+			let backend = Backend::<Block>::new_test(10000, 10000);
+			let blockchain = backend.blockchain();
+
+			let genesis = insert_header(&backend, 0, Default::default(), Vec::new(), Default::default());
+
+			let block100 = (1..=100).fold(genesis, |parent, n| {
+				insert_header(&backend, n, parent, Vec::new(), Default::default())
+			});
+
+			let block7000 = (101..=7000).fold(block100, |parent, n| {
+				insert_header(&backend, n, parent, Vec::new(), Default::default())
+			});
+
+			// This will cause the ancestor of `block100` to be set to `genesis` as a side-effect.
+			lowest_common_ancestor(blockchain, genesis, block100).unwrap();
+
+			// While traversing the tree we will have to do 6900 calls to
+			// `header_metadata`, which will make sure we will exhaust our cache
+			// which only takes 5000 elements. In particular, the `CachedHeaderMetadata` struct for
+			// block #100 will be evicted and will get a new value (with ancestor set to its parent).
+			let tree_route = tree_route(blockchain, block100, block7000).unwrap();
 
-		let block7000 = (101..=7000).fold(block100, |parent, n| {
-			insert_header(&backend, n, parent, Vec::new(), Default::default())
+			assert!(tree_route.retracted().is_empty());
 		});
-
-		// This will cause the ancestor of `block100` to be set to `genesis` as a side-effect.
-		lowest_common_ancestor(blockchain, genesis, block100).unwrap();
-
-		// While traversing the tree we will have to do 6900 calls to
-		// `header_metadata`, which will make sure we will exhaust our cache
-		// which only takes 5000 elements. In particular, the `CachedHeaderMetadata` struct for
-		// block #100 will be evicted and will get a new value (with ancestor set to its parent).
-		let tree_route = tree_route(blockchain, block100, block7000).unwrap();
-
-		assert!(tree_route.retracted().is_empty());
 	}
 
 	#[test]
 	fn test_leaves_with_complex_block_tree() {
-		let backend: Arc<Backend<test_client::runtime::Block>> = Arc::new(Backend::new_test(20, 20));
-		test_client::trait_tests::test_leaves_for_backend(backend);
+		for_each_test_database(|| {
+			let backend: Arc<Backend<test_client::runtime::Block>> = Arc::new(Backend::new_test(20, 20));
+			test_client::trait_tests::test_leaves_for_backend(backend);
+		});
 	}
 
 	#[test]
 	fn test_children_with_complex_block_tree() {
-		let backend: Arc<Backend<test_client::runtime::Block>> = Arc::new(Backend::new_test(20, 20));
-		test_client::trait_tests::test_children_for_backend(backend);
+		for_each_test_database(|| {
+			let backend: Arc<Backend<test_client::runtime::Block>> = Arc::new(Backend::new_test(20, 20));
+			test_client::trait_tests::test_children_for_backend(backend);
+		});
 	}
 
 	#[test]
 	fn test_blockchain_query_by_number_gets_canonical() {
-		let backend: Arc<Backend<test_client::runtime::Block>> = Arc::new(Backend::new_test(20, 20));
-		test_client::trait_tests::test_blockchain_query_by_number_gets_canonical(backend);
+		for_each_test_database(|| {
+			let backend: Arc<Backend<test_client::runtime::Block>> = Arc::new(Backend::new_test(20, 20));
+			test_client::trait_tests::test_blockchain_query_by_number_gets_canonical(backend);
+		});
 	}
 
 	#[test]
 	fn test_leaves_pruned_on_finality() {
-		let backend: Backend<Block> = Backend::new_test(10, 10);
-		let block0 = insert_header(&backend, 0, Default::default(), Default::default(), Default::default());
+		for_each_test_database(|| {
+			let backend: Backend<Block> = Backend::new_test(10, 10);
+			let block0 = insert_header(&backend, 0, Default::default(), Default::default(), Default::default());
+
+			let block1_a = insert_header(&backend, 1, block0, Default::default(), Default::default());
+			let block1_b = insert_header(&backend, 1, block0, Default::default(), [1; 32].into());
+			let block1_c = insert_header(&backend, 1, block0, Default::default(), [2; 32].into());
 
-		let block1_a = insert_header(&backend, 1, block0, Default::default(), Default::default());
-		let block1_b = insert_header(&backend, 1, block0, Default::default(), [1; 32].into());
-		let block1_c = insert_header(&backend, 1, block0, Default::default(), [2; 32].into());
+			assert_eq!(backend.blockchain().leaves().unwrap(), vec![block1_a, block1_b, block1_c]);
 
-		assert_eq!(backend.blockchain().leaves().unwrap(), vec![block1_a, block1_b, block1_c]);
+			let block2_a = insert_header(&backend, 2, block1_a, Default::default(), Default::default());
+			let block2_b = insert_header(&backend, 2, block1_b, Default::default(), Default::default());
+			let block2_c = insert_header(&backend, 2, block1_b, Default::default(), [1; 32].into());
 
-		let block2_a = insert_header(&backend, 2, block1_a, Default::default(), Default::default());
-		let block2_b = insert_header(&backend, 2, block1_b, Default::default(), Default::default());
-		let block2_c = insert_header(&backend, 2, block1_b, Default::default(), [1; 32].into());
+			assert_eq!(backend.blockchain().leaves().unwrap(), vec![block2_a, block2_b, block2_c, block1_c]);
 
-		assert_eq!(backend.blockchain().leaves().unwrap(), vec![block2_a, block2_b, block2_c, block1_c]);
+			backend.finalize_block(BlockId::hash(block1_a), None).unwrap();
+			backend.finalize_block(BlockId::hash(block2_a), None).unwrap();
 
-		backend.finalize_block(BlockId::hash(block1_a), None).unwrap();
-		backend.finalize_block(BlockId::hash(block2_a), None).unwrap();
+			// leaves at same height stay. Leaves at lower heights pruned.
+			assert_eq!(backend.blockchain().leaves().unwrap(), vec![block2_a, block2_b, block2_c]);
+		});
+	}
 
-		// leaves at same height stay. Leaves at lower heights pruned.
-		assert_eq!(backend.blockchain().leaves().unwrap(), vec![block2_a, block2_b, block2_c]);
+	#[test]
+	fn offchain_local_storage_of_abandoned_forks_is_removed_in_archive_mode() {
+		for_each_test_database(|| {
+			use client::backend::OffchainStorage;
+
+			let backend = Backend::<Block>::new(DatabaseSettings {
+				state_cache_size: 16777216,
+				state_cache_child_ratio: Some((50, 100)),
+				pruning: PruningMode::ArchiveAll,
+				source: DatabaseSettingsSrc::Custom(crate::utils::new_test_database()),
+				storage_index: false,
+				blocks_pruning: BlocksPruning::KeepAll,
+			}, 10).unwrap();
+			let block0 = insert_header(&backend, 0, Default::default(), Default::default(), Default::default());
+			let block1_b = insert_header(&backend, 1, block0, Default::default(), [1; 32].into());
+			let block2_b = insert_header(&backend, 2, block1_b, Default::default(), Default::default());
+			let block1_a = insert_header(&backend, 1, block0, Default::default(), Default::default());
+
+			let mut storage = backend.offchain_storage().unwrap();
+			storage.set_local(block1_a.as_ref(), b"key", b"a");
+			storage.set_local(block1_b.as_ref(), b"key", b"b");
+			storage.set_local(block2_b.as_ref(), b"other", b"b");
+
+			backend.finalize_block(BlockId::hash(block1_a), None).unwrap();
+
+			assert_eq!(storage.get_local(block1_a.as_ref(), b"key"), Some(b"a".to_vec()));
+			assert_eq!(storage.get_local(block2_b.as_ref(), b"other"), None);
+			let db = &backend.storage.db;
+			assert_eq!(db.iter_from_prefix(columns::OFFCHAIN, b"local/").count(), 0);
+			assert_eq!(db.iter_from_prefix(columns::OFFCHAIN, b"local_parent/").count(), 0);
+			assert_eq!(db.iter_from_prefix(columns::OFFCHAIN, b"local_children/").count(), 0);
+
+			// the offchain worker of the finalized block may still write after its finalization.
+			storage.set_local(block1_a.as_ref(), b"late", b"a");
+			assert_eq!(storage.get_local(block1_a.as_ref(), b"late"), Some(b"a".to_vec()));
+			assert_eq!(db.iter_from_prefix(columns::OFFCHAIN, b"local/").count(), 0);
+		});
 	}
 
 	#[test]
 	fn test_aux() {
-		let backend: Backend<test_client::runtime::Block> = Backend::new_test(0, 0);
-		assert!(backend.get_aux(b"test").unwrap().is_none());
-		backend.insert_aux(&[(&b"test"[..], &b"hello"[..])], &[]).unwrap();
-		assert_eq!(b"hello", &backend.get_aux(b"test").unwrap().unwrap()[..]);
-		backend.insert_aux(&[], &[&b"test"[..]]).unwrap();
-		assert!(backend.get_aux(b"test").unwrap().is_none());
+		for_each_test_database(|| {
+			let backend: Backend<test_client::runtime::Block> = Backend::new_test(0, 0);
+			assert!(backend.get_aux(b"test").unwrap().is_none());
+			backend.insert_aux(&[(&b"test"[..], &b"hello"[..])], &[]).unwrap();
+			assert_eq!(b"hello", &backend.get_aux(b"test").unwrap().unwrap()[..]);
+			backend.insert_aux(&[], &[&b"test"[..]]).unwrap();
+			assert!(backend.get_aux(b"test").unwrap().is_none());
+		});
 	}
 
 	#[test]
 	fn test_finalize_block_with_justification() {
-		use client::blockchain::{Backend as BlockChainBackend};
+		for_each_test_database(|| {
+			use client::blockchain::{Backend as BlockChainBackend};
 
-		let backend = Backend::<Block>::new_test(10, 10);
+			let backend = Backend::<Block>::new_test(10, 10);
 
-		let block0 = insert_header(&backend, 0, Default::default(), Default::default(), Default::default());
-		let _ = insert_header(&backend, 1, block0, Default::default(), Default::default());
+			let block0 = insert_header(&backend, 0, Default::default(), Default::default(), Default::default());
+			let _ = insert_header(&backend, 1, block0, Default::default(), Default::default());
 
-		let justification = Some(vec![1, 2, 3]);
-		backend.finalize_block(BlockId::Number(1), justification.clone()).unwrap();
+			let justification = Some(vec![1, 2, 3]);
+			backend.finalize_block(BlockId::Number(1), justification.clone()).unwrap();
 
-		assert_eq!(
-			backend.blockchain().justification(BlockId::Number(1)).unwrap(),
-			justification,
-		);
+			assert_eq!(
+				backend.blockchain().justification(BlockId::Number(1)).unwrap(),
+				justification,
+			);
+		});
 	}
 
 	#[test]
 	fn test_finalize_multiple_blocks_in_single_op() {
-		let backend = Backend::<Block>::new_test(10, 10);
-
-		let block0 = insert_header(&backend, 0, Default::default(), Default::default(), Default::default());
-		let block1 = insert_header(&backend, 1, block0, Default::default(), Default::default());
-		let block2 = insert_header(&backend, 2, block1, Default::default(), Default::default());
-		{
-			let mut op = backend.begin_operation().unwrap();
-			backend.begin_state_operation(&mut op, BlockId::Hash(block0)).unwrap();
-			op.mark_finalized(BlockId::Hash(block1), None).unwrap();
-			op.mark_finalized(BlockId::Hash(block2), None).unwrap();
-			backend.commit_operation(op).unwrap();
-		}
+		for_each_test_database(|| {
+			let backend = Backend::<Block>::new_test(10, 10);
+
+			let block0 = insert_header(&backend, 0, Default::default(), Default::default(), Default::default());
+			let block1 = insert_header(&backend, 1, block0, Default::default(), Default::default());
+			let block2 = insert_header(&backend, 2, block1, Default::default(), Default::default());
+			{
+				let mut op = backend.begin_operation().unwrap();
+				backend.begin_state_operation(&mut op, BlockId::Hash(block0)).unwrap();
+				op.mark_finalized(BlockId::Hash(block1), None).unwrap();
+				op.mark_finalized(BlockId::Hash(block2), None).unwrap();
+				backend.commit_operation(op).unwrap();
+			}
+		});
 	}
 
 	#[test]
 	fn test_finalize_non_sequential() {
-		let backend = Backend::<Block>::new_test(10, 10);
-
-		let block0 = insert_header(&backend, 0, Default::default(), Default::default(), Default::default());
-		let block1 = insert_header(&backend, 1, block0, Default::default(), Default::default());
-		let block2 = insert_header(&backend, 2, block1, Default::default(), Default::default());
-		{
-			let mut op = backend.begin_operation().unwrap();
-			backend.begin_state_operation(&mut op, BlockId::Hash(block0)).unwrap();
-			op.mark_finalized(BlockId::Hash(block2), None).unwrap();
-			backend.commit_operation(op).unwrap_err();
-		}
+		for_each_test_database(|| {
+			let backend = Backend::<Block>::new_test(10, 10);
+
+			let block0 = insert_header(&backend, 0, Default::default(), Default::default(), Default::default());
+			let block1 = insert_header(&backend, 1, block0, Default::default(), Default::default());
+			let block2 = insert_header(&backend, 2, block1, Default::default(), Default::default());
+			{
+				let mut op = backend.begin_operation().unwrap();
+				backend.begin_state_operation(&mut op, BlockId::Hash(block0)).unwrap();
+				op.mark_finalized(BlockId::Hash(block2), None).unwrap();
+				backend.commit_operation(op).unwrap_err();
+			}
+		});
 	}
 }
@@ -14,13 +14,13 @@
 // You should have received a copy of the GNU General Public License
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
-//! RocksDB-based light client blockchain storage.
+//! Database-based light client blockchain storage.
 
 use std::{sync::Arc, collections::HashMap};
 use std::convert::TryInto;
 use parking_lot::RwLock;
 
-use kvdb::{KeyValueDB, DBTransaction};
+use database::{Database, Transaction};
 
 use client::backend::{AuxStore, NewBlockState};
 use client::blockchain::{
@@ -39,6 +39,7 @@ use header_metadata::{CachedHeaderMetadata, HeaderMetadata, HeaderMetadataCache}
 use crate::cache::{DbCacheSync, DbCache, ComplexBlockId, EntryType as CacheEntryType};
 use crate::utils::{self, meta_keys, Meta, db_err, read_db, block_id_to_lookup_key, read_meta};
 use crate::DatabaseSettings;
+use crate::check::{self, BlockCheckReport, CheckReport, Issue};
 use log::{trace, warn, debug};
 
 pub(crate) mod columns {
@@ -58,7 +59,7 @@ const CHANGES_TRIE_CHT_PREFIX: u8 = 1;
 /// Light blockchain storage. Stores most recent headers + CHTs for older headers.
 /// Locks order: meta, cache.
 pub struct LightStorage<Block: BlockT> {
-	db: Arc<dyn KeyValueDB>,
+	db: Arc<dyn Database>,
 	meta: RwLock<Meta<NumberFor<Block>, Block::Hash>>,
 	cache: Arc<DbCacheSync<Block>>,
 	header_metadata_cache: HeaderMetadataCache<Block>,
@@ -71,20 +72,16 @@ impl<Block> LightStorage<Block>
 	/// Create new storage with given settings.
 	pub fn new(config: DatabaseSettings) -> ClientResult<Self> {
 		let db = crate::utils::open_database(&config, columns::META, "light")?;
-		Self::from_kvdb(db as Arc<_>)
+		Self::from_database(db)
 	}
 
 	/// Create new memory-backed `LightStorage` for tests.
 	#[cfg(any(test, feature = "test-helpers"))]
 	pub fn new_test() -> Self {
-		use utils::NUM_COLUMNS;
-
-		let db = Arc::new(::kvdb_memorydb::create(NUM_COLUMNS));
-
-		Self::from_kvdb(db as Arc<_>).expect("failed to create test-db")
+		Self::from_database(utils::new_test_database()).expect("failed to create test-db")
 	}
 
-	fn from_kvdb(db: Arc<dyn KeyValueDB>) -> ClientResult<Self> {
+	fn from_database(db: Arc<dyn Database>) -> ClientResult<Self> {
 		let meta = read_meta::<Block>(&*db, columns::META, columns::HEADER)?;
 		let cache = DbCache::new(
 			db.clone(),
@@ -103,6 +100,41 @@ impl<Block> LightStorage<Block>
 		})
 	}
 
+	/// Check the integrity of the database.
+	///
+	/// Headers that have been replaced with a CHT are not expected to be found.
+	pub fn check_database(&self) -> ClientResult<BlockCheckReport<Block>> {
+		let meta = read_meta::<Block>(&*self.db, columns::META, columns::HEADER)?;
+		let mut report = CheckReport::default();
+
+		check::check_headers::<Block>(
+			&*self.db,
+			columns::KEY_LOOKUP,
+			columns::HEADER,
+			&mut report,
+			|_, _, _| Ok(()),
+		)?;
+		check::check_canonical_chain::<Block>(
+			&*self.db,
+			columns::KEY_LOOKUP,
+			columns::HEADER,
+			&meta,
+			true,
+			&mut report,
+		)?;
+		let broken_caches = crate::cache::check_caches::<Block>(
+			&self.db,
+			columns::KEY_LOOKUP,
+			columns::HEADER,
+			columns::CACHE,
+		)?;
+		for (name, at) in broken_caches {
+			report.issues.push(Issue::BrokenCacheEntry(name, at));
+		}
+
+		Ok(report)
+	}
+
 	#[cfg(test)]
 	pub(crate) fn cache(&self) -> &DbCacheSync<Block> {
 		&self.cache
@@ -226,7 +258,7 @@ impl<Block: BlockT> LightStorage<Block> {
 	/// to be best, `route_to` should equal to `best_to`.
 	fn set_head_with_transaction(
 		&self,
-		transaction: &mut DBTransaction,
+		transaction: &mut Transaction,
 		route_to: Block::Hash,
 		best_to: (NumberFor<Block>, Block::Hash),
 	) -> ClientResult<()> {
@@ -276,7 +308,7 @@ impl<Block: BlockT> LightStorage<Block> {
 	// Note that a block is finalized. Only call with child of last finalized block.
 	fn note_finalized(
 		&self,
-		transaction: &mut DBTransaction,
+		transaction: &mut Transaction,
 		header: &Block::Header,
 		hash: Block::Hash,
 	) -> ClientResult<()> {
@@ -382,18 +414,18 @@ impl<Block> AuxStore for LightStorage<Block>
 		I: IntoIterator<Item=&'a(&'c [u8], &'c [u8])>,
 		D: IntoIterator<Item=&'a &'b [u8]>,
 	>(&self, insert: I, delete: D) -> ClientResult<()> {
-		let mut transaction = DBTransaction::new();
+		let mut transaction = Transaction::new();
 		for (k, v) in insert {
 			transaction.put(columns::AUX, k, v);
 		}
 		for k in delete {
 			transaction.delete(columns::AUX, k);
 		}
-		self.db.write(transaction).map_err(db_err)
+		self.db.commit(transaction).map_err(db_err)
 	}
 
 	fn get_aux(&self, key: &[u8]) -> ClientResult<Option<Vec<u8>>> {
-		self.db.get(columns::AUX, key).map(|r| r.map(|v| v.to_vec())).map_err(db_err)
+		self.db.get(columns::AUX, key).map_err(db_err)
 	}
 }
 
@@ -407,7 +439,7 @@ impl<Block> LightBlockchainStorage<Block> for LightStorage<Block>
 		leaf_state: NewBlockState,
 		aux_ops: Vec<(Vec<u8>, Option<Vec<u8>>)>,
 	) -> ClientResult<()> {
-		let mut transaction = DBTransaction::new();
+		let mut transaction = Transaction::new();
 
 		let hash = header.hash();
 		let number = *header.number();
@@ -473,7 +505,7 @@ impl<Block> LightBlockchainStorage<Block> for LightStorage<Block>
 				.into_ops();
 
 			debug!("Light DB Commit {:?} ({})", hash, number);
-			self.db.write(transaction).map_err(db_err)?;
+			self.db.commit(transaction).map_err(db_err)?;
 			cache.commit(cache_ops);
 		}
 
@@ -487,9 +519,9 @@ impl<Block> LightBlockchainStorage<Block> for LightStorage<Block>
 			let hash = header.hash();
 			let number = header.number();
 
-			let mut transaction = DBTransaction::new();
+			let mut transaction = Transaction::new();
 			self.set_head_with_transaction(&mut transaction, hash.clone(), (number.clone(), hash.clone()))?;
-			self.db.write(transaction).map_err(db_err)?;
+			self.db.commit(transaction).map_err(db_err)?;
 			self.update_meta(hash, header.number().clone(), true, false);
 			Ok(())
 		} else {
@@ -515,7 +547,7 @@ impl<Block> LightBlockchainStorage<Block> for LightStorage<Block>
 
 	fn finalize_header(&self, id: BlockId<Block>) -> ClientResult<()> {
 		if let Some(header) = self.header(id)? {
-			let mut transaction = DBTransaction::new();
+			let mut transaction = Transaction::new();
 			let hash = header.hash();
 			let number = *header.number();
 			self.note_finalized(&mut transaction, &header, hash.clone())?;
@@ -528,7 +560,7 @@ impl<Block> LightBlockchainStorage<Block> for LightStorage<Block>
 					)?
 					.into_ops();
 
-				self.db.write(transaction).map_err(db_err)?;
+				self.db.commit(transaction).map_err(db_err)?;
 				cache.commit(cache_ops);
 			}
 			self.update_meta(hash, header.number().clone(), false, true);
@@ -562,6 +594,7 @@ pub(crate) mod tests {
 	use sr_primitives::testing::{H256 as Hash, Header, Block as RawBlock, ExtrinsicWrapper};
 	use header_metadata::{lowest_common_ancestor, tree_route};
 	use super::*;
+	use crate::utils::for_each_test_database;
 
 	type Block = RawBlock<ExtrinsicWrapper<u32>>;
 	type AuthorityId = primitives::ed25519::Public;
@@ -623,514 +656,546 @@ pub(crate) mod tests {
 
 	#[test]
 	fn returns_known_header() {
-		let db = LightStorage::new_test();
-		let known_hash = insert_block(&db, HashMap::new(), || default_header(&Default::default(), 0));
-		let header_by_hash = db.header(BlockId::Hash(known_hash)).unwrap().unwrap();
-		let header_by_number = db.header(BlockId::Number(0)).unwrap().unwrap();
-		assert_eq!(header_by_hash, header_by_number);
+		for_each_test_database(|| {
+			let db = LightStorage::new_test();
+			let known_hash = insert_block(&db, HashMap::new(), || default_header(&Default::default(), 0));
+			let header_by_hash = db.header(BlockId::Hash(known_hash)).unwrap().unwrap();
+			let header_by_number = db.header(BlockId::Number(0)).unwrap().unwrap();
+			assert_eq!(header_by_hash, header_by_number);
+		});
 	}
 
 	#[test]
 	fn does_not_return_unknown_header() {
-		let db = LightStorage::<Block>::new_test();
-		assert!(db.header(BlockId::Hash(Hash::from_low_u64_be(1))).unwrap().is_none());
-		assert!(db.header(BlockId::Number(0)).unwrap().is_none());
+		for_each_test_database(|| {
+			let db = LightStorage::<Block>::new_test();
+			assert!(db.header(BlockId::Hash(Hash::from_low_u64_be(1))).unwrap().is_none());
+			assert!(db.header(BlockId::Number(0)).unwrap().is_none());
+		});
 	}
 
 	#[test]
 	fn returns_info() {
-		let db = LightStorage::new_test();
-		let genesis_hash = insert_block(&db, HashMap::new(), || default_header(&Default::default(), 0));
-		let info = db.info();
-		assert_eq!(info.best_hash, genesis_hash);
-		assert_eq!(info.best_number, 0);
-		assert_eq!(info.genesis_hash, genesis_hash);
-		let best_hash = insert_block(&db, HashMap::new(), || default_header(&genesis_hash, 1));
-		let info = db.info();
-		assert_eq!(info.best_hash, best_hash);
-		assert_eq!(info.best_number, 1);
-		assert_eq!(info.genesis_hash, genesis_hash);
+		for_each_test_database(|| {
+			let db = LightStorage::new_test();
+			let genesis_hash = insert_block(&db, HashMap::new(), || default_header(&Default::default(), 0));
+			let info = db.info();
+			assert_eq!(info.best_hash, genesis_hash);
+			assert_eq!(info.best_number, 0);
+			assert_eq!(info.genesis_hash, genesis_hash);
+			let best_hash = insert_block(&db, HashMap::new(), || default_header(&genesis_hash, 1));
+			let info = db.info();
+			assert_eq!(info.best_hash, best_hash);
+			assert_eq!(info.best_number, 1);
+			assert_eq!(info.genesis_hash, genesis_hash);
+		});
 	}
 
 	#[test]
 	fn returns_block_status() {
-		let db = LightStorage::new_test();
-		let genesis_hash = insert_block(&db, HashMap::new(), || default_header(&Default::default(), 0));
-		assert_eq!(db.status(BlockId::Hash(genesis_hash)).unwrap(), BlockStatus::InChain);
-		assert_eq!(db.status(BlockId::Number(0)).unwrap(), BlockStatus::InChain);
-		assert_eq!(db.status(BlockId::Hash(Hash::from_low_u64_be(1))).unwrap(), BlockStatus::Unknown);
-		assert_eq!(db.status(BlockId::Number(1)).unwrap(), BlockStatus::Unknown);
+		for_each_test_database(|| {
+			let db = LightStorage::new_test();
+			let genesis_hash = insert_block(&db, HashMap::new(), || default_header(&Default::default(), 0));
+			assert_eq!(db.status(BlockId::Hash(genesis_hash)).unwrap(), BlockStatus::InChain);
+			assert_eq!(db.status(BlockId::Number(0)).unwrap(), BlockStatus::InChain);
+			assert_eq!(db.status(BlockId::Hash(Hash::from_low_u64_be(1))).unwrap(), BlockStatus::Unknown);
+			assert_eq!(db.status(BlockId::Number(1)).unwrap(), BlockStatus::Unknown);
+		});
 	}
 
 	#[test]
 	fn returns_block_hash() {
-		let db = LightStorage::new_test();
-		let genesis_hash = insert_block(&db, HashMap::new(), || default_header(&Default::default(), 0));
-		assert_eq!(db.hash(0).unwrap(), Some(genesis_hash));
-		assert_eq!(db.hash(1).unwrap(), None);
+		for_each_test_database(|| {
+			let db = LightStorage::new_test();
+			let genesis_hash = insert_block(&db, HashMap::new(), || default_header(&Default::default(), 0));
+			assert_eq!(db.hash(0).unwrap(), Some(genesis_hash));
+			assert_eq!(db.hash(1).unwrap(), None);
+		});
 	}
 
 	#[test]
 	fn import_header_works() {
-		let db = LightStorage::new_test();
+		for_each_test_database(|| {
+			let db = LightStorage::new_test();
 
-		let genesis_hash = insert_block(&db, HashMap::new(), || default_header(&Default::default(), 0));
-		assert_eq!(db.db.iter(columns::HEADER).count(), 1);
-		assert_eq!(db.db.iter(columns::KEY_LOOKUP).count(), 2);
+			let genesis_hash = insert_block(&db, HashMap::new(), || default_header(&Default::default(), 0));
+			assert_eq!(db.db.iter(columns::HEADER).count(), 1);
+			assert_eq!(db.db.iter(columns::KEY_LOOKUP).count(), 2);
 
-		let _ = insert_block(&db, HashMap::new(), || default_header(&genesis_hash, 1));
-		assert_eq!(db.db.iter(columns::HEADER).count(), 2);
-		assert_eq!(db.db.iter(columns::KEY_LOOKUP).count(), 4);
+			let _ = insert_block(&db, HashMap::new(), || default_header(&genesis_hash, 1));
+			assert_eq!(db.db.iter(columns::HEADER).count(), 2);
+			assert_eq!(db.db.iter(columns::KEY_LOOKUP).count(), 4);
+		});
 	}
 
 	#[test]
 	fn finalized_ancient_headers_are_replaced_with_cht() {
-		fn insert_headers<F: Fn(&Hash, u64) -> Header>(header_producer: F) -> LightStorage<Block> {
-			let db = LightStorage::new_test();
-			let cht_size: u64 = cht::size();
-			let ucht_size: usize = cht_size as _;
+		for_each_test_database(|| {
+			fn insert_headers<F: Fn(&Hash, u64) -> Header>(header_producer: F) -> LightStorage<Block> {
+				let db = LightStorage::new_test();
+				let cht_size: u64 = cht::size();
+				let ucht_size: usize = cht_size as _;
 
-			// insert genesis block header (never pruned)
-			let mut prev_hash = insert_final_block(&db, HashMap::new(), || header_producer(&Default::default(), 0));
+				// insert genesis block header (never pruned)
+				let mut prev_hash = insert_final_block(&db, HashMap::new(), || header_producer(&Default::default(), 0));
 
-			// insert SIZE blocks && ensure that nothing is pruned
+				// insert SIZE blocks && ensure that nothing is pruned
 
-			for number in 0..cht::size() {
-				prev_hash = insert_block(&db, HashMap::new(), || header_producer(&prev_hash, 1 + number));
-			}
-			assert_eq!(db.db.iter(columns::HEADER).count(), 1 + ucht_size);
-			assert_eq!(db.db.iter(columns::CHT).count(), 0);
+				for number in 0..cht::size() {
+					prev_hash = insert_block(&db, HashMap::new(), || header_producer(&prev_hash, 1 + number));
+				}
+				assert_eq!(db.db.iter(columns::HEADER).count(), 1 + ucht_size);
+				assert_eq!(db.db.iter(columns::CHT).count(), 0);
+
+				// insert next SIZE blocks && ensure that nothing is pruned
+				for number in 0..(cht_size as _) {
+					prev_hash = insert_block(
+						&db,
+						HashMap::new(),
+						|| header_producer(&prev_hash, 1 + cht_size + number),
+					);
+				}
+				assert_eq!(db.db.iter(columns::HEADER).count(), 1 + ucht_size + ucht_size);
+				assert_eq!(db.db.iter(columns::CHT).count(), 0);
 
-			// insert next SIZE blocks && ensure that nothing is pruned
-			for number in 0..(cht_size as _) {
+				// insert block #{2 * cht::size() + 1} && check that new CHT is created + headers of this CHT are pruned
+				// nothing is yet finalized, so nothing is pruned.
 				prev_hash = insert_block(
 					&db,
 					HashMap::new(),
-					|| header_producer(&prev_hash, 1 + cht_size + number),
+					|| header_producer(&prev_hash, 1 + cht_size + cht_size),
 				);
-			}
-			assert_eq!(db.db.iter(columns::HEADER).count(), 1 + ucht_size + ucht_size);
-			assert_eq!(db.db.iter(columns::CHT).count(), 0);
-
-			// insert block #{2 * cht::size() + 1} && check that new CHT is created + headers of this CHT are pruned
-			// nothing is yet finalized, so nothing is pruned.
-			prev_hash = insert_block(
-				&db,
-				HashMap::new(),
-				|| header_producer(&prev_hash, 1 + cht_size + cht_size),
-			);
-			assert_eq!(db.db.iter(columns::HEADER).count(), 2 + ucht_size + ucht_size);
-			assert_eq!(db.db.iter(columns::CHT).count(), 0);
+				assert_eq!(db.db.iter(columns::HEADER).count(), 2 + ucht_size + ucht_size);
+				assert_eq!(db.db.iter(columns::CHT).count(), 0);
 
-			// now finalize the block.
-			for i in (0..(ucht_size + ucht_size)).map(|i| i + 1) {
-				db.finalize_header(BlockId::Number(i as _)).unwrap();
+				// now finalize the block.
+				for i in (0..(ucht_size + ucht_size)).map(|i| i + 1) {
+					db.finalize_header(BlockId::Number(i as _)).unwrap();
+				}
+				db.finalize_header(BlockId::Hash(prev_hash)).unwrap();
+				db
 			}
-			db.finalize_header(BlockId::Hash(prev_hash)).unwrap();
-			db
-		}
 
-		// when headers are created without changes tries roots
-		let db = insert_headers(default_header);
-		let cht_size: u64 = cht::size();
-		assert_eq!(db.db.iter(columns::HEADER).count(), (1 + cht_size + 1) as usize);
-		assert_eq!(db.db.iter(columns::KEY_LOOKUP).count(), (2 * (1 + cht_size + 1)) as usize);
-		assert_eq!(db.db.iter(columns::CHT).count(), 1);
-		assert!((0..cht_size as _).all(|i| db.header(BlockId::Number(1 + i)).unwrap().is_none()));
-		assert!(db.header_cht_root(cht_size, cht_size / 2).is_ok());
-		assert!(db.header_cht_root(cht_size, cht_size + cht_size / 2).is_err());
-		assert!(db.changes_trie_cht_root(cht_size, cht_size / 2).is_err());
-		assert!(db.changes_trie_cht_root(cht_size, cht_size + cht_size / 2).is_err());
-
-		// when headers are created with changes tries roots
-		let db = insert_headers(header_with_changes_trie);
-		assert_eq!(db.db.iter(columns::HEADER).count(), (1 + cht_size + 1) as usize);
-		assert_eq!(db.db.iter(columns::CHT).count(), 2);
-		assert!((0..cht_size as _).all(|i| db.header(BlockId::Number(1 + i)).unwrap().is_none()));
-		assert!(db.header_cht_root(cht_size, cht_size / 2).is_ok());
-		assert!(db.header_cht_root(cht_size, cht_size + cht_size / 2).is_err());
-		assert!(db.changes_trie_cht_root(cht_size, cht_size / 2).is_ok());
-		assert!(db.changes_trie_cht_root(cht_size, cht_size + cht_size / 2).is_err());
+			// when headers are created without changes tries roots
+			let db = insert_headers(default_header);
+			let cht_size: u64 = cht::size();
+			assert_eq!(db.db.iter(columns::HEADER).count(), (1 + cht_size + 1) as usize);
+			assert_eq!(db.db.iter(columns::KEY_LOOKUP).count(), (2 * (1 + cht_size + 1)) as usize);
+			assert_eq!(db.db.iter(columns::CHT).count(), 1);
+			assert!((0..cht_size as _).all(|i| db.header(BlockId::Number(1 + i)).unwrap().is_none()));
+			assert!(db.header_cht_root(cht_size, cht_size / 2).is_ok());
+			assert!(db.header_cht_root(cht_size, cht_size + cht_size / 2).is_err());
+			assert!(db.changes_trie_cht_root(cht_size, cht_size / 2).is_err());
+			assert!(db.changes_trie_cht_root(cht_size, cht_size + cht_size / 2).is_err());
+
+			// when headers are created with changes tries roots
+			let db = insert_headers(header_with_changes_trie);
+			assert_eq!(db.db.iter(columns::HEADER).count(), (1 + cht_size + 1) as usize);
+			assert_eq!(db.db.iter(columns::CHT).count(), 2);
+			assert!((0..cht_size as _).all(|i| db.header(BlockId::Number(1 + i)).unwrap().is_none()));
+			assert!(db.header_cht_root(cht_size, cht_size / 2).is_ok());
+			assert!(db.header_cht_root(cht_size, cht_size + cht_size / 2).is_err());
+			assert!(db.changes_trie_cht_root(cht_size, cht_size / 2).is_ok());
+			assert!(db.changes_trie_cht_root(cht_size, cht_size + cht_size / 2).is_err());
+		});
 	}
 
 	#[test]
 	fn get_cht_fails_for_genesis_block() {
-		assert!(LightStorage::<Block>::new_test().header_cht_root(cht::size(), 0).is_err());
+		for_each_test_database(|| {
+			assert!(LightStorage::<Block>::new_test().header_cht_root(cht::size(), 0).is_err());
+		});
 	}
 
 	#[test]
 	fn get_cht_fails_for_non_existant_cht() {
-		let cht_size: u64 = cht::size();
-		assert!(LightStorage::<Block>::new_test().header_cht_root(cht_size, cht_size / 2).is_err());
+		for_each_test_database(|| {
+			let cht_size: u64 = cht::size();
+			assert!(LightStorage::<Block>::new_test().header_cht_root(cht_size, cht_size / 2).is_err());
+		});
 	}
 
 	#[test]
 	fn get_cht_works() {
-		let db = LightStorage::new_test();
-
-		// insert 1 + SIZE + SIZE + 1 blocks so that CHT#0 is created
-		let mut prev_hash = insert_final_block(&db, HashMap::new(), || header_with_changes_trie(&Default::default(), 0));
-		let cht_size: u64 = cht::size();
-		let ucht_size: usize = cht_size as _;
-		for i in 1..1 + ucht_size + ucht_size + 1 {
-			prev_hash = insert_block(&db, HashMap::new(), || header_with_changes_trie(&prev_hash, i as u64));
-			db.finalize_header(BlockId::Hash(prev_hash)).unwrap();
-		}
+		for_each_test_database(|| {
+			let db = LightStorage::new_test();
 
-		let cht_root_1 = db.header_cht_root(cht_size, cht::start_number(cht_size, 0)).unwrap();
-		let cht_root_2 = db.header_cht_root(cht_size, cht::start_number(cht_size, 0) + cht_size / 2).unwrap();
-		let cht_root_3 = db.header_cht_root(cht_size, cht::end_number(cht_size, 0)).unwrap();
-		assert_eq!(cht_root_1, cht_root_2);
-		assert_eq!(cht_root_2, cht_root_3);
-
-		let cht_root_1 = db.changes_trie_cht_root(cht_size, cht::start_number(cht_size, 0)).unwrap();
-		let cht_root_2 = db.changes_trie_cht_root(cht_size, cht::start_number(cht_size, 0) + cht_size / 2).unwrap();
-		let cht_root_3 = db.changes_trie_cht_root(cht_size, cht::end_number(cht_size, 0)).unwrap();
-		assert_eq!(cht_root_1, cht_root_2);
-		assert_eq!(cht_root_2, cht_root_3);
+			// insert 1 + SIZE + SIZE + 1 blocks so that CHT#0 is created
+			let mut prev_hash = insert_final_block(&db, HashMap::new(), || header_with_changes_trie(&Default::default(), 0));
+			let cht_size: u64 = cht::size();
+			let ucht_size: usize = cht_size as _;
+			for i in 1..1 + ucht_size + ucht_size + 1 {
+				prev_hash = insert_block(&db, HashMap::new(), || header_with_changes_trie(&prev_hash, i as u64));
+				db.finalize_header(BlockId::Hash(prev_hash)).unwrap();
+			}
+
+			let cht_root_1 = db.header_cht_root(cht_size, cht::start_number(cht_size, 0)).unwrap();
+			let cht_root_2 = db.header_cht_root(cht_size, cht::start_number(cht_size, 0) + cht_size / 2).unwrap();
+			let cht_root_3 = db.header_cht_root(cht_size, cht::end_number(cht_size, 0)).unwrap();
+			assert_eq!(cht_root_1, cht_root_2);
+			assert_eq!(cht_root_2, cht_root_3);
+
+			let cht_root_1 = db.changes_trie_cht_root(cht_size, cht::start_number(cht_size, 0)).unwrap();
+			let cht_root_2 = db.changes_trie_cht_root(cht_size, cht::start_number(cht_size, 0) + cht_size / 2).unwrap();
+			let cht_root_3 = db.changes_trie_cht_root(cht_size, cht::end_number(cht_size, 0)).unwrap();
+			assert_eq!(cht_root_1, cht_root_2);
+			assert_eq!(cht_root_2, cht_root_3);
+		});
 	}
 
 	#[test]
 	fn tree_route_works() {
-		let db = LightStorage::new_test();
-		let block0 = insert_block(&db, HashMap::new(), || default_header(&Default::default(), 0));
+		for_each_test_database(|| {
+			let db = LightStorage::new_test();
+			let block0 = insert_block(&db, HashMap::new(), || default_header(&Default::default(), 0));
 
-		// fork from genesis: 3 prong.
-		let a1 = insert_block(&db, HashMap::new(), || default_header(&block0, 1));
-		let a2 = insert_block(&db, HashMap::new(), || default_header(&a1, 2));
-		let a3 = insert_block(&db, HashMap::new(), || default_header(&a2, 3));
+			// fork from genesis: 3 prong.
+			let a1 = insert_block(&db, HashMap::new(), || default_header(&block0, 1));
+			let a2 = insert_block(&db, HashMap::new(), || default_header(&a1, 2));
+			let a3 = insert_block(&db, HashMap::new(), || default_header(&a2, 3));
 
-		// fork from genesis: 2 prong.
-		let b1 = insert_block(&db, HashMap::new(), || header_with_extrinsics_root(&block0, 1, Hash::from([1; 32])));
-		let b2 = insert_block(&db, HashMap::new(), || default_header(&b1, 2));
+			// fork from genesis: 2 prong.
+			let b1 = insert_block(&db, HashMap::new(), || header_with_extrinsics_root(&block0, 1, Hash::from([1; 32])));
+			let b2 = insert_block(&db, HashMap::new(), || default_header(&b1, 2));
 
-		{
-			let tree_route = tree_route(&db, a3, b2).unwrap();
+			{
+				let tree_route = tree_route(&db, a3, b2).unwrap();
 
-			assert_eq!(tree_route.common_block().hash, block0);
-			assert_eq!(tree_route.retracted().iter().map(|r| r.hash).collect::<Vec<_>>(), vec![a3, a2, a1]);
-			assert_eq!(tree_route.enacted().iter().map(|r| r.hash).collect::<Vec<_>>(), vec![b1, b2]);
-		}
+				assert_eq!(tree_route.common_block().hash, block0);
+				assert_eq!(tree_route.retracted().iter().map(|r| r.hash).collect::<Vec<_>>(), vec![a3, a2, a1]);
+				assert_eq!(tree_route.enacted().iter().map(|r| r.hash).collect::<Vec<_>>(), vec![b1, b2]);
+			}
 
-		{
-			let tree_route = tree_route(&db, a1, a3).unwrap();
+			{
+				let tree_route = tree_route(&db, a1, a3).unwrap();
 
-			assert_eq!(tree_route.common_block().hash, a1);
-			assert!(tree_route.retracted().is_empty());
-			assert_eq!(tree_route.enacted().iter().map(|r| r.hash).collect::<Vec<_>>(), vec![a2, a3]);
-		}
+				assert_eq!(tree_route.common_block().hash, a1);
+				assert!(tree_route.retracted().is_empty());
+				assert_eq!(tree_route.enacted().iter().map(|r| r.hash).collect::<Vec<_>>(), vec![a2, a3]);
+			}
 
-		{
-			let tree_route = tree_route(&db, a3, a1).unwrap();
+			{
+				let tree_route = tree_route(&db, a3, a1).unwrap();
 
-			assert_eq!(tree_route.common_block().hash, a1);
-			assert_eq!(tree_route.retracted().iter().map(|r| r.hash).collect::<Vec<_>>(), vec![a3, a2]);
-			assert!(tree_route.enacted().is_empty());
-		}
+				assert_eq!(tree_route.common_block().hash, a1);
+				assert_eq!(tree_route.retracted().iter().map(|r| r.hash).collect::<Vec<_>>(), vec![a3, a2]);
+				assert!(tree_route.enacted().is_empty());
+			}
 
-		{
-			let tree_route = tree_route(&db, a2, a2).unwrap();
+			{
+				let tree_route = tree_route(&db, a2, a2).unwrap();
 
-			assert_eq!(tree_route.common_block().hash, a2);
-			assert!(tree_route.retracted().is_empty());
-			assert!(tree_route.enacted().is_empty());
-		}
+				assert_eq!(tree_route.common_block().hash, a2);
+				assert!(tree_route.retracted().is_empty());
+				assert!(tree_route.enacted().is_empty());
+			}
+		});
 	}
 
 	#[test]
 	fn lowest_common_ancestor_works() {
-		let db = LightStorage::new_test();
-		let block0 = insert_block(&db, HashMap::new(), || default_header(&Default::default(), 0));
+		for_each_test_database(|| {
+			let db = LightStorage::new_test();
+			let block0 = insert_block(&db, HashMap::new(), || default_header(&Default::default(), 0));
 
-		// fork from genesis: 3 prong.
-		let a1 = insert_block(&db, HashMap::new(), || default_header(&block0, 1));
-		let a2 = insert_block(&db, HashMap::new(), || default_header(&a1, 2));
-		let a3 = insert_block(&db, HashMap::new(), || default_header(&a2, 3));
+			// fork from genesis: 3 prong.
+			let a1 = insert_block(&db, HashMap::new(), || default_header(&block0, 1));
+			let a2 = insert_block(&db, HashMap::new(), || default_header(&a1, 2));
+			let a3 = insert_block(&db, HashMap::new(), || default_header(&a2, 3));
 
-		// fork from genesis: 2 prong.
-		let b1 = insert_block(&db, HashMap::new(), || header_with_extrinsics_root(&block0, 1, Hash::from([1; 32])));
-		let b2 = insert_block(&db, HashMap::new(), || default_header(&b1, 2));
+			// fork from genesis: 2 prong.
+			let b1 = insert_block(&db, HashMap::new(), || header_with_extrinsics_root(&block0, 1, Hash::from([1; 32])));
+			let b2 = insert_block(&db, HashMap::new(), || default_header(&b1, 2));
 
-		{
-			let lca = lowest_common_ancestor(&db, a3, b2).unwrap();
+			{
+				let lca = lowest_common_ancestor(&db, a3, b2).unwrap();
 
-			assert_eq!(lca.hash, block0);
-			assert_eq!(lca.number, 0);
-		}
+				assert_eq!(lca.hash, block0);
+				assert_eq!(lca.number, 0);
+			}
 
-		{
-			let lca = lowest_common_ancestor(&db, a1, a3).unwrap();
+			{
+				let lca = lowest_common_ancestor(&db, a1, a3).unwrap();
 
-			assert_eq!(lca.hash, a1);
-			assert_eq!(lca.number, 1);
-		}
+				assert_eq!(lca.hash, a1);
+				assert_eq!(lca.number, 1);
+			}
 
-		{
-			let lca = lowest_common_ancestor(&db, a3, a1).unwrap();
+			{
+				let lca = lowest_common_ancestor(&db, a3, a1).unwrap();
 
-			assert_eq!(lca.hash, a1);
-			assert_eq!(lca.number, 1);
-		}
+				assert_eq!(lca.hash, a1);
+				assert_eq!(lca.number, 1);
+			}
 
-		{
-			let lca = lowest_common_ancestor(&db, a2, a3).unwrap();
+			{
+				let lca = lowest_common_ancestor(&db, a2, a3).unwrap();
 
-			assert_eq!(lca.hash, a2);
-			assert_eq!(lca.number, 2);
-		}
+				assert_eq!(lca.hash, a2);
+				assert_eq!(lca.number, 2);
+			}
 
-		{
-			let lca = lowest_common_ancestor(&db, a2, a1).unwrap();
+			{
+				let lca = lowest_common_ancestor(&db, a2, a1).unwrap();
 
-			assert_eq!(lca.hash, a1);
-			assert_eq!(lca.number, 1);
-		}
+				assert_eq!(lca.hash, a1);
+				assert_eq!(lca.number, 1);
+			}
 
-		{
-			let lca = lowest_common_ancestor(&db, a2, a2).unwrap();
+			{
+				let lca = lowest_common_ancestor(&db, a2, a2).unwrap();
 
-			assert_eq!(lca.hash, a2);
-			assert_eq!(lca.number, 2);
-		}
+				assert_eq!(lca.hash, a2);
+				assert_eq!(lca.number, 2);
+			}
+		});
 	}
 
 	#[test]
 	fn authorities_are_cached() {
-		let db = LightStorage::new_test();
+		for_each_test_database(|| {
+			let db = LightStorage::new_test();
 
-		fn run_checks(db: &LightStorage<Block>, max: u64, checks: &[(u64, Option<Vec<AuthorityId>>)]) {
-			for (at, expected) in checks.iter().take_while(|(at, _)| *at <= max) {
-				let actual = get_authorities(db.cache(), BlockId::Number(*at));
-				assert_eq!(*expected, actual);
+			fn run_checks(db: &LightStorage<Block>, max: u64, checks: &[(u64, Option<Vec<AuthorityId>>)]) {
+				for (at, expected) in checks.iter().take_while(|(at, _)| *at <= max) {
+					let actual = get_authorities(db.cache(), BlockId::Number(*at));
+					assert_eq!(*expected, actual);
+				}
 			}
-		}
 
-		fn same_authorities() -> HashMap<well_known_cache_keys::Id, Vec<u8>> {
-			HashMap::new()
-		}
+			fn same_authorities() -> HashMap<well_known_cache_keys::Id, Vec<u8>> {
+				HashMap::new()
+			}
 
-		fn make_authorities(authorities: Vec<AuthorityId>) -> HashMap<well_known_cache_keys::Id, Vec<u8>> {
-			let mut map = HashMap::new();
-			map.insert(well_known_cache_keys::AUTHORITIES, authorities.encode());
-			map
-		}
+			fn make_authorities(authorities: Vec<AuthorityId>) -> HashMap<well_known_cache_keys::Id, Vec<u8>> {
+				let mut map = HashMap::new();
+				map.insert(well_known_cache_keys::AUTHORITIES, authorities.encode());
+				map
+			}
 
-		fn get_authorities(cache: &dyn BlockchainCache<Block>, at: BlockId<Block>) -> Option<Vec<AuthorityId>> {
-			cache.get_at(&well_known_cache_keys::AUTHORITIES, &at)
-				.and_then(|(_, _, val)| Decode::decode(&mut &val[..]).ok())
-		}
+			fn get_authorities(cache: &dyn BlockchainCache<Block>, at: BlockId<Block>) -> Option<Vec<AuthorityId>> {
+				cache.get_at(&well_known_cache_keys::AUTHORITIES, &at)
+					.and_then(|(_, _, val)| Decode::decode(&mut &val[..]).ok())
+			}
 
-		let auth1 = || AuthorityId::from_raw([1u8; 32]);
-		let auth2 = || AuthorityId::from_raw([2u8; 32]);
-		let auth3 = || AuthorityId::from_raw([3u8; 32]);
-		let auth4 = || AuthorityId::from_raw([4u8; 32]);
-		let auth5 = || AuthorityId::from_raw([5u8; 32]);
-		let auth6 = || AuthorityId::from_raw([6u8; 32]);
-
-		let (hash2, hash6) = {
-			// first few blocks are instantly finalized
-			// B0(None) -> B1(None) -> B2(1) -> B3(1) -> B4(1, 2) -> B5(1, 2) -> B6(1, 2)
-			let checks = vec![
-				(0, None),
-				(1, None),
-				(2, Some(vec![auth1()])),
-				(3, Some(vec![auth1()])),
-				(4, Some(vec![auth1(), auth2()])),
-				(5, Some(vec![auth1(), auth2()])),
-				(6, Some(vec![auth1(), auth2()])),
-			];
-
-			let hash0 = insert_final_block(&db, same_authorities(), || default_header(&Default::default(), 0));
-			run_checks(&db, 0, &checks);
-			let hash1 = insert_final_block(&db, same_authorities(), || default_header(&hash0, 1));
-			run_checks(&db, 1, &checks);
-			let hash2 = insert_final_block(&db, make_authorities(vec![auth1()]), || default_header(&hash1, 2));
-			run_checks(&db, 2, &checks);
-			let hash3 = insert_final_block(&db, make_authorities(vec![auth1()]), || default_header(&hash2, 3));
-			run_checks(&db, 3, &checks);
-			let hash4 = insert_final_block(&db, make_authorities(vec![auth1(), auth2()]), || default_header(&hash3, 4));
-			run_checks(&db, 4, &checks);
-			let hash5 = insert_final_block(&db, make_authorities(vec![auth1(), auth2()]), || default_header(&hash4, 5));
-			run_checks(&db, 5, &checks);
-			let hash6 = insert_final_block(&db, same_authorities(), || default_header(&hash5, 6));
-			run_checks(&db, 6, &checks);
-
-			(hash2, hash6)
-		};
+			let auth1 = || AuthorityId::from_raw([1u8; 32]);
+			let auth2 = || AuthorityId::from_raw([2u8; 32]);
+			let auth3 = || AuthorityId::from_raw([3u8; 32]);
+			let auth4 = || AuthorityId::from_raw([4u8; 32]);
+			let auth5 = || AuthorityId::from_raw([5u8; 32]);
+			let auth6 = || AuthorityId::from_raw([6u8; 32]);
+
+			let (hash2, hash6) = {
+				// first few blocks are instantly finalized
+				// B0(None) -> B1(None) -> B2(1) -> B3(1) -> B4(1, 2) -> B5(1, 2) -> B6(1, 2)
+				let checks = vec![
+					(0, None),
+					(1, None),
+					(2, Some(vec![auth1()])),
+					(3, Some(vec![auth1()])),
+					(4, Some(vec![auth1(), auth2()])),
+					(5, Some(vec![auth1(), auth2()])),
+					(6, Some(vec![auth1(), auth2()])),
+				];
+
+				let hash0 = insert_final_block(&db, same_authorities(), || default_header(&Default::default(), 0));
+				run_checks(&db, 0, &checks);
+				let hash1 = insert_final_block(&db, same_authorities(), || default_header(&hash0, 1));
+				run_checks(&db, 1, &checks);
+				let hash2 = insert_final_block(&db, make_authorities(vec![auth1()]), || default_header(&hash1, 2));
+				run_checks(&db, 2, &checks);
+				let hash3 = insert_final_block(&db, make_authorities(vec![auth1()]), || default_header(&hash2, 3));
+				run_checks(&db, 3, &checks);
+				let hash4 = insert_final_block(&db, make_authorities(vec![auth1(), auth2()]), || default_header(&hash3, 4));
+				run_checks(&db, 4, &checks);
+				let hash5 = insert_final_block(&db, make_authorities(vec![auth1(), auth2()]), || default_header(&hash4, 5));
+				run_checks(&db, 5, &checks);
+				let hash6 = insert_final_block(&db, same_authorities(), || default_header(&hash5, 6));
+				run_checks(&db, 6, &checks);
+
+				(hash2, hash6)
+			};
 
-		{
-			// some older non-best blocks are inserted
-			// ... -> B2(1) -> B2_1(1) -> B2_2(2)
-			// => the cache ignores all writes before best finalized block
-			let hash2_1 = insert_non_best_block(&db, make_authorities(vec![auth1()]), || default_header(&hash2, 3));
-			assert_eq!(None, get_authorities(db.cache(), BlockId::Hash(hash2_1)));
-			let hash2_2 = insert_non_best_block(&db, make_authorities(vec![auth1(), auth2()]), || default_header(&hash2_1, 4));
-			assert_eq!(None, get_authorities(db.cache(), BlockId::Hash(hash2_2)));
-		}
+			{
+				// some older non-best blocks are inserted
+				// ... -> B2(1) -> B2_1(1) -> B2_2(2)
+				// => the cache ignores all writes before best finalized block
+				let hash2_1 = insert_non_best_block(&db, make_authorities(vec![auth1()]), || default_header(&hash2, 3));
+				assert_eq!(None, get_authorities(db.cache(), BlockId::Hash(hash2_1)));
+				let hash2_2 = insert_non_best_block(&db, make_authorities(vec![auth1(), auth2()]), || default_header(&hash2_1, 4));
+				assert_eq!(None, get_authorities(db.cache(), BlockId::Hash(hash2_2)));
+			}
 
-		let (hash7, hash8, hash6_1, hash6_2, hash6_1_1, hash6_1_2) = {
-			// inserting non-finalized blocks
-			// B6(None) -> B7(3) -> B8(3)
-			//          \> B6_1(4) -> B6_2(4)
-			//                     \> B6_1_1(5)
-			//                     \> B6_1_2(6) -> B6_1_3(7)
+			let (hash7, hash8, hash6_1, hash6_2, hash6_1_1, hash6_1_2) = {
+				// inserting non-finalized blocks
+				// B6(None) -> B7(3) -> B8(3)
+				//          \> B6_1(4) -> B6_2(4)
+				//                     \> B6_1_1(5)
+				//                     \> B6_1_2(6) -> B6_1_3(7)
+
+				let hash7 = insert_block(&db, make_authorities(vec![auth3()]), || default_header(&hash6, 7));
+				assert_eq!(
+					get_authorities(db.cache(), BlockId::Hash(hash6)),
+					Some(vec![auth1(), auth2()]),
+				);
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash7)), Some(vec![auth3()]));
+				let hash8 = insert_block(&db, make_authorities(vec![auth3()]), || default_header(&hash7, 8));
+				assert_eq!(
+					get_authorities(db.cache(), BlockId::Hash(hash6)),
+					Some(vec![auth1(), auth2()]),
+				);
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash7)), Some(vec![auth3()]));
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash8)), Some(vec![auth3()]));
+				let hash6_1 = insert_block(&db, make_authorities(vec![auth4()]), || default_header(&hash6, 7));
+				assert_eq!(
+					get_authorities(db.cache(), BlockId::Hash(hash6)),
+					Some(vec![auth1(), auth2()]),
+				);
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash7)), Some(vec![auth3()]));
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash8)), Some(vec![auth3()]));
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1)), Some(vec![auth4()]));
+				let hash6_1_1 = insert_non_best_block(&db, make_authorities(vec![auth5()]), || default_header(&hash6_1, 8));
+				assert_eq!(
+					get_authorities(db.cache(), BlockId::Hash(hash6)),
+					Some(vec![auth1(), auth2()]),
+				);
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash7)), Some(vec![auth3()]));
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash8)), Some(vec![auth3()]));
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1)), Some(vec![auth4()]));
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1_1)), Some(vec![auth5()]));
+				let hash6_1_2 = insert_non_best_block(&db, make_authorities(vec![auth6()]), || default_header(&hash6_1, 8));
+				assert_eq!(
+					get_authorities(db.cache(), BlockId::Hash(hash6)),
+					Some(vec![auth1(), auth2()]),
+				);
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash7)), Some(vec![auth3()]));
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash8)), Some(vec![auth3()]));
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1)), Some(vec![auth4()]));
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1_1)), Some(vec![auth5()]));
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1_2)), Some(vec![auth6()]));
+				let hash6_2 = insert_block(&db, make_authorities(vec![auth4()]), || default_header(&hash6_1, 8));
+				assert_eq!(
+					get_authorities(db.cache(), BlockId::Hash(hash6)),
+					Some(vec![auth1(), auth2()]),
+				);
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash7)), Some(vec![auth3()]));
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash8)), Some(vec![auth3()]));
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1)), Some(vec![auth4()]));
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1_1)), Some(vec![auth5()]));
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1_2)), Some(vec![auth6()]));
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_2)), Some(vec![auth4()]));
 
-			let hash7 = insert_block(&db, make_authorities(vec![auth3()]), || default_header(&hash6, 7));
-			assert_eq!(
-				get_authorities(db.cache(), BlockId::Hash(hash6)),
-				Some(vec![auth1(), auth2()]),
-			);
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash7)), Some(vec![auth3()]));
-			let hash8 = insert_block(&db, make_authorities(vec![auth3()]), || default_header(&hash7, 8));
-			assert_eq!(
-				get_authorities(db.cache(), BlockId::Hash(hash6)),
-				Some(vec![auth1(), auth2()]),
-			);
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash7)), Some(vec![auth3()]));
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash8)), Some(vec![auth3()]));
-			let hash6_1 = insert_block(&db, make_authorities(vec![auth4()]), || default_header(&hash6, 7));
-			assert_eq!(
-				get_authorities(db.cache(), BlockId::Hash(hash6)),
-				Some(vec![auth1(), auth2()]),
-			);
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash7)), Some(vec![auth3()]));
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash8)), Some(vec![auth3()]));
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1)), Some(vec![auth4()]));
-			let hash6_1_1 = insert_non_best_block(&db, make_authorities(vec![auth5()]), || default_header(&hash6_1, 8));
-			assert_eq!(
-				get_authorities(db.cache(), BlockId::Hash(hash6)),
-				Some(vec![auth1(), auth2()]),
-			);
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash7)), Some(vec![auth3()]));
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash8)), Some(vec![auth3()]));
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1)), Some(vec![auth4()]));
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1_1)), Some(vec![auth5()]));
-			let hash6_1_2 = insert_non_best_block(&db, make_authorities(vec![auth6()]), || default_header(&hash6_1, 8));
-			assert_eq!(
-				get_authorities(db.cache(), BlockId::Hash(hash6)),
-				Some(vec![auth1(), auth2()]),
-			);
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash7)), Some(vec![auth3()]));
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash8)), Some(vec![auth3()]));
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1)), Some(vec![auth4()]));
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1_1)), Some(vec![auth5()]));
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1_2)), Some(vec![auth6()]));
-			let hash6_2 = insert_block(&db, make_authorities(vec![auth4()]), || default_header(&hash6_1, 8));
-			assert_eq!(
-				get_authorities(db.cache(), BlockId::Hash(hash6)),
-				Some(vec![auth1(), auth2()]),
-			);
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash7)), Some(vec![auth3()]));
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash8)), Some(vec![auth3()]));
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1)), Some(vec![auth4()]));
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1_1)), Some(vec![auth5()]));
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1_2)), Some(vec![auth6()]));
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_2)), Some(vec![auth4()]));
-
-			(hash7, hash8, hash6_1, hash6_2, hash6_1_1, hash6_1_2)
-		};
+				(hash7, hash8, hash6_1, hash6_2, hash6_1_1, hash6_1_2)
+			};
 
-		{
-			// finalize block hash6_1
-			db.finalize_header(BlockId::Hash(hash6_1)).unwrap();
-			assert_eq!(
-				get_authorities(db.cache(), BlockId::Hash(hash6)),
-				Some(vec![auth1(), auth2()]),
-			);
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash7)), None);
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash8)), None);
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1)), Some(vec![auth4()]));
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1_1)), Some(vec![auth5()]));
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1_2)), Some(vec![auth6()]));
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_2)), Some(vec![auth4()]));
-			// finalize block hash6_2
-			db.finalize_header(BlockId::Hash(hash6_2)).unwrap();
-			assert_eq!(
-				get_authorities(db.cache(), BlockId::Hash(hash6)),
-				Some(vec![auth1(), auth2()]),
-			);
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash7)), None);
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash8)), None);
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1)), Some(vec![auth4()]));
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1_1)), None);
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1_2)), None);
-			assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_2)), Some(vec![auth4()]));
-		}
+			{
+				// finalize block hash6_1
+				db.finalize_header(BlockId::Hash(hash6_1)).unwrap();
+				assert_eq!(
+					get_authorities(db.cache(), BlockId::Hash(hash6)),
+					Some(vec![auth1(), auth2()]),
+				);
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash7)), None);
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash8)), None);
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1)), Some(vec![auth4()]));
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1_1)), Some(vec![auth5()]));
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1_2)), Some(vec![auth6()]));
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_2)), Some(vec![auth4()]));
+				// finalize block hash6_2
+				db.finalize_header(BlockId::Hash(hash6_2)).unwrap();
+				assert_eq!(
+					get_authorities(db.cache(), BlockId::Hash(hash6)),
+					Some(vec![auth1(), auth2()]),
+				);
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash7)), None);
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash8)), None);
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1)), Some(vec![auth4()]));
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1_1)), None);
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_1_2)), None);
+				assert_eq!(get_authorities(db.cache(), BlockId::Hash(hash6_2)), Some(vec![auth4()]));
+			}
+		});
 	}
 
 	#[test]
 	fn database_is_reopened() {
-		let db = LightStorage::new_test();
-		let hash0 = insert_final_block(&db, HashMap::new(), || default_header(&Default::default(), 0));
-		assert_eq!(db.info().best_hash, hash0);
-		assert_eq!(db.header(BlockId::Hash(hash0)).unwrap().unwrap().hash(), hash0);
-
-		let db = db.db;
-		let db = LightStorage::from_kvdb(db).unwrap();
-		assert_eq!(db.info().best_hash, hash0);
-		assert_eq!(db.header(BlockId::Hash::<Block>(hash0)).unwrap().unwrap().hash(), hash0);
+		for_each_test_database(|| {
+			let db = LightStorage::new_test();
+			let hash0 = insert_final_block(&db, HashMap::new(), || default_header(&Default::default(), 0));
+			assert_eq!(db.info().best_hash, hash0);
+			assert_eq!(db.header(BlockId::Hash(hash0)).unwrap().unwrap().hash(), hash0);
+
+			let db = db.db;
+			let db = LightStorage::from_database(db).unwrap();
+			assert_eq!(db.info().best_hash, hash0);
+			assert_eq!(db.header(BlockId::Hash::<Block>(hash0)).unwrap().unwrap().hash(), hash0);
+		});
 	}
 
 	#[test]
 	fn aux_store_works() {
-		let db = LightStorage::<Block>::new_test();
-
-		// insert aux1 + aux2 using direct store access
-		db.insert_aux(&[(&[1][..], &[101][..]), (&[2][..], &[102][..])], ::std::iter::empty()).unwrap();
-
-		// check aux values
-		assert_eq!(db.get_aux(&[1]).unwrap(), Some(vec![101]));
-		assert_eq!(db.get_aux(&[2]).unwrap(), Some(vec![102]));
-		assert_eq!(db.get_aux(&[3]).unwrap(), None);
-
-		// delete aux1 + insert aux3 using import operation
-		db.import_header(default_header(&Default::default(), 0), HashMap::new(), NewBlockState::Best, vec![
-			(vec![3], Some(vec![103])),
-			(vec![1], None),
-		]).unwrap();
-
-		// check aux values
-		assert_eq!(db.get_aux(&[1]).unwrap(), None);
-		assert_eq!(db.get_aux(&[2]).unwrap(), Some(vec![102]));
-		assert_eq!(db.get_aux(&[3]).unwrap(), Some(vec![103]));
+		for_each_test_database(|| {
+			let db = LightStorage::<Block>::new_test();
+
+			// insert aux1 + aux2 using direct store access
+			db.insert_aux(&[(&[1][..], &[101][..]), (&[2][..], &[102][..])], ::std::iter::empty()).unwrap();
+
+			// check aux values
+			assert_eq!(db.get_aux(&[1]).unwrap(), Some(vec![101]));
+			assert_eq!(db.get_aux(&[2]).unwrap(), Some(vec![102]));
+			assert_eq!(db.get_aux(&[3]).unwrap(), None);
+
+			// delete aux1 + insert aux3 using import operation
+			db.import_header(default_header(&Default::default(), 0), HashMap::new(), NewBlockState::Best, vec![
+				(vec![3], Some(vec![103])),
+				(vec![1], None),
+			]).unwrap();
+
+			// check aux values
+			assert_eq!(db.get_aux(&[1]).unwrap(), None);
+			assert_eq!(db.get_aux(&[2]).unwrap(), Some(vec![102]));
+			assert_eq!(db.get_aux(&[3]).unwrap(), Some(vec![103]));
+		});
 	}
 
 	#[test]
 	fn cache_can_be_initialized_after_genesis_inserted() {
-		let (genesis_hash, storage) = {
-			let db = LightStorage::<Block>::new_test();
+		for_each_test_database(|| {
+			let (genesis_hash, storage) = {
+				let db = LightStorage::<Block>::new_test();
+
+				// before cache is initialized => None
+				assert_eq!(db.cache().get_at(b"test", &BlockId::Number(0)), None);
+
+				// insert genesis block (no value for cache is provided)
+				let mut genesis_hash = None;
+				insert_block(&db, HashMap::new(), || {
+					let header = default_header(&Default::default(), 0);
+					genesis_hash = Some(header.hash());
+					header
+				});
 
-			// before cache is initialized => None
-			assert_eq!(db.cache().get_at(b"test", &BlockId::Number(0)), None);
+				// after genesis is inserted => None
+				assert_eq!(db.cache().get_at(b"test", &BlockId::Number(0)), None);
 
-			// insert genesis block (no value for cache is provided)
-			let mut genesis_hash = None;
-			insert_block(&db, HashMap::new(), || {
-				let header = default_header(&Default::default(), 0);
-				genesis_hash = Some(header.hash());
-				header
-			});
+				// initialize cache
+				db.cache().initialize(b"test", vec![42]).unwrap();
 
-			// after genesis is inserted => None
-			assert_eq!(db.cache().get_at(b"test", &BlockId::Number(0)), None);
+				// after genesis is inserted + cache is initialized => Some
+				assert_eq!(
+					db.cache().get_at(b"test", &BlockId::Number(0)),
+					Some(((0, genesis_hash.unwrap()), None, vec![42])),
+				);
 
-			// initialize cache
-			db.cache().initialize(b"test", vec![42]).unwrap();
+				(genesis_hash, db.db)
+			};
 
-			// after genesis is inserted + cache is initialized => Some
+			// restart && check that after restart value is read from the cache
+			let db = LightStorage::<Block>::from_database(storage).expect("failed to create test-db");
 			assert_eq!(
 				db.cache().get_at(b"test", &BlockId::Number(0)),
 				Some(((0, genesis_hash.unwrap()), None, vec![42])),
 			);
-
-			(genesis_hash, db.db)
-		};
-
-		// restart && check that after restart value is read from the cache
-		let db = LightStorage::<Block>::from_kvdb(storage as Arc<_>).expect("failed to create test-db");
-		assert_eq!(
-			db.cache().get_at(b"test", &BlockId::Number(0)),
-			Some(((0, genesis_hash.unwrap()), None, vec![42])),
-		);
+		});
 	}
 }
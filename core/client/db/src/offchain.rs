@@ -14,21 +14,42 @@
 // You should have received a copy of the GNU General Public License
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
-//! RocksDB-based offchain workers local storage.
+//! Database-based offchain workers local storage.
+//!
+//! Besides the persistent storage shared by all forks, every block has its own fork-aware storage:
+//! values written at a block are visible from its descendants only. Each imported block records its
+//! parent so that reads can walk up the non-canonical chain, and its parent records it as a child so
+//! that abandoned forks can be found. When a block is canonicalized, its values are merged into the
+//! canonical storage and the storage of the forks it abandons is removed. Values written at a block
+//! that is already canonical go straight to the canonical storage.
 
 use std::{
 	collections::HashMap,
+	io,
 	sync::Arc,
 };
 
 use crate::columns;
-use kvdb::KeyValueDB;
+use database::{Database, Transaction};
 use parking_lot::Mutex;
 
+/// Prefix of the values written at a given block, followed by the block hash and the key.
+const LOCAL_PREFIX: &[u8] = b"local/";
+/// Prefix of the parent of a non-canonical block, followed by the block hash.
+const LOCAL_PARENT_PREFIX: &[u8] = b"local_parent/";
+/// Prefix of the non-canonical children of a block, followed by the block hash and the child hash.
+const LOCAL_CHILDREN_PREFIX: &[u8] = b"local_children/";
+/// Prefix of the values written at canonical blocks, followed by the key.
+const LOCAL_CANONICAL_PREFIX: &[u8] = b"local_canonical/";
+
+fn concat(parts: &[&[u8]]) -> Vec<u8> {
+	parts.iter().flat_map(|part| part.iter()).cloned().collect()
+}
+
 /// Offchain local storage
 #[derive(Clone)]
 pub struct LocalStorage {
-	db: Arc<dyn KeyValueDB>,
+	db: Arc<dyn Database>,
 	locks: Arc<Mutex<HashMap<Vec<u8>, Arc<Mutex<()>>>>>,
 }
 
@@ -40,109 +61,366 @@ impl std::fmt::Debug for LocalStorage {
 }
 
 impl LocalStorage {
-	/// Create new offchain storage for tests (backed by memorydb)
+	/// Create new offchain storage for tests (backed by the test database)
 	#[cfg(any(test, feature = "test-helpers"))]
 	pub fn new_test() -> Self {
-		let db = Arc::new(::kvdb_memorydb::create(crate::utils::NUM_COLUMNS));
-		Self::new(db as _)
+		Self::new(crate::utils::new_test_database())
 	}
 
-	/// Create offchain local storage with given `KeyValueDB` backend.
-	pub fn new(db: Arc<dyn KeyValueDB>) -> Self {
+	/// Create offchain local storage with given `Database` backend.
+	pub fn new(db: Arc<dyn Database>) -> Self {
 		Self {
 			db,
 			locks: Default::default(),
 		}
 	}
-}
 
-impl client::backend::OffchainStorage for LocalStorage {
-	fn set(&mut self, prefix: &[u8], key: &[u8], value: &[u8]) {
-		let key: Vec<u8> = prefix.iter().chain(key).cloned().collect();
-		let mut tx = self.db.transaction();
-		tx.put(columns::OFFCHAIN, &key, value);
+	/// Record the parent of an imported block, so that the fork-aware storage of the block sees the
+	/// values written on its ancestors.
+	pub(crate) fn note_block(transaction: &mut Transaction, block: &[u8], parent: &[u8]) {
+		transaction.put(columns::OFFCHAIN, &concat(&[LOCAL_PARENT_PREFIX, block]), parent);
+		transaction.put(columns::OFFCHAIN, &concat(&[LOCAL_CHILDREN_PREFIX, parent, block]), &[]);
+	}
 
-		if let Err(e) = self.db.write(tx) {
-			log::warn!("Error writing to the offchain DB: {:?}", e);
+	/// Merge the fork-aware storage of a canonicalized block, and of its ancestors that have not
+	/// been canonicalized yet, into the canonical storage. The fork-aware storage of every block
+	/// that does not descend from `block` is removed.
+	///
+	/// The parent is given explicitly since the block may have been noted in the same transaction.
+	pub(crate) fn canonicalize(
+		&self,
+		transaction: &mut Transaction,
+		block: &[u8],
+		parent: &[u8],
+	) -> io::Result<()> {
+		let mut route = vec![(block.to_vec(), parent.to_vec())];
+		let mut current = parent.to_vec();
+		while let Some(parent) = self.parent(&current) {
+			route.push((current, parent.clone()));
+			current = parent;
+		}
+
+		// Oldest first, so that the values of the descendants win.
+		for (block, parent) in route.into_iter().rev() {
+			for pair in self.db.iter_from_prefix(
+				columns::OFFCHAIN,
+				&concat(&[LOCAL_CHILDREN_PREFIX, &parent]),
+			) {
+				let (sibling, _) = pair?;
+				let sibling = &sibling[LOCAL_CHILDREN_PREFIX.len() + parent.len()..];
+				if sibling != &block[..] {
+					self.discard(transaction, sibling, &parent)?;
+				}
+			}
+
+			let prefix = concat(&[LOCAL_PREFIX, &block]);
+			for pair in self.db.iter_from_prefix(columns::OFFCHAIN, &prefix) {
+				let (key, value) = pair?;
+				let canonical_key = concat(&[LOCAL_CANONICAL_PREFIX, &key[prefix.len()..]]);
+				transaction.put(columns::OFFCHAIN, &canonical_key, &value);
+				transaction.delete(columns::OFFCHAIN, &key);
+			}
+			transaction.delete(columns::OFFCHAIN, &concat(&[LOCAL_PARENT_PREFIX, &block]));
+			transaction.delete(columns::OFFCHAIN, &concat(&[LOCAL_CHILDREN_PREFIX, &parent, &block]));
 		}
+		Ok(())
 	}
 
-	fn get(&self, prefix: &[u8], key: &[u8]) -> Option<Vec<u8>> {
-		let key: Vec<u8> = prefix.iter().chain(key).cloned().collect();
-		self.db.get(columns::OFFCHAIN, &key)
+	/// Remove the fork-aware storage of `block` and of all its descendants.
+	fn discard(&self, transaction: &mut Transaction, block: &[u8], parent: &[u8]) -> io::Result<()> {
+		let prefix = concat(&[LOCAL_PREFIX, block]);
+		for pair in self.db.iter_from_prefix(columns::OFFCHAIN, &prefix) {
+			transaction.delete(columns::OFFCHAIN, &pair?.0);
+		}
+		transaction.delete(columns::OFFCHAIN, &concat(&[LOCAL_PARENT_PREFIX, block]));
+		transaction.delete(columns::OFFCHAIN, &concat(&[LOCAL_CHILDREN_PREFIX, parent, block]));
+
+		let children_prefix = concat(&[LOCAL_CHILDREN_PREFIX, block]);
+		for pair in self.db.iter_from_prefix(columns::OFFCHAIN, &children_prefix) {
+			self.discard(transaction, &pair?.0[children_prefix.len()..], block)?;
+		}
+		Ok(())
+	}
+
+	/// The parent of a block that has not been canonicalized yet.
+	fn parent(&self, block: &[u8]) -> Option<Vec<u8>> {
+		self.read(&concat(&[LOCAL_PARENT_PREFIX, block]))
+	}
+
+	/// Whether `block` is part of the canonical chain.
+	fn is_canonical(&self, block: &[u8]) -> bool {
+		match self.db.get(columns::KEY_LOOKUP, block) {
+			// lookup keys start with the number index key of the block.
+			Ok(Some(lookup_key)) if lookup_key.len() >= 4 => self.db
+				.get(columns::KEY_LOOKUP, &lookup_key[..4])
+				.ok()
+				.and_then(|x| x) == Some(lookup_key),
+			_ => false,
+		}
+	}
+
+	/// The key under which a value written at `block` is stored: the fork-aware storage of the block
+	/// if it is not canonicalized yet, the canonical storage if it is. `None` if the block has been
+	/// discarded or is unknown.
+	fn local_key(&self, block: &[u8], key: &[u8]) -> Option<Vec<u8>> {
+		if self.parent(block).is_some() {
+			Some(concat(&[LOCAL_PREFIX, block, key]))
+		} else if self.is_canonical(block) {
+			Some(concat(&[LOCAL_CANONICAL_PREFIX, key]))
+		} else {
+			None
+		}
+	}
+
+	fn read(&self, key: &[u8]) -> Option<Vec<u8>> {
+		self.db.get(columns::OFFCHAIN, key)
 			.ok()
 			.and_then(|x| x)
-			.map(|v| v.to_vec())
 	}
 
-	fn compare_and_set(
-		&mut self,
-		prefix: &[u8],
-		item_key: &[u8],
-		old_value: Option<&[u8]>,
-		new_value: &[u8],
-	) -> bool {
-		let key: Vec<u8> = prefix.iter().chain(item_key).cloned().collect();
+	fn write(&self, key: &[u8], value: &[u8]) {
+		let mut tx = Transaction::new();
+		tx.put(columns::OFFCHAIN, key, value);
+
+		if let Err(e) = self.db.commit(tx) {
+			log::warn!("Error writing to the offchain DB: {:?}", e);
+		}
+	}
+
+	/// Call `f` while holding the lock of the given database key.
+	fn with_lock<R>(&self, key: &[u8], f: impl FnOnce() -> R) -> R {
 		let key_lock = {
 			let mut locks = self.locks.lock();
-			locks.entry(key.clone()).or_default().clone()
+			locks.entry(key.to_vec()).or_default().clone()
 		};
 
-		let is_set;
-		{
+		let result = {
 			let _key_guard = key_lock.lock();
-			let val = self.db.get(columns::OFFCHAIN, &key)
-				.ok()
-				.and_then(|x| x);
-			is_set = val.as_ref().map(|x| &**x) == old_value;
-
-			if is_set {
-				self.set(prefix, item_key, new_value)
-			}
-		}
+			f()
+		};
 
 		// clean the lock map if we're the only entry
 		let mut locks = self.locks.lock();
 		{
 			drop(key_lock);
-			let key_lock = locks.get_mut(&key);
+			let key_lock = locks.get_mut(key);
 			if let Some(_) = key_lock.and_then(Arc::get_mut) {
-				locks.remove(&key);
+				locks.remove(key);
 			}
 		}
-		is_set
+		result
+	}
+}
+
+impl client::backend::OffchainStorage for LocalStorage {
+	fn set(&mut self, prefix: &[u8], key: &[u8], value: &[u8]) {
+		self.write(&concat(&[prefix, key]), value)
+	}
+
+	fn get(&self, prefix: &[u8], key: &[u8]) -> Option<Vec<u8>> {
+		self.read(&concat(&[prefix, key]))
+	}
+
+	fn compare_and_set(
+		&mut self,
+		prefix: &[u8],
+		item_key: &[u8],
+		old_value: Option<&[u8]>,
+		new_value: &[u8],
+	) -> bool {
+		let key = concat(&[prefix, item_key]);
+		self.with_lock(&key, || {
+			let is_set = self.read(&key).as_ref().map(|x| &**x) == old_value;
+			if is_set {
+				self.write(&key, new_value)
+			}
+			is_set
+		})
+	}
+
+	fn set_local(&mut self, block: &[u8], key: &[u8], value: &[u8]) {
+		match self.local_key(block, key) {
+			Some(key) => self.write(&key, value),
+			None => log::debug!("Ignoring offchain write at discarded block {:?}", block),
+		}
+	}
+
+	fn get_local(&self, block: &[u8], key: &[u8]) -> Option<Vec<u8>> {
+		let mut block = block.to_vec();
+		loop {
+			if let Some(value) = self.read(&concat(&[LOCAL_PREFIX, &block, key])) {
+				return Some(value)
+			}
+			match self.parent(&block) {
+				Some(parent) => block = parent,
+				None => break,
+			}
+		}
+		self.read(&concat(&[LOCAL_CANONICAL_PREFIX, key]))
+	}
+
+	fn compare_and_set_local(
+		&mut self,
+		block: &[u8],
+		item_key: &[u8],
+		old_value: Option<&[u8]>,
+		new_value: &[u8],
+	) -> bool {
+		let key = match self.local_key(block, item_key) {
+			Some(key) => key,
+			None => return false,
+		};
+		self.with_lock(&key, || {
+			let is_set = self.get_local(block, item_key).as_ref().map(|x| &**x) == old_value;
+			if is_set {
+				self.write(&key, new_value)
+			}
+			is_set
+		})
 	}
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::utils::for_each_test_database;
 	use client::backend::OffchainStorage;
 
 	#[test]
 	fn should_compare_and_set_and_clear_the_locks_map() {
-		let mut storage = LocalStorage::new_test();
-		let prefix = b"prefix";
-		let key = b"key";
-		let value = b"value";
+		for_each_test_database(|| {
+			let mut storage = LocalStorage::new_test();
+			let prefix = b"prefix";
+			let key = b"key";
+			let value = b"value";
 
-		storage.set(prefix, key, value);
-		assert_eq!(storage.get(prefix, key), Some(value.to_vec()));
+			storage.set(prefix, key, value);
+			assert_eq!(storage.get(prefix, key), Some(value.to_vec()));
 
-		assert_eq!(storage.compare_and_set(prefix, key, Some(value), b"asd"), true);
-		assert_eq!(storage.get(prefix, key), Some(b"asd".to_vec()));
-		assert!(storage.locks.lock().is_empty(), "Locks map should be empty!");
+			assert_eq!(storage.compare_and_set(prefix, key, Some(value), b"asd"), true);
+			assert_eq!(storage.get(prefix, key), Some(b"asd".to_vec()));
+			assert!(storage.locks.lock().is_empty(), "Locks map should be empty!");
+		});
 	}
 
 	#[test]
 	fn should_compare_and_set_on_empty_field() {
-		let mut storage = LocalStorage::new_test();
-		let prefix = b"prefix";
-		let key = b"key";
+		for_each_test_database(|| {
+			let mut storage = LocalStorage::new_test();
+			let prefix = b"prefix";
+			let key = b"key";
 
-		assert_eq!(storage.compare_and_set(prefix, key, None, b"asd"), true);
-		assert_eq!(storage.get(prefix, key), Some(b"asd".to_vec()));
-		assert!(storage.locks.lock().is_empty(), "Locks map should be empty!");
+			assert_eq!(storage.compare_and_set(prefix, key, None, b"asd"), true);
+			assert_eq!(storage.get(prefix, key), Some(b"asd".to_vec()));
+			assert!(storage.locks.lock().is_empty(), "Locks map should be empty!");
+		});
 	}
 
+	#[test]
+	fn local_storage_is_fork_aware_and_canonicalized() {
+		for_each_test_database(|| {
+			// 1 - 2a - 3a
+			//   \ 2b
+			let mut storage = LocalStorage::new_test();
+			let commit = |storage: &LocalStorage, tx| storage.db.commit(tx).unwrap();
+			let mut tx = Transaction::new();
+			LocalStorage::note_block(&mut tx, b"1", b"0");
+			LocalStorage::note_block(&mut tx, b"2a", b"1");
+			LocalStorage::note_block(&mut tx, b"2b", b"1");
+			LocalStorage::note_block(&mut tx, b"3a", b"2a");
+			commit(&storage, tx);
+
+			storage.set_local(b"1", b"key", b"1");
+			assert!(storage.compare_and_set_local(b"2a", b"key", Some(b"1"), b"2a"));
+			storage.set_local(b"2b", b"key", b"2b");
+			storage.set_local(b"2b", b"other", b"2b");
+			assert_eq!(storage.get_local(b"3a", b"key"), Some(b"2a".to_vec()));
+			assert_eq!(storage.get_local(b"2b", b"key"), Some(b"2b".to_vec()));
+			assert_eq!(storage.get(b"", b"key"), None);
+			assert!(storage.locks.lock().is_empty(), "Locks map should be empty!");
+
+			let mut tx = Transaction::new();
+			storage.canonicalize(&mut tx, b"1", b"0").unwrap();
+			commit(&storage, tx);
+			assert_eq!(storage.get_local(b"1", b"key"), Some(b"1".to_vec()));
+			assert_eq!(storage.get_local(b"2b", b"key"), Some(b"2b".to_vec()));
+
+			let mut tx = Transaction::new();
+			storage.canonicalize(&mut tx, b"2a", b"1").unwrap();
+			commit(&storage, tx);
+			assert_eq!(storage.get_local(b"3a", b"key"), Some(b"2a".to_vec()));
+			assert_eq!(storage.get_local(b"2b", b"other"), None);
+			assert_eq!(storage.db.iter_from_prefix(columns::OFFCHAIN, LOCAL_PREFIX).count(), 0);
+			assert_eq!(storage.db.iter_from_prefix(columns::OFFCHAIN, LOCAL_PARENT_PREFIX).count(), 1);
+			assert_eq!(storage.db.iter_from_prefix(columns::OFFCHAIN, LOCAL_CHILDREN_PREFIX).count(), 1);
+		});
+	}
+
+	#[test]
+	fn canonicalization_merges_skipped_ancestors_and_removes_their_forks() {
+		for_each_test_database(|| {
+			// 1 - 2a - 3a
+			//   \ 2b - 3b
+			let mut storage = LocalStorage::new_test();
+			let mut tx = Transaction::new();
+			LocalStorage::note_block(&mut tx, b"1", b"0");
+			LocalStorage::note_block(&mut tx, b"2a", b"1");
+			LocalStorage::note_block(&mut tx, b"2b", b"1");
+			LocalStorage::note_block(&mut tx, b"3a", b"2a");
+			LocalStorage::note_block(&mut tx, b"3b", b"2b");
+			storage.db.commit(tx).unwrap();
+
+			storage.set_local(b"1", b"key", b"1");
+			storage.set_local(b"1", b"other", b"1");
+			storage.set_local(b"3a", b"key", b"3a");
+			storage.set_local(b"2b", b"key", b"2b");
+			storage.set_local(b"3b", b"key", b"3b");
+
+			let mut tx = Transaction::new();
+			storage.canonicalize(&mut tx, b"3a", b"2a").unwrap();
+			storage.db.commit(tx).unwrap();
+
+			assert_eq!(storage.read(&concat(&[LOCAL_CANONICAL_PREFIX, b"key"])), Some(b"3a".to_vec()));
+			assert_eq!(storage.read(&concat(&[LOCAL_CANONICAL_PREFIX, b"other"])), Some(b"1".to_vec()));
+			assert_eq!(storage.db.iter_from_prefix(columns::OFFCHAIN, LOCAL_PREFIX).count(), 0);
+			assert_eq!(storage.db.iter_from_prefix(columns::OFFCHAIN, LOCAL_PARENT_PREFIX).count(), 0);
+			assert_eq!(storage.db.iter_from_prefix(columns::OFFCHAIN, LOCAL_CHILDREN_PREFIX).count(), 0);
+		});
+	}
+
+	#[test]
+	fn local_values_written_at_canonical_blocks_are_canonical() {
+		for_each_test_database(|| {
+			// 1 - 2
+			//   \ 2b
+			let mut storage = LocalStorage::new_test();
+			let mut tx = Transaction::new();
+			LocalStorage::note_block(&mut tx, b"1", b"0");
+			LocalStorage::note_block(&mut tx, b"2", b"1");
+			LocalStorage::note_block(&mut tx, b"2b", b"1");
+			crate::utils::insert_hash_to_key_mapping(&mut tx, columns::KEY_LOOKUP, 1u32, b"1").unwrap();
+			crate::utils::insert_number_to_key_mapping(&mut tx, columns::KEY_LOOKUP, 1u32, b"1").unwrap();
+			crate::utils::insert_hash_to_key_mapping(&mut tx, columns::KEY_LOOKUP, 2u32, b"2b").unwrap();
+			crate::utils::insert_hash_to_key_mapping(&mut tx, columns::KEY_LOOKUP, 2u32, b"2").unwrap();
+			crate::utils::insert_number_to_key_mapping(&mut tx, columns::KEY_LOOKUP, 2u32, b"2").unwrap();
+			storage.db.commit(tx).unwrap();
+
+			let mut tx = Transaction::new();
+			storage.canonicalize(&mut tx, b"2", b"1").unwrap();
+			storage.db.commit(tx).unwrap();
+
+			// the offchain worker of block 1 runs after its canonicalization.
+			storage.set_local(b"1", b"key", b"1");
+			assert_eq!(storage.get_local(b"2", b"key"), Some(b"1".to_vec()));
+			assert!(storage.compare_and_set_local(b"2", b"key", Some(b"1"), b"2"));
+			assert_eq!(storage.get_local(b"1", b"key"), Some(b"2".to_vec()));
+
+			// writes at a discarded block are dropped instead of being stranded.
+			storage.set_local(b"2b", b"key", b"2b");
+			assert!(!storage.compare_and_set_local(b"2b", b"key", Some(b"2"), b"2b"));
+			assert_eq!(storage.get_local(b"2", b"key"), Some(b"2".to_vec()));
+			assert_eq!(storage.db.iter_from_prefix(columns::OFFCHAIN, LOCAL_PREFIX).count(), 0);
+			assert!(storage.locks.lock().is_empty(), "Locks map should be empty!");
+		});
+	}
 }
@@ -44,6 +44,10 @@ pub struct Cache<B: BlockT, H: Hasher> {
 	/// Information on the modifications in recently committed blocks; specifically which keys
 	/// changed in which block. Ordered by block number.
 	modifications: VecDeque<BlockChanges<B::Header>>,
+	/// Number of lookups answered by the shared cache.
+	hits: u64,
+	/// Number of lookups that had to go to the underlying state.
+	misses: u64,
 }
 
 struct LRUMap<K, V>(LinkedHashMap<K, V>, usize, usize);
@@ -153,6 +157,11 @@ impl<B: BlockT, H: Hasher> Cache<B, H> {
 			//  ignore small hashes storage and self.lru_hashes.used_size()
 	}
 
+	/// Returns the number of hits and misses of the shared cache since its creation.
+	pub fn hit_stats(&self) -> (u64, u64) {
+		(self.hits, self.misses)
+	}
+
 	/// Synchronize the shared cache with the best block state.
 	/// This function updates the shared cache by removing entries
 	/// that are invalidated by chain reorganization. It should be
@@ -231,6 +240,8 @@ pub fn new_shared_cache<B: BlockT, H: Hasher>(
 		lru_child_storage: LRUMap(LinkedHashMap::new(), 0,
 			shared_cache_size * child_ratio.0 / child_ratio.1),
 		modifications: VecDeque::new(),
+		hits: 0,
+		misses: 0,
 	}))
 }
 
@@ -478,10 +489,12 @@ impl<H: Hasher, S: StateBackend<H>, B: BlockT> StateBackend<H> for CachingState<
 		if Self::is_allowed(Some(key), None, &self.cache.parent_hash, &cache.modifications) {
 			if let Some(entry) = cache.lru_storage.get(key).map(|a| a.clone()) {
 				trace!("Found in shared cache: {:?}", HexDisplay::from(&key));
+				cache.hits += 1;
 				return Ok(entry)
 			}
 		}
 		trace!("Cache miss: {:?}", HexDisplay::from(&key));
+		cache.misses += 1;
 		let value = self.state.storage(key)?;
 		RwLockUpgradableReadGuard::upgrade(local_cache).storage.insert(key.to_vec(), value.clone());
 		Ok(value)
@@ -497,10 +510,12 @@ impl<H: Hasher, S: StateBackend<H>, B: BlockT> StateBackend<H> for CachingState<
 		if Self::is_allowed(Some(key), None, &self.cache.parent_hash, &cache.modifications) {
 			if let Some(entry) = cache.lru_hashes.get(key).map(|a| a.0.clone()) {
 				trace!("Found hash in shared cache: {:?}", HexDisplay::from(&key));
+				cache.hits += 1;
 				return Ok(entry)
 			}
 		}
 		trace!("Cache hash miss: {:?}", HexDisplay::from(&key));
+		cache.misses += 1;
 		let hash = self.state.storage_hash(key)?;
 		RwLockUpgradableReadGuard::upgrade(local_cache).hashes.insert(key.to_vec(), hash.clone());
 		Ok(hash)
@@ -517,10 +532,12 @@ impl<H: Hasher, S: StateBackend<H>, B: BlockT> StateBackend<H> for CachingState<
 		if Self::is_allowed(None, Some(&key), &self.cache.parent_hash, &cache.modifications) {
 			if let Some(entry) = cache.lru_child_storage.get(&key).map(|a| a.clone()) {
 				trace!("Found in shared cache: {:?}", key);
+				cache.hits += 1;
 				return Ok(entry)
 			}
 		}
 		trace!("Cache miss: {:?}", key);
+		cache.misses += 1;
 		let value = self.state.child_storage(storage_key, &key.1[..])?;
 		RwLockUpgradableReadGuard::upgrade(local_cache).child_storage.insert(key, value.clone());
 		Ok(value)
@@ -725,6 +742,32 @@ mod tests {
 		assert_eq!(shared.lock().used_storage_cache_size(), 34 /* bytes */);
 	}
 
+	#[test]
+	fn should_count_shared_cache_hits_and_misses() {
+		let root_parent = H256::random();
+		let shared = new_shared_cache::<Block, Blake2Hasher>(256*1024, (0,1));
+		let h0 = H256::random();
+
+		let key = H256::random()[..].to_vec();
+		let mut s = CachingState::new(InMemory::<Blake2Hasher>::default(), shared.clone(), Some(root_parent));
+		s.cache.sync_cache(
+			&[],
+			&[],
+			vec![(key.clone(), Some(vec![1]))],
+			vec![],
+			Some(h0.clone()),
+			Some(0),
+			|| true,
+		);
+
+		let s = CachingState::new(InMemory::<Blake2Hasher>::default(), shared.clone(), Some(h0));
+		assert_eq!(s.storage(&key).unwrap(), Some(vec![1]));
+		assert_eq!(shared.lock().hit_stats(), (1, 0));
+
+		assert!(s.storage(&H256::random()[..]).unwrap().is_none());
+		assert_eq!(shared.lock().hit_stats(), (1, 1));
+	}
+
 	#[test]
 	fn fix_storage_mismatch_issue() {
 		let _ = ::env_logger::try_init();
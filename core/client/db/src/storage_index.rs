@@ -0,0 +1,108 @@
+// Copyright 2017-2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Index of the changes of the top-level storage keys, for historical queries on archive nodes.
+//!
+//! Every imported block that writes a key adds an entry keyed by the encoded key, the block number
+//! and the block hash, holding the hash of the new value or `None` if the key was removed. Block
+//! numbers are stored inverted, so that seeking to a block number finds the latest change at or
+//! before that block first. Entries of non-canonical blocks are kept and skipped on reads.
+
+use client::backend::StorageCollection;
+use client::error::{Result as ClientResult, Error as ClientError};
+use codec::{Decode, Encode};
+use database::{Database, Transaction};
+use hash_db::Hasher;
+use primitives::{Blake2Hasher, H256};
+use sr_primitives::traits::{Block as BlockT, NumberFor, UniqueSaturatedFrom, UniqueSaturatedInto};
+
+use crate::columns;
+use crate::utils::db_err;
+
+/// Size of the inverted block number within an entry key.
+const NUMBER_LEN: usize = 8;
+
+/// Encoded key followed by the inverted block number.
+///
+/// Keys are length-prefixed, so that the entries of a key never share a prefix with the entries
+/// of another key.
+fn entry_prefix<N: UniqueSaturatedInto<u64>>(key: &[u8], number: N) -> Vec<u8> {
+	let mut prefix = key.encode();
+	prefix.extend_from_slice(&(u64::max_value() - number.unique_saturated_into()).to_be_bytes());
+	prefix
+}
+
+/// Add the index entries of the changes made by a block to the transaction.
+pub(crate) fn note_changes<Block: BlockT>(
+	transaction: &mut Transaction,
+	number: NumberFor<Block>,
+	hash: &Block::Hash,
+	changes: &StorageCollection,
+) {
+	for (key, value) in changes {
+		let mut entry_key = entry_prefix(key, number);
+		entry_key.extend_from_slice(hash.as_ref());
+		let value_hash = value.as_ref().map(|value| Blake2Hasher::hash(value));
+		transaction.put_vec(columns::STORAGE_INDEX, &entry_key, value_hash.encode());
+	}
+}
+
+/// Read the changes of a key at the canonical blocks `first..=last`, starting with the latest
+/// change at or before `first`.
+///
+/// `canonical_hash` gives the hash of the canonical block at a given number.
+pub(crate) fn read_changes<Block, F>(
+	db: &dyn Database,
+	key: &[u8],
+	first: NumberFor<Block>,
+	last: NumberFor<Block>,
+	canonical_hash: F,
+) -> ClientResult<Vec<(NumberFor<Block>, Option<H256>)>> where
+	Block: BlockT,
+	F: Fn(NumberFor<Block>) -> ClientResult<Option<Block::Hash>>,
+{
+	let prefix = key.encode();
+	let start = entry_prefix(key, last);
+	let mut changes = Vec::new();
+	for pair in db.iter_from_prefix_at(columns::STORAGE_INDEX, &prefix, &start) {
+		let (entry_key, value) = pair.map_err(db_err)?;
+		let corrupted = || ClientError::Backend("Corrupted storage index entry".into());
+		if entry_key.len() < prefix.len() + NUMBER_LEN {
+			return Err(corrupted());
+		}
+		let (number, hash) = entry_key[prefix.len()..].split_at(NUMBER_LEN);
+		let mut inverted = [0u8; NUMBER_LEN];
+		inverted.copy_from_slice(number);
+		let number = NumberFor::<Block>::unique_saturated_from(
+			u64::max_value() - u64::from_be_bytes(inverted)
+		);
+
+		// entries of forks and reverted blocks are left behind
+		match canonical_hash(number)? {
+			Some(canonical) if canonical.as_ref() == hash => (),
+			_ => continue,
+		}
+
+		let value_hash = Option::<H256>::decode(&mut &value[..]).map_err(|_| corrupted())?;
+		changes.push((number, value_hash));
+		if number <= first {
+			break;
+		}
+	}
+
+	changes.reverse();
+	Ok(changes)
+}
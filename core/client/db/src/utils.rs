@@ -20,14 +20,13 @@
 use std::sync::Arc;
 use std::{io, convert::TryInto};
 
-use kvdb::{KeyValueDB, DBTransaction};
+use database::{Database, Transaction};
 #[cfg(feature = "kvdb-rocksdb")]
-use kvdb_rocksdb::{Database, DatabaseConfig};
+use kvdb_rocksdb::DatabaseConfig;
 use log::debug;
 
 use client;
 use codec::Decode;
-use trie::DBValue;
 use sr_primitives::generic::BlockId;
 use sr_primitives::traits::{
 	Block as BlockT, Header as HeaderT, Zero,
@@ -37,7 +36,7 @@ use crate::{DatabaseSettings, DatabaseSettingsSrc};
 
 /// Number of columns in the db. Must be the same for both full && light dbs.
 /// Otherwise RocksDb will fail to open database && check its type.
-pub const NUM_COLUMNS: u32 = 10;
+pub const NUM_COLUMNS: u32 = 11;
 /// Meta column. The set of keys in the column is shared by full && light storages.
 pub const COLUMN_META: Option<u32> = Some(0);
 
@@ -57,6 +56,8 @@ pub mod meta_keys {
 	pub const LEAF_PREFIX: &[u8; 4] = b"leaf";
 	/// Children prefix list key.
 	pub const CHILDREN_PREFIX: &[u8; 8] = b"children";
+	/// Storage index marker, present if the index has been kept since genesis.
+	pub const STORAGE_INDEX: &[u8; 13] = b"storage_index";
 }
 
 /// Database metadata.
@@ -125,7 +126,7 @@ pub fn lookup_key_to_number<N>(key: &[u8]) -> client::error::Result<N> where
 
 /// Delete number to hash mapping in DB transaction.
 pub fn remove_number_to_key_mapping<N: TryInto<u32>>(
-	transaction: &mut DBTransaction,
+	transaction: &mut Transaction,
 	key_lookup_col: Option<u32>,
 	number: N,
 ) -> client::error::Result<()> {
@@ -135,7 +136,7 @@ pub fn remove_number_to_key_mapping<N: TryInto<u32>>(
 
 /// Remove key mappings.
 pub fn remove_key_mappings<N: TryInto<u32>, H: AsRef<[u8]>>(
-	transaction: &mut DBTransaction,
+	transaction: &mut Transaction,
 	key_lookup_col: Option<u32>,
 	number: N,
 	hash: H,
@@ -148,7 +149,7 @@ pub fn remove_key_mappings<N: TryInto<u32>, H: AsRef<[u8]>>(
 /// Place a number mapping into the database. This maps number to current perceived
 /// block hash at that position.
 pub fn insert_number_to_key_mapping<N: TryInto<u32> + Clone, H: AsRef<[u8]>>(
-	transaction: &mut DBTransaction,
+	transaction: &mut Transaction,
 	key_lookup_col: Option<u32>,
 	number: N,
 	hash: H,
@@ -163,7 +164,7 @@ pub fn insert_number_to_key_mapping<N: TryInto<u32> + Clone, H: AsRef<[u8]>>(
 
 /// Insert a hash to key mapping in the database.
 pub fn insert_hash_to_key_mapping<N: TryInto<u32>, H: AsRef<[u8]> + Clone>(
-	transaction: &mut DBTransaction,
+	transaction: &mut Transaction,
 	key_lookup_col: Option<u32>,
 	number: N,
 	hash: H,
@@ -180,7 +181,7 @@ pub fn insert_hash_to_key_mapping<N: TryInto<u32>, H: AsRef<[u8]> + Clone>(
 /// block lookup key is the DB-key header, block and justification are stored under.
 /// looks up lookup key by hash from DB as necessary.
 pub fn block_id_to_lookup_key<Block>(
-	db: &dyn KeyValueDB,
+	db: &dyn Database,
 	key_lookup_col: Option<u32>,
 	id: BlockId<Block>
 ) -> Result<Option<Vec<u8>>, client::error::Error> where
@@ -195,7 +196,7 @@ pub fn block_id_to_lookup_key<Block>(
 		BlockId::Hash(h) => db.get(key_lookup_col, h.as_ref()),
 	};
 
-	res.map(|v| v.map(|v| v.into_vec())).map_err(db_err)
+	res.map_err(db_err)
 }
 
 /// Maps database error to client error
@@ -203,25 +204,33 @@ pub fn db_err(err: io::Error) -> client::error::Error {
 	client::error::Error::Backend(format!("{}", err))
 }
 
-/// Open RocksDB database.
+/// Open the database of the given settings.
 pub fn open_database(
 	config: &DatabaseSettings,
 	col_meta: Option<u32>,
 	db_type: &str
-) -> client::error::Result<Arc<dyn KeyValueDB>> {
-	let db: Arc<dyn KeyValueDB> = match &config.source {
+) -> client::error::Result<Arc<dyn Database>> {
+	let db: Arc<dyn Database> = match &config.source {
 		#[cfg(feature = "kvdb-rocksdb")]
 		DatabaseSettingsSrc::Path { path, cache_size } => {
 			let mut db_config = DatabaseConfig::with_columns(Some(NUM_COLUMNS));
 			db_config.memory_budget = *cache_size;
 			let path = path.to_str().ok_or_else(|| client::error::Error::Backend("Invalid database path".into()))?;
-			Arc::new(Database::open(&db_config, &path).map_err(db_err)?)
+			database::as_seekable_database(kvdb_rocksdb::Database::open(&db_config, &path).map_err(db_err)?)
 		},
 		#[cfg(not(feature = "kvdb-rocksdb"))]
 		DatabaseSettingsSrc::Path { .. } => {
 			let msg = "Try to open RocksDB database with RocksDB disabled".into();
 			return Err(client::error::Error::Backend(msg));
 		},
+		#[cfg(feature = "sled")]
+		DatabaseSettingsSrc::Sled { path, flush_on_commit } =>
+			Arc::new(database::SledDb::open(path, *flush_on_commit).map_err(db_err)?),
+		#[cfg(not(feature = "sled"))]
+		DatabaseSettingsSrc::Sled { .. } => {
+			let msg = "Try to open sled database with sled disabled".into();
+			return Err(client::error::Error::Backend(msg));
+		},
 		DatabaseSettingsSrc::Custom(db) => db.clone(),
 	};
 
@@ -234,22 +243,56 @@ pub fn open_database(
 			}
 		},
 		None => {
-			let mut transaction = DBTransaction::new();
+			let mut transaction = Transaction::new();
 			transaction.put(col_meta, meta_keys::TYPE, db_type.as_bytes());
-			db.write(transaction).map_err(db_err)?;
+			db.commit(transaction).map_err(db_err)?;
 		},
 	}
 
 	Ok(db)
 }
 
+#[cfg(all(feature = "sled", any(test, feature = "test-helpers")))]
+thread_local! {
+	/// Whether `new_test_database` creates a `sled` database instead of an in-memory one.
+	static SLED_TEST_DATABASE: std::cell::Cell<bool> = std::cell::Cell::new(false);
+}
+
+/// Create an empty database for tests.
+///
+/// The database is kept in memory, unless it is created by a run of `for_each_test_database`
+/// against another backend.
+#[cfg(any(test, feature = "test-helpers"))]
+pub fn new_test_database() -> Arc<dyn Database> {
+	#[cfg(feature = "sled")]
+	{
+		if SLED_TEST_DATABASE.with(|sled| sled.get()) {
+			return Arc::new(database::SledDb::temporary().expect("failed to create test-db"));
+		}
+	}
+	database::as_database(kvdb_memorydb::create(NUM_COLUMNS))
+}
+
+/// Run `f` once for each database backend available, with `new_test_database` creating the
+/// databases of that backend.
+#[cfg(any(test, feature = "test-helpers"))]
+pub fn for_each_test_database(mut f: impl FnMut()) {
+	f();
+	#[cfg(feature = "sled")]
+	{
+		SLED_TEST_DATABASE.with(|sled| sled.set(true));
+		f();
+		SLED_TEST_DATABASE.with(|sled| sled.set(false));
+	}
+}
+
 /// Read database column entry for the given block.
 pub fn read_db<Block>(
-	db: &dyn KeyValueDB,
+	db: &dyn Database,
 	col_index: Option<u32>,
 	col: Option<u32>,
 	id: BlockId<Block>
-) -> client::error::Result<Option<DBValue>>
+) -> client::error::Result<Option<Vec<u8>>>
 	where
 		Block: BlockT,
 {
@@ -261,7 +304,7 @@ pub fn read_db<Block>(
 
 /// Read a header from the database.
 pub fn read_header<Block: BlockT>(
-	db: &dyn KeyValueDB,
+	db: &dyn Database,
 	col_index: Option<u32>,
 	col: Option<u32>,
 	id: BlockId<Block>,
@@ -279,7 +322,7 @@ pub fn read_header<Block: BlockT>(
 
 /// Required header from the database.
 pub fn require_header<Block: BlockT>(
-	db: &dyn KeyValueDB,
+	db: &dyn Database,
 	col_index: Option<u32>,
 	col: Option<u32>,
 	id: BlockId<Block>,
@@ -289,7 +332,7 @@ pub fn require_header<Block: BlockT>(
 }
 
 /// Read meta from the database.
-pub fn read_meta<Block>(db: &dyn KeyValueDB, col_meta: Option<u32>, col_header: Option<u32>) -> Result<
+pub fn read_meta<Block>(db: &dyn Database, col_meta: Option<u32>, col_header: Option<u32>) -> Result<
 	Meta<<<Block as BlockT>::Header as HeaderT>::Number, Block::Hash>,
 	client::error::Error,
 >
@@ -217,6 +217,8 @@ pub trait Backend<Block, H>: AuxStore + Send + Sync where
 	fn blockchain(&self) -> &Self::Blockchain;
 	/// Returns the used state cache, if existent.
 	fn used_state_cache_size(&self) -> Option<usize>;
+	/// Returns the number of hits and misses of the state cache, if existent.
+	fn state_cache_hit_stats(&self) -> Option<(u64, u64)>;
 	/// Returns reference to changes trie storage.
 	fn changes_trie_storage(&self) -> Option<&Self::ChangesTrieStorage>;
 	/// Returns a handle to offchain storage.
@@ -234,6 +236,14 @@ pub trait Backend<Block, H>: AuxStore + Send + Sync where
 	/// Attempts to revert the chain by `n` blocks. Returns the number of blocks that were
 	/// successfully reverted.
 	fn revert(&self, n: NumberFor<Block>) -> error::Result<NumberFor<Block>>;
+	/// Writes the complete state of an already imported block, which was imported without being
+	/// executed. Fails if the root of the given storage does not match the block header.
+	fn import_state(
+		&self,
+		block: BlockId<Block>,
+		top: StorageOverlay,
+		children: ChildrenStorageOverlay,
+	) -> error::Result<()>;
 
 	/// Insert auxiliary data into key-value store.
 	fn insert_aux<
@@ -257,6 +267,33 @@ pub trait Backend<Block, H>: AuxStore + Send + Sync where
 	/// something that the import of a block would interfere with, e.g. importing
 	/// a new block or calculating the best head.
 	fn get_import_lock(&self) -> &Mutex<()>;
+
+	/// Returns the changes of the top-level storage `key` in the canonical blocks `first..=last`,
+	/// or `None` if the backend doesn't keep a storage index.
+	///
+	/// The first change is the latest one at or before `first`, if any. Every change is the
+	/// number of the block and the hash of the new value, or `None` if the key was removed.
+	fn indexed_storage_changes(
+		&self,
+		_key: &[u8],
+		_first: NumberFor<Block>,
+		_last: NumberFor<Block>,
+	) -> error::Result<Option<Vec<(NumberFor<Block>, Option<H::Out>)>>> {
+		Ok(None)
+	}
+
+	/// Registers a consensus engine that needs some of the justifications to be kept.
+	///
+	/// Backends that prune the justifications of old finalized blocks keep those that any of the
+	/// registered engines asks for. Other backends ignore it.
+	fn register_justification_retention(&self, _retention: Arc<dyn JustificationRetention<Block>>) {}
+}
+
+/// Tells which justifications of old finalized blocks are still needed by a consensus engine,
+/// e.g. to prove its authority set changes to syncing nodes.
+pub trait JustificationRetention<Block: BlockT>: Send + Sync {
+	/// Whether the justification of the block with the given header must be kept.
+	fn keep_justification(&self, header: &Block::Header) -> bool;
 }
 
 /// Offchain workers local storage.
@@ -277,6 +314,26 @@ pub trait OffchainStorage: Clone + Send + Sync {
 		old_value: Option<&[u8]>,
 		new_value: &[u8],
 	) -> bool;
+
+	/// Persist a value in the fork-aware storage of the given block.
+	///
+	/// The value is visible from `block` and its descendants, but not from other forks.
+	fn set_local(&mut self, block: &[u8], key: &[u8], value: &[u8]);
+
+	/// Retrieve a value from the fork-aware storage, as seen from the given block.
+	fn get_local(&self, block: &[u8], key: &[u8]) -> Option<Vec<u8>>;
+
+	/// Replace the value in the fork-aware storage of the given block if `old_value` matches the
+	/// value seen from that block.
+	///
+	/// Returns `true` if the value has been set and false otherwise.
+	fn compare_and_set_local(
+		&mut self,
+		block: &[u8],
+		key: &[u8],
+		old_value: Option<&[u8]>,
+		new_value: &[u8],
+	) -> bool;
 }
 
 /// Changes trie storage that supports pruning.
@@ -16,7 +16,7 @@
 
 //! Functionality for reading and storing children hashes from db.
 
-use kvdb::{KeyValueDB, DBTransaction};
+use database::{Database, Transaction};
 use codec::{Encode, Decode};
 use crate::error;
 use std::hash::Hash;
@@ -26,7 +26,7 @@ use std::hash::Hash;
 pub fn read_children<
 	K: Eq + Hash + Clone + Encode + Decode,
 	V: Eq + Hash + Clone + Encode + Decode,
->(db: &dyn KeyValueDB, column: Option<u32>, prefix: &[u8], parent_hash: K) -> error::Result<Vec<V>> {
+>(db: &dyn Database, column: Option<u32>, prefix: &[u8], parent_hash: K) -> error::Result<Vec<V>> {
 	let mut buf = prefix.to_vec();
 	parent_hash.using_encoded(|s| buf.extend(s));
 
@@ -54,7 +54,7 @@ pub fn write_children<
 	K: Eq + Hash + Clone + Encode + Decode,
 	V: Eq + Hash + Clone + Encode + Decode,
 >(
-	tx: &mut DBTransaction,
+	tx: &mut Transaction,
 	column: Option<u32>,
 	prefix: &[u8],
 	parent_hash: K,
@@ -69,7 +69,7 @@ pub fn write_children<
 pub fn remove_children<
 	K: Eq + Hash + Clone + Encode + Decode,
 >(
-	tx: &mut DBTransaction,
+	tx: &mut Transaction,
 	column: Option<u32>,
 	prefix: &[u8],
 	parent_hash: K,
@@ -87,9 +87,9 @@ mod tests {
 	#[test]
 	fn children_write_read_remove() {
 		const PREFIX: &[u8] = b"children";
-		let db = ::kvdb_memorydb::create(0);
+		let db = ::database::MemDb::new(0);
 
-		let mut tx = DBTransaction::new();
+		let mut tx = Transaction::new();
 
 		let mut children1 = Vec::new();
 		children1.push(1_3);
@@ -101,7 +101,7 @@ mod tests {
 		children2.push(1_6);
 		write_children(&mut tx, None, PREFIX, 1_2, children2);
 
-		db.write(tx.clone()).unwrap();
+		db.commit(tx.clone()).unwrap();
 
 		let r1: Vec<u32> = read_children(&db, None, PREFIX, 1_1).unwrap();
 		let r2: Vec<u32> = read_children(&db, None, PREFIX, 1_2).unwrap();
@@ -110,7 +110,7 @@ mod tests {
 		assert_eq!(r2, vec![1_4, 1_6]);
 
 		remove_children(&mut tx, None, PREFIX, 1_2);
-		db.write(tx).unwrap();
+		db.commit(tx).unwrap();
 
 		let r1: Vec<u32> = read_children(&db, None, PREFIX, 1_1).unwrap();
 		let r2: Vec<u32> = read_children(&db, None, PREFIX, 1_2).unwrap();
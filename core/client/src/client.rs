@@ -32,7 +32,7 @@ use primitives::{
 };
 use substrate_telemetry::{telemetry, SUBSTRATE_INFO};
 use sr_primitives::{
-	Justification, BuildStorage,
+	Justification, BuildStorage, StorageOverlay, ChildrenStorageOverlay,
 	generic::{BlockId, SignedBlock, DigestItem},
 	traits::{
 		Block as BlockT, Header as HeaderT, Zero, NumberFor,
@@ -41,9 +41,9 @@ use sr_primitives::{
 };
 use state_machine::{
 	DBValue, Backend as StateBackend, ChangesTrieAnchorBlockId, ExecutionStrategy, ExecutionManager,
-	prove_read, prove_child_read, ChangesTrieRootsStorage, ChangesTrieStorage,
-	ChangesTrieTransaction, ChangesTrieConfigurationRange, key_changes, key_changes_proof,
-	OverlayedChanges, BackendTrustLevel, StorageProof, merge_storage_proofs,
+	prove_read, prove_child_read, prove_range_read_with_size, ChangesTrieRootsStorage,
+	ChangesTrieStorage, ChangesTrieTransaction, ChangesTrieConfigurationRange, key_changes,
+	key_changes_proof, OverlayedChanges, BackendTrustLevel, StorageProof, merge_storage_proofs,
 };
 use executor::{RuntimeVersion, RuntimeInfo};
 use consensus::{
@@ -60,7 +60,7 @@ use crate::{
 	},
 	backend::{
 		self, BlockImportOperation, PrunableStateChangesTrieStorage,
-		ClientImportOperation, Finalizer, ImportSummary,
+		ClientImportOperation, Finalizer, ImportSummary, JustificationRetention,
 	},
 	blockchain::{
 		self, Info as ChainInfo, Backend as ChainBackend,
@@ -176,6 +176,8 @@ pub struct ClientInfo<Block: BlockT> {
 	pub chain: ChainInfo<Block>,
 	/// State Cache Size currently used by the backend
 	pub used_state_cache_size: Option<usize>,
+	/// State cache hits and misses counted by the backend
+	pub state_cache_hit_stats: Option<(u64, u64)>,
 }
 
 /// Summary of an imported block
@@ -348,6 +350,15 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 		Ok(keys)
 	}
 
+	/// Given a `BlockId` and a key prefix, return the matching key-value pairs in that block.
+	pub fn storage_pairs(
+		&self,
+		id: &BlockId<Block>,
+		key_prefix: &StorageKey
+	) -> error::Result<Vec<(StorageKey, StorageData)>> {
+		self.storage_pairs_in(id, None, key_prefix)
+	}
+
 	/// Given a `BlockId` and a key, return the value under the key in that block.
 	pub fn storage(&self, id: &BlockId<Block>, key: &StorageKey) -> error::Result<Option<StorageData>> {
 		Ok(self.state_at(id)?
@@ -379,6 +390,37 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 		Ok(keys)
 	}
 
+	/// Given a `BlockId`, a key prefix, and a child storage key, return the matching child storage
+	/// key-value pairs.
+	pub fn child_storage_pairs(
+		&self,
+		id: &BlockId<Block>,
+		child_storage_key: &StorageKey,
+		key_prefix: &StorageKey
+	) -> error::Result<Vec<(StorageKey, StorageData)>> {
+		self.storage_pairs_in(id, Some(child_storage_key), key_prefix)
+	}
+
+	/// Read the key-value pairs of the top-level trie, or of a child trie, in a single pass over
+	/// the trie, failing if any part of it can not be read.
+	fn storage_pairs_in(
+		&self,
+		id: &BlockId<Block>,
+		child_storage_key: Option<&StorageKey>,
+		key_prefix: &StorageKey,
+	) -> error::Result<Vec<(StorageKey, StorageData)>> {
+		let mut state = self.state_at(id)?;
+		let trie_state = state.as_trie_backend()
+			.ok_or_else(|| error::Error::Backend("State can not be iterated".into()))?;
+		let mut pairs = Vec::new();
+		trie_state.essence().try_for_key_values_with_prefix(
+			child_storage_key.map(|key| &key.0[..]),
+			&key_prefix.0,
+			|key, value| pairs.push((StorageKey(key.to_vec()), StorageData(value.to_vec()))),
+		).map_err(error::Error::Backend)?;
+		Ok(pairs)
+	}
+
 	/// Given a `BlockId`, a key and a child storage key, return the value under the key in that block.
 	pub fn child_storage(
 		&self,
@@ -420,6 +462,12 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 		&self.executor
 	}
 
+	/// Registers a consensus engine that needs some of the justifications to be kept when the
+	/// backend prunes those of old finalized blocks.
+	pub fn register_justification_retention(&self, retention: Arc<dyn JustificationRetention<Block>>) {
+		self.backend.register_justification_retention(retention)
+	}
+
 	/// Reads storage value at a given block + key, returning read proof.
 	pub fn read_proof<I>(&self, id: &BlockId<Block>, keys: I) -> error::Result<StorageProof> where
 		I: IntoIterator,
@@ -446,6 +494,24 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 				.map_err(Into::into))
 	}
 
+	/// Reads a chunk of the state at a given block, returning a range proof of the chunk.
+	///
+	/// The chunk contains the key-value pairs with keys strictly greater than `start`, in key
+	/// order, until their total size reaches `max_size`. The returned flag is true when the
+	/// chunk reaches the end of the state. When `child_storage_key` is given, the chunk is read
+	/// from that child trie instead of the top-level trie.
+	pub fn read_state_chunk(
+		&self,
+		id: &BlockId<Block>,
+		child_storage_key: Option<&[u8]>,
+		start: &[u8],
+		max_size: usize,
+	) -> error::Result<(Vec<(Vec<u8>, Vec<u8>)>, bool, StorageProof)> {
+		self.state_at(id)
+			.and_then(|state| prove_range_read_with_size(state, child_storage_key, start, max_size)
+				.map_err(Into::into))
+	}
+
 	/// Execute a call to a contract on top of state in a block of given hash
 	/// AND returning execution proof.
 	///
@@ -554,6 +620,47 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 		.map_err(|err| error::Error::ChangesTrieAccessFailed(err))
 	}
 
+	/// Get pairs of (block, value hash) where key has been changed at given canonical blocks range,
+	/// starting with the latest change at or before `first`.
+	/// Works only for backends that are keeping a storage index, returns `None` otherwise.
+	pub fn indexed_storage_changes(
+		&self,
+		first: NumberFor<Block>,
+		last: NumberFor<Block>,
+		key: &StorageKey,
+	) -> error::Result<Option<Vec<(NumberFor<Block>, Option<H256>)>>> {
+		self.backend.indexed_storage_changes(&key.0, first, last)
+	}
+
+	/// Get the hash of the value of `key` at the canonical block `number` from the storage index.
+	/// Works only for backends that are keeping a storage index, returns `None` otherwise.
+	pub fn indexed_storage_hash(
+		&self,
+		number: NumberFor<Block>,
+		key: &StorageKey,
+	) -> error::Result<Option<Option<H256>>> {
+		Ok(self.indexed_storage_changes(number, number, key)?
+			.map(|changes| changes.last().and_then(|(_, value_hash)| value_hash.clone())))
+	}
+
+	/// Get the value of `key` at the canonical block `number`, reading it at the block where the
+	/// storage index last saw it change.
+	/// Works only for backends that are keeping a storage index, returns `None` otherwise.
+	pub fn indexed_storage(
+		&self,
+		number: NumberFor<Block>,
+		key: &StorageKey,
+	) -> error::Result<Option<Option<StorageData>>> {
+		let changes = match self.indexed_storage_changes(number, number, key)? {
+			Some(changes) => changes,
+			None => return Ok(None),
+		};
+		match changes.last() {
+			Some((changed_at, Some(_))) => self.storage(&BlockId::Number(*changed_at), key).map(Some),
+			_ => Ok(Some(None)),
+		}
+	}
+
 	/// Get proof for computation of (block, extrinsic) pairs where key has been changed at given blocks range.
 	/// `min` is the hash of the first block, which changes trie root is known to the requester - when we're using
 	/// changes tries from ascendants of this block, we should provide proofs for changes tries roots
@@ -838,6 +945,7 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 			finalized,
 			auxiliary,
 			fork_choice,
+			skip_execution,
 		} = import_block;
 
 		assert!(justification.is_some() && finalized || justification.is_none());
@@ -875,6 +983,7 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 			finalized,
 			auxiliary,
 			fork_choice,
+			skip_execution,
 		);
 
 		if let Ok(ImportResult::Imported(ref aux)) = result {
@@ -902,6 +1011,7 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 		finalized: bool,
 		aux: Vec<(Vec<u8>, Option<Vec<u8>>)>,
 		fork_choice: ForkChoiceStrategy,
+		skip_execution: bool,
 	) -> error::Result<ImportResult> where
 		E: CallExecutor<Block, Blake2Hasher> + Send + Sync + Clone,
 	{
@@ -935,14 +1045,20 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 			self.apply_finality_with_block_hash(operation, parent_hash, None, info.best_hash, make_notifications)?;
 		}
 
+		// blocks imported by warp sync are not executed and have no state until it is
+		// imported separately.
 		// FIXME #1232: correct path logic for when to execute this function
-		let (storage_update, changes_update, storage_changes) = self.block_execution(
-			&operation.op,
-			&import_headers,
-			origin,
-			hash,
-			body.clone(),
-		)?;
+		let (storage_update, changes_update, storage_changes) = if skip_execution {
+			(None, None, None)
+		} else {
+			self.block_execution(
+				&operation.op,
+				&import_headers,
+				origin,
+				hash,
+				body.clone(),
+			)?
+		};
 
 		let is_new_best = finalized || match fork_choice {
 			ForkChoiceStrategy::LongestChain => import_headers.post().number() > &info.best_number,
@@ -1204,12 +1320,38 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 		Ok(self.backend.revert(n)?)
 	}
 
+	/// Import the state of the last finalized block, which has been imported without being
+	/// executed, e.g. because its state was downloaded from the network.
+	///
+	/// The blocks above it, which have been imported without state as well, are reverted so that
+	/// they are executed when imported again.
+	pub fn import_state(
+		&self,
+		hash: Block::Hash,
+		top: StorageOverlay,
+		children: ChildrenStorageOverlay,
+	) -> error::Result<()> {
+		let _import_lock = self.backend.get_import_lock().lock();
+		let info = self.backend.blockchain().info();
+		if info.finalized_hash != hash {
+			return Err(error::Error::Msg(
+				format!("Cannot import state of {}: not the last finalized block", hash)
+			));
+		}
+
+		self.backend.import_state(BlockId::Hash(hash), top, children)?;
+		let reverted = self.backend.revert(info.best_number - info.finalized_number)?;
+		info!("Imported state of block #{} ({}), reverted {} blocks", info.finalized_number, hash, reverted);
+		Ok(())
+	}
+
 	/// Get blockchain info.
 	pub fn info(&self) -> ClientInfo<Block> {
 		let info = self.backend.blockchain().info();
 		ClientInfo {
 			chain: info,
 			used_state_cache_size: self.backend.used_state_cache_size(),
+			state_cache_hit_stats: self.backend.state_cache_hit_stats(),
 		}
 	}
 
@@ -1871,7 +2013,7 @@ pub(crate) mod tests {
 	use consensus::{BlockOrigin, SelectChain};
 	use test_client::{
 		prelude::*,
-		client_db::{Backend, DatabaseSettings, DatabaseSettingsSrc, PruningMode},
+		client_db::{Backend, BlocksPruning, DatabaseSettings, DatabaseSettingsSrc, PruningMode},
 		runtime::{self, Block, Transfer, RuntimeApi, TestAPI},
 	};
 
@@ -2761,7 +2903,9 @@ pub(crate) mod tests {
 				source: DatabaseSettingsSrc::Path {
 					path: tmp.path().into(),
 					cache_size: None,
-				}
+				},
+				storage_index: false,
+				blocks_pruning: BlocksPruning::KeepAll,
 			},
 			u64::max_value(),
 		).unwrap());
@@ -2838,4 +2982,80 @@ pub(crate) mod tests {
 			expected_err.to_string(),
 		);
 	}
+
+	#[test]
+	fn imports_state_of_blocks_imported_without_body() {
+		let _ = env_logger::try_init();
+		let tmp = tempfile::tempdir().unwrap();
+
+		let backend = Arc::new(Backend::new(
+			DatabaseSettings {
+				state_cache_size: 1 << 20,
+				state_cache_child_ratio: None,
+				pruning: PruningMode::ArchiveAll,
+				source: DatabaseSettingsSrc::Path {
+					path: tmp.path().into(),
+					cache_size: None,
+				},
+				storage_index: false,
+				blocks_pruning: BlocksPruning::KeepAll,
+			},
+			u64::max_value(),
+		).unwrap());
+		let client = TestClientBuilder::with_backend(backend).build();
+		let source = test_client::new();
+
+		// G -> A1 -> A2
+		let mut a1 = source.new_block(Default::default()).unwrap();
+		a1.push_transfer(Transfer {
+			from: AccountKeyring::Alice.into(),
+			to: AccountKeyring::Bob.into(),
+			amount: 10,
+			nonce: 0,
+		}).unwrap();
+		let a1 = a1.bake().unwrap();
+		source.import(BlockOrigin::Own, a1.clone()).unwrap();
+		let a2 = source.new_block_at(&BlockId::Hash(a1.hash()), Default::default()).unwrap().bake().unwrap();
+		source.import(BlockOrigin::Own, a2.clone()).unwrap();
+
+		let import_without_body = |block: Block, skip_execution| {
+			let (header, _) = block.deconstruct();
+			let import = BlockImportParams {
+				origin: BlockOrigin::NetworkInitialSync,
+				header,
+				justification: None,
+				post_digests: vec![],
+				body: None,
+				finalized: false,
+				auxiliary: Vec::new(),
+				fork_choice: ForkChoiceStrategy::LongestChain,
+				skip_execution,
+			};
+			consensus::BlockImport::import_block(&mut (&client), import, HashMap::new())
+		};
+
+		// blocks without body are still executed, unless warp sync asks otherwise
+		assert!(import_without_body(a1.clone(), false).is_err());
+		assert_eq!(client.info().chain.best_number, 0);
+		for block in vec![a1.clone(), a2.clone()] {
+			import_without_body(block, true).unwrap();
+		}
+		client.finalize_block(BlockId::Hash(a1.hash()), None).unwrap();
+		let code = StorageKey(well_known_keys::CODE.to_vec());
+		assert!(client.storage(&BlockId::Hash(a1.hash()), &code).is_err());
+
+		let state = source.state_at(&BlockId::Hash(a1.hash())).unwrap().pairs().into_iter().collect();
+		client.import_state(a1.hash(), state, Default::default()).unwrap();
+
+		// the blocks above the imported state are reverted
+		assert_eq!(client.info().chain.best_hash, a1.hash());
+		assert_eq!(
+			client.runtime_api().balance_of(&BlockId::Hash(a1.hash()), AccountKeyring::Alice.into()).unwrap(),
+			990,
+		);
+
+		// and executed on top of it when imported again
+		client.import(BlockOrigin::NetworkInitialSync, a2.clone()).unwrap();
+		assert_eq!(client.info().chain.best_hash, a2.hash());
+	}
 }
@@ -37,6 +37,9 @@ pub enum Error {
 	/// Unknown block.
 	#[display(fmt = "UnknownBlock: {}", _0)]
 	UnknownBlock(String),
+	/// Block body has been pruned.
+	#[display(fmt = "Block body has been pruned: {}", _0)]
+	BlockBodyPruned(String),
 	/// Applying extrinsic error.
 	#[display(fmt = "Extrinsic error: {:?}", _0)]
 	ApplyExtrinsicFailed(ApplyError),
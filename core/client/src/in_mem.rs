@@ -682,6 +682,10 @@ where
 		None
 	}
 
+	fn state_cache_hit_stats(&self) -> Option<(u64, u64)> {
+		None
+	}
+
 	fn changes_trie_storage(&self) -> Option<&Self::ChangesTrieStorage> {
 		Some(&self.changes_trie_storage)
 	}
@@ -708,6 +712,25 @@ where
 		Ok(Zero::zero())
 	}
 
+	fn import_state(
+		&self,
+		block: BlockId<Block>,
+		top: StorageOverlay,
+		children: ChildrenStorageOverlay,
+	) -> error::Result<()> {
+		let header = self.blockchain.header(block)?
+			.ok_or_else(|| error::Error::UnknownBlock(format!("{}", block)))?;
+		let mut operation = self.begin_operation()?;
+		let root = backend::BlockImportOperation::reset_storage(&mut operation, top, children)?;
+		if root != *header.state_root() {
+			return Err(error::Error::InvalidStateRoot);
+		}
+
+		let state = operation.new_state.expect("`reset_storage` always sets the new state; qed");
+		self.states.write().insert(header.hash(), state);
+		Ok(())
+	}
+
 	fn get_import_lock(&self) -> &Mutex<()> {
 		&self.import_lock
 	}
@@ -806,9 +829,23 @@ pub fn check_genesis_storage(top: &StorageOverlay, children: &ChildrenStorageOve
 }
 
 /// In-memory storage for offchain workers.
+///
+/// The fork-aware storage only knows the ancestry of the blocks registered with
+/// [`OffchainStorage::note_block`], values written at other blocks are only visible from the
+/// block itself.
 #[derive(Debug, Clone, Default)]
 pub struct OffchainStorage {
 	storage: HashMap<Vec<u8>, Vec<u8>>,
+	local: HashMap<(Vec<u8>, Vec<u8>), Vec<u8>>,
+	parents: HashMap<Vec<u8>, Vec<u8>>,
+}
+
+impl OffchainStorage {
+	/// Register `parent` as the parent of `block`, making the values written on `parent` and its
+	/// ancestors visible from `block`.
+	pub fn note_block(&mut self, block: &[u8], parent: &[u8]) {
+		self.parents.insert(block.to_vec(), parent.to_vec());
+	}
 }
 
 impl backend::OffchainStorage for OffchainStorage {
@@ -844,6 +881,34 @@ impl backend::OffchainStorage for OffchainStorage {
 			_ => false,
 		}
 	}
+
+	fn set_local(&mut self, block: &[u8], key: &[u8], value: &[u8]) {
+		self.local.insert((block.to_vec(), key.to_vec()), value.to_vec());
+	}
+
+	fn get_local(&self, block: &[u8], key: &[u8]) -> Option<Vec<u8>> {
+		let mut block = block.to_vec();
+		loop {
+			if let Some(value) = self.local.get(&(block.clone(), key.to_vec())) {
+				return Some(value.clone())
+			}
+			block = self.parents.get(&block)?.clone();
+		}
+	}
+
+	fn compare_and_set_local(
+		&mut self,
+		block: &[u8],
+		key: &[u8],
+		old_value: Option<&[u8]>,
+		new_value: &[u8],
+	) -> bool {
+		let is_set = self.get_local(block, key).as_ref().map(|v| v.as_slice()) == old_value;
+		if is_set {
+			self.set_local(block, key, new_value);
+		}
+		is_set
+	}
 }
 
 #[cfg(test)]
@@ -890,4 +955,27 @@ mod tests {
 		assert!(storage.compare_and_set(b"B", b"A", None, b"X"));
 		assert_eq!(storage.get(b"B", b"A"), Some(b"X".to_vec()));
 	}
+
+	#[test]
+	fn in_memory_offchain_local_storage_is_fork_aware() {
+		use crate::backend::OffchainStorage as _;
+
+		// 1 - 2a
+		//   \ 2b
+		let mut storage = OffchainStorage::default();
+		storage.note_block(b"2a", b"1");
+		storage.note_block(b"2b", b"1");
+
+		storage.set_local(b"1", b"key", b"1");
+		assert_eq!(storage.get_local(b"2a", b"key"), Some(b"1".to_vec()));
+		assert_eq!(storage.get(b"", b"key"), None);
+
+		assert!(storage.compare_and_set_local(b"2a", b"key", Some(b"1"), b"2a"));
+		assert_eq!(storage.get_local(b"2a", b"key"), Some(b"2a".to_vec()));
+		assert_eq!(storage.get_local(b"2b", b"key"), Some(b"1".to_vec()));
+		assert_eq!(storage.get_local(b"1", b"key"), Some(b"1".to_vec()));
+
+		assert!(!storage.compare_and_set_local(b"2b", b"key", Some(b"2a"), b"2b"));
+		assert_eq!(storage.get_local(b"3", b"key"), None);
+	}
 }
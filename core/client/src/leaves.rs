@@ -18,7 +18,7 @@
 
 use std::collections::BTreeMap;
 use std::cmp::Reverse;
-use kvdb::{KeyValueDB, DBTransaction};
+use database::{Database, Transaction};
 use sr_primitives::traits::SimpleArithmetic;
 use codec::{Encode, Decode};
 use crate::error;
@@ -51,6 +51,11 @@ impl<H, N: Ord> FinalizationDisplaced<H, N> {
 		// one transaction, then there will be no overlap in the keys.
 		self.leaves.append(&mut other.leaves);
 	}
+
+	/// Iterator over all the displaced leaves.
+	pub fn leaves(&self) -> impl Iterator<Item = &H> {
+		self.leaves.values().flatten()
+	}
 }
 
 /// list of leaf hashes ordered by number (descending).
@@ -77,10 +82,11 @@ impl<H, N> LeafSet<H, N> where
 	}
 
 	/// Read the leaf list from the DB, using given prefix for keys.
-	pub fn read_from_db(db: &dyn KeyValueDB, column: Option<u32>, prefix: &[u8]) -> error::Result<Self> {
+	pub fn read_from_db(db: &dyn Database, column: Option<u32>, prefix: &[u8]) -> error::Result<Self> {
 		let mut storage = BTreeMap::new();
 
-		for (key, value) in db.iter_from_prefix(column, prefix) {
+		for pair in db.iter_from_prefix(column, prefix) {
+			let (key, value) = pair.map_err(|e| error::Error::Backend(format!("{}", e)))?;
 			if !key.starts_with(prefix) { break }
 			let raw_hash = &mut &key[prefix.len()..];
 			let hash = match Decode::decode(raw_hash) {
@@ -173,7 +179,7 @@ impl<H, N> LeafSet<H, N> where
 	}
 
 	/// Write the leaf list to the database transaction.
-	pub fn prepare_transaction(&mut self, tx: &mut DBTransaction, column: Option<u32>, prefix: &[u8]) {
+	pub fn prepare_transaction(&mut self, tx: &mut Transaction, column: Option<u32>, prefix: &[u8]) {
 		let mut buf = prefix.to_vec();
 		for LeafSetItem { hash, number } in self.pending_added.drain(..) {
 			hash.using_encoded(|s| buf.extend(s));
@@ -277,7 +283,7 @@ mod tests {
 	#[test]
 	fn flush_to_disk() {
 		const PREFIX: &[u8] = b"abcdefg";
-		let db = ::kvdb_memorydb::create(0);
+		let db = ::database::MemDb::new(0);
 
 		let mut set = LeafSet::new();
 		set.import(0u32, 0u32, 0u32);
@@ -286,10 +292,10 @@ mod tests {
 		set.import(2_1, 2, 1_1);
 		set.import(3_1, 3, 2_1);
 
-		let mut tx = DBTransaction::new();
+		let mut tx = Transaction::new();
 
 		set.prepare_transaction(&mut tx, None, PREFIX);
-		db.write(tx).unwrap();
+		db.commit(tx).unwrap();
 
 		let set2 = LeafSet::read_from_db(&db, None, PREFIX).unwrap();
 		assert_eq!(set, set2);
@@ -311,7 +317,7 @@ mod tests {
 	#[test]
 	fn finalization_consistent_with_disk() {
 		const PREFIX: &[u8] = b"prefix";
-		let db = ::kvdb_memorydb::create(0);
+		let db = ::database::MemDb::new(0);
 
 		let mut set = LeafSet::new();
 		set.import(10_1u32, 10u32, 0u32);
@@ -321,14 +327,14 @@ mod tests {
 
 		assert!(set.contains(10, 10_1));
 
-		let mut tx = DBTransaction::new();
+		let mut tx = Transaction::new();
 		set.prepare_transaction(&mut tx, None, PREFIX);
-		db.write(tx).unwrap();
+		db.commit(tx).unwrap();
 
 		let _ = set.finalize_height(11);
-		let mut tx = DBTransaction::new();
+		let mut tx = Transaction::new();
 		set.prepare_transaction(&mut tx, None, PREFIX);
-		db.write(tx).unwrap();
+		db.commit(tx).unwrap();
 
 		assert!(set.contains(11, 11_1));
 		assert!(set.contains(11, 11_2));
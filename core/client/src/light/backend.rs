@@ -181,6 +181,10 @@ impl<S, Block, H> ClientBackend<Block, H> for Backend<S, H> where
 		None
 	}
 
+	fn state_cache_hit_stats(&self) -> Option<(u64, u64)> {
+		None
+	}
+
 	fn changes_trie_storage(&self) -> Option<&Self::ChangesTrieStorage> {
 		None
 	}
@@ -208,6 +212,15 @@ impl<S, Block, H> ClientBackend<Block, H> for Backend<S, H> where
 		Err(ClientError::NotAvailableOnLightClient)
 	}
 
+	fn import_state(
+		&self,
+		_block: BlockId<Block>,
+		_top: StorageOverlay,
+		_children: ChildrenStorageOverlay,
+	) -> ClientResult<()> {
+		Err(ClientError::NotAvailableOnLightClient)
+	}
+
 	fn get_import_lock(&self) -> &Mutex<()> {
 		&self.import_lock
 	}
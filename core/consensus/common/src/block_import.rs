@@ -133,6 +133,10 @@ pub struct BlockImportParams<Block: BlockT> {
 	/// Fork choice strategy of this import. This should only be set by a
 	/// synchronous import, otherwise it may race against other imports.
 	pub fork_choice: ForkChoiceStrategy,
+	/// Import the block without executing it, leaving it without state.
+	///
+	/// Only set by warp sync, for the blocks below the state it downloads.
+	pub skip_execution: bool,
 }
 
 impl<Block: BlockT> BlockImportParams<Block> {
@@ -27,6 +27,7 @@
 
 use std::collections::HashMap;
 use sr_primitives::{Justification, traits::{Block as BlockT, Header as _, NumberFor}};
+use substrate_prometheus::{Registry, Error as PrometheusError};
 use crate::error::Error as ConsensusError;
 use crate::block_import::{
 	BlockImport, BlockOrigin, BlockImportParams, ImportedAux, JustificationImport, ImportResult,
@@ -63,6 +64,8 @@ pub struct IncomingBlock<B: BlockT> {
 	pub justification: Option<Justification>,
 	/// The peer, we received this from
 	pub origin: Option<Origin>,
+	/// Import the block without executing it, see `BlockImportParams::skip_execution`.
+	pub skip_execution: bool,
 }
 
 /// Type of keys in the blockchain cache that consensus module could use for its needs.
@@ -112,6 +115,13 @@ pub trait ImportQueue<B: BlockT>: Send {
 	/// task and notify later when more actions are ready to be polled. To continue the comparison,
 	/// it is as if this method always returned `Poll::Pending`.
 	fn poll_actions(&mut self, cx: &mut futures::task::Context, link: &mut dyn Link<B>);
+
+	/// Registers the metrics of the queue in the given Prometheus registry.
+	///
+	/// Does nothing by default.
+	fn register_metrics(&mut self, _registry: &Registry) -> Result<(), PrometheusError> {
+		Ok(())
+	}
 }
 
 /// Hooks that the verification queue can use to influence the synchronization
@@ -222,7 +232,7 @@ pub fn import_single_block<B: BlockT, V: Verifier<B>>(
 		r => return Ok(r), // Any other successful result means that the block is already imported.
 	}
 
-	let (import_block, maybe_keys) = verifier.verify(block_origin, header, justification, block.body)
+	let (mut import_block, maybe_keys) = verifier.verify(block_origin, header, justification, block.body)
 		.map_err(|msg| {
 			if let Some(ref peer) = peer {
 				trace!(target: "sync", "Verifying {}({}) from {} failed: {}", number, hash, peer, msg);
@@ -232,6 +242,8 @@ pub fn import_single_block<B: BlockT, V: Verifier<B>>(
 			BlockImportError::VerificationFailed(peer.clone(), msg)
 		})?;
 
+	import_block.skip_execution = block.skip_execution;
+
 	let mut cache = HashMap::new();
 	if let Some(keys) = maybe_keys {
 		cache.extend(keys.into_iter());
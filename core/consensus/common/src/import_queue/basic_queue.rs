@@ -14,10 +14,13 @@
 // You should have received a copy of the GNU General Public License
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::{mem, pin::Pin, time::Duration};
+use std::{fmt, mem, pin::Pin, time::Duration};
 use futures::{prelude::*, channel::mpsc, task::Context, task::Poll};
 use futures_timer::Delay;
 use sr_primitives::{Justification, traits::{Block as BlockT, Header as HeaderT, NumberFor}};
+use substrate_prometheus::{
+	register, Registry, Error as PrometheusError, Histogram, HistogramOpts, IntCounterVec, Opts,
+};
 
 use crate::block_import::BlockOrigin;
 use crate::import_queue::{
@@ -122,6 +125,42 @@ impl<B: BlockT> ImportQueue<B> for BasicQueue<B> {
 
 		self.result_port.poll_actions(cx, link);
 	}
+
+	fn register_metrics(&mut self, registry: &Registry) -> Result<(), PrometheusError> {
+		let metrics = Metrics::register(registry)?;
+		let _ = self.sender.unbounded_send(ToWorkerMsg::SetMetrics(metrics));
+		Ok(())
+	}
+}
+
+/// Metrics reported by the background worker.
+#[derive(Clone)]
+struct Metrics {
+	/// Time taken to verify and import each block.
+	block_import_time: Histogram,
+	/// Number of blocks processed, labeled by result.
+	processed_blocks: IntCounterVec,
+}
+
+impl Metrics {
+	fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			block_import_time: register(Histogram::with_opts(HistogramOpts::new(
+				"import_queue_block_import_time",
+				"Time taken to verify and import a block, in seconds",
+			))?, registry)?,
+			processed_blocks: register(IntCounterVec::new(
+				Opts::new("import_queue_processed_blocks_total", "Number of blocks processed by the import queue"),
+				&["result"],
+			)?, registry)?,
+		})
+	}
+}
+
+impl fmt::Debug for Metrics {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("Metrics").finish()
+	}
 }
 
 /// Message destinated to the background worker.
@@ -130,6 +169,7 @@ enum ToWorkerMsg<B: BlockT> {
 	ImportBlocks(BlockOrigin, Vec<IncomingBlock<B>>),
 	ImportJustification(Origin, B::Hash, NumberFor<B>, Justification),
 	ImportFinalityProof(Origin, B::Hash, NumberFor<B>, Vec<u8>),
+	SetMetrics(Metrics),
 }
 
 struct BlockImportWorker<B: BlockT> {
@@ -137,6 +177,7 @@ struct BlockImportWorker<B: BlockT> {
 	justification_import: Option<BoxJustificationImport<B>>,
 	finality_proof_import: Option<BoxFinalityProofImport<B>>,
 	delay_between_blocks: Duration,
+	metrics: Option<Metrics>,
 }
 
 impl<B: BlockT> BlockImportWorker<B> {
@@ -154,6 +195,7 @@ impl<B: BlockT> BlockImportWorker<B> {
 			justification_import,
 			finality_proof_import,
 			delay_between_blocks: Duration::new(0, 0),
+			metrics: None,
 		};
 
 		// Let's initialize `justification_import` and `finality_proof_import`.
@@ -223,6 +265,9 @@ impl<B: BlockT> BlockImportWorker<B> {
 					ToWorkerMsg::ImportJustification(who, hash, number, justification) => {
 						worker.import_justification(who, hash, number, justification);
 					}
+					ToWorkerMsg::SetMetrics(metrics) => {
+						worker.metrics = Some(metrics);
+					}
 				}
 			}
 		});
@@ -244,7 +289,14 @@ impl<B: BlockT> BlockImportWorker<B> {
 	) -> impl Future<Output = (BoxBlockImport<B>, V)> {
 		let mut result_sender = self.result_sender.clone();
 
-		import_many_blocks(block_import, origin, blocks, verifier, self.delay_between_blocks)
+		import_many_blocks(
+			block_import,
+			origin,
+			blocks,
+			verifier,
+			self.delay_between_blocks,
+			self.metrics.clone(),
+		)
 			.then(move |(imported, count, results, block_import, verifier)| {
 				result_sender.blocks_processed(imported, count, results);
 				future::ready((block_import, verifier))
@@ -315,6 +367,7 @@ fn import_many_blocks<B: BlockT, V: Verifier<B>>(
 	blocks: Vec<IncomingBlock<B>>,
 	verifier: V,
 	delay_between_blocks: Duration,
+	metrics: Option<Metrics>,
 ) -> impl Future<Output = (usize, usize, Vec<(
 	Result<BlockImportResult<NumberFor<B>>, BlockImportError>,
 	B::Hash,
@@ -380,6 +433,7 @@ fn import_many_blocks<B: BlockT, V: Verifier<B>>(
 			Err(BlockImportError::Cancelled)
 		} else {
 			// The actual import.
+			let _timer = metrics.as_ref().map(|m| m.block_import_time.start_timer());
 			import_single_block(
 				&mut **import_handle,
 				blocks_origin.clone(),
@@ -388,6 +442,11 @@ fn import_many_blocks<B: BlockT, V: Verifier<B>>(
 			)
 		};
 
+		if let Some(metrics) = &metrics {
+			let result = if import_result.is_ok() { "success" } else { "failure" };
+			metrics.processed_blocks.with_label_values(&[result]).inc();
+		}
+
 		if import_result.is_ok() {
 			trace!(target: "sync", "Block imported successfully {:?} ({})", block_number, block_hash);
 			imported += 1;
@@ -0,0 +1,86 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Adapter that allows any `kvdb::KeyValueDB` to be used as a [`Database`].
+
+use std::{io, sync::Arc};
+use ::kvdb::{KeyValueDB, DBTransaction};
+use crate::{Database, DatabaseIter, Transaction, Change, ColumnId, skip_to};
+
+struct DbAdapter<D: KeyValueDB + 'static> {
+	db: D,
+	/// Whether `iter_from_prefix` of `db` seeks to the given key and carries on to the end of the
+	/// column.
+	seeks: bool,
+}
+
+/// Wrap a `kvdb::KeyValueDB` into a [`Database`].
+pub fn as_database<D: KeyValueDB + 'static>(db: D) -> Arc<dyn Database> {
+	Arc::new(DbAdapter { db, seeks: false })
+}
+
+/// Wrap a `kvdb::KeyValueDB` whose `iter_from_prefix` seeks to the given key and carries on to
+/// the end of the column, like `kvdb-rocksdb`, into a [`Database`].
+///
+/// Iterating from a key within a prefix then seeks to that key instead of walking the prefix.
+pub fn as_seekable_database<D: KeyValueDB + 'static>(db: D) -> Arc<dyn Database> {
+	Arc::new(DbAdapter { db, seeks: true })
+}
+
+impl<D: KeyValueDB> Database for DbAdapter<D> {
+	fn commit(&self, transaction: Transaction) -> io::Result<()> {
+		let mut tx = DBTransaction::new();
+		for change in transaction.0.into_iter() {
+			match change {
+				Change::Set(col, key, value) => tx.put_vec(col, &key, value),
+				Change::Remove(col, key) => tx.delete(col, &key),
+			}
+		}
+		self.db.write(tx)
+	}
+
+	fn get(&self, col: ColumnId, key: &[u8]) -> io::Result<Option<Vec<u8>>> {
+		self.db.get(col, key).map(|value| value.map(|v| v.into_vec()))
+	}
+
+	fn iter<'a>(&'a self, col: ColumnId) -> DatabaseIter<'a> {
+		Box::new(self.db.iter(col).map(Ok))
+	}
+
+	fn iter_from_prefix<'a>(&'a self, col: ColumnId, prefix: &'a [u8]) -> DatabaseIter<'a> {
+		// some stores only seek to the prefix and carry on to the end of the column.
+		Box::new(self.db.iter_from_prefix(col, prefix)
+			.take_while(move |(key, _)| key.starts_with(prefix))
+			.map(Ok)
+		)
+	}
+
+	fn iter_from_prefix_at<'a>(
+		&'a self,
+		col: ColumnId,
+		prefix: &'a [u8],
+		start: &'a [u8],
+	) -> DatabaseIter<'a> {
+		if !self.seeks {
+			return skip_to(self.iter_from_prefix(col, prefix), start);
+		}
+		let from = if start > prefix { start } else { prefix };
+		Box::new(self.db.iter_from_prefix(col, from)
+			.take_while(move |(key, _)| key.starts_with(prefix))
+			.map(Ok)
+		)
+	}
+}
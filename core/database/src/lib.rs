@@ -0,0 +1,222 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The key-value database used by the client to store data persistently.
+//!
+//! The database is split into columns and every write goes through a [`Transaction`] that is
+//! committed atomically. Storage engines only have to implement the small [`Database`] trait;
+//! any `kvdb::KeyValueDB` can be used through [`as_database`]. With the `sled` feature, a pure-Rust
+//! embedded store is available as [`SledDb`].
+
+mod kvdb;
+mod mem;
+#[cfg(feature = "sled")]
+mod sled;
+
+use std::io;
+
+pub use crate::kvdb::{as_database, as_seekable_database};
+pub use crate::mem::MemDb;
+#[cfg(feature = "sled")]
+pub use crate::sled::SledDb;
+
+/// The identifier of a database column. `None` is the default column.
+pub type ColumnId = Option<u32>;
+
+/// An iterator over the key-value pairs of a column.
+///
+/// An error reading a pair is returned in its place, after which the iterator should not be used.
+pub type DatabaseIter<'a> = Box<dyn Iterator<Item = io::Result<(Box<[u8]>, Box<[u8]>)>> + 'a>;
+
+/// A single change of a [`Transaction`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Change {
+	/// Set the value of a key.
+	Set(ColumnId, Vec<u8>, Vec<u8>),
+	/// Remove a key.
+	Remove(ColumnId, Vec<u8>),
+}
+
+/// A series of changes that are committed to the database atomically.
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct Transaction(pub Vec<Change>);
+
+impl Transaction {
+	/// Create a new, empty transaction.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Set the value of `key` in `col` to `value`.
+	pub fn put(&mut self, col: ColumnId, key: &[u8], value: &[u8]) {
+		self.0.push(Change::Set(col, key.to_vec(), value.to_vec()))
+	}
+
+	/// Set the value of `key` in `col` to `value`, without copying the value.
+	pub fn put_vec(&mut self, col: ColumnId, key: &[u8], value: Vec<u8>) {
+		self.0.push(Change::Set(col, key.to_vec(), value))
+	}
+
+	/// Remove `key` from `col`.
+	pub fn delete(&mut self, col: ColumnId, key: &[u8]) {
+		self.0.push(Change::Remove(col, key.to_vec()))
+	}
+
+	/// Whether the transaction has no changes.
+	pub fn is_empty(&self) -> bool {
+		self.0.is_empty()
+	}
+}
+
+/// A column-based key-value database with transactional commits.
+pub trait Database: Send + Sync {
+	/// Commit all changes of the transaction atomically.
+	fn commit(&self, transaction: Transaction) -> io::Result<()>;
+
+	/// Get the value of `key` in `col`, if any.
+	fn get(&self, col: ColumnId, key: &[u8]) -> io::Result<Option<Vec<u8>>>;
+
+	/// Iterate over all key-value pairs of `col`, ordered by key.
+	fn iter<'a>(&'a self, col: ColumnId) -> DatabaseIter<'a>;
+
+	/// Iterate over the key-value pairs of `col` whose key starts with `prefix`, ordered by key.
+	fn iter_from_prefix<'a>(&'a self, col: ColumnId, prefix: &'a [u8]) -> DatabaseIter<'a>;
+
+	/// Iterate over the key-value pairs of `col` whose key starts with `prefix` and is not less
+	/// than `start`, ordered by key.
+	///
+	/// The default implementation walks the keys from the start of the prefix. Storage engines
+	/// that can seek to a key should override it.
+	fn iter_from_prefix_at<'a>(
+		&'a self,
+		col: ColumnId,
+		prefix: &'a [u8],
+		start: &'a [u8],
+	) -> DatabaseIter<'a> {
+		skip_to(self.iter_from_prefix(col, prefix), start)
+	}
+}
+
+/// Skip the pairs of `iter` whose key is less than `start`.
+pub(crate) fn skip_to<'a>(iter: DatabaseIter<'a>, start: &'a [u8]) -> DatabaseIter<'a> {
+	Box::new(iter.skip_while(move |pair| match pair {
+		Ok((key, _)) => &key[..] < start,
+		Err(_) => false,
+	}))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ::kvdb::{KeyValueDB, DBTransaction, DBValue};
+
+	/// Run the checks every `Database` implementation must pass.
+	fn test_database(db: &dyn Database) {
+		let mut tx = Transaction::new();
+		tx.put(None, b"key", b"value");
+		tx.put(Some(1), b"prefix/a", b"a");
+		tx.put(Some(1), b"prefix/b", b"b");
+		tx.put(Some(1), b"other", b"c");
+		db.commit(tx).unwrap();
+
+		assert_eq!(db.get(None, b"key").unwrap(), Some(b"value".to_vec()));
+		assert_eq!(db.get(Some(1), b"key").unwrap(), None);
+		assert_eq!(db.iter(Some(1)).count(), 3);
+		let prefixed: Vec<_> = db.iter_from_prefix(Some(1), b"prefix/")
+			.map(|pair| pair.unwrap())
+			.map(|(k, v)| (k.to_vec(), v.to_vec()))
+			.collect();
+		assert_eq!(prefixed, vec![
+			(b"prefix/a".to_vec(), b"a".to_vec()),
+			(b"prefix/b".to_vec(), b"b".to_vec()),
+		]);
+
+		let mut tx = Transaction::new();
+		tx.delete(None, b"key");
+		tx.put(Some(1), b"prefix/a", b"a2");
+		db.commit(tx).unwrap();
+
+		assert_eq!(db.get(None, b"key").unwrap(), None);
+		assert_eq!(db.get(Some(1), b"prefix/a").unwrap(), Some(b"a2".to_vec()));
+
+		let from_b: Vec<_> = db.iter_from_prefix_at(Some(1), b"prefix/", b"prefix/b")
+			.map(|pair| pair.unwrap().0.to_vec())
+			.collect();
+		assert_eq!(from_b, vec![b"prefix/b".to_vec()]);
+		assert_eq!(db.iter_from_prefix_at(Some(1), b"prefix/", b"other").count(), 2);
+		assert_eq!(db.iter_from_prefix_at(Some(1), b"prefix/", b"z").count(), 0);
+	}
+
+	#[test]
+	fn mem_db_works() {
+		test_database(&MemDb::new(2));
+	}
+
+	/// A `kvdb::KeyValueDB` whose `iter_from_prefix` seeks to the prefix and carries on to the end
+	/// of the column, like RocksDB.
+	struct SeekingKvdb(kvdb_memorydb::InMemory);
+
+	impl KeyValueDB for SeekingKvdb {
+		fn get(&self, col: ColumnId, key: &[u8]) -> io::Result<Option<DBValue>> {
+			self.0.get(col, key)
+		}
+
+		fn get_by_prefix(&self, col: ColumnId, prefix: &[u8]) -> Option<Box<[u8]>> {
+			self.0.get_by_prefix(col, prefix)
+		}
+
+		fn write_buffered(&self, transaction: DBTransaction) {
+			self.0.write_buffered(transaction)
+		}
+
+		fn flush(&self) -> io::Result<()> {
+			self.0.flush()
+		}
+
+		fn iter<'a>(&'a self, col: ColumnId) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+			self.0.iter(col)
+		}
+
+		fn iter_from_prefix<'a>(
+			&'a self,
+			col: ColumnId,
+			prefix: &'a [u8],
+		) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+			Box::new(self.0.iter(col).skip_while(move |(key, _)| &key[..] < prefix))
+		}
+
+		fn restore(&self, new_db: &str) -> io::Result<()> {
+			self.0.restore(new_db)
+		}
+	}
+
+	#[test]
+	fn kvdb_adapter_works() {
+		test_database(&*as_database(kvdb_memorydb::create(2)));
+		test_database(&*as_database(SeekingKvdb(kvdb_memorydb::create(2))));
+	}
+
+	#[test]
+	fn seekable_kvdb_adapter_works() {
+		test_database(&*as_seekable_database(SeekingKvdb(kvdb_memorydb::create(2))));
+	}
+
+	#[cfg(feature = "sled")]
+	#[test]
+	fn sled_db_works() {
+		test_database(&SledDb::temporary().unwrap());
+	}
+}
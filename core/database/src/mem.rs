@@ -0,0 +1,87 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! In-memory implementation of [`Database`], for tests.
+
+use std::{io, collections::{HashMap, BTreeMap}};
+use parking_lot::RwLock;
+use crate::{Database, DatabaseIter, Transaction, Change, ColumnId};
+
+/// A [`Database`] that keeps all the columns in memory.
+#[derive(Default)]
+pub struct MemDb(RwLock<HashMap<ColumnId, BTreeMap<Vec<u8>, Vec<u8>>>>);
+
+impl MemDb {
+	/// Create a new database with the default column and `columns` numbered columns.
+	pub fn new(columns: u32) -> Self {
+		let mut data = HashMap::new();
+		data.insert(None, BTreeMap::new());
+		for col in 0..columns {
+			data.insert(Some(col), BTreeMap::new());
+		}
+		MemDb(RwLock::new(data))
+	}
+
+	fn collect<'a>(&self, col: ColumnId, prefix: &[u8], start: &[u8]) -> DatabaseIter<'a> {
+		let data = self.0.read();
+		let start = std::cmp::max(prefix, start).to_vec();
+		let pairs: Vec<_> = data.get(&col)
+			.into_iter()
+			.flat_map(|column| column.range(start.clone()..))
+			.take_while(|(key, _)| key.starts_with(prefix))
+			.map(|(key, value)| Ok((key.clone().into_boxed_slice(), value.clone().into_boxed_slice())))
+			.collect();
+		Box::new(pairs.into_iter())
+	}
+}
+
+impl Database for MemDb {
+	fn commit(&self, transaction: Transaction) -> io::Result<()> {
+		let mut data = self.0.write();
+		for change in transaction.0.into_iter() {
+			match change {
+				Change::Set(col, key, value) => {
+					data.entry(col).or_default().insert(key, value);
+				},
+				Change::Remove(col, key) => {
+					data.entry(col).or_default().remove(&key);
+				},
+			}
+		}
+		Ok(())
+	}
+
+	fn get(&self, col: ColumnId, key: &[u8]) -> io::Result<Option<Vec<u8>>> {
+		Ok(self.0.read().get(&col).and_then(|column| column.get(key).cloned()))
+	}
+
+	fn iter<'a>(&'a self, col: ColumnId) -> DatabaseIter<'a> {
+		self.collect(col, &[], &[])
+	}
+
+	fn iter_from_prefix<'a>(&'a self, col: ColumnId, prefix: &'a [u8]) -> DatabaseIter<'a> {
+		self.collect(col, prefix, prefix)
+	}
+
+	fn iter_from_prefix_at<'a>(
+		&'a self,
+		col: ColumnId,
+		prefix: &'a [u8],
+		start: &'a [u8],
+	) -> DatabaseIter<'a> {
+		self.collect(col, prefix, start)
+	}
+}
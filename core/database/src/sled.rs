@@ -0,0 +1,116 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! [`Database`] implementation backed by `sled`, a pure-Rust embedded store.
+//!
+//! All the columns are kept in the default tree of the store, with every key prefixed by its
+//! column, so that a [`Transaction`] spanning several columns is applied as a single atomic batch.
+//! Unless the database is opened with `flush_on_commit`, committed batches are written to disk by
+//! the background flush of `sled`, so the latest commits may be lost on a crash.
+
+use std::{io, path::Path};
+use crate::{Database, DatabaseIter, Transaction, Change, ColumnId};
+
+/// A [`Database`] stored in a `sled` database.
+pub struct SledDb {
+	db: ::sled::Db,
+	flush_on_commit: bool,
+}
+
+fn other_err(e: ::sled::Error) -> io::Error {
+	io::Error::new(io::ErrorKind::Other, format!("{:?}", e))
+}
+
+fn column_key(col: ColumnId, key: &[u8]) -> Vec<u8> {
+	let mut column_key = col.map_or(0, |col| col + 1).to_be_bytes().to_vec();
+	column_key.extend_from_slice(key);
+	column_key
+}
+
+impl SledDb {
+	/// Open the database at the given path, creating it if it does not exist.
+	///
+	/// With `flush_on_commit`, every commit waits for its changes to be flushed to disk.
+	pub fn open(path: &Path, flush_on_commit: bool) -> io::Result<Self> {
+		let db = ::sled::open(path).map_err(other_err)?;
+		Ok(SledDb { db, flush_on_commit })
+	}
+
+	/// Open a temporary database that is removed when dropped.
+	pub fn temporary() -> io::Result<Self> {
+		let db = ::sled::Config::default().temporary(true).open().map_err(other_err)?;
+		Ok(SledDb { db, flush_on_commit: false })
+	}
+
+	fn scan<'a>(&'a self, col: ColumnId, prefix: &[u8], start: &[u8]) -> DatabaseIter<'a> {
+		let column_prefix_len = column_key(col, &[]).len();
+		let prefix = column_key(col, prefix);
+		let start = std::cmp::max(prefix.clone(), column_key(col, start));
+		Box::new(self.db.range(start..)
+			.take_while(move |pair| match pair {
+				Ok((key, _)) => key.starts_with(&prefix),
+				Err(_) => true,
+			})
+			.map(move |pair| pair
+				.map(|(key, value)| (
+					key[column_prefix_len..].to_vec().into_boxed_slice(),
+					value.to_vec().into_boxed_slice(),
+				))
+				.map_err(other_err)
+			)
+		)
+	}
+}
+
+impl Database for SledDb {
+	fn commit(&self, transaction: Transaction) -> io::Result<()> {
+		let mut batch = ::sled::Batch::default();
+		for change in transaction.0.into_iter() {
+			match change {
+				Change::Set(col, key, value) => batch.insert(column_key(col, &key), value),
+				Change::Remove(col, key) => batch.remove(column_key(col, &key)),
+			}
+		}
+		self.db.apply_batch(batch).map_err(other_err)?;
+		if self.flush_on_commit {
+			self.db.flush().map_err(other_err)?;
+		}
+		Ok(())
+	}
+
+	fn get(&self, col: ColumnId, key: &[u8]) -> io::Result<Option<Vec<u8>>> {
+		self.db.get(column_key(col, key))
+			.map(|value| value.map(|v| v.to_vec()))
+			.map_err(other_err)
+	}
+
+	fn iter<'a>(&'a self, col: ColumnId) -> DatabaseIter<'a> {
+		self.scan(col, &[], &[])
+	}
+
+	fn iter_from_prefix<'a>(&'a self, col: ColumnId, prefix: &'a [u8]) -> DatabaseIter<'a> {
+		self.scan(col, prefix, prefix)
+	}
+
+	fn iter_from_prefix_at<'a>(
+		&'a self,
+		col: ColumnId,
+		prefix: &'a [u8],
+		start: &'a [u8],
+	) -> DatabaseIter<'a> {
+		self.scan(col, prefix, start)
+	}
+}
@@ -40,6 +40,7 @@ use sr_primitives::traits::{
 	Block as BlockT, Header as HeaderT, NumberFor, One, Zero,
 };
 use substrate_telemetry::{telemetry, CONSENSUS_INFO};
+use substrate_prometheus::{register, Registry, Error as PrometheusError, IntGauge};
 
 use crate::{
 	CommandOrError, Commit, Config, Error, Network, Precommit, Prevote,
@@ -368,6 +369,24 @@ impl<Block: BlockT> SharedVoterSetState<Block> {
 	}
 }
 
+/// Prometheus metrics of the voter.
+#[derive(Clone)]
+pub(crate) struct Metrics {
+	/// The last round completed by the voter.
+	finality_grandpa_round: IntGauge,
+}
+
+impl Metrics {
+	pub(crate) fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			finality_grandpa_round: register(
+				IntGauge::new("finality_grandpa_round", "Highest completed GRANDPA round")?,
+				registry,
+			)?,
+		})
+	}
+}
+
 /// The environment we run GRANDPA in.
 pub(crate) struct Environment<B, E, Block: BlockT, N: Network<Block>, RA, SC, VR> {
 	pub(crate) client: Arc<Client<B, E, Block, RA>>,
@@ -380,6 +399,7 @@ pub(crate) struct Environment<B, E, Block: BlockT, N: Network<Block>, RA, SC, VR
 	pub(crate) set_id: SetId,
 	pub(crate) voter_set_state: SharedVoterSetState<Block>,
 	pub(crate) voting_rule: VR,
+	pub(crate) metrics: Option<Metrics>,
 }
 
 impl<B, E, Block: BlockT, N: Network<Block>, RA, SC, VR> Environment<B, E, Block, N, RA, SC, VR> {
@@ -806,6 +826,10 @@ where
 			Ok(Some(set_state))
 		})?;
 
+		if let Some(metrics) = &self.metrics {
+			metrics.finality_grandpa_round.set(round as i64);
+		}
+
 		Ok(())
 	}
 
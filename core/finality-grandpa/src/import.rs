@@ -23,7 +23,7 @@ use parking_lot::RwLockWriteGuard;
 
 use client::{blockchain, CallExecutor, Client, well_known_cache_keys};
 use client::blockchain::HeaderBackend;
-use client::backend::Backend;
+use client::backend::{Backend, JustificationRetention};
 use client::utils::is_descendent_of;
 use consensus_common::{
 	BlockImport, Error as ConsensusError,
@@ -198,6 +198,16 @@ fn find_forced_change<B: BlockT>(header: &B::Header)
 	header.digest().convert_first(|l| l.try_to(id).and_then(filter_log))
 }
 
+/// Keeps the justifications of the blocks signalling an authority set change when the backend
+/// prunes those of old finalized blocks, since they prove the change to syncing nodes.
+pub(crate) struct AuthoritySetChangeRetention;
+
+impl<Block: BlockT> JustificationRetention<Block> for AuthoritySetChangeRetention {
+	fn keep_justification(&self, header: &Block::Header) -> bool {
+		find_scheduled_change::<Block>(header).is_some() || find_forced_change::<Block>(header).is_some()
+	}
+}
+
 impl<B, E, Block: BlockT<Hash=H256>, RA, SC>
 	GrandpaBlockImport<B, E, Block, RA, SC>
 where
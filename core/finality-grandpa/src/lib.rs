@@ -53,7 +53,7 @@
 //! included in the newly-finalized chain.
 
 use futures::prelude::*;
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use futures::sync::mpsc;
 use client::{
 	BlockchainEvents, CallExecutor, Client, backend::Backend, error::Error as ClientError,
@@ -405,6 +405,7 @@ where
 	)?;
 
 	let (voter_commands_tx, voter_commands_rx) = mpsc::unbounded();
+	client.register_justification_retention(Arc::new(import::AuthoritySetChangeRetention));
 
 	Ok((
 		GrandpaBlockImport::new(
@@ -514,6 +515,8 @@ pub struct GrandpaParams<B, E, Block: BlockT<Hash=H256>, N, RA, SC, VR, X> {
 	pub telemetry_on_connect: Option<mpsc::UnboundedReceiver<()>>,
 	/// A voting rule used to potentially restrict target votes.
 	pub voting_rule: VR,
+	/// If supplied, the voter metrics are registered in this Prometheus registry.
+	pub prometheus_registry: Option<substrate_prometheus::Registry>,
 }
 
 /// Run a GRANDPA voter as a task. Provide configuration and a link to a
@@ -541,6 +544,7 @@ pub fn run_grandpa_voter<B, E, Block: BlockT<Hash=H256>, N, RA, SC, VR, X>(
 		on_exit,
 		telemetry_on_connect,
 		voting_rule,
+		prometheus_registry,
 	} = grandpa_params;
 
 	let LinkHalf {
@@ -595,6 +599,7 @@ pub fn run_grandpa_voter<B, E, Block: BlockT<Hash=H256>, N, RA, SC, VR, X>(
 		voting_rule,
 		persistent_data,
 		voter_commands_rx,
+		prometheus_registry,
 	);
 
 	let voter_work = voter_work
@@ -641,7 +646,16 @@ where
 		voting_rule: VR,
 		persistent_data: PersistentData<Block>,
 		voter_commands_rx: mpsc::UnboundedReceiver<VoterCommand<Block::Hash, NumberFor<Block>>>,
+		prometheus_registry: Option<substrate_prometheus::Registry>,
 	) -> Self {
+		let metrics = match prometheus_registry.as_ref().map(environment::Metrics::register) {
+			Some(Ok(metrics)) => Some(metrics),
+			Some(Err(e)) => {
+				warn!(target: "afg", "Failed to register GRANDPA metrics: {}", e);
+				None
+			},
+			None => None,
+		};
 
 		let voters = persistent_data.authority_set.current_authorities();
 		let env = Arc::new(Environment {
@@ -655,6 +669,7 @@ where
 			authority_set: persistent_data.authority_set.clone(),
 			consensus_changes: persistent_data.consensus_changes.clone(),
 			voter_set_state: persistent_data.set_state.clone(),
+			metrics,
 		});
 
 		let mut work = VoterWork {
@@ -772,6 +787,7 @@ where
 					consensus_changes: self.env.consensus_changes.clone(),
 					network: self.env.network.clone(),
 					voting_rule: self.env.voting_rule.clone(),
+					metrics: self.env.metrics.clone(),
 				});
 
 				self.rebuild_voter();
@@ -663,6 +663,7 @@ pub mod tests {
 			finalized: false,
 			auxiliary: Vec::new(),
 			fork_choice: ForkChoiceStrategy::LongestChain,
+			skip_execution: false,
 		};
 		do_import_block::<_, _, _, TestJustification>(
 			&client,
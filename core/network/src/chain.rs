@@ -22,7 +22,7 @@ use client::light::fetcher::{ChangesProof, StorageProof};
 use consensus::{BlockImport, BlockStatus, Error as ConsensusError};
 use sr_primitives::traits::{Block as BlockT, Header as HeaderT};
 use sr_primitives::generic::{BlockId};
-use sr_primitives::Justification;
+use sr_primitives::{Justification, StorageOverlay, ChildrenStorageOverlay};
 use primitives::{H256, Blake2Hasher, storage::StorageKey};
 
 /// Local client abstraction for the network.
@@ -76,6 +76,25 @@ pub trait Client<Block: BlockT>: Send + Sync {
 
 	/// Returns `true` if the given `block` is a descendent of `base`.
 	fn is_descendent_of(&self, base: &Block::Hash, block: &Block::Hash) -> Result<bool, Error>;
+
+	/// Get a chunk of the state of a block, starting after the `start` key, with its range proof.
+	///
+	/// The returned flag is true when the chunk reaches the end of the state.
+	fn state_chunk(
+		&self,
+		block: &Block::Hash,
+		storage_key: Option<&[u8]>,
+		start: &[u8],
+		max_size: usize,
+	) -> Result<(Vec<(Vec<u8>, Vec<u8>)>, bool, StorageProof), Error>;
+
+	/// Import the downloaded state of the last finalized block.
+	fn import_state(
+		&self,
+		block: Block::Hash,
+		top: StorageOverlay,
+		children: ChildrenStorageOverlay,
+	) -> Result<(), Error>;
 }
 
 /// Finality proof provider.
@@ -166,4 +185,24 @@ impl<B, E, Block, RA> Client<Block> for SubstrateClient<B, E, Block, RA> where
 
 		Ok(ancestor.hash == *base)
 	}
+
+	fn state_chunk(
+		&self,
+		block: &Block::Hash,
+		storage_key: Option<&[u8]>,
+		start: &[u8],
+		max_size: usize,
+	) -> Result<(Vec<(Vec<u8>, Vec<u8>)>, bool, StorageProof), Error> {
+		(self as &SubstrateClient<B, E, Block, RA>)
+			.read_state_chunk(&BlockId::Hash(block.clone()), storage_key, start, max_size)
+	}
+
+	fn import_state(
+		&self,
+		block: Block::Hash,
+		top: StorageOverlay,
+		children: ChildrenStorageOverlay,
+	) -> Result<(), Error> {
+		(self as &SubstrateClient<B, E, Block, RA>).import_state(block, top, children)
+	}
 }
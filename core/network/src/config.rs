@@ -261,6 +261,8 @@ pub struct NetworkConfiguration {
 	pub node_name: String,
 	/// Configuration for the transport layer.
 	pub transport: TransportConfig,
+	/// Strategy used to sync the chain.
+	pub sync_mode: SyncMode,
 }
 
 impl Default for NetworkConfiguration {
@@ -282,6 +284,7 @@ impl Default for NetworkConfiguration {
 				enable_mdns: false,
 				wasm_external_transport: None,
 			},
+			sync_mode: SyncMode::Full,
 		}
 	}
 }
@@ -358,6 +361,27 @@ impl NonReservedPeerMode {
 	}
 }
 
+/// The strategy used to sync the chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyncMode {
+	/// Download and execute all the blocks. This is the default.
+	Full,
+	/// Download the headers and justifications, then the state of the last finalized block, and
+	/// only execute the blocks after it. Only used when syncing from genesis on a full node.
+	Warp,
+}
+
+impl SyncMode {
+	/// Attempt to parse the sync mode from a string.
+	pub fn parse(s: &str) -> Option<Self> {
+		match s {
+			"full" => Some(SyncMode::Full),
+			"warp" => Some(SyncMode::Warp),
+			_ => None,
+		}
+	}
+}
+
 /// The configuration of a node's secret key, describing the type of key
 /// and how it is obtained. A node's identity keypair is the result of
 /// the evaluation of the node key configuration.
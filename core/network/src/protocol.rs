@@ -38,9 +38,10 @@ use message::generic::{Message as GenericMessage, ConsensusMessage};
 use consensus_gossip::{ConsensusGossip, MessageRecipient as GossipMessageRecipient};
 use light_dispatch::{LightDispatch, LightDispatchNetwork, RequestData};
 use specialization::NetworkSpecialization;
+use state_requests::{StateRequests, OnStateRequest};
 use sync::{ChainSync, SyncState};
 use crate::service::{TransactionPool, ExHashT};
-use crate::config::{BoxFinalityProofRequestBuilder, Roles};
+use crate::config::{BoxFinalityProofRequestBuilder, Roles, SyncMode};
 use rustc_hex::ToHex;
 use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
@@ -57,6 +58,7 @@ pub mod message;
 pub mod event;
 pub mod light_dispatch;
 pub mod specialization;
+mod state_requests;
 pub mod sync;
 
 const REQUEST_TIMEOUT_SEC: u64 = 40;
@@ -66,9 +68,11 @@ const TICK_TIMEOUT: time::Duration = time::Duration::from_millis(1100);
 const PROPAGATE_TIMEOUT: time::Duration = time::Duration::from_millis(2900);
 
 /// Current protocol version.
-pub(crate) const CURRENT_VERSION: u32 = 4;
+pub(crate) const CURRENT_VERSION: u32 = 5;
 /// Lowest version we support
 pub(crate) const MIN_VERSION: u32 = 3;
+/// Lowest version which supports state requests.
+const STATE_REQUEST_MIN_VERSION: u32 = 5;
 
 // Maximum allowed entries in `BlockResponse`
 const MAX_BLOCK_DATA_RESPONSE: u32 = 128;
@@ -90,6 +94,8 @@ const PEER_BEHIND_US_LIGHT_REPUTATION_CHANGE: i32 = -(1 << 8);
 const NEW_EXTRINSIC_REPUTATION_CHANGE: i32 = 1 << 7;
 /// We sent an RPC query to the given node, but it failed.
 const RPC_FAILED_REPUTATION_CHANGE: i32 = -(1 << 12);
+/// Reputation change when a peer sends a state request before we answered its previous one.
+const STATE_REQUEST_FLOOD_REPUTATION_CHANGE: i32 = -(1 << 12);
 
 // Lock must always be taken in order declared here.
 pub struct Protocol<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> {
@@ -112,6 +118,8 @@ pub struct Protocol<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> {
 	transaction_pool: Arc<dyn TransactionPool<H, B>>,
 	/// When asked for a proof of finality, we use this struct to build one.
 	finality_proof_provider: Option<Arc<dyn FinalityProofProvider<B>>>,
+	/// State requests of warp syncing peers, served in the background.
+	state_requests: StateRequests<B>,
 	/// Handles opening the unique substream and sending and receiving raw messages.
 	behaviour: LegacyProto<B, Substream<StreamMuxerBox>>,
 }
@@ -346,12 +354,15 @@ struct ContextData<B: BlockT, H: ExHashT> {
 pub struct ProtocolConfig {
 	/// Assigned roles.
 	pub roles: Roles,
+	/// Strategy used to sync the chain.
+	pub sync_mode: SyncMode,
 }
 
 impl Default for ProtocolConfig {
 	fn default() -> ProtocolConfig {
 		ProtocolConfig {
 			roles: Roles::FULL,
+			sync_mode: SyncMode::Full,
 		}
 	}
 }
@@ -373,6 +384,7 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 		let info = chain.info();
 		let sync = ChainSync::new(
 			config.roles,
+			config.sync_mode,
 			chain.clone(),
 			&info,
 			finality_proof_request_builder,
@@ -386,6 +398,7 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 			tick_timeout: Box::new(futures_timer::Interval::new(TICK_TIMEOUT).map(|v| Ok::<_, ()>(v)).compat()),
 			propagate_timeout: Box::new(futures_timer::Interval::new(PROPAGATE_TIMEOUT).map(|v| Ok::<_, ()>(v)).compat()),
 			config,
+			state_requests: StateRequests::new(chain.clone()),
 			context_data: ContextData {
 				peers: HashMap::new(),
 				chain,
@@ -562,6 +575,10 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 				return self.on_finality_proof_response(who, response),
 			GenericMessage::RemoteReadChildRequest(request) =>
 				self.on_remote_read_child_request(who, request),
+			GenericMessage::StateRequest(request) =>
+				self.on_state_request(who, request),
+			GenericMessage::StateResponse(response) =>
+				self.on_state_response(who, response),
 			GenericMessage::Consensus(msg) => {
 				if self.context_data.peers.get(&who).map_or(false, |peer| peer.info.protocol_version > 2) {
 					self.consensus_gossip.on_incoming(
@@ -714,17 +731,23 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 			} else {
 				None
 			};
+			let body = if get_body {
+				match self.context_data.chain.body(&BlockId::Hash(hash)) {
+					Ok(body) => body,
+					// the peer has to get the rest of the range from someone else
+					Err(client::error::Error::BlockBodyPruned(_)) => {
+						trace!(target: "sync", "Body of {} is pruned, truncating response", hash);
+						break;
+					},
+					Err(_) => None,
+				}
+			} else {
+				None
+			};
 			let block_data = message::generic::BlockData {
 				hash: hash,
 				header: if get_header { Some(header) } else { None },
-				body: if get_body {
-					self.context_data
-						.chain
-						.body(&BlockId::Hash(hash))
-						.unwrap_or(None)
-				} else {
-					None
-				},
+				body,
 				receipt: None,
 				message_queue: None,
 				justification,
@@ -1572,6 +1595,37 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 		}
 	}
 
+	fn on_state_request(
+		&mut self,
+		who: PeerId,
+		request: message::StateRequest<B::Hash>,
+	) {
+		trace!(target: "sync", "State request from {} for {}", who, request.block);
+		match self.state_requests.on_request(who.clone(), request) {
+			OnStateRequest::Queued => {},
+			OnStateRequest::Busy => trace!(target: "sync", "Too many state requests, ignoring request from {}", who),
+			OnStateRequest::AlreadyQueued => {
+				debug!(target: "sync", "State request from {} while its previous one is being served", who);
+				self.peerset_handle.report_peer(who, STATE_REQUEST_FLOOD_REPUTATION_CHANGE);
+			},
+		}
+	}
+
+	fn on_state_response(
+		&mut self,
+		who: PeerId,
+		response: message::StateResponse,
+	) {
+		trace!(target: "sync", "State response from {} with {} entries", who, response.entries.len());
+		match self.sync.on_state_data(who, response) {
+			Ok(sync::OnStateData::Continue) | Ok(sync::OnStateData::Importing) => {},
+			Err(sync::BadPeer(id, repu)) => {
+				self.behaviour.disconnect_peer(&id);
+				self.peerset_handle.report_peer(id, repu);
+			}
+		}
+	}
+
 	fn on_remote_body_response(
 		&mut self,
 		peer: PeerId,
@@ -1668,6 +1722,16 @@ Protocol<B, S, H> {
 		for (id, r) in self.sync.finality_proof_requests() {
 			send_message(&mut self.behaviour, &mut self.context_data.peers, id, GenericMessage::FinalityProofRequest(r))
 		}
+		while let Some((id, r)) = self.state_requests.poll_response() {
+			send_message(&mut self.behaviour, &mut self.context_data.peers, id, GenericMessage::StateResponse(r))
+		}
+		let peers = &self.context_data.peers;
+		let state_request = self.sync.state_request(|id| {
+			peers.get(id).map_or(false, |peer| peer.info.protocol_version >= STATE_REQUEST_MIN_VERSION)
+		});
+		if let Some((id, r)) = state_request {
+			send_message(&mut self.behaviour, &mut self.context_data.peers, id, GenericMessage::StateRequest(r))
+		}
 
 		let event = match self.behaviour.poll(params) {
 			Async::NotReady => return Async::NotReady,
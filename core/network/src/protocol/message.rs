@@ -24,7 +24,7 @@ pub use self::generic::{
 	RemoteHeaderRequest, RemoteHeaderResponse,
 	RemoteChangesRequest, RemoteChangesResponse,
 	FinalityProofRequest, FinalityProofResponse,
-	FromBlock, RemoteReadChildRequest,
+	FromBlock, RemoteReadChildRequest, StateRequest,
 };
 use client::light::fetcher::StorageProof;
 
@@ -135,6 +135,19 @@ pub struct RemoteReadResponse {
 	pub proof: StorageProof,
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
+/// State chunk response.
+pub struct StateResponse {
+	/// Id of a request this response was made for.
+	pub id: RequestId,
+	/// Key-value pairs of the chunk, in key order.
+	pub entries: Vec<(Vec<u8>, Vec<u8>)>,
+	/// Range proof of the entries, which also proves that no entry has been left out.
+	pub proof: StorageProof,
+	/// Whether the chunk reaches the end of the requested trie.
+	pub complete: bool,
+}
+
 /// Generic types.
 pub mod generic {
 	use codec::{Encode, Decode, Input, Output};
@@ -143,7 +156,7 @@ pub mod generic {
 	use super::{
 		RemoteReadResponse, Transactions, Direction,
 		RequestId, BlockAttributes, RemoteCallResponse, ConsensusEngineId,
-		BlockState, StorageProof,
+		BlockState, StorageProof, StateResponse,
 	};
 	/// Consensus is mostly opaque to us
 	#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
@@ -217,6 +230,10 @@ pub mod generic {
 		FinalityProofRequest(FinalityProofRequest<Hash>),
 		/// Finality proof reponse.
 		FinalityProofResponse(FinalityProofResponse<Hash>),
+		/// State chunk request.
+		StateRequest(StateRequest<Hash>),
+		/// State chunk response.
+		StateResponse(StateResponse),
 		/// Chain-specific message.
 		#[codec(index = "255")]
 		ChainSpecific(Vec<u8>),
@@ -420,4 +437,17 @@ pub mod generic {
 		/// Finality proof (if available).
 		pub proof: Option<Vec<u8>>,
 	}
+
+	#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
+	/// State chunk request.
+	pub struct StateRequest<H> {
+		/// Unique request id.
+		pub id: RequestId,
+		/// Hash of the block to request the state of.
+		pub block: H,
+		/// Child trie to read from, or `None` for the top-level trie.
+		pub storage_key: Option<Vec<u8>>,
+		/// The chunk starts after this key.
+		pub start: Vec<u8>,
+	}
 }
@@ -0,0 +1,162 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Serving the state requests of warp syncing peers.
+//!
+//! Reading a chunk of state and proving it is too slow to be done on the network thread, so the
+//! requests are served by a background thread. The queue of requests is bounded and every peer
+//! can only have one request in it, so that peers cannot make us do an unbounded amount of work.
+
+use crate::chain::Client;
+use crate::protocol::{message, sync::MAX_STATE_RESPONSE_SIZE};
+use client::light::fetcher::StorageProof;
+use futures::{prelude::*, sync::mpsc};
+use libp2p::PeerId;
+use log::{trace, warn};
+use sr_primitives::traits::Block as BlockT;
+use std::collections::HashSet;
+use std::sync::{Arc, mpsc as std_mpsc};
+use std::thread;
+
+/// Maximum number of state requests waiting to be served.
+const MAX_QUEUED_REQUESTS: usize = 8;
+
+/// Result of [`StateRequests::on_request`].
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum OnStateRequest {
+	/// The request has been queued.
+	Queued,
+	/// Too many requests are queued, the request has been dropped.
+	Busy,
+	/// The peer already has a request in the queue, the request has been dropped.
+	AlreadyQueued,
+}
+
+/// Queue of state requests served by a background thread.
+pub(crate) struct StateRequests<B: BlockT> {
+	chain: Arc<dyn Client<B>>,
+	/// Channels to and from the background thread, which is started with the first request.
+	worker: Option<(
+		std_mpsc::SyncSender<(PeerId, message::StateRequest<B::Hash>)>,
+		mpsc::UnboundedReceiver<(PeerId, message::StateResponse)>,
+	)>,
+	/// Peers with a request in the queue.
+	queued: HashSet<PeerId>,
+}
+
+impl<B: BlockT> StateRequests<B> {
+	/// Create a new queue, serving requests from the state of `chain`.
+	pub(crate) fn new(chain: Arc<dyn Client<B>>) -> Self {
+		StateRequests {
+			chain,
+			worker: None,
+			queued: HashSet::new(),
+		}
+	}
+
+	/// Queue a request received from `who`.
+	pub(crate) fn on_request(
+		&mut self,
+		who: PeerId,
+		request: message::StateRequest<B::Hash>,
+	) -> OnStateRequest {
+		if self.queued.contains(&who) {
+			return OnStateRequest::AlreadyQueued
+		}
+
+		if self.worker.is_none() {
+			let (request_tx, request_rx) = std_mpsc::sync_channel(MAX_QUEUED_REQUESTS);
+			let (response_tx, response_rx) = mpsc::unbounded();
+			let chain = self.chain.clone();
+			let spawned = thread::Builder::new()
+				.name("state-requests".into())
+				.spawn(move || serve(chain, request_rx, response_tx));
+			if let Err(e) = spawned {
+				warn!(target: "sync", "Failed to start serving state requests: {}", e);
+				return OnStateRequest::Busy
+			}
+			self.worker = Some((request_tx, response_rx));
+		}
+
+		let (requests, _) = self.worker.as_ref().expect("worker has been started above; qed");
+		match requests.try_send((who.clone(), request)) {
+			Ok(()) => {
+				self.queued.insert(who);
+				OnStateRequest::Queued
+			},
+			Err(std_mpsc::TrySendError::Full(_)) => OnStateRequest::Busy,
+			Err(std_mpsc::TrySendError::Disconnected(_)) => {
+				self.worker = None;
+				self.queued.clear();
+				OnStateRequest::Busy
+			},
+		}
+	}
+
+	/// Get the next served request, if any.
+	pub(crate) fn poll_response(&mut self) -> Option<(PeerId, message::StateResponse)> {
+		let (_, responses) = self.worker.as_mut()?;
+		match responses.poll() {
+			Ok(Async::Ready(Some((who, response)))) => {
+				self.queued.remove(&who);
+				Some((who, response))
+			},
+			Ok(Async::Ready(None)) | Err(()) => {
+				// the background thread has stopped, it is restarted with the next request
+				self.worker = None;
+				self.queued.clear();
+				None
+			},
+			Ok(Async::NotReady) => None,
+		}
+	}
+}
+
+/// Serve the requests until the queue is dropped.
+fn serve<B: BlockT>(
+	chain: Arc<dyn Client<B>>,
+	requests: std_mpsc::Receiver<(PeerId, message::StateRequest<B::Hash>)>,
+	responses: mpsc::UnboundedSender<(PeerId, message::StateResponse)>,
+) {
+	for (who, request) in requests {
+		let chunk = chain.state_chunk(
+			&request.block,
+			request.storage_key.as_ref().map(|key| &key[..]),
+			&request.start,
+			MAX_STATE_RESPONSE_SIZE,
+		);
+		let (entries, complete, proof) = match chunk {
+			Ok(chunk) => chunk,
+			Err(error) => {
+				trace!(target: "sync", "State request from {} for {} failed with: {}",
+					who,
+					request.block,
+					error
+				);
+				(Vec::new(), false, StorageProof::empty())
+			},
+		};
+		let response = message::StateResponse {
+			id: request.id,
+			entries,
+			proof,
+			complete,
+		};
+		if responses.unbounded_send((who, response)).is_err() {
+			break
+		}
+	}
+}
@@ -34,24 +34,28 @@ use consensus::{BlockOrigin, BlockStatus,
 	import_queue::{IncomingBlock, BlockImportResult, BlockImportError}
 };
 use crate::{
-	config::{Roles, BoxFinalityProofRequestBuilder},
-	message::{self, generic::FinalityProofRequest, BlockAnnounce, BlockAttributes, BlockRequest, BlockResponse,
-	FinalityProofResponse},
+	config::{Roles, BoxFinalityProofRequestBuilder, SyncMode},
+	message::{self, generic::{FinalityProofRequest, StateRequest}, BlockAnnounce, BlockAttributes, BlockRequest,
+	BlockResponse, FinalityProofResponse, StateResponse},
 	protocol
 };
 use either::Either;
 use extra_requests::ExtraRequests;
+use futures::{Async, Future, sync::oneshot};
 use libp2p::PeerId;
 use log::{debug, trace, warn, info, error};
+use primitives::storage::well_known_keys;
 use sr_primitives::{
 	Justification,
 	generic::BlockId,
 	traits::{Block as BlockT, Header, NumberFor, Zero, One, CheckedSub, SaturatedConversion}
 };
-use std::{fmt, ops::Range, collections::{HashMap, HashSet, VecDeque}, sync::Arc};
+use state::{StateSync, StateImport};
+use std::{fmt, ops::Range, collections::{HashMap, HashSet, VecDeque}, sync::Arc, thread};
 
 mod blocks;
 mod extra_requests;
+mod state;
 
 /// Maximum blocks to request in a single packet.
 const MAX_BLOCKS_TO_REQUEST: usize = 128;
@@ -93,6 +97,15 @@ const BAD_BLOCK_REPUTATION_CHANGE: i32 = -(1 << 29);
 /// Reputation change for peers which send us a block with bad justifications.
 const BAD_JUSTIFICATION_REPUTATION_CHANGE: i32 = -(1 << 16);
 
+/// Reputation change for peers which omit the block bodies we asked for.
+const MISSING_BODY_REPUTATION_CHANGE: i32 = -(1 << 16);
+
+/// Reputation change for peers which send us an invalid chunk of state.
+const BAD_STATE_REPUTATION_CHANGE: i32 = -(1 << 20);
+
+/// Maximum size of the entries of a state response.
+pub(crate) const MAX_STATE_RESPONSE_SIZE: usize = 2 * 1024 * 1024;
+
 /// The main data structure which contains all the state for a chains
 /// active syncing strategy.
 pub struct ChainSync<B: BlockT> {
@@ -127,7 +140,19 @@ pub struct ChainSync<B: BlockT> {
 	/// A flag that caches idle state with no pending requests.
 	is_idle: bool,
 	/// A type to check incoming block announcements.
-	block_announce_validator: Box<dyn BlockAnnounceValidator<B> + Send>
+	block_announce_validator: Box<dyn BlockAnnounceValidator<B> + Send>,
+	/// Progress of the warp sync, if one is in progress.
+	warp_sync: Option<WarpSync<B>>,
+}
+
+/// Phases of a warp sync.
+enum WarpSync<B: BlockT> {
+	/// Importing the headers and justifications of the chain, without executing the blocks.
+	Headers,
+	/// Downloading the state of the last finalized block.
+	State(StateSync<B>),
+	/// Importing the downloaded state of the given block in the background.
+	Importing(B::Hash, oneshot::Receiver<Result<(), String>>),
 }
 
 /// All the data we have about a Peer that we are trying to sync with
@@ -182,7 +207,9 @@ pub enum PeerSyncState<B: BlockT> {
 	/// Downloading justification for given block hash.
 	DownloadingJustification(B::Hash),
 	/// Downloading finality proof for given block hash.
-	DownloadingFinalityProof(B::Hash)
+	DownloadingFinalityProof(B::Hash),
+	/// Downloading a chunk of the state of the warp sync target.
+	DownloadingState,
 }
 
 impl<B: BlockT> PeerSyncState<B> {
@@ -261,6 +288,15 @@ pub enum OnBlockJustification<B: BlockT> {
 	}
 }
 
+/// Result of [`ChainSync::on_state_data`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OnStateData {
+	/// More state needs to be downloaded.
+	Continue,
+	/// The state has been downloaded and is being imported. Normal sync resumes once it is.
+	Importing,
+}
+
 /// Result of [`ChainSync::on_block_finality_proof`].
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum OnBlockFinalityProof<B: BlockT> {
@@ -279,6 +315,7 @@ impl<B: BlockT> ChainSync<B> {
 	/// Create a new instance.
 	pub fn new(
 		role: Roles,
+		mode: SyncMode,
 		client: Arc<dyn crate::chain::Client<B>>,
 		info: &ClientInfo<B>,
 		request_builder: Option<BoxFinalityProofRequestBuilder<B>>,
@@ -286,7 +323,24 @@ impl<B: BlockT> ChainSync<B> {
 	) -> Self {
 		let mut required_block_attributes = BlockAttributes::HEADER | BlockAttributes::JUSTIFICATION;
 
-		if role.is_full() {
+		// Warp sync only runs until the state of a finalized block has been imported, so we
+		// only need to carry on with it if there is no state at our best block yet, i.e. if we
+		// start from genesis or a previous warp sync has been interrupted.
+		let warp_sync = match mode {
+			SyncMode::Warp if role.is_full() => {
+				let best = &info.chain.best_hash;
+				let code = [well_known_keys::CODE.to_vec()];
+				if info.chain.best_number.is_zero() || client.read_proof(best, &code).is_err() {
+					info!("Warp sync: downloading headers");
+					Some(WarpSync::Headers)
+				} else {
+					None
+				}
+			},
+			_ => None,
+		};
+
+		if role.is_full() && warp_sync.is_none() {
 			required_block_attributes |= BlockAttributes::BODY
 		}
 
@@ -306,6 +360,7 @@ impl<B: BlockT> ChainSync<B> {
 			fork_targets: Default::default(),
 			is_idle: false,
 			block_announce_validator,
+			warp_sync,
 		}
 	}
 
@@ -558,8 +613,49 @@ impl<B: BlockT> ChainSync<B> {
 		})
 	}
 
+	/// Get the next state request of the warp sync, if any.
+	///
+	/// Only peers for which `can_serve` returns true are asked for state.
+	pub fn state_request(&mut self, can_serve: impl Fn(&PeerId) -> bool) -> Option<(PeerId, StateRequest<B::Hash>)> {
+		let state_sync = match self.warp_sync {
+			Some(WarpSync::State(ref mut state_sync)) => state_sync,
+			_ => return None,
+		};
+
+		let mut timed_out = None;
+		if let Some(who) = state_sync.pending_peer().cloned() {
+			if !state_sync.is_request_timed_out() {
+				return None
+			}
+			debug!(target: "sync", "State request to {} timed out", who);
+			state_sync.cancel_request();
+			if let Some(peer) = self.peers.get_mut(&who) {
+				peer.state = PeerSyncState::Available;
+			}
+			timed_out = Some(who);
+		}
+
+		let (_, target_number) = state_sync.target();
+		let (who, peer) = self.peers.iter_mut().find(|(who, peer)| {
+			peer.state.is_available()
+				&& peer.best_number >= target_number
+				&& timed_out.as_ref() != Some(*who)
+				&& can_serve(who)
+		})?;
+		trace!(target: "sync", "New state request for {}", who);
+		peer.state = PeerSyncState::DownloadingState;
+		Some((who.clone(), state_sync.next_request(who.clone())))
+	}
+
 	/// Get an iterator over all block requests of all peers.
 	pub fn block_requests(&mut self) -> impl Iterator<Item = (PeerId, BlockRequest<B>)> + '_ {
+		self.poll_state_import();
+		self.maybe_start_state_sync();
+		match self.warp_sync {
+			Some(WarpSync::State(_)) | Some(WarpSync::Importing(..)) =>
+				return Either::Left(std::iter::empty()),
+			_ => {},
+		}
 		if self.is_idle {
 			return Either::Left(std::iter::empty())
 		}
@@ -621,6 +717,17 @@ impl<B: BlockT> ChainSync<B> {
 	pub fn on_block_data
 		(&mut self, who: PeerId, request: BlockRequest<B>, response: BlockResponse<B>) -> Result<OnBlockData<B>, BadPeer>
 	{
+		let downloading_state = match self.warp_sync {
+			Some(WarpSync::State(_)) | Some(WarpSync::Importing(..)) => true,
+			_ => false,
+		};
+		// the blocks below the state downloaded by the warp sync are not executed
+		let skip_execution = match self.warp_sync {
+			Some(WarpSync::Headers) => true,
+			_ => false,
+		};
+		let missing_body = self.required_block_attributes.contains(BlockAttributes::BODY)
+			&& response.blocks.iter().any(|b| b.header.is_some() && b.body.is_none());
 		let new_blocks: Vec<IncomingBlock<B>> =
 			if let Some(peer) = self.peers.get_mut(&who) {
 				let mut blocks = response.blocks;
@@ -630,6 +737,20 @@ impl<B: BlockT> ChainSync<B> {
 				}
 				self.is_idle = false;
 				match &mut peer.state {
+					PeerSyncState::DownloadingNew(_) | PeerSyncState::DownloadingStale(_)
+						if missing_body || downloading_state =>
+					{
+						self.blocks.clear_peer_download(&who);
+						peer.state = PeerSyncState::Available;
+						if missing_body && request.fields.contains(BlockAttributes::BODY) {
+							debug!(target: "sync", "Peer {} sent blocks without the requested bodies", who);
+							return Err(BadPeer(who, MISSING_BODY_REPUTATION_CHANGE))
+						}
+						// The request has been made during a warp sync, these blocks will be
+						// requested again with their bodies once the state is imported.
+						trace!(target: "sync", "Ignoring blocks downloaded during warp sync from {}", who);
+						Vec::new()
+					}
 					PeerSyncState::DownloadingNew(start_block) => {
 						self.blocks.clear_peer_download(&who);
 						self.blocks.insert(*start_block, blocks, who);
@@ -644,6 +765,7 @@ impl<B: BlockT> ChainSync<B> {
 									body: block_data.block.body,
 									justification: block_data.block.justification,
 									origin: block_data.origin,
+									skip_execution,
 								}
 							}).collect()
 					}
@@ -656,6 +778,7 @@ impl<B: BlockT> ChainSync<B> {
 								body: b.body,
 								justification: b.justification,
 								origin: Some(who.clone()),
+								skip_execution,
 							}
 						}).collect()
 					}
@@ -712,7 +835,8 @@ impl<B: BlockT> ChainSync<B> {
 
 					| PeerSyncState::Available
 					| PeerSyncState::DownloadingJustification(..)
-					| PeerSyncState::DownloadingFinalityProof(..) => Vec::new()
+					| PeerSyncState::DownloadingFinalityProof(..)
+					| PeerSyncState::DownloadingState => Vec::new()
 				}
 			} else {
 				Vec::new()
@@ -819,6 +943,51 @@ impl<B: BlockT> ChainSync<B> {
 		Ok(OnBlockFinalityProof::Nothing)
 	}
 
+	/// Handle a response from the remote to a state request that we made.
+	pub fn on_state_data(&mut self, who: PeerId, response: StateResponse) -> Result<OnStateData, BadPeer> {
+		match self.peers.get_mut(&who) {
+			Some(ref mut peer) if peer.state == PeerSyncState::DownloadingState =>
+				peer.state = PeerSyncState::Available,
+			_ => {
+				trace!(target: "sync", "Unexpected state response from {}", who);
+				return Ok(OnStateData::Continue)
+			}
+		}
+
+		self.is_idle = false;
+		match self.warp_sync {
+			Some(WarpSync::State(ref mut state_sync)) if state_sync.pending_peer() == Some(&who) =>
+				match state_sync.on_response(response) {
+					StateImport::Continue => return Ok(OnStateData::Continue),
+					StateImport::BadResponse => return Err(BadPeer(who, BAD_STATE_REPUTATION_CHANGE)),
+					StateImport::Complete => {},
+				},
+			_ => return Ok(OnStateData::Continue),
+		}
+
+		if let Some(WarpSync::State(state_sync)) = self.warp_sync.take() {
+			let (hash, top, children) = state_sync.into_state();
+			// writing the state takes a while, keep it off the network thread
+			let (tx, rx) = oneshot::channel();
+			let client = self.client.clone();
+			let spawned = thread::Builder::new()
+				.name("state-import".into())
+				.spawn(move || {
+					let result = client.import_state(hash, top, children).map_err(|e| format!("{:?}", e));
+					let _ = tx.send(result);
+				});
+			match spawned {
+				Ok(_) => self.warp_sync = Some(WarpSync::Importing(hash, rx)),
+				Err(e) => {
+					warn!(target: "sync", "Warp sync: failed to start importing state of {}: {}", hash, e);
+					self.warp_sync = Some(WarpSync::Headers);
+					return Ok(OnStateData::Continue)
+				},
+			}
+		}
+		Ok(OnStateData::Importing)
+	}
+
 	/// A batch of blocks have been processed, with or without errors.
 	///
 	/// Call this when a batch of blocks have been processed by the import
@@ -1092,6 +1261,11 @@ impl<B: BlockT> ChainSync<B> {
 
 	/// Call when a peer has disconnected.
 	pub fn peer_disconnected(&mut self, who: PeerId) {
+		if let Some(WarpSync::State(ref mut state_sync)) = self.warp_sync {
+			if state_sync.pending_peer() == Some(&who) {
+				state_sync.cancel_request();
+			}
+		}
 		self.blocks.clear_peer_download(&who);
 		self.peers.remove(&who);
 		self.extra_justifications.peer_disconnected(&who);
@@ -1156,6 +1330,83 @@ impl<B: BlockT> ChainSync<B> {
 		}
 	}
 
+	/// Move a warp sync from downloading headers to downloading state, once the headers have
+	/// caught up with our peers and a block past genesis has been finalized.
+	fn maybe_start_state_sync(&mut self) {
+		match self.warp_sync {
+			Some(WarpSync::Headers) => {},
+			_ => return,
+		}
+		if !self.queue_blocks.is_empty() {
+			return
+		}
+		let major_sync_blocks: NumberFor<B> = MAJOR_SYNC_BLOCKS.into();
+		match self.peers.values().map(|p| p.best_number).max() {
+			Some(best_seen) if best_seen <= self.best_queued_number + major_sync_blocks => {},
+			_ => return,
+		}
+
+		let info = self.client.info().chain;
+		if info.finalized_number.is_zero() {
+			trace!(target: "sync", "Warp sync: waiting for finality");
+			return
+		}
+		match self.client.header(&BlockId::Hash(info.finalized_hash)) {
+			Ok(Some(header)) => {
+				info!(
+					"Warp sync: downloading state of #{} ({})",
+					info.finalized_number,
+					info.finalized_hash,
+				);
+				self.warp_sync = Some(WarpSync::State(StateSync::new(&header)));
+			},
+			Ok(None) => warn!(target: "sync", "Warp sync: missing header of finalized block {}", info.finalized_hash),
+			Err(e) => warn!(target: "sync", "Warp sync: error reading finalized header: {:?}", e),
+		}
+	}
+
+	/// Check whether the import of the state downloaded by the warp sync has finished.
+	fn poll_state_import(&mut self) {
+		let (hash, result) = match self.warp_sync {
+			Some(WarpSync::Importing(ref hash, ref mut import)) => match import.poll() {
+				Ok(Async::Ready(result)) => (*hash, result),
+				Ok(Async::NotReady) => return,
+				Err(_) => (*hash, Err("import was interrupted".into())),
+			},
+			_ => return,
+		};
+
+		match result {
+			Ok(()) => {
+				info!("Warp sync: state imported, switching to full sync");
+				self.complete_warp_sync();
+			},
+			Err(e) => {
+				// Either finality has moved past the target, or some entries have been omitted
+				// and the state root does not match. Start over from the last finalized block.
+				warn!(target: "sync", "Warp sync: failed to import state of {}: {}", hash, e);
+				self.warp_sync = Some(WarpSync::Headers);
+			},
+		}
+	}
+
+	/// Switch to full sync after the state of a finalized block has been imported and the blocks
+	/// above it have been reverted.
+	fn complete_warp_sync(&mut self) {
+		self.warp_sync = None;
+		self.required_block_attributes |= BlockAttributes::BODY;
+		self.queue_blocks.clear();
+		self.blocks.clear();
+		self.best_importing_number = Zero::zero();
+		let info = self.client.info().chain;
+		self.best_queued_hash = info.best_hash;
+		self.best_queued_number = info.best_number;
+		for peer in self.peers.values_mut() {
+			peer.common_number = std::cmp::min(peer.common_number, info.best_number);
+		}
+		self.is_idle = false;
+	}
+
 	/// What is the status of the block corresponding to the given hash?
 	fn block_status(&self, hash: &B::Hash) -> Result<BlockStatus, ClientError> {
 		if self.queue_blocks.contains(hash) {
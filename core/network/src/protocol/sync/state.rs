@@ -0,0 +1,255 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::message::{generic::StateRequest, StateResponse};
+use libp2p::PeerId;
+use log::{debug, trace};
+use primitives::{H256, Blake2Hasher, convert_hash, storage::well_known_keys};
+use sr_primitives::{StorageOverlay, ChildrenStorageOverlay, traits::{Block as BlockT, Header, NumberFor}};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+// Time to wait for a state response before asking another peer.
+const STATE_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Result of [`StateSync::on_response`].
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum StateImport {
+	/// The chunk has been accepted, more chunks are needed.
+	Continue,
+	/// The chunk has been accepted and the state is complete.
+	Complete,
+	/// The chunk is invalid.
+	BadResponse,
+}
+
+/// Downloads the state of a block in chunks.
+///
+/// The state is requested trie by trie, starting with the top-level trie, in key order. Every
+/// chunk comes with a range proof against the state root of the target block, which shows that
+/// no entry has been left out, and is checked before being accepted.
+#[derive(Debug)]
+pub(crate) struct StateSync<B: BlockT> {
+	/// Hash of the block whose state is downloaded.
+	target_hash: B::Hash,
+	/// Number of the block whose state is downloaded.
+	target_number: NumberFor<B>,
+	/// State root of the target block.
+	target_root: H256,
+	/// Downloaded top-level trie, without the child trie roots.
+	top: StorageOverlay,
+	/// Downloaded child tries.
+	children: ChildrenStorageOverlay,
+	/// Child tries found in the top-level trie that remain to be downloaded.
+	pending_children: VecDeque<Vec<u8>>,
+	/// The trie being downloaded, `None` for the top-level trie.
+	current_child: Option<Vec<u8>>,
+	/// Last key received from the trie being downloaded.
+	last_key: Vec<u8>,
+	/// Whether all the tries have been downloaded.
+	complete: bool,
+	/// Peer we are waiting for a response from, and when the request was made.
+	pending_request: Option<(PeerId, Instant)>,
+	/// Total size of the downloaded entries.
+	downloaded_bytes: usize,
+}
+
+impl<B: BlockT> StateSync<B> {
+	/// Start downloading the state of the given block.
+	pub(crate) fn new(header: &B::Header) -> Self {
+		StateSync {
+			target_hash: header.hash(),
+			target_number: *header.number(),
+			target_root: convert_hash(header.state_root()),
+			top: Default::default(),
+			children: Default::default(),
+			pending_children: VecDeque::new(),
+			current_child: None,
+			last_key: Vec::new(),
+			complete: false,
+			pending_request: None,
+			downloaded_bytes: 0,
+		}
+	}
+
+	/// Hash and number of the block whose state is downloaded.
+	pub(crate) fn target(&self) -> (B::Hash, NumberFor<B>) {
+		(self.target_hash, self.target_number)
+	}
+
+	/// Returns the peer the last request was sent to, if it has not answered yet.
+	pub(crate) fn pending_peer(&self) -> Option<&PeerId> {
+		self.pending_request.as_ref().map(|(peer, _)| peer)
+	}
+
+	/// Returns true if the pending request has not been answered in time.
+	pub(crate) fn is_request_timed_out(&self) -> bool {
+		self.pending_request.as_ref().map_or(false, |(_, time)| time.elapsed() > STATE_REQUEST_TIMEOUT)
+	}
+
+	/// Forget about the pending request, e.g. because the peer has disconnected.
+	pub(crate) fn cancel_request(&mut self) {
+		self.pending_request = None;
+	}
+
+	/// Build the request for the next chunk, to be sent to `who`.
+	pub(crate) fn next_request(&mut self, who: PeerId) -> StateRequest<B::Hash> {
+		self.pending_request = Some((who, Instant::now()));
+		StateRequest {
+			id: 0,
+			block: self.target_hash,
+			storage_key: self.current_child.clone(),
+			start: self.last_key.clone(),
+		}
+	}
+
+	/// Handle a response to the pending request.
+	pub(crate) fn on_response(&mut self, response: StateResponse) -> StateImport {
+		self.pending_request = None;
+
+		if response.entries.is_empty() && !response.complete {
+			debug!(target: "sync", "Empty state response for incomplete trie");
+			return StateImport::BadResponse
+		}
+
+		let checked = state_machine::read_range_proof_check::<Blake2Hasher>(
+			self.target_root,
+			response.proof,
+			self.current_child.as_ref().map(|key| &key[..]),
+			&self.last_key,
+			response.entries.len(),
+		);
+		match checked {
+			Ok((ref entries, complete)) if *entries == response.entries && complete == response.complete => {},
+			Ok(_) => {
+				debug!(target: "sync", "State response entries do not match the proof");
+				return StateImport::BadResponse
+			},
+			Err(e) => {
+				debug!(target: "sync", "Invalid state proof: {}", e);
+				return StateImport::BadResponse
+			},
+		}
+
+		if let Some((key, _)) = response.entries.last() {
+			self.last_key = key.clone();
+		}
+		for (key, value) in response.entries {
+			self.downloaded_bytes += key.len() + value.len();
+			match self.current_child {
+				None if well_known_keys::is_child_storage_key(&key) => self.pending_children.push_back(key),
+				None => { self.top.insert(key, value); },
+				Some(ref storage_key) => {
+					self.children.entry(storage_key.clone()).or_default().insert(key, value);
+				},
+			}
+		}
+		trace!(target: "sync", "Downloaded {} bytes of state of {}", self.downloaded_bytes, self.target_hash);
+
+		if response.complete {
+			self.last_key = Vec::new();
+			self.current_child = self.pending_children.pop_front();
+			self.complete = self.current_child.is_none();
+		}
+
+		if self.complete {
+			StateImport::Complete
+		} else {
+			StateImport::Continue
+		}
+	}
+
+	/// Consume the downloaded state. Should only be called once the download is complete.
+	pub(crate) fn into_state(self) -> (B::Hash, StorageOverlay, ChildrenStorageOverlay) {
+		(self.target_hash, self.top, self.children)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use client::light::fetcher::StorageProof;
+	use primitives::storage::StorageKey;
+	use test_client::runtime::{Block, Header};
+	use sr_primitives::generic::BlockId;
+
+	#[test]
+	fn downloads_state_in_checked_chunks() {
+		let client = test_client::new();
+		let header = client.header(&BlockId::Number(0)).unwrap().unwrap();
+		let block = BlockId::Number(0);
+		let mut sync = StateSync::<Block>::new(&header);
+		let peer = PeerId::random();
+
+		loop {
+			let request = sync.next_request(peer.clone());
+			let (entries, complete, proof) = client.read_state_chunk(
+				&block,
+				request.storage_key.as_ref().map(|key| &key[..]),
+				&request.start,
+				64,
+			).unwrap();
+			let response = StateResponse { id: 0, entries, proof, complete };
+			match sync.on_response(response) {
+				StateImport::Continue => continue,
+				StateImport::Complete => break,
+				StateImport::BadResponse => panic!("valid response was rejected"),
+			}
+		}
+
+		let (hash, top, _) = sync.into_state();
+		assert_eq!(hash, header.hash());
+		assert_eq!(top.len(), client.storage_keys(&block, &StorageKey(Vec::new())).unwrap().len());
+	}
+
+	#[test]
+	fn rejects_entries_not_matching_proof() {
+		let client = test_client::new();
+		let header: Header = client.header(&BlockId::Number(0)).unwrap().unwrap();
+		let mut sync = StateSync::<Block>::new(&header);
+
+		let request = sync.next_request(PeerId::random());
+		let (mut entries, complete, _) = client.read_state_chunk(
+			&BlockId::Number(0),
+			None,
+			&request.start,
+			1024,
+		).unwrap();
+		entries[0].1 = b"forged".to_vec();
+		let response = StateResponse { id: 0, entries, proof: StorageProof::empty(), complete };
+
+		assert_eq!(sync.on_response(response), StateImport::BadResponse);
+	}
+
+	#[test]
+	fn rejects_chunks_with_omitted_entries() {
+		let client = test_client::new();
+		let header: Header = client.header(&BlockId::Number(0)).unwrap().unwrap();
+		let mut sync = StateSync::<Block>::new(&header);
+
+		let request = sync.next_request(PeerId::random());
+		let (mut entries, complete, proof) = client.read_state_chunk(
+			&BlockId::Number(0),
+			None,
+			&request.start,
+			1024,
+		).unwrap();
+		entries.remove(1);
+		let response = StateResponse { id: 0, entries, proof, complete };
+
+		assert_eq!(sync.on_response(response), StateImport::BadResponse);
+	}
+}
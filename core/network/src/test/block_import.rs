@@ -39,7 +39,8 @@ fn prepare_good_block() -> (TestClient, Hash, u64, PeerId, IncomingBlock<Block>)
 		header,
 		body: None,
 		justification,
-		origin: Some(peer_id.clone())
+		origin: Some(peer_id.clone()),
+		skip_execution: false,
 	})
 }
 
@@ -96,6 +96,7 @@ impl<B: BlockT> Verifier<B> for PassThroughVerifier {
 			post_digests: vec![],
 			auxiliary: Vec::new(),
 			fork_choice: ForkChoiceStrategy::LongestChain,
+			skip_execution: false,
 		}, maybe_keys))
 	}
 }
@@ -556,6 +557,7 @@ pub trait TestNetFactory: Sized {
 			network_config: NetworkConfiguration {
 				listen_addresses: vec![listen_addr.clone()],
 				transport: TransportConfig::MemoryOnly,
+				sync_mode: config.sync_mode,
 				..NetworkConfiguration::default()
 			},
 			chain: client.clone(),
@@ -630,6 +632,7 @@ pub trait TestNetFactory: Sized {
 			network_config: NetworkConfiguration {
 				listen_addresses: vec![listen_addr.clone()],
 				transport: TransportConfig::MemoryOnly,
+				sync_mode: config.sync_mode,
 				..NetworkConfiguration::default()
 			},
 			chain: client.clone(),
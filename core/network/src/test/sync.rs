@@ -14,8 +14,9 @@
 // You should have received a copy of the GNU General Public License
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
-use crate::config::Roles;
+use crate::config::{Roles, SyncMode};
 use consensus::BlockOrigin;
+use primitives::storage::{StorageKey, well_known_keys};
 use futures03::TryFutureExt as _;
 use std::time::Duration;
 use tokio::runtime::current_thread;
@@ -592,3 +593,44 @@ fn can_sync_explicit_forks() {
 		Ok(Async::Ready(()))
 	})).unwrap();
 }
+
+#[test]
+fn warp_sync_downloads_finalized_state_and_executes_blocks_above_it() {
+	let _ = ::env_logger::try_init();
+	let mut runtime = current_thread::Runtime::new().unwrap();
+	let mut net = TestNet::new(1);
+	net.add_full_peer(&ProtocolConfig { sync_mode: SyncMode::Warp, ..Default::default() });
+	net.peer(0).push_blocks(20, true);
+	net.peer(0).client().finalize_block(BlockId::Number(15), None, true).unwrap();
+
+	// the warp syncing peer imports the headers without executing the blocks
+	runtime.block_on(futures::future::poll_fn::<(), (), _>(|| -> Result<_, ()> {
+		net.poll();
+		if net.peer(1).client().info().chain.best_number < 20 {
+			return Ok(Async::NotReady)
+		}
+		Ok(Async::Ready(()))
+	})).unwrap();
+	let code = StorageKey(well_known_keys::CODE.to_vec());
+	let warp_client = net.peer(1).client().as_full().unwrap();
+	assert!(warp_client.storage(&BlockId::Number(20), &code).is_err());
+
+	// once a block is finalized, its state is downloaded and the blocks above it are executed
+	net.peer(1).client().finalize_block(BlockId::Number(15), None, true).unwrap();
+	runtime.block_on(futures::future::poll_fn::<(), (), _>(|| -> Result<_, ()> {
+		net.poll();
+		if warp_client.info().chain.best_number < 20 || warp_client.storage(&BlockId::Number(20), &code).is_err() {
+			return Ok(Async::NotReady)
+		}
+		Ok(Async::Ready(()))
+	})).unwrap();
+
+	let source_client = net.peer(0).client().as_full().unwrap();
+	let state_root = |client: &PeersFullClient| *client.header(&BlockId::Number(20)).unwrap().unwrap().state_root();
+	assert_eq!(state_root(&warp_client), state_root(&source_client));
+	assert_eq!(
+		warp_client.storage(&BlockId::Number(20), &code).unwrap(),
+		source_client.storage(&BlockId::Number(20), &code).unwrap(),
+	);
+	assert!(net.peers()[0].blockchain_canon_equals(&net.peers()[1]));
+}
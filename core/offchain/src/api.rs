@@ -23,7 +23,7 @@ use std::{
 
 use client::backend::OffchainStorage;
 use futures::{StreamExt as _, Future, FutureExt as _, future, channel::mpsc};
-use log::{info, debug, warn, error};
+use log::{info, debug, warn};
 use network::{PeerId, Multiaddr, NetworkStateInfo};
 use codec::{Encode, Decode};
 use primitives::offchain::{
@@ -55,22 +55,14 @@ pub(crate) struct Api<Storage, Block: traits::Block> {
 	sender: mpsc::UnboundedSender<ExtMessage>,
 	db: Storage,
 	network_state: Arc<dyn NetworkStateInfo + Send + Sync>,
-	_at: BlockId<Block>,
+	/// The block the worker runs at, the `LOCAL` storage is scoped to it.
+	at: Block::Hash,
 	/// Is this node a potential validator?
 	is_validator: bool,
 	/// Everything HTTP-related is handled by a different struct.
 	http: http::HttpApi,
 }
 
-fn unavailable_yet<R: Default>(name: &str) -> R {
-	error!(
-		"The {:?} API is not available for offchain workers yet. Follow \
-		https://github.com/paritytech/substrate/issues/1458 for details", name
-	);
-	Default::default()
-}
-
-const LOCAL_DB: &str = "LOCAL (fork-aware) DB";
 const STORAGE_PREFIX: &[u8] = b"storage";
 
 impl<Storage, Block> OffchainExt for Api<Storage, Block>
@@ -114,7 +106,7 @@ where
 	fn local_storage_set(&mut self, kind: StorageKind, key: &[u8], value: &[u8]) {
 		match kind {
 			StorageKind::PERSISTENT => self.db.set(STORAGE_PREFIX, key, value),
-			StorageKind::LOCAL => unavailable_yet(LOCAL_DB),
+			StorageKind::LOCAL => self.db.set_local(self.at.as_ref(), key, value),
 		}
 	}
 
@@ -129,14 +121,16 @@ where
 			StorageKind::PERSISTENT => {
 				self.db.compare_and_set(STORAGE_PREFIX, key, old_value, new_value)
 			},
-			StorageKind::LOCAL => unavailable_yet(LOCAL_DB),
+			StorageKind::LOCAL => {
+				self.db.compare_and_set_local(self.at.as_ref(), key, old_value, new_value)
+			},
 		}
 	}
 
 	fn local_storage_get(&mut self, kind: StorageKind, key: &[u8]) -> Option<Vec<u8>> {
 		match kind {
 			StorageKind::PERSISTENT => self.db.get(STORAGE_PREFIX, key),
-			StorageKind::LOCAL => unavailable_yet(LOCAL_DB),
+			StorageKind::LOCAL => self.db.get_local(self.at.as_ref(), key),
 		}
 	}
 
@@ -273,7 +267,7 @@ impl<A: ChainApi> AsyncApi<A> {
 	pub fn new<S: OffchainStorage>(
 		transaction_pool: Arc<Pool<A>>,
 		db: S,
-		at: BlockId<A::Block>,
+		at: <A::Block as traits::Block>::Hash,
 		network_state: Arc<dyn NetworkStateInfo + Send + Sync>,
 		is_validator: bool,
 	) -> (Api<S, A::Block>, AsyncApi<A>) {
@@ -285,7 +279,7 @@ impl<A: ChainApi> AsyncApi<A> {
 			sender,
 			db,
 			network_state,
-			_at: at,
+			at,
 			is_validator,
 			http: http_api,
 		};
@@ -293,7 +287,7 @@ impl<A: ChainApi> AsyncApi<A> {
 		let async_api = AsyncApi {
 			receiver: Some(rx),
 			transaction_pool,
-			at,
+			at: BlockId::hash(at),
 			http: Some(http_worker),
 		};
 
@@ -338,10 +332,10 @@ impl<A: ChainApi> AsyncApi<A> {
 mod tests {
 	use super::*;
 	use std::{convert::{TryFrom, TryInto}, time::SystemTime};
-	use sr_primitives::traits::Zero;
+	use client::backend::Backend as _;
 	use client_db::offchain::LocalStorage;
 	use network::PeerId;
-	use test_client::runtime::Block;
+	use test_client::{TestClientBuilder, DefaultTestClientBuilderExt, TestClientBuilderExt, runtime::Block};
 
 	struct MockNetworkStateInfo();
 
@@ -357,8 +351,11 @@ mod tests {
 
 	fn offchain_api() -> (Api<LocalStorage, Block>, AsyncApi<impl ChainApi>) {
 		let _ = env_logger::try_init();
-		let db = LocalStorage::new_test();
-		let client = Arc::new(test_client::new());
+		// The storage of the client's backend, so that the genesis block is known to it.
+		let builder = TestClientBuilder::new();
+		let db = builder.backend().offchain_storage().expect("the test backend has offchain storage");
+		let client = Arc::new(builder.build());
+		let genesis_hash = client.info().chain.genesis_hash;
 		let pool = Arc::new(
 			Pool::new(Default::default(), transaction_pool::FullChainApi::new(client.clone()))
 		);
@@ -367,7 +364,7 @@ mod tests {
 		AsyncApi::new(
 			pool,
 			db,
-			BlockId::Number(Zero::zero()),
+			genesis_hash,
 			mock,
 			false,
 		)
@@ -454,6 +451,22 @@ mod tests {
 		assert_eq!(api.local_storage_get(kind, key), Some(b"value".to_vec()));
 	}
 
+	#[test]
+	fn should_set_and_get_local_storage_of_the_block() {
+		// given
+		let mut api = offchain_api().0;
+		let key = b"test";
+
+		// when
+		assert_eq!(api.local_storage_get(StorageKind::LOCAL, key), None);
+		api.local_storage_set(StorageKind::LOCAL, key, b"value");
+		assert_eq!(api.local_storage_compare_and_set(StorageKind::LOCAL, key, None, b"xxx"), false);
+
+		// then
+		assert_eq!(api.local_storage_get(StorageKind::LOCAL, key), Some(b"value".to_vec()));
+		assert_eq!(api.local_storage_get(StorageKind::PERSISTENT, key), None);
+	}
+
 	#[test]
 	fn should_convert_network_states() {
 		// given
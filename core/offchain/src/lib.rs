@@ -46,7 +46,7 @@ use futures::future::Future;
 use log::{debug, warn};
 use network::NetworkStateInfo;
 use primitives::{offchain, ExecutionContext};
-use sr_primitives::{generic::BlockId, traits::{self, ProvideRuntimeApi}};
+use sr_primitives::{generic::BlockId, traits::{self, Header as _, ProvideRuntimeApi}};
 use transaction_pool::txpool::{Pool, ChainApi};
 
 mod api;
@@ -99,13 +99,14 @@ impl<Client, Storage, Block> OffchainWorkers<
 	#[must_use]
 	pub fn on_block_imported<A>(
 		&self,
-		number: &<Block::Header as traits::Header>::Number,
+		header: &Block::Header,
 		pool: &Arc<Pool<A>>,
 		network_state: Arc<dyn NetworkStateInfo + Send + Sync>,
 		is_validator: bool,
 	) -> impl Future<Output = ()> where A: ChainApi<Block=Block> + 'static {
 		let runtime = self.client.runtime_api();
-		let at = BlockId::number(*number);
+		let hash = header.hash();
+		let at = BlockId::hash(hash);
 		let has_api = runtime.has_api::<dyn OffchainWorkerApi<Block>>(&at);
 		debug!("Checking offchain workers at {:?}: {:?}", at, has_api);
 
@@ -113,12 +114,12 @@ impl<Client, Storage, Block> OffchainWorkers<
 			let (api, runner) = api::AsyncApi::new(
 				pool.clone(),
 				self.db.clone(),
-				at.clone(),
+				hash,
 				network_state.clone(),
 				is_validator,
 			);
 			debug!("Spawning offchain workers at {:?}", at);
-			let number = *number;
+			let number = *header.number();
 			let client = self.client.clone();
 			self.spawn_worker(move || {
 				let runtime = client.runtime_api();
@@ -177,10 +178,11 @@ mod tests {
 		let pool = Arc::new(Pool::new(Default::default(), transaction_pool::FullChainApi::new(client.clone())));
 		let db = client_db::offchain::LocalStorage::new_test();
 		let network_state = Arc::new(MockNetworkStateInfo());
+		let header = client.header(&BlockId::number(0)).unwrap().unwrap();
 
 		// when
 		let offchain = OffchainWorkers::new(client, db);
-		futures::executor::block_on(offchain.on_block_imported(&0u64, &pool, network_state, false));
+		futures::executor::block_on(offchain.on_block_imported(&header, &pool, network_state, false));
 
 		// then
 		assert_eq!(pool.status().ready, 1);
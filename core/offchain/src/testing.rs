@@ -66,7 +66,11 @@ pub struct State {
 	/// Persistent local storage
 	pub persistent_storage: client::in_mem::OffchainStorage,
 	/// Local storage
+	///
+	/// Register the ancestry of the blocks with `OffchainStorage::note_block` to make it fork-aware.
 	pub local_storage: client::in_mem::OffchainStorage,
+	/// Hash of the block the `LOCAL` storage is accessed at.
+	pub block: Vec<u8>,
 	/// A vector of transactions submitted from the runtime.
 	pub transactions: Vec<Vec<u8>>,
 }
@@ -170,11 +174,11 @@ impl offchain::Externalities for TestOffchainExt {
 	}
 
 	fn local_storage_set(&mut self, kind: StorageKind, key: &[u8], value: &[u8]) {
-		let mut state = self.0.write();
+		let state = &mut *self.0.write();
 		match kind {
-			StorageKind::LOCAL => &mut state.local_storage,
-			StorageKind::PERSISTENT => &mut state.persistent_storage,
-		}.set(b"", key, value);
+			StorageKind::LOCAL => state.local_storage.set_local(&state.block, key, value),
+			StorageKind::PERSISTENT => state.persistent_storage.set(b"", key, value),
+		}
 	}
 
 	fn local_storage_compare_and_set(
@@ -184,19 +188,21 @@ impl offchain::Externalities for TestOffchainExt {
 		old_value: Option<&[u8]>,
 		new_value: &[u8]
 	) -> bool {
-		let mut state = self.0.write();
+		let state = &mut *self.0.write();
 		match kind {
-			StorageKind::LOCAL => &mut state.local_storage,
-			StorageKind::PERSISTENT => &mut state.persistent_storage,
-		}.compare_and_set(b"", key, old_value, new_value)
+			StorageKind::LOCAL => state.local_storage
+				.compare_and_set_local(&state.block, key, old_value, new_value),
+			StorageKind::PERSISTENT => state.persistent_storage
+				.compare_and_set(b"", key, old_value, new_value),
+		}
 	}
 
 	fn local_storage_get(&mut self, kind: StorageKind, key: &[u8]) -> Option<Vec<u8>> {
 		let state = self.0.read();
 		match kind {
-			StorageKind::LOCAL => &state.local_storage,
-			StorageKind::PERSISTENT => &state.persistent_storage,
-		}.get(b"", key)
+			StorageKind::LOCAL => state.local_storage.get_local(&state.block, key),
+			StorageKind::PERSISTENT => state.persistent_storage.get(b"", key),
+		}
 	}
 
 	fn http_request_start(&mut self, method: &str, uri: &str, meta: &[u8]) -> Result<RequestId, ()> {
@@ -305,3 +311,44 @@ impl offchain::Externalities for TestOffchainExt {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use primitives::offchain::Externalities as _;
+
+	#[test]
+	fn local_storage_should_be_fork_aware() {
+		// 1 - 2a
+		//   \ 2b
+		let (mut ext, state) = TestOffchainExt::new();
+		state.write().local_storage.note_block(b"2a", b"1");
+		state.write().local_storage.note_block(b"2b", b"1");
+
+		state.write().block = b"1".to_vec();
+		ext.local_storage_set(StorageKind::LOCAL, b"key", b"1");
+
+		state.write().block = b"2a".to_vec();
+		assert_eq!(ext.local_storage_get(StorageKind::LOCAL, b"key"), Some(b"1".to_vec()));
+		assert!(ext.local_storage_compare_and_set(StorageKind::LOCAL, b"key", Some(b"1"), b"2a"));
+		assert_eq!(ext.local_storage_get(StorageKind::LOCAL, b"key"), Some(b"2a".to_vec()));
+
+		state.write().block = b"2b".to_vec();
+		assert_eq!(ext.local_storage_get(StorageKind::LOCAL, b"key"), Some(b"1".to_vec()));
+		assert!(!ext.local_storage_compare_and_set(StorageKind::LOCAL, b"key", Some(b"2a"), b"2b"));
+	}
+
+	#[test]
+	fn persistent_storage_should_be_shared_across_forks() {
+		let (mut ext, state) = TestOffchainExt::new();
+		state.write().local_storage.note_block(b"2a", b"1");
+		state.write().local_storage.note_block(b"2b", b"1");
+
+		state.write().block = b"2a".to_vec();
+		ext.local_storage_set(StorageKind::PERSISTENT, b"key", b"value");
+
+		state.write().block = b"2b".to_vec();
+		assert_eq!(ext.local_storage_get(StorageKind::PERSISTENT, b"key"), Some(b"value".to_vec()));
+		assert_eq!(ext.local_storage_get(StorageKind::LOCAL, b"key"), None);
+	}
+}
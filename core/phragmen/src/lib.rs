@@ -128,6 +128,13 @@ pub struct Support<AccountId> {
 /// A linkage from a candidate and its [`Support`].
 pub type SupportMap<A> = BTreeMap<A, Support<A>>;
 
+/// The score of an election result, used to compare two alternative solutions for the same set of
+/// candidates and voters.
+///
+/// It is made of, in order: the minimum support of any elected candidate (to be maximized), the
+/// sum of all supports (to be maximized) and the sum of all supports squared (to be minimized).
+pub type PhragmenScore = [ExtendedBalance; 3];
+
 /// Perform election based on Phragmén algorithm.
 ///
 /// Returns an `Option` the set of winners and their detailed support ratio from each voter if
@@ -403,6 +410,43 @@ pub fn build_support_map<Balance, AccountId, FS, C>(
 	supports
 }
 
+/// Evaluate the score of the given support map. See [`PhragmenScore`] for what is computed.
+///
+/// The supports are expected to be built from the solution that is being scored, e.g. through
+/// [`build_support_map`].
+pub fn evaluate_support<AccountId>(support: &SupportMap<AccountId>) -> PhragmenScore {
+	let mut min_support = ExtendedBalance::max_value();
+	let mut sum: ExtendedBalance = Zero::zero();
+	// NOTE: this might saturate for very large supports. Votes are converted from `u64` so a
+	// single squared support always fits.
+	let mut sum_squared: ExtendedBalance = Zero::zero();
+	for (_, support) in support.iter() {
+		sum = sum.saturating_add(support.total);
+		let squared = support.total.saturating_mul(support.total);
+		sum_squared = sum_squared.saturating_add(squared);
+		if support.total < min_support {
+			min_support = support.total;
+		}
+	}
+	if support.is_empty() {
+		min_support = Zero::zero();
+	}
+	[min_support, sum, sum_squared]
+}
+
+/// Compare two election scores and return `true` if `this` is strictly better than `that`.
+///
+/// The comparison is lexicographic: a larger minimum support wins, then a larger sum of supports,
+/// then a smaller sum of supports squared.
+pub fn is_score_better(this: PhragmenScore, that: PhragmenScore) -> bool {
+	use rstd::cmp::Ordering;
+	match this[0].cmp(&that[0]).then(this[1].cmp(&that[1])) {
+		Ordering::Greater => true,
+		Ordering::Less => false,
+		Ordering::Equal => this[2] < that[2],
+	}
+}
+
 /// Performs equalize post-processing to the output of the election algorithm. This happens in
 /// rounds. The number of rounds and the maximum diff-per-round tolerance can be tuned through input
 /// parameters.
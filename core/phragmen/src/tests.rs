@@ -19,7 +19,7 @@
 #![cfg(test)]
 
 use crate::mock::*;
-use crate::{elect, PhragmenResult};
+use crate::{elect, evaluate_support, is_score_better, PhragmenResult, Support, SupportMap};
 use support::assert_eq_uvec;
 use sr_primitives::Perbill;
 
@@ -349,3 +349,31 @@ fn phragmen_linear_equalize() {
 
 	run_and_compare(candidates, voters, stake_of, 2, 2, true);
 }
+
+#[test]
+fn evaluate_support_works() {
+	let mut support_map = SupportMap::<AccountId>::new();
+	support_map.insert(10, Support { own: 10, total: 30, others: vec![(1, 20)] });
+	support_map.insert(20, Support { own: 20, total: 40, others: vec![(1, 20)] });
+
+	assert_eq!(evaluate_support(&support_map), [30, 70, 30 * 30 + 40 * 40]);
+	assert_eq!(evaluate_support(&SupportMap::<AccountId>::new()), [0, 0, 0]);
+}
+
+#[test]
+fn score_comparison_is_lexicographical() {
+	// larger minimum support is better, regardless of the rest.
+	assert!(is_score_better([10, 20, 30], [9, 25, 5]));
+	assert!(!is_score_better([9, 25, 5], [10, 20, 30]));
+
+	// then larger sum of supports.
+	assert!(is_score_better([10, 21, 40], [10, 20, 30]));
+	assert!(!is_score_better([10, 19, 5], [10, 20, 30]));
+
+	// then smaller sum of supports squared.
+	assert!(is_score_better([10, 20, 29], [10, 20, 30]));
+	assert!(!is_score_better([10, 20, 31], [10, 20, 30]));
+
+	// equal scores are not better.
+	assert!(!is_score_better([10, 20, 30], [10, 20, 30]));
+}
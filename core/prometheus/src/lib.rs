@@ -0,0 +1,146 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Prometheus metrics endpoint.
+//!
+//! Components register their metrics in a shared [`Registry`], which is served in the Prometheus
+//! text format on the `/metrics` path of the HTTP server started by [`init_prometheus`].
+
+#![warn(missing_docs)]
+
+use std::net::SocketAddr;
+
+use prometheus::{core::Collector, Encoder, TextEncoder};
+
+pub use prometheus::{
+	self, Registry, Error as PrometheusError, Opts, HistogramOpts, Histogram, HistogramVec,
+	IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Gauge, GaugeVec,
+};
+
+/// Prometheus endpoint errors.
+#[derive(Debug, derive_more::Display, derive_more::From)]
+pub enum Error {
+	/// HTTP server error.
+	#[cfg(not(target_os = "unknown"))]
+	Hyper(hyper::Error),
+	/// Metric registration error.
+	Prometheus(PrometheusError),
+	/// The endpoint is not available on this platform.
+	#[display(fmt="The Prometheus endpoint is not available on this platform.")]
+	Unsupported,
+}
+
+impl std::error::Error for Error {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			#[cfg(not(target_os = "unknown"))]
+			Error::Hyper(ref err) => Some(err),
+			Error::Prometheus(ref err) => Some(err),
+			Error::Unsupported => None,
+		}
+	}
+}
+
+/// Register `metric` in `registry`, returning it back for convenience.
+pub fn register<T: Clone + Collector + 'static>(metric: T, registry: &Registry) -> Result<T, Error> {
+	registry.register(Box::new(metric.clone()))?;
+	Ok(metric)
+}
+
+/// Encode all the metrics of `registry` in the Prometheus text format.
+pub fn encode(registry: &Registry) -> Result<Vec<u8>, Error> {
+	let mut buffer = Vec::new();
+	TextEncoder::new().encode(&registry.gather(), &mut buffer)?;
+	Ok(buffer)
+}
+
+/// Bind the Prometheus endpoint to `address`.
+///
+/// The returned future serves the metrics of `registry` until it is dropped.
+#[cfg(not(target_os = "unknown"))]
+pub fn init_prometheus(
+	address: SocketAddr,
+	registry: Registry,
+) -> Result<impl futures01::Future<Item = (), Error = ()> + Send, Error> {
+	use futures01::Future;
+	use hyper::{Body, Request, Response, Server, StatusCode, service::service_fn_ok};
+
+	let serve_request = move |request: Request<Body>| -> Response<Body> {
+		if request.uri().path() != "/metrics" {
+			return Response::builder()
+				.status(StatusCode::NOT_FOUND)
+				.body(Body::from("Not found."))
+				.expect("Sending 404 response with a static body; qed")
+		}
+
+		match encode(&registry) {
+			Ok(buffer) => Response::builder()
+				.status(StatusCode::OK)
+				.header("Content-Type", TextEncoder::new().format_type())
+				.body(Body::from(buffer))
+				.expect("Sending metrics with a valid content type; qed"),
+			Err(e) => {
+				log::warn!(target: "prometheus", "Unable to encode metrics: {}", e);
+				Response::builder()
+					.status(StatusCode::INTERNAL_SERVER_ERROR)
+					.body(Body::empty())
+					.expect("Sending 500 response with an empty body; qed")
+			},
+		}
+	};
+
+	let server = Server::try_bind(&address)?
+		.serve(move || service_fn_ok(serve_request.clone()));
+	log::info!(target: "prometheus", "Prometheus metrics served on http://{}/metrics", server.local_addr());
+
+	Ok(server.map_err(|e| log::error!(target: "prometheus", "Prometheus server error: {}", e)))
+}
+
+/// Bind the Prometheus endpoint to `address`.
+///
+/// There is no HTTP server on this platform, so this always fails.
+#[cfg(target_os = "unknown")]
+pub fn init_prometheus(
+	_address: SocketAddr,
+	_registry: Registry,
+) -> Result<futures01::future::Empty<(), ()>, Error> {
+	Err(Error::Unsupported)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn registered_metrics_are_encoded() {
+		let registry = Registry::new();
+		let gauge = register(IntGauge::new("test_gauge", "A test gauge").unwrap(), &registry).unwrap();
+		gauge.set(42);
+
+		let encoded = String::from_utf8(encode(&registry).unwrap()).unwrap();
+		assert!(encoded.contains("# HELP test_gauge A test gauge"));
+		assert!(encoded.contains("test_gauge 42"));
+	}
+
+	#[test]
+	fn registering_twice_fails() {
+		let registry = Registry::new();
+		let gauge = IntGauge::new("test_gauge", "A test gauge").unwrap();
+		register(gauge.clone(), &registry).unwrap();
+
+		assert!(register(gauge, &registry).is_err());
+	}
+}
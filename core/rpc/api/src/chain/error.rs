@@ -32,6 +32,9 @@ pub enum Error {
 	/// Client error.
 	#[display(fmt="Client error: {}", _0)]
 	Client(Box<dyn std::error::Error + Send>),
+	/// Requested block data has been pruned.
+	#[display(fmt="Block data has been pruned: {}", _0)]
+	Pruned(String),
 	/// Other error type.
 	Other(String),
 }
@@ -56,6 +59,11 @@ impl From<Error> for rpc::Error {
 				message,
 				data: None,
 			},
+			Error::Pruned(block) => rpc::Error {
+				code: rpc::ErrorCode::ServerError(BASE_ERROR + 2),
+				message: format!("Block data has been pruned: {}", block),
+				data: None,
+			},
 			e => errors::internal(e),
 		}
 	}
@@ -278,5 +278,8 @@ fn subscribe_headers<B, E, Block, RA, F, G, S, ERR>(
 }
 
 fn client_err(err: client::error::Error) -> Error {
-	Error::Client(Box::new(err))
+	match err {
+		client::error::Error::BlockBodyPruned(block) => Error::Pruned(block),
+		err => Error::Client(Box::new(err)),
+	}
 }
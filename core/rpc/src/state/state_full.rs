@@ -80,6 +80,40 @@ impl<B, E, Block: BlockT, RA> FullState<B, E, Block, RA>
 		Ok(hash.unwrap_or_else(|| self.client.info().chain.best_hash))
 	}
 
+	/// Returns the number of the given block if it is on the canonical chain.
+	fn canonical_number(&self, block: Block::Hash) -> ClientResult<Option<NumberFor<Block>>> {
+		let number = match self.client.header(&BlockId::Hash(block))? {
+			Some(header) => *header.number(),
+			None => return Ok(None),
+		};
+		Ok(match self.client.block_hash(number)? {
+			Some(canonical) if canonical == block => Some(number),
+			_ => None,
+		})
+	}
+
+	/// Reads the value of `key` at `block`, using the storage index if the backend keeps one and
+	/// the block is canonical.
+	fn storage_at(&self, block: Block::Hash, key: &StorageKey) -> ClientResult<Option<StorageData>> {
+		if let Some(number) = self.canonical_number(block)? {
+			if let Some(value) = self.client.indexed_storage(number, key)? {
+				return Ok(value);
+			}
+		}
+		self.client.storage(&BlockId::Hash(block), key)
+	}
+
+	/// Reads the hash of the value of `key` at `block`, using the storage index if the backend
+	/// keeps one and the block is canonical.
+	fn storage_hash_at(&self, block: Block::Hash, key: &StorageKey) -> ClientResult<Option<Block::Hash>> {
+		if let Some(number) = self.canonical_number(block)? {
+			if let Some(value_hash) = self.client.indexed_storage_hash(number, key)? {
+				return Ok(value_hash);
+			}
+		}
+		self.client.storage_hash(&BlockId::Hash(block), key)
+	}
+
 	/// Splits the `query_storage` block range into 'filtered' and 'unfiltered' subranges.
 	/// Blocks that contain changes within filtered subrange could be filtered using changes tries.
 	/// Blocks that contain changes within unfiltered subrange must be filtered manually.
@@ -220,6 +254,63 @@ impl<B, E, Block: BlockT, RA> FullState<B, E, Block, RA>
 		changes.extend(changes_map.into_iter().map(|(_, cs)| cs));
 		Ok(())
 	}
+
+	/// Collects the changes of keys within the whole range using the storage index of the backend.
+	///
+	/// Returns `false` without collecting anything if the backend doesn't keep a storage index or
+	/// if the range isn't on the canonical chain.
+	fn query_storage_indexed(
+		&self,
+		range: &QueryStorageRange<Block>,
+		keys: &[StorageKey],
+		changes: &mut Vec<StorageChangeSet<Block::Hash>>,
+	) -> Result<bool> {
+		let last_index = range.hashes.len() - 1;
+		let last_number = range.first_number + last_index.saturated_into();
+		let last_hash = self.client.block_hash(last_number).map_err(client_err)?;
+		if last_hash.as_ref() != range.hashes.last() {
+			return Ok(false);
+		}
+
+		let mut changes_map: BTreeMap<NumberFor<Block>, StorageChangeSet<Block::Hash>> = BTreeMap::new();
+		for key in keys {
+			let key_changes = match self.client
+				.indexed_storage_changes(range.first_number, last_number, key)
+				.map_err(client_err)?
+			{
+				Some(key_changes) => key_changes,
+				None => return Ok(false),
+			};
+
+			// the value at the first block is always reported
+			let first_hash = range.hashes[0].clone();
+			let first_value = self.client
+				.storage(&BlockId::Hash(first_hash), key)
+				.map_err(client_err)?;
+			changes_map.entry(range.first_number)
+				.or_insert_with(|| StorageChangeSet { block: first_hash, changes: Vec::new() })
+				.changes.push((key.clone(), first_value));
+
+			let mut last_value_hash = None;
+			for (block, value_hash) in key_changes {
+				if block <= range.first_number || value_hash == last_value_hash {
+					last_value_hash = value_hash;
+					continue;
+				}
+
+				let block_hash = range.hashes[(block - range.first_number).saturated_into::<usize>()].clone();
+				let value_at_block = self.client
+					.storage(&BlockId::Hash(block_hash), key)
+					.map_err(client_err)?;
+				changes_map.entry(block)
+					.or_insert_with(|| StorageChangeSet { block: block_hash, changes: Vec::new() })
+					.changes.push((key.clone(), value_at_block));
+				last_value_hash = value_hash;
+			}
+		}
+		changes.extend(changes_map.into_iter().map(|(_, cs)| cs));
+		Ok(true)
+	}
 }
 
 impl<B, E, Block, RA> StateBackend<B, E, Block, RA> for FullState<B, E, Block, RA>
@@ -272,7 +363,7 @@ impl<B, E, Block, RA> StateBackend<B, E, Block, RA> for FullState<B, E, Block, R
 	) -> FutureResult<Option<StorageData>> {
 		Box::new(result(
 			self.block_or_best(block)
-				.and_then(|block| self.client.storage(&BlockId::Hash(block), &key))
+				.and_then(|block| self.storage_at(block, &key))
 				.map_err(client_err)))
 	}
 
@@ -283,7 +374,7 @@ impl<B, E, Block, RA> StateBackend<B, E, Block, RA> for FullState<B, E, Block, R
 	) -> FutureResult<Option<Block::Hash>> {
 		Box::new(result(
 			self.block_or_best(block)
-				.and_then(|block| self.client.storage_hash(&BlockId::Hash(block), &key))
+				.and_then(|block| self.storage_hash_at(block, &key))
 				.map_err(client_err)))
 	}
 
@@ -346,9 +437,11 @@ impl<B, E, Block, RA> StateBackend<B, E, Block, RA> for FullState<B, E, Block, R
 		let call_fn = move || {
 			let range = self.split_query_storage_range(from, to)?;
 			let mut changes = Vec::new();
-			let mut last_values = HashMap::new();
-			self.query_storage_unfiltered(&range, &keys, &mut last_values, &mut changes)?;
-			self.query_storage_filtered(&range, &keys, &last_values, &mut changes)?;
+			if !self.query_storage_indexed(&range, &keys, &mut changes)? {
+				let mut last_values = HashMap::new();
+				self.query_storage_unfiltered(&range, &keys, &mut last_values, &mut changes)?;
+				self.query_storage_filtered(&range, &keys, &last_values, &mut changes)?;
+			}
 			Ok(changes)
 		};
 		Box::new(result(call_fn()))
@@ -23,6 +23,7 @@ use assert_matches::assert_matches;
 use futures::stream::Stream;
 use primitives::storage::well_known_keys;
 use sr_io::blake2_256;
+use sr_primitives::generic::BlockId;
 use test_client::{
 	prelude::*,
 	consensus::BlockOrigin,
@@ -259,6 +260,79 @@ fn should_query_storage() {
 
 	run_tests(Arc::new(test_client::new()));
 	run_tests(Arc::new(TestClientBuilder::new().set_support_changes_trie(true).build()));
+	run_tests(Arc::new(indexed_test_client()));
+}
+
+/// A test client whose backend keeps a storage index.
+fn indexed_test_client() -> TestClient {
+	use test_client::client_db::{
+		self, DatabaseSettings, DatabaseSettingsSrc, PruningMode, BlocksPruning, database::MemDb,
+	};
+
+	let backend = client_db::Backend::new(DatabaseSettings {
+		state_cache_size: 16777216,
+		state_cache_child_ratio: Some((50, 100)),
+		pruning: PruningMode::ArchiveAll,
+		source: DatabaseSettingsSrc::Custom(Arc::new(MemDb::default())),
+		storage_index: true,
+		blocks_pruning: BlocksPruning::KeepAll,
+	}, u64::max_value()).unwrap();
+	TestClientBuilder::with_backend(Arc::new(backend)).build()
+}
+
+#[test]
+fn should_query_storage_through_the_storage_index() {
+	let core = tokio::runtime::Runtime::new().unwrap();
+	let client = Arc::new(indexed_test_client());
+	let api = new_full(client.clone(), Subscriptions::new(Arc::new(core.executor())));
+	let key = StorageKey(vec![1]);
+
+	let add_block = |parent, value| {
+		let mut builder = client.new_block_at(&BlockId::Hash(parent), Default::default()).unwrap();
+		builder.push_storage_change(vec![1], Some(vec![value])).unwrap();
+		let block = builder.bake().unwrap();
+		let hash = block.header.hash();
+		client.import(BlockOrigin::Own, block).unwrap();
+		hash
+	};
+	let genesis_hash = client.genesis_hash();
+	let block1_hash = add_block(genesis_hash, 1);
+	let block2_hash = add_block(block1_hash, 2);
+	let fork_hash = add_block(genesis_hash, 3);
+	assert_eq!(client.info().chain.best_hash, block2_hash);
+	assert!(client.indexed_storage_changes(0, 2, &key).unwrap().is_some());
+
+	// the canonical range is read from the index
+	let result = api.query_storage(vec![key.clone()], genesis_hash, Some(block2_hash).into());
+	assert_eq!(result.wait().unwrap(), vec![
+		StorageChangeSet { block: genesis_hash, changes: vec![(key.clone(), None)] },
+		StorageChangeSet { block: block1_hash, changes: vec![(key.clone(), Some(StorageData(vec![1])))] },
+		StorageChangeSet { block: block2_hash, changes: vec![(key.clone(), Some(StorageData(vec![2])))] },
+	]);
+
+	// the fork isn't indexed and is read from the state
+	let result = api.query_storage(vec![key.clone()], genesis_hash, Some(fork_hash).into());
+	assert_eq!(result.wait().unwrap(), vec![
+		StorageChangeSet { block: genesis_hash, changes: vec![(key.clone(), None)] },
+		StorageChangeSet { block: fork_hash, changes: vec![(key.clone(), Some(StorageData(vec![3])))] },
+	]);
+
+	assert_eq!(
+		api.storage(key.clone(), Some(block1_hash).into()).wait().unwrap(),
+		Some(StorageData(vec![1])),
+	);
+	assert_eq!(api.storage(key.clone(), Some(fork_hash).into()).wait().unwrap(), Some(StorageData(vec![3])));
+	assert_eq!(api.storage(key.clone(), Some(genesis_hash).into()).wait().unwrap(), None);
+	assert_eq!(
+		api.storage_hash(key.clone(), None.into()).wait().unwrap(),
+		Some(blake2_256(&[2]).into()),
+	);
+	assert_eq!(
+		client.indexed_storage_hash(2, &key).unwrap(),
+		Some(Some(blake2_256(&[2]).into())),
+	);
+	assert_eq!(client.indexed_storage(1, &key).unwrap(), Some(Some(StorageData(vec![1]))));
+	assert_eq!(client.indexed_storage(0, &key).unwrap(), Some(None));
 }
 
 #[test]
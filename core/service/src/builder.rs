@@ -16,7 +16,7 @@
 
 use crate::{Service, NetworkStatus, NetworkState, error::{self, Error}, DEFAULT_PROTOCOL_ID};
 use crate::{SpawnTaskHandle, start_rpc_servers, build_network_future, TransactionPoolAdapter};
-use crate::status_sinks;
+use crate::{metrics::ServiceMetrics, status_sinks};
 use crate::config::{Configuration, DatabaseConfig};
 use client::{
 	BlockchainEvents, Client, runtime_api,
@@ -37,8 +37,9 @@ use log::{info, warn};
 use network::{FinalityProofProvider, OnDemand, NetworkService, NetworkStateInfo, DhtEvent};
 use network::{config::BoxFinalityProofRequestBuilder, specialization::NetworkSpecialization};
 use parking_lot::{Mutex, RwLock};
-use primitives::{Blake2Hasher, H256, Hasher};
+use primitives::{Blake2Hasher, H256, Hasher, storage::{StorageKey, well_known_keys}};
 use rpc;
+use sr_primitives::{StorageOverlay, ChildrenStorageOverlay};
 use sr_primitives::generic::BlockId;
 use sr_primitives::traits::{
 	Block as BlockT, Extrinsic, ProvideRuntimeApi, NumberFor, One, Zero, Header, SaturatedConversion
@@ -84,6 +85,7 @@ pub struct ServiceBuilder<TBl, TRtApi, TCfg, TGen, TCSExt, TCl, TFchr, TSc, TImp
 	rpc_extensions: TRpc,
 	remote_backend: Option<Arc<dyn RemoteBlockchain<TBl>>>,
 	dht_event_tx: Option<mpsc::Sender<DhtEvent>>,
+	prometheus_registry: Option<substrate_prometheus::Registry>,
 	marker: PhantomData<(TBl, TRtApi)>,
 }
 
@@ -180,9 +182,16 @@ where TGen: RuntimeGenesis, TCSExt: Extension {
 							path: path.clone(),
 							cache_size: cache_size.clone().map(|u| u as usize),
 						},
+					DatabaseConfig::Sled { path, flush_on_commit } =>
+						client_db::DatabaseSettingsSrc::Sled {
+							path: path.clone(),
+							flush_on_commit: *flush_on_commit,
+						},
 					DatabaseConfig::Custom(db) =>
 						client_db::DatabaseSettingsSrc::Custom(db.clone()),
 				},
+				storage_index: config.storage_index,
+				blocks_pruning: config.blocks_pruning,
 			};
 			
 			client_db::new_client(
@@ -212,6 +221,7 @@ where TGen: RuntimeGenesis, TCSExt: Extension {
 			rpc_extensions: Default::default(),
 			remote_backend: None,
 			dht_event_tx: None,
+			prometheus_registry: None,
 			marker: PhantomData,
 		})
 	}
@@ -255,9 +265,16 @@ where TGen: RuntimeGenesis, TCSExt: Extension {
 							path: path.clone(),
 							cache_size: cache_size.clone().map(|u| u as usize),
 						},
+					DatabaseConfig::Sled { path, flush_on_commit } =>
+						client_db::DatabaseSettingsSrc::Sled {
+							path: path.clone(),
+							flush_on_commit: *flush_on_commit,
+						},
 					DatabaseConfig::Custom(db) =>
 						client_db::DatabaseSettingsSrc::Custom(db.clone()),
 				},
+				storage_index: false,
+				blocks_pruning: client_db::BlocksPruning::KeepAll,
 			};
 			client_db::light::LightStorage::new(db_settings)?
 		};
@@ -287,6 +304,7 @@ where TGen: RuntimeGenesis, TCSExt: Extension {
 			rpc_extensions: Default::default(),
 			remote_backend: Some(remote_blockchain),
 			dht_event_tx: None,
+			prometheus_registry: None,
 			marker: PhantomData,
 		})
 	}
@@ -336,6 +354,7 @@ impl<TBl, TRtApi, TCfg, TGen, TCSExt, TCl, TFchr, TSc, TImpQu, TFprb, TFpp, TNet
 			rpc_extensions: self.rpc_extensions,
 			remote_backend: self.remote_backend,
 			dht_event_tx: self.dht_event_tx,
+			prometheus_registry: self.prometheus_registry,
 			marker: self.marker,
 		})
 	}
@@ -379,6 +398,7 @@ impl<TBl, TRtApi, TCfg, TGen, TCSExt, TCl, TFchr, TSc, TImpQu, TFprb, TFpp, TNet
 			rpc_extensions: self.rpc_extensions,
 			remote_backend: self.remote_backend,
 			dht_event_tx: self.dht_event_tx,
+			prometheus_registry: self.prometheus_registry,
 			marker: self.marker,
 		})
 	}
@@ -406,6 +426,7 @@ impl<TBl, TRtApi, TCfg, TGen, TCSExt, TCl, TFchr, TSc, TImpQu, TFprb, TFpp, TNet
 			rpc_extensions: self.rpc_extensions,
 			remote_backend: self.remote_backend,
 			dht_event_tx: self.dht_event_tx,
+			prometheus_registry: self.prometheus_registry,
 			marker: self.marker,
 		})
 	}
@@ -448,6 +469,7 @@ impl<TBl, TRtApi, TCfg, TGen, TCSExt, TCl, TFchr, TSc, TImpQu, TFprb, TFpp, TNet
 			rpc_extensions: self.rpc_extensions,
 			remote_backend: self.remote_backend,
 			dht_event_tx: self.dht_event_tx,
+			prometheus_registry: self.prometheus_registry,
 			marker: self.marker,
 		})
 	}
@@ -514,6 +536,7 @@ impl<TBl, TRtApi, TCfg, TGen, TCSExt, TCl, TFchr, TSc, TImpQu, TFprb, TFpp, TNet
 			rpc_extensions: self.rpc_extensions,
 			remote_backend: self.remote_backend,
 			dht_event_tx: self.dht_event_tx,
+			prometheus_registry: self.prometheus_registry,
 			marker: self.marker,
 		})
 	}
@@ -561,6 +584,7 @@ impl<TBl, TRtApi, TCfg, TGen, TCSExt, TCl, TFchr, TSc, TImpQu, TFprb, TFpp, TNet
 			rpc_extensions: self.rpc_extensions,
 			remote_backend: self.remote_backend,
 			dht_event_tx: self.dht_event_tx,
+			prometheus_registry: self.prometheus_registry,
 			marker: self.marker,
 		})
 	}
@@ -588,6 +612,7 @@ impl<TBl, TRtApi, TCfg, TGen, TCSExt, TCl, TFchr, TSc, TImpQu, TFprb, TFpp, TNet
 			rpc_extensions,
 			remote_backend: self.remote_backend,
 			dht_event_tx: self.dht_event_tx,
+			prometheus_registry: self.prometheus_registry,
 			marker: self.marker,
 		})
 	}
@@ -614,6 +639,36 @@ impl<TBl, TRtApi, TCfg, TGen, TCSExt, TCl, TFchr, TSc, TImpQu, TFprb, TFpp, TNet
 			rpc_extensions: self.rpc_extensions,
 			remote_backend: self.remote_backend,
 			dht_event_tx: Some(dht_event_tx),
+			prometheus_registry: self.prometheus_registry,
+			marker: self.marker,
+		})
+	}
+
+	/// Defines the registry served by the Prometheus endpoint, so that components built outside
+	/// of the service can register their own metrics in it.
+	///
+	/// A fresh registry is used if none is provided and the endpoint is enabled.
+	pub fn with_prometheus_registry(
+		self,
+		registry: substrate_prometheus::Registry,
+	) -> Result<ServiceBuilder<TBl, TRtApi, TCfg, TGen, TCSExt, TCl, TFchr, TSc, TImpQu, TFprb, TFpp,
+								TNetP, TExPool, TRpc, Backend>, Error> {
+		Ok(ServiceBuilder {
+			config: self.config,
+			client: self.client,
+			backend: self.backend,
+			keystore: self.keystore,
+			fetcher: self.fetcher,
+			select_chain: self.select_chain,
+			import_queue: self.import_queue,
+			finality_proof_request_builder: self.finality_proof_request_builder,
+			finality_proof_provider: self.finality_proof_provider,
+			network_protocol: self.network_protocol,
+			transaction_pool: self.transaction_pool,
+			rpc_extensions: self.rpc_extensions,
+			remote_backend: self.remote_backend,
+			dht_event_tx: self.dht_event_tx,
+			prometheus_registry: Some(registry),
 			marker: self.marker,
 		})
 	}
@@ -660,6 +715,26 @@ pub trait ServiceBuilderRevert {
 	) -> Result<(), Error>;
 }
 
+/// Implemented on `ServiceBuilder`. Allows exporting the raw state of the chain once you have
+/// given all the required components to the builder.
+pub trait ServiceBuilderExportState {
+	/// Type of block of the builder.
+	type Block: BlockT;
+
+	/// Exports the top and child storage at `block`, or at the best block if unspecified.
+	fn export_raw_state(
+		&self,
+		block: Option<NumberFor<Self::Block>>,
+	) -> Result<(StorageOverlay, ChildrenStorageOverlay), Error>;
+}
+
+/// Implemented on `ServiceBuilder`. Allows checking the integrity of the database once you have
+/// given all the required components to the builder.
+pub trait ServiceBuilderCheckDb {
+	/// Checks the database, repairing the recoverable issues if `repair` is set.
+	fn check_db(&self, repair: bool) -> Result<(), Error>;
+}
+
 impl<
 	TBl, TRtApi, TCfg, TGen, TCSExt, TBackend,
 	TExec, TFchr, TSc, TImpQu, TFprb, TFpp, TNetP,
@@ -728,6 +803,55 @@ where
 	}
 }
 
+impl<TBl, TRtApi, TCfg, TGen, TCSExt, TBackend, TExec, TFchr, TSc, TImpQu, TFprb, TFpp, TNetP, TExPool, TRpc>
+	ServiceBuilderExportState for ServiceBuilder<TBl, TRtApi, TCfg, TGen, TCSExt, Client<TBackend, TExec, TBl, TRtApi>,
+		TFchr, TSc, TImpQu, TFprb, TFpp, TNetP, TExPool, TRpc, TBackend>
+where
+	TBl: BlockT<Hash = <Blake2Hasher as Hasher>::Out>,
+	TBackend: 'static + client::backend::Backend<TBl, Blake2Hasher> + Send,
+	TExec: 'static + client::CallExecutor<TBl, Blake2Hasher> + Send + Sync + Clone
+{
+	type Block = TBl;
+
+	fn export_raw_state(
+		&self,
+		block: Option<NumberFor<TBl>>,
+	) -> Result<(StorageOverlay, ChildrenStorageOverlay), Error> {
+		let client = &self.client;
+		export_raw_state!(client, block)
+	}
+}
+
+impl<TBl, TRtApi, TCfg, TGen, TCSExt, TExec, TFchr, TSc, TImpQu, TFprb, TFpp, TNetP, TExPool, TRpc>
+	ServiceBuilderCheckDb for ServiceBuilder<TBl, TRtApi, TCfg, TGen, TCSExt,
+		Client<TFullBackend<TBl>, TExec, TBl, TRtApi>,
+		TFchr, TSc, TImpQu, TFprb, TFpp, TNetP, TExPool, TRpc, TFullBackend<TBl>>
+where
+	TBl: BlockT<Hash = <Blake2Hasher as Hasher>::Out>,
+{
+	fn check_db(&self, repair: bool) -> Result<(), Error> {
+		let report = self.backend.check_database(repair)?;
+		crate::chain_ops::report_db_check(report)
+	}
+}
+
+impl<TBl, TRtApi, TCfg, TGen, TCSExt, TExec, TFchr, TSc, TImpQu, TFprb, TFpp, TNetP, TExPool, TRpc>
+	ServiceBuilderCheckDb for ServiceBuilder<TBl, TRtApi, TCfg, TGen, TCSExt,
+		Client<TLightBackend<TBl>, TExec, TBl, TRtApi>,
+		TFchr, TSc, TImpQu, TFprb, TFpp, TNetP, TExPool, TRpc, TLightBackend<TBl>>
+where
+	TBl: BlockT<Hash = <Blake2Hasher as Hasher>::Out>,
+{
+	fn check_db(&self, repair: bool) -> Result<(), Error> {
+		// nothing the light client stores can be repaired.
+		if repair {
+			warn!("Repairing is only supported for full nodes, checking only");
+		}
+		let report = self.backend.blockchain().storage().check_database()?;
+		crate::chain_ops::report_db_check(report)
+	}
+}
+
 impl<TBl, TRtApi, TCfg, TGen, TCSExt, TBackend, TExec, TSc, TImpQu, TNetP, TExPoolApi, TRpc>
 ServiceBuilder<
 	TBl,
@@ -795,6 +919,7 @@ ServiceBuilder<
 			rpc_extensions,
 			remote_backend,
 			dht_event_tx,
+			prometheus_registry,
 		} = self;
 
 		session::generate_initial_session_keys(
@@ -808,7 +933,22 @@ ServiceBuilder<
 		let (to_spawn_tx, to_spawn_rx) =
 			mpsc::unbounded::<Box<dyn Future<Item = (), Error = ()> + Send>>();
 
-		let import_queue = Box::new(import_queue);
+		// The registry is only created on demand, but a registry given by the user is always
+		// filled so that it can be exposed by other means than our endpoint.
+		let prometheus_registry = match config.prometheus_port {
+			Some(_) => Some(prometheus_registry.unwrap_or_default()),
+			None => prometheus_registry,
+		};
+
+		let mut import_queue = Box::new(import_queue);
+		let service_metrics = match prometheus_registry.as_ref() {
+			Some(registry) => {
+				import_queue.register_metrics(registry)?;
+				Some(ServiceMetrics::register(registry)?)
+			},
+			None => None,
+		};
+
 		let chain_info = client.info().chain;
 
 		let version = config.full_version();
@@ -886,7 +1026,6 @@ ServiceBuilder<
 			let events = client.import_notification_stream()
 				.map(|v| Ok::<_, ()>(v)).compat()
 				.for_each(move |notification| {
-					let number = *notification.header.number();
 					let txpool = txpool.upgrade();
 
 					if let (Some(txpool), Some(client)) = (txpool.as_ref(), wclient.upgrade()) {
@@ -901,8 +1040,12 @@ ServiceBuilder<
 
 					let offchain = offchain.as_ref().and_then(|o| o.upgrade());
 					if let (Some(txpool), Some(offchain)) = (txpool, offchain) {
-						let future = offchain.on_block_imported(&number, &txpool, network_state_info.clone(), is_validator)
-							.map(|()| Ok(()));
+						let future = offchain.on_block_imported(
+							&notification.header,
+							&txpool,
+							network_state_info.clone(),
+							is_validator,
+						).map(|()| Ok(()));
 						let _ = to_spawn_tx_.unbounded_send(Box::new(Compat::new(future)));
 					}
 
@@ -958,6 +1101,30 @@ ServiceBuilder<
 				None => 0,
 			};
 
+			if let Some(metrics) = service_metrics.as_ref() {
+				metrics.block_height.with_label_values(&["best"]).set(best_number as i64);
+				metrics.block_height.with_label_values(&["finalized"]).set(finalized_number as i64);
+				if let Some(best_seen_block) = net_status.best_seen_block {
+					metrics.block_height.with_label_values(&["sync_target"])
+						.set(best_seen_block.saturated_into::<u64>() as i64);
+				}
+				metrics.transactions.with_label_values(&["ready"]).set(txpool_status.ready as i64);
+				metrics.transactions.with_label_values(&["future"]).set(txpool_status.future as i64);
+				metrics.peers.with_label_values(&["connected"]).set(num_peers as i64);
+				metrics.peers.with_label_values(&["sync"]).set(net_status.num_sync_peers as i64);
+				metrics.bandwidth.with_label_values(&["in"]).set(bandwidth_download as i64);
+				metrics.bandwidth.with_label_values(&["out"]).set(bandwidth_upload as i64);
+				metrics.queued_blocks.set(net_status.num_queued_blocks as i64);
+				metrics.state_cache_size.set(used_state_cache_size as i64);
+				if let Some((hits, misses)) = info.state_cache_hit_stats {
+					// the backend reports totals, the counters only move forward by the difference.
+					for (result, total) in &[("hit", hits), ("miss", misses)] {
+						let counter = metrics.state_cache_lookups.with_label_values(&[*result]);
+						counter.inc_by(total.saturating_sub(counter.get() as u64) as i64);
+					}
+				}
+			}
+
 			// get cpu usage and memory usage of this process
 			let (cpu_usage, memory) = if let Some(self_pid) = self_pid {
 				if sys.refresh_process(self_pid) {
@@ -1060,6 +1227,14 @@ ServiceBuilder<
 		let rpc_handlers = gen_handler();
 		let rpc = start_rpc_servers(&config, gen_handler)?;
 
+		// Prometheus endpoint
+		if let (Some(address), Some(registry)) = (config.prometheus_port, prometheus_registry.clone()) {
+			let server = substrate_prometheus::init_prometheus(address, registry)?;
+			let _ = to_spawn_tx.unbounded_send(Box::new(server
+				.select(exit.clone())
+				.then(|_| Ok(()))));
+		}
+
 
 		let _ = to_spawn_tx.unbounded_send(Box::new(build_network_future(
 			config.roles,
@@ -1135,6 +1310,7 @@ ServiceBuilder<
 			_offchain_workers: offchain_workers,
 			_telemetry_on_connect_sinks: telemetry_connection_sinks.clone(),
 			keystore,
+			prometheus_registry,
 			marker: PhantomData::<TBl>,
 		})
 	}
@@ -1291,4 +1467,38 @@ mod tests {
 		assert_eq!(pool.status().ready, 1);
 		assert_eq!(pool.status().future, 0);
 	}
+
+	#[test]
+	fn exported_raw_state_builds_the_same_genesis() {
+		let child_storage_key = well_known_keys::CHILD_STORAGE_KEY_PREFIX.iter()
+			.chain(b"default:child")
+			.cloned()
+			.collect::<Vec<u8>>();
+		let client = TestClientBuilder::new()
+			.add_extra_storage(b"key".to_vec(), b"value".to_vec())
+			.add_extra_child_storage(child_storage_key.clone(), b"child_key".to_vec(), b"child_value".to_vec())
+			.build();
+		let block = None;
+		let export = || -> Result<(StorageOverlay, ChildrenStorageOverlay), Error> {
+			export_raw_state!(client, block)
+		};
+		let (top, children) = export().unwrap();
+		assert_eq!(top.get(&b"key"[..]), Some(&b"value".to_vec()));
+		assert_eq!(
+			children.get(&child_storage_key).and_then(|child| child.get(&b"child_key"[..])),
+			Some(&b"child_value".to_vec()),
+		);
+
+		let mut builder = TestClientBuilder::new();
+		for (key, value) in top {
+			builder = builder.add_extra_storage(key, value);
+		}
+		for (storage_key, child) in children {
+			for (key, value) in child {
+				builder = builder.add_extra_child_storage(storage_key.clone(), key, value);
+			}
+		}
+		let imported = builder.build();
+		assert_eq!(imported.info().chain.genesis_hash, client.info().chain.genesis_hash);
+	}
 }
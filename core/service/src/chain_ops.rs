@@ -17,6 +17,7 @@
 //! Chain utilities.
 
 use crate::error;
+use log::{info, warn};
 use chain_spec::{ChainSpec, RuntimeGenesis, Extension};
 
 /// Defines the logic for an operation exporting blocks within a range.
@@ -222,6 +223,57 @@ macro_rules! revert_chain {
 }}
 }
 
+/// Exports the raw state of the chain at a given block, or at the best block if unspecified.
+#[macro_export]
+macro_rules! export_raw_state {
+($client:ident, $block:ident) => {{
+	let block = match $block {
+		Some(number) => BlockId::Number(number),
+		None => BlockId::Hash($client.info().chain.best_hash),
+	};
+	let empty_prefix = StorageKey(Vec::new());
+	let mut top = StorageOverlay::new();
+	let mut children = ChildrenStorageOverlay::new();
+
+	// the roots of the child tries are recomputed when building the genesis.
+	for (key, value) in $client.storage_pairs(&block, &empty_prefix)? {
+		if well_known_keys::is_child_storage_key(&key.0) {
+			let child = $client.child_storage_pairs(&block, &key, &empty_prefix)?;
+			children.insert(key.0, child.into_iter().map(|(k, v)| (k.0, v.0)).collect());
+		} else {
+			top.insert(key.0, value.0);
+		}
+	}
+
+	info!("Exported {} keys and {} child tries at {}", top.len(), children.len(), block);
+	Ok((top, children))
+}}
+}
+
+/// Log the outcome of a database check, failing if it found issues that weren't repaired.
+pub fn report_db_check<N, H>(report: client_db::check::CheckReport<N, H>) -> error::Result<()> where
+	N: std::fmt::Debug,
+	H: std::fmt::Debug,
+{
+	for issue in &report.issues {
+		warn!("{}", issue);
+	}
+	info!(
+		"Checked {} headers ({} reverted) and {} states. Found {} issues, repaired {}.",
+		report.headers,
+		report.reverted_headers,
+		report.states,
+		report.issues.len(),
+		report.repaired,
+	);
+
+	let unrepaired = report.issues.len() - report.repaired;
+	if unrepaired > 0 {
+		return Err(format!("{} database issues left unrepaired", unrepaired).into());
+	}
+	Ok(())
+}
+
 /// Build a chain spec json
 pub fn build_spec<G, E>(spec: ChainSpec<G, E>, raw: bool) -> error::Result<String> where
 	G: RuntimeGenesis,
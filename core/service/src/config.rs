@@ -17,11 +17,11 @@
 //! Service configuration.
 
 pub use client::ExecutionStrategies;
-pub use client_db::{kvdb::KeyValueDB, PruningMode};
+pub use client_db::{Database, PruningMode, BlocksPruning, as_database};
 pub use network::config::{ExtTransport, NetworkConfiguration, Roles};
 pub use substrate_executor::WasmExecutionMethod;
 
-use std::{path::PathBuf, net::SocketAddr, sync::Arc};
+use std::{path::{Path, PathBuf}, net::SocketAddr, sync::Arc};
 use transaction_pool;
 use chain_spec::{ChainSpec, RuntimeGenesis, Extension, NoExtension};
 use primitives::crypto::Protected;
@@ -53,6 +53,10 @@ pub struct Configuration<C, G, E = NoExtension> {
 	pub state_cache_child_ratio: Option<usize>,
 	/// Pruning settings.
 	pub pruning: PruningMode,
+	/// Keep an index of the storage changes for fast historical queries.
+	pub storage_index: bool,
+	/// Block bodies and justifications pruning settings.
+	pub blocks_pruning: BlocksPruning,
 	/// Chain configuration.
 	pub chain_spec: ChainSpec<G, E>,
 	/// Custom configuration.
@@ -71,6 +75,8 @@ pub struct Configuration<C, G, E = NoExtension> {
 	pub rpc_ws_max_connections: Option<usize>,
 	/// CORS settings for HTTP & WS servers. `None` if all origins are allowed.
 	pub rpc_cors: Option<Vec<String>>,
+	/// Prometheus endpoint binding address. `None` if disabled.
+	pub prometheus_port: Option<SocketAddr>,
 	/// Telemetry service URL. `None` if disabled.
 	pub telemetry_endpoints: Option<TelemetryEndpoints>,
 	/// External WASM transport for the telemetry. If `Some`, when connection to a telemetry
@@ -101,7 +107,7 @@ pub struct Configuration<C, G, E = NoExtension> {
 /// Configuration of the database of the client.
 #[derive(Clone)]
 pub enum DatabaseConfig {
-	/// Database file at a specific path. Recommended for most uses.
+	/// RocksDB database file at a specific path. Recommended for most uses.
 	Path {
 		/// Path to the database.
 		path: PathBuf,
@@ -109,8 +115,26 @@ pub enum DatabaseConfig {
 		cache_size: Option<u32>,
 	},
 
+	/// Sled database at a specific path.
+	Sled {
+		/// Path to the database.
+		path: PathBuf,
+		/// Whether every commit waits for the database to be flushed to disk.
+		flush_on_commit: bool,
+	},
+
 	/// A custom implementation of an already-open database.
-	Custom(Arc<dyn KeyValueDB>),
+	Custom(Arc<dyn Database>),
+}
+
+impl DatabaseConfig {
+	/// Returns the path of the database, if it is stored on disk.
+	pub fn path(&self) -> Option<&Path> {
+		match self {
+			DatabaseConfig::Path { path, .. } | DatabaseConfig::Sled { path, .. } => Some(path),
+			DatabaseConfig::Custom(_) => None,
+		}
+	}
 }
 
 impl<C, G, E> Configuration<C, G, E> where
@@ -138,12 +162,15 @@ impl<C, G, E> Configuration<C, G, E> where
 			state_cache_child_ratio: Default::default(),
 			custom: Default::default(),
 			pruning: PruningMode::default(),
+			storage_index: false,
+			blocks_pruning: BlocksPruning::KeepAll,
 			wasm_method: WasmExecutionMethod::Interpreted,
 			execution_strategies: Default::default(),
 			rpc_http: None,
 			rpc_ws: None,
 			rpc_ws_max_connections: None,
 			rpc_cors: Some(vec![]),
+			prometheus_port: None,
 			telemetry_endpoints: None,
 			telemetry_external_transport: None,
 			default_heap_pages: None,
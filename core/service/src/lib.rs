@@ -25,6 +25,7 @@ pub mod chain_ops;
 pub mod error;
 
 mod builder;
+mod metrics;
 mod status_sinks;
 
 use std::io;
@@ -54,8 +55,11 @@ use sr_primitives::generic::BlockId;
 use sr_primitives::traits::NumberFor;
 
 pub use self::error::Error;
-pub use self::builder::{ServiceBuilder, ServiceBuilderExport, ServiceBuilderImport, ServiceBuilderRevert};
-pub use config::{Configuration, Roles, PruningMode};
+pub use self::builder::{
+	ServiceBuilder, ServiceBuilderExport, ServiceBuilderImport, ServiceBuilderRevert,
+	ServiceBuilderExportState, ServiceBuilderCheckDb,
+};
+pub use config::{Configuration, Roles, PruningMode, BlocksPruning};
 pub use chain_spec::{ChainSpec, Properties, RuntimeGenesis, Extension as ChainSpecExtension};
 pub use transaction_pool::txpool::{
 	self, Pool as TransactionPool, Options as TransactionPoolOptions, ChainApi, IntoPoolError
@@ -102,6 +106,7 @@ pub struct Service<TBl, TCl, TSc, TNetStatus, TNet, TTxPool, TOc> {
 	_telemetry_on_connect_sinks: Arc<Mutex<Vec<mpsc::UnboundedSender<()>>>>,
 	_offchain_workers: Option<Arc<TOc>>,
 	keystore: keystore::KeyStorePtr,
+	prometheus_registry: Option<substrate_prometheus::Registry>,
 	marker: PhantomData<TBl>,
 }
 
@@ -168,6 +173,9 @@ pub trait AbstractService: 'static + Future<Item = (), Error = Error> +
 	/// Returns the keystore that stores keys.
 	fn keystore(&self) -> keystore::KeyStorePtr;
 
+	/// Returns the registry served by the Prometheus endpoint, if enabled.
+	fn prometheus_registry(&self) -> Option<substrate_prometheus::Registry>;
+
 	/// Starts an RPC query.
 	///
 	/// The query is passed as a string and must be a JSON text similar to what an HTTP client
@@ -233,6 +241,10 @@ where
 		self.keystore.clone()
 	}
 
+	fn prometheus_registry(&self) -> Option<substrate_prometheus::Registry> {
+		self.prometheus_registry.clone()
+	}
+
 	fn spawn_task(&self, task: impl Future<Item = (), Error = ()> + Send + 'static) {
 		let task = task.select(self.on_exit()).then(|_| Ok(()));
 		let _ = self.to_spawn_tx.unbounded_send(Box::new(task));
@@ -433,6 +445,7 @@ fn build_network_future<
 				num_active_peers: network.num_active_peers(),
 				average_download_per_sec: network.average_download_per_sec(),
 				average_upload_per_sec: network.average_upload_per_sec(),
+				num_queued_blocks: network.num_queued_blocks(),
 			};
 			let state = network.network_state();
 			(status, state)
@@ -485,6 +498,8 @@ pub struct NetworkStatus<B: BlockT> {
 	pub average_download_per_sec: u64,
 	/// Uploaded bytes per second averaged over the past few seconds.
 	pub average_upload_per_sec: u64,
+	/// Number of blocks waiting in the import queue.
+	pub num_queued_blocks: u32,
 }
 
 impl<TBl, TCl, TSc, TNetStatus, TNet, TTxPool, TOc> Drop for
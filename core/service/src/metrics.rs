@@ -0,0 +1,73 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Service metrics exposed on the Prometheus endpoint.
+
+use substrate_prometheus::{
+	register, Registry, Error, Opts, IntCounterVec, IntGauge, IntGaugeVec,
+};
+
+/// Metrics refreshed every time the service reports to the telemetry.
+pub(crate) struct ServiceMetrics {
+	/// Block height, labeled by status (`best`, `finalized` or `sync_target`).
+	pub block_height: IntGaugeVec,
+	/// Number of transactions in the pool, labeled by queue (`ready` or `future`).
+	pub transactions: IntGaugeVec,
+	/// Number of peers, labeled by kind (`connected` or `sync`).
+	pub peers: IntGaugeVec,
+	/// Average bandwidth in bytes per second, labeled by direction (`in` or `out`).
+	pub bandwidth: IntGaugeVec,
+	/// Number of blocks waiting in the import queue.
+	pub queued_blocks: IntGauge,
+	/// Memory used by the state cache, in bytes.
+	pub state_cache_size: IntGauge,
+	/// Number of state cache lookups, labeled by result (`hit` or `miss`).
+	pub state_cache_lookups: IntCounterVec,
+}
+
+impl ServiceMetrics {
+	/// Creates the metrics and registers them in `registry`.
+	pub fn register(registry: &Registry) -> Result<Self, Error> {
+		Ok(Self {
+			block_height: register(IntGaugeVec::new(
+				Opts::new("block_height", "Block height info of the chain"),
+				&["status"],
+			)?, registry)?,
+			transactions: register(IntGaugeVec::new(
+				Opts::new("transaction_pool_transactions", "Number of transactions in the pool"),
+				&["queue"],
+			)?, registry)?,
+			peers: register(IntGaugeVec::new(
+				Opts::new("network_peers", "Number of network peers"),
+				&["kind"],
+			)?, registry)?,
+			bandwidth: register(IntGaugeVec::new(
+				Opts::new("network_bandwidth", "Average network bandwidth in bytes per second"),
+				&["direction"],
+			)?, registry)?,
+			queued_blocks: register(IntGauge::new(
+				"import_queue_blocks", "Number of blocks waiting in the import queue",
+			)?, registry)?,
+			state_cache_size: register(IntGauge::new(
+				"state_cache_bytes", "Memory used by the state cache in bytes",
+			)?, registry)?,
+			state_cache_lookups: register(IntCounterVec::new(
+				Opts::new("state_cache_lookups", "Number of state cache lookups since start"),
+				&["result"],
+			)?, registry)?,
+		})
+	}
+}
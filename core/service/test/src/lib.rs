@@ -34,7 +34,7 @@ use service::{
 	Error,
 };
 use network::{multiaddr, Multiaddr};
-use network::config::{NetworkConfiguration, TransportConfig, NodeKeyConfig, Secret, NonReservedPeerMode};
+use network::config::{NetworkConfiguration, TransportConfig, NodeKeyConfig, Secret, NonReservedPeerMode, SyncMode};
 use sr_primitives::{generic::BlockId, traits::Block as BlockT};
 
 /// Maximum duration of single wait call.
@@ -160,6 +160,7 @@ fn node_config<G, E: Clone> (
 			enable_mdns: false,
 			wasm_external_transport: None,
 		},
+		sync_mode: SyncMode::Full,
 	};
 
 	Configuration {
@@ -178,6 +179,8 @@ fn node_config<G, E: Clone> (
 		state_cache_size: 16777216,
 		state_cache_child_ratio: None,
 		pruning: Default::default(),
+		storage_index: false,
+		blocks_pruning: Default::default(),
 		chain_spec: (*spec).clone(),
 		custom: Default::default(),
 		name: format!("Node {}", index),
@@ -187,6 +190,7 @@ fn node_config<G, E: Clone> (
 		rpc_ws: None,
 		rpc_ws_max_connections: None,
 		rpc_cors: None,
+		prometheus_port: None,
 		telemetry_endpoints: None,
 		telemetry_external_transport: None,
 		default_heap_pages: None,
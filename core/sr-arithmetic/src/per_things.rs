@@ -19,6 +19,7 @@ use serde::{Serialize, Deserialize};
 
 use rstd::{ops, prelude::*, convert::TryInto};
 use codec::{Encode, Decode, CompactAs};
+use srml_metadata::TypeInfo;
 use crate::traits::{SaturatedConversion, UniqueSaturatedInto, Saturating};
 use substrate_debug_derive::RuntimeDebug;
 
@@ -28,7 +29,7 @@ macro_rules! implement_per_thing {
 		///
 		#[doc = $title]
 		#[cfg_attr(feature = "std", derive(Serialize, Deserialize, Ord, PartialOrd))]
-		#[derive(Encode, Decode, Default, Copy, Clone, PartialEq, Eq, RuntimeDebug, CompactAs)]
+		#[derive(Encode, Decode, Default, Copy, Clone, PartialEq, Eq, RuntimeDebug, CompactAs, TypeInfo)]
 		pub struct $name($type);
 
 		impl $name {
@@ -24,9 +24,10 @@ use rstd::prelude::*;
 use crate::ConsensusEngineId;
 use crate::codec::{Decode, Encode, Input, Error};
 use primitives::RuntimeDebug;
+use srml_metadata::{TypeInfo, Registry, TypeDef, TypeId, Field, Variant};
 
 /// Generic header digest.
-#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub struct Digest<Hash: Encode + Decode> {
 	/// A list of logs in the digest.
@@ -260,6 +261,39 @@ impl<Hash: Decode> Decode for DigestItem<Hash> {
 	}
 }
 
+impl<Hash: TypeInfo> TypeInfo for DigestItem<Hash> {
+	fn path() -> Vec<&'static str> {
+		module_path!().split("::").chain(rstd::iter::once("DigestItem")).collect()
+	}
+
+	fn params(registry: &mut Registry) -> Vec<TypeId> {
+		vec![registry.register::<Hash>()]
+	}
+
+	fn type_def(registry: &mut Registry) -> TypeDef {
+		let mut engine_item = |name, item_type: DigestItemType| Variant::new(name, item_type as u8, vec![
+			Field::unnamed(registry.register::<ConsensusEngineId>()),
+			Field::unnamed(registry.register::<Vec<u8>>()),
+		]);
+		let pre_runtime = engine_item("PreRuntime", DigestItemType::PreRuntime);
+		let consensus = engine_item("Consensus", DigestItemType::Consensus);
+		let seal = engine_item("Seal", DigestItemType::Seal);
+
+		// Ordered by the index of the item type.
+		TypeDef::Variant(vec![
+			Variant::new("Other", DigestItemType::Other as u8, vec![
+				Field::unnamed(registry.register::<Vec<u8>>()),
+			]),
+			Variant::new("ChangesTrieRoot", DigestItemType::ChangesTrieRoot as u8, vec![
+				Field::unnamed(registry.register::<Hash>()),
+			]),
+			consensus,
+			seal,
+			pre_runtime,
+		])
+	}
+}
+
 impl<'a, Hash> DigestItemRef<'a, Hash> {
 	/// Cast this digest item into `ChangesTrieRoot`.
 	pub fn as_changes_trie_root(&self) -> Option<&'a Hash> {
@@ -28,7 +28,9 @@ use primitives::U256;
 use rstd::{
 	convert::TryFrom,
 	fmt::Debug,
+	prelude::*,
 };
+use srml_metadata::{TypeInfo, Registry, TypeDef, TypeId, Field};
 
 /// Abstraction over a block header for a substrate chain.
 #[derive(PartialEq, Eq, Clone, primitives::RuntimeDebug)]
@@ -103,6 +105,30 @@ impl<Number, Hash> codec::EncodeLike for Header<Number, Hash> where
 	Hash::Output: Encode,
 {}
 
+impl<Number, Hash> TypeInfo for Header<Number, Hash> where
+	Number: TypeInfo + Copy + Into<U256> + TryFrom<U256>,
+	Hash: HashT + 'static,
+	Hash::Output: TypeInfo,
+{
+	fn path() -> Vec<&'static str> {
+		module_path!().split("::").chain(rstd::iter::once("Header")).collect()
+	}
+
+	fn params(registry: &mut Registry) -> Vec<TypeId> {
+		vec![registry.register::<Number>(), registry.register::<Hash::Output>()]
+	}
+
+	fn type_def(registry: &mut Registry) -> TypeDef {
+		TypeDef::Composite(vec![
+			Field::named("parent_hash", registry.register::<Hash::Output>()),
+			Field::named("number", registry.register_compact::<Number>()),
+			Field::named("state_root", registry.register::<Hash::Output>()),
+			Field::named("extrinsics_root", registry.register::<Hash::Output>()),
+			Field::named("digest", registry.register::<Digest<Hash::Output>>()),
+		])
+	}
+}
+
 impl<Number, Hash> traits::Header for Header<Number, Hash> where
 	Number: Member + MaybeSerializeDeserialize + Debug + rstd::hash::Hash + MaybeDisplay +
 		SimpleArithmetic + Codec + Copy + Into<U256> + TryFrom<U256>,
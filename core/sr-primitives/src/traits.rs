@@ -29,7 +29,7 @@ use crate::transaction_validity::{
 	ValidTransaction, TransactionValidity, TransactionValidityError, UnknownTransaction,
 };
 use crate::generic::{Digest, DigestItem};
-use crate::weights::DispatchInfo;
+use crate::weights::{DispatchInfo, Weight};
 pub use arithmetic::traits::{
 	SimpleArithmetic, UniqueSaturatedInto, UniqueSaturatedFrom, Saturating, SaturatedConversion,
 	Zero, One, Bounded, CheckedAdd, CheckedSub, CheckedMul, CheckedDiv,
@@ -341,6 +341,25 @@ pub trait OnInitialize<BlockNumber> {
 	fn on_initialize(_n: BlockNumber) {}
 }
 
+/// The runtime upgrade trait. Implementing this lets you express what should happen
+/// for your module when the runtime's `spec_version` changed (right before the first block with
+/// the new runtime is initialized).
+pub trait OnRuntimeUpgrade {
+	/// Perform a module upgrade, e.g. migrating storage to a new layout.
+	///
+	/// Return the weight consumed by the upgrade.
+	fn on_runtime_upgrade() -> Weight { 0 }
+}
+
+#[impl_for_tuples(30)]
+impl OnRuntimeUpgrade for Tuple {
+	fn on_runtime_upgrade() -> Weight {
+		let mut weight: Weight = 0;
+		for_tuples!( #( weight = weight.saturating_add(Tuple::on_runtime_upgrade()); )* );
+		weight
+	}
+}
+
 /// Off-chain computation trait.
 ///
 /// Implementing this trait on a module allows you to perform long-running tasks
@@ -1150,6 +1169,24 @@ macro_rules! impl_opaque_keys {
 			}
 		}
 
+		impl $crate::metadata::TypeInfo for $name {
+			fn path() -> $crate::rstd::vec::Vec<&'static str> {
+				module_path!().split("::").chain($crate::rstd::iter::once(stringify!($name))).collect()
+			}
+
+			fn type_def(registry: &mut $crate::metadata::Registry) -> $crate::metadata::TypeDef {
+				let _ = &registry;
+				$crate::metadata::TypeDef::Composite(<[_]>::to_vec(&[
+					$(
+						$crate::metadata::Field::named(
+							stringify!($field),
+							registry.register::<<$type as $crate::BoundToRuntimeAppPublic>::Public>(),
+						),
+					)*
+				]))
+			}
+		}
+
 		impl $crate::traits::OpaqueKeys for $name {
 			type KeyTypeIdProviders = ( $( $type, )* );
 
@@ -21,6 +21,7 @@ use rstd::vec::Vec;
 
 use codec::{Encode, Decode};
 use sr_primitives::Perbill;
+use srml_metadata::TypeInfo;
 
 use crate::SessionIndex;
 
@@ -131,7 +132,7 @@ impl<Reporter, Offender> OnOffenceHandler<Reporter, Offender> for () {
 }
 
 /// A details about an offending authority for a particular kind of offence.
-#[derive(Clone, PartialEq, Eq, Encode, Decode, sr_primitives::RuntimeDebug)]
+#[derive(Clone, PartialEq, Eq, Encode, Decode, sr_primitives::RuntimeDebug, TypeInfo)]
 pub struct OffenceDetails<Reporter, Offender> {
 	/// The offending authority id
 	pub offender: Offender,
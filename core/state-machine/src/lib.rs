@@ -633,6 +633,73 @@ where
 	Ok(proving_backend.extract_proof())
 }
 
+/// Generate a range proof of the state.
+///
+/// The range contains the key-value pairs of the top-level trie, or of the given child trie, whose
+/// key is greater than `start`, in key order, until their total size reaches `size_limit`.
+/// Returns the range, whether it reaches the end of the trie, and the proof.
+pub fn prove_range_read_with_size<B, H>(
+	mut backend: B,
+	child_storage_key: Option<&[u8]>,
+	start: &[u8],
+	size_limit: usize,
+) -> Result<(Vec<(Vec<u8>, Vec<u8>)>, bool, StorageProof), Box<dyn Error>>
+where
+	B: Backend<H>,
+	H: Hasher,
+	H::Out: Ord,
+{
+	let trie_backend = backend.as_trie_backend()
+		.ok_or_else(|| Box::new(ExecutionError::UnableToGenerateProof) as Box<dyn Error>)?;
+	let mut recorder = ProofRecorder::new();
+	let mut entries = Vec::new();
+	let mut size = 0;
+	let mut complete = true;
+	trie_backend.essence().for_key_values_after(child_storage_key, start, Some(&mut recorder), |key, value| {
+		if size >= size_limit {
+			// the next key is part of the proof, showing that the range ends here
+			complete = false;
+			return false;
+		}
+		size += key.len() + value.len();
+		entries.push((key.to_vec(), value.to_vec()));
+		true
+	}).map_err(|e| Box::new(e) as Box<dyn Error>)?;
+
+	let proof = StorageProof::new(recorder.drain().into_iter().map(|record| record.data).collect());
+	Ok((entries, complete, proof))
+}
+
+/// Check a range proof generated by `prove_range_read_with_size`, for a range of `count` pairs.
+///
+/// Returns the key-value pairs of the range and whether it reaches the end of the trie. Fails
+/// if any trie node visited while iterating over the range is missing from the proof.
+pub fn read_range_proof_check<H>(
+	root: H::Out,
+	proof: StorageProof,
+	child_storage_key: Option<&[u8]>,
+	start: &[u8],
+	count: usize,
+) -> Result<(Vec<(Vec<u8>, Vec<u8>)>, bool), Box<dyn Error>>
+where
+	H: Hasher,
+	H::Out: Ord,
+{
+	let proving_backend = create_proof_check_backend::<H>(root, proof)?;
+	let mut entries = Vec::new();
+	let mut complete = true;
+	proving_backend.essence().for_key_values_after(child_storage_key, start, None, |key, value| {
+		if entries.len() >= count {
+			complete = false;
+			return false;
+		}
+		entries.push((key.to_vec(), value.to_vec()));
+		true
+	}).map_err(|e| Box::new(e) as Box<dyn Error>)?;
+
+	Ok((entries, complete))
+}
+
 /// Check storage read proof, generated by `prove_read` call.
 pub fn read_proof_check<H, I>(
 	root: H::Out,
@@ -1076,6 +1143,70 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn prove_range_read_and_proof_check_works() {
+		let remote_backend = trie_backend::tests::test_trie();
+		let remote_root = remote_backend.storage_root(::std::iter::empty()).0;
+		let pairs = remote_backend.pairs();
+
+		let mut start = Vec::new();
+		let mut checked = Vec::new();
+		loop {
+			let (entries, complete, proof) = prove_range_read_with_size(
+				trie_backend::tests::test_trie(),
+				None,
+				&start,
+				16,
+			).unwrap();
+			let (local_entries, local_complete) = read_range_proof_check::<Blake2Hasher>(
+				remote_root,
+				proof.clone(),
+				None,
+				&start,
+				entries.len(),
+			).unwrap();
+			assert_eq!(local_entries, entries);
+			assert_eq!(local_complete, complete);
+
+			// leaving out the last pair is detected
+			let (_, truncated_complete) = read_range_proof_check::<Blake2Hasher>(
+				remote_root,
+				proof,
+				None,
+				&start,
+				entries.len() - 1,
+			).unwrap();
+			assert!(!truncated_complete);
+
+			start = entries.last().unwrap().0.clone();
+			checked.extend(entries);
+			if complete {
+				break;
+			}
+		}
+		assert_eq!(checked, pairs);
+
+		// on child trie
+		let (entries, complete, proof) = prove_range_read_with_size(
+			trie_backend::tests::test_trie(),
+			Some(b":child_storage:default:sub1"),
+			&[],
+			usize::max_value(),
+		).unwrap();
+		assert!(complete);
+		assert_eq!(entries, vec![(b"value3".to_vec(), vec![142]), (b"value4".to_vec(), vec![124])]);
+		assert_eq!(
+			read_range_proof_check::<Blake2Hasher>(
+				remote_root,
+				proof,
+				Some(b":child_storage:default:sub1"),
+				&[],
+				entries.len(),
+			).unwrap(),
+			(entries, true),
+		);
+	}
+
 	#[test]
 	fn cannot_change_changes_trie_config() {
 		let backend = trie_backend::tests::test_trie();
@@ -21,9 +21,9 @@ use std::ops::Deref;
 use std::sync::Arc;
 use log::{debug, warn};
 use hash_db::{self, Hasher, EMPTY_PREFIX, Prefix};
-use trie::{Trie, MemoryDB, PrefixedMemoryDB, DBValue,
-	default_child_trie_root, read_trie_value, read_child_trie_value,
-	for_keys_in_child_trie};
+use trie::{Trie, MemoryDB, PrefixedMemoryDB, DBValue, Recorder,
+	default_child_trie_root, read_trie_value, read_trie_value_with, read_child_trie_value,
+	for_keys_in_child_trie, for_key_values_after};
 use trie::trie_types::{TrieDB, TrieError, Layout};
 use crate::backend::Consolidate;
 
@@ -180,6 +180,98 @@ impl<S: TrieBackendStorage<H>, H: Hasher> TrieBackendEssence<S, H> {
 		self.keys_values_with_prefix_inner(&self.root, prefix, f)
 	}
 
+	/// Execute given closure for the key and values of the top-level trie, or of the given child
+	/// trie, that start with `prefix`, in key order.
+	///
+	/// Unlike `for_key_values_with_prefix`, a failure to read the trie is returned.
+	pub fn try_for_key_values_with_prefix<F: FnMut(&[u8], &[u8])>(
+		&self,
+		child_storage_key: Option<&[u8]>,
+		prefix: &[u8],
+		mut f: F,
+	) -> Result<(), String> {
+		let mut read_overlay = S::Overlay::default();
+		let eph = Ephemeral {
+			storage: &self.storage,
+			overlay: &mut read_overlay,
+		};
+
+		let map_e = |e| format!("Trie lookup error: {}", e);
+
+		let root = match child_storage_key {
+			None => self.root.clone(),
+			Some(storage_key) => {
+				let root_vec = read_trie_value::<Layout<H>, _>(&eph, &self.root, storage_key)
+					.map_err(map_e)?
+					.unwrap_or(default_child_trie_root::<Layout<H>>(storage_key));
+				let mut root = H::Out::default();
+				root.as_mut().copy_from_slice(&root_vec);
+				root
+			},
+		};
+
+		let mut iter = || -> Result<(), Box<TrieError<H::Out>>> {
+			let trie = TrieDB::<H>::new(&eph, &root)?;
+			let mut iter = trie.iter()?;
+
+			iter.seek(prefix)?;
+
+			for x in iter {
+				let (key, value) = x?;
+
+				if !key.starts_with(prefix) {
+					break;
+				}
+
+				f(&key, &value);
+			}
+
+			Ok(())
+		};
+
+		iter().map_err(|e| format!("Trie lookup error: {}", e))
+	}
+
+	/// Execute given closure for the key and values of the top-level trie, or of the given child
+	/// trie, that come after `start`, in key order, until it returns false.
+	///
+	/// The trie nodes that are read are recorded by `recorder`, if any.
+	pub fn for_key_values_after<F: FnMut(&[u8], &[u8]) -> bool>(
+		&self,
+		child_storage_key: Option<&[u8]>,
+		start: &[u8],
+		mut recorder: Option<&mut Recorder<H::Out>>,
+		f: F,
+	) -> Result<(), String> {
+		let mut read_overlay = S::Overlay::default();
+		let eph = Ephemeral {
+			storage: &self.storage,
+			overlay: &mut read_overlay,
+		};
+
+		let map_e = |e| format!("Trie lookup error: {}", e);
+
+		let root = match child_storage_key {
+			None => self.root.clone(),
+			Some(storage_key) => {
+				let root_vec = match recorder {
+					Some(ref mut recorder) => read_trie_value_with::<Layout<H>, _, _>(
+						&eph,
+						&self.root,
+						storage_key,
+						&mut **recorder,
+					),
+					None => read_trie_value::<Layout<H>, _>(&eph, &self.root, storage_key),
+				}.map_err(map_e)?.unwrap_or(default_child_trie_root::<Layout<H>>(storage_key));
+				let mut root = H::Out::default();
+				root.as_mut().copy_from_slice(&root_vec);
+				root
+			},
+		};
+
+		for_key_values_after::<Layout<H>, _, _>(&eph, &root, start, recorder, f).map_err(map_e)
+	}
+
 }
 
 pub(crate) struct Ephemeral<'a, S: 'a + TrieBackendStorage<H>, H: 'a + Hasher> {
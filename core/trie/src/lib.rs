@@ -264,6 +264,45 @@ pub fn record_all_keys<L: TrieConfiguration, DB>(
 	Ok(())
 }
 
+/// Call `f` for the key-value pairs whose key is greater than `start`, in key order, until it
+/// returns false.
+///
+/// When a recorder is given, it records the nodes needed to replay the iteration on a partial
+/// trie, including the path to the key for which `f` returned false.
+pub fn for_key_values_after<L: TrieConfiguration, DB, F>(
+	db: &DB,
+	root: &TrieHash<L>,
+	start: &[u8],
+	mut recorder: Option<&mut Recorder<TrieHash<L>>>,
+	mut f: F,
+) -> Result<(), Box<TrieError<L>>> where
+	DB: hash_db::HashDBRef<L::Hash, trie_db::DBValue>,
+	F: FnMut(&[u8], &[u8]) -> bool,
+{
+	let trie = TrieDB::<L>::new(&*db, root)?;
+	if let Some(ref mut recorder) = recorder {
+		// seeking reads the same nodes as looking up `start`
+		trie.get_with(start, &mut **recorder)?;
+	}
+
+	let mut iter = trie.iter()?;
+	iter.seek(start)?;
+	for x in iter {
+		let (key, value) = x?;
+		if &key[..] == start {
+			continue;
+		}
+		if let Some(ref mut recorder) = recorder {
+			trie.get_with(&key, &mut **recorder)?;
+		}
+		if !f(&key, &value) {
+			break;
+		}
+	}
+
+	Ok(())
+}
+
 /// Read a value from the child trie.
 pub fn read_child_trie_value<L: TrieConfiguration, DB>(
 	_storage_key: &[u8],
@@ -18,8 +18,10 @@ use crate::ChainSpec;
 use futures::{prelude::*, sync::oneshot, sync::mpsc};
 use libp2p::wasm_ext;
 use log::{debug, info};
-use std::sync::Arc;
-use substrate_service::{AbstractService, RpcSession, Roles as ServiceRoles, Configuration, config::DatabaseConfig};
+use substrate_service::{
+	AbstractService, RpcSession, Roles as ServiceRoles, Configuration,
+	config::{DatabaseConfig, as_database},
+};
 use wasm_bindgen::prelude::*;
 
 /// Starts the client.
@@ -48,7 +50,7 @@ fn start_inner(wasm_ext: wasm_ext::ffi::Transport) -> Result<Client, Box<dyn std
 		config.roles = ServiceRoles::LIGHT;
 		config.name = "Browser node".to_string();
 		config.database = {
-			let db = Arc::new(kvdb_memorydb::create(10));
+			let db = as_database(kvdb_memorydb::create(11));
 			DatabaseConfig::Custom(db)
 		};
 		config
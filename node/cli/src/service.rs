@@ -214,6 +214,7 @@ macro_rules! new_full {
 					on_exit: service.on_exit(),
 					telemetry_on_connect: Some(service.telemetry_on_connect_stream()),
 					voting_rule: grandpa::VotingRulesBuilder::default().build(),
+					prometheus_registry: service.prometheus_registry(),
 				};
 				// the GRANDPA voter task is considered infallible, i.e.
 				// if it fails we take down the service with it.
@@ -390,6 +391,7 @@ mod tests {
 				body: Some(block.extrinsics),
 				header: block.header,
 				auxiliary: Vec::new(),
+				skip_execution: false,
 			}
 		};
 		let extrinsic_factory =
@@ -519,6 +521,7 @@ mod tests {
 					finalized: true,
 					auxiliary: Vec::new(),
 					fork_choice: ForkChoiceStrategy::LongestChain,
+					skip_execution: false,
 				};
 
 				block_import.import_block(params, Default::default())
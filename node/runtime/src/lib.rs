@@ -22,8 +22,10 @@
 
 use rstd::prelude::*;
 use support::{
-	construct_runtime, parameter_types, traits::{SplitTwoWays, Currency, Randomness}
+	construct_runtime, parameter_types, metadata::TypeInfo,
+	traits::{SplitTwoWays, Currency, Randomness, InstanceFilter},
 };
+use codec::{Encode, Decode};
 use primitives::u32_trait::{_1, _2, _3, _4};
 use node_primitives::{
 	AccountId, AccountIndex, Balance, BlockNumber, Hash, Index,
@@ -34,13 +36,15 @@ use client::{
 	block_builder::api::{self as block_builder_api, InherentData, CheckInherentsResult},
 	runtime_api as client_api, impl_runtime_apis
 };
-use sr_primitives::{Permill, Perbill, ApplyResult, impl_opaque_keys, generic, create_runtime_str};
+use sr_primitives::{
+	Permill, Perbill, Percent, ApplyResult, RuntimeDebug, impl_opaque_keys, generic, create_runtime_str,
+};
 use sr_primitives::curve::PiecewiseLinear;
 use sr_primitives::transaction_validity::TransactionValidity;
 use sr_primitives::weights::Weight;
 use sr_primitives::traits::{
 	self, BlakeTwo256, Block as BlockT, NumberFor, StaticLookup, SaturatedConversion,
-	OpaqueKeys,
+	OpaqueKeys, ConvertInto,
 };
 use version::RuntimeVersion;
 #[cfg(any(feature = "std", test))]
@@ -81,8 +85,8 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	// and set impl_version to equal spec_version. If only runtime
 	// implementation changes and behavior does not, then leave spec_version as
 	// is and increment impl_version.
-	spec_version: 191,
-	impl_version: 191,
+	spec_version: 206,
+	impl_version: 206,
 	apis: RUNTIME_API_VERSIONS,
 };
 
@@ -130,9 +134,163 @@ impl system::Trait for Runtime {
 	type Version = Version;
 }
 
+parameter_types! {
+	// One storage item; value is size 4+4+16+32 bytes = 56 bytes.
+	pub const MultisigDepositBase: Balance = 30 * CENTS;
+	// Additional storage item size of 32 bytes.
+	pub const MultisigDepositFactor: Balance = 5 * CENTS;
+	pub const MaxSignatories: u16 = 100;
+}
+
 impl utility::Trait for Runtime {
 	type Event = Event;
 	type Call = Call;
+	type Currency = Balances;
+	type MultisigDepositBase = MultisigDepositBase;
+	type MultisigDepositFactor = MultisigDepositFactor;
+	type MaxSignatories = MaxSignatories;
+}
+
+parameter_types! {
+	// One storage item; key size 32, value size 16.
+	pub const ProxyDepositBase: Balance = 20 * CENTS;
+	// Additional storage item size of 33 bytes.
+	pub const ProxyDepositFactor: Balance = 3 * CENTS;
+	pub const MaxProxies: u16 = 32;
+}
+
+/// The type used to represent the kinds of proxying allowed.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub enum ProxyType {
+	/// Any call may be dispatched.
+	Any,
+	/// Only calls which cannot move the funds of the proxied account may be dispatched.
+	NonTransfer,
+	/// Only governance related calls may be dispatched.
+	Governance,
+	/// Only staking related calls may be dispatched.
+	Staking,
+}
+
+impl Default for ProxyType {
+	fn default() -> Self { ProxyType::Any }
+}
+
+impl InstanceFilter<Call> for ProxyType {
+	fn filter(&self, c: &Call) -> bool {
+		match self {
+			ProxyType::Any => true,
+			// An explicit list, so that the calls of modules added later are not allowed until they
+			// have been reviewed.
+			ProxyType::NonTransfer => match c {
+				Call::System(..) | Call::Timestamp(..) | Call::Authorship(..) | Call::Session(..)
+					| Call::Democracy(..) | Call::Council(..) | Call::TechnicalCommittee(..)
+					| Call::Elections(..) | Call::TechnicalMembership(..) | Call::FinalityTracker(..)
+					| Call::Grandpa(..) | Call::Treasury(..) | Call::ImOnline(..) | Call::Nicks(..)
+					| Call::Identity(..) | Call::Scheduler(..) | Call::Preimage(..) => true,
+				// Omitting `vested_transfer`.
+				Call::Vesting(vesting::Call::vest(..)) | Call::Vesting(vesting::Call::vest_other(..)) => true,
+				// Omitting `bond`, `set_payee` and `set_controller`, which redirect rewards or hand
+				// the control of the stash to another account.
+				Call::Staking(staking::Call::bond_extra(..)) | Call::Staking(staking::Call::unbond(..))
+					| Call::Staking(staking::Call::withdraw_unbonded(..))
+					| Call::Staking(staking::Call::validate(..))
+					| Call::Staking(staking::Call::nominate(..))
+					| Call::Staking(staking::Call::chill(..))
+					| Call::Staking(staking::Call::payout_stakers(..)) => true,
+				// Omitting `create_recovery`, `initiate_recovery` and `as_recovered`, which would
+				// let other accounts take over the proxied account or act as a recovered one.
+				Call::Recovery(recovery::Call::vouch_recovery(..))
+					| Call::Recovery(recovery::Call::claim_recovery(..))
+					| Call::Recovery(recovery::Call::close_recovery(..))
+					| Call::Recovery(recovery::Call::remove_recovery(..))
+					| Call::Recovery(recovery::Call::cancel_recovered(..)) => true,
+				// Omitting `create` and `join`, which move funds into a pool.
+				Call::NominationPools(nomination_pools::Call::claim_payout(..))
+					| Call::NominationPools(nomination_pools::Call::unbond(..))
+					| Call::NominationPools(nomination_pools::Call::withdraw_unbonded(..))
+					| Call::NominationPools(nomination_pools::Call::nominate(..)) => true,
+				// Omitting `Balances`, `Contracts`, `Sudo`, `Utility` and `Proxy`, which transfer
+				// funds, dispatch arbitrary calls or add proxies with more rights.
+				_ => false,
+			},
+			ProxyType::Governance => match c {
+				Call::Democracy(..) | Call::Council(..) | Call::TechnicalCommittee(..)
+					| Call::Elections(..) | Call::Treasury(..) => true,
+				_ => false,
+			},
+			ProxyType::Staking => match c {
+				Call::Staking(..) => true,
+				_ => false,
+			},
+		}
+	}
+	fn is_superset(&self, o: &Self) -> bool {
+		match (self, o) {
+			(x, y) if x == y => true,
+			(ProxyType::Any, _) => true,
+			(_, ProxyType::Any) => false,
+			(ProxyType::NonTransfer, ProxyType::Governance) => true,
+			_ => false,
+		}
+	}
+}
+
+impl proxy::Trait for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type Currency = Balances;
+	type ProxyType = ProxyType;
+	type ProxyDepositBase = ProxyDepositBase;
+	type ProxyDepositFactor = ProxyDepositFactor;
+	type MaxProxies = MaxProxies;
+}
+
+parameter_types! {
+	// 80% of `MaximumBlockWeight`.
+	pub const MaximumSchedulerWeight: Weight = 800_000_000;
+}
+
+impl scheduler::Trait for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type MaximumWeight = MaximumSchedulerWeight;
+	type ScheduleOrigin = system::EnsureRoot<AccountId>;
+	type Preimages = Preimage;
+}
+
+parameter_types! {
+	pub const PreimageMaxSize: u32 = 4 * 1024 * 1024;
+	pub const PreimageBaseDeposit: Balance = 1 * DOLLARS;
+	pub const PreimageByteDeposit: Balance = 1 * CENTS;
+	pub const PreimageReapPeriod: BlockNumber = 28 * 24 * 60 * MINUTES;
+}
+
+impl preimage::Trait for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type ManagerOrigin = system::EnsureRoot<AccountId>;
+	type MaxSize = PreimageMaxSize;
+	type BaseDeposit = PreimageBaseDeposit;
+	type ByteDeposit = PreimageByteDeposit;
+	type ReapPeriod = PreimageReapPeriod;
+}
+
+parameter_types! {
+	pub const ConfigDepositBase: Balance = 5 * DOLLARS;
+	pub const FriendDepositFactor: Balance = 50 * CENTS;
+	pub const MaxFriends: u16 = 9;
+	pub const RecoveryDeposit: Balance = 5 * DOLLARS;
+}
+
+impl recovery::Trait for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type Currency = Balances;
+	type ConfigDepositBase = ConfigDepositBase;
+	type FriendDepositFactor = FriendDepositFactor;
+	type MaxFriends = MaxFriends;
+	type RecoveryDeposit = RecoveryDeposit;
 }
 
 parameter_types! {
@@ -252,7 +410,11 @@ srml_staking_reward_curve::build! {
 parameter_types! {
 	pub const SessionsPerEra: sr_staking_primitives::SessionIndex = 6;
 	pub const BondingDuration: staking::EraIndex = 24 * 28;
+	pub const SlashDeferDuration: staking::EraIndex = 24 * 7; // 1/4 the bonding duration.
+	pub const HistoryDepth: staking::EraIndex = 84;
+	pub const MaxNominatorRewardedPerValidator: u32 = 64;
 	pub const RewardCurve: &'static PiecewiseLinear<'static> = &REWARD_CURVE;
+	pub const ElectionLookahead: BlockNumber = EPOCH_DURATION_IN_BLOCKS / 4;
 }
 
 impl staking::Trait for Runtime {
@@ -265,8 +427,34 @@ impl staking::Trait for Runtime {
 	type Reward = (); // rewards are minted from the void
 	type SessionsPerEra = SessionsPerEra;
 	type BondingDuration = BondingDuration;
+	type SlashDeferDuration = SlashDeferDuration;
+	/// A super-majority of the council can cancel the slash.
+	type SlashCancelOrigin = collective::EnsureProportionAtLeast<_3, _4, AccountId, CouncilCollective>;
+	type HistoryDepth = HistoryDepth;
+	type MaxNominatorRewardedPerValidator = MaxNominatorRewardedPerValidator;
 	type SessionInterface = Self;
 	type RewardCurve = RewardCurve;
+	type NextNewSession = Babe;
+	type ElectionLookahead = ElectionLookahead;
+	type Call = Call;
+	type SubmitTransaction = SubmitTransaction;
+	// validators already sign their heartbeats with this key from the offchain worker.
+	type KeyType = ImOnlineId;
+}
+
+parameter_types! {
+	pub const MinCreateBond: Balance = 10 * DOLLARS;
+	pub const MinJoinBond: Balance = 1 * DOLLARS;
+	pub const MaxUnbonding: u32 = 8;
+}
+
+impl nomination_pools::Trait for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type Staking = Staking;
+	type MinCreateBond = MinCreateBond;
+	type MinJoinBond = MinJoinBond;
+	type MaxUnbonding = MaxUnbonding;
 }
 
 parameter_types! {
@@ -303,6 +491,12 @@ impl democracy::Trait for Runtime {
 	// only do it once and it lasts only for the cooloff period.
 	type VetoOrigin = collective::EnsureMember<AccountId, TechnicalCollective>;
 	type CooloffPeriod = CooloffPeriod;
+	type Preimages = Preimage;
+	type Scheduler = Scheduler;
+}
+
+parameter_types! {
+	pub const CouncilMotionDuration: BlockNumber = 3 * DAYS;
 }
 
 type CouncilCollective = collective::Instance1;
@@ -310,6 +504,8 @@ impl collective::Trait<CouncilCollective> for Runtime {
 	type Origin = Origin;
 	type Proposal = Call;
 	type Event = Event;
+	type MotionDuration = CouncilMotionDuration;
+	type Preimages = Preimage;
 }
 
 parameter_types! {
@@ -335,11 +531,17 @@ impl elections_phragmen::Trait for Runtime {
 	type ChangeMembers = Council;
 }
 
+parameter_types! {
+	pub const TechnicalMotionDuration: BlockNumber = 3 * DAYS;
+}
+
 type TechnicalCollective = collective::Instance2;
 impl collective::Trait<TechnicalCollective> for Runtime {
 	type Origin = Origin;
 	type Proposal = Call;
 	type Event = Event;
+	type MotionDuration = TechnicalMotionDuration;
+	type Preimages = Preimage;
 }
 
 impl membership::Trait<membership::Instance1> for Runtime {
@@ -348,6 +550,7 @@ impl membership::Trait<membership::Instance1> for Runtime {
 	type RemoveOrigin = collective::EnsureProportionMoreThan<_1, _2, AccountId, CouncilCollective>;
 	type SwapOrigin = collective::EnsureProportionMoreThan<_1, _2, AccountId, CouncilCollective>;
 	type ResetOrigin = collective::EnsureProportionMoreThan<_1, _2, AccountId, CouncilCollective>;
+	type PrimeOrigin = collective::EnsureProportionMoreThan<_1, _2, AccountId, CouncilCollective>;
 	type MembershipInitialized = TechnicalCommittee;
 	type MembershipChanged = TechnicalCommittee;
 }
@@ -357,6 +560,14 @@ parameter_types! {
 	pub const ProposalBondMinimum: Balance = 1 * DOLLARS;
 	pub const SpendPeriod: BlockNumber = 1 * DAYS;
 	pub const Burn: Permill = Permill::from_percent(50);
+	pub const TipCountdown: BlockNumber = 1 * DAYS;
+	pub const TipFindersFee: Percent = Percent::from_percent(20);
+	pub const TipReportDepositBase: Balance = 1 * DOLLARS;
+	pub const DataDepositPerByte: Balance = 1 * CENTS;
+	pub const MaximumReasonLength: u32 = 16384;
+	pub const BountyDepositBase: Balance = 1 * DOLLARS;
+	pub const BountyDepositPayoutDelay: BlockNumber = 8 * DAYS;
+	pub const BountyValueMinimum: Balance = 10 * DOLLARS;
 }
 
 impl treasury::Trait for Runtime {
@@ -369,6 +580,17 @@ impl treasury::Trait for Runtime {
 	type ProposalBondMinimum = ProposalBondMinimum;
 	type SpendPeriod = SpendPeriod;
 	type Burn = Burn;
+	type Tippers = Council;
+	type TipCountdown = TipCountdown;
+	type TipFindersFee = TipFindersFee;
+	type TipReportDepositBase = TipReportDepositBase;
+	type DataDepositPerByte = DataDepositPerByte;
+	type MaximumReasonLength = MaximumReasonLength;
+	type BountyDepositBase = BountyDepositBase;
+	type BountyDepositPayoutDelay = BountyDepositPayoutDelay;
+	type BountyValueMinimum = BountyValueMinimum;
+	type Call = Call;
+	type Scheduler = Scheduler;
 }
 
 parameter_types! {
@@ -464,6 +686,42 @@ impl nicks::Trait for Runtime {
 	type MaxLength = MaxLength;
 }
 
+parameter_types! {
+	pub const BasicDeposit: Balance = 10 * DOLLARS;
+	pub const FieldDeposit: Balance = 250 * CENTS;
+	pub const SubAccountDeposit: Balance = 2 * DOLLARS;
+	pub const MaximumSubAccounts: u32 = 100;
+	pub const MaxAdditionalFields: u32 = 100;
+}
+
+impl identity::Trait for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type BasicDeposit = BasicDeposit;
+	type FieldDeposit = FieldDeposit;
+	type SubAccountDeposit = SubAccountDeposit;
+	type MaximumSubAccounts = MaximumSubAccounts;
+	type MaxAdditionalFields = MaxAdditionalFields;
+	type Slashed = Treasury;
+	type ForceOrigin = collective::EnsureMember<AccountId, CouncilCollective>;
+	type RegistrarOrigin = collective::EnsureMember<AccountId, CouncilCollective>;
+}
+
+parameter_types! {
+	pub const MinVestedTransfer: Balance = 100 * DOLLARS;
+	pub const MaxVestingSchedules: u32 = 28;
+}
+
+impl vesting::Trait for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type BlockNumberToBalance = ConvertInto;
+	type MinVestedTransfer = MinVestedTransfer;
+	type MaxVestingSchedules = MaxVestingSchedules;
+	// The chain specifications of the node give no account a vesting schedule at genesis.
+	type LegacyVestingAccounts = ();
+}
+
 impl system::offchain::CreateTransaction<Runtime, UncheckedExtrinsic> for Runtime {
 	type Public = <Signature as traits::Verify>::Signer;
 	type Signature = Signature;
@@ -501,14 +759,15 @@ construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic
 	{
 		System: system::{Module, Call, Storage, Config, Event},
-		Utility: utility::{Module, Call, Event},
+		Utility: utility::{Module, Call, Storage, Event<T>},
 		Babe: babe::{Module, Call, Storage, Config, Inherent(Timestamp)},
 		Timestamp: timestamp::{Module, Call, Storage, Inherent},
 		Authorship: authorship::{Module, Call, Storage, Inherent},
 		Indices: indices,
 		Balances: balances::{default, Error},
+		Vesting: vesting::{Module, Call, Storage, Event<T>, Config<T>},
 		TransactionPayment: transaction_payment::{Module, Storage},
-		Staking: staking::{default, OfflineWorker},
+		Staking: staking::{default, OfflineWorker, ValidateUnsigned},
 		Session: session::{Module, Call, Storage, Event, Config<T>},
 		Democracy: democracy::{Module, Call, Storage, Config, Event<T>},
 		Council: collective::<Instance1>::{Module, Call, Storage, Origin<T>, Event<T>, Config<T>},
@@ -524,6 +783,12 @@ construct_runtime!(
 		Offences: offences::{Module, Call, Storage, Event},
 		RandomnessCollectiveFlip: randomness_collective_flip::{Module, Call, Storage},
 		Nicks: nicks::{Module, Call, Storage, Event<T>},
+		Identity: identity::{Module, Call, Storage, Event<T>},
+		Proxy: proxy::{Module, Call, Storage, Event<T>},
+		Scheduler: scheduler::{Module, Call, Storage, Event<T>},
+		Preimage: preimage::{Module, Call, Storage, Event<T>},
+		Recovery: recovery::{Module, Call, Storage, Event<T>},
+		NominationPools: nomination_pools::{Module, Call, Storage, Event<T>},
 	}
 );
 
@@ -719,4 +984,17 @@ mod tests {
 		let x = SubmitTransaction::default();
 		is_submit_signed_transaction(x);
 	}
+	#[test]
+	fn non_transfer_proxy_cannot_move_funds() {
+		let allowed = |call: Call| ProxyType::NonTransfer.filter(&call);
+
+		assert!(allowed(Call::System(system::Call::remark(vec![]))));
+		assert!(allowed(Call::Staking(staking::Call::chill())));
+		assert!(!allowed(Call::Staking(staking::Call::set_payee(staking::RewardDestination::Stash))));
+		assert!(!allowed(Call::Recovery(recovery::Call::initiate_recovery(Default::default()))));
+		assert!(!allowed(Call::Proxy(proxy::Call::add_proxy(Default::default(), ProxyType::Any))));
+
+		assert!(ProxyType::NonTransfer.is_superset(&ProxyType::Governance));
+		assert!(!ProxyType::NonTransfer.is_superset(&ProxyType::Staking));
+	}
 }
@@ -22,7 +22,7 @@
 
 use rstd::{result, prelude::*};
 use rstd::collections::btree_set::BTreeSet;
-use support::{decl_module, decl_storage};
+use support::{decl_module, decl_storage, metadata::TypeInfo};
 use support::traits::{FindAuthor, VerifySeal, Get};
 use support::dispatch::Result as DispatchResult;
 use codec::{Encode, Decode};
@@ -195,9 +195,10 @@ where
 	}
 }
 
-#[derive(Encode, Decode, sr_primitives::RuntimeDebug)]
+/// An entry of the recent uncles, public as it is part of the storage metadata.
+#[derive(Encode, Decode, sr_primitives::RuntimeDebug, TypeInfo)]
 #[cfg_attr(any(feature = "std", test), derive(PartialEq))]
-enum UncleEntryItem<BlockNumber, Hash, Author> {
+pub enum UncleEntryItem<BlockNumber, Hash, Author> {
 	InclusionHeight(BlockNumber),
 	Uncle(Hash, Option<Author>),
 }
@@ -582,160 +582,6 @@ fn transfer_overflow_isnt_exploitable() {
 	});
 }
 
-#[test]
-fn check_vesting_status() {
-	ExtBuilder::default()
-		.existential_deposit(256)
-		.monied(true)
-		.vesting(true)
-		.build()
-		.execute_with(|| {
-			assert_eq!(System::block_number(), 1);
-			let user1_free_balance = Balances::free_balance(&1);
-			let user2_free_balance = Balances::free_balance(&2);
-			let user12_free_balance = Balances::free_balance(&12);
-			assert_eq!(user1_free_balance, 256 * 10); // Account 1 has free balance
-			assert_eq!(user2_free_balance, 256 * 20); // Account 2 has free balance
-			assert_eq!(user12_free_balance, 256 * 10); // Account 12 has free balance
-			let user1_vesting_schedule = VestingSchedule {
-				locked: 256 * 5,
-				per_block: 128, // Vesting over 10 blocks
-				starting_block: 0,
-			};
-			let user2_vesting_schedule = VestingSchedule {
-				locked: 256 * 20,
-				per_block: 256, // Vesting over 20 blocks
-				starting_block: 10,
-			};
-			let user12_vesting_schedule = VestingSchedule {
-				locked: 256 * 5,
-				per_block: 64, // Vesting over 20 blocks
-				starting_block: 10,
-			};
-			assert_eq!(Balances::vesting(&1), Some(user1_vesting_schedule)); // Account 1 has a vesting schedule
-			assert_eq!(Balances::vesting(&2), Some(user2_vesting_schedule)); // Account 2 has a vesting schedule
-			assert_eq!(Balances::vesting(&12), Some(user12_vesting_schedule)); // Account 12 has a vesting schedule
-
-			// Account 1 has only 128 units vested from their illiquid 256 * 5 units at block 1
-			assert_eq!(Balances::vesting_balance(&1), 128 * 9);
-			// Account 2 has their full balance locked
-			assert_eq!(Balances::vesting_balance(&2), user2_free_balance);
-			// Account 12 has only their illiquid funds locked
-			assert_eq!(Balances::vesting_balance(&12), user12_free_balance - 256 * 5);
-
-			System::set_block_number(10);
-			assert_eq!(System::block_number(), 10);
-
-			// Account 1 has fully vested by block 10
-			assert_eq!(Balances::vesting_balance(&1), 0);
-			// Account 2 has started vesting by block 10
-			assert_eq!(Balances::vesting_balance(&2), user2_free_balance);
-			// Account 12 has started vesting by block 10
-			assert_eq!(Balances::vesting_balance(&12), user12_free_balance - 256 * 5);
-
-			System::set_block_number(30);
-			assert_eq!(System::block_number(), 30);
-
-			assert_eq!(Balances::vesting_balance(&1), 0); // Account 1 is still fully vested, and not negative
-			assert_eq!(Balances::vesting_balance(&2), 0); // Account 2 has fully vested by block 30
-			assert_eq!(Balances::vesting_balance(&12), 0); // Account 2 has fully vested by block 30
-
-		});
-}
-
-#[test]
-fn unvested_balance_should_not_transfer() {
-	ExtBuilder::default()
-		.existential_deposit(10)
-		.monied(true)
-		.vesting(true)
-		.build()
-		.execute_with(|| {
-			assert_eq!(System::block_number(), 1);
-			let user1_free_balance = Balances::free_balance(&1);
-			assert_eq!(user1_free_balance, 100); // Account 1 has free balance
-			// Account 1 has only 5 units vested at block 1 (plus 50 unvested)
-			assert_eq!(Balances::vesting_balance(&1), 45);
-			assert_noop!(
-				Balances::transfer(Some(1).into(), 2, 56),
-				"vesting balance too high to send value",
-			); // Account 1 cannot send more than vested amount
-		});
-}
-
-#[test]
-fn vested_balance_should_transfer() {
-	ExtBuilder::default()
-		.existential_deposit(10)
-		.monied(true)
-		.vesting(true)
-		.build()
-		.execute_with(|| {
-			assert_eq!(System::block_number(), 1);
-			let user1_free_balance = Balances::free_balance(&1);
-			assert_eq!(user1_free_balance, 100); // Account 1 has free balance
-			// Account 1 has only 5 units vested at block 1 (plus 50 unvested)
-			assert_eq!(Balances::vesting_balance(&1), 45);
-			assert_ok!(Balances::transfer(Some(1).into(), 2, 55));
-		});
-}
-
-#[test]
-fn extra_balance_should_transfer() {
-	ExtBuilder::default()
-		.existential_deposit(10)
-		.monied(true)
-		.vesting(true)
-		.build()
-		.execute_with(|| {
-			assert_eq!(System::block_number(), 1);
-			assert_ok!(Balances::transfer(Some(3).into(), 1, 100));
-			assert_ok!(Balances::transfer(Some(3).into(), 2, 100));
-
-			let user1_free_balance = Balances::free_balance(&1);
-			assert_eq!(user1_free_balance, 200); // Account 1 has 100 more free balance than normal
-
-			let user2_free_balance = Balances::free_balance(&2);
-			assert_eq!(user2_free_balance, 300); // Account 2 has 100 more free balance than normal
-
-			// Account 1 has only 5 units vested at block 1 (plus 150 unvested)
-			assert_eq!(Balances::vesting_balance(&1), 45);
-			assert_ok!(Balances::transfer(Some(1).into(), 3, 155)); // Account 1 can send extra units gained
-
-			// Account 2 has no units vested at block 1, but gained 100
-			assert_eq!(Balances::vesting_balance(&2), 200);
-			assert_ok!(Balances::transfer(Some(2).into(), 3, 100)); // Account 2 can send extra units gained
-		});
-}
-
-#[test]
-fn liquid_funds_should_transfer_with_delayed_vesting() {
-	ExtBuilder::default()
-		.existential_deposit(256)
-		.monied(true)
-		.vesting(true)
-		.build()
-		.execute_with(|| {
-			assert_eq!(System::block_number(), 1);
-			let user12_free_balance = Balances::free_balance(&12);
-
-			assert_eq!(user12_free_balance, 2560); // Account 12 has free balance
-			// Account 12 has liquid funds
-			assert_eq!(Balances::vesting_balance(&12), user12_free_balance - 256 * 5);
-
-			// Account 12 has delayed vesting
-			let user12_vesting_schedule = VestingSchedule {
-				locked: 256 * 5,
-				per_block: 64, // Vesting over 20 blocks
-				starting_block: 10,
-			};
-			assert_eq!(Balances::vesting(&12), Some(user12_vesting_schedule));
-
-			// Account 12 can still send liquid funds
-			assert_ok!(Balances::transfer(Some(12).into(), 3, 256 * 5));
-		});
-}
-
 #[test]
 fn burn_must_work() {
 	ExtBuilder::default().monied(true).build().execute_with(|| {
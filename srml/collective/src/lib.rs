@@ -19,6 +19,14 @@
 //!
 //! The membership can be provided in one of two ways: either directly, using the Root-dispatchable
 //! function `set_members`, or indirectly, through implementing the `ChangeMembers`
+//!
+//! A motion is open for voting for `MotionDuration` blocks. Once its outcome is decided, or once it
+//! has expired, anyone can `close` it. When an expired motion is closed, the members that did not
+//! vote are counted as voting the same way as the prime member, if there is one and it voted, and
+//! against the motion otherwise.
+//!
+//! The proposal of a motion is stored as a preimage through `Preimages`, where it is requested for
+//! as long as the motion is open. The motion itself only refers to the hash of its proposal.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 #![recursion_limit="128"]
@@ -27,11 +35,16 @@ use rstd::{prelude::*, result};
 use primitives::u32_trait::Value as U32;
 use sr_primitives::RuntimeDebug;
 use sr_primitives::traits::{Hash, EnsureOrigin};
-use sr_primitives::weights::SimpleDispatchInfo;
+use sr_primitives::weights::{
+	SimpleDispatchInfo, GetDispatchInfo, WeighData, ClassifyDispatch, DispatchClass, Weight,
+};
 use support::{
 	dispatch::{Dispatchable, Parameter}, codec::{Encode, Decode},
-	traits::{ChangeMembers, InitializeMembers}, decl_module, decl_event,
-	decl_storage, ensure,
+	traits::{
+		ChangeMembers, InitializeMembers, Contains, ContainsCount, Get,
+		preimage::{PreimageProvider, PreimageRecipient},
+	},
+	decl_module, decl_event, decl_storage, ensure, storage::unhashed, metadata::TypeInfo,
 };
 use system::{self, ensure_signed, ensure_root};
 
@@ -49,10 +62,16 @@ pub trait Trait<I=DefaultInstance>: system::Trait {
 	type Origin: From<RawOrigin<Self::AccountId, I>>;
 
 	/// The outer call dispatch type.
-	type Proposal: Parameter + Dispatchable<Origin=<Self as Trait<I>>::Origin>;
+	type Proposal: Parameter + Dispatchable<Origin=<Self as Trait<I>>::Origin> + GetDispatchInfo;
 
 	/// The outer event type.
 	type Event: From<Event<Self, I>> + Into<<Self as system::Trait>::Event>;
+
+	/// The time-out for council motions.
+	type MotionDuration: Get<Self::BlockNumber>;
+
+	/// The store of the proposals of open motions.
+	type Preimages: PreimageRecipient<Self::Hash>;
 }
 
 /// Origin for the collective module.
@@ -69,9 +88,9 @@ pub enum RawOrigin<AccountId, I> {
 /// Origin for the collective module.
 pub type Origin<T, I=DefaultInstance> = RawOrigin<<T as system::Trait>::AccountId, I>;
 
-#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
 /// Info for keeping track of a motion being voted on.
-pub struct Votes<AccountId> {
+pub struct Votes<AccountId, BlockNumber> {
 	/// The proposal's unique index.
 	index: ProposalIndex,
 	/// The number of approval votes that are needed to pass the motion.
@@ -80,20 +99,76 @@ pub struct Votes<AccountId> {
 	ayes: Vec<AccountId>,
 	/// The current set of voters that rejected it.
 	nays: Vec<AccountId>,
+	/// The hard end time of this vote.
+	end: BlockNumber,
+}
+
+/// Info for keeping track of a motion being voted on, as stored before motions had an end.
+#[derive(Decode)]
+struct LegacyVotes<AccountId> {
+	index: ProposalIndex,
+	threshold: MemberCount,
+	ayes: Vec<AccountId>,
+	nays: Vec<AccountId>,
+}
+
+/// Weight of migrating the votes of a single open motion.
+const VOTES_MIGRATION_WEIGHT: Weight = 10_000;
+
+/// Weight of moving the proposal of a single open motion to the preimages.
+const PROPOSAL_MIGRATION_WEIGHT: Weight = 100_000;
+
+/// Weight of a dispatchable that may dispatch a proposal: a fixed base weight, plus the weight of
+/// the proposal if it is dispatched.
+struct WeightWithProposal(Weight);
+
+// `execute`: the proposal is always dispatched.
+impl<P: GetDispatchInfo> WeighData<(&Box<P>,)> for WeightWithProposal {
+	fn weigh_data(&self, (proposal,): (&Box<P>,)) -> Weight {
+		self.0.saturating_add(proposal.get_dispatch_info().weight)
+	}
+}
+
+// `propose`: the proposal is only dispatched right away if the threshold is lower than two.
+impl<P: GetDispatchInfo> WeighData<(&MemberCount, &Box<P>)> for WeightWithProposal {
+	fn weigh_data(&self, (threshold, proposal): (&MemberCount, &Box<P>)) -> Weight {
+		if *threshold < 2 {
+			self.0.saturating_add(proposal.get_dispatch_info().weight)
+		} else {
+			self.0
+		}
+	}
+}
+
+// `close`: the proposal is not known, so the caller provides an upper bound of its weight.
+impl<H> WeighData<(&H, &ProposalIndex, &Weight)> for WeightWithProposal {
+	fn weigh_data(&self, (_, _, proposal_weight_bound): (&H, &ProposalIndex, &Weight)) -> Weight {
+		self.0.saturating_add(*proposal_weight_bound)
+	}
+}
+
+impl<T> ClassifyDispatch<T> for WeightWithProposal {
+	fn classify_dispatch(&self, _: T) -> DispatchClass {
+		DispatchClass::Operational
+	}
 }
 
 decl_storage! {
 	trait Store for Module<T: Trait<I>, I: Instance=DefaultInstance> as Collective {
 		/// The hashes of the active proposals.
 		pub Proposals get(fn proposals): Vec<T::Hash>;
-		/// Actual proposal for a given hash, if it's current.
-		pub ProposalOf get(fn proposal_of): map T::Hash => Option<<T as Trait<I>>::Proposal>;
+		/// The proposals of the motions opened before proposals were stored as preimages. Moved to
+		/// `Preimages` on runtime upgrade.
+		ProposalOf: map T::Hash => Option<<T as Trait<I>>::Proposal>;
 		/// Votes on a given proposal, if it is ongoing.
-		pub Voting get(fn voting): map T::Hash => Option<Votes<T::AccountId>>;
+		pub Voting get(fn voting): map T::Hash => Option<Votes<T::AccountId, T::BlockNumber>>;
 		/// Proposals so far.
 		pub ProposalCount get(fn proposal_count): u32;
 		/// The current members of the collective. This is stored sorted (just by value).
 		pub Members get(fn members): Vec<T::AccountId>;
+		/// The member who provides the default vote for any other members that do not vote before
+		/// the timeout. If None, then no member has that privilege.
+		pub Prime get(fn prime): Option<T::AccountId>;
 	}
 	add_extra_genesis {
 		config(phantom): rstd::marker::PhantomData<I>;
@@ -121,6 +196,9 @@ decl_event!(
 		Executed(Hash, bool),
 		/// A single member did some action; `bool` is true if returned without error.
 		MemberExecuted(Hash, bool),
+		/// A motion was closed with the given final tally of yes and no votes, counting the
+		/// votes of any abstaining members.
+		Closed(Hash, MemberCount, MemberCount),
 	}
 );
 
@@ -131,25 +209,34 @@ decl_module! {
 	pub struct Module<T: Trait<I>, I: Instance=DefaultInstance> for enum Call where origin: <T as system::Trait>::Origin {
 		fn deposit_event() = default;
 
-		/// Set the collective's membership manually to `new_members`. Be nice to the chain and
-		/// provide it pre-sorted.
+		fn on_runtime_upgrade() -> Weight {
+			Self::migrate_legacy_votes().saturating_add(Self::migrate_legacy_proposals())
+		}
+
+		/// Set the collective's membership manually to `new_members`, and its prime member to
+		/// `prime`, which must be one of `new_members`. Be nice to the chain and provide it
+		/// pre-sorted.
 		///
 		/// Requires root origin.
 		#[weight = SimpleDispatchInfo::FixedOperational(100_000)]
-		fn set_members(origin, new_members: Vec<T::AccountId>) {
+		fn set_members(origin, new_members: Vec<T::AccountId>, prime: Option<T::AccountId>) {
 			ensure_root(origin)?;
 			let mut new_members = new_members;
 			new_members.sort();
+			if let Some(ref p) = prime {
+				ensure!(new_members.binary_search(p).is_ok(), "prime must be a member");
+			}
 			<Members<T, I>>::mutate(|m| {
 				<Self as ChangeMembers<T::AccountId>>::set_members_sorted(&new_members[..], m);
 				*m = new_members;
 			});
+			<Self as ChangeMembers<T::AccountId>>::set_prime(prime);
 		}
 
 		/// Dispatch a proposal from a member using the `Member` origin.
 		///
 		/// Origin must be a member of the collective.
-		#[weight = SimpleDispatchInfo::FixedOperational(100_000)]
+		#[weight = WeightWithProposal(100_000)]
 		fn execute(origin, proposal: Box<<T as Trait<I>>::Proposal>) {
 			let who = ensure_signed(origin)?;
 			ensure!(Self::is_member(&who), "proposer not a member");
@@ -159,38 +246,48 @@ decl_module! {
 			Self::deposit_event(RawEvent::MemberExecuted(proposal_hash, ok));
 		}
 
+		/// Unless it is executed right away, the proposal is stored as a preimage until the motion
+		/// is closed.
+		///
 		/// # <weight>
 		/// - Bounded storage reads and writes.
 		/// - Argument `threshold` has bearing on weight.
+		/// - The weight of the proposal is included if it is executed right away.
 		/// # </weight>
-		#[weight = SimpleDispatchInfo::FixedOperational(5_000_000)]
+		#[weight = WeightWithProposal(5_000_000)]
 		fn propose(origin, #[compact] threshold: MemberCount, proposal: Box<<T as Trait<I>>::Proposal>) {
 			let who = ensure_signed(origin)?;
 			ensure!(Self::is_member(&who), "proposer not a member");
 
 			let proposal_hash = T::Hashing::hash_of(&proposal);
 
-			ensure!(!<ProposalOf<T, I>>::exists(proposal_hash), "duplicate proposals not allowed");
+			ensure!(!<Voting<T, I>>::exists(proposal_hash), "duplicate proposals not allowed");
 
 			if threshold < 2 {
 				let seats = Self::members().len() as MemberCount;
 				let ok = proposal.dispatch(RawOrigin::Members(1, seats).into()).is_ok();
 				Self::deposit_event(RawEvent::Executed(proposal_hash, ok));
 			} else {
+				T::Preimages::note_preimage(proposal.encode())
+					.map_err(|_| "proposal could not be stored")?;
+				T::Preimages::request_preimage(&proposal_hash);
+
 				let index = Self::proposal_count();
 				<ProposalCount<I>>::mutate(|i| *i += 1);
 				<Proposals<T, I>>::mutate(|proposals| proposals.push(proposal_hash));
-				<ProposalOf<T, I>>::insert(proposal_hash, *proposal);
-				let votes = Votes { index, threshold, ayes: vec![who.clone()], nays: vec![] };
+				let end = <system::Module<T>>::block_number() + T::MotionDuration::get();
+				let votes = Votes { index, threshold, ayes: vec![who.clone()], nays: vec![], end };
 				<Voting<T, I>>::insert(proposal_hash, votes);
 
 				Self::deposit_event(RawEvent::Proposed(who, index, proposal_hash, threshold));
 			}
 		}
 
+		/// Vote on an open motion. The motion is not executed or disapproved by the vote itself;
+		/// once its outcome is decided it must be `close`d.
+		///
 		/// # <weight>
 		/// - Bounded storage read and writes.
-		/// - Will be slightly heavier if the proposal is approved / disapproved after the vote.
 		/// # </weight>
 		#[weight = SimpleDispatchInfo::FixedOperational(200_000)]
 		fn vote(origin, proposal: T::Hash, #[compact] index: ProposalIndex, approve: bool) {
@@ -227,31 +324,72 @@ decl_module! {
 			let no_votes = voting.nays.len() as MemberCount;
 			Self::deposit_event(RawEvent::Voted(who, proposal, approve, yes_votes, no_votes));
 
+			<Voting<T, I>>::insert(&proposal, voting);
+		}
+
+		/// Close a motion, executing it if it was approved and dropping it otherwise.
+		///
+		/// May be called by any signed account, either once the outcome of the motion is decided
+		/// by the votes cast so far, or once `MotionDuration` has passed since it was proposed. In
+		/// the latter case, members who did not vote are counted as voting like the prime member,
+		/// if there is one and it voted, and against the motion otherwise.
+		///
+		/// `proposal_weight_bound` must be at least the weight of the proposal, and is what the
+		/// caller pays for.
+		///
+		/// # <weight>
+		/// - Bounded storage reads and writes.
+		/// - The weight of the proposal, bounded by `proposal_weight_bound`.
+		/// # </weight>
+		#[weight = WeightWithProposal(200_000)]
+		fn close(
+			origin,
+			proposal: T::Hash,
+			#[compact] index: ProposalIndex,
+			#[compact] proposal_weight_bound: Weight
+		) {
+			let _ = ensure_signed(origin)?;
+
+			let voting = Self::voting(&proposal).ok_or("proposal must exist")?;
+			ensure!(voting.index == index, "mismatched index");
+
+			let mut no_votes = voting.nays.len() as MemberCount;
+			let mut yes_votes = voting.ayes.len() as MemberCount;
 			let seats = Self::members().len() as MemberCount;
-			let approved = yes_votes >= voting.threshold;
-			let disapproved = seats.saturating_sub(no_votes) < voting.threshold;
-			if approved || disapproved {
-				if approved {
-					Self::deposit_event(RawEvent::Approved(proposal));
-
-					// execute motion, assuming it exists.
-					if let Some(p) = <ProposalOf<T, I>>::take(&proposal) {
-						let origin = RawOrigin::Members(voting.threshold, seats).into();
-						let ok = p.dispatch(origin).is_ok();
-						Self::deposit_event(RawEvent::Executed(proposal, ok));
-					}
-				} else {
-					// disapproved
-					Self::deposit_event(RawEvent::Disapproved(proposal));
+			let decided = yes_votes >= voting.threshold
+				|| seats.saturating_sub(no_votes) < voting.threshold;
+
+			if !decided {
+				ensure!(<system::Module<T>>::block_number() >= voting.end, "motion has not ended yet");
+
+				// default voting strategy.
+				let abstentions = seats.saturating_sub(yes_votes + no_votes);
+				match Self::prime() {
+					Some(ref who) if voting.ayes.contains(who) => yes_votes += abstentions,
+					_ => no_votes += abstentions,
 				}
+			}
+
+			let approved = yes_votes >= voting.threshold;
+			if approved {
+				let p = Self::proposal_of(&proposal).ok_or("proposal must exist")?;
+				ensure!(
+					p.get_dispatch_info().weight <= proposal_weight_bound,
+					"proposal weight exceeds the given bound",
+				);
+
+				Self::deposit_event(RawEvent::Closed(proposal, yes_votes, no_votes));
+				Self::deposit_event(RawEvent::Approved(proposal));
 
-				// remove vote
-				<Voting<T, I>>::remove(&proposal);
-				<Proposals<T, I>>::mutate(|proposals| proposals.retain(|h| h != &proposal));
+				let origin = RawOrigin::Members(voting.threshold, seats).into();
+				let ok = p.dispatch(origin).is_ok();
+				Self::deposit_event(RawEvent::Executed(proposal, ok));
 			} else {
-				// update voting
-				<Voting<T, I>>::insert(&proposal, voting);
+				Self::deposit_event(RawEvent::Closed(proposal, yes_votes, no_votes));
+				Self::deposit_event(RawEvent::Disapproved(proposal));
 			}
+
+			Self::remove_proposal(proposal);
 		}
 	}
 }
@@ -260,6 +398,61 @@ impl<T: Trait<I>, I: Instance> Module<T, I> {
 	pub fn is_member(who: &T::AccountId) -> bool {
 		Self::members().contains(who)
 	}
+
+	/// The proposal with the given hash, if its preimage is available.
+	pub fn proposal_of(hash: &T::Hash) -> Option<<T as Trait<I>>::Proposal> {
+		T::Preimages::get_preimage(hash).and_then(|bytes| Decode::decode(&mut &bytes[..]).ok())
+	}
+
+	/// Remove a motion and its votes from storage, and release its proposal.
+	fn remove_proposal(proposal: T::Hash) {
+		T::Preimages::unrequest_preimage(&proposal);
+		<Voting<T, I>>::remove(&proposal);
+		<Proposals<T, I>>::mutate(|proposals| proposals.retain(|h| h != &proposal));
+	}
+
+	/// Give the votes of any open motion stored without an end a full `MotionDuration` from now.
+	fn migrate_legacy_votes() -> Weight {
+		let end = <system::Module<T>>::block_number() + T::MotionDuration::get();
+		let proposals = Self::proposals();
+		for h in proposals.iter() {
+			let key = <Voting<T, I>>::hashed_key_for(h);
+			let raw = match unhashed::get_raw(&key) {
+				Some(raw) => raw,
+				None => continue,
+			};
+			// the current layout is a strict extension of the legacy one, so try it first.
+			if Votes::<T::AccountId, T::BlockNumber>::decode(&mut &raw[..]).is_ok() {
+				continue;
+			}
+			if let Ok(LegacyVotes { index, threshold, ayes, nays }) = Decode::decode(&mut &raw[..]) {
+				<Voting<T, I>>::insert(h, Votes { index, threshold, ayes, nays, end });
+			}
+		}
+		(proposals.len() as Weight).saturating_mul(VOTES_MIGRATION_WEIGHT)
+	}
+
+	/// Move the proposals of open motions out of `ProposalOf` into `Preimages`. A motion whose
+	/// proposal cannot be stored there could never be closed, so it is dropped.
+	fn migrate_legacy_proposals() -> Weight {
+		let mut migrated: Weight = 0;
+		for h in Self::proposals() {
+			let key = <ProposalOf<T, I>>::hashed_key_for(&h);
+			let raw = match unhashed::get_raw(&key) {
+				Some(raw) => raw,
+				None => continue,
+			};
+			unhashed::kill(&key);
+			if T::Preimages::note_preimage(raw).is_ok() {
+				T::Preimages::request_preimage(&h);
+			} else {
+				<Voting<T, I>>::remove(&h);
+				<Proposals<T, I>>::mutate(|proposals| proposals.retain(|p| p != &h));
+			}
+			migrated += 1;
+		}
+		migrated.saturating_mul(PROPOSAL_MIGRATION_WEIGHT)
+	}
 }
 
 impl<T: Trait<I>, I: Instance> ChangeMembers<T::AccountId> for Module<T, I> {
@@ -281,6 +474,14 @@ impl<T: Trait<I>, I: Instance> ChangeMembers<T::AccountId> for Module<T, I> {
 			);
 		}
 		<Members<T, I>>::put(new);
+		// the prime member keeps its privilege for as long as it stays in the collective.
+		if Self::prime().map_or(false, |p| new.binary_search(&p).is_err()) {
+			<Prime<T, I>>::kill();
+		}
+	}
+
+	fn set_prime(prime: Option<T::AccountId>) {
+		<Prime<T, I>>::mutate(|p| *p = prime);
 	}
 }
 
@@ -293,6 +494,18 @@ impl<T: Trait<I>, I: Instance> InitializeMembers<T::AccountId> for Module<T, I>
 	}
 }
 
+impl<T: Trait<I>, I: Instance> Contains<T::AccountId> for Module<T, I> {
+	fn contains(who: &T::AccountId) -> bool {
+		Self::members().binary_search(who).is_ok()
+	}
+}
+
+impl<T: Trait<I>, I: Instance> ContainsCount<T::AccountId> for Module<T, I> {
+	fn count() -> usize {
+		Self::members().len()
+	}
+}
+
 /// Ensure that the origin `o` represents at least `n` members. Returns `Ok` or an `Err`
 /// otherwise.
 pub fn ensure_members<OuterOrigin, AccountId, I>(o: OuterOrigin, n: MemberCount)
@@ -383,16 +596,59 @@ mod tests {
 	use hex_literal::hex;
 	use primitives::H256;
 	use sr_primitives::{
-		Perbill, traits::{BlakeTwo256, IdentityLookup, Block as BlockT}, testing::Header,
+		Perbill, traits::{BlakeTwo256, IdentityLookup, Block as BlockT, OnRuntimeUpgrade}, testing::Header,
 		BuildStorage,
 	};
 	use crate as collective;
+	use std::{cell::RefCell, collections::BTreeMap};
+
+	thread_local! {
+		static PREIMAGES: RefCell<BTreeMap<H256, (Vec<u8>, u32)>> = RefCell::new(BTreeMap::new());
+	}
+
+	/// Preimages kept in memory for as long as they are requested.
+	pub struct TestPreimages;
+	impl PreimageProvider<H256> for TestPreimages {
+		fn have_preimage(hash: &H256) -> bool {
+			PREIMAGES.with(|p| p.borrow().get(hash).map_or(false, |(bytes, _)| !bytes.is_empty()))
+		}
+		fn get_preimage(hash: &H256) -> Option<Vec<u8>> {
+			PREIMAGES.with(|p| p.borrow().get(hash).map(|(bytes, _)| bytes.clone()))
+				.filter(|bytes| !bytes.is_empty())
+		}
+		fn preimage_requested(hash: &H256) -> bool {
+			PREIMAGES.with(|p| p.borrow().get(hash).map_or(false, |(_, count)| *count > 0))
+		}
+		fn request_preimage(hash: &H256) {
+			PREIMAGES.with(|p| p.borrow_mut().entry(*hash).or_insert((vec![], 0)).1 += 1);
+		}
+		fn unrequest_preimage(hash: &H256) {
+			PREIMAGES.with(|p| {
+				let mut p = p.borrow_mut();
+				let count = p.get_mut(hash).map(|(_, count)| { *count -= 1; *count });
+				if count == Some(0) {
+					p.remove(hash);
+				}
+			});
+		}
+	}
+	impl PreimageRecipient<H256> for TestPreimages {
+		fn note_preimage(bytes: Vec<u8>) -> Result<(), ()> {
+			if bytes.len() > 64 {
+				return Err(())
+			}
+			let hash = BlakeTwo256::hash(&bytes);
+			PREIMAGES.with(|p| p.borrow_mut().entry(hash).or_insert((vec![], 0)).0 = bytes);
+			Ok(())
+		}
+	}
 
 	parameter_types! {
 		pub const BlockHashCount: u64 = 250;
 		pub const MaximumBlockWeight: u32 = 1024;
 		pub const MaximumBlockLength: u32 = 2 * 1024;
 		pub const AvailableBlockRatio: Perbill = Perbill::one();
+		pub const MotionDuration: u64 = 3;
 	}
 	impl system::Trait for Test {
 		type Origin = Origin;
@@ -415,11 +671,15 @@ mod tests {
 		type Origin = Origin;
 		type Proposal = Call;
 		type Event = Event;
+		type MotionDuration = MotionDuration;
+		type Preimages = TestPreimages;
 	}
 	impl Trait for Test {
 		type Origin = Origin;
 		type Proposal = Call;
 		type Event = Event;
+		type MotionDuration = MotionDuration;
+		type Preimages = TestPreimages;
 	}
 
 	pub type Block = sr_primitives::generic::Block<Header, UncheckedExtrinsic>;
@@ -470,12 +730,12 @@ mod tests {
 			assert_ok!(Collective::vote(Origin::signed(2), hash.clone(), 0, true));
 			assert_eq!(
 				Collective::voting(&hash),
-				Some(Votes { index: 0, threshold: 3, ayes: vec![1, 2], nays: vec![] })
+				Some(Votes { index: 0, threshold: 3, ayes: vec![1, 2], nays: vec![], end: 4 })
 			);
 			Collective::change_members_sorted(&[4], &[1], &[2, 3, 4]);
 			assert_eq!(
 				Collective::voting(&hash),
-				Some(Votes { index: 0, threshold: 3, ayes: vec![2], nays: vec![] })
+				Some(Votes { index: 0, threshold: 3, ayes: vec![2], nays: vec![], end: 4 })
 			);
 
 			let proposal = make_proposal(69);
@@ -484,12 +744,12 @@ mod tests {
 			assert_ok!(Collective::vote(Origin::signed(3), hash.clone(), 1, false));
 			assert_eq!(
 				Collective::voting(&hash),
-				Some(Votes { index: 1, threshold: 2, ayes: vec![2], nays: vec![3] })
+				Some(Votes { index: 1, threshold: 2, ayes: vec![2], nays: vec![3], end: 4 })
 			);
 			Collective::change_members_sorted(&[], &[3], &[2, 4]);
 			assert_eq!(
 				Collective::voting(&hash),
-				Some(Votes { index: 1, threshold: 2, ayes: vec![2], nays: vec![] })
+				Some(Votes { index: 1, threshold: 2, ayes: vec![2], nays: vec![], end: 4 })
 			);
 		});
 	}
@@ -504,12 +764,12 @@ mod tests {
 			assert_ok!(Collective::vote(Origin::signed(2), hash.clone(), 0, true));
 			assert_eq!(
 				Collective::voting(&hash),
-				Some(Votes { index: 0, threshold: 3, ayes: vec![1, 2], nays: vec![] })
+				Some(Votes { index: 0, threshold: 3, ayes: vec![1, 2], nays: vec![], end: 4 })
 			);
-			assert_ok!(Collective::set_members(Origin::ROOT, vec![2, 3, 4]));
+			assert_ok!(Collective::set_members(Origin::ROOT, vec![2, 3, 4], None));
 			assert_eq!(
 				Collective::voting(&hash),
-				Some(Votes { index: 0, threshold: 3, ayes: vec![2], nays: vec![] })
+				Some(Votes { index: 0, threshold: 3, ayes: vec![2], nays: vec![], end: 4 })
 			);
 
 			let proposal = make_proposal(69);
@@ -518,12 +778,12 @@ mod tests {
 			assert_ok!(Collective::vote(Origin::signed(3), hash.clone(), 1, false));
 			assert_eq!(
 				Collective::voting(&hash),
-				Some(Votes { index: 1, threshold: 2, ayes: vec![2], nays: vec![3] })
+				Some(Votes { index: 1, threshold: 2, ayes: vec![2], nays: vec![3], end: 4 })
 			);
-			assert_ok!(Collective::set_members(Origin::ROOT, vec![2, 4]));
+			assert_ok!(Collective::set_members(Origin::ROOT, vec![2, 4], None));
 			assert_eq!(
 				Collective::voting(&hash),
-				Some(Votes { index: 1, threshold: 2, ayes: vec![2], nays: vec![] })
+				Some(Votes { index: 1, threshold: 2, ayes: vec![2], nays: vec![], end: 4 })
 			);
 		});
 	}
@@ -539,7 +799,7 @@ mod tests {
 			assert_eq!(Collective::proposal_of(&hash), Some(proposal));
 			assert_eq!(
 				Collective::voting(&hash),
-				Some(Votes { index: 0, threshold: 3, ayes: vec![1], nays: vec![] })
+				Some(Votes { index: 0, threshold: 3, ayes: vec![1], nays: vec![], end: 4 })
 			);
 
 			assert_eq!(System::events(), vec![
@@ -606,7 +866,7 @@ mod tests {
 			assert_ok!(Collective::propose(Origin::signed(1), 2, Box::new(proposal.clone())));
 			assert_eq!(
 				Collective::voting(&hash),
-				Some(Votes { index: 0, threshold: 2, ayes: vec![1], nays: vec![] })
+				Some(Votes { index: 0, threshold: 2, ayes: vec![1], nays: vec![], end: 4 })
 			);
 			assert_noop!(
 				Collective::vote(Origin::signed(1), hash.clone(), 0, true),
@@ -615,7 +875,7 @@ mod tests {
 			assert_ok!(Collective::vote(Origin::signed(1), hash.clone(), 0, false));
 			assert_eq!(
 				Collective::voting(&hash),
-				Some(Votes { index: 0, threshold: 2, ayes: vec![], nays: vec![1] })
+				Some(Votes { index: 0, threshold: 2, ayes: vec![], nays: vec![1], end: 4 })
 			);
 			assert_noop!(
 				Collective::vote(Origin::signed(1), hash.clone(), 0, false),
@@ -656,6 +916,7 @@ mod tests {
 			let hash: H256 = proposal.blake2_256().into();
 			assert_ok!(Collective::propose(Origin::signed(1), 3, Box::new(proposal.clone())));
 			assert_ok!(Collective::vote(Origin::signed(2), hash.clone(), 0, false));
+			assert_ok!(Collective::close(Origin::signed(2), hash.clone(), 0, 0));
 
 			assert_eq!(System::events(), vec![
 				EventRecord {
@@ -680,6 +941,15 @@ mod tests {
 					)),
 					topics: vec![],
 				},
+				EventRecord {
+					phase: Phase::Finalization,
+					event: Event::collective_Instance1(RawEvent::Closed(
+						hex!["68eea8f20b542ec656c6ac2d10435ae3bd1729efc34d1354ab85af840aad2d35"].into(),
+						1,
+						1,
+					)),
+					topics: vec![],
+				},
 				EventRecord {
 					phase: Phase::Finalization,
 					event: Event::collective_Instance1(RawEvent::Disapproved(
@@ -699,6 +969,8 @@ mod tests {
 			let hash: H256 = proposal.blake2_256().into();
 			assert_ok!(Collective::propose(Origin::signed(1), 2, Box::new(proposal.clone())));
 			assert_ok!(Collective::vote(Origin::signed(2), hash.clone(), 0, true));
+			let proposal_weight = proposal.get_dispatch_info().weight;
+			assert_ok!(Collective::close(Origin::signed(2), hash.clone(), 0, proposal_weight));
 
 			assert_eq!(System::events(), vec![
 				EventRecord {
@@ -722,6 +994,15 @@ mod tests {
 					)),
 					topics: vec![],
 				},
+				EventRecord {
+					phase: Phase::Finalization,
+					event: Event::collective_Instance1(RawEvent::Closed(
+						hex!["68eea8f20b542ec656c6ac2d10435ae3bd1729efc34d1354ab85af840aad2d35"].into(),
+						2,
+						0,
+					)),
+					topics: vec![],
+				},
 				EventRecord {
 					phase: Phase::Finalization,
 					event: Event::collective_Instance1(RawEvent::Approved(
@@ -740,4 +1021,199 @@ mod tests {
 			]);
 		});
 	}
+
+	#[test]
+	fn close_works_only_once_motion_has_ended_or_is_decided() {
+		make_ext().execute_with(|| {
+			System::set_block_number(1);
+			let proposal = make_proposal(42);
+			let hash: H256 = proposal.blake2_256().into();
+			assert_ok!(Collective::propose(Origin::signed(1), 3, Box::new(proposal.clone())));
+			assert_ok!(Collective::vote(Origin::signed(2), hash.clone(), 0, true));
+
+			assert_noop!(
+				Collective::close(Origin::signed(4), hash.clone(), 0, 0),
+				"motion has not ended yet",
+			);
+			assert_noop!(
+				Collective::close(Origin::signed(4), hash.clone(), 1, 0),
+				"mismatched index",
+			);
+
+			System::set_block_number(4);
+			assert_ok!(Collective::close(Origin::signed(4), hash.clone(), 0, 0));
+			assert_eq!(Collective::proposals(), Vec::<H256>::new());
+			assert_eq!(Collective::proposal_of(&hash), None);
+			assert_eq!(Collective::voting(&hash), None);
+
+			let record = |event| EventRecord { phase: Phase::Finalization, event, topics: vec![] };
+			assert_eq!(&System::events()[2..], &[
+				record(Event::collective_Instance1(RawEvent::Closed(hash.clone(), 2, 1))),
+				record(Event::collective_Instance1(RawEvent::Disapproved(hash.clone()))),
+			][..]);
+		});
+	}
+
+	#[test]
+	fn close_with_prime_voting_aye_counts_abstentions_as_ayes() {
+		make_ext().execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(Collective::set_members(Origin::ROOT, vec![1, 2, 3], Some(3)));
+			assert_eq!(Collective::prime(), Some(3));
+
+			let proposal = make_proposal(42);
+			let proposal_weight = proposal.get_dispatch_info().weight;
+			let hash: H256 = proposal.blake2_256().into();
+			assert_ok!(Collective::propose(Origin::signed(3), 3, Box::new(proposal.clone())));
+
+			System::set_block_number(4);
+			assert_ok!(Collective::close(Origin::signed(4), hash.clone(), 0, proposal_weight));
+
+			let record = |event| EventRecord { phase: Phase::Finalization, event, topics: vec![] };
+			assert_eq!(&System::events()[1..], &[
+				record(Event::collective_Instance1(RawEvent::Closed(hash.clone(), 3, 0))),
+				record(Event::collective_Instance1(RawEvent::Approved(hash.clone()))),
+				record(Event::collective_Instance1(RawEvent::Executed(hash.clone(), false))),
+			][..]);
+		});
+	}
+
+	#[test]
+	fn close_with_prime_voting_nay_counts_abstentions_as_nays() {
+		make_ext().execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(Collective::set_members(Origin::ROOT, vec![1, 2, 3], Some(3)));
+
+			let proposal = make_proposal(42);
+			let hash: H256 = proposal.blake2_256().into();
+			assert_ok!(Collective::propose(Origin::signed(1), 2, Box::new(proposal.clone())));
+			assert_ok!(Collective::vote(Origin::signed(3), hash.clone(), 0, false));
+
+			System::set_block_number(4);
+			assert_ok!(Collective::close(Origin::signed(4), hash.clone(), 0, 0));
+
+			let record = |event| EventRecord { phase: Phase::Finalization, event, topics: vec![] };
+			assert_eq!(&System::events()[2..], &[
+				record(Event::collective_Instance1(RawEvent::Closed(hash.clone(), 1, 2))),
+				record(Event::collective_Instance1(RawEvent::Disapproved(hash.clone()))),
+			][..]);
+		});
+	}
+
+	#[test]
+	fn close_requires_proposal_weight_bound() {
+		make_ext().execute_with(|| {
+			System::set_block_number(1);
+			let proposal = make_proposal(42);
+			let proposal_weight = proposal.get_dispatch_info().weight;
+			let hash: H256 = proposal.blake2_256().into();
+			assert_ok!(Collective::propose(Origin::signed(1), 2, Box::new(proposal.clone())));
+			assert_ok!(Collective::vote(Origin::signed(2), hash.clone(), 0, true));
+
+			assert_noop!(
+				Collective::close(Origin::signed(2), hash.clone(), 0, proposal_weight - 1),
+				"proposal weight exceeds the given bound",
+			);
+			assert_ok!(Collective::close(Origin::signed(2), hash.clone(), 0, proposal_weight));
+		});
+	}
+
+	#[test]
+	fn set_members_rejects_prime_not_in_members() {
+		make_ext().execute_with(|| {
+			assert_noop!(
+				Collective::set_members(Origin::ROOT, vec![1, 2, 3], Some(4)),
+				"prime must be a member",
+			);
+			assert_ok!(Collective::set_members(Origin::ROOT, vec![1, 2, 3], Some(1)));
+			Collective::change_members_sorted(&[4], &[], &[1, 2, 3, 4]);
+			assert_eq!(Collective::prime(), Some(1));
+			Collective::change_members_sorted(&[], &[1], &[2, 3, 4]);
+			assert_eq!(Collective::prime(), None);
+		});
+	}
+
+	#[test]
+	fn legacy_votes_are_migrated_on_runtime_upgrade() {
+		#[derive(Encode)]
+		struct OldVotes {
+			index: ProposalIndex,
+			threshold: MemberCount,
+			ayes: Vec<u64>,
+			nays: Vec<u64>,
+		}
+
+		make_ext().execute_with(|| {
+			System::set_block_number(2);
+			let proposal = make_proposal(42);
+			let hash: H256 = proposal.blake2_256().into();
+			assert_ok!(Collective::propose(Origin::signed(1), 3, Box::new(proposal.clone())));
+			let old = OldVotes { index: 0, threshold: 3, ayes: vec![1], nays: vec![2] };
+			unhashed::put_raw(&<Voting<Test, Instance1>>::hashed_key_for(&hash), &old.encode());
+			assert_eq!(Collective::voting(&hash), None);
+
+			System::set_block_number(4);
+			assert_eq!(<Collective as OnRuntimeUpgrade>::on_runtime_upgrade(), 10_000);
+			assert_eq!(
+				Collective::voting(&hash),
+				Some(Votes { index: 0, threshold: 3, ayes: vec![1], nays: vec![2], end: 7 })
+			);
+
+			// votes already in the current layout are left alone.
+			assert_eq!(<Collective as OnRuntimeUpgrade>::on_runtime_upgrade(), 10_000);
+			assert_eq!(
+				Collective::voting(&hash),
+				Some(Votes { index: 0, threshold: 3, ayes: vec![1], nays: vec![2], end: 7 })
+			);
+		});
+	}
+
+	#[test]
+	fn proposal_is_kept_as_preimage_while_the_motion_is_open() {
+		make_ext().execute_with(|| {
+			System::set_block_number(1);
+			let proposal = make_proposal(42);
+			let hash: H256 = proposal.blake2_256().into();
+			assert_ok!(Collective::propose(Origin::signed(1), 2, Box::new(proposal.clone())));
+			assert!(TestPreimages::preimage_requested(&hash));
+			assert_eq!(TestPreimages::get_preimage(&hash), Some(proposal.encode()));
+
+			assert_ok!(Collective::vote(Origin::signed(2), hash.clone(), 0, true));
+			assert_ok!(Collective::close(Origin::signed(2), hash.clone(), 0, 1_000_000));
+			assert!(!TestPreimages::preimage_requested(&hash));
+
+			// a proposal which cannot be stored cannot be proposed.
+			let proposal = Call::System(system::Call::remark(vec![0; 64]));
+			assert_noop!(
+				Collective::propose(Origin::signed(1), 2, Box::new(proposal)),
+				"proposal could not be stored",
+			);
+		});
+	}
+
+	#[test]
+	fn legacy_proposals_are_moved_to_preimages_on_runtime_upgrade() {
+		make_ext().execute_with(|| {
+			System::set_block_number(1);
+			let proposal = make_proposal(42);
+			let hash: H256 = proposal.blake2_256().into();
+			let too_large = Call::System(system::Call::remark(vec![0; 64]));
+			let too_large_hash: H256 = too_large.blake2_256().into();
+			for (i, (h, p)) in vec![(hash, proposal.clone()), (too_large_hash, too_large)].into_iter().enumerate() {
+				<Proposals<Test, Instance1>>::mutate(|proposals| proposals.push(h));
+				<ProposalOf<Test, Instance1>>::insert(h, p);
+				let votes = Votes { index: i as u32, threshold: 2, ayes: vec![1], nays: vec![], end: 4 };
+				<Voting<Test, Instance1>>::insert(h, votes);
+			}
+
+			assert_eq!(<Collective as OnRuntimeUpgrade>::on_runtime_upgrade(), 220_000);
+			assert!(!<ProposalOf<Test, Instance1>>::exists(hash));
+			assert!(TestPreimages::preimage_requested(&hash));
+			assert_eq!(Collective::proposal_of(&hash), Some(proposal));
+
+			// the motion whose proposal could not be moved is dropped.
+			assert_eq!(Collective::proposals(), vec![hash]);
+			assert_eq!(Collective::voting(&too_large_hash), None);
+		});
+	}
 }
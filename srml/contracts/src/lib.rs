@@ -125,6 +125,7 @@ use support::{
 	Parameter, decl_module, decl_event, decl_storage, storage::child,
 	parameter_types, IsSubType
 };
+use support::metadata::{TypeInfo, Registry, TypeDef, TypeId, Field, Variant};
 use support::traits::{OnFreeBalanceZero, OnUnbalanced, Currency, Get, Time, Randomness};
 use system::{ensure_signed, RawOrigin, ensure_root};
 use primitives::storage::well_known_keys::CHILD_STORAGE_KEY_PREFIX;
@@ -202,12 +203,28 @@ impl<T: Trait> ContractInfo<T> {
 	}
 }
 
+impl<T: Trait> TypeInfo for ContractInfo<T> where
+	AliveContractInfo<T>: TypeInfo,
+	TombstoneContractInfo<T>: TypeInfo,
+{
+	fn path() -> Vec<&'static str> {
+		module_path!().split("::").chain(rstd::iter::once("ContractInfo")).collect()
+	}
+
+	fn type_def(registry: &mut Registry) -> TypeDef {
+		TypeDef::Variant(vec![
+			Variant::new("Alive", 0, vec![Field::unnamed(registry.register::<AliveContractInfo<T>>())]),
+			Variant::new("Tombstone", 1, vec![Field::unnamed(registry.register::<TombstoneContractInfo<T>>())]),
+		])
+	}
+}
+
 pub type AliveContractInfo<T> =
 	RawAliveContractInfo<CodeHash<T>, BalanceOf<T>, <T as system::Trait>::BlockNumber>;
 
 /// Information for managing an account and its sub trie abstraction.
 /// This is the required info to cache for an account.
-#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
 pub struct RawAliveContractInfo<CodeHash, Balance, BlockNumber> {
 	/// Unique ID for the subtree encoded as a bytes vector.
 	pub trie_id: TrieId,
@@ -244,6 +261,21 @@ where
 	}
 }
 
+// The hasher is only a marker, the tombstone is encoded as the hash alone.
+impl<H: TypeInfo, Hasher: 'static> TypeInfo for RawTombstoneContractInfo<H, Hasher> {
+	fn path() -> Vec<&'static str> {
+		module_path!().split("::").chain(rstd::iter::once("RawTombstoneContractInfo")).collect()
+	}
+
+	fn params(registry: &mut Registry) -> Vec<TypeId> {
+		vec![registry.register::<H>()]
+	}
+
+	fn type_def(registry: &mut Registry) -> TypeDef {
+		TypeDef::Composite(vec![Field::unnamed(registry.register::<H>())])
+	}
+}
+
 /// Get a trie id (trie id must be unique and collision resistant depending upon its context).
 /// Note that it is different than encode because trie id should be collision resistant
 /// (being a proper unique identifier).
@@ -922,7 +954,7 @@ impl<T: Trait> Config<T> {
 
 /// Definition of the cost schedule and other parameterizations for wasm vm.
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
-#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo)]
 pub struct Schedule {
 	/// Version of the schedule.
 	pub version: u32,
@@ -25,6 +25,7 @@ use crate::gas::GasMeter;
 use rstd::prelude::*;
 use codec::{Encode, Decode};
 use sandbox;
+use support::metadata::TypeInfo;
 
 #[macro_use]
 mod env_def;
@@ -38,7 +39,7 @@ use self::code_cache::load as load_code;
 pub use self::code_cache::save as save_code;
 
 /// A prepared wasm module ready for execution.
-#[derive(Clone, Encode, Decode)]
+#[derive(Clone, Encode, Decode, TypeInfo)]
 pub struct PrefabWasmModule {
 	/// Version of the schedule with which the code was instrumented.
 	#[codec(compact)]
@@ -15,168 +15,71 @@
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Democratic system: Handles administration of general stakeholder voting.
+//!
+//! Accounts vote on referenda with a given balance and conviction. The balance of every
+//! current vote of an account stays locked. Once a referendum is over, its vote can be
+//! removed with `remove_vote`; if the vote was on the winning side, its balance stays locked
+//! for the enactment periods implied by its conviction. `unlock` then releases whatever is no
+//! longer needed.
+//!
+//! Proposals are referred to by their hash only. Their preimages are kept by `T::Preimages`,
+//! from which democracy requests them for as long as they are proposed, voted on or queued for
+//! enactment.
+//!
+//! Approved proposals are enacted by `T::Scheduler`, which dispatches `enact_proposal` once the
+//! enactment delay of their referendum has passed.
 #![recursion_limit="128"]
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use rstd::prelude::*;
-use rstd::{result, convert::TryFrom};
+use rstd::result;
 use sr_primitives::{
 	RuntimeDebug,
-	traits::{Zero, Bounded, CheckedMul, CheckedDiv, EnsureOrigin, Hash, Dispatchable},
-	weights::SimpleDispatchInfo,
+	traits::{Zero, Bounded, EnsureOrigin, Dispatchable, Saturating},
+	weights::{SimpleDispatchInfo, Weight},
 };
-use codec::{Ref, Encode, Decode, Input, Output, Error};
+use codec::{Ref, Encode, Decode};
 use support::{
-	decl_module, decl_storage, decl_event, ensure,
-	Parameter,
+	decl_module, decl_storage, decl_event, ensure, storage,
+	Parameter, metadata::TypeInfo,
 	traits::{
 		Currency, ReservableCurrency, LockableCurrency, WithdrawReason, LockIdentifier, Get,
-		OnFreeBalanceZero
+		OnFreeBalanceZero, preimage::PreimageProvider, schedule,
 	}
 };
 use support::dispatch::Result;
 use system::{ensure_signed, ensure_root};
 
 mod vote_threshold;
+mod vote;
+mod conviction;
 pub use vote_threshold::{Approved, VoteThreshold};
+pub use vote::{Vote, AccountVote, Voting, PriorLock};
+pub use conviction::Conviction;
 
 const DEMOCRACY_ID: LockIdentifier = *b"democrac";
 
+/// The maximum number of votes an account may have recorded at once.
+const MAX_VOTES: usize = 100;
+
+/// The storage prefix of the votes recorded before `VotingOf`, keyed by referendum and voter.
+const LEGACY_VOTE_OF_PREFIX: &[u8] = b"Democracy VoteOf";
+
+/// The weight of migrating a single legacy vote.
+const VOTE_MIGRATION_WEIGHT: Weight = 20_000;
+
 /// A proposal index.
 pub type PropIndex = u32;
 
 /// A referendum index.
 pub type ReferendumIndex = u32;
 
-/// A value denoting the strength of conviction of a vote.
-#[derive(Encode, Decode, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, RuntimeDebug)]
-pub enum Conviction {
-	/// 0.1x votes, unlocked.
-	None,
-	/// 1x votes, locked for an enactment period following a successful vote.
-	Locked1x,
-	/// 2x votes, locked for 2x enactment periods following a successful vote.
-	Locked2x,
-	/// 3x votes, locked for 4x...
-	Locked3x,
-	/// 4x votes, locked for 8x...
-	Locked4x,
-	/// 5x votes, locked for 16x...
-	Locked5x,
-	/// 6x votes, locked for 32x...
-	Locked6x,
-}
-
-impl Default for Conviction {
-	fn default() -> Self {
-		Conviction::None
-	}
-}
-
-impl From<Conviction> for u8 {
-	fn from(c: Conviction) -> u8 {
-		match c {
-			Conviction::None => 0,
-			Conviction::Locked1x => 1,
-			Conviction::Locked2x => 2,
-			Conviction::Locked3x => 3,
-			Conviction::Locked4x => 4,
-			Conviction::Locked5x => 5,
-			Conviction::Locked6x => 6,
-		}
-	}
-}
-
-impl TryFrom<u8> for Conviction {
-	type Error = ();
-	fn try_from(i: u8) -> result::Result<Conviction, ()> {
-		Ok(match i {
-			0 => Conviction::None,
-			1 => Conviction::Locked1x,
-			2 => Conviction::Locked2x,
-			3 => Conviction::Locked3x,
-			4 => Conviction::Locked4x,
-			5 => Conviction::Locked5x,
-			6 => Conviction::Locked6x,
-			_ => return Err(()),
-		})
-	}
-}
-
-impl Conviction {
-	/// The amount of time (in number of periods) that our conviction implies a successful voter's
-	/// balance should be locked for.
-	fn lock_periods(self) -> u32 {
-		match self {
-			Conviction::None => 0,
-			Conviction::Locked1x => 1,
-			Conviction::Locked2x => 2,
-			Conviction::Locked3x => 4,
-			Conviction::Locked4x => 8,
-			Conviction::Locked5x => 16,
-			Conviction::Locked6x => 32,
-		}
-	}
-
-	/// The votes of a voter of the given `balance` with our conviction.
-	fn votes<
-		B: From<u8> + Zero + Copy + CheckedMul + CheckedDiv + Bounded
-	>(self, balance: B) -> (B, B) {
-		match self {
-			Conviction::None => {
-				let r = balance.checked_div(&10u8.into()).unwrap_or_else(Zero::zero);
-				(r, r)
-			}
-			x => (
-				balance.checked_mul(&u8::from(x).into()).unwrap_or_else(B::max_value),
-				balance,
-			)
-		}
-	}
-}
-
-impl Bounded for Conviction {
-	fn min_value() -> Self {
-		Conviction::None
-	}
-
-	fn max_value() -> Self {
-		Conviction::Locked6x
-	}
-}
-
 const MAX_RECURSION_LIMIT: u32 = 16;
 
-/// A number of lock periods, plus a vote, one way or the other.
-#[derive(Copy, Clone, Eq, PartialEq, Default, RuntimeDebug)]
-pub struct Vote {
-	pub aye: bool,
-	pub conviction: Conviction,
-}
-
-impl Encode for Vote {
-	fn encode_to<T: Output>(&self, output: &mut T) {
-		output.push_byte(u8::from(self.conviction) | if self.aye { 0b1000_0000 } else { 0 });
-	}
-}
-
-impl codec::EncodeLike for Vote {}
-
-impl Decode for Vote {
-	fn decode<I: Input>(input: &mut I) -> core::result::Result<Self, Error> {
-		let b = input.read_byte()?;
-		Ok(Vote {
-			aye: (b & 0b1000_0000) == 0b1000_0000,
-			conviction: Conviction::try_from(b & 0b0111_1111)
-				.map_err(|_| Error::from("Invalid conviction"))?,
-		})
-	}
-}
-
 type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
 
 pub trait Trait: system::Trait + Sized {
-	type Proposal: Parameter + Dispatchable<Origin=Self::Origin>;
+	type Proposal: Parameter + Dispatchable<Origin=Self::Origin> + From<Call<Self>>;
 	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
 
 	/// Currency type for this module.
@@ -227,30 +130,36 @@ pub trait Trait: system::Trait + Sized {
 
 	/// Period in blocks where an external proposal may not be re-submitted after being vetoed.
 	type CooloffPeriod: Get<Self::BlockNumber>;
+
+	/// The provider of the preimages of proposals.
+	type Preimages: PreimageProvider<Self::Hash>;
+
+	/// The scheduler with which approved proposals are enacted.
+	type Scheduler: schedule::Named<Self::BlockNumber, Self::Proposal>;
 }
 
 /// Info regarding an ongoing referendum.
-#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
-pub struct ReferendumInfo<BlockNumber: Parameter, Proposal: Parameter> {
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct ReferendumInfo<BlockNumber: Parameter, Hash: Parameter> {
 	/// When voting on this referendum will end.
 	end: BlockNumber,
-	/// The proposal being voted on.
-	proposal: Proposal,
+	/// The hash of the proposal being voted on.
+	proposal_hash: Hash,
 	/// The thresholding mechanism to determine whether it passed.
 	threshold: VoteThreshold,
 	/// The delay (in blocks) to wait after a successful referendum before deploying.
 	delay: BlockNumber,
 }
 
-impl<BlockNumber: Parameter, Proposal: Parameter> ReferendumInfo<BlockNumber, Proposal> {
+impl<BlockNumber: Parameter, Hash: Parameter> ReferendumInfo<BlockNumber, Hash> {
 	/// Create a new instance.
 	pub fn new(
 		end: BlockNumber,
-		proposal: Proposal,
+		proposal_hash: Hash,
 		threshold: VoteThreshold,
 		delay: BlockNumber
 	) -> Self {
-		ReferendumInfo { end, proposal, threshold, delay }
+		ReferendumInfo { end, proposal_hash, threshold, delay }
 	}
 }
 
@@ -259,7 +168,7 @@ decl_storage! {
 		/// The number of (public) proposals that have been made so far.
 		pub PublicPropCount get(fn public_prop_count) build(|_| 0 as PropIndex) : PropIndex;
 		/// The public proposals. Unsorted.
-		pub PublicProps get(fn public_props): Vec<(PropIndex, T::Proposal, T::AccountId)>;
+		pub PublicProps get(fn public_props): Vec<(PropIndex, T::Hash, T::AccountId)>;
 		/// Those who have locked a deposit.
 		pub DepositOf get(fn deposit_of): map PropIndex => Option<(BalanceOf<T>, Vec<T::AccountId>)>;
 
@@ -269,23 +178,27 @@ decl_storage! {
 		pub NextTally get(fn next_tally) build(|_| 0 as ReferendumIndex): ReferendumIndex;
 		/// Information concerning any given referendum.
 		pub ReferendumInfoOf get(fn referendum_info):
-			map ReferendumIndex => Option<(ReferendumInfo<T::BlockNumber, T::Proposal>)>;
-		/// Queue of successful referenda to be dispatched.
+			map ReferendumIndex => Option<(ReferendumInfo<T::BlockNumber, T::Hash>)>;
+		/// Queue of successful referenda to be dispatched, from before they were scheduled with
+		/// `T::Scheduler`. Only drained.
 		pub DispatchQueue get(fn dispatch_queue):
-			map T::BlockNumber => Vec<Option<(T::Proposal, ReferendumIndex)>>;
+			map T::BlockNumber => Vec<Option<(T::Hash, ReferendumIndex)>>;
+		/// The proposals scheduled for enactment, by the index of the referendum which approved
+		/// them.
+		pub QueuedProposal get(fn queued_proposal): map ReferendumIndex => Option<T::Hash>;
 
 		/// Get the voters for the current proposal.
 		pub VotersFor get(fn voters_for): map ReferendumIndex => Vec<T::AccountId>;
 
-		/// Get the vote in a given referendum of a particular voter. The result is meaningful only
-		/// if `voters_for` includes the voter when called with the referendum (you'll get the
-		/// default `Vote` value otherwise). If you don't want to check `voters_for`, then you can
-		/// also check for simple existence with `VoteOf::exists` first.
-		pub VoteOf get(fn vote_of): map (ReferendumIndex, T::AccountId) => Vote;
+		/// All votes of a particular voter, along with the lock left over by its past votes.
+		pub VotingOf get(fn voting_of): map T::AccountId => Voting<BalanceOf<T>, T::BlockNumber>;
 
-		/// Who is able to vote for whom. Value is the fund-holding account, key is the
-		/// vote-transaction-sending account.
-		pub Proxy get(fn proxy): map T::AccountId => Option<T::AccountId>;
+		/// Whether a referendum that is over was approved, and the block at which it ended.
+		/// Cancelled referenda have no entry. Pruned once no vote on it can imply a lock anymore.
+		pub ReferendumResultOf get(fn referendum_result):
+			map ReferendumIndex => Option<(bool, T::BlockNumber)>;
+		/// The lowest referendum index whose result may not have been pruned yet.
+		pub NextResultToPrune get(fn next_result_to_prune): ReferendumIndex;
 
 		/// Get the account (and lock periods) to which another account is delegating vote.
 		pub Delegations get(fn delegations): linked_map T::AccountId => (T::AccountId, Conviction);
@@ -298,7 +211,7 @@ decl_storage! {
 		/// This happens when a referendum needs to be tabled and one of two conditions are met:
 		/// - `LastTabledWasExternal` is `false`; or
 		/// - `PublicProps` is empty.
-		pub NextExternal: Option<(T::Proposal, VoteThreshold)>;
+		pub NextExternal: Option<(T::Hash, VoteThreshold)>;
 
 		/// A record of who vetoed what. Maps proposal hash to a possible existent block number
 		/// (until when it may not be resubmitted) and who vetoed it.
@@ -327,6 +240,10 @@ decl_event!(
 		Delegated(AccountId, AccountId),
 		Undelegated(AccountId),
 		Vetoed(AccountId, Hash, BlockNumber),
+		/// A proposal could not be enacted because its preimage is missing.
+		PreimageMissing(Hash, ReferendumIndex),
+		/// A proposal could not be enacted because its preimage is not a valid proposal.
+		PreimageInvalid(Hash, ReferendumIndex),
 	}
 );
 
@@ -356,7 +273,8 @@ decl_module! {
 
 		fn deposit_event() = default;
 
-		/// Propose a sensitive action to be taken.
+		/// Propose a sensitive action to be taken, given the hash of the proposal. Its preimage
+		/// must be noted with `T::Preimages` by the time it is enacted.
 		///
 		/// # <weight>
 		/// - O(1).
@@ -364,7 +282,7 @@ decl_module! {
 		/// # </weight>
 		#[weight = SimpleDispatchInfo::FixedNormal(5_000_000)]
 		fn propose(origin,
-			proposal: Box<T::Proposal>,
+			proposal_hash: T::Hash,
 			#[compact] value: BalanceOf<T>
 		) {
 			let who = ensure_signed(origin)?;
@@ -377,7 +295,8 @@ decl_module! {
 			PublicPropCount::put(index + 1);
 			<DepositOf<T>>::insert(index, (value, &[&who][..]));
 
-			let new_prop = (index, proposal, who);
+			T::Preimages::request_preimage(&proposal_hash);
+			let new_prop = (index, proposal_hash, who);
 			<PublicProps<T>>::append_or_put(&[Ref::from(&new_prop)][..]);
 
 			Self::deposit_event(RawEvent::Proposed(index, value));
@@ -400,36 +319,56 @@ decl_module! {
 			<DepositOf<T>>::insert(proposal, deposit);
 		}
 
-		/// Vote in a referendum. If `vote.is_aye()`, the vote is to enact the proposal;
-		/// otherwise it is a vote to keep the status quo.
+		/// Vote in a referendum, or replace a previous vote in it. A standard vote is either to
+		/// enact the proposal or to keep the status quo, with some conviction; a split vote
+		/// puts some balance on each side, without conviction.
+		///
+		/// The balance of the vote is locked until the vote is removed with `remove_vote` and
+		/// the account is unlocked with `unlock`.
 		///
 		/// # <weight>
-		/// - O(1).
-		/// - One DB change, one DB entry.
+		/// - O(V) where V is the number of votes of the voter, capped by `MAX_VOTES`.
+		/// - Two DB changes, one DB entry.
 		/// # </weight>
 		#[weight = SimpleDispatchInfo::FixedNormal(200_000)]
 		fn vote(origin,
 			#[compact] ref_index: ReferendumIndex,
-			vote: Vote
+			vote: AccountVote<BalanceOf<T>>
 		) -> Result {
 			let who = ensure_signed(origin)?;
-			Self::do_vote(who, ref_index, vote)
+			Self::try_vote(&who, ref_index, vote)
 		}
 
-		/// Vote in a referendum on behalf of a stash. If `vote.is_aye()`, the vote is to enact
-		/// the proposal;  otherwise it is a vote to keep the status quo.
+		/// Remove the vote of the origin in a referendum.
+		///
+		/// If the referendum is ongoing, the vote no longer counts. If it is over and the vote
+		/// was on the winning side, its balance stays locked until the end of the lock periods
+		/// of its conviction, counted from the end of the referendum.
+		///
+		/// The lock itself is only lowered by a subsequent call to `unlock`.
 		///
 		/// # <weight>
-		/// - O(1).
-		/// - One DB change, one DB entry.
+		/// - O(V) where V is the number of votes of the voter, capped by `MAX_VOTES`, plus the
+		///   number of voters of the referendum if it is ongoing.
+		/// - One DB change.
 		/// # </weight>
 		#[weight = SimpleDispatchInfo::FixedNormal(200_000)]
-		fn proxy_vote(origin,
-			#[compact] ref_index: ReferendumIndex,
-			vote: Vote
-		) -> Result {
-			let who = Self::proxy(ensure_signed(origin)?).ok_or("not a proxy")?;
-			Self::do_vote(who, ref_index, vote)
+		fn remove_vote(origin, #[compact] ref_index: ReferendumIndex) -> Result {
+			let who = ensure_signed(origin)?;
+			Self::try_remove_vote(&who, ref_index)
+		}
+
+		/// Update the democracy lock of `target` to what its votes and past votes still
+		/// require, removing it entirely if nothing is left.
+		///
+		/// # <weight>
+		/// - O(V) where V is the number of votes of `target`, capped by `MAX_VOTES`.
+		/// - One DB change.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		fn unlock(origin, target: T::AccountId) {
+			ensure_signed(origin)?;
+			Self::update_lock(&target);
 		}
 
 		/// Schedule an emergency cancellation of a referendum. Cannot happen twice to the same
@@ -439,24 +378,24 @@ decl_module! {
 			T::CancellationOrigin::ensure_origin(origin)?;
 
 			let info = Self::referendum_info(ref_index).ok_or("unknown index")?;
-			let h = T::Hashing::hash_of(&info.proposal);
+			let h = info.proposal_hash;
 			ensure!(!<Cancellations<T>>::exists(h), "cannot cancel the same proposal twice");
 
 			<Cancellations<T>>::insert(h, true);
 			Self::clear_referendum(ref_index);
+			T::Preimages::unrequest_preimage(&h);
 		}
 
 		/// Schedule a referendum to be tabled once it is legal to schedule an external
 		/// referendum.
 		#[weight = SimpleDispatchInfo::FixedNormal(5_000_000)]
-		fn external_propose(origin, proposal: Box<T::Proposal>) {
+		fn external_propose(origin, proposal_hash: T::Hash) {
 			T::ExternalOrigin::ensure_origin(origin)?;
 			ensure!(!<NextExternal<T>>::exists(), "proposal already made");
-			let proposal_hash = T::Hashing::hash_of(&proposal);
 			if let Some((until, _)) = <Blacklist<T>>::get(proposal_hash) {
 				ensure!(<system::Module<T>>::block_number() >= until, "proposal still blacklisted");
 			}
-			<NextExternal<T>>::put((*proposal, VoteThreshold::SuperMajorityApprove));
+			Self::put_next_external(proposal_hash, VoteThreshold::SuperMajorityApprove);
 		}
 
 		/// Schedule a majority-carries referendum to be tabled next once it is legal to schedule
@@ -465,9 +404,9 @@ decl_module! {
 		/// Unlike `external_propose`, blacklisting has no effect on this and it may replace a
 		/// pre-scheduled `external_propose` call.
 		#[weight = SimpleDispatchInfo::FixedNormal(5_000_000)]
-		fn external_propose_majority(origin, proposal: Box<T::Proposal>) {
+		fn external_propose_majority(origin, proposal_hash: T::Hash) {
 			T::ExternalMajorityOrigin::ensure_origin(origin)?;
-			<NextExternal<T>>::put((*proposal, VoteThreshold::SimpleMajority));
+			Self::put_next_external(proposal_hash, VoteThreshold::SimpleMajority);
 		}
 
 		/// Schedule a negative-turnout-bias referendum to be tabled next once it is legal to
@@ -476,9 +415,9 @@ decl_module! {
 		/// Unlike `external_propose`, blacklisting has no effect on this and it may replace a
 		/// pre-scheduled `external_propose` call.
 		#[weight = SimpleDispatchInfo::FixedNormal(5_000_000)]
-		fn external_propose_default(origin, proposal: Box<T::Proposal>) {
+		fn external_propose_default(origin, proposal_hash: T::Hash) {
 			T::ExternalDefaultOrigin::ensure_origin(origin)?;
-			<NextExternal<T>>::put((*proposal, VoteThreshold::SuperMajorityAgainst));
+			Self::put_next_external(proposal_hash, VoteThreshold::SuperMajorityAgainst);
 		}
 
 		/// Schedule the currently externally-proposed majority-carries referendum to be tabled
@@ -497,15 +436,15 @@ decl_module! {
 			delay: T::BlockNumber
 		) {
 			T::FastTrackOrigin::ensure_origin(origin)?;
-			let (proposal, threshold) = <NextExternal<T>>::get().ok_or("no proposal made")?;
+			let (e_proposal_hash, threshold) = <NextExternal<T>>::get().ok_or("no proposal made")?;
 			ensure!(threshold != VoteThreshold::SuperMajorityApprove, "next external proposal not simple majority");
-			ensure!(proposal_hash == T::Hashing::hash_of(&proposal), "invalid hash");
+			ensure!(proposal_hash == e_proposal_hash, "invalid hash");
 
 			<NextExternal<T>>::kill();
 			let now = <system::Module<T>>::block_number();
 			// We don't consider it an error if `vote_period` is too low, like `emergency_propose`.
 			let period = voting_period.max(T::EmergencyVotingPeriod::get());
-			Self::inject_referendum(now + period, proposal, threshold, delay).map(|_| ())?;
+			Self::inject_referendum(now + period, proposal_hash, threshold, delay).map(|_| ())?;
 		}
 
 		/// Veto and blacklist the external proposal hash.
@@ -513,8 +452,8 @@ decl_module! {
 		fn veto_external(origin, proposal_hash: T::Hash) {
 			let who = T::VetoOrigin::ensure_origin(origin)?;
 
-			if let Some((proposal, _)) = <NextExternal<T>>::get() {
-				ensure!(proposal_hash == T::Hashing::hash_of(&proposal), "unknown proposal");
+			if let Some((e_proposal_hash, _)) = <NextExternal<T>>::get() {
+				ensure!(proposal_hash == e_proposal_hash, "unknown proposal");
 			} else {
 				Err("no external proposal")?;
 			}
@@ -531,73 +470,44 @@ decl_module! {
 
 			Self::deposit_event(RawEvent::Vetoed(who, proposal_hash, until));
 			<NextExternal<T>>::kill();
+			T::Preimages::unrequest_preimage(&proposal_hash);
 		}
 
 		/// Remove a referendum.
 		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
 		fn cancel_referendum(origin, #[compact] ref_index: ReferendumIndex) {
 			ensure_root(origin)?;
-			Self::clear_referendum(ref_index);
+			Self::internal_cancel_referendum(ref_index);
 		}
 
-		/// Cancel a proposal queued for enactment.
+		/// Cancel the enactment of the proposal approved by referendum `which`.
 		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
-		fn cancel_queued(
-			origin,
-			#[compact] when: T::BlockNumber,
-			#[compact] which: u32,
-			#[compact] what: ReferendumIndex
-		) {
+		fn cancel_queued(origin, #[compact] which: ReferendumIndex) {
 			ensure_root(origin)?;
-			let which = which as usize;
-			let mut items = <DispatchQueue<T>>::get(when);
-			if items.get(which).and_then(Option::as_ref).map_or(false, |x| x.1 == what) {
-				items[which] = None;
-				<DispatchQueue<T>>::insert(when, items);
-			} else {
-				Err("proposal not found")?
-			}
-		}
-
-		fn on_initialize(n: T::BlockNumber) {
-			if let Err(e) = Self::end_block(n) {
-				sr_primitives::print(e);
-			}
+			let proposal_hash = Self::queued_proposal(which).ok_or("proposal not found")?;
+			T::Scheduler::cancel_named(Self::enactment_id(which)).map_err(|_| "proposal not found")?;
+			<QueuedProposal<T>>::remove(which);
+			T::Preimages::unrequest_preimage(&proposal_hash);
 		}
 
-		/// Specify a proxy. Called by the stash.
+		/// Enact the proposal approved by referendum `index`. Scheduled with `T::Scheduler` once
+		/// the referendum has passed.
 		///
-		/// # <weight>
-		/// - One extra DB entry.
-		/// # </weight>
-		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
-		fn set_proxy(origin, proxy: T::AccountId) {
-			let who = ensure_signed(origin)?;
-			ensure!(!<Proxy<T>>::exists(&proxy), "already a proxy");
-			<Proxy<T>>::insert(proxy, who)
+		/// The dispatch origin of this call must be _Root_.
+		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+		fn enact_proposal(origin, proposal_hash: T::Hash, #[compact] index: ReferendumIndex) {
+			ensure_root(origin)?;
+			Self::do_enact_proposal(proposal_hash, index);
 		}
 
-		/// Clear the proxy. Called by the proxy.
-		///
-		/// # <weight>
-		/// - One DB clear.
-		/// # </weight>
-		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
-		fn resign_proxy(origin) {
-			let who = ensure_signed(origin)?;
-			<Proxy<T>>::remove(who);
+		fn on_runtime_upgrade() -> Weight {
+			Self::migrate_legacy_votes()
 		}
 
-		/// Clear the proxy. Called by the stash.
-		///
-		/// # <weight>
-		/// - One DB clear.
-		/// # </weight>
-		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
-		fn remove_proxy(origin, proxy: T::AccountId) {
-			let who = ensure_signed(origin)?;
-			ensure!(&Self::proxy(&proxy).ok_or("not a proxy")? == &who, "wrong proxy");
-			<Proxy<T>>::remove(proxy);
+		fn on_initialize(n: T::BlockNumber) {
+			if let Err(e) = Self::end_block(n) {
+				sr_primitives::print(e);
+			}
 		}
 
 		/// Delegate vote.
@@ -633,13 +543,10 @@ decl_module! {
 			// Indefinite lock is reduced to the maximum voting lock that could be possible.
 			let now = <system::Module<T>>::block_number();
 			let locked_until = now + T::EnactmentPeriod::get() * conviction.lock_periods().into();
-			T::Currency::set_lock(
-				DEMOCRACY_ID,
-				&who,
-				Bounded::max_value(),
-				locked_until,
-				WithdrawReason::Transfer.into()
-			);
+			<VotingOf<T>>::mutate(&who, |voting| {
+				voting.prior.accumulate(locked_until, Bounded::max_value())
+			});
+			Self::update_lock(&who);
 			Self::deposit_event(RawEvent::Undelegated(who));
 		}
 	}
@@ -661,7 +568,7 @@ impl<T: Trait> Module<T> {
 
 	/// Get all referenda currently active.
 	pub fn active_referenda()
-		-> Vec<(ReferendumIndex, ReferendumInfo<T::BlockNumber, T::Proposal>)>
+		-> Vec<(ReferendumIndex, ReferendumInfo<T::BlockNumber, T::Hash>)>
 	{
 		let next = Self::next_tally();
 		let last = Self::referendum_count();
@@ -673,7 +580,7 @@ impl<T: Trait> Module<T> {
 	/// Get all referenda ready for tally at block `n`.
 	pub fn maturing_referenda_at(
 		n: T::BlockNumber
-	) -> Vec<(ReferendumIndex, ReferendumInfo<T::BlockNumber, T::Proposal>)> {
+	) -> Vec<(ReferendumIndex, ReferendumInfo<T::BlockNumber, T::Hash>)> {
 		let next = Self::next_tally();
 		let last = Self::referendum_count();
 		(next..last).into_iter()
@@ -682,20 +589,35 @@ impl<T: Trait> Module<T> {
 			.collect()
 	}
 
+	/// Get the vote of `who` in a referendum, if any.
+	pub fn vote_of(
+		ref_index: ReferendumIndex,
+		who: &T::AccountId,
+	) -> Option<AccountVote<BalanceOf<T>>> {
+		let votes = Self::voting_of(who).votes;
+		votes.binary_search_by_key(&ref_index, |&(i, _)| i).ok().map(|i| votes[i].1)
+	}
+
 	/// Get the voters for the current proposal.
 	pub fn tally(ref_index: ReferendumIndex) -> (BalanceOf<T>, BalanceOf<T>, BalanceOf<T>) {
 		let (approve, against, capital):
 			(BalanceOf<T>, BalanceOf<T>, BalanceOf<T>) = Self::voters_for(ref_index)
 				.iter()
-				.map(|voter| (
-					T::Currency::total_balance(voter), Self::vote_of((ref_index, voter.clone()))
-				))
-				.map(|(balance, Vote { aye, conviction })| {
-					let (votes, turnout) = conviction.votes(balance);
-					if aye {
-						(votes, Zero::zero(), turnout)
-					} else {
-						(Zero::zero(), votes, turnout)
+				.filter_map(|voter| Self::vote_of(ref_index, voter))
+				.map(|vote| match vote {
+					AccountVote::Standard { vote: Vote { aye, conviction }, balance } => {
+						let (votes, turnout) = conviction.votes(balance);
+						if aye {
+							(votes, Zero::zero(), turnout)
+						} else {
+							(Zero::zero(), votes, turnout)
+						}
+					}
+					AccountVote::Split { aye, nay } => {
+						// split votes carry no conviction.
+						let (aye_votes, aye_turnout) = Conviction::None.votes(aye);
+						let (nay_votes, nay_turnout) = Conviction::None.votes(nay);
+						(aye_votes, nay_votes, aye_turnout + nay_turnout)
 					}
 				}).fold(
 					(Zero::zero(), Zero::zero(), Zero::zero()),
@@ -712,7 +634,12 @@ impl<T: Trait> Module<T> {
 		Self::voters_for(ref_index).iter().fold(
 			(Zero::zero(), Zero::zero(), Zero::zero()),
 			|(approve_acc, against_acc, turnout_acc), voter| {
-				let Vote { aye, conviction } = Self::vote_of((ref_index, voter.clone()));
+				// only standard votes carry the votes of their delegators.
+				let (aye, conviction) = match Self::vote_of(ref_index, voter) {
+					Some(AccountVote::Standard { vote: Vote { aye, conviction }, .. }) =>
+						(aye, conviction),
+					_ => return (approve_acc, against_acc, turnout_acc),
+				};
 				let (votes, turnout) = Self::delegated_votes(
 					ref_index,
 					voter.clone(),
@@ -737,7 +664,7 @@ impl<T: Trait> Module<T> {
 		if recursion_limit == 0 { return (Zero::zero(), Zero::zero()); }
 		<Delegations<T>>::enumerate()
 			.filter(|(delegator, (delegate, _))|
-				*delegate == to && !<VoteOf<T>>::exists(&(ref_index, delegator.clone()))
+				*delegate == to && Self::vote_of(ref_index, delegator).is_none()
 			).fold(
 				(Zero::zero(), Zero::zero()),
 				|(votes_acc, turnout_acc), (delegator, (_delegate, max_conviction))| {
@@ -757,27 +684,28 @@ impl<T: Trait> Module<T> {
 
 	// Exposed mutables.
 
-	#[cfg(feature = "std")]
-	pub fn force_proxy(stash: T::AccountId, proxy: T::AccountId) {
-		<Proxy<T>>::insert(proxy, stash)
-	}
-
-	/// Start a referendum.
+	/// Start a referendum. The preimage of the proposal is requested until the referendum is
+	/// over.
 	pub fn internal_start_referendum(
-		proposal: T::Proposal,
+		proposal_hash: T::Hash,
 		threshold: VoteThreshold,
 		delay: T::BlockNumber
 	) -> result::Result<ReferendumIndex, &'static str> {
-		<Module<T>>::inject_referendum(
+		let ref_index = <Module<T>>::inject_referendum(
 			<system::Module<T>>::block_number() + T::VotingPeriod::get(),
-			proposal,
+			proposal_hash,
 			threshold,
 			delay
-		)
+		)?;
+		T::Preimages::request_preimage(&proposal_hash);
+		Ok(ref_index)
 	}
 
 	/// Remove a referendum.
 	pub fn internal_cancel_referendum(ref_index: ReferendumIndex) {
+		if let Some(info) = Self::referendum_info(ref_index) {
+			T::Preimages::unrequest_preimage(&info.proposal_hash);
+		}
 		Self::deposit_event(RawEvent::Cancelled(ref_index));
 		<Module<T>>::clear_referendum(ref_index);
 	}
@@ -785,19 +713,123 @@ impl<T: Trait> Module<T> {
 	// private.
 
 	/// Actually enact a vote, if legit.
-	fn do_vote(who: T::AccountId, ref_index: ReferendumIndex, vote: Vote) -> Result {
+	fn try_vote(
+		who: &T::AccountId,
+		ref_index: ReferendumIndex,
+		vote: AccountVote<BalanceOf<T>>,
+	) -> Result {
 		ensure!(Self::is_active_referendum(ref_index), "vote given for invalid referendum.");
-		if !<VoteOf<T>>::exists((ref_index, &who)) {
-			<VotersFor<T>>::append_or_insert(ref_index, &[&who][..]);
+		ensure!(vote.balance() <= T::Currency::free_balance(who), "insufficient funds for vote");
+		let mut voting = Self::voting_of(who);
+		match voting.votes.binary_search_by_key(&ref_index, |&(i, _)| i) {
+			Ok(i) => voting.votes[i].1 = vote,
+			Err(i) => {
+				ensure!(voting.votes.len() < MAX_VOTES, "too many votes");
+				voting.votes.insert(i, (ref_index, vote));
+				<VotersFor<T>>::append_or_insert(ref_index, &[who][..]);
+			}
 		}
-		<VoteOf<T>>::insert((ref_index, &who), vote);
+		<VotingOf<T>>::insert(who, voting);
+		Self::update_lock(who);
 		Ok(())
 	}
 
+	/// Remove the vote of `who` in a referendum, keeping track of the lock it still implies if
+	/// the referendum is over.
+	fn try_remove_vote(who: &T::AccountId, ref_index: ReferendumIndex) -> Result {
+		let mut voting = Self::voting_of(who);
+		let i = voting.votes.binary_search_by_key(&ref_index, |&(i, _)| i)
+			.map_err(|_| "vote not found")?;
+		let (_, vote) = voting.votes.remove(i);
+		if Self::is_active_referendum(ref_index) {
+			<VotersFor<T>>::mutate(ref_index, |voters| voters.retain(|v| v != who));
+		} else if let Some((approved, end)) = Self::referendum_result(ref_index) {
+			if let Some((lock_periods, balance)) = vote.locked_if(approved) {
+				let unlock_at = end.saturating_add(T::EnactmentPeriod::get() * lock_periods.into());
+				voting.prior.accumulate(unlock_at, balance);
+			}
+		}
+		<VotingOf<T>>::insert(who, voting);
+		Ok(())
+	}
+
+	/// Set the democracy lock of `who` to what its votes and past votes still require.
+	fn update_lock(who: &T::AccountId) {
+		// a delegating account is locked indefinitely anyway.
+		if <Delegations<T>>::exists(who) {
+			return
+		}
+
+		let mut voting = Self::voting_of(who);
+		voting.prior.rejig(<system::Module<T>>::block_number());
+		let lock_needed = voting.locked_balance();
+		if voting.is_empty() {
+			<VotingOf<T>>::remove(who);
+		} else {
+			<VotingOf<T>>::insert(who, voting);
+		}
+
+		if lock_needed.is_zero() {
+			T::Currency::remove_lock(DEMOCRACY_ID, who);
+		} else {
+			T::Currency::set_lock(
+				DEMOCRACY_ID,
+				who,
+				lock_needed,
+				T::BlockNumber::max_value(),
+				WithdrawReason::Transfer.into()
+			);
+		}
+	}
+
+	/// Move the votes on ongoing referenda out of the legacy `VoteOf` storage into `VotingOf`.
+	///
+	/// Those votes counted the whole balance of the voter, so they are migrated as standard votes
+	/// with the free balance of the voter, which is locked from now on.
+	fn migrate_legacy_votes() -> Weight {
+		let mut migrated: Weight = 0;
+		for index in Self::next_tally()..Self::referendum_count() {
+			for voter in Self::voters_for(index) {
+				let mut key = LEGACY_VOTE_OF_PREFIX.to_vec();
+				(index, &voter).encode_to(&mut key);
+				let vote: Option<Vote> = storage::hashed::take(&runtime_io::blake2_256, &key);
+				if let Some(vote) = vote {
+					let balance = T::Currency::free_balance(&voter);
+					<VotingOf<T>>::mutate(&voter, |voting| {
+						if let Err(i) = voting.votes.binary_search_by_key(&index, |&(i, _)| i) {
+							voting.votes.insert(i, (index, AccountVote::Standard { vote, balance }));
+						}
+					});
+					Self::update_lock(&voter);
+					migrated += 1;
+				}
+			}
+		}
+		migrated.saturating_mul(VOTE_MIGRATION_WEIGHT)
+	}
+
+	/// Remove the results of referenda which ended long enough ago that no vote on them can
+	/// imply a lock anymore. Removing such a vote later on leaves no prior lock, as for a
+	/// cancelled referendum.
+	fn prune_results(now: T::BlockNumber) {
+		let max_lock = T::EnactmentPeriod::get() * Conviction::max_value().lock_periods().into();
+		let next_tally = Self::next_tally();
+		let mut next = Self::next_result_to_prune();
+		while next < next_tally {
+			// referenda end in index order, so neither do the later ones expire before this one.
+			match Self::referendum_result(next) {
+				Some((_, end)) if end.saturating_add(max_lock) > now => break,
+				_ => <ReferendumResultOf<T>>::remove(next),
+			}
+			next += 1;
+		}
+		NextResultToPrune::put(next);
+	}
+
 	/// Start a referendum
 	fn inject_referendum(
 		end: T::BlockNumber,
-		proposal: T::Proposal,
+		proposal_hash: T::Hash,
 		threshold: VoteThreshold,
 		delay: T::BlockNumber,
 	) -> result::Result<ReferendumIndex, &'static str> {
@@ -811,25 +843,48 @@ impl<T: Trait> Module<T> {
 		}
 
 		ReferendumCount::put(ref_index + 1);
-		let item = ReferendumInfo { end, proposal, threshold, delay };
+		let item = ReferendumInfo { end, proposal_hash, threshold, delay };
 		<ReferendumInfoOf<T>>::insert(ref_index, item);
 		Self::deposit_event(RawEvent::Started(ref_index, threshold));
 		Ok(ref_index)
 	}
 
-	/// Remove all info on a referendum.
+	/// Remove all info on a referendum. The votes themselves stay recorded with their voters
+	/// until they are removed.
 	fn clear_referendum(ref_index: ReferendumIndex) {
 		<ReferendumInfoOf<T>>::remove(ref_index);
 		<VotersFor<T>>::remove(ref_index);
-		for v in Self::voters_for(ref_index) {
-			<VoteOf<T>>::remove((ref_index, v));
+	}
+
+	/// Replace the next external proposal, if any, and request the preimage of the new one.
+	fn put_next_external(proposal_hash: T::Hash, threshold: VoteThreshold) {
+		if let Some((previous_hash, _)) = <NextExternal<T>>::get() {
+			T::Preimages::unrequest_preimage(&previous_hash);
 		}
+		T::Preimages::request_preimage(&proposal_hash);
+		<NextExternal<T>>::put((proposal_hash, threshold));
 	}
 
-	/// Enact a proposal from a referendum.
-	fn enact_proposal(proposal: T::Proposal, index: ReferendumIndex) {
-		let ok = proposal.dispatch(system::RawOrigin::Root.into()).is_ok();
-		Self::deposit_event(RawEvent::Executed(index, ok));
+	/// The name under which the enactment of the proposal approved by referendum `index` is
+	/// scheduled.
+	fn enactment_id(index: ReferendumIndex) -> Vec<u8> {
+		(DEMOCRACY_ID, index).encode()
+	}
+
+	/// Enact a proposal from a referendum, fetching it from its preimage.
+	fn do_enact_proposal(proposal_hash: T::Hash, index: ReferendumIndex) {
+		<QueuedProposal<T>>::remove(index);
+		match T::Preimages::get_preimage(&proposal_hash) {
+			Some(bytes) => match T::Proposal::decode(&mut &bytes[..]) {
+				Ok(proposal) => {
+					let ok = proposal.dispatch(system::RawOrigin::Root.into()).is_ok();
+					Self::deposit_event(RawEvent::Executed(index, ok));
+				}
+				Err(_) => Self::deposit_event(RawEvent::PreimageInvalid(proposal_hash, index)),
+			},
+			None => Self::deposit_event(RawEvent::PreimageMissing(proposal_hash, index)),
+		}
+		T::Preimages::unrequest_preimage(&proposal_hash);
 	}
 
 	/// Table the next waiting proposal for a vote.
@@ -843,12 +898,12 @@ impl<T: Trait> Module<T> {
 
 	/// Table the waiting external proposal for a vote, if there is one.
 	fn launch_external(now: T::BlockNumber) -> Result {
-		if let Some((proposal, threshold)) = <NextExternal<T>>::take() {
+		if let Some((proposal_hash, threshold)) = <NextExternal<T>>::take() {
 			LastTabledWasExternal::put(true);
 			Self::deposit_event(RawEvent::ExternalTabled);
 			Self::inject_referendum(
 				now + T::VotingPeriod::get(),
-				proposal,
+				proposal_hash,
 				threshold,
 				T::EnactmentPeriod::get(),
 			)?;
@@ -866,7 +921,7 @@ impl<T: Trait> Module<T> {
 			.max_by_key(|x| Self::locked_for((x.1).0).unwrap_or_else(Zero::zero)
 				/* ^^ defensive only: All current public proposals have an amount locked*/)
 		{
-			let (prop_index, proposal, _) = public_props.swap_remove(winner_index);
+			let (prop_index, proposal_hash, _) = public_props.swap_remove(winner_index);
 			<PublicProps<T>>::put(public_props);
 
 			if let Some((deposit, depositors)) = <DepositOf<T>>::take(prop_index) {
@@ -877,7 +932,7 @@ impl<T: Trait> Module<T> {
 				Self::deposit_event(RawEvent::Tabled(prop_index, deposit, depositors));
 				Self::inject_referendum(
 					now + T::VotingPeriod::get(),
-					proposal,
+					proposal_hash,
 					VoteThreshold::SuperMajorityApprove,
 					T::EnactmentPeriod::get(),
 				)?;
@@ -892,47 +947,41 @@ impl<T: Trait> Module<T> {
 	fn bake_referendum(
 		now: T::BlockNumber,
 		index: ReferendumIndex,
-		info: ReferendumInfo<T::BlockNumber, T::Proposal>
+		info: ReferendumInfo<T::BlockNumber, T::Hash>
 	) -> Result {
 		let (approve, against, capital) = Self::tally(index);
 		let total_issuance = T::Currency::total_issuance();
 		let approved = info.threshold.approved(approve, against, capital, total_issuance);
 
 		// Logic defined in https://www.slideshare.net/gavofyork/governance-in-polkadot-poc3
-		// Essentially, we extend the lock-period of the coins behind the winning votes to be the
-		// vote strength times the public delay period from now.
-		for (a, Vote { conviction, .. }) in Self::voters_for(index).into_iter()
-			.map(|a| (a.clone(), Self::vote_of((index, a))))
-			// ^^^ defensive only: all items come from `voters`; for an item to be in `voters`
-			// there must be a vote registered; qed
-			.filter(|&(_, vote)| vote.aye == approved)	// Just the winning coins
-		{
-			// now plus: the base lock period multiplied by the number of periods this voter
-			// offered to lock should they win...
-			let locked_until = now + T::EnactmentPeriod::get() * conviction.lock_periods().into();
-			// ...extend their bondage until at least then.
-			T::Currency::extend_lock(
-				DEMOCRACY_ID,
-				&a,
-				Bounded::max_value(),
-				locked_until,
-				WithdrawReason::Transfer.into()
-			);
-		}
+		// Essentially, the coins behind the winning votes stay locked for the vote strength times
+		// the public delay period from now. This is accounted for when the votes are removed.
+		<ReferendumResultOf<T>>::insert(index, (approved, now));
 
 		Self::clear_referendum(index);
 		if approved {
 			Self::deposit_event(RawEvent::Passed(index));
 			if info.delay.is_zero() {
-				Self::enact_proposal(info.proposal, index);
+				Self::do_enact_proposal(info.proposal_hash, index);
 			} else {
-				<DispatchQueue<T>>::append_or_insert(
+				let scheduled = T::Scheduler::schedule_named(
+					Self::enactment_id(index),
 					now + info.delay,
-					&[Some((info.proposal, index))][..]
+					None,
+					schedule::HARD_DEADLINE,
+					Call::enact_proposal(info.proposal_hash, index).into(),
 				);
+				if scheduled.is_ok() {
+					<QueuedProposal<T>>::insert(index, info.proposal_hash);
+				} else {
+					// defensive only: the name of an enactment is unique to its referendum.
+					sr_primitives::print("Failed to schedule the enactment of a referendum");
+					T::Preimages::unrequest_preimage(&info.proposal_hash);
+				}
 			}
 		} else {
 			Self::deposit_event(RawEvent::NotPassed(index));
+			T::Preimages::unrequest_preimage(&info.proposal_hash);
 		}
 		NextTally::put(index + 1);
 
@@ -954,16 +1003,19 @@ impl<T: Trait> Module<T> {
 			Self::bake_referendum(now, index, info)?;
 		}
 
-		for (proposal, index) in <DispatchQueue<T>>::take(now).into_iter().filter_map(|x| x) {
-			Self::enact_proposal(proposal, index);
+		// proposals queued for enactment before they were scheduled with `T::Scheduler`.
+		for (proposal_hash, index) in <DispatchQueue<T>>::take(now).into_iter().filter_map(|x| x) {
+			Self::do_enact_proposal(proposal_hash, index);
 		}
+
+		Self::prune_results(now);
 		Ok(())
 	}
 }
 
 impl<T: Trait> OnFreeBalanceZero<T::AccountId> for Module<T> {
 	fn on_free_balance_zero(who: &T::AccountId) {
-		<Proxy<T>>::remove(who)
+		<VotingOf<T>>::remove(who);
 	}
 }
 
@@ -972,18 +1024,40 @@ mod tests {
 	use super::*;
 	use support::{
 		impl_outer_origin, impl_outer_dispatch, assert_noop, assert_ok, parameter_types,
-		traits::Contains
+		traits::{Contains, OnInitialize},
 	};
 	use primitives::H256;
-	use sr_primitives::{traits::{BlakeTwo256, IdentityLookup, Bounded}, testing::Header, Perbill};
+	use sr_primitives::{
+		traits::{BlakeTwo256, IdentityLookup, Bounded, Hash, OnRuntimeUpgrade}, testing::Header, Perbill,
+	};
 	use balances::BalanceLock;
-	use system::EnsureSignedBy;
+	use system::{EnsureSignedBy, EnsureRoot};
 
 	const AYE: Vote = Vote{ aye: true, conviction: Conviction::None };
 	const NAY: Vote = Vote{ aye: false, conviction: Conviction::None };
 	const BIG_AYE: Vote = Vote{ aye: true, conviction: Conviction::Locked1x };
 	const BIG_NAY: Vote = Vote{ aye: false, conviction: Conviction::Locked1x };
 
+	fn standard(who: u64, vote: Vote) -> AccountVote<u64> {
+		AccountVote::Standard { vote, balance: Balances::free_balance(&who) }
+	}
+
+	fn aye(who: u64) -> AccountVote<u64> {
+		standard(who, AYE)
+	}
+
+	fn nay(who: u64) -> AccountVote<u64> {
+		standard(who, NAY)
+	}
+
+	fn big_aye(who: u64) -> AccountVote<u64> {
+		standard(who, BIG_AYE)
+	}
+
+	fn big_nay(who: u64) -> AccountVote<u64> {
+		standard(who, BIG_NAY)
+	}
+
 	impl_outer_origin! {
 		pub enum Origin for Test {}
 	}
@@ -1037,6 +1111,31 @@ mod tests {
 		type TransferFee = TransferFee;
 		type CreationFee = CreationFee;
 	}
+	parameter_types! {
+		pub const MaxSize: u32 = 1024;
+		pub const BaseDeposit: u64 = 1;
+		pub const ByteDeposit: u64 = 1;
+		pub const ReapPeriod: u64 = 100;
+	}
+	impl preimage::Trait for Test {
+		type Event = ();
+		type Currency = Balances;
+		type ManagerOrigin = EnsureRoot<u64>;
+		type MaxSize = MaxSize;
+		type BaseDeposit = BaseDeposit;
+		type ByteDeposit = ByteDeposit;
+		type ReapPeriod = ReapPeriod;
+	}
+	parameter_types! {
+		pub const MaximumWeight: sr_primitives::weights::Weight = 1_000_000;
+	}
+	impl scheduler::Trait for Test {
+		type Event = ();
+		type Call = Call;
+		type MaximumWeight = MaximumWeight;
+		type ScheduleOrigin = EnsureRoot<u64>;
+		type Preimages = Preimage;
+	}
 	parameter_types! {
 		pub const LaunchPeriod: u64 = 2;
 		pub const VotingPeriod: u64 = 2;
@@ -1072,13 +1171,14 @@ mod tests {
 		type CancellationOrigin = EnsureSignedBy<Four, u64>;
 		type VetoOrigin = EnsureSignedBy<OneToFive, u64>;
 		type CooloffPeriod = CooloffPeriod;
+		type Preimages = Preimage;
+		type Scheduler = Scheduler;
 	}
 
 	fn new_test_ext() -> runtime_io::TestExternalities {
 		let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
 		balances::GenesisConfig::<Test>{
 			balances: vec![(1, 10), (2, 20), (3, 30), (4, 40), (5, 50), (6, 60)],
-			vesting: vec![],
 		}.assimilate_storage(&mut t).unwrap();
 		GenesisConfig::default().assimilate_storage(&mut t).unwrap();
 		runtime_io::TestExternalities::new(t)
@@ -1086,6 +1186,8 @@ mod tests {
 
 	type System = system::Module<Test>;
 	type Balances = balances::Module<Test>;
+	type Preimage = preimage::Module<Test>;
+	type Scheduler = scheduler::Module<Test>;
 	type Democracy = Module<Test>;
 
 	#[test]
@@ -1101,16 +1203,27 @@ mod tests {
 		Call::Balances(balances::Call::set_balance(42, value, 0))
 	}
 
+	/// Note the preimage of `set_balance_proposal(value)`, if not noted yet, and return its hash.
+	fn set_balance_proposal_hash(value: u64) -> H256 {
+		let proposal = set_balance_proposal(value);
+		let hash = BlakeTwo256::hash_of(&proposal);
+		if !Preimage::have_preimage(&hash) {
+			assert_ok!(Preimage::note_preimage(Origin::ROOT, proposal.encode()));
+		}
+		hash
+	}
+
 	fn propose_set_balance(who: u64, value: u64, delay: u64) -> super::Result {
 		Democracy::propose(
 			Origin::signed(who),
-			Box::new(set_balance_proposal(value)),
+			set_balance_proposal_hash(value),
 			delay
 		)
 	}
 
 	fn next_block() {
 		assert_eq!(Democracy::end_block(System::block_number()), Ok(()));
+		Scheduler::on_initialize(System::block_number());
 		System::set_block_number(System::block_number() + 1);
 	}
 
@@ -1126,7 +1239,7 @@ mod tests {
 			System::set_block_number(0);
 			assert_ok!(Democracy::external_propose(
 				Origin::signed(2),
-				Box::new(set_balance_proposal(1)),
+				set_balance_proposal_hash(1),
 			));
 			assert_ok!(propose_set_balance(6, 2, 2));
 
@@ -1137,7 +1250,7 @@ mod tests {
 				Democracy::referendum_info(0),
 				Some(ReferendumInfo {
 					end: 2,
-					proposal: set_balance_proposal(1),
+					proposal_hash: set_balance_proposal_hash(1),
 					threshold: VoteThreshold::SuperMajorityApprove,
 					delay: 2
 				})
@@ -1145,7 +1258,7 @@ mod tests {
 			// replenish external
 			assert_ok!(Democracy::external_propose(
 				Origin::signed(2),
-				Box::new(set_balance_proposal(3)),
+				set_balance_proposal_hash(3),
 			));
 
 			fast_forward_to(3);
@@ -1155,7 +1268,7 @@ mod tests {
 				Democracy::referendum_info(1),
 				Some(ReferendumInfo {
 					end: 4,
-					proposal: set_balance_proposal(2),
+					proposal_hash: set_balance_proposal_hash(2),
 					threshold: VoteThreshold::SuperMajorityApprove,
 					delay: 2
 				})
@@ -1169,7 +1282,7 @@ mod tests {
 				Democracy::referendum_info(2),
 				Some(ReferendumInfo {
 					end: 6,
-					proposal: set_balance_proposal(3),
+					proposal_hash: set_balance_proposal_hash(3),
 					threshold: VoteThreshold::SuperMajorityApprove,
 					delay: 2
 				})
@@ -1177,7 +1290,7 @@ mod tests {
 			// replenish external
 			assert_ok!(Democracy::external_propose(
 				Origin::signed(2),
-				Box::new(set_balance_proposal(5)),
+				set_balance_proposal_hash(5),
 			));
 
 			fast_forward_to(7);
@@ -1187,7 +1300,7 @@ mod tests {
 				Democracy::referendum_info(3),
 				Some(ReferendumInfo {
 					end: 8,
-					proposal: set_balance_proposal(5),
+					proposal_hash: set_balance_proposal_hash(5),
 					threshold: VoteThreshold::SuperMajorityApprove,
 					delay: 2
 				})
@@ -1195,7 +1308,7 @@ mod tests {
 			// replenish both
 			assert_ok!(Democracy::external_propose(
 				Origin::signed(2),
-				Box::new(set_balance_proposal(7)),
+				set_balance_proposal_hash(7),
 			));
 			assert_ok!(propose_set_balance(6, 4, 2));
 
@@ -1206,7 +1319,7 @@ mod tests {
 				Democracy::referendum_info(4),
 				Some(ReferendumInfo {
 					end: 10,
-					proposal: set_balance_proposal(4),
+					proposal_hash: set_balance_proposal_hash(4),
 					threshold: VoteThreshold::SuperMajorityApprove,
 					delay: 2
 				})
@@ -1214,7 +1327,7 @@ mod tests {
 			// replenish public again
 			assert_ok!(propose_set_balance(6, 6, 2));
 			// cancel external
-			let h = BlakeTwo256::hash_of(&set_balance_proposal(7));
+			let h = set_balance_proposal_hash(7);
 			assert_ok!(Democracy::veto_external(Origin::signed(3), h));
 
 			fast_forward_to(11);
@@ -1224,7 +1337,7 @@ mod tests {
 				Democracy::referendum_info(5),
 				Some(ReferendumInfo {
 					end: 12,
-					proposal: set_balance_proposal(6),
+					proposal_hash: set_balance_proposal_hash(6),
 					threshold: VoteThreshold::SuperMajorityApprove,
 					delay: 2
 				})
@@ -1239,7 +1352,7 @@ mod tests {
 			System::set_block_number(0);
 			let r = Democracy::inject_referendum(
 				2,
-				set_balance_proposal(2),
+				set_balance_proposal_hash(2),
 				VoteThreshold::SuperMajorityApprove,
 				2
 			).unwrap();
@@ -1253,7 +1366,7 @@ mod tests {
 
 			let r = Democracy::inject_referendum(
 				2,
-				set_balance_proposal(2),
+				set_balance_proposal_hash(2),
 				VoteThreshold::SuperMajorityApprove,
 				2
 			).unwrap();
@@ -1268,32 +1381,32 @@ mod tests {
 			System::set_block_number(0);
 			assert_ok!(Democracy::external_propose(
 				Origin::signed(2),
-				Box::new(set_balance_proposal(2)),
+				set_balance_proposal_hash(2),
 			));
 			assert!(<NextExternal<Test>>::exists());
 
-			let h = BlakeTwo256::hash_of(&set_balance_proposal(2));
+			let h = set_balance_proposal_hash(2);
 			assert_ok!(Democracy::veto_external(Origin::signed(3), h.clone()));
 			// cancelled.
 			assert!(!<NextExternal<Test>>::exists());
 			// fails - same proposal can't be resubmitted.
 			assert_noop!(Democracy::external_propose(
 				Origin::signed(2),
-				Box::new(set_balance_proposal(2)),
+				BlakeTwo256::hash_of(&set_balance_proposal(2)),
 			), "proposal still blacklisted");
 
 			fast_forward_to(1);
 			// fails as we're still in cooloff period.
 			assert_noop!(Democracy::external_propose(
 				Origin::signed(2),
-				Box::new(set_balance_proposal(2)),
+				BlakeTwo256::hash_of(&set_balance_proposal(2)),
 			), "proposal still blacklisted");
 
 			fast_forward_to(2);
 			// works; as we're out of the cooloff period.
 			assert_ok!(Democracy::external_propose(
 				Origin::signed(2),
-				Box::new(set_balance_proposal(2)),
+				set_balance_proposal_hash(2),
 			));
 			assert!(<NextExternal<Test>>::exists());
 
@@ -1312,12 +1425,12 @@ mod tests {
 			// same proposal fails as we're still in cooloff
 			assert_noop!(Democracy::external_propose(
 				Origin::signed(2),
-				Box::new(set_balance_proposal(2)),
+				BlakeTwo256::hash_of(&set_balance_proposal(2)),
 			), "proposal still blacklisted");
 			// different proposal works fine.
 			assert_ok!(Democracy::external_propose(
 				Origin::signed(2),
-				Box::new(set_balance_proposal(3)),
+				set_balance_proposal_hash(3),
 			));
 		});
 	}
@@ -1328,22 +1441,22 @@ mod tests {
 			System::set_block_number(0);
 			assert_noop!(Democracy::external_propose(
 				Origin::signed(1),
-				Box::new(set_balance_proposal(2)),
+				BlakeTwo256::hash_of(&set_balance_proposal(2)),
 			), "Invalid origin");
 			assert_ok!(Democracy::external_propose(
 				Origin::signed(2),
-				Box::new(set_balance_proposal(2)),
+				set_balance_proposal_hash(2),
 			));
 			assert_noop!(Democracy::external_propose(
 				Origin::signed(2),
-				Box::new(set_balance_proposal(1)),
+				BlakeTwo256::hash_of(&set_balance_proposal(1)),
 			), "proposal already made");
 			fast_forward_to(1);
 			assert_eq!(
 				Democracy::referendum_info(0),
 				Some(ReferendumInfo {
 					end: 2,
-					proposal: set_balance_proposal(2),
+					proposal_hash: set_balance_proposal_hash(2),
 					threshold: VoteThreshold::SuperMajorityApprove,
 					delay: 2
 				})
@@ -1357,18 +1470,18 @@ mod tests {
 			System::set_block_number(0);
 			assert_noop!(Democracy::external_propose_majority(
 				Origin::signed(1),
-				Box::new(set_balance_proposal(2))
+				BlakeTwo256::hash_of(&set_balance_proposal(2))
 			), "Invalid origin");
 			assert_ok!(Democracy::external_propose_majority(
 				Origin::signed(3),
-				Box::new(set_balance_proposal(2))
+				set_balance_proposal_hash(2)
 			));
 			fast_forward_to(1);
 			assert_eq!(
 				Democracy::referendum_info(0),
 				Some(ReferendumInfo {
 					end: 2,
-					proposal: set_balance_proposal(2),
+					proposal_hash: set_balance_proposal_hash(2),
 					threshold: VoteThreshold::SimpleMajority,
 					delay: 2,
 				})
@@ -1382,18 +1495,18 @@ mod tests {
 			System::set_block_number(0);
 			assert_noop!(Democracy::external_propose_default(
 				Origin::signed(3),
-				Box::new(set_balance_proposal(2))
+				BlakeTwo256::hash_of(&set_balance_proposal(2))
 			), "Invalid origin");
 			assert_ok!(Democracy::external_propose_default(
 				Origin::signed(1),
-				Box::new(set_balance_proposal(2))
+				set_balance_proposal_hash(2)
 			));
 			fast_forward_to(1);
 			assert_eq!(
 				Democracy::referendum_info(0),
 				Some(ReferendumInfo {
 					end: 2,
-					proposal: set_balance_proposal(2),
+					proposal_hash: set_balance_proposal_hash(2),
 					threshold: VoteThreshold::SuperMajorityAgainst,
 					delay: 2,
 				})
@@ -1405,11 +1518,11 @@ mod tests {
 	fn fast_track_referendum_works() {
 		new_test_ext().execute_with(|| {
 			System::set_block_number(0);
-			let h = BlakeTwo256::hash_of(&set_balance_proposal(2));
+			let h = set_balance_proposal_hash(2);
 			assert_noop!(Democracy::fast_track(Origin::signed(5), h, 3, 2), "no proposal made");
 			assert_ok!(Democracy::external_propose_majority(
 				Origin::signed(3),
-				Box::new(set_balance_proposal(2))
+				set_balance_proposal_hash(2)
 			));
 			assert_noop!(Democracy::fast_track(Origin::signed(1), h, 3, 2), "Invalid origin");
 			assert_ok!(Democracy::fast_track(Origin::signed(5), h, 0, 0));
@@ -1417,7 +1530,7 @@ mod tests {
 				Democracy::referendum_info(0),
 				Some(ReferendumInfo {
 					end: 1,
-					proposal: set_balance_proposal(2),
+					proposal_hash: set_balance_proposal_hash(2),
 					threshold: VoteThreshold::SimpleMajority,
 					delay: 0,
 				})
@@ -1429,10 +1542,10 @@ mod tests {
 	fn fast_track_referendum_fails_when_no_simple_majority() {
 		new_test_ext().execute_with(|| {
 			System::set_block_number(0);
-			let h = BlakeTwo256::hash_of(&set_balance_proposal(2));
+			let h = set_balance_proposal_hash(2);
 			assert_ok!(Democracy::external_propose(
 				Origin::signed(2),
-				Box::new(set_balance_proposal(2))
+				set_balance_proposal_hash(2)
 			));
 			assert_noop!(
 				Democracy::fast_track(Origin::signed(5), h, 3, 2),
@@ -1465,20 +1578,20 @@ mod tests {
 			fast_forward_to(1);
 
 			let r = 0;
-			assert_ok!(Democracy::vote(Origin::signed(1), r, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), r, aye(1)));
 
 			assert_eq!(Democracy::referendum_count(), 1);
 			assert_eq!(
 				Democracy::referendum_info(0),
 				Some(ReferendumInfo {
 					end: 2,
-					proposal: set_balance_proposal(2),
+					proposal_hash: set_balance_proposal_hash(2),
 					threshold: VoteThreshold::SuperMajorityApprove,
 					delay: 2
 				})
 			);
 			assert_eq!(Democracy::voters_for(r), vec![1]);
-			assert_eq!(Democracy::vote_of((r, 1)), AYE);
+			assert_eq!(Democracy::vote_of(r, &1), Some(aye(1)));
 			assert_eq!(Democracy::tally(r), (1, 0, 1));
 
 			fast_forward_to(2);
@@ -1490,9 +1603,7 @@ mod tests {
 			fast_forward_to(3);
 
 			assert!(Democracy::referendum_info(0).is_none());
-			assert_eq!(Democracy::dispatch_queue(4), vec![
-				Some((set_balance_proposal(2), 0))
-			]);
+			assert_eq!(Democracy::queued_proposal(0), Some(set_balance_proposal_hash(2)));
 
 			// referendum passes and wait another two blocks for enactment.
 			fast_forward_to(5);
@@ -1510,68 +1621,19 @@ mod tests {
 			// end of 0 => next referendum scheduled.
 			fast_forward_to(1);
 
-			assert_ok!(Democracy::vote(Origin::signed(1), 0, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), 0, aye(1)));
 
 			fast_forward_to(3);
 
-			assert_eq!(Democracy::dispatch_queue(4), vec![
-				Some((set_balance_proposal(2), 0))
-			]);
-
-			assert_noop!(Democracy::cancel_queued(Origin::ROOT, 3, 0, 0), "proposal not found");
-			assert_noop!(Democracy::cancel_queued(Origin::ROOT, 4, 1, 0), "proposal not found");
-			assert_ok!(Democracy::cancel_queued(Origin::ROOT, 4, 0, 0));
-			assert_eq!(Democracy::dispatch_queue(4), vec![None]);
-		});
-	}
-
-	#[test]
-	fn proxy_should_work() {
-		new_test_ext().execute_with(|| {
-			assert_eq!(Democracy::proxy(10), None);
-			assert_ok!(Democracy::set_proxy(Origin::signed(1), 10));
-			assert_eq!(Democracy::proxy(10), Some(1));
-
-			// Can't set when already set.
-			assert_noop!(Democracy::set_proxy(Origin::signed(2), 10), "already a proxy");
-
-			// But this works because 11 isn't proxying.
-			assert_ok!(Democracy::set_proxy(Origin::signed(2), 11));
-			assert_eq!(Democracy::proxy(10), Some(1));
-			assert_eq!(Democracy::proxy(11), Some(2));
-
-			// 2 cannot fire 1's proxy:
-			assert_noop!(Democracy::remove_proxy(Origin::signed(2), 10), "wrong proxy");
-
-			// 1 fires his proxy:
-			assert_ok!(Democracy::remove_proxy(Origin::signed(1), 10));
-			assert_eq!(Democracy::proxy(10), None);
-			assert_eq!(Democracy::proxy(11), Some(2));
-
-			// 11 resigns:
-			assert_ok!(Democracy::resign_proxy(Origin::signed(11)));
-			assert_eq!(Democracy::proxy(10), None);
-			assert_eq!(Democracy::proxy(11), None);
-		});
-	}
-
-	#[test]
-	fn single_proposal_should_work_with_proxy() {
-		new_test_ext().execute_with(|| {
-			System::set_block_number(0);
-			assert_ok!(propose_set_balance(1, 2, 1));
-
-			fast_forward_to(1);
-			let r = 0;
-			assert_ok!(Democracy::set_proxy(Origin::signed(1), 10));
-			assert_ok!(Democracy::proxy_vote(Origin::signed(10), r, AYE));
+			assert_eq!(Democracy::queued_proposal(0), Some(set_balance_proposal_hash(2)));
 
-			assert_eq!(Democracy::voters_for(r), vec![1]);
-			assert_eq!(Democracy::vote_of((r, 1)), AYE);
-			assert_eq!(Democracy::tally(r), (1, 0, 1));
+			assert_noop!(Democracy::cancel_queued(Origin::ROOT, 1), "proposal not found");
+			assert_ok!(Democracy::cancel_queued(Origin::ROOT, 0));
+			assert_eq!(Democracy::queued_proposal(0), None);
+			assert!(!Preimage::preimage_requested(&set_balance_proposal_hash(2)));
 
 			fast_forward_to(5);
-			assert_eq!(Balances::free_balance(&42), 2);
+			assert_eq!(Balances::free_balance(&42), 0);
 		});
 	}
 
@@ -1588,9 +1650,9 @@ mod tests {
 			assert_ok!(Democracy::delegate(Origin::signed(2), 1, Conviction::max_value()));
 
 			let r = 0;
-			assert_ok!(Democracy::vote(Origin::signed(1), r, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), r, aye(1)));
 			assert_eq!(Democracy::voters_for(r), vec![1]);
-			assert_eq!(Democracy::vote_of((r, 1)), AYE);
+			assert_eq!(Democracy::vote_of(r, &1), Some(aye(1)));
 			// Delegated vote is counted.
 			assert_eq!(Democracy::tally(r), (3, 0, 3));
 
@@ -1614,7 +1676,7 @@ mod tests {
 			assert_ok!(Democracy::delegate(Origin::signed(3), 2, Conviction::max_value()));
 			assert_ok!(Democracy::delegate(Origin::signed(1), 3, Conviction::max_value()));
 			let r = 0;
-			assert_ok!(Democracy::vote(Origin::signed(1), r, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), r, aye(1)));
 			assert_eq!(Democracy::voters_for(r), vec![1]);
 
 			// Delegated vote is counted.
@@ -1637,13 +1699,13 @@ mod tests {
 			fast_forward_to(1);
 
 			let r = 0;
-			assert_ok!(Democracy::vote(Origin::signed(1), r, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), r, aye(1)));
 			// Vote.
-			assert_ok!(Democracy::vote(Origin::signed(2), r, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(2), r, aye(2)));
 			// Delegate vote.
 			assert_ok!(Democracy::delegate(Origin::signed(2), 1, Conviction::max_value()));
 			assert_eq!(Democracy::voters_for(r), vec![1, 2]);
-			assert_eq!(Democracy::vote_of((r, 1)), AYE);
+			assert_eq!(Democracy::vote_of(r, &1), Some(aye(1)));
 			// Delegated vote is not counted.
 			assert_eq!(Democracy::tally(r), (3, 0, 3));
 
@@ -1666,11 +1728,11 @@ mod tests {
 
 			fast_forward_to(1);
 			let r = 0;
-			assert_ok!(Democracy::vote(Origin::signed(1), r, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), r, aye(1)));
 
 			assert_eq!(Democracy::referendum_count(), 1);
 			assert_eq!(Democracy::voters_for(r), vec![1]);
-			assert_eq!(Democracy::vote_of((r, 1)), AYE);
+			assert_eq!(Democracy::vote_of(r, &1), Some(aye(1)));
 
 			// Delegated vote is not counted.
 			assert_eq!(Democracy::tally(r), (1, 0, 1));
@@ -1692,17 +1754,17 @@ mod tests {
 			fast_forward_to(1);
 			let r = 0;
 
-			assert_ok!(Democracy::vote(Origin::signed(1), r, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), r, aye(1)));
 
 			// Delegate vote.
 			assert_ok!(Democracy::delegate(Origin::signed(2), 1, Conviction::max_value()));
 
 			// Vote.
-			assert_ok!(Democracy::vote(Origin::signed(2), r, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(2), r, aye(2)));
 
 			assert_eq!(Democracy::referendum_count(), 1);
 			assert_eq!(Democracy::voters_for(r), vec![1, 2]);
-			assert_eq!(Democracy::vote_of((r, 1)), AYE);
+			assert_eq!(Democracy::vote_of(r, &1), Some(aye(1)));
 
 			// Delegated vote is not counted.
 			assert_eq!(Democracy::tally(r), (3, 0, 3));
@@ -1748,7 +1810,8 @@ mod tests {
 	fn proposal_with_deposit_below_minimum_should_not_work() {
 		new_test_ext().execute_with(|| {
 			System::set_block_number(1);
-			assert_noop!(propose_set_balance(1, 2, 0), "value too low");
+			let h = BlakeTwo256::hash_of(&set_balance_proposal(2));
+			assert_noop!(Democracy::propose(Origin::signed(1), h, 0), "value too low");
 		});
 	}
 
@@ -1756,7 +1819,8 @@ mod tests {
 	fn poor_proposer_should_not_work() {
 		new_test_ext().execute_with(|| {
 			System::set_block_number(1);
-			assert_noop!(propose_set_balance(1, 2, 11), "proposer\'s balance too low");
+			let h = BlakeTwo256::hash_of(&set_balance_proposal(2));
+			assert_noop!(Democracy::propose(Origin::signed(1), h, 11), "proposer\'s balance too low");
 		});
 	}
 
@@ -1777,11 +1841,11 @@ mod tests {
 			assert_ok!(propose_set_balance(1, 4, 4));
 			assert_ok!(propose_set_balance(1, 3, 3));
 			fast_forward_to(1);
-			assert_ok!(Democracy::vote(Origin::signed(1), 0, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), 0, aye(1)));
 			fast_forward_to(3);
-			assert_ok!(Democracy::vote(Origin::signed(1), 1, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), 1, aye(1)));
 			fast_forward_to(5);
-			assert_ok!(Democracy::vote(Origin::signed(1), 2, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), 2, aye(1)));
 		});
 	}
 
@@ -1791,14 +1855,14 @@ mod tests {
 			System::set_block_number(1);
 			let r = Democracy::inject_referendum(
 				1,
-				set_balance_proposal(2),
+				set_balance_proposal_hash(2),
 				VoteThreshold::SuperMajorityApprove,
 				0
 			).unwrap();
-			assert_ok!(Democracy::vote(Origin::signed(1), r, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), r, aye(1)));
 
 			assert_eq!(Democracy::voters_for(r), vec![1]);
-			assert_eq!(Democracy::vote_of((r, 1)), AYE);
+			assert_eq!(Democracy::vote_of(r, &1), Some(aye(1)));
 			assert_eq!(Democracy::tally(r), (1, 0, 1));
 
 			next_block();
@@ -1814,11 +1878,11 @@ mod tests {
 			System::set_block_number(1);
 			let r = Democracy::inject_referendum(
 				1,
-				set_balance_proposal(2),
+				set_balance_proposal_hash(2),
 				VoteThreshold::SuperMajorityApprove,
 				0
 			).unwrap();
-			assert_ok!(Democracy::vote(Origin::signed(1), r, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), r, aye(1)));
 			assert_ok!(Democracy::cancel_referendum(Origin::ROOT, r.into()));
 
 			next_block();
@@ -1834,14 +1898,14 @@ mod tests {
 			System::set_block_number(1);
 			let r = Democracy::inject_referendum(
 				1,
-				set_balance_proposal(2),
+				set_balance_proposal_hash(2),
 				VoteThreshold::SuperMajorityApprove,
 				0
 			).unwrap();
-			assert_ok!(Democracy::vote(Origin::signed(1), r, NAY));
+			assert_ok!(Democracy::vote(Origin::signed(1), r, nay(1)));
 
 			assert_eq!(Democracy::voters_for(r), vec![1]);
-			assert_eq!(Democracy::vote_of((r, 1)), NAY);
+			assert_eq!(Democracy::vote_of(r, &1), Some(nay(1)));
 			assert_eq!(Democracy::tally(r), (0, 1, 1));
 
 			next_block();
@@ -1857,16 +1921,16 @@ mod tests {
 			System::set_block_number(1);
 			let r = Democracy::inject_referendum(
 				1,
-				set_balance_proposal(2),
+				set_balance_proposal_hash(2),
 				VoteThreshold::SuperMajorityApprove,
 				0
 			).unwrap();
-			assert_ok!(Democracy::vote(Origin::signed(1), r, BIG_AYE));
-			assert_ok!(Democracy::vote(Origin::signed(2), r, BIG_NAY));
-			assert_ok!(Democracy::vote(Origin::signed(3), r, BIG_NAY));
-			assert_ok!(Democracy::vote(Origin::signed(4), r, BIG_AYE));
-			assert_ok!(Democracy::vote(Origin::signed(5), r, BIG_NAY));
-			assert_ok!(Democracy::vote(Origin::signed(6), r, BIG_AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), r, big_aye(1)));
+			assert_ok!(Democracy::vote(Origin::signed(2), r, big_nay(2)));
+			assert_ok!(Democracy::vote(Origin::signed(3), r, big_nay(3)));
+			assert_ok!(Democracy::vote(Origin::signed(4), r, big_aye(4)));
+			assert_ok!(Democracy::vote(Origin::signed(5), r, big_nay(5)));
+			assert_ok!(Democracy::vote(Origin::signed(6), r, big_aye(6)));
 
 			assert_eq!(Democracy::tally(r), (110, 100, 210));
 
@@ -1883,16 +1947,16 @@ mod tests {
 			System::set_block_number(1);
 			let r = Democracy::inject_referendum(
 				1,
-				set_balance_proposal(2),
+				set_balance_proposal_hash(2),
 				VoteThreshold::SuperMajorityApprove,
 				1
 			).unwrap();
-			assert_ok!(Democracy::vote(Origin::signed(1), r, AYE));
-			assert_ok!(Democracy::vote(Origin::signed(2), r, AYE));
-			assert_ok!(Democracy::vote(Origin::signed(3), r, AYE));
-			assert_ok!(Democracy::vote(Origin::signed(4), r, AYE));
-			assert_ok!(Democracy::vote(Origin::signed(5), r, AYE));
-			assert_ok!(Democracy::vote(Origin::signed(6), r, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), r, aye(1)));
+			assert_ok!(Democracy::vote(Origin::signed(2), r, aye(2)));
+			assert_ok!(Democracy::vote(Origin::signed(3), r, aye(3)));
+			assert_ok!(Democracy::vote(Origin::signed(4), r, aye(4)));
+			assert_ok!(Democracy::vote(Origin::signed(5), r, aye(5)));
+			assert_ok!(Democracy::vote(Origin::signed(6), r, aye(6)));
 
 			assert_eq!(Democracy::tally(r), (21, 0, 21));
 
@@ -1911,12 +1975,12 @@ mod tests {
 			System::set_block_number(1);
 			let r = Democracy::inject_referendum(
 				1,
-				set_balance_proposal(2),
+				set_balance_proposal_hash(2),
 				VoteThreshold::SuperMajorityApprove,
 				0
 			).unwrap();
-			assert_ok!(Democracy::vote(Origin::signed(5), r, BIG_NAY));
-			assert_ok!(Democracy::vote(Origin::signed(6), r, BIG_AYE));
+			assert_ok!(Democracy::vote(Origin::signed(5), r, big_nay(5)));
+			assert_ok!(Democracy::vote(Origin::signed(6), r, big_aye(6)));
 
 			assert_eq!(Democracy::tally(r), (60, 50, 110));
 
@@ -1936,13 +2000,13 @@ mod tests {
 			System::set_block_number(1);
 			let r = Democracy::inject_referendum(
 				1,
-				set_balance_proposal(2),
+				set_balance_proposal_hash(2),
 				VoteThreshold::SuperMajorityApprove,
 				0
 			).unwrap();
-			assert_ok!(Democracy::vote(Origin::signed(4), r, BIG_AYE));
-			assert_ok!(Democracy::vote(Origin::signed(5), r, BIG_NAY));
-			assert_ok!(Democracy::vote(Origin::signed(6), r, BIG_AYE));
+			assert_ok!(Democracy::vote(Origin::signed(4), r, big_aye(4)));
+			assert_ok!(Democracy::vote(Origin::signed(5), r, big_nay(5)));
+			assert_ok!(Democracy::vote(Origin::signed(6), r, big_aye(6)));
 
 			assert_eq!(Democracy::tally(r), (100, 50, 150));
 
@@ -1959,56 +2023,78 @@ mod tests {
 			System::set_block_number(0);
 			let r = Democracy::inject_referendum(
 				1,
-				set_balance_proposal(2),
+				set_balance_proposal_hash(2),
 				VoteThreshold::SuperMajorityApprove,
 				0
 			).unwrap();
-			assert_ok!(Democracy::vote(Origin::signed(1), r, Vote {
+			assert_ok!(Democracy::vote(Origin::signed(1), r, standard(1, Vote {
 				aye: false,
 				conviction: Conviction::Locked5x
-			}));
-			assert_ok!(Democracy::vote(Origin::signed(2), r, Vote {
+			})));
+			assert_ok!(Democracy::vote(Origin::signed(2), r, standard(2, Vote {
 				aye: true,
 				conviction: Conviction::Locked4x
-			}));
-			assert_ok!(Democracy::vote(Origin::signed(3), r, Vote {
+			})));
+			assert_ok!(Democracy::vote(Origin::signed(3), r, standard(3, Vote {
 				aye: true,
 				conviction: Conviction::Locked3x
-			}));
-			assert_ok!(Democracy::vote(Origin::signed(4), r, Vote {
+			})));
+			assert_ok!(Democracy::vote(Origin::signed(4), r, standard(4, Vote {
 				aye: true,
 				conviction: Conviction::Locked2x
-			}));
-			assert_ok!(Democracy::vote(Origin::signed(5), r, Vote {
+			})));
+			assert_ok!(Democracy::vote(Origin::signed(5), r, standard(5, Vote {
 				aye: false,
 				conviction: Conviction::Locked1x
-			}));
+			})));
 
 			assert_eq!(Democracy::tally(r), (250, 100, 150));
 
 			fast_forward_to(2);
 
+			// the votes keep their balance locked until they are removed.
+			for who in 1..=5 {
+				assert_eq!(Balances::locks(who), vec![BalanceLock {
+					id: DEMOCRACY_ID,
+					amount: Balances::free_balance(&who),
+					until: u64::max_value(),
+					reasons: WithdrawReason::Transfer.into()
+				}]);
+			}
+
+			for who in 1..=5 {
+				assert_ok!(Democracy::remove_vote(Origin::signed(who), r));
+				assert_ok!(Democracy::unlock(Origin::signed(who), who));
+			}
+
+			// losing side is unlocked right away, winning side stays locked for the lock periods
+			// of its conviction.
 			assert_eq!(Balances::locks(1), vec![]);
-			assert_eq!(Balances::locks(2), vec![BalanceLock {
-				id: DEMOCRACY_ID,
-				amount: u64::max_value(),
-				until: 17,
-				reasons: WithdrawReason::Transfer.into()
-			}]);
-			assert_eq!(Balances::locks(3), vec![BalanceLock {
-				id: DEMOCRACY_ID,
-				amount: u64::max_value(),
-				until: 9,
-				reasons: WithdrawReason::Transfer.into()
-			}]);
+			assert_eq!(Balances::locks(2)[0].amount, 20);
+			assert_eq!(Balances::locks(3)[0].amount, 30);
 			assert_eq!(Balances::locks(4), vec![BalanceLock {
 				id: DEMOCRACY_ID,
-				amount: u64::max_value(),
-				until: 5,
+				amount: 40,
+				until: u64::max_value(),
 				reasons: WithdrawReason::Transfer.into()
 			}]);
 			assert_eq!(Balances::locks(5), vec![]);
 
+			fast_forward_to(5);
+			assert_ok!(Democracy::unlock(Origin::signed(1), 4));
+			assert_eq!(Balances::locks(4), vec![]);
+			assert_ok!(Democracy::unlock(Origin::signed(1), 3));
+			assert_eq!(Balances::locks(3).len(), 1);
+
+			fast_forward_to(9);
+			assert_ok!(Democracy::unlock(Origin::signed(1), 3));
+			assert_eq!(Balances::locks(3), vec![]);
+
+			fast_forward_to(17);
+			assert_ok!(Democracy::unlock(Origin::signed(1), 2));
+			assert_eq!(Balances::locks(2), vec![]);
+			assert_eq!(Democracy::voting_of(2), Default::default());
+
 			assert_eq!(Balances::free_balance(&42), 2);
 		});
 	}
@@ -2019,27 +2105,27 @@ mod tests {
 			System::set_block_number(1);
 			let r = Democracy::inject_referendum(
 				1,
-				set_balance_proposal(2),
+				set_balance_proposal_hash(2),
 				VoteThreshold::SuperMajorityApprove,
 				0
 			).unwrap();
-			assert_ok!(Democracy::vote(Origin::signed(1), r, Vote {
+			assert_ok!(Democracy::vote(Origin::signed(1), r, standard(1, Vote {
 				aye: false,
 				conviction: Conviction::Locked5x
-			}));
-			assert_ok!(Democracy::vote(Origin::signed(2), r, Vote {
+			})));
+			assert_ok!(Democracy::vote(Origin::signed(2), r, standard(2, Vote {
 				aye: true,
 				conviction: Conviction::Locked4x
-			}));
-			assert_ok!(Democracy::vote(Origin::signed(3), r, Vote {
+			})));
+			assert_ok!(Democracy::vote(Origin::signed(3), r, standard(3, Vote {
 				aye: true,
 				conviction: Conviction::Locked3x
-			}));
+			})));
 			assert_ok!(Democracy::delegate(Origin::signed(4), 2, Conviction::Locked2x));
-			assert_ok!(Democracy::vote(Origin::signed(5), r, Vote {
+			assert_ok!(Democracy::vote(Origin::signed(5), r, standard(5, Vote {
 				aye: false,
 				conviction: Conviction::Locked1x
-			}));
+			})));
 
 			assert_eq!(Democracy::tally(r), (250, 100, 150));
 
@@ -2049,4 +2135,213 @@ mod tests {
 			assert_eq!(Balances::free_balance(&42), 2);
 		});
 	}
+
+	#[test]
+	fn split_vote_should_work() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			let r = Democracy::inject_referendum(
+				1,
+				set_balance_proposal_hash(2),
+				VoteThreshold::SuperMajorityApprove,
+				0
+			).unwrap();
+			assert_noop!(
+				Democracy::vote(Origin::signed(4), r, AccountVote::Split { aye: 30, nay: 11 }),
+				"insufficient funds for vote"
+			);
+			let split = AccountVote::Split { aye: 30, nay: 10 };
+			assert_ok!(Democracy::vote(Origin::signed(4), r, split));
+			assert_ok!(Democracy::vote(Origin::signed(2), r, big_nay(2)));
+
+			// split votes carry no conviction.
+			assert_eq!(Democracy::tally(r), (3, 21, 24));
+			assert_eq!(Balances::locks(4)[0].amount, 40);
+
+			fast_forward_to(3);
+			assert_eq!(Balances::free_balance(&42), 0);
+
+			// split votes are never locked past the end of the referendum.
+			assert_ok!(Democracy::remove_vote(Origin::signed(4), r));
+			assert_ok!(Democracy::unlock(Origin::signed(4), 4));
+			assert_eq!(Balances::locks(4), vec![]);
+		});
+	}
+
+	#[test]
+	fn removing_vote_during_referendum_should_work() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			let r = Democracy::inject_referendum(
+				1,
+				set_balance_proposal_hash(2),
+				VoteThreshold::SuperMajorityApprove,
+				0
+			).unwrap();
+			assert_ok!(Democracy::vote(Origin::signed(1), r, big_aye(1)));
+			assert_ok!(Democracy::vote(Origin::signed(2), r, aye(2)));
+			assert_eq!(Democracy::tally(r), (12, 0, 12));
+
+			assert_noop!(Democracy::remove_vote(Origin::signed(3), r), "vote not found");
+			assert_ok!(Democracy::remove_vote(Origin::signed(1), r));
+			assert_eq!(Democracy::voters_for(r), vec![2]);
+			assert_eq!(Democracy::vote_of(r, &1), None);
+			assert_eq!(Democracy::tally(r), (2, 0, 2));
+
+			// no lock is left over by a vote removed before the end of the referendum.
+			assert_ok!(Democracy::unlock(Origin::signed(1), 1));
+			assert_eq!(Balances::locks(1), vec![]);
+			assert_eq!(Democracy::voting_of(1), Default::default());
+		});
+	}
+
+	#[test]
+	fn changing_vote_should_update_lock() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			let r = Democracy::inject_referendum(
+				1,
+				set_balance_proposal_hash(2),
+				VoteThreshold::SuperMajorityApprove,
+				0
+			).unwrap();
+			assert_ok!(Democracy::vote(Origin::signed(1), r, aye(1)));
+			assert_eq!(Balances::locks(1)[0].amount, 10);
+
+			assert_eq!(Democracy::tally(r), (1, 0, 1));
+
+			assert_ok!(Democracy::vote(Origin::signed(1), r, AccountVote::Standard {
+				vote: BIG_AYE,
+				balance: 5,
+			}));
+			assert_eq!(Democracy::voters_for(r), vec![1]);
+			assert_eq!(Balances::locks(1)[0].amount, 5);
+			assert_eq!(Democracy::tally(r), (5, 0, 5));
+		});
+	}
+
+	#[test]
+	fn legacy_votes_should_be_migrated_on_runtime_upgrade() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			let r = Democracy::inject_referendum(
+				2,
+				set_balance_proposal_hash(2),
+				VoteThreshold::SuperMajorityApprove,
+				0
+			).unwrap();
+			let legacy_key = |who: u64| {
+				let mut key = LEGACY_VOTE_OF_PREFIX.to_vec();
+				(r, who).encode_to(&mut key);
+				key
+			};
+			for &(who, vote) in &[(1, BIG_AYE), (2, NAY)] {
+				storage::hashed::put(&runtime_io::blake2_256, &legacy_key(who), &vote);
+				<VotersFor<Test>>::append_or_insert(r, &[who][..]);
+			}
+
+			assert_eq!(<Democracy as OnRuntimeUpgrade>::on_runtime_upgrade(), 2 * VOTE_MIGRATION_WEIGHT);
+			assert!(!storage::hashed::exists(&runtime_io::blake2_256, &legacy_key(1)));
+			assert_eq!(Democracy::voting_of(1).votes, vec![(r, big_aye(1))]);
+			assert_eq!(Democracy::voting_of(2).votes, vec![(r, nay(2))]);
+			assert_eq!(Balances::locks(1)[0].amount, 10);
+			assert_eq!(Balances::locks(2)[0].amount, 20);
+			assert_eq!(Democracy::tally(r), (10, 2, 30));
+
+			// nothing left to migrate.
+			assert_eq!(<Democracy as OnRuntimeUpgrade>::on_runtime_upgrade(), 0);
+			assert_eq!(Democracy::voting_of(1).votes, vec![(r, big_aye(1))]);
+		});
+	}
+
+	#[test]
+	fn referendum_results_should_be_pruned_once_no_lock_depends_on_them() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			let r = Democracy::inject_referendum(
+				2,
+				set_balance_proposal_hash(2),
+				VoteThreshold::SuperMajorityApprove,
+				0
+			).unwrap();
+			assert_ok!(Democracy::vote(Origin::signed(1), r, standard(1, Vote {
+				aye: true,
+				conviction: Conviction::Locked6x,
+			})));
+
+			fast_forward_to(3);
+			assert_eq!(Democracy::referendum_result(r).map(|(_, end)| end), Some(2));
+
+			// the longest lock lasts 32 enactment periods after the end of the referendum.
+			fast_forward_to(66);
+			assert!(Democracy::referendum_result(r).is_some());
+			assert_eq!(Democracy::next_result_to_prune(), 0);
+
+			fast_forward_to(67);
+			assert_eq!(Democracy::referendum_result(r), None);
+			assert_eq!(Democracy::next_result_to_prune(), 1);
+
+			assert_ok!(Democracy::remove_vote(Origin::signed(1), r));
+			assert_eq!(Balances::locks(1), vec![]);
+		});
+	}
+
+	#[test]
+	fn preimage_should_be_requested_until_enacted() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(0);
+			assert_ok!(propose_set_balance(1, 2, 1));
+			let h = set_balance_proposal_hash(2);
+			assert!(Preimage::preimage_requested(&h));
+
+			fast_forward_to(1);
+			assert_ok!(Democracy::vote(Origin::signed(1), 0, aye(1)));
+
+			// queued for enactment.
+			fast_forward_to(3);
+			assert!(Preimage::preimage_requested(&h));
+
+			fast_forward_to(5);
+			assert_eq!(Balances::free_balance(&42), 2);
+			assert!(!Preimage::preimage_requested(&h));
+		});
+	}
+
+	#[test]
+	fn proposal_without_preimage_should_not_be_enacted() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(0);
+			let h = BlakeTwo256::hash_of(&set_balance_proposal(2));
+			assert_ok!(Democracy::propose(Origin::signed(1), h, 1));
+			assert!(Preimage::preimage_requested(&h));
+
+			fast_forward_to(1);
+			assert_ok!(Democracy::vote(Origin::signed(1), 0, aye(1)));
+
+			fast_forward_to(5);
+			assert_eq!(Balances::free_balance(&42), 0);
+			assert!(!Preimage::preimage_requested(&h));
+		});
+	}
+
+	#[test]
+	fn failed_referendum_should_unrequest_preimage() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			let h = set_balance_proposal_hash(2);
+			let r = Democracy::internal_start_referendum(
+				h,
+				VoteThreshold::SuperMajorityApprove,
+				0
+			).unwrap();
+			assert!(Preimage::preimage_requested(&h));
+			assert_ok!(Democracy::vote(Origin::signed(1), r, nay(1)));
+
+			fast_forward_to(4);
+			assert_eq!(Balances::free_balance(&42), 0);
+			assert!(!Preimage::preimage_requested(&h));
+			// the preimage itself is only reaped later on.
+			assert!(Preimage::have_preimage(&h));
+		});
+	}
 }
@@ -0,0 +1,153 @@
+// Copyright 2017-2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The vote datatypes.
+
+use rstd::{prelude::*, convert::TryFrom};
+use codec::{Encode, EncodeLike, Decode, Input, Output, Error};
+use sr_primitives::{RuntimeDebug, traits::{Zero, Saturating}};
+use support::metadata::{TypeInfo, Registry, TypeDef, Variant};
+use crate::{Conviction, ReferendumIndex};
+
+/// A number of lock periods, plus a vote, one way or the other.
+#[derive(Copy, Clone, Eq, PartialEq, Default, RuntimeDebug)]
+pub struct Vote {
+	/// Whether the vote is in favour of the proposal.
+	pub aye: bool,
+	/// The conviction of the vote.
+	pub conviction: Conviction,
+}
+
+impl Encode for Vote {
+	fn encode_to<T: Output>(&self, output: &mut T) {
+		output.push_byte(u8::from(self.conviction) | if self.aye { 0b1000_0000 } else { 0 });
+	}
+}
+
+impl EncodeLike for Vote {}
+
+// A vote is a single byte: the conviction, with the highest bit set for an aye.
+impl TypeInfo for Vote {
+	fn path() -> Vec<&'static str> {
+		module_path!().split("::").chain(rstd::iter::once("Vote")).collect()
+	}
+
+	fn type_def(_: &mut Registry) -> TypeDef {
+		let nays = [
+			"NayNone", "NayLocked1x", "NayLocked2x", "NayLocked3x", "NayLocked4x", "NayLocked5x", "NayLocked6x",
+		];
+		let ayes = [
+			"AyeNone", "AyeLocked1x", "AyeLocked2x", "AyeLocked3x", "AyeLocked4x", "AyeLocked5x", "AyeLocked6x",
+		];
+		let nays = nays.iter().enumerate().map(|(conviction, name)| (*name, conviction as u8));
+		let ayes = ayes.iter().enumerate().map(|(conviction, name)| (*name, 0b1000_0000 | conviction as u8));
+		TypeDef::Variant(nays.chain(ayes).map(|(name, index)| Variant::new(name, index, Vec::new())).collect())
+	}
+}
+
+impl Decode for Vote {
+	fn decode<I: Input>(input: &mut I) -> core::result::Result<Self, Error> {
+		let b = input.read_byte()?;
+		Ok(Vote {
+			aye: (b & 0b1000_0000) == 0b1000_0000,
+			conviction: Conviction::try_from(b & 0b0111_1111)
+				.map_err(|_| Error::from("Invalid conviction"))?,
+		})
+	}
+}
+
+/// A vote for a referendum of a particular account.
+#[derive(Encode, Decode, Copy, Clone, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub enum AccountVote<Balance> {
+	/// A standard vote, one-way (approve or reject) with a given amount of conviction.
+	Standard { vote: Vote, balance: Balance },
+	/// A split vote with balances given for both ways, and with no conviction. This is useful
+	/// for custodial accounts voting on behalf of several parties.
+	Split { aye: Balance, nay: Balance },
+}
+
+impl<Balance: Saturating + Copy> AccountVote<Balance> {
+	/// Returns `Some` of the lock periods and the balance that the account must keep locked
+	/// after the end of a referendum with the given outcome, if any.
+	pub fn locked_if(self, approved: bool) -> Option<(u32, Balance)> {
+		// winning side: locked for the lock periods of the conviction. losing side and split
+		// votes: unlocked as soon as the referendum is over.
+		match self {
+			AccountVote::Standard { vote, balance } if vote.aye == approved =>
+				Some((vote.conviction.lock_periods(), balance)),
+			_ => None,
+		}
+	}
+
+	/// The total balance involved in this vote.
+	pub fn balance(self) -> Balance {
+		match self {
+			AccountVote::Standard { balance, .. } => balance,
+			AccountVote::Split { aye, nay } => aye.saturating_add(nay),
+		}
+	}
+}
+
+/// A "prior" lock, i.e. the lock left over by votes on referenda that are over: the block
+/// until which it holds and the locked amount.
+#[derive(Encode, Decode, Default, Copy, Clone, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct PriorLock<BlockNumber, Balance>(BlockNumber, Balance);
+
+impl<BlockNumber: Ord + Copy + Zero, Balance: Ord + Copy + Zero> PriorLock<BlockNumber, Balance> {
+	/// Accumulates an additional lock, keeping the latest end and the highest amount.
+	pub fn accumulate(&mut self, until: BlockNumber, amount: Balance) {
+		self.0 = self.0.max(until);
+		self.1 = self.1.max(amount);
+	}
+
+	/// The amount that must stay locked.
+	pub fn locked(&self) -> Balance {
+		self.1
+	}
+
+	/// Drop the lock if it is over at `now`.
+	pub fn rejig(&mut self, now: BlockNumber) {
+		if now >= self.0 {
+			self.0 = Zero::zero();
+			self.1 = Zero::zero();
+		}
+	}
+}
+
+/// The votes of an account, along with the lock left over by its votes on finished referenda.
+#[derive(Encode, Decode, Default, Clone, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct Voting<Balance, BlockNumber> {
+	/// The current votes of the account, sorted by referendum index.
+	pub votes: Vec<(ReferendumIndex, AccountVote<Balance>)>,
+	/// The lock left over by removed votes.
+	pub prior: PriorLock<BlockNumber, Balance>,
+}
+
+impl<Balance: Saturating + Ord + Zero + Copy, BlockNumber: Ord + Copy + Zero>
+	Voting<Balance, BlockNumber>
+{
+	/// The balance that must stay locked for the votes of the account.
+	pub fn locked_balance(&self) -> Balance {
+		self.votes.iter()
+			.map(|(_, vote)| vote.balance())
+			.fold(self.prior.locked(), |a, b| a.max(b))
+	}
+
+	/// Whether nothing is recorded for this account anymore.
+	pub fn is_empty(&self) -> bool {
+		self.votes.is_empty() && self.prior.locked().is_zero()
+	}
+}
@@ -39,7 +39,9 @@
 //! their votes at any time by calling `vote()` again. This keeps the bond untouched but can
 //! optionally change the locked `value`. After a round, votes are kept and might still be valid for
 //! further rounds. A voter is responsible for calling `remove_voter` once they are done to have
-//! their bond back and remove the lock.
+//! their bond back and remove the lock. The bond is not tied to any term: the amount reserved upon
+//! the first vote is recorded and is exactly what is returned, even if `VotingBond` has changed in
+//! the meantime.
 //!
 //! Voters also report other voters as being defunct to earn their bond. A voter is defunct once all
 //! of the candidates that they have voted for are neither a valid candidate anymore nor a member.
@@ -50,8 +52,9 @@
 //!
 //! ### Candidacy and Members
 //!
-//! Candidates also reserve a bond as they submit candidacy. A candidate cannot take their candidacy
-//! back. A candidate can end up in one of the below situations:
+//! Candidates also reserve a bond as they submit candidacy. As for voters, the amount reserved is
+//! recorded and is exactly what is later returned or slashed. A candidate can end up in one of the
+//! below situations:
 //!	  - **Winner**: A winner is kept as a _member_. They must still have a bond in reserve and they
 //!		are automatically counted as a candidate for the next election.
 //!   - **Loser**: Any of the candidate who are not a winner are left as losers. A loser might be an
@@ -60,7 +63,11 @@
 //!		the loser candidate.
 //!   - **Runner-up**: Runners-up are the best candidates immediately after the winners. The number
 //!		of runners_up to keep is configurable. Runners-up are used, in order that they are elected,
-//! 	as replacements when a candidate is kicked by `remove_member()`.
+//! 	as replacements when a member is kicked by `remove_member()` or renounces.
+//!
+//! A candidate, runner-up or member can take their candidacy back at any time with
+//! `renounce_candidacy()`, which returns their bond. A renouncing member is replaced by the best
+//! runner-up, if any.
 //!
 //! Note that with the members being the default candidates for the next round and votes persisting
 //! in storage, the election system is entirely stable given no further input. This means that if
@@ -157,6 +164,12 @@ decl_storage! {
 		pub VotesOf get(fn votes_of): linked_map T::AccountId => Vec<T::AccountId>;
 		/// Locked stake of a voter.
 		pub StakeOf get(fn stake_of): map T::AccountId => BalanceOf<T>;
+		/// The bond reserved from a voter upon their first vote. Returned in full once they are
+		/// removed as a voter.
+		pub VotingBondOf get(fn voting_bond_of): map T::AccountId => Option<BalanceOf<T>>;
+		/// The bond reserved from a candidate upon submitting their candidacy. Returned or slashed
+		/// in full once they are no longer a candidate, runner-up or member.
+		pub CandidacyBondOf get(fn candidacy_bond_of): map T::AccountId => Option<BalanceOf<T>>;
 
 		/// The present candidate list. Sorted based on account id. A current member can never enter
 		/// this vector and is always implicitly assumed to be a candidate.
@@ -215,8 +228,10 @@ decl_module! {
 
 			if !Self::is_voter(&who) {
 				// first time voter. Reserve bond.
-				T::Currency::reserve(&who, T::VotingBond::get())
+				let bond = T::VotingBond::get();
+				T::Currency::reserve(&who, bond)
 					.map_err(|_| "voter can not pay voting bond")?;
+				<VotingBondOf<T>>::insert(&who, bond);
 			}
 			// Amount to be locked up.
 			let locked_balance = value.min(T::Currency::total_balance(&who));
@@ -278,12 +293,13 @@ decl_module! {
 			let valid = Self::is_defunct_voter(&target);
 			if valid {
 				// reporter will get the voting bond of the target
-				T::Currency::repatriate_reserved(&target, &reporter, T::VotingBond::get())?;
+				T::Currency::repatriate_reserved(&target, &reporter, Self::voter_bond(&target))?;
 				// remove the target. They are defunct.
 				Self::do_remove_voter(&target, false);
 			} else {
 				// slash the bond of the reporter.
-				let imbalance = T::Currency::slash_reserved(&reporter, T::VotingBond::get()).0;
+				let bond = Self::voter_bond(&reporter);
+				let imbalance = T::Currency::slash_reserved(&reporter, bond).0;
 				T::BadReport::on_unbalanced(imbalance);
 				// remove the reporter.
 				Self::do_remove_voter(&reporter, false);
@@ -316,13 +332,64 @@ decl_module! {
 
 			ensure!(!Self::is_member(&who), "member cannot re-submit candidacy");
 
-			T::Currency::reserve(&who, T::CandidacyBond::get())
+			let bond = T::CandidacyBond::get();
+			T::Currency::reserve(&who, bond)
 				.map_err(|_| "candidate does not have enough funds")?;
 
+			<CandidacyBondOf<T>>::insert(&who, bond);
 			<Candidates<T>>::mutate(|c| c.insert(index, who));
 		}
 
-		/// Remove a particular member from the set. This is effective immediately.
+		/// Renounce one's intention to be a candidate for the next election round. 3 potential
+		/// outcomes exist:
+		///   - `origin` is a candidate and not elected in any set. In this case, the bond is
+		///     unreserved, returned and origin is removed as a candidate.
+		///   - `origin` is a current runner up. In this case, the bond is unreserved, returned and
+		///     origin is removed as a runner.
+		///   - `origin` is a current member. In this case, the bond is unreserved and origin is
+		///     removed as a member, consequently not being a candidate for the next round anymore.
+		///     Similar to `remove_member`, if a runner-up is available, the best runner-up replaces
+		///     the outgoing member immediately. Otherwise, the seat is left empty until the next
+		///     round.
+		///
+		/// # <weight>
+		/// #### State
+		/// Reads: O(LogN) Given N candidates.
+		/// Writes: O(1)
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(2_000_000)]
+		fn renounce_candidacy(origin) {
+			let who = ensure_signed(origin)?;
+
+			// NOTE: the three conditions are attempted in turn and this fails if none of them
+			// match. Only one of them can ever match, as the sets never overlap.
+			if Self::remove_and_replace_member(&who).is_ok() {
+				T::Currency::unreserve(&who, Self::take_candidacy_bond(&who));
+				Self::deposit_event(RawEvent::MemberRenounced(who));
+				return Ok(());
+			}
+
+			let mut runners_up = Self::runners_up();
+			if let Some(index) = runners_up.iter().position(|(ref r, ref _s)| r == &who) {
+				runners_up.remove(index);
+				T::Currency::unreserve(&who, Self::take_candidacy_bond(&who));
+				<RunnersUp<T>>::put(runners_up);
+				return Ok(());
+			}
+
+			let mut candidates = Self::candidates();
+			if let Ok(index) = candidates.binary_search(&who) {
+				candidates.remove(index);
+				T::Currency::unreserve(&who, Self::take_candidacy_bond(&who));
+				<Candidates<T>>::put(candidates);
+				return Ok(());
+			}
+
+			Err("origin is not a candidate, member or a runner up")?
+		}
+
+		/// Remove a particular member from the set. This is effective immediately and the bond of
+		/// the outgoing member is slashed.
 		///
 		/// If a runner-up is available, then the best runner-up will be removed and replaces the
 		/// outgoing member. Otherwise, a new phragmen round is started.
@@ -339,39 +406,13 @@ decl_module! {
 			ensure_root(origin)?;
 			let who = T::Lookup::lookup(who)?;
 
-			let mut members_with_stake = Self::members();
-			if let Ok(index) = members_with_stake.binary_search_by(|(ref m, ref _s)| m.cmp(&who)) {
-				// remove, slash, emit event.
-				members_with_stake.remove(index);
-				let (imbalance, _) = T::Currency::slash_reserved(&who, T::CandidacyBond::get());
+			if let Ok(had_replacement) = Self::remove_and_replace_member(&who) {
+				// slash, emit event.
+				let (imbalance, _) = T::Currency::slash_reserved(&who, Self::take_candidacy_bond(&who));
 				T::KickedMember::on_unbalanced(imbalance);
-				Self::deposit_event(RawEvent::MemberKicked(who.clone()));
-
-				let mut runners_up = Self::runners_up();
-				if let Some((replacement, stake)) = runners_up.pop() {
-					// replace the outgoing with the best runner up.
-					if let Err(index) = members_with_stake
-						.binary_search_by(|(ref m, ref _s)| m.cmp(&replacement))
-					{
-						members_with_stake.insert(index, (replacement.clone(), stake));
-						ElectionRounds::mutate(|v| *v += 1);
-						T::ChangeMembers::change_members_sorted(
-							&[replacement],
-							&[who],
-							&members_with_stake
-								.iter()
-								.map(|(m, _)| m.clone())
-								.collect::<Vec<T::AccountId>>(),
-						);
-					}
-					// else it would mean that the runner up was already a member. This cannot
-					// happen. If it does, not much that we can do about it.
-
-					<Members<T>>::put(members_with_stake);
-					<RunnersUp<T>>::put(runners_up);
-				} else {
-					// update `Members` storage -- `do_phragmen` adds this to the candidate list.
-					<Members<T>>::put(members_with_stake);
+				Self::deposit_event(RawEvent::MemberKicked(who));
+
+				if !had_replacement {
 					// trigger a new phragmen. grab a cup of coffee. This might take a while.
 					Self::do_phragmen();
 				}
@@ -405,6 +446,8 @@ decl_event!(
 		/// A member has been removed. This should always be followed by either `NewTerm` ot
 		/// `EmptyTerm`.
 		MemberKicked(AccountId),
+		/// A member has renounced their candidacy.
+		MemberRenounced(AccountId),
 		/// A voter (first element) was reported (byt the second element) with the the report being
 		/// successful or not (third element).
 		VoterReported(AccountId, AccountId, bool),
@@ -474,18 +517,75 @@ impl<T: Trait> Module<T> {
 		}
 	}
 
+	/// Remove `who` from the members, replacing them with the best runner-up if one exists.
+	///
+	/// The new membership is reported through `ChangeMembers`. Returns `Err(())` if `who` is not a
+	/// member, and otherwise whether a replacement was found. The bond of `who` is left untouched.
+	///
+	/// State: O(1) given the bounded number of members and runners-up.
+	fn remove_and_replace_member(who: &T::AccountId) -> Result<bool, ()> {
+		let mut members_with_stake = Self::members();
+		let index = members_with_stake
+			.binary_search_by(|(ref m, ref _s)| m.cmp(who))
+			.map_err(|_| ())?;
+		members_with_stake.remove(index);
+
+		// replace the outgoing with the best runner up.
+		let next_up = <RunnersUp<T>>::mutate(|runners_up| runners_up.pop());
+		let maybe_replacement = next_up.and_then(|(replacement, stake)|
+			// if the runner up is already a member, which cannot happen, there is not much that
+			// we can do about it.
+			members_with_stake
+				.binary_search_by(|(ref m, ref _s)| m.cmp(&replacement))
+				.err()
+				.map(|index| {
+					members_with_stake.insert(index, (replacement.clone(), stake));
+					replacement
+				})
+		);
+		if maybe_replacement.is_some() {
+			ElectionRounds::mutate(|v| *v += 1);
+		}
+
+		<Members<T>>::put(&members_with_stake);
+		let members = members_with_stake.into_iter().map(|(m, _)| m).collect::<Vec<T::AccountId>>();
+		let incoming = maybe_replacement.iter().cloned().collect::<Vec<T::AccountId>>();
+		T::ChangeMembers::change_members_sorted(&incoming, &[who.clone()], &members);
+
+		Ok(!incoming.is_empty())
+	}
+
+	/// The voting bond reserved from `who`.
+	///
+	/// Voters from before the bond was recorded are assumed to have reserved the current
+	/// `VotingBond`.
+	fn voter_bond(who: &T::AccountId) -> BalanceOf<T> {
+		Self::voting_bond_of(who).unwrap_or_else(T::VotingBond::get)
+	}
+
+	/// Remove the record of the candidacy bond reserved from `who`, and return its amount.
+	///
+	/// Candidates, runners-up and members from before the bond was recorded are assumed to have
+	/// reserved the current `CandidacyBond`.
+	fn take_candidacy_bond(who: &T::AccountId) -> BalanceOf<T> {
+		<CandidacyBondOf<T>>::take(who).unwrap_or_else(T::CandidacyBond::get)
+	}
+
 	/// Remove a certain someone as a voter.
 	///
 	/// This will clean always clean the storage associated with the voter, and remove the balance
 	/// lock. Optionally, it would also return the reserved voting bond if indicated by `unreserve`.
 	fn do_remove_voter(who: &T::AccountId, unreserve: bool) {
+		let bond = Self::voter_bond(who);
+
 		// remove storage and lock.
 		<VotesOf<T>>::remove(who);
 		<StakeOf<T>>::remove(who);
+		<VotingBondOf<T>>::remove(who);
 		T::Currency::remove_lock(MODULE_ID, who);
 
 		if unreserve {
-			T::Currency::unreserve(who, T::VotingBond::get());
+			T::Currency::unreserve(who, bond);
 		}
 	}
 
@@ -589,6 +689,9 @@ impl<T: Trait> Module<T> {
 				.rev()
 				.collect::<Vec<(T::AccountId, BalanceOf<T>)>>();
 
+			// the member with the most backing stake becomes the prime member.
+			let prime = new_members.iter().max_by_key(|(_, stake)| *stake).map(|(m, _)| m.clone());
+
 			// sort and save the members.
 			new_members.sort();
 			<Members<T>>::put(&new_members);
@@ -606,6 +709,7 @@ impl<T: Trait> Module<T> {
 				&outgoing.clone(),
 				&Self::members_ids(),
 			);
+			T::ChangeMembers::set_prime(prime);
 
 			// unlike exposed_candidates, these are members who were in the list and no longer
 			// exist. They must get their bond back.
@@ -618,7 +722,7 @@ impl<T: Trait> Module<T> {
 				if new_members.binary_search_by_key(&c, |(m, _)| m.clone()).is_err()
 					&& !Self::runners_up_ids().contains(&c)
 				{
-					let (imbalance, _) = T::Currency::slash_reserved(&c, T::CandidacyBond::get());
+					let (imbalance, _) = T::Currency::slash_reserved(&c, Self::take_candidacy_bond(&c));
 					T::LoserCandidate::on_unbalanced(imbalance);
 				}
 			});
@@ -629,7 +733,7 @@ impl<T: Trait> Module<T> {
 
 		// unreserve the bond of all the outgoings.
 		to_release_bond.iter().for_each(|m| {
-			T::Currency::unreserve(&m, T::CandidacyBond::get());
+			T::Currency::unreserve(&m, Self::take_candidacy_bond(&m));
 		});
 
 		// clean candidates.
@@ -720,15 +824,19 @@ mod tests {
 		type CreationFee = CreationFee;
 	}
 
-	parameter_types! {
-		pub const CandidacyBond: u64 = 3;
-	}
-
 	thread_local! {
+		static CANDIDACY_BOND: RefCell<u64> = RefCell::new(3);
 		static VOTING_BOND: RefCell<u64> = RefCell::new(2);
 		static DESIRED_MEMBERS: RefCell<u32> = RefCell::new(2);
 		static DESIRED_RUNNERS_UP: RefCell<u32> = RefCell::new(2);
 		static TERM_DURATION: RefCell<u64> = RefCell::new(5);
+		static MEMBERS: RefCell<Vec<u64>> = RefCell::new(vec![]);
+		static PRIME: RefCell<Option<u64>> = RefCell::new(None);
+	}
+
+	pub struct CandidacyBond;
+	impl Get<u64> for CandidacyBond {
+		fn get() -> u64 { CANDIDACY_BOND.with(|v| *v.borrow()) }
 	}
 
 	pub struct VotingBond;
@@ -753,7 +861,12 @@ mod tests {
 
 	pub struct TestChangeMembers;
 	impl ChangeMembers<u64> for TestChangeMembers {
-		fn change_members_sorted(_: &[u64], _: &[u64], _: &[u64]) {}
+		fn change_members_sorted(_: &[u64], _: &[u64], new: &[u64]) {
+			MEMBERS.with(|m| *m.borrow_mut() = new.to_vec());
+		}
+		fn set_prime(who: Option<u64>) {
+			PRIME.with(|p| *p.borrow_mut() = who);
+		}
 	}
 
 	/// Simple structure that exposes how u64 currency can be represented as... u64.
@@ -842,7 +955,6 @@ mod tests {
 						(5, 50 * self.balance_factor),
 						(6, 60 * self.balance_factor)
 					],
-					vesting: vec![],
 				}),
 			}.build_storage().unwrap().into()
 		}
@@ -856,6 +968,10 @@ mod tests {
 		(Balances::free_balance(who), Balances::reserved_balance(who))
 	}
 
+	fn reported_members() -> Vec<u64> {
+		MEMBERS.with(|m| m.borrow().clone())
+	}
+
 	fn has_lock(who: &u64) -> u64 {
 		let lock = Balances::locks(who)[0].clone();
 		assert_eq!(lock.id, MODULE_ID);
@@ -1172,6 +1288,31 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn remove_voter_returns_the_bond_reserved_at_first_vote() {
+		ExtBuilder::default().voter_bond(8).build().execute_with(|| {
+			assert_ok!(Elections::submit_candidacy(Origin::signed(5)));
+			assert_ok!(Elections::vote(Origin::signed(2), vec![5], 20));
+			assert_eq!(balances(&2), (12, 8));
+			assert_eq!(Elections::voting_bond_of(2), Some(8));
+
+			// the bond changes in the meantime, and voting again does not touch the bond.
+			VOTING_BOND.with(|v| *v.borrow_mut() = 2);
+			assert_ok!(Elections::vote(Origin::signed(2), vec![5], 10));
+			assert_eq!(balances(&2), (12, 8));
+
+			System::set_block_number(5);
+			assert_ok!(Elections::end_block(System::block_number()));
+			assert_eq!(Elections::members_ids(), vec![5]);
+
+			// still reserved in the next term, and returned in full.
+			assert_eq!(balances(&2), (12, 8));
+			assert_ok!(Elections::remove_voter(Origin::signed(2)));
+			assert_eq!(balances(&2), (20, 0));
+			assert_eq!(Elections::voting_bond_of(2), None);
+		});
+	}
+
 	#[test]
 	fn non_voter_remove_should_not_work() {
 		ExtBuilder::default().build().execute_with(|| {
@@ -1354,6 +1495,25 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn prime_is_the_member_with_most_backing_stake() {
+		ExtBuilder::default().build().execute_with(|| {
+			assert_ok!(Elections::submit_candidacy(Origin::signed(5)));
+			assert_ok!(Elections::submit_candidacy(Origin::signed(4)));
+			assert_ok!(Elections::submit_candidacy(Origin::signed(3)));
+
+			assert_ok!(Elections::vote(Origin::signed(2), vec![5], 20));
+			assert_ok!(Elections::vote(Origin::signed(4), vec![4], 15));
+			assert_ok!(Elections::vote(Origin::signed(3), vec![3], 30));
+
+			System::set_block_number(5);
+			assert_ok!(Elections::end_block(System::block_number()));
+
+			assert_eq!(Elections::members(), vec![(3, 30), (5, 20)]);
+			assert_eq!(PRIME.with(|p| *p.borrow()), Some(3));
+		});
+	}
+
 	#[test]
 	fn defunct_voter_will_be_counted() {
 		ExtBuilder::default().build().execute_with(|| {
@@ -1598,6 +1758,34 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn remove_member_promotes_best_runner_up() {
+		ExtBuilder::default().desired_runners_up(2).build().execute_with(|| {
+			assert_ok!(Elections::submit_candidacy(Origin::signed(5)));
+			assert_ok!(Elections::submit_candidacy(Origin::signed(4)));
+			assert_ok!(Elections::submit_candidacy(Origin::signed(3)));
+			assert_ok!(Elections::submit_candidacy(Origin::signed(2)));
+
+			assert_ok!(Elections::vote(Origin::signed(2), vec![2], 20));
+			assert_ok!(Elections::vote(Origin::signed(3), vec![3], 30));
+			assert_ok!(Elections::vote(Origin::signed(4), vec![4], 40));
+			assert_ok!(Elections::vote(Origin::signed(5), vec![5], 50));
+
+			System::set_block_number(5);
+			assert_ok!(Elections::end_block(System::block_number()));
+			assert_eq!(Elections::members_ids(), vec![4, 5]);
+			assert_eq!(Elections::runners_up_ids(), vec![2, 3]);
+			assert_eq!(reported_members(), vec![4, 5]);
+
+			assert_ok!(Elections::remove_member(Origin::ROOT, 4));
+
+			assert_eq!(balances(&4), (35, 2)); // slashed
+			assert_eq!(Elections::members(), vec![(3, 30), (5, 50)]);
+			assert_eq!(Elections::runners_up_ids(), vec![2]);
+			assert_eq!(reported_members(), vec![3, 5]);
+		});
+	}
+
 	#[test]
 	fn seats_should_be_released_when_no_vote() {
 		ExtBuilder::default().build().execute_with(|| {
@@ -1681,6 +1869,38 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn candidacy_bond_reserved_at_submission_is_returned_or_slashed() {
+		ExtBuilder::default().build().execute_with(|| {
+			assert_ok!(Elections::submit_candidacy(Origin::signed(5)));
+			assert_ok!(Elections::submit_candidacy(Origin::signed(3)));
+			assert_ok!(Elections::vote(Origin::signed(3), vec![5], 20));
+			assert_ok!(Elections::vote(Origin::signed(4), vec![5], 40));
+			assert_eq!(balances(&3), (25, 5));
+			assert_eq!(Elections::candidacy_bond_of(3), Some(3));
+
+			// the bond changes in the meantime.
+			CANDIDACY_BOND.with(|v| *v.borrow_mut() = 4);
+			assert_ok!(Elections::submit_candidacy(Origin::signed(2)));
+			assert_eq!(balances(&2), (16, 4));
+			assert_ok!(Elections::renounce_candidacy(Origin::signed(2)));
+			assert_eq!(balances(&2), (20, 0));
+			assert_eq!(Elections::candidacy_bond_of(2), None);
+
+			System::set_block_number(5);
+			assert_ok!(Elections::end_block(System::block_number()));
+			assert_eq!(Elections::members_ids(), vec![5]);
+
+			// the loser loses its candidacy bond only, and keeps its voting bond.
+			assert_eq!(balances(&3), (25, 2));
+			assert_eq!(Elections::candidacy_bond_of(3), None);
+
+			assert_ok!(Elections::renounce_candidacy(Origin::signed(5)));
+			assert_eq!(balances(&5), (50, 0));
+			assert_eq!(Elections::candidacy_bond_of(5), None);
+		});
+	}
+
 	#[test]
 	fn incoming_outgoing_are_reported() {
 		ExtBuilder::default().build().execute_with(|| {
@@ -1747,6 +1967,84 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn candidate_can_renounce() {
+		ExtBuilder::default().build().execute_with(|| {
+			assert_ok!(Elections::submit_candidacy(Origin::signed(5)));
+			assert_ok!(Elections::submit_candidacy(Origin::signed(4)));
+			assert_eq!(balances(&5), (47, 3));
+
+			assert_ok!(Elections::renounce_candidacy(Origin::signed(5)));
+			assert_eq!(balances(&5), (50, 0));
+			assert_eq!(Elections::candidates(), vec![4]);
+		});
+	}
+
+	#[test]
+	fn runner_up_can_renounce() {
+		ExtBuilder::default().desired_runners_up(2).build().execute_with(|| {
+			assert_ok!(Elections::submit_candidacy(Origin::signed(5)));
+			assert_ok!(Elections::submit_candidacy(Origin::signed(4)));
+			assert_ok!(Elections::submit_candidacy(Origin::signed(3)));
+
+			assert_ok!(Elections::vote(Origin::signed(3), vec![3], 30));
+			assert_ok!(Elections::vote(Origin::signed(4), vec![4], 40));
+			assert_ok!(Elections::vote(Origin::signed(5), vec![5], 50));
+
+			System::set_block_number(5);
+			assert_ok!(Elections::end_block(System::block_number()));
+			assert_eq!(Elections::members_ids(), vec![4, 5]);
+			assert_eq!(Elections::runners_up_ids(), vec![3]);
+
+			assert_eq!(balances(&3), (25, 5));
+			assert_ok!(Elections::renounce_candidacy(Origin::signed(3)));
+			assert_eq!(balances(&3), (28, 2));
+			assert_eq!(Elections::runners_up_ids(), vec![]);
+			assert_eq!(Elections::members_ids(), vec![4, 5]);
+		});
+	}
+
+	#[test]
+	fn member_can_renounce_and_runner_up_is_promoted() {
+		ExtBuilder::default().desired_runners_up(2).build().execute_with(|| {
+			assert_ok!(Elections::submit_candidacy(Origin::signed(5)));
+			assert_ok!(Elections::submit_candidacy(Origin::signed(4)));
+			assert_ok!(Elections::submit_candidacy(Origin::signed(3)));
+
+			assert_ok!(Elections::vote(Origin::signed(3), vec![3], 30));
+			assert_ok!(Elections::vote(Origin::signed(4), vec![4], 40));
+			assert_ok!(Elections::vote(Origin::signed(5), vec![5], 50));
+
+			System::set_block_number(5);
+			assert_ok!(Elections::end_block(System::block_number()));
+			assert_eq!(Elections::members_ids(), vec![4, 5]);
+			assert_eq!(Elections::runners_up_ids(), vec![3]);
+
+			assert_ok!(Elections::renounce_candidacy(Origin::signed(4)));
+			// bond returned, not slashed.
+			assert_eq!(balances(&4), (38, 2));
+			assert_eq!(Elections::members_ids(), vec![3, 5]);
+			assert_eq!(Elections::runners_up_ids(), vec![]);
+			assert_eq!(reported_members(), vec![3, 5]);
+
+			// without a runner-up, the seat is left empty until the next round.
+			assert_ok!(Elections::renounce_candidacy(Origin::signed(3)));
+			assert_eq!(balances(&3), (28, 2));
+			assert_eq!(Elections::members_ids(), vec![5]);
+			assert_eq!(reported_members(), vec![5]);
+		});
+	}
+
+	#[test]
+	fn non_candidate_cannot_renounce() {
+		ExtBuilder::default().build().execute_with(|| {
+			assert_noop!(
+				Elections::renounce_candidacy(Origin::signed(5)),
+				"origin is not a candidate, member or a runner up"
+			);
+		});
+	}
+
 	#[test]
 	fn members_are_sorted_based_on_id_runners_on_merit() {
 		ExtBuilder::default().desired_runners_up(2).build().execute_with(|| {
@@ -31,7 +31,7 @@ use sr_primitives::{
 	weights::SimpleDispatchInfo,
 };
 use support::{
-	dispatch::Result, decl_storage, decl_event, ensure, decl_module,
+	dispatch::Result, decl_storage, decl_event, ensure, decl_module, metadata::TypeInfo,
 	traits::{
 		Currency, ExistenceRequirement, Get, LockableCurrency, LockIdentifier,
 		OnUnbalanced, ReservableCurrency, WithdrawReason, WithdrawReasons, ChangeMembers
@@ -101,7 +101,7 @@ mod tests;
 // entries before they increase the capacity.
 
 /// The activity status of a voter.
-#[derive(PartialEq, Eq, Copy, Clone, Encode, Decode, Default, RuntimeDebug)]
+#[derive(PartialEq, Eq, Copy, Clone, Encode, Decode, Default, RuntimeDebug, TypeInfo)]
 pub struct VoterInfo<Balance> {
 	/// Last VoteIndex in which this voter assigned (or initialized) approvals.
 	last_active: VoteIndex,
@@ -84,6 +84,7 @@ use sr_primitives::{
 	transaction_validity::TransactionValidity,
 };
 use codec::{Codec, Encode};
+use support::storage::StorageValue;
 use system::{extrinsics_root, DigestOf};
 
 /// Trait that can be used to execute a block.
@@ -105,7 +106,11 @@ impl<
 	Block: traits::Block<Header=System::Header, Hash=System::Hash>,
 	Context: Default,
 	UnsignedValidator,
-	AllModules: OnInitialize<System::BlockNumber> + OnFinalize<System::BlockNumber> + OffchainWorker<System::BlockNumber>,
+	AllModules:
+		OnRuntimeUpgrade +
+		OnInitialize<System::BlockNumber> +
+		OnFinalize<System::BlockNumber> +
+		OffchainWorker<System::BlockNumber>,
 > ExecuteBlock<Block> for Executive<System, Block, Context, UnsignedValidator, AllModules>
 where
 	Block::Extrinsic: Checkable<Context> + Codec,
@@ -124,7 +129,11 @@ impl<
 	Block: traits::Block<Header=System::Header, Hash=System::Hash>,
 	Context: Default,
 	UnsignedValidator,
-	AllModules: OnInitialize<System::BlockNumber> + OnFinalize<System::BlockNumber> + OffchainWorker<System::BlockNumber>,
+	AllModules:
+		OnRuntimeUpgrade +
+		OnInitialize<System::BlockNumber> +
+		OnFinalize<System::BlockNumber> +
+		OffchainWorker<System::BlockNumber>,
 > Executive<System, Block, Context, UnsignedValidator, AllModules>
 where
 	Block::Extrinsic: Checkable<Context> + Codec,
@@ -147,9 +156,28 @@ where
 		digest: &Digest<System::Hash>,
 	) {
 		<system::Module<System>>::initialize(block_number, parent_hash, extrinsics_root, digest);
+		if Self::runtime_upgraded() {
+			let weight = <AllModules as OnRuntimeUpgrade>::on_runtime_upgrade();
+			<system::Module<System>>::register_extra_weight_unchecked(weight);
+		}
 		<AllModules as OnInitialize<System::BlockNumber>>::on_initialize(*block_number);
 	}
 
+	/// Returns whether the runtime's `spec_version` changed since the last executed block, and
+	/// records the current one.
+	///
+	/// The version is recorded at genesis, so a missing one means that the chain predates this
+	/// check and that the runtime introducing it is an upgrade.
+	fn runtime_upgraded() -> bool {
+		let spec_version = <system::Module<System>>::runtime_version().spec_version;
+		if <system::Module<System>>::last_runtime_upgrade() == Some(spec_version) {
+			return false;
+		}
+
+		system::LastRuntimeUpgrade::put(spec_version);
+		true
+	}
+
 	fn initial_checks(block: &Block) {
 		let header = block.header();
 
@@ -301,8 +329,10 @@ mod tests {
 	};
 	use support::{
 		impl_outer_event, impl_outer_origin, parameter_types, impl_outer_dispatch,
-		traits::{Currency, LockIdentifier, LockableCurrency, WithdrawReasons, WithdrawReason},
+		traits::{Currency, LockIdentifier, LockableCurrency, WithdrawReasons, WithdrawReason, Get},
 	};
+	use sr_version::RuntimeVersion;
+	use std::cell::RefCell;
 	use system::Call as SystemCall;
 	use balances::Call as BalancesCall;
 	use hex_literal::hex;
@@ -350,7 +380,21 @@ mod tests {
 		type MaximumBlockWeight = MaximumBlockWeight;
 		type AvailableBlockRatio = AvailableBlockRatio;
 		type MaximumBlockLength = MaximumBlockLength;
-		type Version = ();
+		type Version = TestVersion;
+	}
+
+	thread_local! {
+		static SPEC_VERSION: RefCell<u32> = RefCell::new(0);
+	}
+
+	pub struct TestVersion;
+	impl Get<RuntimeVersion> for TestVersion {
+		fn get() -> RuntimeVersion {
+			RuntimeVersion {
+				spec_version: SPEC_VERSION.with(|v| *v.borrow()),
+				..Default::default()
+			}
+		}
 	}
 	parameter_types! {
 		pub const ExistentialDeposit: u64 = 0;
@@ -400,7 +444,26 @@ mod tests {
 		transaction_payment::ChargeTransactionPayment<Runtime>
 	);
 	type TestXt = sr_primitives::testing::TestXt<Call, SignedExtra>;
-	type Executive = super::Executive<Runtime, Block<TestXt>, system::ChainContext<Runtime>, Runtime, ()>;
+	type Executive = super::Executive<
+		Runtime,
+		Block<TestXt>,
+		system::ChainContext<Runtime>,
+		Runtime,
+		CustomOnRuntimeUpgrade,
+	>;
+
+	const CUSTOM_ON_RUNTIME_KEY: &[u8] = b":custom:on_runtime";
+
+	pub struct CustomOnRuntimeUpgrade;
+	impl OnRuntimeUpgrade for CustomOnRuntimeUpgrade {
+		fn on_runtime_upgrade() -> Weight {
+			runtime_io::set_storage(CUSTOM_ON_RUNTIME_KEY, &true.encode());
+			100
+		}
+	}
+	impl OnInitialize<u64> for CustomOnRuntimeUpgrade {}
+	impl OnFinalize<u64> for CustomOnRuntimeUpgrade {}
+	impl OffchainWorker<u64> for CustomOnRuntimeUpgrade {}
 
 	fn extra(nonce: u64, fee: u64) -> SignedExtra {
 		(
@@ -420,7 +483,6 @@ mod tests {
 		let mut t = system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
 		balances::GenesisConfig::<Runtime> {
 			balances: vec![(1, 211)],
-			vesting: vec![],
 		}.assimilate_storage(&mut t).unwrap();
 		let xt = sr_primitives::testing::TestXt(sign_extra(1, 0, 0), Call::Balances(BalancesCall::transfer(2, 69)));
 		let weight = xt.get_dispatch_info().weight as u64;
@@ -444,7 +506,6 @@ mod tests {
 		let mut t = system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
 		balances::GenesisConfig::<Runtime> {
 			balances: vec![(1, 111 * balance_factor)],
-			vesting: vec![],
 		}.assimilate_storage(&mut t).unwrap();
 		t.into()
 	}
@@ -642,4 +703,48 @@ mod tests {
 		execute_with_lock(WithdrawReasons::all());
 		execute_with_lock(WithdrawReasons::except(WithdrawReason::TransactionPayment));
 	}
+
+	fn initialize_block_one() {
+		Executive::initialize_block(&Header::new(
+			1,
+			H256::default(),
+			H256::default(),
+			[69u8; 32].into(),
+			Digest::default(),
+		));
+	}
+
+	#[test]
+	fn runtime_upgraded_should_work() {
+		new_test_ext(1).execute_with(|| {
+			// The genesis version is recorded.
+			assert_eq!(<system::Module<Runtime>>::last_runtime_upgrade(), Some(0));
+			assert!(!Executive::runtime_upgraded());
+
+			SPEC_VERSION.with(|v| *v.borrow_mut() = 1);
+			assert!(Executive::runtime_upgraded());
+			assert_eq!(<system::Module<Runtime>>::last_runtime_upgrade(), Some(1));
+			assert!(!Executive::runtime_upgraded());
+
+			// A chain created before the version was recorded is upgraded.
+			system::LastRuntimeUpgrade::kill();
+			assert!(Executive::runtime_upgraded());
+			assert_eq!(<system::Module<Runtime>>::last_runtime_upgrade(), Some(1));
+			assert!(!Executive::runtime_upgraded());
+		});
+	}
+
+	#[test]
+	fn custom_runtime_upgrade_is_called_on_spec_version_change() {
+		new_test_ext(1).execute_with(|| {
+			initialize_block_one();
+			assert!(runtime_io::storage(CUSTOM_ON_RUNTIME_KEY).is_none());
+			assert_eq!(<system::Module<Runtime>>::all_extrinsics_weight(), 0);
+
+			SPEC_VERSION.with(|v| *v.borrow_mut() = 1);
+			initialize_block_one();
+			assert_eq!(runtime_io::storage(CUSTOM_ON_RUNTIME_KEY), Some(true.encode()));
+			assert_eq!(<system::Module<Runtime>>::all_extrinsics_weight(), 100);
+		});
+	}
 }
@@ -162,7 +162,7 @@ use rstd::prelude::*;
 use rstd::{cmp, result, fmt::Debug};
 use support::dispatch::Result;
 use support::{
-	decl_event, decl_module, decl_storage, ensure,
+	decl_event, decl_module, decl_storage, ensure, metadata::TypeInfo,
 	traits::{
 		Currency, ExistenceRequirement, Imbalance, LockIdentifier, LockableCurrency, ReservableCurrency,
 		SignedImbalance, UpdateBalanceOutcome, WithdrawReason, WithdrawReasons,
@@ -205,7 +205,7 @@ impl<T: Trait> Subtrait for T {
 }
 
 /// Asset creation options.
-#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo)]
 pub struct AssetOptions<Balance: HasCompact, AccountId> {
 	/// Initial issuance of this asset. All deposit to the creater of the asset.
 	#[codec(compact)]
@@ -215,7 +215,7 @@ pub struct AssetOptions<Balance: HasCompact, AccountId> {
 }
 
 /// Owner of an asset.
-#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo)]
 pub enum Owner<AccountId> {
 	/// No owner.
 	None,
@@ -230,7 +230,7 @@ impl<AccountId> Default for Owner<AccountId> {
 }
 
 /// Asset permissions
-#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo)]
 pub struct PermissionsV1<AccountId> {
 	/// Who have permission to update asset permission
 	pub update: Owner<AccountId>,
@@ -247,7 +247,9 @@ enum PermissionVersionNumber {
 }
 
 /// Versioned asset permission
-#[derive(Clone, PartialEq, Eq, RuntimeDebug)]
+///
+/// Encoded like a derived enum, so the derived `TypeInfo` describes the manual encoding.
+#[derive(Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
 pub enum PermissionVersions<AccountId> {
 	V1(PermissionsV1<AccountId>),
 }
@@ -433,7 +435,7 @@ decl_module! {
 	}
 }
 
-#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
 pub struct BalanceLock<Balance, BlockNumber> {
 	pub id: LockIdentifier,
 	pub amount: Balance,
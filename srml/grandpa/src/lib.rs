@@ -33,7 +33,7 @@ pub use substrate_finality_grandpa_primitives as fg_primitives;
 use rstd::prelude::*;
 use codec::{self as codec, Encode, Decode, Error};
 use support::{
-	decl_event, decl_storage, decl_module, dispatch::Result,
+	decl_event, decl_storage, decl_module, dispatch::Result, metadata::TypeInfo,
 };
 use sr_primitives::{
 	generic::{DigestItem, OpaqueDigestItemId}, traits::Zero, Perbill,
@@ -68,7 +68,7 @@ pub struct OldStoredPendingChange<N> {
 }
 
 /// A stored pending change.
-#[derive(Encode)]
+#[derive(Encode, TypeInfo)]
 pub struct StoredPendingChange<N> {
 	/// The block number this was scheduled at.
 	pub scheduled_at: N,
@@ -98,7 +98,7 @@ impl<N: Decode> Decode for StoredPendingChange<N> {
 /// Current state of the GRANDPA authority set. State transitions must happen in
 /// the same order of states defined below, e.g. `Paused` implies a prior
 /// `PendingPause`.
-#[derive(Decode, Encode)]
+#[derive(Decode, Encode, TypeInfo)]
 #[cfg_attr(test, derive(Debug, PartialEq))]
 pub enum StoredState<N> {
 	/// The current authority set is live, and GRANDPA is enabled.
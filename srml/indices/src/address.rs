@@ -18,9 +18,10 @@
 
 #[cfg(feature = "std")]
 use std::fmt;
-use rstd::convert::TryInto;
+use rstd::{prelude::*, convert::TryInto};
 use crate::Member;
 use codec::{Encode, Decode, Input, Output, Error};
+use support::metadata::{TypeInfo, Registry, TypeDef, TypeId, Field, Variant};
 
 /// An indices-aware address, which can be either a direct `AccountId` or
 /// an index.
@@ -120,6 +121,32 @@ impl<AccountId, AccountIndex> codec::EncodeLike for Address<AccountId, AccountIn
 	AccountIndex: Member + Encode + PartialOrd<AccountIndex> + Ord + Copy + From<u32> + TryInto<u32>,
 {}
 
+// Indices below `0xf0` are encoded as the variant byte itself. Rather than a variant per byte
+// value, they are all described by the field-less `ShortIndex` variant, listed at index `0x00`:
+// any first byte in `0x00..=0xef` is such an index and nothing follows it.
+impl<AccountId, AccountIndex> TypeInfo for Address<AccountId, AccountIndex> where
+	AccountId: Member + TypeInfo,
+	AccountIndex: Member + TypeInfo,
+{
+	fn path() -> Vec<&'static str> {
+		module_path!().split("::").chain(rstd::iter::once("Address")).collect()
+	}
+
+	fn params(registry: &mut Registry) -> Vec<TypeId> {
+		vec![registry.register::<AccountId>(), registry.register::<AccountIndex>()]
+	}
+
+	fn type_def(registry: &mut Registry) -> TypeDef {
+		TypeDef::Variant(vec![
+			Variant::new("ShortIndex", 0x00, Vec::new()),
+			Variant::new("Index16", 0xfc, vec![Field::unnamed(registry.register::<u16>())]),
+			Variant::new("Index32", 0xfd, vec![Field::unnamed(registry.register::<u32>())]),
+			Variant::new("Index", 0xfe, vec![Field::unnamed(registry.register::<AccountIndex>())]),
+			Variant::new("Id", 0xff, vec![Field::unnamed(registry.register::<AccountId>())]),
+		])
+	}
+}
+
 impl<AccountId, AccountIndex> Default for Address<AccountId, AccountIndex> where
 	AccountId: Member + Default,
 	AccountIndex: Member,
@@ -132,6 +159,7 @@ impl<AccountId, AccountIndex> Default for Address<AccountId, AccountIndex> where
 #[cfg(test)]
 mod tests {
 	use codec::{Encode, Decode};
+	use support::metadata::{Registry, TypeDef};
 
 	type Address = super::Address<[u8; 8], u32>;
 	fn index(i: u32) -> Address { super::Address::Index(i) }
@@ -155,4 +183,24 @@ mod tests {
 		compare(Some(index(0x10000)), &[253, 0, 0, 1, 0][..]);
 		compare(Some(id([42, 69, 42, 69, 42, 69, 42, 69])), &[255, 42, 69, 42, 69, 42, 69, 42, 69][..]);
 	}
+
+	#[test]
+	fn type_info_matches_the_encoding() {
+		let mut registry = Registry::new();
+		let address = registry.register::<Address>();
+		let types = registry.into_types();
+		let variants = match &types[address.0 as usize].def {
+			TypeDef::Variant(variants) => variants.clone(),
+			def => panic!("unexpected definition {:?}", def),
+		};
+		assert_eq!(variants.len(), 5);
+
+		let addresses = vec![(index(2), 0), (index(0xef), 0), (index(304), 1), (index(0x10000), 1), (id([42; 8]), 1)];
+		for (address, fields) in addresses {
+			let encoded = address.encode();
+			let byte = if encoded[0] < 0xf0 { 0x00 } else { encoded[0] };
+			let variant = variants.iter().find(|v| v.index == byte).expect("the variant byte is described");
+			assert_eq!(variant.fields.len(), fields);
+		}
+	}
 }
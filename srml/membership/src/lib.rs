@@ -45,6 +45,9 @@ pub trait Trait<I=DefaultInstance>: system::Trait {
 	/// Required origin for resetting membership.
 	type ResetOrigin: EnsureOrigin<Self::Origin>;
 
+	/// Required origin for setting or resetting the prime member.
+	type PrimeOrigin: EnsureOrigin<Self::Origin>;
+
 	/// The receiver of the signal for when the membership has been initialized. This happens pre-
 	/// genesis and will usually be the same as `MembershipChanged`. If you need to do something
 	/// different on initialization, then you can change this accordingly.
@@ -58,6 +61,9 @@ decl_storage! {
 	trait Store for Module<T: Trait<I>, I: Instance=DefaultInstance> as Membership {
 		/// The current membership, stored as an ordered Vec.
 		Members get(fn members): Vec<T::AccountId>;
+
+		/// The current prime member, if one exists.
+		Prime get(fn prime): Option<T::AccountId>;
 	}
 	add_extra_genesis {
 		config(members): Vec<T::AccountId>;
@@ -132,6 +138,7 @@ decl_module! {
 			<Members<T, I>>::put(&members);
 
 			T::MembershipChanged::change_members_sorted(&[], &[who], &members[..]);
+			Self::rejig_prime(&members);
 
 			Self::deposit_event(RawEvent::MemberRemoved);
 		}
@@ -160,6 +167,7 @@ decl_module! {
 				&[remove],
 				&members[..],
 			);
+			Self::rejig_prime(&members);
 
 			Self::deposit_event(RawEvent::MembersSwapped);
 		}
@@ -179,11 +187,54 @@ decl_module! {
 			members.sort();
 			<Members<T, I>>::mutate(|m| {
 				T::MembershipChanged::set_members_sorted(&members[..], m);
+				Self::rejig_prime(&members);
 				*m = members;
 			});
 
 			Self::deposit_event(RawEvent::MembersReset);
 		}
+
+		/// Set the prime member. Must be a current member.
+		///
+		/// May only be called from `PrimeOrigin` or root.
+		#[weight = SimpleDispatchInfo::FixedNormal(50_000)]
+		fn set_prime(origin, who: T::AccountId) {
+			T::PrimeOrigin::try_origin(origin)
+				.map(|_| ())
+				.or_else(ensure_root)
+				.map_err(|_| "bad origin")?;
+
+			Self::members().binary_search(&who).ok().ok_or("not a member")?;
+			<Prime<T, I>>::put(&who);
+			T::MembershipChanged::set_prime(Some(who));
+		}
+
+		/// Remove the prime member if it exists.
+		///
+		/// May only be called from `PrimeOrigin` or root.
+		#[weight = SimpleDispatchInfo::FixedNormal(50_000)]
+		fn clear_prime(origin) {
+			T::PrimeOrigin::try_origin(origin)
+				.map(|_| ())
+				.or_else(ensure_root)
+				.map_err(|_| "bad origin")?;
+
+			<Prime<T, I>>::kill();
+			T::MembershipChanged::set_prime(None);
+		}
+	}
+}
+
+impl<T: Trait<I>, I: Instance> Module<T, I> {
+	/// Clear the prime member if it is no longer part of `members`, otherwise hand it on again
+	/// since the receiver of `MembershipChanged` may have dropped it along with the change.
+	fn rejig_prime(members: &[T::AccountId]) {
+		if let Some(prime) = Self::prime() {
+			match members.binary_search(&prime) {
+				Ok(_) => T::MembershipChanged::set_prime(Some(prime)),
+				Err(_) => <Prime<T, I>>::kill(),
+			}
+		}
 	}
 }
 
@@ -241,6 +292,7 @@ mod tests {
 
 	thread_local! {
 		static MEMBERS: RefCell<Vec<u64>> = RefCell::new(vec![]);
+		static PRIME: RefCell<Option<u64>> = RefCell::new(None);
 	}
 
 	pub struct TestChangeMembers;
@@ -255,6 +307,10 @@ mod tests {
 			assert_eq!(old_plus_incoming, new_plus_outgoing);
 
 			MEMBERS.with(|m| *m.borrow_mut() = new.to_vec());
+			PRIME.with(|p| *p.borrow_mut() = None);
+		}
+		fn set_prime(who: Option<u64>) {
+			PRIME.with(|p| *p.borrow_mut() = who);
 		}
 	}
 	impl InitializeMembers<u64> for TestChangeMembers {
@@ -269,6 +325,7 @@ mod tests {
 		type RemoveOrigin = EnsureSignedBy<Two, u64>;
 		type SwapOrigin = EnsureSignedBy<Three, u64>;
 		type ResetOrigin = EnsureSignedBy<Four, u64>;
+		type PrimeOrigin = EnsureSignedBy<Five, u64>;
 		type MembershipInitialized = TestChangeMembers;
 		type MembershipChanged = TestChangeMembers;
 	}
@@ -295,6 +352,21 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn prime_member_works() {
+		new_test_ext().execute_with(|| {
+			assert_noop!(Membership::set_prime(Origin::signed(4), 20), "bad origin");
+			assert_noop!(Membership::set_prime(Origin::signed(5), 15), "not a member");
+			assert_ok!(Membership::set_prime(Origin::signed(5), 20));
+			assert_eq!(Membership::prime(), Some(20));
+			assert_eq!(PRIME.with(|m| *m.borrow()), Membership::prime());
+
+			assert_ok!(Membership::clear_prime(Origin::signed(5)));
+			assert_eq!(Membership::prime(), None);
+			assert_eq!(PRIME.with(|m| *m.borrow()), Membership::prime());
+		});
+	}
+
 	#[test]
 	fn add_member_works() {
 		new_test_ext().execute_with(|| {
@@ -311,9 +383,23 @@ mod tests {
 		new_test_ext().execute_with(|| {
 			assert_noop!(Membership::remove_member(Origin::signed(5), 20), "bad origin");
 			assert_noop!(Membership::remove_member(Origin::signed(2), 15), "not a member");
+			assert_ok!(Membership::set_prime(Origin::signed(5), 20));
 			assert_ok!(Membership::remove_member(Origin::signed(2), 20));
 			assert_eq!(Membership::members(), vec![10, 30]);
 			assert_eq!(MEMBERS.with(|m| m.borrow().clone()), Membership::members());
+			assert_eq!(Membership::prime(), None);
+			assert_eq!(PRIME.with(|m| *m.borrow()), Membership::prime());
+		});
+	}
+
+	#[test]
+	fn swap_member_keeps_the_prime_member() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(Membership::set_prime(Origin::signed(5), 30));
+			assert_ok!(Membership::swap_member(Origin::signed(3), 10, 25));
+			assert_eq!(Membership::members(), vec![20, 25, 30]);
+			assert_eq!(Membership::prime(), Some(30));
+			assert_eq!(PRIME.with(|m| *m.borrow()), Membership::prime());
 		});
 	}
 
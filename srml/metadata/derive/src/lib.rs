@@ -0,0 +1,313 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Derive macro for the `TypeInfo` trait of `srml-metadata`.
+//!
+//! The generated description follows the `parity-scale-codec` derive: fields and variants marked
+//! with `#[codec(skip)]` are left out, fields marked with `#[codec(compact)]` are described as compact
+//! and enum variants are indexed by `#[codec(index = "..")]`, their explicit discriminant or their
+//! position, in this order.
+//!
+//! Type parameters used directly in a described field are bounded by `TypeInfo` and listed as the
+//! parameters of the type. A field only reaching a parameter through one of its associated types,
+//! like `T::BlockNumber`, is bounded as a whole instead, so that `T` itself, usually the runtime,
+//! does not need to implement `TypeInfo`.
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use proc_macro_crate::crate_name;
+use quote::quote;
+use syn::{
+	parse_quote, spanned::Spanned, Attribute, Data, DeriveInput, Error, Expr, ExprLit, Fields,
+	GenericArgument, Ident, Lit, Meta, NestedMeta, PathArguments, Result, Type, TypePath, Variant,
+};
+
+const CRATE_NAME: &str = "srml-metadata";
+const SUPPORT_CRATE_NAME: &str = "srml-support";
+
+#[proc_macro_derive(TypeInfo, attributes(codec))]
+pub fn type_info_derive(input: TokenStream) -> TokenStream {
+	match syn::parse(input) {
+		Ok(ast) => derive(ast).unwrap_or_else(|e| e.to_compile_error()).into(),
+		Err(e) => e.to_compile_error().into(),
+	}
+}
+
+fn derive(mut ast: DeriveInput) -> Result<TokenStream2> {
+	let krate = metadata_crate()?;
+	let name = &ast.ident;
+	let name_str = name.to_string();
+
+	let all_params = ast.generics.type_params().map(|p| p.ident.clone()).collect::<Vec<_>>();
+	let mut used = Vec::new();
+	let mut bounded_fields = Vec::new();
+	for ty in described_types(&ast.data)? {
+		let mut uses = ParamUses::default();
+		uses.visit(&all_params, ty);
+		for param in uses.direct {
+			if !used.contains(&param) {
+				used.push(param);
+			}
+		}
+		if uses.associated {
+			bounded_fields.push(ty.clone());
+		}
+	}
+
+	// Keep the declaration order for the parameters of the type.
+	let params = all_params.iter().filter(|p| used.contains(p)).collect::<Vec<_>>();
+	for param in ast.generics.type_params_mut() {
+		param.bounds.push(parse_quote!('static));
+		if used.contains(&param.ident) {
+			param.bounds.push(parse_quote!(#krate::TypeInfo));
+		}
+	}
+	if !bounded_fields.is_empty() {
+		let where_clause = ast.generics.make_where_clause();
+		for ty in bounded_fields {
+			where_clause.predicates.push(parse_quote!(#ty: #krate::TypeInfo));
+		}
+	}
+
+	let def = match &ast.data {
+		Data::Struct(data) => {
+			let fields = describe_fields(&krate, &data.fields)?;
+			quote!( #krate::TypeDef::Composite(#fields) )
+		},
+		Data::Enum(data) => {
+			let mut variants = Vec::new();
+			// Like the codec derive, skipped variants do not take a position.
+			for variant in data.variants.iter() {
+				if !has_codec_flag(&variant.attrs, "skip")? {
+					variants.push(describe_variant(&krate, variants.len(), variant)?);
+				}
+			}
+			quote!( #krate::TypeDef::Variant(<[_]>::to_vec(&[ #( #variants ),* ])) )
+		},
+		Data::Union(data) => return Err(Error::new(
+			data.union_token.span(),
+			"TypeInfo can not be derived for unions",
+		)),
+	};
+
+	let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+	Ok(quote! {
+		impl #impl_generics #krate::TypeInfo for #name #ty_generics #where_clause {
+			fn path() -> #krate::rstd::vec::Vec<&'static str> {
+				module_path!().split("::").chain(::core::iter::once(#name_str)).collect()
+			}
+
+			fn params(
+				registry: &mut #krate::Registry,
+			) -> #krate::rstd::vec::Vec<#krate::TypeId> {
+				let _ = &registry;
+				<[_]>::to_vec(&[ #( registry.register::<#params>() ),* ])
+			}
+
+			fn type_def(registry: &mut #krate::Registry) -> #krate::TypeDef {
+				let _ = &registry;
+				#def
+			}
+		}
+	})
+}
+
+/// Find the path under which the `srml-metadata` items are reachable from the crate being
+/// compiled.
+fn metadata_crate() -> Result<TokenStream2> {
+	const PROOF: &str = "CARGO_PKG_NAME always defined when compiling; qed";
+
+	if let Ok(name) = crate_name(CRATE_NAME) {
+		let name = Ident::new(&name, Span::call_site());
+		return Ok(quote!( #name ))
+	}
+
+	// Runtime modules usually only depend on `srml-support`, which re-exports the registry.
+	if let Ok(name) = crate_name(SUPPORT_CRATE_NAME) {
+		let name = Ident::new(&name, Span::call_site());
+		return Ok(quote!( #name::metadata ))
+	}
+
+	match std::env::var("CARGO_PKG_NAME").expect(PROOF).as_str() {
+		// we return the name of the crate here instead of `crate` to support integration tests.
+		CRATE_NAME => Ok(quote!( srml_metadata )),
+		SUPPORT_CRATE_NAME => Ok(quote!( crate::metadata )),
+		_ => Err(Error::new(
+			Span::call_site(),
+			format!("`{}` or `{}` must be a dependency to derive TypeInfo", CRATE_NAME, SUPPORT_CRATE_NAME),
+		)),
+	}
+}
+
+fn describe_variant(krate: &TokenStream2, position: usize, variant: &Variant) -> Result<TokenStream2> {
+	let name = variant.ident.to_string();
+	let index = variant_index(position, variant)?;
+	let fields = describe_fields(krate, &variant.fields)?;
+
+	Ok(quote! {
+		#krate::Variant {
+			name: #krate::DecodeDifferent::Encode(#name),
+			index: #index,
+			fields: #fields,
+		}
+	})
+}
+
+fn describe_fields(krate: &TokenStream2, fields: &Fields) -> Result<TokenStream2> {
+	let mut described = Vec::new();
+
+	for field in fields.iter() {
+		if has_codec_flag(&field.attrs, "skip")? {
+			continue
+		}
+
+		let ty = &field.ty;
+		let name = match &field.ident {
+			Some(ident) => {
+				let ident = ident.to_string();
+				quote!( Some(#krate::DecodeDifferent::Encode(#ident)) )
+			},
+			None => quote!( None ),
+		};
+		let id = if has_codec_flag(&field.attrs, "compact")? {
+			quote!( registry.register_compact::<#ty>() )
+		} else {
+			quote!( registry.register::<#ty>() )
+		};
+
+		described.push(quote!( #krate::Field { name: #name, ty: #id } ));
+	}
+
+	Ok(quote!( <[_]>::to_vec(&[ #( #described ),* ]) ))
+}
+
+/// The types of all fields taking part in the encoding.
+fn described_types(data: &Data) -> Result<Vec<&Type>> {
+	let mut fields = Vec::new();
+	match data {
+		Data::Struct(data) => fields.extend(data.fields.iter()),
+		Data::Enum(data) => for variant in data.variants.iter() {
+			if !has_codec_flag(&variant.attrs, "skip")? {
+				fields.extend(variant.fields.iter());
+			}
+		},
+		Data::Union(_) => (),
+	}
+
+	let mut types = Vec::new();
+	for field in fields {
+		if !has_codec_flag(&field.attrs, "skip")? {
+			types.push(&field.ty);
+		}
+	}
+	Ok(types)
+}
+
+/// How the type parameters are used by a field type.
+#[derive(Default)]
+struct ParamUses {
+	/// Parameters used as a type, possibly nested in another type like `Vec<T>`.
+	direct: Vec<Ident>,
+	/// Whether a parameter is used through an associated type, like `T::Balance`.
+	associated: bool,
+}
+
+impl ParamUses {
+	fn visit(&mut self, params: &[Ident], ty: &Type) {
+		match ty {
+			Type::Path(TypePath { qself, path }) => {
+				if let Some(qself) = qself {
+					let mut inner = ParamUses::default();
+					inner.visit(params, &qself.ty);
+					self.associated |= inner.associated || !inner.direct.is_empty();
+				} else if let Some(first) = path.segments.first() {
+					if params.contains(&first.ident) {
+						if path.segments.len() == 1 {
+							self.direct.push(first.ident.clone());
+						} else {
+							self.associated = true;
+						}
+					}
+				}
+				for segment in path.segments.iter() {
+					if let PathArguments::AngleBracketed(args) = &segment.arguments {
+						for arg in args.args.iter() {
+							if let GenericArgument::Type(ty) = arg {
+								self.visit(params, ty);
+							}
+						}
+					}
+				}
+			},
+			Type::Array(array) => self.visit(params, &array.elem),
+			Type::Slice(slice) => self.visit(params, &slice.elem),
+			Type::Reference(reference) => self.visit(params, &reference.elem),
+			Type::Ptr(ptr) => self.visit(params, &ptr.elem),
+			Type::Paren(paren) => self.visit(params, &paren.elem),
+			Type::Group(group) => self.visit(params, &group.elem),
+			Type::Tuple(tuple) => for elem in tuple.elems.iter() {
+				self.visit(params, elem);
+			},
+			_ => (),
+		}
+	}
+}
+
+fn variant_index(position: usize, variant: &Variant) -> Result<u8> {
+	for meta in codec_metas(&variant.attrs)? {
+		if let NestedMeta::Meta(Meta::NameValue(ref nv)) = meta {
+			if nv.path.is_ident("index") {
+				if let Lit::Str(ref s) = nv.lit {
+					return s.value().parse::<u8>()
+						.map_err(|_| Error::new(s.span(), "index must be a valid u8"))
+				}
+				return Err(Error::new(nv.lit.span(), "index must be a string literal"))
+			}
+		}
+	}
+
+	if let Some((_, Expr::Lit(ExprLit { lit: Lit::Int(ref int), .. }))) = variant.discriminant {
+		return int.base10_parse::<u8>()
+	}
+
+	if position > u8::max_value() as usize {
+		return Err(Error::new(variant.span(), "enums can have at most 256 variants"))
+	}
+
+	Ok(position as u8)
+}
+
+fn has_codec_flag(attrs: &[Attribute], flag: &str) -> Result<bool> {
+	Ok(codec_metas(attrs)?.iter().any(|meta| match meta {
+		NestedMeta::Meta(Meta::Path(ref path)) => path.is_ident(flag),
+		_ => false,
+	}))
+}
+
+fn codec_metas(attrs: &[Attribute]) -> Result<Vec<NestedMeta>> {
+	let mut metas = Vec::new();
+
+	for attr in attrs.iter().filter(|a| a.path.is_ident("codec")) {
+		if let Meta::List(list) = attr.parse_meta()? {
+			metas.extend(list.nested.into_iter());
+		}
+	}
+
+	Ok(metas)
+}
@@ -30,6 +30,16 @@ use codec::{Encode, Output};
 use rstd::vec::Vec;
 use primitives::RuntimeDebug;
 
+mod registry;
+
+pub use registry::{
+	Field, Registry, Type, TypeDef, TypeDefPrimitive, TypeId, TypeInfo, Variant,
+};
+pub use srml_metadata_derive::TypeInfo;
+
+#[doc(hidden)]
+pub use rstd;
+
 #[cfg(feature = "std")]
 type StringBuf = String;
 
@@ -344,6 +354,8 @@ pub enum RuntimeMetadata {
 	V7(RuntimeMetadataDeprecated),
 	/// Version 8 for runtime metadata.
 	V8(RuntimeMetadataV8),
+	/// Version 9 for runtime metadata, describing types through a portable registry.
+	V9(RuntimeMetadataV9),
 }
 
 /// Enum that should fail.
@@ -372,7 +384,7 @@ pub struct RuntimeMetadataV8 {
 }
 
 /// The latest version of the metadata.
-pub type RuntimeMetadataLastVersion = RuntimeMetadataV8;
+pub type RuntimeMetadataLastVersion = RuntimeMetadataV9;
 
 /// All metadata about an runtime module.
 #[derive(Clone, PartialEq, Eq, Encode, RuntimeDebug)]
@@ -395,8 +407,121 @@ impl Into<primitives::OpaqueMetadata> for RuntimeMetadataPrefixed {
 	}
 }
 
-impl Into<RuntimeMetadataPrefixed> for RuntimeMetadataLastVersion {
+impl Into<RuntimeMetadataPrefixed> for RuntimeMetadataV8 {
 	fn into(self) -> RuntimeMetadataPrefixed {
 		RuntimeMetadataPrefixed(META_RESERVED, RuntimeMetadata::V8(self))
 	}
 }
+
+impl Into<RuntimeMetadataPrefixed> for RuntimeMetadataV9 {
+	fn into(self) -> RuntimeMetadataPrefixed {
+		RuntimeMetadataPrefixed(META_RESERVED, RuntimeMetadata::V9(self))
+	}
+}
+
+/// The metadata of a runtime, with every type referring to an entry of `types`.
+///
+/// Unlike the previous versions, which name types by their Rust path, a client only needs this
+/// structure to encode calls and decode storage and events.
+#[derive(Clone, PartialEq, Eq, Encode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Decode, Serialize))]
+pub struct RuntimeMetadataV9 {
+	/// The type registry, every `TypeId` of the metadata is an index into it.
+	pub types: Vec<Type>,
+	pub modules: Vec<ModuleMetadataV9>,
+}
+
+impl RuntimeMetadataV9 {
+	/// Build the metadata from the modules, taking the types they registered in `registry`.
+	pub fn new(registry: Registry, modules: Vec<ModuleMetadataV9>) -> Self {
+		RuntimeMetadataV9 { types: registry.into_types(), modules }
+	}
+}
+
+/// All metadata about a runtime module, see [`RuntimeMetadataV9`].
+#[derive(Clone, PartialEq, Eq, Encode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Decode, Serialize))]
+pub struct ModuleMetadataV9 {
+	pub name: DecodeDifferentStr,
+	pub storage: Option<StorageMetadataV9>,
+	pub calls: Option<Vec<FunctionMetadataV9>>,
+	pub event: Option<Vec<EventMetadataV9>>,
+	pub constants: Vec<ModuleConstantMetadataV9>,
+	pub errors: Vec<ErrorMetadata>,
+}
+
+/// All the metadata about a function, see [`RuntimeMetadataV9`].
+#[derive(Clone, PartialEq, Eq, Encode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Decode, Serialize))]
+pub struct FunctionMetadataV9 {
+	pub name: DecodeDifferentStr,
+	pub arguments: Vec<FunctionArgumentMetadataV9>,
+	pub documentation: Vec<DecodeDifferentStr>,
+}
+
+/// All the metadata about a function argument, see [`RuntimeMetadataV9`].
+#[derive(Clone, PartialEq, Eq, Encode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Decode, Serialize))]
+pub struct FunctionArgumentMetadataV9 {
+	pub name: DecodeDifferentStr,
+	pub ty: TypeId,
+}
+
+/// All the metadata about an event, see [`RuntimeMetadataV9`].
+#[derive(Clone, PartialEq, Eq, Encode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Decode, Serialize))]
+pub struct EventMetadataV9 {
+	pub name: DecodeDifferentStr,
+	pub arguments: Vec<TypeId>,
+	pub documentation: Vec<DecodeDifferentStr>,
+}
+
+/// All metadata of the storage, see [`RuntimeMetadataV9`].
+#[derive(Clone, PartialEq, Eq, Encode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Decode, Serialize))]
+pub struct StorageMetadataV9 {
+	/// The common prefix used by all storage entries.
+	pub prefix: DecodeDifferentStr,
+	pub entries: Vec<StorageEntryMetadataV9>,
+}
+
+/// All the metadata about one storage entry, see [`RuntimeMetadataV9`].
+#[derive(Clone, PartialEq, Eq, Encode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Decode, Serialize))]
+pub struct StorageEntryMetadataV9 {
+	pub name: DecodeDifferentStr,
+	pub modifier: StorageEntryModifier,
+	pub ty: StorageEntryTypeV9,
+	pub default: ByteGetter,
+	pub documentation: Vec<DecodeDifferentStr>,
+}
+
+/// A storage entry type, see [`RuntimeMetadataV9`].
+#[derive(Clone, PartialEq, Eq, Encode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Decode, Serialize))]
+pub enum StorageEntryTypeV9 {
+	Plain(TypeId),
+	Map {
+		hasher: StorageHasher,
+		key: TypeId,
+		value: TypeId,
+		is_linked: bool,
+	},
+	DoubleMap {
+		hasher: StorageHasher,
+		key1: TypeId,
+		key2: TypeId,
+		value: TypeId,
+		key2_hasher: StorageHasher,
+	},
+}
+
+/// All the metadata about one module constant, see [`RuntimeMetadataV9`].
+#[derive(Clone, PartialEq, Eq, Encode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Decode, Serialize))]
+pub struct ModuleConstantMetadataV9 {
+	pub name: DecodeDifferentStr,
+	pub ty: TypeId,
+	pub value: ByteGetter,
+	pub documentation: Vec<DecodeDifferentStr>,
+}
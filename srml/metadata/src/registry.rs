@@ -0,0 +1,564 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Portable type registry.
+//!
+//! Every type implementing [`TypeInfo`] can describe its SCALE encoding structurally (struct
+//! fields, enum variants, sequences, tuples, compact integers, ...). Types are interned into a
+//! [`Registry`] and refer to each other by [`TypeId`], an index into the registry, so the whole
+//! registry can be encoded and shipped in the runtime metadata. A client can then encode and
+//! decode any registered type without knowing anything about the chain.
+
+#[cfg(feature = "std")]
+use serde::Serialize;
+#[cfg(feature = "std")]
+use codec::Decode;
+use codec::{Encode, Compact, OptionBool};
+use rstd::{prelude::*, marker::PhantomData, collections::btree_map::BTreeMap};
+use primitives::RuntimeDebug;
+use crate::{DecodeDifferent, StringBuf};
+
+type DecodeDifferentStr = DecodeDifferent<&'static str, StringBuf>;
+
+/// Index of a type in a [`Registry`].
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Encode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Decode, Serialize))]
+pub struct TypeId(#[codec(compact)] pub u32);
+
+/// A primitive type, encoded as defined by SCALE.
+#[derive(Clone, PartialEq, Eq, Encode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Decode, Serialize))]
+pub enum TypeDefPrimitive {
+	Bool,
+	Str,
+	U8,
+	U16,
+	U32,
+	U64,
+	U128,
+	I8,
+	I16,
+	I32,
+	I64,
+	I128,
+}
+
+/// A field of a struct or of an enum variant.
+#[derive(Clone, PartialEq, Eq, Encode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Decode, Serialize))]
+pub struct Field {
+	/// Name of the field, `None` for tuple-like structs and variants.
+	pub name: Option<DecodeDifferentStr>,
+	/// Type of the field.
+	pub ty: TypeId,
+}
+
+impl Field {
+	/// A named field of the given type.
+	pub fn named(name: &'static str, ty: TypeId) -> Self {
+		Field { name: Some(DecodeDifferent::Encode(name)), ty }
+	}
+
+	/// An unnamed field of the given type.
+	pub fn unnamed(ty: TypeId) -> Self {
+		Field { name: None, ty }
+	}
+}
+
+/// A variant of an enum.
+#[derive(Clone, PartialEq, Eq, Encode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Decode, Serialize))]
+pub struct Variant {
+	/// Name of the variant.
+	pub name: DecodeDifferentStr,
+	/// Index of the variant, the first byte of its encoding.
+	pub index: u8,
+	/// Fields of the variant, encoded in order after the index.
+	pub fields: Vec<Field>,
+}
+
+impl Variant {
+	/// A variant with the given name, index and fields.
+	pub fn new(name: &'static str, index: u8, fields: Vec<Field>) -> Self {
+		Variant { name: DecodeDifferent::Encode(name), index, fields }
+	}
+}
+
+/// The structure of a type.
+#[derive(Clone, PartialEq, Eq, Encode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Decode, Serialize))]
+pub enum TypeDef {
+	/// A primitive type.
+	Primitive(TypeDefPrimitive),
+	/// A struct, its fields are encoded in order.
+	Composite(Vec<Field>),
+	/// An enum, encoded as the index of the variant followed by its fields.
+	Variant(Vec<Variant>),
+	/// A variable length sequence, encoded as its compact length followed by the elements.
+	Sequence(TypeId),
+	/// A fixed length array, encoded as its elements.
+	Array {
+		len: u32,
+		ty: TypeId,
+	},
+	/// A tuple, its elements are encoded in order.
+	Tuple(Vec<TypeId>),
+	/// A compact encoded integer.
+	Compact(TypeId),
+}
+
+/// A type of the registry.
+#[derive(Clone, PartialEq, Eq, Encode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Decode, Serialize))]
+pub struct Type {
+	/// Path of the type, e.g. `["srml_staking", "RewardDestination"]`. Empty for anonymous types
+	/// (primitives, sequences, tuples, ...).
+	pub path: Vec<DecodeDifferentStr>,
+	/// The generic parameters the type was instantiated with.
+	pub params: Vec<TypeId>,
+	/// The structure of the type.
+	pub def: TypeDef,
+}
+
+/// Something that can describe its own SCALE encoding.
+///
+/// Can be derived for structs and enums with `#[derive(TypeInfo)]`, which respects the
+/// `#[codec(compact)]`, `#[codec(skip)]` and `#[codec(index = "..")]` attributes.
+pub trait TypeInfo: 'static {
+	/// The path of the type, empty for anonymous types.
+	fn path() -> Vec<&'static str> {
+		Vec::new()
+	}
+
+	/// The generic parameters of the type, registered in `registry`.
+	fn params(_registry: &mut Registry) -> Vec<TypeId> {
+		Vec::new()
+	}
+
+	/// The structure of the type, registering the types it is made of in `registry`.
+	fn type_def(registry: &mut Registry) -> TypeDef;
+
+	/// The id of the type this type is encoded as, if it is only a transparent wrapper (e.g.
+	/// `Box<T>`). Transparent types are not registered themselves.
+	fn transparent(_registry: &mut Registry) -> Option<TypeId> {
+		None
+	}
+}
+
+/// Registry of types, each type is registered once and referred to by its [`TypeId`].
+#[derive(Default)]
+pub struct Registry {
+	ids: BTreeMap<core::any::TypeId, TypeId>,
+	types: Vec<Option<Type>>,
+}
+
+impl Registry {
+	/// Create an empty registry.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Register `T` and the types it is made of, returning its id.
+	///
+	/// Registering a type twice returns the same id.
+	pub fn register<T: TypeInfo + ?Sized>(&mut self) -> TypeId {
+		if let Some(id) = T::transparent(self) {
+			return id;
+		}
+
+		let key = core::any::TypeId::of::<T>();
+		if let Some(id) = self.ids.get(&key) {
+			return *id;
+		}
+
+		// Reserve the id before building the definition, so recursive types refer to it.
+		let id = TypeId(self.types.len() as u32);
+		self.ids.insert(key, id);
+		self.types.push(None);
+
+		let ty = Type {
+			path: T::path().into_iter().map(DecodeDifferent::Encode).collect(),
+			params: T::params(self),
+			def: T::type_def(self),
+		};
+		self.types[id.0 as usize] = Some(ty);
+		id
+	}
+
+	/// Register the compact encoding of `T`, as used for `#[codec(compact)]` fields.
+	pub fn register_compact<T: TypeInfo>(&mut self) -> TypeId {
+		self.register::<Compact<T>>()
+	}
+
+	/// The number of registered types.
+	pub fn len(&self) -> usize {
+		self.types.len()
+	}
+
+	/// Whether no type has been registered yet.
+	pub fn is_empty(&self) -> bool {
+		self.types.is_empty()
+	}
+
+	/// Consume the registry, returning the types ordered by id.
+	pub fn into_types(self) -> Vec<Type> {
+		self.types.into_iter()
+			.map(|ty| ty.expect("every reserved id is filled before `register` returns; qed"))
+			.collect()
+	}
+}
+
+macro_rules! impl_primitives {
+	( $( $t:ty => $primitive:ident, )* ) => { $(
+		impl TypeInfo for $t {
+			fn type_def(_: &mut Registry) -> TypeDef {
+				TypeDef::Primitive(TypeDefPrimitive::$primitive)
+			}
+		}
+	)* }
+}
+
+impl_primitives! {
+	bool => Bool,
+	str => Str,
+	u8 => U8,
+	u16 => U16,
+	u32 => U32,
+	u64 => U64,
+	u128 => U128,
+	i8 => I8,
+	i16 => I16,
+	i32 => I32,
+	i64 => I64,
+	i128 => I128,
+}
+
+#[cfg(feature = "std")]
+impl TypeInfo for String {
+	fn type_def(_: &mut Registry) -> TypeDef {
+		TypeDef::Primitive(TypeDefPrimitive::Str)
+	}
+}
+
+impl<T: TypeInfo> TypeInfo for Vec<T> {
+	fn params(registry: &mut Registry) -> Vec<TypeId> {
+		vec![registry.register::<T>()]
+	}
+
+	fn type_def(registry: &mut Registry) -> TypeDef {
+		TypeDef::Sequence(registry.register::<T>())
+	}
+}
+
+impl<T: TypeInfo> TypeInfo for [T] {
+	fn type_def(registry: &mut Registry) -> TypeDef {
+		TypeDef::Sequence(registry.register::<T>())
+	}
+}
+
+impl<K: TypeInfo, V: TypeInfo> TypeInfo for BTreeMap<K, V> {
+	fn path() -> Vec<&'static str> {
+		vec!["BTreeMap"]
+	}
+
+	fn params(registry: &mut Registry) -> Vec<TypeId> {
+		vec![registry.register::<K>(), registry.register::<V>()]
+	}
+
+	fn type_def(registry: &mut Registry) -> TypeDef {
+		TypeDef::Sequence(registry.register::<(K, V)>())
+	}
+}
+
+macro_rules! impl_transparent {
+	( $( $t:ty ),* ) => { $(
+		impl<T: TypeInfo + ?Sized> TypeInfo for $t {
+			fn type_def(registry: &mut Registry) -> TypeDef {
+				T::type_def(registry)
+			}
+
+			fn transparent(registry: &mut Registry) -> Option<TypeId> {
+				Some(registry.register::<T>())
+			}
+		}
+	)* }
+}
+
+impl_transparent!(Box<T>, &'static T);
+
+// `Option<T>` is encoded as a one byte index followed by the value for every `T`, including
+// `bool`. The one byte encoding of an optional boolean is provided by `OptionBool`.
+impl<T: TypeInfo> TypeInfo for Option<T> {
+	fn path() -> Vec<&'static str> {
+		vec!["Option"]
+	}
+
+	fn params(registry: &mut Registry) -> Vec<TypeId> {
+		vec![registry.register::<T>()]
+	}
+
+	fn type_def(registry: &mut Registry) -> TypeDef {
+		TypeDef::Variant(vec![
+			Variant::new("None", 0, Vec::new()),
+			Variant::new("Some", 1, vec![Field::unnamed(registry.register::<T>())]),
+		])
+	}
+}
+
+impl TypeInfo for OptionBool {
+	fn path() -> Vec<&'static str> {
+		vec!["OptionBool"]
+	}
+
+	fn type_def(_: &mut Registry) -> TypeDef {
+		TypeDef::Variant(vec![
+			Variant::new("None", 0, Vec::new()),
+			Variant::new("True", 1, Vec::new()),
+			Variant::new("False", 2, Vec::new()),
+		])
+	}
+}
+
+impl<T: TypeInfo, E: TypeInfo> TypeInfo for Result<T, E> {
+	fn path() -> Vec<&'static str> {
+		vec!["Result"]
+	}
+
+	fn params(registry: &mut Registry) -> Vec<TypeId> {
+		vec![registry.register::<T>(), registry.register::<E>()]
+	}
+
+	fn type_def(registry: &mut Registry) -> TypeDef {
+		TypeDef::Variant(vec![
+			Variant::new("Ok", 0, vec![Field::unnamed(registry.register::<T>())]),
+			Variant::new("Err", 1, vec![Field::unnamed(registry.register::<E>())]),
+		])
+	}
+}
+
+impl<T: TypeInfo> TypeInfo for Compact<T> {
+	fn type_def(registry: &mut Registry) -> TypeDef {
+		TypeDef::Compact(registry.register::<T>())
+	}
+}
+
+impl<T: 'static + ?Sized> TypeInfo for PhantomData<T> {
+	fn type_def(_: &mut Registry) -> TypeDef {
+		TypeDef::Tuple(Vec::new())
+	}
+}
+
+macro_rules! impl_tuples {
+	( $( ( $( $t:ident ),* ) )* ) => { $(
+		impl< $( $t: TypeInfo ),* > TypeInfo for ( $( $t, )* ) {
+			#[allow(unused_variables)]
+			fn type_def(registry: &mut Registry) -> TypeDef {
+				TypeDef::Tuple(vec![ $( registry.register::<$t>() ),* ])
+			}
+		}
+	)* }
+}
+
+impl_tuples! {
+	()
+	(A)
+	(A, B)
+	(A, B, C)
+	(A, B, C, D)
+	(A, B, C, D, E)
+	(A, B, C, D, E, F)
+	(A, B, C, D, E, F, G)
+	(A, B, C, D, E, F, G, H)
+	(A, B, C, D, E, F, G, H, I)
+	(A, B, C, D, E, F, G, H, I, J)
+	(A, B, C, D, E, F, G, H, I, J, K)
+	(A, B, C, D, E, F, G, H, I, J, K, L)
+}
+
+macro_rules! impl_arrays {
+	( $( $n:expr ),* ) => { $(
+		impl<T: TypeInfo> TypeInfo for [T; $n] {
+			fn type_def(registry: &mut Registry) -> TypeDef {
+				TypeDef::Array { len: $n, ty: registry.register::<T>() }
+			}
+		}
+	)* }
+}
+
+impl_arrays!(
+	1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16,
+	17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32,
+	40, 48, 56, 64, 72, 96, 128, 160, 192, 224, 256
+);
+
+macro_rules! impl_fixed_hashes {
+	( $( $t:ident => $len:expr, )* ) => { $(
+		impl TypeInfo for primitives::$t {
+			fn path() -> Vec<&'static str> {
+				vec!["substrate_primitives", stringify!($t)]
+			}
+
+			fn type_def(registry: &mut Registry) -> TypeDef {
+				TypeDef::Composite(vec![Field::unnamed(registry.register::<[u8; $len]>())])
+			}
+		}
+	)* }
+}
+
+impl_fixed_hashes! {
+	H160 => 20,
+	H256 => 32,
+	H512 => 64,
+}
+
+macro_rules! impl_newtypes {
+	( $( $( $segment:ident )::+ => $inner:ty, )* ) => { $(
+		impl TypeInfo for primitives::$( $segment )::+ {
+			fn path() -> Vec<&'static str> {
+				vec!["substrate_primitives", $( stringify!($segment) ),+]
+			}
+
+			fn type_def(registry: &mut Registry) -> TypeDef {
+				TypeDef::Composite(vec![Field::unnamed(registry.register::<$inner>())])
+			}
+		}
+	)* }
+}
+
+impl_newtypes! {
+	crypto::AccountId32 => [u8; 32],
+	crypto::KeyTypeId => [u8; 4],
+	ed25519::Public => [u8; 32],
+	ed25519::Signature => [u8; 64],
+	sr25519::Public => [u8; 32],
+	sr25519::Signature => [u8; 64],
+	ecdsa::Public => [u8; 33],
+	ecdsa::Signature => [u8; 65],
+	offchain::OpaquePeerId => Vec<u8>,
+	offchain::OpaqueMultiaddr => Vec<u8>,
+}
+
+impl TypeInfo for primitives::offchain::OpaqueNetworkState {
+	fn path() -> Vec<&'static str> {
+		vec!["substrate_primitives", "offchain", "OpaqueNetworkState"]
+	}
+
+	fn type_def(registry: &mut Registry) -> TypeDef {
+		TypeDef::Composite(vec![
+			Field::named("peer_id", registry.register::<primitives::offchain::OpaquePeerId>()),
+			Field::named("external_addresses", registry.register::<Vec<primitives::offchain::OpaqueMultiaddr>>()),
+		])
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	struct Recursive(Vec<Recursive>);
+
+	impl TypeInfo for Recursive {
+		fn path() -> Vec<&'static str> {
+			vec!["Recursive"]
+		}
+
+		fn type_def(registry: &mut Registry) -> TypeDef {
+			TypeDef::Composite(vec![Field::unnamed(registry.register::<Vec<Recursive>>())])
+		}
+	}
+
+	#[test]
+	fn types_are_registered_once() {
+		let mut registry = Registry::new();
+		let a = registry.register::<(u32, Vec<u32>)>();
+		let b = registry.register::<Vec<u32>>();
+		let c = registry.register::<(u32, Vec<u32>)>();
+
+		assert_eq!(a, c);
+		assert_eq!(registry.len(), 3);
+
+		let types = registry.into_types();
+		assert_eq!(types[a.0 as usize].def, TypeDef::Tuple(vec![TypeId(1), b]));
+		assert_eq!(types[1].def, TypeDef::Primitive(TypeDefPrimitive::U32));
+		assert_eq!(types[b.0 as usize].def, TypeDef::Sequence(TypeId(1)));
+	}
+
+	#[test]
+	fn recursive_types_refer_to_themselves() {
+		let mut registry = Registry::new();
+		let id = registry.register::<Recursive>();
+
+		let types = registry.into_types();
+		assert_eq!(types.len(), 2);
+		assert_eq!(types[id.0 as usize].def, TypeDef::Composite(vec![
+			Field::unnamed(TypeId(1)),
+		]));
+		assert_eq!(types[1].def, TypeDef::Sequence(id));
+	}
+
+	#[test]
+	fn compact_and_options_are_described() {
+		let mut registry = Registry::new();
+		let id = registry.register::<Option<Compact<u64>>>();
+
+		let types = registry.into_types();
+		match &types[id.0 as usize].def {
+			TypeDef::Variant(variants) => {
+				assert_eq!(variants.len(), 2);
+				assert_eq!(variants[1].index, 1);
+				let inner = variants[1].fields[0].ty;
+				assert_eq!(types[inner.0 as usize].def, TypeDef::Compact(TypeId(2)));
+			},
+			def => panic!("unexpected definition {:?}", def),
+		}
+	}
+
+	#[test]
+	fn boxes_and_references_are_transparent() {
+		let mut registry = Registry::new();
+		let id = registry.register::<u32>();
+
+		assert_eq!(registry.register::<Box<u32>>(), id);
+		assert_eq!(registry.register::<&'static u32>(), id);
+		assert_eq!(registry.register::<Box<Box<u32>>>(), id);
+		assert_eq!(registry.len(), 1);
+	}
+
+	fn variant_indices(types: &[Type], id: TypeId) -> Vec<(u8, usize)> {
+		match &types[id.0 as usize].def {
+			TypeDef::Variant(variants) => variants.iter().map(|v| (v.index, v.fields.len())).collect(),
+			def => panic!("unexpected definition {:?}", def),
+		}
+	}
+
+	#[test]
+	fn optional_booleans_match_their_encoding() {
+		let mut registry = Registry::new();
+		let option = registry.register::<Option<bool>>();
+		let option_bool = registry.register::<OptionBool>();
+		let types = registry.into_types();
+
+		// `Option<bool>` is an index followed by the boolean.
+		assert_eq!(Some(true).encode(), vec![1, 1]);
+		assert_eq!(variant_indices(&types, option), vec![(0, 0), (1, 1)]);
+
+		// `OptionBool` folds the boolean into the index.
+		assert_eq!(OptionBool(None).encode(), vec![0]);
+		assert_eq!(OptionBool(Some(true)).encode(), vec![1]);
+		assert_eq!(OptionBool(Some(false)).encode(), vec![2]);
+		assert_eq!(variant_indices(&types, option_bool), vec![(0, 0), (1, 0), (2, 0)]);
+	}
+}
@@ -0,0 +1,160 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+use codec::Encode;
+use srml_metadata::{
+	DecodeDifferent, Field, Registry, TypeDef, TypeDefPrimitive, TypeId, TypeInfo, Variant,
+};
+
+#[derive(Encode, TypeInfo)]
+struct Unit;
+
+#[derive(Encode, TypeInfo)]
+struct Ledger<Balance> {
+	#[codec(compact)]
+	total: Balance,
+	#[codec(skip)]
+	#[allow(dead_code)]
+	cache: Option<u32>,
+	unlocking: Vec<(Balance, u32)>,
+}
+
+#[derive(Encode, TypeInfo)]
+enum Destination {
+	Staked,
+	#[codec(index = "5")]
+	Account([u8; 32]),
+	Controller,
+}
+
+#[derive(Encode, TypeInfo)]
+enum Call<T> {
+	#[codec(skip)]
+	#[allow(dead_code)]
+	Phantom(std::marker::PhantomData<T>),
+	Transfer(u32),
+	Remark,
+}
+
+trait Trait: 'static {
+	type BlockNumber;
+}
+
+struct Runtime;
+
+impl Trait for Runtime {
+	type BlockNumber = u64;
+}
+
+#[derive(Encode, TypeInfo)]
+struct Schedule<T: Trait> {
+	start: T::BlockNumber,
+	#[codec(compact)]
+	period: <T as Trait>::BlockNumber,
+}
+
+fn def_of(registry: Registry, id: TypeId) -> TypeDef {
+	registry.into_types().remove(id.0 as usize).def
+}
+
+#[test]
+fn unit_struct_is_an_empty_composite() {
+	let mut registry = Registry::new();
+	let id = registry.register::<Unit>();
+	let types = registry.into_types();
+
+	assert_eq!(types[id.0 as usize].def, TypeDef::Composite(vec![]));
+	assert_eq!(
+		types[id.0 as usize].path,
+		vec![DecodeDifferent::Encode("derive"), DecodeDifferent::Encode("Unit")],
+	);
+}
+
+#[test]
+fn struct_fields_follow_codec_attributes() {
+	let mut registry = Registry::new();
+	let id = registry.register::<Ledger<u64>>();
+	let compact = registry.register_compact::<u64>();
+	let unlocking = registry.register::<Vec<(u64, u32)>>();
+	let balance = registry.register::<u64>();
+
+	let types = registry.into_types();
+	let ledger = &types[id.0 as usize];
+	assert_eq!(ledger.params, vec![balance]);
+	assert_eq!(ledger.def, TypeDef::Composite(vec![
+		Field { name: Some(DecodeDifferent::Encode("total")), ty: compact },
+		Field { name: Some(DecodeDifferent::Encode("unlocking")), ty: unlocking },
+	]));
+}
+
+#[test]
+fn enum_variants_use_codec_indices() {
+	let mut registry = Registry::new();
+	let id = registry.register::<Destination>();
+	let account = registry.register::<[u8; 32]>();
+
+	assert_eq!(def_of(registry, id), TypeDef::Variant(vec![
+		Variant { name: DecodeDifferent::Encode("Staked"), index: 0, fields: vec![] },
+		Variant {
+			name: DecodeDifferent::Encode("Account"),
+			index: 5,
+			fields: vec![Field { name: None, ty: account }],
+		},
+		Variant { name: DecodeDifferent::Encode("Controller"), index: 2, fields: vec![] },
+	]));
+	assert_eq!(Destination::Account([0; 32]).encode()[0], 5);
+}
+
+#[test]
+fn skipped_variants_take_no_index() {
+	let mut registry = Registry::new();
+	let id = registry.register::<Call<u64>>();
+	let value = registry.register::<u32>();
+
+	assert_eq!(def_of(registry, id), TypeDef::Variant(vec![
+		Variant {
+			name: DecodeDifferent::Encode("Transfer"),
+			index: 0,
+			fields: vec![Field { name: None, ty: value }],
+		},
+		Variant { name: DecodeDifferent::Encode("Remark"), index: 1, fields: vec![] },
+	]));
+	assert_eq!(Call::<u64>::Remark.encode(), vec![1]);
+}
+
+#[test]
+fn associated_types_are_described_without_their_parameter() {
+	let mut registry = Registry::new();
+	let id = registry.register::<Schedule<Runtime>>();
+	let start = registry.register::<u64>();
+	let period = registry.register_compact::<u64>();
+
+	let types = registry.into_types();
+	let schedule = &types[id.0 as usize];
+	assert!(schedule.params.is_empty());
+	assert_eq!(schedule.def, TypeDef::Composite(vec![
+		Field { name: Some(DecodeDifferent::Encode("start")), ty: start },
+		Field { name: Some(DecodeDifferent::Encode("period")), ty: period },
+	]));
+}
+
+#[test]
+fn primitives_are_described() {
+	let mut registry = Registry::new();
+	let id = registry.register::<u128>();
+
+	assert_eq!(def_of(registry, id), TypeDef::Primitive(TypeDefPrimitive::U128));
+}
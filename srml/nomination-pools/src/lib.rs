@@ -0,0 +1,671 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Nomination Pools Module
+//!
+//! A module allowing accounts to pool their funds and nominate as a single nominator.
+//!
+//! - [`nomination_pools::Trait`](./trait.Trait.html)
+//! - [`Call`](./enum.Call.html)
+//!
+//! ## Overview
+//!
+//! Each pool has a bonded account, which is bonded in the Staking module and nominates as a
+//! single nominator, and a reward account, which is the controller of the bonded account and
+//! receives its staking rewards. Neither account has a key: both are only ever operated by this
+//! module.
+//!
+//! ### Points
+//!
+//! Members join a pool with any amount above `MinJoinBond`, which is transferred to the bonded
+//! account and bonded. In exchange, they receive points of the pool. The first funds of a pool
+//! are issued points one to one; later, points are issued at the current ratio of points to the
+//! active stake of the pool. A slash of the active stake of the pool therefore reduces the value
+//! of every point in the same proportion.
+//!
+//! ### Rewards
+//!
+//! The rewards received by the reward account are shared among the members proportionally to
+//! their points at the time the rewards were received. Pending rewards are paid out with
+//! `claim_payout`, and automatically before the points of a member change.
+//!
+//! ### Unbonding
+//!
+//! A member unbonds some of their points with `unbond`. The corresponding active stake is
+//! unbonded in the Staking module, and accounted for in an unbonding pool for the era at which
+//! it unlocks, in which the member receives points in turn. Slashes of the unbonding funds of the
+//! bonded account are applied proportionally to all the unbonding pools. Once the bonding
+//! duration has passed, the member withdraws their share with `withdraw_unbonded`.
+//!
+//! The points of all the unbonding pools of a pool are issued at the same rate. The pools which
+//! unlocked at least the bonding duration ago are merged into a single era-less pool, so that
+//! a pool keeps a bounded number of unbonding pools even if its members never withdraw.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! * `create` - Create a new pool, joining it with an initial bond.
+//! * `join` - Join a pool, bonding some funds into it.
+//! * `claim_payout` - Pay out the pending rewards of the sender.
+//! * `unbond` - Unbond some of the points of the sender.
+//! * `withdraw_unbonded` - Withdraw the unbonded funds of the sender, once unlocked.
+//! * `nominate` - Nominate validators on behalf of a pool. Called by the root of the pool.
+//!
+//! [`Call`]: ./enum.Call.html
+//! [`Trait`]: ./trait.Trait.html
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+use rstd::prelude::*;
+use codec::{Encode, Decode};
+use sr_primitives::{
+	ModuleId, RuntimeDebug, helpers_128bit::multiply_by_rational,
+	traits::{AccountIdConversion, SaturatedConversion, Saturating, StaticLookup, Zero},
+	weights::SimpleDispatchInfo,
+};
+use support::{
+	decl_module, decl_storage, decl_event, ensure, dispatch, metadata::TypeInfo,
+	traits::{Currency, Get, staking::{EraIndex, StakingInterface}},
+};
+use system::ensure_signed;
+
+const MODULE_ID: ModuleId = ModuleId(*b"py/nopls");
+
+/// The precision of the reward counter of a pool.
+const REWARD_PRECISION: u128 = 1_000_000_000_000;
+
+/// Identifier of a pool.
+pub type PoolId = u32;
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+
+pub trait Trait: system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+	/// The currency that is bonded by the pools.
+	type Currency: Currency<Self::AccountId>;
+
+	/// The staking system the pools bond and nominate through.
+	type Staking: StakingInterface<AccountId = Self::AccountId, Balance = BalanceOf<Self>>;
+
+	/// The minimum amount to create a pool with.
+	type MinCreateBond: Get<BalanceOf<Self>>;
+
+	/// The minimum amount to join a pool with.
+	type MinJoinBond: Get<BalanceOf<Self>>;
+
+	/// The maximum number of distinct eras in which a member can have funds unlocking.
+	type MaxUnbonding: Get<u32>;
+}
+
+/// The kind of the accounts of a pool.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+enum AccountType {
+	Bonded,
+	Reward,
+}
+
+/// A pool, bonded as a single nominator.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct BondedPool<AccountId, Balance> {
+	/// The account allowed to nominate on behalf of the pool.
+	pub root: AccountId,
+	/// The total points of the members, backed by the active stake of the pool.
+	pub points: Balance,
+	/// The number of members of the pool, including the unbonding ones.
+	pub member_count: u32,
+}
+
+/// The reward accounting of a pool.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, Default, RuntimeDebug, TypeInfo)]
+pub struct RewardPool<Balance> {
+	/// The rewards received per point since the creation of the pool, multiplied by
+	/// `REWARD_PRECISION`.
+	pub reward_counter: u128,
+	/// The total rewards received as of the last update of `reward_counter`.
+	pub last_recorded_total_payouts: Balance,
+	/// The total rewards claimed by the members.
+	pub total_rewards_claimed: Balance,
+}
+
+/// Unbonding funds of a pool.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, Default, RuntimeDebug, TypeInfo)]
+pub struct UnbondPool<Balance> {
+	/// The total points of the members unbonding in this pool.
+	pub points: Balance,
+	/// The funds backing the points.
+	pub balance: Balance,
+}
+
+/// The unbonding pools of a pool. Their points are all issued at the same rate, so that pools
+/// can be merged by adding them up.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, Default, RuntimeDebug, TypeInfo)]
+pub struct UnbondPools<Balance> {
+	/// The pools which unlocked at least the bonding duration ago, merged together.
+	pub no_era: UnbondPool<Balance>,
+	/// The later pools, by the era at which they unlock, sorted by era.
+	pub with_era: Vec<(EraIndex, UnbondPool<Balance>)>,
+}
+
+impl<Balance: Saturating + Copy> UnbondPools<Balance> {
+	/// The total points and funds of all the pools.
+	fn total(&self) -> (Balance, Balance) {
+		self.with_era.iter().map(|(_, pool)| pool).fold(
+			(self.no_era.points, self.no_era.balance),
+			|(points, balance), pool| (points.saturating_add(pool.points), balance.saturating_add(pool.balance)),
+		)
+	}
+
+	/// Merge the pools unlocking at or before `era` into the era-less pool.
+	fn merge_until(&mut self, era: EraIndex) {
+		let no_era = &mut self.no_era;
+		self.with_era.retain(|(e, pool)| {
+			if *e > era {
+				return true;
+			}
+			no_era.points = no_era.points.saturating_add(pool.points);
+			no_era.balance = no_era.balance.saturating_add(pool.balance);
+			false
+		});
+	}
+}
+
+/// A member of a pool.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct PoolMember<Balance> {
+	/// The pool of the member.
+	pub pool_id: PoolId,
+	/// The points of the member in the bonded pool.
+	pub points: Balance,
+	/// The reward counter of the pool as of the last payout of the member.
+	pub last_reward_counter: u128,
+	/// The points of the member in the unbonding pools, by unlocking era.
+	pub unbonding: Vec<(EraIndex, Balance)>,
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as NominationPools {
+		/// The identifier of the last created pool.
+		pub LastPoolId get(fn last_pool_id): PoolId;
+
+		/// The pools, by identifier.
+		pub BondedPools get(fn bonded_pool):
+			map PoolId => Option<BondedPool<T::AccountId, BalanceOf<T>>>;
+
+		/// The reward accounting of the pools.
+		pub RewardPools get(fn reward_pool): map PoolId => RewardPool<BalanceOf<T>>;
+
+		/// The unbonding pools of the pools.
+		pub SubPools get(fn sub_pools): map PoolId => UnbondPools<BalanceOf<T>>;
+
+		/// The members of the pools.
+		pub PoolMembers get(fn pool_member): map T::AccountId => Option<PoolMember<BalanceOf<T>>>;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where
+		AccountId = <T as system::Trait>::AccountId,
+		Balance = BalanceOf<T>,
+	{
+		/// A pool has been created by an account.
+		Created(PoolId, AccountId),
+		/// An account has bonded an amount into a pool.
+		Bonded(AccountId, PoolId, Balance),
+		/// An amount of rewards has been paid out to a member of a pool.
+		PaidOut(AccountId, PoolId, Balance),
+		/// A member has unbonded an amount from a pool, unlocking at the given era.
+		Unbonded(AccountId, PoolId, Balance, EraIndex),
+		/// A member has withdrawn an amount of unlocked funds from a pool.
+		Withdrawn(AccountId, PoolId, Balance),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		fn deposit_event() = default;
+
+		/// The minimum amount to create a pool with.
+		const MinCreateBond: BalanceOf<T> = T::MinCreateBond::get();
+
+		/// The minimum amount to join a pool with.
+		const MinJoinBond: BalanceOf<T> = T::MinJoinBond::get();
+
+		/// The maximum number of distinct eras in which a member can have funds unlocking.
+		const MaxUnbonding: u32 = T::MaxUnbonding::get();
+
+		/// Create a new pool, bonding `amount` from the sender into it. The sender becomes the
+		/// first member of the pool, and `root` is allowed to nominate on its behalf.
+		///
+		/// The existential deposit is also transferred from the sender, to keep the reward
+		/// account of the pool alive.
+		///
+		/// The dispatch origin for this call must be _Signed_, and not be a member of a pool.
+		///
+		/// # <weight>
+		/// - Two balance transfers.
+		/// - One staking bond.
+		/// - Five storage writes.
+		/// - Two events.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(1_000_000)]
+		fn create(origin, #[compact] amount: BalanceOf<T>, root: T::AccountId) {
+			let who = ensure_signed(origin)?;
+			ensure!(amount >= T::MinCreateBond::get(), "amount below minimum create bond");
+			ensure!(!<PoolMembers<T>>::exists(&who), "already a pool member");
+			let ed = T::Currency::minimum_balance();
+			ensure!(
+				T::Currency::free_balance(&who) >= amount.saturating_add(ed),
+				"insufficient balance to create pool"
+			);
+			let pool_id = Self::last_pool_id().checked_add(1).ok_or("too many pools")?;
+
+			let bonded_account = Self::bonded_account(pool_id);
+			let reward_account = Self::reward_account(pool_id);
+			T::Currency::transfer(&who, &bonded_account, amount)?;
+			if !ed.is_zero() {
+				T::Currency::transfer(&who, &reward_account, ed)?;
+			}
+			T::Staking::bond(&bonded_account, &reward_account, amount)?;
+
+			LastPoolId::put(pool_id);
+			<BondedPools<T>>::insert(pool_id, BondedPool { root, points: amount, member_count: 1 });
+			<RewardPools<T>>::insert(pool_id, RewardPool::default());
+			<PoolMembers<T>>::insert(&who, PoolMember {
+				pool_id,
+				points: amount,
+				last_reward_counter: 0,
+				unbonding: vec![],
+			});
+
+			Self::deposit_event(RawEvent::Created(pool_id, who.clone()));
+			Self::deposit_event(RawEvent::Bonded(who, pool_id, amount));
+		}
+
+		/// Join the pool `pool_id`, bonding `amount` from the sender into it.
+		///
+		/// The dispatch origin for this call must be _Signed_, and not be a member of a pool.
+		///
+		/// # <weight>
+		/// - One balance transfer.
+		/// - One staking bond.
+		/// - Three storage writes.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(1_000_000)]
+		fn join(origin, #[compact] amount: BalanceOf<T>, pool_id: PoolId) {
+			let who = ensure_signed(origin)?;
+			ensure!(amount >= T::MinJoinBond::get(), "amount below minimum join bond");
+			ensure!(!<PoolMembers<T>>::exists(&who), "already a pool member");
+			let mut pool = Self::bonded_pool(pool_id).ok_or("pool not found")?;
+			Self::ensure_staking_open()?;
+
+			let active = Self::active_stake(pool_id);
+			ensure!(pool.points.is_zero() || !active.is_zero(), "pool is fully slashed");
+			let points = Self::balance_to_points(amount, pool.points, active);
+			// record the rewards received so far, before the points change.
+			let reward_pool = Self::update_reward_pool(pool_id, pool.points);
+
+			let bonded_account = Self::bonded_account(pool_id);
+			T::Currency::transfer(&who, &bonded_account, amount)?;
+			Self::bond_into_pool(pool_id, bonded_account, amount)?;
+
+			pool.points = pool.points.saturating_add(points);
+			pool.member_count = pool.member_count.saturating_add(1);
+			<PoolMembers<T>>::insert(&who, PoolMember {
+				pool_id,
+				points,
+				last_reward_counter: reward_pool.reward_counter,
+				unbonding: vec![],
+			});
+			<BondedPools<T>>::insert(pool_id, pool);
+			<RewardPools<T>>::insert(pool_id, reward_pool);
+
+			Self::deposit_event(RawEvent::Bonded(who, pool_id, amount));
+		}
+
+		/// Pay out the pending rewards of the sender.
+		///
+		/// The dispatch origin for this call must be _Signed_ by a member of a pool.
+		///
+		/// # <weight>
+		/// - One balance transfer.
+		/// - Two storage writes.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(500_000)]
+		fn claim_payout(origin) {
+			let who = ensure_signed(origin)?;
+			let mut member = Self::pool_member(&who).ok_or("not a pool member")?;
+			let pool = Self::bonded_pool(member.pool_id).ok_or("pool not found")?;
+
+			Self::do_claim_payout(&who, &mut member, &pool)?;
+			<PoolMembers<T>>::insert(&who, member);
+		}
+
+		/// Unbond `points` of the points of the sender. The pending rewards of the sender are
+		/// paid out first.
+		///
+		/// The corresponding funds unlock after the bonding duration of the Staking module, and
+		/// can then be withdrawn with `withdraw_unbonded`.
+		///
+		/// The dispatch origin for this call must be _Signed_ by a member of a pool.
+		///
+		/// # <weight>
+		/// - O(B) where B is the bonding duration, which bounds the number of unbonding pools kept
+		///   by era.
+		/// - One balance transfer.
+		/// - One staking unbond.
+		/// - Four storage writes.
+		/// - Two events.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(1_000_000)]
+		fn unbond(origin, #[compact] points: BalanceOf<T>) {
+			let who = ensure_signed(origin)?;
+			let mut member = Self::pool_member(&who).ok_or("not a pool member")?;
+			ensure!(!points.is_zero(), "cannot unbond zero points");
+			ensure!(points <= member.points, "not enough points");
+			let pool_id = member.pool_id;
+			let mut pool = Self::bonded_pool(pool_id).ok_or("pool not found")?;
+			Self::ensure_staking_open()?;
+
+			let current_era = T::Staking::current_era();
+			let era = current_era + T::Staking::bonding_duration();
+			let merges = member.unbonding.last().map_or(false, |(e, _)| *e == era);
+			ensure!(
+				merges || (member.unbonding.len() as u32) < T::MaxUnbonding::get(),
+				"too many unbonding chunks"
+			);
+
+			Self::do_claim_payout(&who, &mut member, &pool)?;
+
+			let mut sub_pools = Self::merged_sub_pools(pool_id, current_era);
+			Self::apply_unbonding_slash(pool_id, &mut sub_pools);
+
+			let balance = Self::points_to_balance(points, pool.points, Self::active_stake(pool_id));
+			T::Staking::unbond(&Self::reward_account(pool_id), balance)?;
+
+			let (total_points, total_balance) = sub_pools.total();
+			let unbond_points = Self::balance_to_points(balance, total_points, total_balance);
+			if sub_pools.with_era.last().map_or(true, |(e, _)| *e != era) {
+				sub_pools.with_era.push((era, UnbondPool::default()));
+			}
+			let (_, unbond_pool) = sub_pools.with_era.last_mut().expect("pushed above if missing; qed");
+			unbond_pool.points = unbond_pool.points.saturating_add(unbond_points);
+			unbond_pool.balance = unbond_pool.balance.saturating_add(balance);
+
+			match member.unbonding.last_mut() {
+				Some((_, p)) if merges => *p = p.saturating_add(unbond_points),
+				_ => member.unbonding.push((era, unbond_points)),
+			}
+			member.points -= points;
+			pool.points = pool.points.saturating_sub(points);
+
+			<PoolMembers<T>>::insert(&who, member);
+			<BondedPools<T>>::insert(pool_id, pool);
+			<SubPools<T>>::insert(pool_id, sub_pools);
+
+			Self::deposit_event(RawEvent::Unbonded(who, pool_id, balance, era));
+		}
+
+		/// Withdraw the unbonded funds of the sender which have unlocked.
+		///
+		/// The sender stops being a member of their pool once they have no points left and all of
+		/// their funds are withdrawn.
+		///
+		/// The dispatch origin for this call must be _Signed_ by a member of a pool.
+		///
+		/// # <weight>
+		/// - O(B) where B is the bonding duration, which bounds the number of unbonding pools kept
+		///   by era.
+		/// - One balance transfer.
+		/// - One staking withdrawal.
+		/// - Three storage writes.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(1_000_000)]
+		fn withdraw_unbonded(origin) {
+			let who = ensure_signed(origin)?;
+			let mut member = Self::pool_member(&who).ok_or("not a pool member")?;
+			let pool_id = member.pool_id;
+			let mut pool = Self::bonded_pool(pool_id).ok_or("pool not found")?;
+			Self::ensure_staking_open()?;
+
+			let current_era = T::Staking::current_era();
+			ensure!(
+				member.unbonding.iter().any(|(era, _)| *era <= current_era),
+				"nothing to withdraw"
+			);
+
+			let mut sub_pools = Self::merged_sub_pools(pool_id, current_era);
+			Self::apply_unbonding_slash(pool_id, &mut sub_pools);
+
+			let reward_account = Self::reward_account(pool_id);
+			if T::Staking::active_stake(&reward_account).is_some() {
+				T::Staking::withdraw_unbonded(&reward_account)?;
+			}
+
+			let mut balance = BalanceOf::<T>::zero();
+			member.unbonding.retain(|(era, points)| {
+				if *era > current_era {
+					return true;
+				}
+				// the pool of the era may have been merged into the era-less pool.
+				let unbond_pool = match sub_pools.with_era.iter_mut().find(|(e, _)| e == era) {
+					Some((_, unbond_pool)) => unbond_pool,
+					None => &mut sub_pools.no_era,
+				};
+				let share = Self::points_to_balance(*points, unbond_pool.points, unbond_pool.balance);
+				unbond_pool.points = unbond_pool.points.saturating_sub(*points);
+				unbond_pool.balance = unbond_pool.balance.saturating_sub(share);
+				balance = balance.saturating_add(share);
+				false
+			});
+			sub_pools.with_era.retain(|(_, p)| !p.points.is_zero());
+
+			T::Currency::transfer(&Self::bonded_account(pool_id), &who, balance)?;
+
+			if member.points.is_zero() && member.unbonding.is_empty() {
+				<PoolMembers<T>>::remove(&who);
+				pool.member_count = pool.member_count.saturating_sub(1);
+				<BondedPools<T>>::insert(pool_id, pool);
+			} else {
+				<PoolMembers<T>>::insert(&who, member);
+			}
+			<SubPools<T>>::insert(pool_id, sub_pools);
+
+			Self::deposit_event(RawEvent::Withdrawn(who, pool_id, balance));
+		}
+
+		/// Nominate `targets` on behalf of the pool `pool_id`.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the root of the pool.
+		///
+		/// # <weight>
+		/// - One staking nomination.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(750_000)]
+		fn nominate(origin, pool_id: PoolId, targets: Vec<<T::Lookup as StaticLookup>::Source>) {
+			let who = ensure_signed(origin)?;
+			let pool = Self::bonded_pool(pool_id).ok_or("pool not found")?;
+			ensure!(pool.root == who, "not the pool root");
+
+			let targets = targets.into_iter()
+				.map(|t| T::Lookup::lookup(t))
+				.collect::<Result<Vec<T::AccountId>, _>>()?;
+			T::Staking::nominate(&Self::reward_account(pool_id), targets)?;
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// The bonded account of a pool, which is bonded as a nominator.
+	pub fn bonded_account(pool_id: PoolId) -> T::AccountId {
+		MODULE_ID.into_sub_account((AccountType::Bonded, pool_id))
+	}
+
+	/// The reward account of a pool, which is the controller of the bonded account and receives
+	/// its rewards.
+	pub fn reward_account(pool_id: PoolId) -> T::AccountId {
+		MODULE_ID.into_sub_account((AccountType::Reward, pool_id))
+	}
+
+	/// The active stake of a pool in the Staking module.
+	fn active_stake(pool_id: PoolId) -> BalanceOf<T> {
+		T::Staking::active_stake(&Self::reward_account(pool_id)).unwrap_or_default()
+	}
+
+	/// Ensure the Staking module accepts changes to the stake of the pools.
+	fn ensure_staking_open() -> dispatch::Result {
+		ensure!(!T::Staking::election_ongoing(), "staking election window is open");
+		Ok(())
+	}
+
+	/// The points to issue for `balance`, given the total points and balance of a pool.
+	fn balance_to_points(
+		balance: BalanceOf<T>,
+		total_points: BalanceOf<T>,
+		total_balance: BalanceOf<T>,
+	) -> BalanceOf<T> {
+		if total_points.is_zero() || total_balance.is_zero() {
+			return balance;
+		}
+		multiply_by_rational(
+			balance.saturated_into(),
+			total_points.saturated_into(),
+			total_balance.saturated_into(),
+		).map(|p| p.saturated_into()).unwrap_or_else(|_| Zero::zero())
+	}
+
+	/// The balance backing `points`, given the total points and balance of a pool.
+	fn points_to_balance(
+		points: BalanceOf<T>,
+		total_points: BalanceOf<T>,
+		total_balance: BalanceOf<T>,
+	) -> BalanceOf<T> {
+		if total_points.is_zero() {
+			return Zero::zero();
+		}
+		multiply_by_rational(
+			points.saturated_into(),
+			total_balance.saturated_into(),
+			total_points.saturated_into(),
+		).map(|b| b.saturated_into()).unwrap_or_else(|_| Zero::zero())
+	}
+
+	/// Record the rewards received by a pool since the last update, shared among its current
+	/// `points`. The returned reward pool must be written back by the caller.
+	fn update_reward_pool(pool_id: PoolId, points: BalanceOf<T>) -> RewardPool<BalanceOf<T>> {
+		let mut reward_pool = Self::reward_pool(pool_id);
+		// the existential deposit of the reward account is not a reward.
+		let balance = T::Currency::free_balance(&Self::reward_account(pool_id))
+			.saturating_sub(T::Currency::minimum_balance());
+		let total_payouts = balance.saturating_add(reward_pool.total_rewards_claimed);
+
+		// rewards received while the pool has no points are kept for the next members.
+		if !points.is_zero() {
+			let new_payouts = total_payouts.saturating_sub(reward_pool.last_recorded_total_payouts);
+			let increase = multiply_by_rational(
+				new_payouts.saturated_into(),
+				REWARD_PRECISION,
+				points.saturated_into(),
+			).unwrap_or(0);
+			reward_pool.reward_counter = reward_pool.reward_counter.saturating_add(increase);
+			reward_pool.last_recorded_total_payouts = total_payouts;
+		}
+		reward_pool
+	}
+
+	/// Pay out the pending rewards of `member` of `pool`. The member must be written back by
+	/// the caller.
+	fn do_claim_payout(
+		who: &T::AccountId,
+		member: &mut PoolMember<BalanceOf<T>>,
+		pool: &BondedPool<T::AccountId, BalanceOf<T>>,
+	) -> dispatch::Result {
+		let pool_id = member.pool_id;
+		let mut reward_pool = Self::update_reward_pool(pool_id, pool.points);
+		let pending: BalanceOf<T> = multiply_by_rational(
+			reward_pool.reward_counter.saturating_sub(member.last_reward_counter),
+			member.points.saturated_into(),
+			REWARD_PRECISION,
+		).unwrap_or(0).saturated_into();
+
+		if !pending.is_zero() {
+			T::Currency::transfer(&Self::reward_account(pool_id), who, pending)?;
+			reward_pool.total_rewards_claimed =
+				reward_pool.total_rewards_claimed.saturating_add(pending);
+			Self::deposit_event(RawEvent::PaidOut(who.clone(), pool_id, pending));
+		}
+		member.last_reward_counter = reward_pool.reward_counter;
+		<RewardPools<T>>::insert(pool_id, reward_pool);
+		Ok(())
+	}
+
+	/// Bond `amount`, already transferred to the bonded account of a pool, into the Staking
+	/// module. The bonded account is bonded anew if it was fully withdrawn before.
+	fn bond_into_pool(
+		pool_id: PoolId,
+		bonded_account: T::AccountId,
+		amount: BalanceOf<T>,
+	) -> dispatch::Result {
+		let reward_account = Self::reward_account(pool_id);
+		if T::Staking::active_stake(&reward_account).is_some() {
+			T::Staking::bond_extra(&bonded_account, amount)
+		} else {
+			T::Staking::bond(&bonded_account, &reward_account, amount)
+		}
+	}
+
+	/// The unbonding pools of a pool, with the pools which unlocked at least the bonding
+	/// duration before `current_era` merged into the era-less pool. This keeps at most twice the
+	/// bonding duration of pools by era.
+	fn merged_sub_pools(pool_id: PoolId, current_era: EraIndex) -> UnbondPools<BalanceOf<T>> {
+		let mut sub_pools = Self::sub_pools(pool_id);
+		if let Some(era) = current_era.checked_sub(T::Staking::bonding_duration()) {
+			sub_pools.merge_until(era);
+		}
+		sub_pools
+	}
+
+	/// Apply any slash of the unbonding funds of a pool to its unbonding pools, proportionally.
+	///
+	/// The funds of the bonded account which are not actively bonded are all unbonding funds,
+	/// whether they are still unlocking in the Staking module or already withdrawn from it.
+	fn apply_unbonding_slash(pool_id: PoolId, sub_pools: &mut UnbondPools<BalanceOf<T>>) {
+		let (_, tracked) = sub_pools.total();
+		let actual = T::Currency::free_balance(&Self::bonded_account(pool_id))
+			.saturating_sub(Self::active_stake(pool_id));
+
+		if actual < tracked {
+			let with_era = sub_pools.with_era.iter_mut().map(|(_, pool)| pool);
+			for unbond_pool in rstd::iter::once(&mut sub_pools.no_era).chain(with_era) {
+				unbond_pool.balance = multiply_by_rational(
+					unbond_pool.balance.saturated_into(),
+					actual.saturated_into(),
+					tracked.saturated_into(),
+				).map(|b| b.saturated_into()).unwrap_or_else(|_| Zero::zero());
+			}
+		}
+	}
+}
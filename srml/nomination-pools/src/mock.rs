@@ -0,0 +1,214 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Test utilities
+
+use sr_primitives::{Perbill, KeyTypeId};
+use sr_primitives::curve::PiecewiseLinear;
+use sr_primitives::traits::{IdentityLookup, Convert, OpaqueKeys, SaturatedConversion};
+use sr_primitives::testing::{Header, UintAuthorityId, TestXt};
+use sr_staking_primitives::SessionIndex;
+use primitives::{H256, crypto::key_types};
+use support::{impl_outer_origin, parameter_types};
+use staking::{EraIndex, StakerStatus};
+use crate::{Module, Trait};
+
+/// The account id type of the tests. It is large enough to hold the sub-accounts of the pools.
+pub type AccountId = u128;
+pub type BlockNumber = u64;
+pub type Balance = u64;
+
+/// Simple structure that exposes how u64 currency can be represented as... u64.
+pub struct CurrencyToVoteHandler;
+impl Convert<u64, u64> for CurrencyToVoteHandler {
+	fn convert(x: u64) -> u64 { x }
+}
+impl Convert<u128, u64> for CurrencyToVoteHandler {
+	fn convert(x: u128) -> u64 { x.saturated_into() }
+}
+
+pub struct TestSessionHandler;
+impl session::SessionHandler<AccountId> for TestSessionHandler {
+	const KEY_TYPE_IDS: &'static [KeyTypeId] = &[key_types::DUMMY];
+	fn on_genesis_session<Ks: OpaqueKeys>(_validators: &[(AccountId, Ks)]) {}
+	fn on_new_session<Ks: OpaqueKeys>(_: bool, _: &[(AccountId, Ks)], _: &[(AccountId, Ks)]) {}
+	fn on_disabled(_validator_index: usize) {}
+}
+
+impl_outer_origin!{
+	pub enum Origin for Test {}
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Test;
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const MaximumBlockWeight: u32 = 1024;
+	pub const MaximumBlockLength: u32 = 2 * 1024;
+	pub const AvailableBlockRatio: Perbill = Perbill::one();
+}
+impl system::Trait for Test {
+	type Origin = Origin;
+	type Index = u64;
+	type BlockNumber = BlockNumber;
+	type Call = ();
+	type Hash = H256;
+	type Hashing = ::sr_primitives::traits::BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = ();
+	type BlockHashCount = BlockHashCount;
+	type MaximumBlockWeight = MaximumBlockWeight;
+	type AvailableBlockRatio = AvailableBlockRatio;
+	type MaximumBlockLength = MaximumBlockLength;
+	type Version = ();
+}
+parameter_types! {
+	pub const ExistentialDeposit: Balance = 1;
+	pub const TransferFee: Balance = 0;
+	pub const CreationFee: Balance = 0;
+}
+impl balances::Trait for Test {
+	type Balance = Balance;
+	type OnFreeBalanceZero = Staking;
+	type OnNewAccount = ();
+	type Event = ();
+	type TransferPayment = ();
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type TransferFee = TransferFee;
+	type CreationFee = CreationFee;
+}
+parameter_types! {
+	pub const Period: BlockNumber = 1;
+	pub const Offset: BlockNumber = 0;
+	pub const DisabledValidatorsThreshold: Perbill = Perbill::from_percent(25);
+}
+impl session::Trait for Test {
+	type OnSessionEnding = session::historical::NoteHistoricalRoot<Test, Staking>;
+	type Keys = UintAuthorityId;
+	type ShouldEndSession = session::PeriodicSessions<Period, Offset>;
+	type SessionHandler = TestSessionHandler;
+	type Event = ();
+	type ValidatorId = AccountId;
+	type ValidatorIdOf = staking::StashOf<Test>;
+	type SelectInitialValidators = Staking;
+	type DisabledValidatorsThreshold = DisabledValidatorsThreshold;
+}
+impl session::historical::Trait for Test {
+	type FullIdentification = staking::Exposure<AccountId, Balance>;
+	type FullIdentificationOf = staking::ExposureOf<Test>;
+}
+parameter_types! {
+	pub const MinimumPeriod: u64 = 5;
+}
+impl timestamp::Trait for Test {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = MinimumPeriod;
+}
+srml_staking_reward_curve::build! {
+	const I_NPOS: PiecewiseLinear<'static> = curve!(
+		min_inflation: 0_025_000,
+		max_inflation: 0_100_000,
+		ideal_stake: 0_500_000,
+		falloff: 0_050_000,
+		max_piece_count: 40,
+		test_precision: 0_005_000,
+	);
+}
+parameter_types! {
+	pub const SessionsPerEra: SessionIndex = 3;
+	pub const BondingDuration: EraIndex = 3;
+	pub const SlashDeferDuration: EraIndex = 0;
+	pub const HistoryDepth: EraIndex = 84;
+	pub const MaxNominatorRewardedPerValidator: u32 = 64;
+	pub const RewardCurve: &'static PiecewiseLinear<'static> = &I_NPOS;
+	pub const ElectionLookahead: BlockNumber = 0;
+}
+pub type Extrinsic = TestXt<staking::Call<Test>, ()>;
+type SubmitTransaction = system::offchain::TransactionSubmitter<(), staking::Call<Test>, Extrinsic>;
+impl staking::Trait for Test {
+	type Currency = Balances;
+	type Time = timestamp::Module<Self>;
+	type CurrencyToVote = CurrencyToVoteHandler;
+	type OnRewardMinted = ();
+	type Event = ();
+	type Slash = ();
+	type Reward = ();
+	type SessionsPerEra = SessionsPerEra;
+	type BondingDuration = BondingDuration;
+	type SlashDeferDuration = SlashDeferDuration;
+	type SlashCancelOrigin = system::EnsureRoot<Self::AccountId>;
+	type HistoryDepth = HistoryDepth;
+	type MaxNominatorRewardedPerValidator = MaxNominatorRewardedPerValidator;
+	type SessionInterface = Self;
+	type RewardCurve = RewardCurve;
+	type NextNewSession = session::PeriodicSessions<Period, Offset>;
+	type ElectionLookahead = ElectionLookahead;
+	type Call = staking::Call<Test>;
+	type SubmitTransaction = SubmitTransaction;
+	type KeyType = UintAuthorityId;
+}
+parameter_types! {
+	pub const MinCreateBond: Balance = 10;
+	pub const MinJoinBond: Balance = 2;
+	pub const MaxUnbonding: u32 = 2;
+}
+impl Trait for Test {
+	type Event = ();
+	type Currency = Balances;
+	type Staking = Staking;
+	type MinCreateBond = MinCreateBond;
+	type MinJoinBond = MinJoinBond;
+	type MaxUnbonding = MaxUnbonding;
+}
+
+pub type Balances = balances::Module<Test>;
+pub type Staking = staking::Module<Test>;
+pub type Pools = Module<Test>;
+
+pub fn new_test_ext() -> runtime_io::TestExternalities {
+	let mut storage = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let _ = balances::GenesisConfig::<Test> {
+		balances: vec![
+			(1, 1000),
+			(2, 1000),
+			(3, 1000),
+			(10, 1),
+			(11, 2000),
+			(20, 1),
+			(21, 2000),
+		],
+	}.assimilate_storage(&mut storage);
+	let _ = staking::GenesisConfig::<Test> {
+		current_era: 0,
+		stakers: vec![
+			// (stash, controller, staked_amount, status)
+			(11, 10, 1000, StakerStatus::<AccountId>::Validator),
+			(21, 20, 1000, StakerStatus::<AccountId>::Validator),
+		],
+		validator_count: 2,
+		minimum_validator_count: 0,
+		slash_reward_fraction: Perbill::from_percent(10),
+		..Default::default()
+	}.assimilate_storage(&mut storage);
+	let _ = session::GenesisConfig::<Test> {
+		keys: vec![11, 21].into_iter().map(|x| (x, UintAuthorityId(x as u64))).collect(),
+	}.assimilate_storage(&mut storage);
+	storage.into()
+}
@@ -0,0 +1,261 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tests for the module.
+
+use super::*;
+use mock::*;
+use support::{assert_ok, assert_noop, StorageMap, StorageValue};
+
+fn active_stake(pool_id: PoolId) -> Balance {
+	Staking::ledger(&Pools::reward_account(pool_id)).unwrap().active
+}
+
+fn set_era(era: EraIndex) {
+	staking::CurrentEra::put(era);
+}
+
+fn add_rewards(pool_id: PoolId, amount: Balance) {
+	let _ = Balances::deposit_creating(&Pools::reward_account(pool_id), amount);
+}
+
+/// Slash the active stake of a pool, as the Staking module would.
+fn slash_active(pool_id: PoolId, amount: Balance) {
+	<staking::Ledger<Test>>::mutate(&Pools::reward_account(pool_id), |l| {
+		let l = l.as_mut().unwrap();
+		l.active -= amount;
+		l.total -= amount;
+	});
+	let _ = Balances::slash(&Pools::bonded_account(pool_id), amount);
+}
+
+/// Slash the unlocking funds of a pool, as the Staking module would.
+fn slash_unlocking(pool_id: PoolId, amount: Balance) {
+	<staking::Ledger<Test>>::mutate(&Pools::reward_account(pool_id), |l| {
+		l.as_mut().unwrap().total -= amount;
+	});
+	let _ = Balances::slash(&Pools::bonded_account(pool_id), amount);
+}
+
+#[test]
+fn create_works() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Pools::create(Origin::signed(1), 9, 1), "amount below minimum create bond");
+		assert_noop!(
+			Pools::create(Origin::signed(1), 1000, 1),
+			"insufficient balance to create pool"
+		);
+
+		assert_ok!(Pools::create(Origin::signed(1), 100, 1));
+		assert_eq!(Pools::last_pool_id(), 1);
+		assert_eq!(
+			Pools::bonded_pool(1),
+			Some(BondedPool { root: 1, points: 100, member_count: 1 })
+		);
+		assert_eq!(Pools::pool_member(1).unwrap().points, 100);
+
+		// the pool is bonded, and its reward account is kept alive.
+		assert_eq!(Staking::bonded(&Pools::bonded_account(1)), Some(Pools::reward_account(1)));
+		assert_eq!(active_stake(1), 100);
+		assert_eq!(Balances::free_balance(&Pools::reward_account(1)), 1);
+		assert_eq!(Balances::free_balance(&1), 899);
+
+		assert_noop!(Pools::create(Origin::signed(1), 100, 1), "already a pool member");
+	});
+}
+
+#[test]
+fn join_issues_points_by_active_stake() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pools::create(Origin::signed(1), 100, 1));
+		assert_noop!(Pools::join(Origin::signed(2), 1, 1), "amount below minimum join bond");
+		assert_noop!(Pools::join(Origin::signed(2), 50, 2), "pool not found");
+		assert_noop!(Pools::join(Origin::signed(1), 50, 1), "already a pool member");
+
+		assert_ok!(Pools::join(Origin::signed(2), 50, 1));
+		assert_eq!(Pools::pool_member(2).unwrap().points, 50);
+		assert_eq!(active_stake(1), 150);
+		assert_eq!(Balances::free_balance(&2), 950);
+
+		// after a slash, the same amount buys more points.
+		slash_active(1, 30);
+		assert_ok!(Pools::join(Origin::signed(3), 60, 1));
+		assert_eq!(Pools::pool_member(3).unwrap().points, 75);
+		assert_eq!(
+			Pools::bonded_pool(1),
+			Some(BondedPool { root: 1, points: 225, member_count: 3 })
+		);
+		assert_eq!(active_stake(1), 180);
+	});
+}
+
+#[test]
+fn rewards_are_shared_by_points() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pools::create(Origin::signed(1), 100, 1));
+		assert_ok!(Pools::join(Origin::signed(2), 50, 1));
+
+		add_rewards(1, 30);
+		assert_ok!(Pools::claim_payout(Origin::signed(1)));
+		assert_ok!(Pools::claim_payout(Origin::signed(2)));
+		assert_eq!(Balances::free_balance(&1), 899 + 20);
+		assert_eq!(Balances::free_balance(&2), 950 + 10);
+
+		// a new member does not get a share of the past rewards.
+		assert_ok!(Pools::join(Origin::signed(3), 150, 1));
+		add_rewards(1, 60);
+		assert_ok!(Pools::claim_payout(Origin::signed(3)));
+		assert_ok!(Pools::claim_payout(Origin::signed(1)));
+		assert_eq!(Balances::free_balance(&3), 850 + 30);
+		assert_eq!(Balances::free_balance(&1), 899 + 20 + 20);
+
+		// claiming again pays nothing more.
+		assert_ok!(Pools::claim_payout(Origin::signed(1)));
+		assert_eq!(Balances::free_balance(&1), 899 + 20 + 20);
+
+		// the pending rewards of 2 are paid out before their points change.
+		assert_ok!(Pools::unbond(Origin::signed(2), 50));
+		assert_eq!(Balances::free_balance(&2), 950 + 10 + 10);
+		assert_eq!(Balances::free_balance(&Pools::reward_account(1)), 1);
+
+		assert_noop!(Pools::claim_payout(Origin::signed(4)), "not a pool member");
+	});
+}
+
+#[test]
+fn unbond_and_withdraw_works() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pools::create(Origin::signed(1), 100, 1));
+		assert_ok!(Pools::join(Origin::signed(2), 60, 1));
+		assert_noop!(Pools::unbond(Origin::signed(2), 61), "not enough points");
+		assert_noop!(Pools::unbond(Origin::signed(2), 0), "cannot unbond zero points");
+
+		// unbonding twice in the same era is merged.
+		assert_ok!(Pools::unbond(Origin::signed(2), 20));
+		assert_ok!(Pools::unbond(Origin::signed(2), 10));
+		assert_eq!(Pools::pool_member(2).unwrap().unbonding, vec![(3, 30)]);
+		assert_eq!(active_stake(1), 130);
+		assert_noop!(Pools::withdraw_unbonded(Origin::signed(2)), "nothing to withdraw");
+
+		set_era(1);
+		assert_ok!(Pools::unbond(Origin::signed(2), 10));
+		set_era(2);
+		assert_noop!(Pools::unbond(Origin::signed(2), 10), "too many unbonding chunks");
+
+		set_era(3);
+		assert_ok!(Pools::withdraw_unbonded(Origin::signed(2)));
+		assert_eq!(Balances::free_balance(&2), 970);
+		let member = Pools::pool_member(2).unwrap();
+		assert_eq!((member.points, member.unbonding), (20, vec![(4, 10)]));
+
+		set_era(4);
+		assert_ok!(Pools::unbond(Origin::signed(2), 20));
+		assert_ok!(Pools::withdraw_unbonded(Origin::signed(2)));
+		assert_eq!(Balances::free_balance(&2), 980);
+
+		// once everything is withdrawn, 2 is no longer a member.
+		set_era(7);
+		assert_ok!(Pools::withdraw_unbonded(Origin::signed(2)));
+		assert_eq!(Balances::free_balance(&2), 1000);
+		assert_eq!(Pools::pool_member(2), None);
+		assert_eq!(Pools::bonded_pool(1).unwrap().member_count, 1);
+		assert_eq!(Pools::sub_pools(1), UnbondPools::default());
+		assert_eq!(active_stake(1), 100);
+	});
+}
+
+#[test]
+fn unbonding_members_share_the_staking_chunk_of_an_era() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pools::create(Origin::signed(1), 100, 1));
+		assert_ok!(Pools::join(Origin::signed(2), 100, 1));
+		assert_ok!(Pools::join(Origin::signed(3), 100, 1));
+
+		assert_ok!(Pools::unbond(Origin::signed(1), 10));
+		assert_ok!(Pools::unbond(Origin::signed(2), 20));
+		assert_ok!(Pools::unbond(Origin::signed(3), 30));
+		let ledger = Staking::ledger(&Pools::reward_account(1)).unwrap();
+		assert_eq!((ledger.active, ledger.unlocking.len()), (240, 1));
+
+		set_era(1);
+		assert_ok!(Pools::unbond(Origin::signed(1), 10));
+		assert_eq!(Staking::ledger(&Pools::reward_account(1)).unwrap().unlocking.len(), 2);
+	});
+}
+
+#[test]
+fn slashes_are_applied_proportionally() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pools::create(Origin::signed(1), 100, 1));
+		assert_ok!(Pools::join(Origin::signed(2), 100, 1));
+		assert_ok!(Pools::unbond(Origin::signed(2), 100));
+		assert_eq!(Pools::sub_pools(1).with_era, vec![(3, UnbondPool { points: 100, balance: 100 })]);
+
+		// half of both the active and the unlocking funds are slashed.
+		slash_active(1, 50);
+		slash_unlocking(1, 50);
+
+		set_era(3);
+		assert_ok!(Pools::withdraw_unbonded(Origin::signed(2)));
+		assert_eq!(Balances::free_balance(&2), 900 + 50);
+
+		assert_ok!(Pools::unbond(Origin::signed(1), 100));
+		assert_eq!(Pools::sub_pools(1).with_era, vec![(6, UnbondPool { points: 50, balance: 50 })]);
+	});
+}
+
+#[test]
+fn unbonding_pools_older_than_the_bonding_duration_are_merged() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pools::create(Origin::signed(1), 100, 1));
+		assert_ok!(Pools::join(Origin::signed(2), 100, 1));
+		assert_ok!(Pools::join(Origin::signed(3), 100, 1));
+
+		assert_ok!(Pools::unbond(Origin::signed(2), 50));
+		set_era(1);
+		assert_ok!(Pools::unbond(Origin::signed(3), 20));
+		set_era(4);
+		assert_ok!(Pools::unbond(Origin::signed(1), 10));
+		assert_eq!(Pools::sub_pools(1).with_era.len(), 3);
+
+		// the pools unlocking at eras 3 and 4 are merged, whether or not their members withdraw.
+		set_era(7);
+		assert_ok!(Pools::unbond(Origin::signed(1), 10));
+		assert_eq!(Pools::sub_pools(1), UnbondPools {
+			no_era: UnbondPool { points: 70, balance: 70 },
+			with_era: vec![(7, UnbondPool { points: 10, balance: 10 }), (10, UnbondPool { points: 10, balance: 10 })],
+		});
+
+		// the members of the merged pools still withdraw their share.
+		assert_ok!(Pools::withdraw_unbonded(Origin::signed(2)));
+		assert_eq!(Balances::free_balance(&2), 900 + 50);
+		assert_ok!(Pools::withdraw_unbonded(Origin::signed(3)));
+		assert_eq!(Balances::free_balance(&3), 900 + 20);
+		assert_eq!(Pools::sub_pools(1).no_era, UnbondPool { points: 0, balance: 0 });
+	});
+}
+
+#[test]
+fn only_root_can_nominate() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pools::create(Origin::signed(1), 100, 3));
+		assert_noop!(Pools::nominate(Origin::signed(1), 1, vec![11, 21]), "not the pool root");
+		assert_noop!(Pools::nominate(Origin::signed(3), 2, vec![11, 21]), "pool not found");
+
+		assert_ok!(Pools::nominate(Origin::signed(3), 1, vec![11, 21]));
+		assert_eq!(Staking::nominators(&Pools::bonded_account(1)), vec![11, 21]);
+	});
+}
@@ -0,0 +1,673 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Preimage Module
+//!
+//! - [`preimage::Trait`](./trait.Trait.html)
+//! - [`Call`](./enum.Call.html)
+//!
+//! ## Overview
+//!
+//! The Preimage module stores the preimages of hashes on behalf of other modules, so that they
+//! only need to keep track of the hash of, for instance, a large proposal.
+//!
+//! Modules request the preimages they need through the `preimage::PreimageProvider` trait, which
+//! this module implements, and may store them through the `preimage::PreimageRecipient` trait.
+//! Requests are counted, and a preimage is kept for as long as it is requested. Anyone may note a
+//! preimage: a deposit proportional to its size is reserved, unless the preimage is requested at
+//! the time it is noted. Once a preimage is not requested anymore, it is removed after
+//! `ReapPeriod` blocks and its deposit, if any, is refunded.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! * `note_preimage` - Store a preimage, reserving a deposit if it is not requested.
+//! * `unnote_preimage` - Remove a preimage that is not requested, refunding its deposit.
+//! * `request_preimage` - Request a preimage to be kept. Called by the manager origin.
+//! * `unrequest_preimage` - Cancel a previous request. Called by the manager origin.
+//!
+//! [`Call`]: ./enum.Call.html
+//! [`Trait`]: ./trait.Trait.html
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use rstd::prelude::*;
+use codec::{Encode, Decode};
+use sr_primitives::{
+	RuntimeDebug,
+	traits::{EnsureOrigin, Hash, Saturating},
+	weights::SimpleDispatchInfo,
+};
+use support::{
+	decl_module, decl_event, decl_storage, ensure, metadata::TypeInfo,
+	traits::{Currency, ReservableCurrency, Get, preimage::{PreimageProvider, PreimageRecipient}},
+};
+use system::ensure_signed;
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+
+pub trait Trait: system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+	/// The currency trait.
+	type Currency: ReservableCurrency<Self::AccountId>;
+
+	/// The origin which may request and unrequest preimages, and note and unnote them without a
+	/// deposit.
+	type ManagerOrigin: EnsureOrigin<Self::Origin>;
+
+	/// The maximum size of a preimage, in bytes.
+	type MaxSize: Get<u32>;
+
+	/// The base deposit for noting a preimage.
+	type BaseDeposit: Get<BalanceOf<Self>>;
+
+	/// The additional deposit for each byte of a noted preimage.
+	type ByteDeposit: Get<BalanceOf<Self>>;
+
+	/// The number of blocks after which a preimage that is not requested is removed.
+	type ReapPeriod: Get<Self::BlockNumber>;
+}
+
+/// The request status of a preimage.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub enum RequestStatus<AccountId, Balance, BlockNumber> {
+	/// The preimage is noted but not requested. It is removed at block `expiry`.
+	Unrequested {
+		/// The account which noted the preimage and its deposit, if it was noted with one.
+		deposit: Option<(AccountId, Balance)>,
+		/// The length of the preimage.
+		len: u32,
+		/// The block at which the preimage is removed.
+		expiry: BlockNumber,
+	},
+	/// The preimage is requested `count` times. It may not be noted yet.
+	Requested {
+		/// The account which noted the preimage and its deposit, if it was noted with one before
+		/// being requested.
+		deposit: Option<(AccountId, Balance)>,
+		/// The number of requests for the preimage.
+		count: u32,
+		/// The length of the preimage, if it is noted.
+		len: Option<u32>,
+	},
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Preimage {
+		/// The request status of the preimage of a hash.
+		pub StatusFor get(fn status_for):
+			map T::Hash => Option<RequestStatus<T::AccountId, BalanceOf<T>, T::BlockNumber>>;
+
+		/// The noted preimages.
+		pub PreimageFor get(fn preimage_for): map T::Hash => Option<Vec<u8>>;
+
+		/// The hashes of the preimages to remove at a given block.
+		pub Expiring get(fn expiring): map T::BlockNumber => Vec<T::Hash>;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where
+		<T as system::Trait>::Hash,
+	{
+		/// A preimage has been noted.
+		Noted(Hash),
+		/// A preimage has been requested.
+		Requested(Hash),
+		/// A preimage has been removed.
+		Cleared(Hash),
+	}
+);
+
+decl_module! {
+	/// Preimage module declaration.
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		fn deposit_event() = default;
+
+		/// The maximum size of a preimage, in bytes.
+		const MaxSize: u32 = T::MaxSize::get();
+
+		/// The base deposit for noting a preimage.
+		const BaseDeposit: BalanceOf<T> = T::BaseDeposit::get();
+
+		/// The additional deposit for each byte of a noted preimage.
+		const ByteDeposit: BalanceOf<T> = T::ByteDeposit::get();
+
+		/// The number of blocks after which a preimage that is not requested is removed.
+		const ReapPeriod: T::BlockNumber = T::ReapPeriod::get();
+
+		/// Note a preimage.
+		///
+		/// If the preimage is not requested, a deposit of `BaseDeposit` plus `ByteDeposit` per
+		/// byte is reserved from the sender, unless the sender is the manager origin. The deposit
+		/// is refunded once the preimage is removed.
+		///
+		/// # <weight>
+		/// - O(P) where P is the size of the preimage.
+		/// - At most one balance operation.
+		/// - Three storage writes.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		fn note_preimage(origin, bytes: Vec<u8>) {
+			let maybe_sender = match T::ManagerOrigin::try_origin(origin) {
+				Ok(_) => None,
+				Err(origin) => Some(ensure_signed(origin)?),
+			};
+			Self::note_bytes(bytes, maybe_sender.as_ref())?;
+		}
+
+		/// Remove a preimage that is not requested, refunding its deposit.
+		///
+		/// The sender must be the account which noted the preimage, or the manager origin. If the
+		/// preimage is requested, only its deposit is refunded and the preimage is kept.
+		///
+		/// # <weight>
+		/// - O(E) where E is the number of preimages expiring at the same block.
+		/// - One balance operation.
+		/// - Three storage writes.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		fn unnote_preimage(origin, hash: T::Hash) {
+			let maybe_sender = match T::ManagerOrigin::try_origin(origin) {
+				Ok(_) => None,
+				Err(origin) => Some(ensure_signed(origin)?),
+			};
+			Self::do_unnote(&hash, maybe_sender.as_ref())?;
+		}
+
+		/// Request a preimage to be kept until it is unrequested. Noting it becomes free.
+		///
+		/// The dispatch origin for this call must be `ManagerOrigin`.
+		///
+		/// # <weight>
+		/// - O(E) where E is the number of preimages expiring at the same block.
+		/// - Two storage writes.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedOperational(100_000)]
+		fn request_preimage(origin, hash: T::Hash) {
+			T::ManagerOrigin::ensure_origin(origin)?;
+			Self::do_request(&hash);
+		}
+
+		/// Cancel a previous request for a preimage.
+		///
+		/// The dispatch origin for this call must be `ManagerOrigin`.
+		///
+		/// # <weight>
+		/// - O(1).
+		/// - Two storage writes.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedOperational(100_000)]
+		fn unrequest_preimage(origin, hash: T::Hash) {
+			T::ManagerOrigin::ensure_origin(origin)?;
+			Self::do_unrequest(&hash)?;
+		}
+
+		fn on_initialize(now: T::BlockNumber) {
+			for hash in <Expiring<T>>::take(now) {
+				// defensive only: an expiring preimage stops expiring as soon as it is requested.
+				if let Some(RequestStatus::Unrequested { deposit, .. }) = Self::status_for(&hash) {
+					if let Some((owner, amount)) = deposit {
+						T::Currency::unreserve(&owner, amount);
+					}
+					Self::clear(&hash);
+				}
+			}
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// Store a preimage, reserving its deposit from `maybe_depositor` if it is not requested.
+	fn note_bytes(
+		bytes: Vec<u8>,
+		maybe_depositor: Option<&T::AccountId>,
+	) -> Result<T::Hash, &'static str> {
+		ensure!(bytes.len() <= T::MaxSize::get() as usize, "preimage too large");
+		let hash = T::Hashing::hash(&bytes);
+		ensure!(!<PreimageFor<T>>::exists(&hash), "preimage already noted");
+		let len = bytes.len() as u32;
+
+		let status = match Self::status_for(&hash) {
+			Some(RequestStatus::Requested { deposit, count, .. }) =>
+				RequestStatus::Requested { deposit, count, len: Some(len) },
+			// a preimage which is not requested has a status only once it is noted.
+			_ => {
+				let deposit = match maybe_depositor {
+					Some(who) => {
+						let amount = T::BaseDeposit::get()
+							.saturating_add(T::ByteDeposit::get().saturating_mul(len.into()));
+						T::Currency::reserve(who, amount)
+							.map_err(|_| "not enough balance to note preimage")?;
+						Some((who.clone(), amount))
+					}
+					None => None,
+				};
+				let expiry = Self::schedule_expiry(&hash);
+				RequestStatus::Unrequested { deposit, len, expiry }
+			}
+		};
+
+		<StatusFor<T>>::insert(&hash, status);
+		<PreimageFor<T>>::insert(&hash, bytes);
+		Self::deposit_event(RawEvent::Noted(hash));
+		Ok(hash)
+	}
+
+	/// Remove a preimage, or only refund its deposit if it is requested. If `maybe_owner` is
+	/// `Some`, it must be the account which noted the preimage.
+	fn do_unnote(
+		hash: &T::Hash,
+		maybe_owner: Option<&T::AccountId>,
+	) -> support::dispatch::Result {
+		let is_owner = |owner: Option<&T::AccountId>|
+			maybe_owner.map_or(true, |who| owner == Some(who));
+
+		match Self::status_for(hash).ok_or("preimage not noted")? {
+			RequestStatus::Unrequested { deposit, expiry, .. } => {
+				let owner = deposit.as_ref().map(|(owner, _)| owner);
+				ensure!(is_owner(owner), "not the owner of the preimage");
+				if let Some((owner, amount)) = deposit {
+					T::Currency::unreserve(&owner, amount);
+				}
+				<Expiring<T>>::mutate(expiry, |hashes| hashes.retain(|h| h != hash));
+				Self::clear(hash);
+			}
+			RequestStatus::Requested { deposit: Some((owner, amount)), count, len } => {
+				ensure!(is_owner(Some(&owner)), "not the owner of the preimage");
+				T::Currency::unreserve(&owner, amount);
+				let status = RequestStatus::Requested { deposit: None, count, len };
+				<StatusFor<T>>::insert(hash, status);
+			}
+			RequestStatus::Requested { deposit: None, .. } => Err("preimage is requested")?,
+		}
+		Ok(())
+	}
+
+	/// Count a request for a preimage, and keep it from being removed.
+	fn do_request(hash: &T::Hash) {
+		let status = match Self::status_for(hash) {
+			Some(RequestStatus::Requested { deposit, count, len }) =>
+				RequestStatus::Requested { deposit, count: count.saturating_add(1), len },
+			Some(RequestStatus::Unrequested { deposit, len, expiry }) => {
+				<Expiring<T>>::mutate(expiry, |hashes| hashes.retain(|h| h != hash));
+				Self::deposit_event(RawEvent::Requested(*hash));
+				RequestStatus::Requested { deposit, count: 1, len: Some(len) }
+			}
+			None => {
+				Self::deposit_event(RawEvent::Requested(*hash));
+				RequestStatus::Requested { deposit: None, count: 1, len: None }
+			}
+		};
+		<StatusFor<T>>::insert(hash, status);
+	}
+
+	/// Remove a request for a preimage. Once it is not requested anymore, a noted preimage is
+	/// scheduled for removal.
+	fn do_unrequest(hash: &T::Hash) -> support::dispatch::Result {
+		match Self::status_for(hash) {
+			Some(RequestStatus::Requested { deposit, count, len }) if count > 1 => {
+				let status = RequestStatus::Requested { deposit, count: count - 1, len };
+				<StatusFor<T>>::insert(hash, status);
+			}
+			Some(RequestStatus::Requested { deposit, len: Some(len), .. }) => {
+				let expiry = Self::schedule_expiry(hash);
+				<StatusFor<T>>::insert(hash, RequestStatus::Unrequested { deposit, len, expiry });
+			}
+			// never noted: there is no preimage or deposit to take care of.
+			Some(RequestStatus::Requested { len: None, .. }) => <StatusFor<T>>::remove(hash),
+			_ => Err("preimage not requested")?,
+		}
+		Ok(())
+	}
+
+	/// Schedule the removal of a preimage `ReapPeriod` blocks from now, and return the block at
+	/// which it is removed.
+	fn schedule_expiry(hash: &T::Hash) -> T::BlockNumber {
+		let expiry = <system::Module<T>>::block_number() + T::ReapPeriod::get();
+		<Expiring<T>>::append_or_insert(expiry, &[hash][..]);
+		expiry
+	}
+
+	/// Remove a preimage along with its status.
+	fn clear(hash: &T::Hash) {
+		<StatusFor<T>>::remove(hash);
+		<PreimageFor<T>>::remove(hash);
+		Self::deposit_event(RawEvent::Cleared(*hash));
+	}
+}
+
+impl<T: Trait> PreimageProvider<T::Hash> for Module<T> {
+	fn have_preimage(hash: &T::Hash) -> bool {
+		<PreimageFor<T>>::exists(hash)
+	}
+
+	fn get_preimage(hash: &T::Hash) -> Option<Vec<u8>> {
+		<PreimageFor<T>>::get(hash)
+	}
+
+	fn preimage_requested(hash: &T::Hash) -> bool {
+		match Self::status_for(hash) {
+			Some(RequestStatus::Requested { .. }) => true,
+			_ => false,
+		}
+	}
+
+	fn request_preimage(hash: &T::Hash) {
+		Self::do_request(hash)
+	}
+
+	fn unrequest_preimage(hash: &T::Hash) {
+		let _ = Self::do_unrequest(hash);
+	}
+}
+
+impl<T: Trait> PreimageRecipient<T::Hash> for Module<T> {
+	fn note_preimage(bytes: Vec<u8>) -> Result<(), ()> {
+		if <PreimageFor<T>>::exists(T::Hashing::hash(&bytes)) {
+			return Ok(())
+		}
+		Self::note_bytes(bytes, None).map(|_| ()).map_err(|_| ())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use support::{assert_ok, assert_noop, impl_outer_origin, parameter_types};
+	use support::traits::OnInitialize;
+	use primitives::H256;
+	use system::EnsureSignedBy;
+	use sr_primitives::{
+		Perbill, testing::Header, traits::{BlakeTwo256, IdentityLookup},
+	};
+
+	impl_outer_origin! {
+		pub enum Origin for Test {}
+	}
+
+	// For testing the module, we construct most of a mock runtime. This means
+	// first constructing a configuration type (`Test`) which `impl`s each of the
+	// configuration traits of modules we want to use.
+	#[derive(Clone, Eq, PartialEq)]
+	pub struct Test;
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const MaximumBlockWeight: u32 = 1024;
+		pub const MaximumBlockLength: u32 = 2 * 1024;
+		pub const AvailableBlockRatio: Perbill = Perbill::one();
+	}
+	impl system::Trait for Test {
+		type Origin = Origin;
+		type Index = u64;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Call = ();
+		type Hashing = BlakeTwo256;
+		type AccountId = u64;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = Header;
+		type Event = ();
+		type BlockHashCount = BlockHashCount;
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type MaximumBlockLength = MaximumBlockLength;
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type Version = ();
+	}
+	parameter_types! {
+		pub const ExistentialDeposit: u64 = 0;
+		pub const TransferFee: u64 = 0;
+		pub const CreationFee: u64 = 0;
+	}
+	impl balances::Trait for Test {
+		type Balance = u64;
+		type OnFreeBalanceZero = ();
+		type OnNewAccount = ();
+		type Event = ();
+		type TransferPayment = ();
+		type DustRemoval = ();
+		type ExistentialDeposit = ExistentialDeposit;
+		type TransferFee = TransferFee;
+		type CreationFee = CreationFee;
+	}
+	parameter_types! {
+		pub const MaxSize: u32 = 8;
+		pub const BaseDeposit: u64 = 2;
+		pub const ByteDeposit: u64 = 1;
+		pub const ReapPeriod: u64 = 10;
+		pub const One: u64 = 1;
+	}
+	impl Trait for Test {
+		type Event = ();
+		type Currency = Balances;
+		type ManagerOrigin = EnsureSignedBy<One, u64>;
+		type MaxSize = MaxSize;
+		type BaseDeposit = BaseDeposit;
+		type ByteDeposit = ByteDeposit;
+		type ReapPeriod = ReapPeriod;
+	}
+	type System = system::Module<Test>;
+	type Balances = balances::Module<Test>;
+	type Preimage = Module<Test>;
+
+	fn new_test_ext() -> runtime_io::TestExternalities {
+		let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+		balances::GenesisConfig::<Test> {
+			balances: vec![
+				(1, 100),
+				(2, 100),
+				(3, 1),
+			],
+		}.assimilate_storage(&mut t).unwrap();
+		t.into()
+	}
+
+	fn run_to_block(n: u64) {
+		while System::block_number() < n {
+			System::set_block_number(System::block_number() + 1);
+			Preimage::on_initialize(System::block_number());
+		}
+	}
+
+	fn hash_of(bytes: &[u8]) -> H256 {
+		BlakeTwo256::hash(bytes)
+	}
+
+	#[test]
+	fn note_preimage_should_reserve_deposit() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(Preimage::note_preimage(Origin::signed(2), vec![1, 2, 3]));
+			let hash = hash_of(&[1, 2, 3]);
+			assert_eq!(Balances::reserved_balance(&2), 5);
+			assert!(Preimage::have_preimage(&hash));
+			assert_eq!(Preimage::get_preimage(&hash), Some(vec![1, 2, 3]));
+			assert!(!Preimage::preimage_requested(&hash));
+
+			assert_noop!(
+				Preimage::note_preimage(Origin::signed(1), vec![1, 2, 3]),
+				"preimage already noted"
+			);
+			assert_noop!(
+				Preimage::note_preimage(Origin::signed(2), vec![0; 9]),
+				"preimage too large"
+			);
+			assert_noop!(
+				Preimage::note_preimage(Origin::signed(3), vec![1]),
+				"not enough balance to note preimage"
+			);
+		});
+	}
+
+	#[test]
+	fn manager_notes_without_deposit() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(Preimage::note_preimage(Origin::signed(1), vec![1]));
+			assert_eq!(Balances::reserved_balance(&1), 0);
+			assert!(Preimage::have_preimage(&hash_of(&[1])));
+
+			// only the manager may unnote a preimage noted without a deposit.
+			assert_noop!(
+				Preimage::unnote_preimage(Origin::signed(2), hash_of(&[1])),
+				"not the owner of the preimage"
+			);
+			assert_ok!(Preimage::unnote_preimage(Origin::signed(1), hash_of(&[1])));
+			assert!(!Preimage::have_preimage(&hash_of(&[1])));
+		});
+	}
+
+	#[test]
+	fn unnote_preimage_should_refund_deposit() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(Preimage::note_preimage(Origin::signed(2), vec![1]));
+			let hash = hash_of(&[1]);
+			assert_noop!(
+				Preimage::unnote_preimage(Origin::signed(3), hash),
+				"not the owner of the preimage"
+			);
+			assert_ok!(Preimage::unnote_preimage(Origin::signed(2), hash));
+			assert_eq!(Balances::reserved_balance(&2), 0);
+			assert!(!Preimage::have_preimage(&hash));
+			assert_eq!(Preimage::status_for(&hash), None);
+			assert!(Preimage::expiring(10).is_empty());
+			assert_noop!(Preimage::unnote_preimage(Origin::signed(2), hash), "preimage not noted");
+		});
+	}
+
+	#[test]
+	fn unrequested_preimage_should_be_reaped() {
+		new_test_ext().execute_with(|| {
+			run_to_block(1);
+			assert_ok!(Preimage::note_preimage(Origin::signed(2), vec![1]));
+			assert_eq!(Preimage::expiring(11), vec![hash_of(&[1])]);
+
+			run_to_block(10);
+			assert!(Preimage::have_preimage(&hash_of(&[1])));
+			run_to_block(11);
+			assert!(!Preimage::have_preimage(&hash_of(&[1])));
+			assert_eq!(Preimage::status_for(&hash_of(&[1])), None);
+			assert_eq!(Balances::reserved_balance(&2), 0);
+		});
+	}
+
+	#[test]
+	fn requested_preimage_is_noted_for_free_and_kept() {
+		new_test_ext().execute_with(|| {
+			let hash = hash_of(&[1]);
+			assert_noop!(Preimage::request_preimage(Origin::signed(2), hash), "Invalid origin");
+			assert_ok!(Preimage::request_preimage(Origin::signed(1), hash));
+			assert!(Preimage::preimage_requested(&hash));
+			assert!(!Preimage::have_preimage(&hash));
+
+			assert_ok!(Preimage::note_preimage(Origin::signed(2), vec![1]));
+			assert_eq!(Balances::reserved_balance(&2), 0);
+			assert_noop!(
+				Preimage::unnote_preimage(Origin::signed(2), hash),
+				"preimage is requested"
+			);
+
+			run_to_block(20);
+			assert_eq!(Preimage::get_preimage(&hash), Some(vec![1]));
+
+			// once unrequested, the preimage is reaped after the usual period.
+			assert_ok!(Preimage::unrequest_preimage(Origin::signed(1), hash));
+			assert!(!Preimage::preimage_requested(&hash));
+			run_to_block(29);
+			assert!(Preimage::have_preimage(&hash));
+			run_to_block(30);
+			assert!(!Preimage::have_preimage(&hash));
+		});
+	}
+
+	#[test]
+	fn recipient_notes_requested_preimage_without_deposit() {
+		new_test_ext().execute_with(|| {
+			let hash = hash_of(&[1]);
+			<Preimage as PreimageProvider<_>>::request_preimage(&hash);
+			assert_ok!(<Preimage as PreimageRecipient<_>>::note_preimage(vec![1]));
+			assert_eq!(Preimage::get_preimage(&hash), Some(vec![1]));
+			assert_eq!(
+				Preimage::status_for(&hash),
+				Some(RequestStatus::Requested { deposit: None, count: 1, len: Some(1) }),
+			);
+
+			// noting it again is a no-op, while a preimage too large cannot be noted.
+			assert_ok!(<Preimage as PreimageRecipient<_>>::note_preimage(vec![1]));
+			assert_eq!(<Preimage as PreimageRecipient<_>>::note_preimage(vec![0; 9]), Err(()));
+		});
+	}
+
+	#[test]
+	fn requests_are_counted() {
+		new_test_ext().execute_with(|| {
+			let hash = hash_of(&[1]);
+			<Preimage as PreimageProvider<_>>::request_preimage(&hash);
+			<Preimage as PreimageProvider<_>>::request_preimage(&hash);
+			<Preimage as PreimageProvider<_>>::unrequest_preimage(&hash);
+			assert!(Preimage::preimage_requested(&hash));
+			<Preimage as PreimageProvider<_>>::unrequest_preimage(&hash);
+			assert!(!Preimage::preimage_requested(&hash));
+			// never noted: nothing is left behind.
+			assert_eq!(Preimage::status_for(&hash), None);
+			assert_noop!(
+				Preimage::unrequest_preimage(Origin::signed(1), hash),
+				"preimage not requested"
+			);
+		});
+	}
+
+	#[test]
+	fn requesting_noted_preimage_keeps_it_and_its_deposit() {
+		new_test_ext().execute_with(|| {
+			let hash = hash_of(&[1]);
+			assert_ok!(Preimage::note_preimage(Origin::signed(2), vec![1]));
+			assert_ok!(Preimage::request_preimage(Origin::signed(1), hash));
+			assert!(Preimage::expiring(10).is_empty());
+
+			run_to_block(10);
+			assert!(Preimage::have_preimage(&hash));
+			assert_eq!(Balances::reserved_balance(&2), 3);
+
+			// the owner may get the deposit back, while the preimage stays requested.
+			assert_ok!(Preimage::unnote_preimage(Origin::signed(2), hash));
+			assert_eq!(Balances::reserved_balance(&2), 0);
+			assert!(Preimage::have_preimage(&hash));
+		});
+	}
+
+	#[test]
+	fn reaping_unrequested_preimage_should_refund_deposit() {
+		new_test_ext().execute_with(|| {
+			let hash = hash_of(&[1]);
+			assert_ok!(Preimage::note_preimage(Origin::signed(2), vec![1]));
+			assert_ok!(Preimage::request_preimage(Origin::signed(1), hash));
+			run_to_block(5);
+			assert_ok!(Preimage::unrequest_preimage(Origin::signed(1), hash));
+			assert_eq!(Balances::reserved_balance(&2), 3);
+
+			run_to_block(15);
+			assert!(!Preimage::have_preimage(&hash));
+			assert_eq!(Balances::reserved_balance(&2), 0);
+			assert_eq!(Balances::free_balance(&2), 100);
+		});
+	}
+}
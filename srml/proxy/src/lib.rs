@@ -0,0 +1,538 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Proxy Module
+//! A module allowing accounts to give permission to other accounts to dispatch types of calls from
+//! their signed origin.
+//!
+//! - [`proxy::Trait`](./trait.Trait.html)
+//! - [`Call`](./enum.Call.html)
+//!
+//! ## Overview
+//!
+//! An account may register any number of proxies, up to `MaxProxies`, each with a proxy type.
+//! The proxy type is defined by the runtime and decides which calls the proxy may dispatch on
+//! behalf of the account, through its `InstanceFilter` implementation. A deposit is reserved
+//! from the account for as long as it has proxies.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! * `proxy` - Dispatch a call on behalf of an account that registered the sender as a proxy.
+//! * `add_proxy` - Register an account as a proxy of the sender.
+//! * `remove_proxy` - Unregister a proxy of the sender.
+//! * `remove_proxies` - Unregister all the proxies of the sender and return the deposit.
+//!
+//! [`Call`]: ./enum.Call.html
+//! [`Trait`]: ./trait.Trait.html
+
+// Ensure we're `no_std` when compiling for Wasm.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use rstd::prelude::*;
+use support::{decl_module, decl_event, decl_storage, ensure, Parameter, IsSubType};
+use support::traits::{Currency, ReservableCurrency, Get, InstanceFilter};
+use system::ensure_signed;
+use sr_primitives::{
+	DispatchError,
+	traits::{Dispatchable, Member, Zero},
+	weights::{
+		SimpleDispatchInfo, GetDispatchInfo, WeighData, ClassifyDispatch, DispatchClass, Weight,
+	},
+};
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+
+/// Weight of a dispatchable that dispatches a call: a fixed base weight, plus the weight of the
+/// call.
+struct WeightWithCall(Weight);
+
+// `proxy`: the call is dispatched whenever the sender is a proxy of `real`.
+impl<A, P, C: GetDispatchInfo> WeighData<(&A, &Option<P>, &Box<C>)> for WeightWithCall {
+	fn weigh_data(&self, (_, _, call): (&A, &Option<P>, &Box<C>)) -> Weight {
+		self.0.saturating_add(call.get_dispatch_info().weight)
+	}
+}
+
+impl<T> ClassifyDispatch<T> for WeightWithCall {
+	fn classify_dispatch(&self, _: T) -> DispatchClass {
+		DispatchClass::Normal
+	}
+}
+
+/// Configuration trait.
+pub trait Trait: system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+	/// The overarching call type.
+	type Call: Parameter + Dispatchable<Origin=Self::Origin> + GetDispatchInfo + IsSubType<Module<Self>, Self>;
+
+	/// The currency mechanism.
+	type Currency: ReservableCurrency<Self::AccountId>;
+
+	/// A kind of proxy; specified with the proxy and passed in to the `InstanceFilter` to decide
+	/// which calls the proxy may dispatch.
+	type ProxyType: Parameter + Member + Ord + PartialOrd + InstanceFilter<<Self as Trait>::Call> + Default;
+
+	/// The base amount of currency needed to reserve for creating a proxy list.
+	///
+	/// This is held for an additional storage item whose value size is
+	/// `sizeof(Balance)` bytes and whose key size is `sizeof(AccountId)` bytes.
+	type ProxyDepositBase: Get<BalanceOf<Self>>;
+
+	/// The amount of currency needed per proxy added.
+	///
+	/// This is held for adding 32 bytes plus an instance of `ProxyType` more into a pre-existing
+	/// storage value.
+	type ProxyDepositFactor: Get<BalanceOf<Self>>;
+
+	/// The maximum amount of proxies allowed for a single account.
+	type MaxProxies: Get<u16>;
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Proxy {
+		/// The set of account proxies. Maps the account which has delegated to the accounts
+		/// which are being delegated to, together with the amount held on deposit.
+		pub Proxies get(fn proxies): map T::AccountId => (Vec<(T::AccountId, T::ProxyType)>, BalanceOf<T>);
+	}
+}
+
+decl_event!(
+	/// Events type.
+	pub enum Event<T> where
+		AccountId = <T as system::Trait>::AccountId,
+		ProxyType = <T as Trait>::ProxyType
+	{
+		/// A proxy was executed. First param is the account on whose behalf the call was
+		/// dispatched, second is the proxy, third is the result of the call.
+		ProxyExecuted(AccountId, AccountId, Result<(), DispatchError>),
+		/// A proxy was added. First param is the delegating account, second is the proxy.
+		ProxyAdded(AccountId, AccountId, ProxyType),
+		/// A proxy was removed. First param is the delegating account, second is the proxy.
+		ProxyRemoved(AccountId, AccountId, ProxyType),
+		/// All the proxies of an account were removed.
+		ProxiesRemoved(AccountId),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		/// Deposit one of this module's events by using the default implementation.
+		fn deposit_event() = default;
+
+		/// The base deposit reserved for having any proxies.
+		const ProxyDepositBase: BalanceOf<T> = T::ProxyDepositBase::get();
+
+		/// The additional deposit reserved per proxy.
+		const ProxyDepositFactor: BalanceOf<T> = T::ProxyDepositFactor::get();
+
+		/// The maximum amount of proxies allowed for a single account.
+		const MaxProxies: u16 = T::MaxProxies::get();
+
+		/// Dispatch the given `call` from an account that the sender is authorised for through
+		/// `add_proxy`.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// Parameters:
+		/// - `real`: The account that the proxy will make a call on behalf of.
+		/// - `force_proxy_type`: Specify the exact proxy type to be used and checked for this call.
+		/// - `call`: The call to be made by the `real` account.
+		///
+		/// The result of the call is deposited in a `ProxyExecuted` event.
+		///
+		/// # <weight>
+		/// - `O(P)` where `P` is the number of proxies of `real`, capped by `MaxProxies`.
+		/// - One storage read.
+		/// - One event.
+		/// - The weight of the `call`.
+		/// # </weight>
+		#[weight = WeightWithCall(1_000_000)]
+		fn proxy(origin,
+			real: T::AccountId,
+			force_proxy_type: Option<T::ProxyType>,
+			call: Box<<T as Trait>::Call>,
+		) {
+			let who = ensure_signed(origin)?;
+			let (_, proxy_type) = <Proxies<T>>::get(&real).0.into_iter()
+				.find(|(proxy, proxy_type)| {
+					proxy == &who && force_proxy_type.as_ref().map_or(true, |t| t == proxy_type)
+				})
+				.ok_or("not a proxy")?;
+			ensure!(proxy_type.filter(&call), "unproxyable call");
+
+			// A proxy may only change the proxies of `real` within its own permissions, otherwise
+			// it could grant itself a more powerful proxy type.
+			match call.is_sub_type() {
+				Some(Call::add_proxy(_, requested)) | Some(Call::remove_proxy(_, requested)) =>
+					ensure!(proxy_type.is_superset(requested), "no permission"),
+				_ => {},
+			}
+
+			let result = call.dispatch(system::RawOrigin::Signed(real.clone()).into());
+			Self::deposit_event(RawEvent::ProxyExecuted(real, who, result.map_err(Into::into)));
+		}
+
+		/// Register a proxy account for the sender that is able to make calls on its behalf.
+		///
+		/// The deposit for the proxies of the sender is raised by `ProxyDepositFactor`, plus
+		/// `ProxyDepositBase` if this is its first proxy.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// Parameters:
+		/// - `proxy`: The account that the `caller` would like to make a proxy.
+		/// - `proxy_type`: The permissions allowed for this proxy account.
+		///
+		/// # <weight>
+		/// - `O(P)` where `P` is the number of proxies of the sender, capped by `MaxProxies`.
+		/// - One balance-reserve operation.
+		/// - One storage read/write.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		fn add_proxy(origin, proxy: T::AccountId, proxy_type: T::ProxyType) {
+			let who = ensure_signed(origin)?;
+			let (mut proxies, old_deposit) = <Proxies<T>>::get(&who);
+			ensure!(proxies.len() < T::MaxProxies::get() as usize, "too many proxies");
+			let typed_proxy = (proxy, proxy_type);
+			let index = proxies.binary_search(&typed_proxy).err().ok_or("already a proxy")?;
+			proxies.insert(index, typed_proxy.clone());
+
+			let new_deposit = Self::deposit(proxies.len());
+			if new_deposit > old_deposit {
+				T::Currency::reserve(&who, new_deposit - old_deposit)?;
+			} else if new_deposit < old_deposit {
+				let _ = T::Currency::unreserve(&who, old_deposit - new_deposit);
+			}
+			<Proxies<T>>::insert(&who, (proxies, new_deposit));
+
+			let (proxy, proxy_type) = typed_proxy;
+			Self::deposit_event(RawEvent::ProxyAdded(who, proxy, proxy_type));
+		}
+
+		/// Unregister a proxy account for the sender.
+		///
+		/// The deposit is lowered accordingly, and returned entirely once the last proxy is
+		/// removed.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// Parameters:
+		/// - `proxy`: The account that the `caller` would like to remove as a proxy.
+		/// - `proxy_type`: The permissions currently enabled for the removed proxy account.
+		///
+		/// # <weight>
+		/// - `O(P)` where `P` is the number of proxies of the sender, capped by `MaxProxies`.
+		/// - One balance-unreserve operation.
+		/// - One storage read/write.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		fn remove_proxy(origin, proxy: T::AccountId, proxy_type: T::ProxyType) {
+			let who = ensure_signed(origin)?;
+			let (mut proxies, old_deposit) = <Proxies<T>>::get(&who);
+			let typed_proxy = (proxy, proxy_type);
+			let index = proxies.binary_search(&typed_proxy).ok().ok_or("not found")?;
+			proxies.remove(index);
+
+			let new_deposit = if proxies.is_empty() { Zero::zero() } else { Self::deposit(proxies.len()) };
+			if new_deposit < old_deposit {
+				let _ = T::Currency::unreserve(&who, old_deposit - new_deposit);
+			}
+			if proxies.is_empty() {
+				<Proxies<T>>::remove(&who);
+			} else {
+				<Proxies<T>>::insert(&who, (proxies, new_deposit));
+			}
+
+			let (proxy, proxy_type) = typed_proxy;
+			Self::deposit_event(RawEvent::ProxyRemoved(who, proxy, proxy_type));
+		}
+
+		/// Unregister all proxy accounts for the sender and return its deposit.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// # <weight>
+		/// - O(1).
+		/// - One balance-unreserve operation.
+		/// - One storage removal.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		fn remove_proxies(origin) {
+			let who = ensure_signed(origin)?;
+			let (_, deposit) = <Proxies<T>>::take(&who);
+			let _ = T::Currency::unreserve(&who, deposit);
+
+			Self::deposit_event(RawEvent::ProxiesRemoved(who));
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// The deposit held for an account with `proxies` proxies.
+	fn deposit(proxies: usize) -> BalanceOf<T> {
+		T::ProxyDepositBase::get() + T::ProxyDepositFactor::get() * (proxies as u32).into()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use codec::{Encode, Decode};
+	use support::{assert_ok, assert_noop, impl_outer_origin, parameter_types, impl_outer_dispatch};
+	use primitives::H256;
+	use sr_primitives::{Perbill, traits::{BlakeTwo256, IdentityLookup}, testing::Header};
+
+	impl_outer_origin! {
+		pub enum Origin for Test {}
+	}
+
+	impl_outer_dispatch! {
+		pub enum Call for Test where origin: Origin {
+			balances::Balances,
+			utility::Utility,
+			proxy::Proxy,
+		}
+	}
+
+	// For testing the module, we construct most of a mock runtime. This means
+	// first constructing a configuration type (`Test`) which `impl`s each of the
+	// configuration traits of modules we want to use.
+	#[derive(Clone, Eq, PartialEq)]
+	pub struct Test;
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const MaximumBlockWeight: u32 = 1024;
+		pub const MaximumBlockLength: u32 = 2 * 1024;
+		pub const AvailableBlockRatio: Perbill = Perbill::one();
+	}
+	impl system::Trait for Test {
+		type Origin = Origin;
+		type Index = u64;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Call = Call;
+		type Hashing = BlakeTwo256;
+		type AccountId = u64;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = Header;
+		type Event = ();
+		type BlockHashCount = BlockHashCount;
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type MaximumBlockLength = MaximumBlockLength;
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type Version = ();
+	}
+	parameter_types! {
+		pub const ExistentialDeposit: u64 = 0;
+		pub const TransferFee: u64 = 0;
+		pub const CreationFee: u64 = 0;
+	}
+	impl balances::Trait for Test {
+		type Balance = u64;
+		type OnFreeBalanceZero = ();
+		type OnNewAccount = ();
+		type Event = ();
+		type TransferPayment = ();
+		type DustRemoval = ();
+		type ExistentialDeposit = ExistentialDeposit;
+		type TransferFee = TransferFee;
+		type CreationFee = CreationFee;
+	}
+	parameter_types! {
+		pub const MultisigDepositBase: u64 = 1;
+		pub const MultisigDepositFactor: u64 = 1;
+		pub const MaxSignatories: u16 = 4;
+	}
+	impl utility::Trait for Test {
+		type Event = ();
+		type Call = Call;
+		type Currency = Balances;
+		type MultisigDepositBase = MultisigDepositBase;
+		type MultisigDepositFactor = MultisigDepositFactor;
+		type MaxSignatories = MaxSignatories;
+	}
+	parameter_types! {
+		pub const ProxyDepositBase: u64 = 1;
+		pub const ProxyDepositFactor: u64 = 1;
+		pub const MaxProxies: u16 = 3;
+	}
+	#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Encode, Decode, Debug)]
+	pub enum ProxyType {
+		Any,
+		NonTransfer,
+		JustTransfer,
+	}
+	impl Default for ProxyType {
+		fn default() -> Self { ProxyType::Any }
+	}
+	impl InstanceFilter<Call> for ProxyType {
+		fn filter(&self, c: &Call) -> bool {
+			match self {
+				ProxyType::Any => true,
+				ProxyType::NonTransfer => match c {
+					Call::Balances(balances::Call::transfer(..)) => false,
+					_ => true,
+				},
+				ProxyType::JustTransfer => match c {
+					Call::Balances(balances::Call::transfer(..)) => true,
+					_ => false,
+				},
+			}
+		}
+		fn is_superset(&self, o: &Self) -> bool {
+			self == &ProxyType::Any || self == o
+		}
+	}
+	impl Trait for Test {
+		type Event = ();
+		type Call = Call;
+		type Currency = Balances;
+		type ProxyType = ProxyType;
+		type ProxyDepositBase = ProxyDepositBase;
+		type ProxyDepositFactor = ProxyDepositFactor;
+		type MaxProxies = MaxProxies;
+	}
+	type Balances = balances::Module<Test>;
+	type Utility = utility::Module<Test>;
+	type Proxy = Module<Test>;
+
+	fn new_test_ext() -> runtime_io::TestExternalities {
+		let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+		balances::GenesisConfig::<Test> {
+			balances: vec![(1, 10), (2, 10), (3, 10), (4, 10), (5, 2)],
+		}.assimilate_storage(&mut t).unwrap();
+		t.into()
+	}
+
+	#[test]
+	fn add_remove_proxies_works() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(Proxy::add_proxy(Origin::signed(1), 2, ProxyType::Any));
+			assert_noop!(Proxy::add_proxy(Origin::signed(1), 2, ProxyType::Any), "already a proxy");
+			assert_eq!(Balances::reserved_balance(1), 2);
+			assert_ok!(Proxy::add_proxy(Origin::signed(1), 2, ProxyType::JustTransfer));
+			assert_eq!(Balances::reserved_balance(1), 3);
+			assert_ok!(Proxy::add_proxy(Origin::signed(1), 3, ProxyType::Any));
+			assert_eq!(Balances::reserved_balance(1), 4);
+			assert_noop!(Proxy::add_proxy(Origin::signed(1), 4, ProxyType::Any), "too many proxies");
+
+			assert_noop!(Proxy::remove_proxy(Origin::signed(1), 3, ProxyType::JustTransfer), "not found");
+			assert_ok!(Proxy::remove_proxy(Origin::signed(1), 3, ProxyType::Any));
+			assert_eq!(Balances::reserved_balance(1), 3);
+			assert_ok!(Proxy::remove_proxy(Origin::signed(1), 2, ProxyType::Any));
+			assert_eq!(Balances::reserved_balance(1), 2);
+			assert_ok!(Proxy::remove_proxy(Origin::signed(1), 2, ProxyType::JustTransfer));
+			assert_eq!(Balances::reserved_balance(1), 0);
+			assert_eq!(Proxy::proxies(1), (vec![], 0));
+		});
+	}
+
+	#[test]
+	fn add_proxy_requires_deposit() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(Proxy::add_proxy(Origin::signed(5), 1, ProxyType::Any));
+			assert_noop!(Proxy::add_proxy(Origin::signed(5), 2, ProxyType::Any), "not enough free funds");
+		});
+	}
+
+	#[test]
+	fn remove_proxies_returns_deposit() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(Proxy::add_proxy(Origin::signed(1), 2, ProxyType::Any));
+			assert_ok!(Proxy::add_proxy(Origin::signed(1), 3, ProxyType::JustTransfer));
+			assert_eq!(Balances::reserved_balance(1), 3);
+			assert_ok!(Proxy::remove_proxies(Origin::signed(1)));
+			assert_eq!(Balances::reserved_balance(1), 0);
+			assert_eq!(Balances::free_balance(1), 10);
+			assert_noop!(
+				Proxy::proxy(Origin::signed(2), 1, None, Box::new(Call::Balances(balances::Call::transfer(6, 1)))),
+				"not a proxy",
+			);
+		});
+	}
+
+	#[test]
+	fn filtering_works() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(Proxy::add_proxy(Origin::signed(1), 2, ProxyType::Any));
+			assert_ok!(Proxy::add_proxy(Origin::signed(1), 3, ProxyType::JustTransfer));
+			assert_ok!(Proxy::add_proxy(Origin::signed(1), 4, ProxyType::NonTransfer));
+
+			let call = Box::new(Call::Balances(balances::Call::transfer(6, 1)));
+			assert_ok!(Proxy::proxy(Origin::signed(2), 1, None, call.clone()));
+			assert_ok!(Proxy::proxy(Origin::signed(3), 1, None, call.clone()));
+			assert_noop!(Proxy::proxy(Origin::signed(4), 1, None, call.clone()), "unproxyable call");
+			assert_eq!(Balances::free_balance(6), 2);
+
+			let call = Box::new(Call::Utility(utility::Call::approve_as_multi(2, vec![2], None, [0; 32])));
+			assert_ok!(Proxy::proxy(Origin::signed(2), 1, None, call.clone()));
+			assert_noop!(Proxy::proxy(Origin::signed(3), 1, None, call.clone()), "unproxyable call");
+			assert_ok!(Proxy::proxy(Origin::signed(4), 1, None, call));
+		});
+	}
+
+	#[test]
+	fn forced_proxy_type_is_checked() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(Proxy::add_proxy(Origin::signed(1), 2, ProxyType::JustTransfer));
+			let call = Box::new(Call::Balances(balances::Call::transfer(6, 1)));
+			assert_noop!(
+				Proxy::proxy(Origin::signed(2), 1, Some(ProxyType::Any), call.clone()),
+				"not a proxy",
+			);
+			assert_ok!(Proxy::proxy(Origin::signed(2), 1, Some(ProxyType::JustTransfer), call));
+			assert_eq!(Balances::free_balance(6), 1);
+		});
+	}
+
+	#[test]
+	fn proxies_cannot_escalate_their_permissions() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(Proxy::add_proxy(Origin::signed(1), 2, ProxyType::Any));
+			assert_ok!(Proxy::add_proxy(Origin::signed(1), 3, ProxyType::NonTransfer));
+
+			let call = Box::new(Call::Proxy(super::Call::add_proxy(3, ProxyType::Any)));
+			assert_noop!(Proxy::proxy(Origin::signed(3), 1, None, call.clone()), "no permission");
+			let call = Box::new(Call::Proxy(super::Call::remove_proxy(2, ProxyType::Any)));
+			assert_noop!(Proxy::proxy(Origin::signed(3), 1, None, call.clone()), "no permission");
+
+			assert_ok!(Proxy::proxy(Origin::signed(2), 1, None, call));
+			assert_eq!(Proxy::proxies(1).0, vec![(3, ProxyType::NonTransfer)]);
+		});
+	}
+
+	#[test]
+	fn proxy_weighs_the_dispatched_call() {
+		let transfer = Box::new(Call::Balances(balances::Call::transfer(6, 1)));
+		let transfer_weight = transfer.get_dispatch_info().weight;
+		let call = Call::Proxy(super::Call::proxy(1, None, transfer));
+		assert_eq!(call.get_dispatch_info().weight, 1_000_000 + transfer_weight);
+
+		let remove = Box::new(Call::Proxy(super::Call::remove_proxies()));
+		let remove_weight = remove.get_dispatch_info().weight;
+		let call = Call::Proxy(super::Call::proxy(1, Some(ProxyType::Any), remove));
+		assert_eq!(call.get_dispatch_info().weight, 1_000_000 + remove_weight);
+		assert!(remove_weight != transfer_weight);
+	}
+}
@@ -0,0 +1,667 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Recovery Module
+//! A module for recovering access to an account whose key is lost, through social recovery.
+//!
+//! - [`recovery::Trait`](./trait.Trait.html)
+//! - [`Call`](./enum.Call.html)
+//!
+//! ## Overview
+//!
+//! An account makes itself recoverable by choosing a set of friends, a threshold of friends and
+//! a delay period. A deposit is reserved for as long as the account is recoverable.
+//!
+//! When the key of the account is lost, a rescuer, usually a new account of the same user,
+//! initiates a recovery of the lost account, reserving `RecoveryDeposit`. Friends then vouch for
+//! the recovery attempt. Once enough friends have vouched and the delay period has passed since
+//! the attempt was initiated, the rescuer may claim the lost account, after which it can
+//! dispatch calls with the signed origin of the lost account through `as_recovered`.
+//!
+//! If the key of the account is not actually lost, the account can close any recovery attempt
+//! made on it during the delay period, and take the deposit of the rescuer.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! * `create_recovery` - Make the sender recoverable through a set of friends.
+//! * `initiate_recovery` - Start a recovery attempt of a recoverable account.
+//! * `vouch_recovery` - Vouch for a recovery attempt, as a friend of the lost account.
+//! * `claim_recovery` - Claim a lost account, once the recovery attempt is vouched for and
+//!   the delay period has passed.
+//! * `as_recovered` - Dispatch a call with the signed origin of a recovered account.
+//! * `close_recovery` - Close a recovery attempt made on the sender, taking the deposit of the
+//!   rescuer.
+//! * `remove_recovery` - Make the sender unrecoverable, once no recovery attempt is active.
+//! * `cancel_recovered` - Stop being able to dispatch calls as a recovered account.
+//! * `set_recovered` - Allow an account to dispatch calls as another account. Called by root.
+//!
+//! [`Call`]: ./enum.Call.html
+//! [`Trait`]: ./trait.Trait.html
+
+// Ensure we're `no_std` when compiling for Wasm.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use rstd::prelude::*;
+use codec::{Encode, Decode};
+use sr_primitives::{
+	RuntimeDebug, DispatchError,
+	traits::{Dispatchable, Saturating},
+	weights::{
+		SimpleDispatchInfo, GetDispatchInfo, WeighData, ClassifyDispatch, DispatchClass, Weight,
+	},
+};
+use support::{
+	decl_module, decl_event, decl_storage, ensure, Parameter, metadata::TypeInfo,
+	traits::{Currency, ReservableCurrency, Get},
+};
+use system::{ensure_signed, ensure_root};
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+
+/// Weight of a dispatchable that dispatches a call: a fixed base weight, plus the weight of the
+/// call.
+struct WeightWithCall(Weight);
+
+// `as_recovered`: the call is always dispatched.
+impl<A, C: GetDispatchInfo> WeighData<(&A, &Box<C>)> for WeightWithCall {
+	fn weigh_data(&self, (_, call): (&A, &Box<C>)) -> Weight {
+		self.0.saturating_add(call.get_dispatch_info().weight)
+	}
+}
+
+impl<T> ClassifyDispatch<T> for WeightWithCall {
+	fn classify_dispatch(&self, _: T) -> DispatchClass {
+		DispatchClass::Normal
+	}
+}
+
+/// Configuration trait.
+pub trait Trait: system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+	/// The overarching call type.
+	type Call: Parameter + Dispatchable<Origin=Self::Origin> + GetDispatchInfo;
+
+	/// The currency mechanism.
+	type Currency: ReservableCurrency<Self::AccountId>;
+
+	/// The base amount of currency reserved for making an account recoverable.
+	type ConfigDepositBase: Get<BalanceOf<Self>>;
+
+	/// The amount of currency reserved for each friend of a recoverable account.
+	type FriendDepositFactor: Get<BalanceOf<Self>>;
+
+	/// The maximum number of friends of a recoverable account.
+	type MaxFriends: Get<u16>;
+
+	/// The amount of currency reserved for initiating a recovery attempt.
+	type RecoveryDeposit: Get<BalanceOf<Self>>;
+}
+
+/// The recovery configuration of an account.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, Default, RuntimeDebug, TypeInfo)]
+pub struct RecoveryConfig<BlockNumber, Balance, AccountId> {
+	/// The minimum number of blocks between the start of a recovery attempt and its claim.
+	pub delay_period: BlockNumber,
+	/// The deposit reserved for this configuration.
+	pub deposit: Balance,
+	/// The friends which may vouch for a recovery attempt, sorted.
+	pub friends: Vec<AccountId>,
+	/// The number of friends which must vouch for a recovery attempt before it can be claimed.
+	pub threshold: u16,
+}
+
+/// An ongoing recovery attempt.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, Default, RuntimeDebug, TypeInfo)]
+pub struct ActiveRecovery<BlockNumber, Balance, AccountId> {
+	/// The block at which the attempt was initiated.
+	pub created: BlockNumber,
+	/// The deposit reserved from the rescuer.
+	pub deposit: Balance,
+	/// The friends which have vouched for the attempt, sorted.
+	pub friends: Vec<AccountId>,
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Recovery {
+		/// The recovery configuration of the recoverable accounts.
+		pub Recoverable get(fn recovery_config):
+			map T::AccountId => Option<RecoveryConfig<T::BlockNumber, BalanceOf<T>, T::AccountId>>;
+
+		/// The ongoing recovery attempts, by lost account and rescuer.
+		pub ActiveRecoveries get(fn active_recovery):
+			double_map T::AccountId, blake2_256(T::AccountId)
+			=> Option<ActiveRecovery<T::BlockNumber, BalanceOf<T>, T::AccountId>>;
+
+		/// The number of ongoing recovery attempts of a lost account.
+		pub ActiveRecoveryCount get(fn active_recovery_count): map T::AccountId => u32;
+
+		/// The account which a rescuer may dispatch calls for, once it has claimed it.
+		pub Proxy get(fn proxy): map T::AccountId => Option<T::AccountId>;
+	}
+}
+
+decl_event!(
+	/// Events type.
+	pub enum Event<T> where
+		AccountId = <T as system::Trait>::AccountId,
+	{
+		/// A recovered account dispatched a call through its rescuer, with the result of the call.
+		RecoveredCallExecuted(AccountId, AccountId, Result<(), DispatchError>),
+		/// An account has been made recoverable.
+		RecoveryCreated(AccountId),
+		/// A recovery attempt has been initiated for a lost account by a rescuer.
+		RecoveryInitiated(AccountId, AccountId),
+		/// A friend has vouched for the recovery attempt of a lost account by a rescuer.
+		RecoveryVouched(AccountId, AccountId, AccountId),
+		/// A recovery attempt of a lost account by a rescuer has been closed.
+		RecoveryClosed(AccountId, AccountId),
+		/// A lost account has been recovered by a rescuer.
+		AccountRecovered(AccountId, AccountId),
+		/// An account has been made unrecoverable.
+		RecoveryRemoved(AccountId),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		/// Deposit one of this module's events by using the default implementation.
+		fn deposit_event() = default;
+
+		/// The base deposit reserved for making an account recoverable.
+		const ConfigDepositBase: BalanceOf<T> = T::ConfigDepositBase::get();
+
+		/// The additional deposit reserved per friend of a recoverable account.
+		const FriendDepositFactor: BalanceOf<T> = T::FriendDepositFactor::get();
+
+		/// The maximum number of friends of a recoverable account.
+		const MaxFriends: u16 = T::MaxFriends::get();
+
+		/// The deposit reserved for initiating a recovery attempt.
+		const RecoveryDeposit: BalanceOf<T> = T::RecoveryDeposit::get();
+
+		/// Dispatch a call with the signed origin of an account that the sender has recovered.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// The result of the call is deposited in a `RecoveredCallExecuted` event.
+		///
+		/// # <weight>
+		/// - One storage read.
+		/// - One event.
+		/// - The weight of the `call`.
+		/// # </weight>
+		#[weight = WeightWithCall(10_000)]
+		fn as_recovered(origin, account: T::AccountId, call: Box<<T as Trait>::Call>) {
+			let who = ensure_signed(origin)?;
+			ensure!(Self::proxy(&who).as_ref() == Some(&account), "not allowed");
+			let result = call.dispatch(system::RawOrigin::Signed(account.clone()).into());
+			Self::deposit_event(RawEvent::RecoveredCallExecuted(
+				account,
+				who,
+				result.map_err(Into::into),
+			));
+		}
+
+		/// Allow `rescuer` to dispatch calls as `lost`, bypassing the recovery process.
+		///
+		/// The dispatch origin for this call must be _Root_.
+		///
+		/// # <weight>
+		/// - One storage write.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+		fn set_recovered(origin, lost: T::AccountId, rescuer: T::AccountId) {
+			ensure_root(origin)?;
+			<Proxy<T>>::insert(&rescuer, &lost);
+			Self::deposit_event(RawEvent::AccountRecovered(lost, rescuer));
+		}
+
+		/// Make the sender recoverable.
+		///
+		/// `ConfigDepositBase` plus `FriendDepositFactor` per friend is reserved from the sender
+		/// until it is made unrecoverable with `remove_recovery`.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// Parameters:
+		/// - `friends`: The accounts which may vouch for a recovery attempt. Must be sorted and
+		///   free of duplicates, and no more than `MaxFriends`.
+		/// - `threshold`: The number of friends which must vouch for a recovery attempt. Must be
+		///   at least one and at most the number of friends.
+		/// - `delay_period`: The minimum number of blocks between the start of a recovery attempt
+		///   and its claim.
+		///
+		/// # <weight>
+		/// - O(F) where F is the number of friends, capped by `MaxFriends`.
+		/// - One balance-reserve operation.
+		/// - One storage write.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		fn create_recovery(origin,
+			friends: Vec<T::AccountId>,
+			threshold: u16,
+			delay_period: T::BlockNumber,
+		) {
+			let who = ensure_signed(origin)?;
+			ensure!(!<Recoverable<T>>::exists(&who), "already recoverable");
+			ensure!(threshold >= 1, "threshold must be greater than zero");
+			ensure!(!friends.is_empty(), "at least one friend is required");
+			ensure!(friends.len() <= T::MaxFriends::get() as usize, "too many friends");
+			ensure!(
+				threshold as usize <= friends.len(),
+				"threshold greater than the number of friends"
+			);
+			ensure!(friends.windows(2).all(|w| w[0] < w[1]), "friends must be sorted and unique");
+
+			let friend_deposit = T::FriendDepositFactor::get()
+				.saturating_mul((friends.len() as u32).into());
+			let deposit = T::ConfigDepositBase::get().saturating_add(friend_deposit);
+			T::Currency::reserve(&who, deposit)?;
+
+			let config = RecoveryConfig { delay_period, deposit, friends, threshold };
+			<Recoverable<T>>::insert(&who, config);
+			Self::deposit_event(RawEvent::RecoveryCreated(who));
+		}
+
+		/// Initiate a recovery attempt of a recoverable account, reserving `RecoveryDeposit` from
+		/// the sender.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// # <weight>
+		/// - One balance-reserve operation.
+		/// - Two storage reads, two storage writes.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		fn initiate_recovery(origin, account: T::AccountId) {
+			let who = ensure_signed(origin)?;
+			ensure!(<Recoverable<T>>::exists(&account), "account not recoverable");
+			ensure!(!<ActiveRecoveries<T>>::exists(&account, &who), "recovery already started");
+
+			let deposit = T::RecoveryDeposit::get();
+			T::Currency::reserve(&who, deposit)?;
+
+			let recovery = ActiveRecovery {
+				created: <system::Module<T>>::block_number(),
+				deposit,
+				friends: vec![],
+			};
+			<ActiveRecoveries<T>>::insert(&account, &who, recovery);
+			<ActiveRecoveryCount<T>>::mutate(&account, |count| *count += 1);
+			Self::deposit_event(RawEvent::RecoveryInitiated(account, who));
+		}
+
+		/// Vouch for the recovery attempt of `lost` by `rescuer`.
+		///
+		/// The dispatch origin for this call must be _Signed_ by a friend of `lost`.
+		///
+		/// # <weight>
+		/// - O(F) where F is the number of friends, capped by `MaxFriends`.
+		/// - Two storage reads, one storage write.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		fn vouch_recovery(origin, lost: T::AccountId, rescuer: T::AccountId) {
+			let who = ensure_signed(origin)?;
+			let config = Self::recovery_config(&lost).ok_or("account not recoverable")?;
+			let mut recovery = Self::active_recovery(&lost, &rescuer)
+				.ok_or("recovery not started")?;
+			ensure!(config.friends.binary_search(&who).is_ok(), "not a friend");
+			let index = recovery.friends.binary_search(&who).err().ok_or("already vouched")?;
+			recovery.friends.insert(index, who.clone());
+
+			<ActiveRecoveries<T>>::insert(&lost, &rescuer, recovery);
+			Self::deposit_event(RawEvent::RecoveryVouched(lost, rescuer, who));
+		}
+
+		/// Claim `account` once enough of its friends have vouched for the recovery attempt of
+		/// the sender, and the delay period has passed. The sender can then dispatch calls as
+		/// `account` with `as_recovered`.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the rescuer.
+		///
+		/// # <weight>
+		/// - Three storage reads, one storage write.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		fn claim_recovery(origin, account: T::AccountId) {
+			let who = ensure_signed(origin)?;
+			let config = Self::recovery_config(&account).ok_or("account not recoverable")?;
+			let recovery = Self::active_recovery(&account, &who).ok_or("recovery not started")?;
+			ensure!(!<Proxy<T>>::exists(&who), "already recovering an account");
+			let now = <system::Module<T>>::block_number();
+			ensure!(
+				now >= recovery.created.saturating_add(config.delay_period),
+				"recovery still delayed"
+			);
+			ensure!(recovery.friends.len() >= config.threshold as usize, "threshold not met");
+
+			<Proxy<T>>::insert(&who, &account);
+			Self::deposit_event(RawEvent::AccountRecovered(account, who));
+		}
+
+		/// Close the recovery attempt of the sender by `rescuer`. The deposit of the rescuer is
+		/// transferred to the sender.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the account being recovered,
+		/// possibly through `as_recovered` once the account has been recovered.
+		///
+		/// # <weight>
+		/// - One balance operation.
+		/// - One storage read, two storage writes.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		fn close_recovery(origin, rescuer: T::AccountId) {
+			let who = ensure_signed(origin)?;
+			let recovery = Self::active_recovery(&who, &rescuer).ok_or("recovery not started")?;
+			<ActiveRecoveries<T>>::remove(&who, &rescuer);
+			<ActiveRecoveryCount<T>>::mutate(&who, |count| *count = count.saturating_sub(1));
+			let _ = T::Currency::repatriate_reserved(&rescuer, &who, recovery.deposit);
+			Self::deposit_event(RawEvent::RecoveryClosed(who, rescuer));
+		}
+
+		/// Make the sender unrecoverable, returning the deposit of its recovery configuration.
+		///
+		/// All recovery attempts of the sender must have been closed with `close_recovery`
+		/// first.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// # <weight>
+		/// - One balance operation.
+		/// - Two storage reads, one storage write.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		fn remove_recovery(origin) {
+			let who = ensure_signed(origin)?;
+			ensure!(Self::active_recovery_count(&who) == 0, "recovery still active");
+			let config = <Recoverable<T>>::take(&who).ok_or("account not recoverable")?;
+			T::Currency::unreserve(&who, config.deposit);
+			Self::deposit_event(RawEvent::RecoveryRemoved(who));
+		}
+
+		/// Stop being able to dispatch calls as `account`.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the rescuer of `account`.
+		///
+		/// # <weight>
+		/// - One storage read, one storage write.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		fn cancel_recovered(origin, account: T::AccountId) {
+			let who = ensure_signed(origin)?;
+			ensure!(Self::proxy(&who).as_ref() == Some(&account), "not allowed");
+			<Proxy<T>>::remove(&who);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use support::{assert_ok, assert_noop, impl_outer_origin, parameter_types, impl_outer_dispatch};
+	use primitives::H256;
+	use sr_primitives::{Perbill, traits::{BlakeTwo256, IdentityLookup}, testing::Header};
+
+	impl_outer_origin! {
+		pub enum Origin for Test {}
+	}
+
+	impl_outer_dispatch! {
+		pub enum Call for Test where origin: Origin {
+			balances::Balances,
+			recovery::Recovery,
+		}
+	}
+
+	// For testing the module, we construct most of a mock runtime. This means
+	// first constructing a configuration type (`Test`) which `impl`s each of the
+	// configuration traits of modules we want to use.
+	#[derive(Clone, Eq, PartialEq)]
+	pub struct Test;
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const MaximumBlockWeight: u32 = 1024;
+		pub const MaximumBlockLength: u32 = 2 * 1024;
+		pub const AvailableBlockRatio: Perbill = Perbill::one();
+	}
+	impl system::Trait for Test {
+		type Origin = Origin;
+		type Index = u64;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Call = Call;
+		type Hashing = BlakeTwo256;
+		type AccountId = u64;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = Header;
+		type Event = ();
+		type BlockHashCount = BlockHashCount;
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type MaximumBlockLength = MaximumBlockLength;
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type Version = ();
+	}
+	parameter_types! {
+		pub const ExistentialDeposit: u64 = 0;
+		pub const TransferFee: u64 = 0;
+		pub const CreationFee: u64 = 0;
+	}
+	impl balances::Trait for Test {
+		type Balance = u64;
+		type OnFreeBalanceZero = ();
+		type OnNewAccount = ();
+		type Event = ();
+		type TransferPayment = ();
+		type DustRemoval = ();
+		type ExistentialDeposit = ExistentialDeposit;
+		type TransferFee = TransferFee;
+		type CreationFee = CreationFee;
+	}
+	parameter_types! {
+		pub const ConfigDepositBase: u64 = 10;
+		pub const FriendDepositFactor: u64 = 1;
+		pub const MaxFriends: u16 = 3;
+		pub const RecoveryDeposit: u64 = 10;
+	}
+	impl Trait for Test {
+		type Event = ();
+		type Call = Call;
+		type Currency = Balances;
+		type ConfigDepositBase = ConfigDepositBase;
+		type FriendDepositFactor = FriendDepositFactor;
+		type MaxFriends = MaxFriends;
+		type RecoveryDeposit = RecoveryDeposit;
+	}
+	type System = system::Module<Test>;
+	type Balances = balances::Module<Test>;
+	type Recovery = Module<Test>;
+
+	fn new_test_ext() -> runtime_io::TestExternalities {
+		let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+		balances::GenesisConfig::<Test> {
+			balances: vec![(1, 100), (2, 100), (3, 100), (4, 100), (5, 100)],
+		}.assimilate_storage(&mut t).unwrap();
+		t.into()
+	}
+
+	fn transfer_call(dest: u64, value: u64) -> Box<Call> {
+		Box::new(Call::Balances(balances::Call::transfer(dest, value)))
+	}
+
+	#[test]
+	fn recovery_works() {
+		new_test_ext().execute_with(|| {
+			// 5 makes itself recoverable by 2, 3 and 4, two of them being needed.
+			assert_ok!(Recovery::create_recovery(Origin::signed(5), vec![2, 3, 4], 2, 10));
+			assert_eq!(Balances::reserved_balance(&5), 13);
+
+			// 1 initiates the recovery of 5, and friends vouch for it.
+			assert_ok!(Recovery::initiate_recovery(Origin::signed(1), 5));
+			assert_eq!(Balances::reserved_balance(&1), 10);
+			assert_ok!(Recovery::vouch_recovery(Origin::signed(2), 5, 1));
+			assert_noop!(Recovery::claim_recovery(Origin::signed(1), 5), "recovery still delayed");
+			assert_ok!(Recovery::vouch_recovery(Origin::signed(3), 5, 1));
+
+			System::set_block_number(10);
+			assert_noop!(
+				Recovery::as_recovered(Origin::signed(1), 5, transfer_call(1, 10)),
+				"not allowed"
+			);
+			assert_ok!(Recovery::claim_recovery(Origin::signed(1), 5));
+
+			// 1 gets all the funds of 5, including the deposits.
+			let close = Box::new(Call::Recovery(super::Call::close_recovery(1)));
+			assert_ok!(Recovery::as_recovered(Origin::signed(1), 5, close));
+			let remove = Box::new(Call::Recovery(super::Call::remove_recovery()));
+			assert_ok!(Recovery::as_recovered(Origin::signed(1), 5, remove));
+			assert_ok!(Recovery::as_recovered(Origin::signed(1), 5, transfer_call(1, 110)));
+			assert_eq!(Balances::free_balance(&1), 200);
+			assert_eq!(Balances::total_balance(&5), 0);
+			assert_eq!(Recovery::recovery_config(&5), None);
+
+			assert_ok!(Recovery::cancel_recovered(Origin::signed(1), 5));
+			assert_noop!(
+				Recovery::as_recovered(Origin::signed(1), 5, transfer_call(1, 0)),
+				"not allowed"
+			);
+		});
+	}
+
+	#[test]
+	fn malicious_recovery_can_be_closed() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(Recovery::create_recovery(Origin::signed(5), vec![2, 3, 4], 2, 10));
+			assert_ok!(Recovery::initiate_recovery(Origin::signed(1), 5));
+			assert_ok!(Recovery::vouch_recovery(Origin::signed(2), 5, 1));
+			assert_noop!(
+				Recovery::remove_recovery(Origin::signed(5)),
+				"recovery still active"
+			);
+
+			// 5 still has its key: it closes the attempt and takes the deposit.
+			assert_ok!(Recovery::close_recovery(Origin::signed(5), 1));
+			assert_eq!(Balances::total_balance(&1), 90);
+			assert_eq!(Balances::free_balance(&5), 97);
+			assert_eq!(Recovery::active_recovery(&5, &1), None);
+
+			System::set_block_number(10);
+			assert_noop!(Recovery::claim_recovery(Origin::signed(1), 5), "recovery not started");
+
+			assert_ok!(Recovery::remove_recovery(Origin::signed(5)));
+			assert_eq!(Balances::free_balance(&5), 110);
+		});
+	}
+
+	#[test]
+	fn create_recovery_checks_config() {
+		new_test_ext().execute_with(|| {
+			assert_noop!(
+				Recovery::create_recovery(Origin::signed(5), vec![2, 3], 0, 10),
+				"threshold must be greater than zero"
+			);
+			assert_noop!(
+				Recovery::create_recovery(Origin::signed(5), vec![], 1, 10),
+				"at least one friend is required"
+			);
+			assert_noop!(
+				Recovery::create_recovery(Origin::signed(5), vec![1, 2, 3, 4], 1, 10),
+				"too many friends"
+			);
+			assert_noop!(
+				Recovery::create_recovery(Origin::signed(5), vec![2, 3], 3, 10),
+				"threshold greater than the number of friends"
+			);
+			assert_noop!(
+				Recovery::create_recovery(Origin::signed(5), vec![3, 2], 1, 10),
+				"friends must be sorted and unique"
+			);
+			assert_noop!(
+				Recovery::create_recovery(Origin::signed(5), vec![2, 2], 1, 10),
+				"friends must be sorted and unique"
+			);
+			assert_ok!(Recovery::create_recovery(Origin::signed(5), vec![2, 3], 1, 10));
+			assert_noop!(
+				Recovery::create_recovery(Origin::signed(5), vec![2, 3], 1, 10),
+				"already recoverable"
+			);
+		});
+	}
+
+	#[test]
+	fn vouching_and_claiming_checks() {
+		new_test_ext().execute_with(|| {
+			assert_noop!(
+				Recovery::initiate_recovery(Origin::signed(1), 5),
+				"account not recoverable"
+			);
+			assert_ok!(Recovery::create_recovery(Origin::signed(5), vec![2, 3, 4], 2, 0));
+			assert_noop!(Recovery::vouch_recovery(Origin::signed(2), 5, 1), "recovery not started");
+			assert_ok!(Recovery::initiate_recovery(Origin::signed(1), 5));
+			assert_noop!(
+				Recovery::initiate_recovery(Origin::signed(1), 5),
+				"recovery already started"
+			);
+
+			assert_noop!(Recovery::vouch_recovery(Origin::signed(1), 5, 1), "not a friend");
+			assert_ok!(Recovery::vouch_recovery(Origin::signed(2), 5, 1));
+			assert_noop!(Recovery::vouch_recovery(Origin::signed(2), 5, 1), "already vouched");
+			assert_noop!(Recovery::claim_recovery(Origin::signed(1), 5), "threshold not met");
+			assert_ok!(Recovery::vouch_recovery(Origin::signed(4), 5, 1));
+			assert_ok!(Recovery::claim_recovery(Origin::signed(1), 5));
+			assert_eq!(Recovery::proxy(&1), Some(5));
+			assert_noop!(
+				Recovery::claim_recovery(Origin::signed(1), 5),
+				"already recovering an account"
+			);
+		});
+	}
+
+	#[test]
+	fn set_recovered_works() {
+		new_test_ext().execute_with(|| {
+			assert_noop!(Recovery::set_recovered(Origin::signed(1), 5, 1), "RequireRootOrigin");
+			assert_ok!(Recovery::set_recovered(Origin::ROOT, 5, 1));
+			assert_ok!(Recovery::as_recovered(Origin::signed(1), 5, transfer_call(1, 100)));
+			assert_eq!(Balances::free_balance(&1), 200);
+		});
+	}
+
+	#[test]
+	fn as_recovered_weighs_the_dispatched_call() {
+		let transfer = transfer_call(1, 100);
+		let transfer_weight = transfer.get_dispatch_info().weight;
+		let call = Call::Recovery(super::Call::as_recovered(5, transfer));
+		assert_eq!(call.get_dispatch_info().weight, 10_000 + transfer_weight);
+
+		let remove = Box::new(Call::Recovery(super::Call::remove_recovery()));
+		let remove_weight = remove.get_dispatch_info().weight;
+		let call = Call::Recovery(super::Call::as_recovered(5, remove));
+		assert_eq!(call.get_dispatch_info().weight, 10_000 + remove_weight);
+		assert!(remove_weight != transfer_weight);
+	}
+}
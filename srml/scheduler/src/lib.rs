@@ -0,0 +1,732 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Scheduler Module
+//! A module for scheduling dispatches.
+//!
+//! - [`scheduler::Trait`](./trait.Trait.html)
+//! - [`Call`](./enum.Call.html)
+//!
+//! ## Overview
+//!
+//! This module exposes capabilities for scheduling dispatches to occur at a specified block
+//! number or at a specified period. These scheduled dispatches may be named or anonymous and may
+//! be canceled.
+//!
+//! Scheduled calls are dispatched with the _Root_ origin at the beginning of the block, in order
+//! of priority. Once `MaximumWeight` is reached, the remaining calls are postponed to the next
+//! block, unless their priority is `HARD_DEADLINE` or higher.
+//!
+//! A call may be scheduled by the hash of its preimage instead of as is, so that a large call is
+//! not stored twice. Its preimage is requested from `Preimages` until the task is done, and looked
+//! up when the call is due. If the preimage is not available at that point, that dispatch of the
+//! task is skipped.
+//!
+//! Other modules may schedule calls through the `schedule::Anon` and `schedule::Named` traits,
+//! which this module implements.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! * `schedule` - Schedule a dispatch, which may be periodic, to occur at a specified block and
+//!   with a specified priority.
+//! * `cancel` - Cancel a scheduled dispatch, specified by block number and index.
+//! * `schedule_named` - Augments the `schedule` interface with an additional `Vec<u8>` parameter
+//!   that can be used for identification.
+//! * `cancel_named` - The named complement to the cancel function.
+//!
+//! [`Call`]: ./enum.Call.html
+//! [`Trait`]: ./trait.Trait.html
+
+// Ensure we're `no_std` when compiling for Wasm.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use rstd::prelude::*;
+use codec::{Encode, Decode};
+use support::{decl_module, decl_event, decl_storage, ensure, Parameter, metadata::TypeInfo};
+use support::traits::{Get, schedule::{self, MaybeHashed}, preimage::PreimageProvider};
+use sr_primitives::{
+	RuntimeDebug, DispatchError,
+	traits::{Dispatchable, EnsureOrigin, One, Zero, Saturating},
+	weights::{Weight, GetDispatchInfo, SimpleDispatchInfo},
+};
+
+/// Our module's configuration trait. All our types and constants go in here. If the
+/// module is dependent on specific other modules, then their configuration traits
+/// should be added to our implied traits list.
+///
+/// `system::Trait` should always be included in our implied traits.
+pub trait Trait: system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+	/// The aggregated call type.
+	type Call: Parameter + Dispatchable<Origin=<Self as system::Trait>::Origin> + GetDispatchInfo;
+
+	/// The maximum weight that may be scheduled per block for any dispatchables of less priority
+	/// than `schedule::HARD_DEADLINE`.
+	type MaximumWeight: Get<Weight>;
+
+	/// Required origin to schedule or cancel calls.
+	type ScheduleOrigin: EnsureOrigin<<Self as system::Trait>::Origin>;
+
+	/// The provider of the preimages of calls scheduled by hash.
+	type Preimages: PreimageProvider<Self::Hash>;
+}
+
+/// A scheduled call, either as is or by the hash of its preimage.
+pub type CallOrHashOf<T> = MaybeHashed<<T as Trait>::Call, <T as system::Trait>::Hash>;
+
+/// The location of a scheduled task that can be used to remove it.
+pub type TaskAddress<BlockNumber> = (BlockNumber, u32);
+
+/// Information regarding an item to be executed in the future.
+#[derive(Clone, RuntimeDebug, Encode, Decode, TypeInfo)]
+pub struct Scheduled<Call, BlockNumber> {
+	/// The unique identity for this task, if there is one.
+	maybe_id: Option<Vec<u8>>,
+	/// This task's priority.
+	priority: schedule::Priority,
+	/// The call to be dispatched, or the hash of its preimage.
+	call: Call,
+	/// If the call is periodic, then this points to the information concerning that, with the
+	/// number of executions remaining after the next one.
+	maybe_periodic: Option<schedule::Period<BlockNumber>>,
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Scheduler {
+		/// Items to be executed, indexed by the block number that they should be executed on.
+		pub Agenda get(fn agenda): map T::BlockNumber
+			=> Vec<Option<Scheduled<CallOrHashOf<T>, T::BlockNumber>>>;
+
+		/// Lookup from identity to the block number and index of the task.
+		Lookup: map Vec<u8> => Option<TaskAddress<T::BlockNumber>>;
+	}
+}
+
+decl_event!(
+	/// Events type.
+	pub enum Event<T> where BlockNumber = <T as system::Trait>::BlockNumber {
+		/// A task has been scheduled at the given block number and index.
+		Scheduled(BlockNumber, u32),
+		/// The task at the given block number and index has been canceled.
+		Canceled(BlockNumber, u32),
+		/// A task has been dispatched, with its address, identity and the result of the call.
+		Dispatched(TaskAddress<BlockNumber>, Option<Vec<u8>>, Result<(), DispatchError>),
+		/// The call of a task, with the given address and identity, was scheduled by hash and its
+		/// preimage was not available, so it was not dispatched.
+		CallUnavailable(TaskAddress<BlockNumber>, Option<Vec<u8>>),
+	}
+);
+
+decl_module! {
+	/// Scheduler module declaration.
+	pub struct Module<T: Trait> for enum Call where origin: <T as system::Trait>::Origin {
+		/// Deposit one of this module's events by using the default implementation.
+		fn deposit_event() = default;
+
+		/// The maximum weight of the scheduled calls executed in a block, for calls of less
+		/// priority than `schedule::HARD_DEADLINE`.
+		const MaximumWeight: Weight = T::MaximumWeight::get();
+
+		/// Schedule a call to be dispatched with the _Root_ origin at the beginning of block
+		/// `when`.
+		///
+		/// The dispatch origin for this call must be `ScheduleOrigin`.
+		///
+		/// - `when`: The block at which to dispatch the call. Must be in the future.
+		/// - `maybe_periodic`: If `Some((period, count))`, the call is dispatched `count` times
+		/// in total, every `period` blocks.
+		/// - `priority`: The priority of the call within its block; lower values come first.
+		/// - `call`: The call to dispatch, or the hash of its preimage, which is requested until
+		/// the task is done.
+		///
+		/// # <weight>
+		/// - O(A) where `A` is the number of calls already scheduled at block `when`.
+		/// - One storage read/write.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+		fn schedule(origin,
+			when: T::BlockNumber,
+			maybe_periodic: Option<schedule::Period<T::BlockNumber>>,
+			priority: schedule::Priority,
+			call: Box<CallOrHashOf<T>>,
+		) {
+			T::ScheduleOrigin::ensure_origin(origin)?;
+			ensure!(when > <system::Module<T>>::block_number(), "schedule in the past");
+			Self::do_schedule(None, when, maybe_periodic, priority, *call);
+		}
+
+		/// Cancel an anonymously scheduled task.
+		///
+		/// The dispatch origin for this call must be `ScheduleOrigin`.
+		///
+		/// # <weight>
+		/// - O(A) where `A` is the number of calls scheduled at block `when`.
+		/// - One storage read/write.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+		fn cancel(origin, when: T::BlockNumber, index: u32) {
+			T::ScheduleOrigin::ensure_origin(origin)?;
+			Self::do_cancel((when, index))?;
+		}
+
+		/// Schedule a named task, which can be canceled by its `id`.
+		///
+		/// The dispatch origin for this call must be `ScheduleOrigin`.
+		///
+		/// Fails if a task with the same `id` is already scheduled. The parameters are otherwise
+		/// the same as for `schedule`.
+		///
+		/// # <weight>
+		/// - O(A) where `A` is the number of calls already scheduled at block `when`.
+		/// - Two storage reads, two storage writes.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+		fn schedule_named(origin,
+			id: Vec<u8>,
+			when: T::BlockNumber,
+			maybe_periodic: Option<schedule::Period<T::BlockNumber>>,
+			priority: schedule::Priority,
+			call: Box<CallOrHashOf<T>>,
+		) {
+			T::ScheduleOrigin::ensure_origin(origin)?;
+			ensure!(when > <system::Module<T>>::block_number(), "schedule in the past");
+			ensure!(!Lookup::<T>::exists(&id), "id already in use");
+			Self::do_schedule(Some(id), when, maybe_periodic, priority, *call);
+		}
+
+		/// Cancel a named scheduled task.
+		///
+		/// The dispatch origin for this call must be `ScheduleOrigin`.
+		///
+		/// # <weight>
+		/// - O(A) where `A` is the number of calls scheduled at the block of the task.
+		/// - Two storage reads, two storage writes.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+		fn cancel_named(origin, id: Vec<u8>) {
+			T::ScheduleOrigin::ensure_origin(origin)?;
+			Self::do_cancel_named(id)?;
+		}
+
+		fn on_initialize(now: T::BlockNumber) {
+			let limit = T::MaximumWeight::get();
+			let mut queued = Agenda::<T>::take(now).into_iter()
+				.enumerate()
+				.filter_map(|(index, s)| s.map(|s| (index as u32, s)))
+				.collect::<Vec<_>>();
+			queued.sort_by_key(|(_, s)| s.priority);
+
+			let mut total_weight: Weight = 0;
+			let mut postponed = Vec::new();
+			for (order, (index, s)) in queued.into_iter().enumerate() {
+				let maybe_call = Self::resolve(&s.call);
+				let call_weight = maybe_call.as_ref().map_or(0, |call| call.get_dispatch_info().weight);
+				let next_weight = total_weight.saturating_add(call_weight);
+				// The first task is always executed, so that a single task heavier than the limit
+				// cannot stall the agenda forever.
+				if order > 0 && s.priority > schedule::HARD_DEADLINE && next_weight > limit {
+					postponed.push(s);
+					continue
+				}
+				total_weight = next_weight;
+
+				let maybe_result = maybe_call
+					.map(|call| call.dispatch(system::RawOrigin::Root.into()).map_err(Into::into));
+				let maybe_id = s.maybe_id.clone();
+				if let Some((period, remaining)) = s.maybe_periodic {
+					let maybe_periodic = if remaining > 1 { Some((period, remaining - 1)) } else { None };
+					Self::push_task(now.saturating_add(period), Scheduled { maybe_periodic, ..s });
+				} else {
+					if let Some(ref id) = s.maybe_id {
+						Lookup::<T>::remove(id);
+					}
+					Self::release(&s.call);
+				}
+				match maybe_result {
+					Some(result) => Self::deposit_event(RawEvent::Dispatched((now, index), maybe_id, result)),
+					None => Self::deposit_event(RawEvent::CallUnavailable((now, index), maybe_id)),
+				}
+			}
+
+			let next = now + One::one();
+			for s in postponed {
+				Self::push_task(next, s);
+			}
+
+			<system::Module<T>>::register_extra_weight_unchecked(total_weight);
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	fn do_schedule(
+		maybe_id: Option<Vec<u8>>,
+		when: T::BlockNumber,
+		maybe_periodic: Option<schedule::Period<T::BlockNumber>>,
+		priority: schedule::Priority,
+		call: CallOrHashOf<T>,
+	) -> TaskAddress<T::BlockNumber> {
+		if let MaybeHashed::Hash(ref hash) = call {
+			T::Preimages::request_preimage(hash);
+		}
+		// A task scheduled for the current block or before would never be executed.
+		let when = when.max(<system::Module<T>>::block_number() + One::one());
+		// Only keep the number of executions after the first one.
+		let maybe_periodic = maybe_periodic
+			.filter(|(period, count)| !period.is_zero() && *count > 1)
+			.map(|(period, count)| (period, count - 1));
+
+		let address = Self::push_task(when, Scheduled { maybe_id, priority, call, maybe_periodic });
+		Self::deposit_event(RawEvent::Scheduled(address.0, address.1));
+		address
+	}
+
+	fn do_cancel((when, index): TaskAddress<T::BlockNumber>) -> Result<(), &'static str> {
+		let mut agenda = Agenda::<T>::get(when);
+		let s = agenda.get_mut(index as usize).and_then(Option::take).ok_or("not found")?;
+		Agenda::<T>::insert(when, agenda);
+		if let Some(id) = s.maybe_id {
+			Lookup::<T>::remove(id);
+		}
+		Self::release(&s.call);
+		Self::deposit_event(RawEvent::Canceled(when, index));
+		Ok(())
+	}
+
+	fn do_cancel_named(id: Vec<u8>) -> Result<(), &'static str> {
+		let address = Lookup::<T>::get(&id).ok_or("not found")?;
+		Self::do_cancel(address)
+	}
+
+	/// The call of a task, if it is given as is or its preimage is available and decodes.
+	fn resolve(call: &CallOrHashOf<T>) -> Option<<T as Trait>::Call> {
+		match call {
+			MaybeHashed::Value(call) => Some(call.clone()),
+			MaybeHashed::Hash(hash) => T::Preimages::get_preimage(hash)
+				.and_then(|bytes| Decode::decode(&mut &bytes[..]).ok()),
+		}
+	}
+
+	/// Drop the request for the preimage of the call of a task that is done or canceled.
+	fn release(call: &CallOrHashOf<T>) {
+		if let MaybeHashed::Hash(hash) = call {
+			T::Preimages::unrequest_preimage(hash);
+		}
+	}
+
+	/// Append a task to the agenda of `when` and record its address if it is named.
+	fn push_task(
+		when: T::BlockNumber,
+		s: Scheduled<CallOrHashOf<T>, T::BlockNumber>,
+	) -> TaskAddress<T::BlockNumber> {
+		let maybe_id = s.maybe_id.clone();
+		let mut agenda = Agenda::<T>::get(when);
+		let address = (when, agenda.len() as u32);
+		agenda.push(Some(s));
+		Agenda::<T>::insert(when, agenda);
+		if let Some(id) = maybe_id {
+			Lookup::<T>::insert(id, address);
+		}
+		address
+	}
+}
+
+impl<T: Trait> schedule::Anon<T::BlockNumber, <T as Trait>::Call> for Module<T> {
+	type Address = TaskAddress<T::BlockNumber>;
+
+	fn schedule(
+		when: T::BlockNumber,
+		maybe_periodic: Option<schedule::Period<T::BlockNumber>>,
+		priority: schedule::Priority,
+		call: <T as Trait>::Call,
+	) -> Self::Address {
+		Self::do_schedule(None, when, maybe_periodic, priority, call.into())
+	}
+
+	fn cancel(address: Self::Address) -> Result<(), ()> {
+		Self::do_cancel(address).map_err(|_| ())
+	}
+}
+
+impl<T: Trait> schedule::Named<T::BlockNumber, <T as Trait>::Call> for Module<T> {
+	type Address = TaskAddress<T::BlockNumber>;
+
+	fn schedule_named(
+		id: Vec<u8>,
+		when: T::BlockNumber,
+		maybe_periodic: Option<schedule::Period<T::BlockNumber>>,
+		priority: schedule::Priority,
+		call: <T as Trait>::Call,
+	) -> Result<Self::Address, ()> {
+		if Lookup::<T>::exists(&id) {
+			return Err(())
+		}
+		Ok(Self::do_schedule(Some(id), when, maybe_periodic, priority, call.into()))
+	}
+
+	fn cancel_named(id: Vec<u8>) -> Result<(), ()> {
+		Self::do_cancel_named(id).map_err(|_| ())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use support::{assert_ok, assert_noop, impl_outer_origin, impl_outer_dispatch, parameter_types};
+	use support::traits::{OnInitialize, schedule::{Anon, Named}};
+	use primitives::H256;
+	use sr_primitives::{Perbill, traits::{BlakeTwo256, IdentityLookup, Hash}, testing::Header};
+	use system::EnsureRoot;
+	use std::{cell::RefCell, collections::BTreeMap};
+
+	thread_local! {
+		static PREIMAGES: RefCell<BTreeMap<H256, Vec<u8>>> = RefCell::new(BTreeMap::new());
+		static REQUESTS: RefCell<BTreeMap<H256, u32>> = RefCell::new(BTreeMap::new());
+	}
+
+	/// Preimages which are noted and requested in memory.
+	pub struct TestPreimages;
+	impl TestPreimages {
+		fn note(call: &Call) -> H256 {
+			let hash = BlakeTwo256::hash_of(call);
+			PREIMAGES.with(|p| p.borrow_mut().insert(hash, call.encode()));
+			hash
+		}
+		fn requests(hash: &H256) -> u32 {
+			REQUESTS.with(|r| r.borrow().get(hash).cloned().unwrap_or(0))
+		}
+	}
+	impl PreimageProvider<H256> for TestPreimages {
+		fn have_preimage(hash: &H256) -> bool {
+			PREIMAGES.with(|p| p.borrow().contains_key(hash))
+		}
+		fn get_preimage(hash: &H256) -> Option<Vec<u8>> {
+			PREIMAGES.with(|p| p.borrow().get(hash).cloned())
+		}
+		fn preimage_requested(hash: &H256) -> bool {
+			Self::requests(hash) > 0
+		}
+		fn request_preimage(hash: &H256) {
+			REQUESTS.with(|r| *r.borrow_mut().entry(*hash).or_insert(0) += 1);
+		}
+		fn unrequest_preimage(hash: &H256) {
+			REQUESTS.with(|r| r.borrow_mut().entry(*hash).and_modify(|c| *c = c.saturating_sub(1)));
+		}
+	}
+
+	mod logger {
+		use super::*;
+		use std::cell::RefCell;
+		use system::ensure_root;
+
+		thread_local! {
+			static LOG: RefCell<Vec<u32>> = RefCell::new(Vec::new());
+		}
+		pub fn log() -> Vec<u32> {
+			LOG.with(|log| log.borrow().clone())
+		}
+		pub trait Trait: system::Trait {}
+		decl_module! {
+			pub struct Module<T: Trait> for enum Call where origin: <T as system::Trait>::Origin {
+				#[weight = SimpleDispatchInfo::FixedNormal(1_000)]
+				fn log(origin, i: u32) {
+					ensure_root(origin)?;
+					LOG.with(|log| log.borrow_mut().push(i));
+				}
+
+				#[weight = SimpleDispatchInfo::FixedNormal(600)]
+				fn log_heavy(origin, i: u32) {
+					ensure_root(origin)?;
+					LOG.with(|log| log.borrow_mut().push(i));
+				}
+			}
+		}
+	}
+
+	impl_outer_origin! {
+		pub enum Origin for Test {}
+	}
+
+	impl_outer_dispatch! {
+		pub enum Call for Test where origin: Origin {
+			logger::Logger,
+		}
+	}
+
+	// For testing the module, we construct most of a mock runtime. This means
+	// first constructing a configuration type (`Test`) which `impl`s each of the
+	// configuration traits of modules we want to use.
+	#[derive(Clone, Eq, PartialEq)]
+	pub struct Test;
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const MaximumBlockWeight: u32 = 2_000_000;
+		pub const MaximumBlockLength: u32 = 2 * 1024;
+		pub const AvailableBlockRatio: Perbill = Perbill::one();
+	}
+	impl system::Trait for Test {
+		type Origin = Origin;
+		type Index = u64;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Call = Call;
+		type Hashing = BlakeTwo256;
+		type AccountId = u64;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = Header;
+		type Event = ();
+		type BlockHashCount = BlockHashCount;
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type MaximumBlockLength = MaximumBlockLength;
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type Version = ();
+	}
+	impl logger::Trait for Test {}
+	parameter_types! {
+		pub const MaximumWeight: Weight = 1_500;
+	}
+	impl Trait for Test {
+		type Event = ();
+		type Call = Call;
+		type MaximumWeight = MaximumWeight;
+		type ScheduleOrigin = EnsureRoot<u64>;
+		type Preimages = TestPreimages;
+	}
+	type System = system::Module<Test>;
+	type Logger = logger::Module<Test>;
+	type Scheduler = Module<Test>;
+
+	fn new_test_ext() -> runtime_io::TestExternalities {
+		let t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+		t.into()
+	}
+
+	fn run_to_block(n: u64) {
+		while System::block_number() < n {
+			Scheduler::on_initialize(System::block_number() + 1);
+			System::set_block_number(System::block_number() + 1);
+		}
+	}
+
+	fn log_call(i: u32) -> Call {
+		Call::Logger(logger::Call::log(i))
+	}
+
+	#[test]
+	fn basic_scheduling_works() {
+		new_test_ext().execute_with(|| {
+			<Scheduler as Anon<_, _>>::schedule(4, None, 127, log_call(42));
+			run_to_block(3);
+			assert!(logger::log().is_empty());
+			run_to_block(4);
+			assert_eq!(logger::log(), vec![42u32]);
+			run_to_block(100);
+			assert_eq!(logger::log(), vec![42u32]);
+			assert!(Scheduler::agenda(4).is_empty());
+		});
+	}
+
+	#[test]
+	fn periodic_scheduling_works() {
+		new_test_ext().execute_with(|| {
+			// at #4, every 3 blocks, 3 times.
+			<Scheduler as Anon<_, _>>::schedule(4, Some((3, 3)), 127, log_call(42));
+			run_to_block(3);
+			assert!(logger::log().is_empty());
+			run_to_block(4);
+			assert_eq!(logger::log(), vec![42u32]);
+			run_to_block(6);
+			assert_eq!(logger::log(), vec![42u32]);
+			run_to_block(7);
+			assert_eq!(logger::log(), vec![42u32, 42u32]);
+			run_to_block(9);
+			assert_eq!(logger::log(), vec![42u32, 42u32]);
+			run_to_block(10);
+			assert_eq!(logger::log(), vec![42u32, 42u32, 42u32]);
+			run_to_block(100);
+			assert_eq!(logger::log(), vec![42u32, 42u32, 42u32]);
+		});
+	}
+
+	#[test]
+	fn cancel_named_scheduling_works_with_normal_cancel() {
+		new_test_ext().execute_with(|| {
+			// at #4.
+			<Scheduler as Named<_, _>>::schedule_named(b"1".to_vec(), 4, None, 127, log_call(69)).unwrap();
+			let i = <Scheduler as Anon<_, _>>::schedule(4, None, 127, log_call(42));
+			run_to_block(3);
+			assert!(logger::log().is_empty());
+			assert_eq!(<Scheduler as Named<_, _>>::cancel_named(b"1".to_vec()), Ok(()));
+			assert_eq!(<Scheduler as Anon<_, _>>::cancel(i), Ok(()));
+			run_to_block(100);
+			assert!(logger::log().is_empty());
+		});
+	}
+
+	#[test]
+	fn cancel_named_periodic_scheduling_works() {
+		new_test_ext().execute_with(|| {
+			// at #4, every 3 blocks, 3 times.
+			<Scheduler as Named<_, _>>::schedule_named(b"1".to_vec(), 4, Some((3, 3)), 127, log_call(42)).unwrap();
+			// same id results in error.
+			assert!(<Scheduler as Named<_, _>>::schedule_named(b"1".to_vec(), 4, None, 127, log_call(69)).is_err());
+			// different id is ok.
+			<Scheduler as Named<_, _>>::schedule_named(b"2".to_vec(), 8, None, 127, log_call(69)).unwrap();
+			run_to_block(3);
+			assert!(logger::log().is_empty());
+			run_to_block(4);
+			assert_eq!(logger::log(), vec![42u32]);
+			run_to_block(6);
+			assert_eq!(<Scheduler as Named<_, _>>::cancel_named(b"1".to_vec()), Ok(()));
+			run_to_block(100);
+			assert_eq!(logger::log(), vec![42u32, 69u32]);
+		});
+	}
+
+	#[test]
+	fn scheduler_respects_weight_limits() {
+		new_test_ext().execute_with(|| {
+			<Scheduler as Anon<_, _>>::schedule(4, None, 127, log_call(42));
+			<Scheduler as Anon<_, _>>::schedule(4, None, 127, log_call(69));
+			run_to_block(4);
+			assert_eq!(logger::log(), vec![42u32]);
+			run_to_block(5);
+			assert_eq!(logger::log(), vec![42u32, 69u32]);
+		});
+	}
+
+	#[test]
+	fn scheduler_respects_hard_deadlines_more() {
+		new_test_ext().execute_with(|| {
+			<Scheduler as Anon<_, _>>::schedule(4, None, 0, log_call(42));
+			<Scheduler as Anon<_, _>>::schedule(4, None, 0, log_call(69));
+			run_to_block(4);
+			assert_eq!(logger::log(), vec![42u32, 69u32]);
+		});
+	}
+
+	#[test]
+	fn scheduler_respects_priority_ordering() {
+		new_test_ext().execute_with(|| {
+			<Scheduler as Anon<_, _>>::schedule(4, None, 1, log_call(42));
+			<Scheduler as Anon<_, _>>::schedule(4, None, 0, log_call(69));
+			run_to_block(4);
+			assert_eq!(logger::log(), vec![69u32, 42u32]);
+		});
+	}
+
+	#[test]
+	fn scheduler_respects_priority_ordering_with_soft_deadlines() {
+		new_test_ext().execute_with(|| {
+			<Scheduler as Anon<_, _>>::schedule(4, None, 255, Call::Logger(logger::Call::log_heavy(42)));
+			<Scheduler as Anon<_, _>>::schedule(4, None, 127, Call::Logger(logger::Call::log_heavy(69)));
+			<Scheduler as Anon<_, _>>::schedule(4, None, 126, Call::Logger(logger::Call::log_heavy(2600)));
+			run_to_block(4);
+			assert_eq!(logger::log(), vec![2600u32, 69u32]);
+			run_to_block(5);
+			assert_eq!(logger::log(), vec![2600u32, 69u32, 42u32]);
+		});
+	}
+
+	#[test]
+	fn scheduled_calls_are_accounted_in_block_weight() {
+		new_test_ext().execute_with(|| {
+			<Scheduler as Anon<_, _>>::schedule(2, None, 0, log_call(42));
+			<Scheduler as Anon<_, _>>::schedule(2, None, 0, log_call(69));
+			run_to_block(2);
+			assert_eq!(System::all_extrinsics_weight(), 2_000);
+		});
+	}
+
+	#[test]
+	fn dispatchables_check_origin_and_block() {
+		new_test_ext().execute_with(|| {
+			run_to_block(2);
+			let call = Box::new(log_call(42).into());
+			assert_noop!(
+				Scheduler::schedule(Origin::signed(1), 4, None, 127, call.clone()),
+				"Invalid origin",
+			);
+			assert_noop!(Scheduler::schedule(Origin::ROOT, 2, None, 127, call.clone()), "schedule in the past");
+			assert_ok!(Scheduler::schedule_named(Origin::ROOT, b"1".to_vec(), 4, None, 127, call.clone()));
+			assert_noop!(
+				Scheduler::schedule_named(Origin::ROOT, b"1".to_vec(), 5, None, 127, call.clone()),
+				"id already in use",
+			);
+			assert_ok!(Scheduler::schedule(Origin::ROOT, 4, None, 127, call));
+			assert_ok!(Scheduler::cancel(Origin::ROOT, 4, 1));
+			assert_noop!(Scheduler::cancel(Origin::ROOT, 4, 1), "not found");
+			assert_ok!(Scheduler::cancel_named(Origin::ROOT, b"1".to_vec()));
+			run_to_block(10);
+			assert!(logger::log().is_empty());
+		});
+	}
+
+	#[test]
+	fn calls_scheduled_by_hash_are_looked_up_when_due() {
+		new_test_ext().execute_with(|| {
+			let hash = TestPreimages::note(&log_call(42));
+			assert_ok!(Scheduler::schedule(Origin::ROOT, 4, None, 127, Box::new(MaybeHashed::Hash(hash))));
+			assert_eq!(TestPreimages::requests(&hash), 1);
+			run_to_block(3);
+			assert!(logger::log().is_empty());
+			run_to_block(4);
+			assert_eq!(logger::log(), vec![42u32]);
+			assert_eq!(TestPreimages::requests(&hash), 0);
+		});
+	}
+
+	#[test]
+	fn calls_without_preimage_are_skipped_until_noted() {
+		new_test_ext().execute_with(|| {
+			// at #4, every 3 blocks, 3 times.
+			let hash = BlakeTwo256::hash_of(&log_call(42));
+			let call = Box::new(MaybeHashed::Hash(hash));
+			assert_ok!(Scheduler::schedule(Origin::ROOT, 4, Some((3, 3)), 127, call));
+			run_to_block(4);
+			assert!(logger::log().is_empty());
+			assert_eq!(TestPreimages::requests(&hash), 1);
+
+			TestPreimages::note(&log_call(42));
+			run_to_block(100);
+			assert_eq!(logger::log(), vec![42u32, 42u32]);
+			assert_eq!(TestPreimages::requests(&hash), 0);
+		});
+	}
+
+	#[test]
+	fn canceling_a_call_scheduled_by_hash_drops_its_request() {
+		new_test_ext().execute_with(|| {
+			let hash = TestPreimages::note(&log_call(42));
+			let call = Box::new(MaybeHashed::Hash(hash));
+			assert_ok!(Scheduler::schedule_named(Origin::ROOT, b"1".to_vec(), 4, None, 127, call));
+			assert_eq!(TestPreimages::requests(&hash), 1);
+			assert_ok!(Scheduler::cancel_named(Origin::ROOT, b"1".to_vec()));
+			assert_eq!(TestPreimages::requests(&hash), 0);
+			run_to_block(100);
+			assert!(logger::log().is_empty());
+		});
+	}
+}
@@ -123,7 +123,7 @@ use rstd::{prelude::*, marker::PhantomData, ops::{Sub, Rem}};
 use codec::Decode;
 use sr_primitives::{KeyTypeId, Perbill, RuntimeAppPublic, BoundToRuntimeAppPublic};
 use sr_primitives::weights::SimpleDispatchInfo;
-use sr_primitives::traits::{Convert, Zero, Member, OpaqueKeys};
+use sr_primitives::traits::{Convert, Zero, Member, OpaqueKeys, Saturating};
 use sr_staking_primitives::SessionIndex;
 use support::{
 	dispatch::Result, ConsensusEngineId, decl_module, decl_event,
@@ -165,6 +165,39 @@ impl<
 	}
 }
 
+/// Something that can estimate at which block the next session rotation will happen. This should
+/// be the same logical unit that dictates `ShouldEndSession` to the session module.
+pub trait EstimateNextSessionRotation<BlockNumber> {
+	/// Return the block number at which the next session rotation is estimated to happen.
+	///
+	/// `None` should be returned if the estimation fails to come to an answer.
+	fn estimate_next_session_rotation(now: BlockNumber) -> Option<BlockNumber>;
+}
+
+impl<BlockNumber> EstimateNextSessionRotation<BlockNumber> for () {
+	fn estimate_next_session_rotation(_: BlockNumber) -> Option<BlockNumber> {
+		None
+	}
+}
+
+impl<
+	BlockNumber: Rem<Output=BlockNumber> + Sub<Output=BlockNumber> + Zero + PartialOrd + Saturating
+		+ Clone,
+	Period: Get<BlockNumber>,
+	Offset: Get<BlockNumber>,
+> EstimateNextSessionRotation<BlockNumber> for PeriodicSessions<Period, Offset> {
+	fn estimate_next_session_rotation(now: BlockNumber) -> Option<BlockNumber> {
+		let offset = Offset::get();
+		let period = Period::get();
+		Some(if now < offset {
+			offset
+		} else {
+			let block_after_last_session = (now.clone() - offset) % period.clone();
+			now.saturating_add(period.saturating_sub(block_after_last_session))
+		})
+	}
+}
+
 /// An event handler for when the session is ending.
 /// TODO [slashing] consider renaming to OnSessionStarting
 pub trait OnSessionEnding<ValidatorId> {
@@ -465,7 +498,7 @@ decl_module! {
 	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
 		/// Used as first key for `NextKeys` and `KeyOwner` to put all the data into the same branch
 		/// of the trie.
-		const DEDUP_KEY_PREFIX: &[u8] = DEDUP_KEY_PREFIX;
+		const DEDUP_KEY_PREFIX: &'static [u8] = DEDUP_KEY_PREFIX;
 
 		fn deposit_event() = default;
 
@@ -665,7 +698,8 @@ impl<T: Trait> Module<T> {
 		}
 	}
 
-	fn load_keys(v: &T::ValidatorId) -> Option<T::Keys> {
+	/// Get the next session keys of a validator, if it has set any.
+	pub fn load_keys(v: &T::ValidatorId) -> Option<T::Keys> {
 		<NextKeys<T>>::get(DEDUP_KEY_PREFIX, v)
 	}
 
@@ -982,6 +1016,11 @@ mod tests {
 		}
 
 		assert!(P::should_end_session(13));
+
+		assert_eq!(P::estimate_next_session_rotation(0), Some(3));
+		assert_eq!(P::estimate_next_session_rotation(3), Some(13));
+		assert_eq!(P::estimate_next_session_rotation(4), Some(13));
+		assert_eq!(P::estimate_next_session_rotation(12), Some(13));
 	}
 
 	#[test]
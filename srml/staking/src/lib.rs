@@ -108,6 +108,12 @@
 //! determined, a value is deducted from the balance of the validator and all the nominators who
 //! voted for this validator (values are deducted from the _stash_ account of the slashed entity).
 //!
+//! A stash is only ever slashed for the largest offence within each of its slashing spans, and
+//! nominators are slashed in proportion to the exposure they had at the time of the offence.
+//! Slashes are deferred by `SlashDeferDuration` eras after being computed, during which the
+//! `SlashCancelOrigin` may cancel them via
+//! [`cancel_deferred_slash`](enum.Call.html#variant.cancel_deferred_slash).
+//!
 //! Similar to slashing, rewards are also shared among a validator and its associated nominators.
 //! Yet, the reward funds are not always transferred to the stash account and can be configured.
 //! See [Reward Calculation](#reward-calculation) for more details.
@@ -173,6 +179,12 @@
 //! defined staking rate. The full specification can be found
 //! [here](https://research.web3.foundation/en/latest/polkadot/Token%20Economics/#inflation-model).
 //!
+//! The reward of an era is not paid out when the era ends. Instead, the exposures, validator
+//! preferences and reward points of the era are stored, and anyone can call
+//! [`payout_stakers`](./enum.Call.html#variant.payout_stakers) to pay a validator and its
+//! nominators for that era. Each validator can be paid out only once per era, and only for the last
+//! [`HistoryDepth`](./trait.Trait.html#associatedtype.HistoryDepth) eras.
+//!
 //! Total reward is split among validators and their nominators depending on the number of points
 //! they received during the era. Points are added to a validator using
 //! [`reward_by_ids`](./enum.Call.html#variant.reward_by_ids) or
@@ -231,6 +243,17 @@
 //! values until the total difference among votes of a particular nominator are less than a
 //! threshold.
 //!
+//! To keep this computation out of the block, the election can be run off-chain. Shortly before
+//! the last session of an era ends (see `ElectionLookahead`), an election window opens and a
+//! snapshot of the validators and nominators is taken. Validators then compute the election on
+//! that snapshot in their offchain worker and submit it through
+//! [`submit_election_solution_unsigned`](./enum.Call.html#variant.submit_election_solution_unsigned).
+//! The chain only checks and scores the solutions, and keeps the best one. A solution must be
+//! signed with the `KeyType` session key of one of the current validators, and validators retry
+//! their submission every few blocks until a solution is queued. While the window is open, calls
+//! that change stakes or nominations are rejected. If no valid solution was submitted by the end
+//! of the era, the election is run on-chain.
+//!
 //! ## GenesisConfig
 //!
 //! The Staking module depends on the [`GenesisConfig`](./struct.GenesisConfig.html).
@@ -249,26 +272,35 @@ mod mock;
 #[cfg(test)]
 mod tests;
 
+mod slashing;
+mod offchain_election;
+
 pub mod inflation;
 
 use rstd::{prelude::*, result};
-use codec::{HasCompact, Encode, Decode};
+use codec::{HasCompact, Encode, Decode, Input};
 use support::{
-	decl_module, decl_event, decl_storage, ensure,
+	decl_module, decl_event, decl_storage, ensure, debug, Parameter, metadata::TypeInfo,
+	dispatch::Result as DispatchResult,
 	traits::{
 		Currency, OnFreeBalanceZero, OnDilution, LockIdentifier, LockableCurrency,
-		WithdrawReasons, OnUnbalanced, Imbalance, Get, Time
+		WithdrawReasons, OnUnbalanced, Imbalance, Get, Time, staking::StakingInterface,
 	}
 };
-use session::{historical::OnSessionEnding, SelectInitialValidators};
+use session::{historical::OnSessionEnding, SelectInitialValidators, EstimateNextSessionRotation};
 use sr_primitives::{
 	Perbill,
 	RuntimeDebug,
+	RuntimeAppPublic,
 	curve::PiecewiseLinear,
-	weights::SimpleDispatchInfo,
+	weights::{Weight, SimpleDispatchInfo},
 	traits::{
 		Convert, Zero, One, StaticLookup, CheckedSub, Saturating, Bounded, SaturatedConversion,
-	}
+		SimpleArithmetic, EnsureOrigin, Member, OpaqueKeys,
+	},
+	transaction_validity::{
+		TransactionValidity, ValidTransaction, InvalidTransaction, TransactionPriority,
+	},
 };
 use sr_staking_primitives::{
 	SessionIndex,
@@ -276,14 +308,21 @@ use sr_staking_primitives::{
 };
 #[cfg(feature = "std")]
 use sr_primitives::{Serialize, Deserialize};
-use system::{ensure_signed, ensure_root};
+use system::{ensure_signed, ensure_root, ensure_none, offchain::SubmitUnsignedTransaction};
 
-use phragmen::{elect, equalize, build_support_map, ExtendedBalance, PhragmenStakedAssignment};
+use phragmen::{
+	elect, equalize, build_support_map, evaluate_support, is_score_better, ExtendedBalance,
+	PhragmenAssignment, PhragmenStakedAssignment, PhragmenScore,
+};
+
+pub use offchain_election::{CompactAssignments, ValidatorIndex, NominatorIndex};
 
 const DEFAULT_MINIMUM_VALIDATOR_COUNT: u32 = 4;
 const MAX_NOMINATIONS: usize = 16;
 const MAX_UNLOCKING_CHUNKS: usize = 32;
 const STAKING_ID: LockIdentifier = *b"staking ";
+/// The weight of migrating the ledger of one stash.
+const LEDGER_MIGRATION_WEIGHT: Weight = 10_000;
 
 /// Counter for the number of eras that have passed.
 pub type EraIndex = u32;
@@ -292,7 +331,7 @@ pub type EraIndex = u32;
 pub type Points = u32;
 
 /// Reward points of an era. Used to split era total payout between validators.
-#[derive(Encode, Decode, Default)]
+#[derive(Encode, Decode, Default, TypeInfo)]
 pub struct EraPoints {
 	/// Total number of points. Equals the sum of reward points for each validator.
 	total: Points,
@@ -313,6 +352,26 @@ impl EraPoints {
 	}
 }
 
+/// Reward points of a past era, keyed by validator stash. Used to split the era payout
+/// between the validators that were elected in that era.
+#[derive(PartialEq, Encode, Decode, Default, RuntimeDebug, TypeInfo)]
+pub struct EraRewardPoints<AccountId> {
+	/// Total number of points. Equals the sum of reward points for each validator.
+	total: Points,
+	/// The reward points earned by a given validator.
+	individual: Vec<(AccountId, Points)>,
+}
+
+impl<AccountId: PartialEq> EraRewardPoints<AccountId> {
+	/// The reward points earned by the given validator, if any.
+	fn points_of(&self, who: &AccountId) -> Points {
+		self.individual.iter()
+			.find(|(v, _)| v == who)
+			.map(|(_, p)| *p)
+			.unwrap_or(0)
+	}
+}
+
 /// Indicates the initial status of the staker.
 #[derive(RuntimeDebug)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
@@ -326,7 +385,7 @@ pub enum StakerStatus<AccountId> {
 }
 
 /// A destination account for payment.
-#[derive(PartialEq, Eq, Copy, Clone, Encode, Decode, RuntimeDebug)]
+#[derive(PartialEq, Eq, Copy, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
 pub enum RewardDestination {
 	/// Pay into the stash account, increasing the amount at stake accordingly.
 	Staked,
@@ -343,7 +402,7 @@ impl Default for RewardDestination {
 }
 
 /// Preference of what happens on a slash event.
-#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
 pub struct ValidatorPrefs<Balance: HasCompact> {
 	/// Reward that validator takes up-front; only the rest is split between themselves and
 	/// nominators.
@@ -360,7 +419,7 @@ impl<B: Default + HasCompact + Copy> Default for ValidatorPrefs<B> {
 }
 
 /// Just a Balance/BlockNumber tuple to encode when a chunk of funds will be unlocked.
-#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
 pub struct UnlockChunk<Balance: HasCompact> {
 	/// Amount of funds to be unlocked.
 	#[codec(compact)]
@@ -371,7 +430,7 @@ pub struct UnlockChunk<Balance: HasCompact> {
 }
 
 /// The ledger of a (bonded) stash.
-#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+#[derive(PartialEq, Eq, Clone, Encode, RuntimeDebug, TypeInfo)]
 pub struct StakingLedger<AccountId, Balance: HasCompact> {
 	/// The stash account whose balance is actually locked and at stake.
 	pub stash: AccountId,
@@ -386,6 +445,26 @@ pub struct StakingLedger<AccountId, Balance: HasCompact> {
 	/// Any balance that is becoming free, which may eventually be transferred out
 	/// of the stash (assuming it doesn't get slashed first).
 	pub unlocking: Vec<UnlockChunk<Balance>>,
+	/// The sorted list of eras for which the rewards of this stash have already been paid out
+	/// through `payout_stakers`. Eras older than the history depth are pruned on claim.
+	pub claimed_rewards: Vec<EraIndex>,
+}
+
+// Ledgers stored before `claimed_rewards` was added end after `unlocking`. They are decoded with
+// no claimed rewards, and stored in the current layout whenever they are next updated.
+impl<AccountId: Decode, Balance: HasCompact> Decode for StakingLedger<AccountId, Balance> {
+	fn decode<I: Input>(input: &mut I) -> result::Result<Self, codec::Error> {
+		let stash = AccountId::decode(input)?;
+		let total = <Balance as HasCompact>::Type::decode(input)?.into();
+		let active = <Balance as HasCompact>::Type::decode(input)?.into();
+		let unlocking = Vec::<UnlockChunk<Balance>>::decode(input)?;
+		let claimed_rewards = if input.remaining_len()? == Some(0) {
+			Vec::new()
+		} else {
+			Vec::<EraIndex>::decode(input)?
+		};
+		Ok(StakingLedger { stash, total, active, unlocking, claimed_rewards })
+	}
 }
 
 impl<
@@ -404,12 +483,69 @@ impl<
 				false
 			})
 			.collect();
-		Self { total, active: self.active, stash: self.stash, unlocking }
+		Self {
+			total,
+			active: self.active,
+			stash: self.stash,
+			unlocking,
+			claimed_rewards: self.claimed_rewards,
+		}
+	}
+}
+
+impl<AccountId, Balance> StakingLedger<AccountId, Balance> where
+	Balance: SimpleArithmetic + Saturating + Copy,
+{
+	/// Slash the validator for a given amount of balance. This can grow the value
+	/// of the slash in the case that the validator has less than `minimum_balance`
+	/// active funds. Returns the amount of funds actually slashed.
+	///
+	/// Slashes from `active` funds first, and then `unlocking`, starting with the
+	/// chunks that are closest to unlocking.
+	fn slash(&mut self, mut value: Balance, minimum_balance: Balance) -> Balance {
+		let pre_total = self.total;
+		let total = &mut self.total;
+		let active = &mut self.active;
+
+		let slash_out_of = |
+			total_remaining: &mut Balance,
+			target: &mut Balance,
+			value: &mut Balance,
+		| {
+			let mut slash_from_target = (*value).min(*target);
+
+			if !slash_from_target.is_zero() {
+				*target -= slash_from_target;
+
+				// don't leave a dust balance in the staking system.
+				if *target <= minimum_balance {
+					slash_from_target += rstd::mem::replace(target, Zero::zero());
+				}
+
+				*total_remaining = total_remaining.saturating_sub(slash_from_target);
+				*value = value.saturating_sub(slash_from_target);
+			}
+		};
+
+		slash_out_of(total, active, &mut value);
+
+		let i = self.unlocking.iter_mut()
+			.map(|chunk| {
+				slash_out_of(total, &mut chunk.value, &mut value);
+				chunk.value
+			})
+			.take_while(|value| value.is_zero()) // take all fully-consumed chunks out.
+			.count();
+
+		// kill all drained chunks.
+		let _ = self.unlocking.drain(..i);
+
+		pre_total.saturating_sub(*total)
 	}
 }
 
 /// The amount of exposure (to slashing) than an individual nominator has.
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Encode, Decode, RuntimeDebug)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
 pub struct IndividualExposure<AccountId, Balance: HasCompact> {
 	/// The stash account of the nominator in question.
 	who: AccountId,
@@ -419,7 +555,7 @@ pub struct IndividualExposure<AccountId, Balance: HasCompact> {
 }
 
 /// A snapshot of the stake backing a single validator in the system.
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Encode, Decode, Default, RuntimeDebug)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Encode, Decode, Default, RuntimeDebug, TypeInfo)]
 pub struct Exposure<AccountId, Balance: HasCompact> {
 	/// The total balance backing this validator.
 	#[codec(compact)]
@@ -431,12 +567,20 @@ pub struct Exposure<AccountId, Balance: HasCompact> {
 	pub others: Vec<IndividualExposure<AccountId, Balance>>,
 }
 
-/// A slashing event occurred, slashing a validator for a given amount of balance.
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Encode, Decode, Default, RuntimeDebug)]
-pub struct SlashJournalEntry<AccountId, Balance: HasCompact> {
-	who: AccountId,
-	amount: Balance,
-	own_slash: Balance, // the amount of `who`'s own exposure that was slashed
+/// A pending slash record. The value of the slash has been computed but not applied yet,
+/// rather deferred for several eras.
+#[derive(Encode, Decode, Default, RuntimeDebug, TypeInfo)]
+pub struct UnappliedSlash<AccountId, Balance: HasCompact> {
+	/// The stash ID of the offending validator.
+	validator: AccountId,
+	/// The validator's own slash.
+	own: Balance,
+	/// All other slashed stakers and amounts.
+	others: Vec<(AccountId, Balance)>,
+	/// Reporters of the offence; bounty payout recipients.
+	reporters: Vec<AccountId>,
+	/// The amount of payout.
+	payout: Balance,
 }
 
 pub type BalanceOf<T> =
@@ -461,6 +605,8 @@ pub trait SessionInterface<AccountId>: system::Trait {
 	fn validators() -> Vec<AccountId>;
 	/// Prune historical session tries up to but not including the given index.
 	fn prune_historical_up_to(up_to: SessionIndex);
+	/// Get the session key of type `K` of a validator, if it has set one.
+	fn session_key<K: RuntimeAppPublic + Decode>(validator: &AccountId) -> Option<K>;
 }
 
 impl<T: Trait> SessionInterface<<T as system::Trait>::AccountId> for T where
@@ -485,6 +631,10 @@ impl<T: Trait> SessionInterface<<T as system::Trait>::AccountId> for T where
 	fn prune_historical_up_to(up_to: SessionIndex) {
 		<session::historical::Module<T>>::prune_up_to(up_to);
 	}
+
+	fn session_key<K: RuntimeAppPublic + Decode>(validator: &<T as system::Trait>::AccountId) -> Option<K> {
+		<session::Module<T>>::load_keys(validator).and_then(|keys| keys.get(K::ID))
+	}
 }
 
 pub trait Trait: system::Trait {
@@ -519,15 +669,48 @@ pub trait Trait: system::Trait {
 	/// Number of eras that staked funds must remain bonded for.
 	type BondingDuration: Get<EraIndex>;
 
+	/// Number of eras that slashes are deferred by, after computation. This
+	/// should be less than the bonding duration. Set to 0 if slashes should be
+	/// applied immediately, without opportunity for intervention.
+	type SlashDeferDuration: Get<EraIndex>;
+
+	/// The origin which can cancel a deferred slash. Root can always do this.
+	type SlashCancelOrigin: EnsureOrigin<Self::Origin>;
+
+	/// Number of past eras for which exposures, reward points and payouts are kept, and
+	/// hence for which rewards can still be claimed via `payout_stakers`.
+	type HistoryDepth: Get<EraIndex>;
+
+	/// The maximum number of nominators rewarded for each validator. Only the nominators with the
+	/// biggest stake behind a validator are rewarded, which bounds the cost of `payout_stakers`.
+	type MaxNominatorRewardedPerValidator: Get<u32>;
+
 	/// Interface for interacting with a session module.
 	type SessionInterface: self::SessionInterface<Self::AccountId>;
 
 	/// The NPoS reward curve to use.
 	type RewardCurve: Get<&'static PiecewiseLinear<'static>>;
+
+	/// Something that can estimate the block at which the next session will start.
+	type NextNewSession: EstimateNextSessionRotation<Self::BlockNumber>;
+
+	/// How many blocks before the end of the era the election window opens, during which
+	/// validators can submit an election solution computed off-chain. Zero disables the
+	/// off-chain election, in which case it is always run on-chain.
+	type ElectionLookahead: Get<Self::BlockNumber>;
+
+	/// The overarching call type.
+	type Call: From<Call<Self>>;
+
+	/// A transaction submitter, used by validators to submit election solutions.
+	type SubmitTransaction: SubmitUnsignedTransaction<Self, <Self as Trait>::Call>;
+
+	/// The session key type validators sign their election solutions with.
+	type KeyType: Member + Parameter + RuntimeAppPublic;
 }
 
 /// Mode of era-forcing.
-#[derive(Copy, Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+#[derive(Copy, Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub enum Forcing {
 	/// Not forcing anything - just let whatever happen.
@@ -544,6 +727,54 @@ impl Default for Forcing {
 	fn default() -> Self { Forcing::NotForcing }
 }
 
+/// Status of the election window of the current era.
+#[derive(Copy, Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub enum ElectionStatus<BlockNumber> {
+	/// No solution can be submitted.
+	Closed,
+	/// Solutions can be submitted. Contains the block at which the window opened.
+	Open(BlockNumber),
+}
+
+impl<BlockNumber> ElectionStatus<BlockNumber> {
+	/// Whether the election window is open.
+	pub fn is_open(&self) -> bool {
+		match self {
+			ElectionStatus::Open(_) => true,
+			ElectionStatus::Closed => false,
+		}
+	}
+
+	/// Whether the election window is closed.
+	pub fn is_closed(&self) -> bool {
+		!self.is_open()
+	}
+}
+
+impl<BlockNumber> Default for ElectionStatus<BlockNumber> {
+	fn default() -> Self { ElectionStatus::Closed }
+}
+
+/// The way an election result was computed.
+#[derive(Copy, Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub enum ElectionCompute {
+	/// The election was run on-chain, at the end of the era.
+	OnChain,
+	/// The election was run off-chain and submitted as an unsigned transaction.
+	Unsigned,
+}
+
+/// The result of an election, to be enacted at the end of the era.
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct ElectionResult<AccountId> {
+	/// The elected validator stashes.
+	elected_stashes: Vec<AccountId>,
+	/// The ratio of their stake each nominator assigns to each of the elected stashes.
+	assignments: Vec<(AccountId, Vec<PhragmenAssignment<AccountId>>)>,
+	/// How the result was computed.
+	compute: ElectionCompute,
+}
+
 decl_storage! {
 	trait Store for Module<T: Trait> as Staking {
 
@@ -609,12 +840,78 @@ decl_storage! {
 		/// The rest of the slashed value is handled by the `Slash`.
 		pub SlashRewardFraction get(fn slash_reward_fraction) config(): Perbill;
 
+		/// Exposure of each validator elected in a past era, as it stood at the end of that era,
+		/// with only the `MaxNominatorRewardedPerValidator` biggest nominators, which are the ones
+		/// rewarded.
+		///
+		/// Only kept for the last `HistoryDepth` eras.
+		pub ErasStakers get(fn eras_stakers):
+			double_map EraIndex, twox_128(T::AccountId) => Exposure<T::AccountId, BalanceOf<T>>;
+
+		/// Preferences of each validator elected in a past era, as they stood at the end of
+		/// that era.
+		///
+		/// Only kept for the last `HistoryDepth` eras.
+		pub ErasValidatorPrefs get(fn eras_validator_prefs):
+			double_map EraIndex, twox_128(T::AccountId) => ValidatorPrefs<BalanceOf<T>>;
+
+		/// The total reward to be paid out to the stakers of a past era. Not set for the
+		/// current era, nor for eras older than `HistoryDepth`.
+		pub ErasValidatorReward get(fn eras_validator_reward): map EraIndex => Option<BalanceOf<T>>;
+
+		/// Reward points earned by the validators of a past era.
+		///
+		/// Only kept for the last `HistoryDepth` eras.
+		pub ErasRewardPoints get(fn eras_reward_points): map EraIndex => EraRewardPoints<T::AccountId>;
+
+		/// All unapplied slashes that are queued for later.
+		pub UnappliedSlashes: map EraIndex => Vec<UnappliedSlash<T::AccountId, BalanceOf<T>>>;
+
+		/// The earliest era for which we have a pending, unapplied slash.
+		EarliestUnappliedSlash: Option<EraIndex>;
+
 		/// A mapping from still-bonded eras to the first session index of that era.
 		BondedEras: Vec<(EraIndex, SessionIndex)>;
 
-		/// All slashes that have occurred in a given era.
-		EraSlashJournal get(fn era_slash_journal):
-			map EraIndex => Vec<SlashJournalEntry<T::AccountId, BalanceOf<T>>>;
+		/// All slashing events on validators, mapped by era to the highest slash proportion
+		/// and slash value of the era.
+		ValidatorSlashInEra:
+			double_map EraIndex, twox_128(T::AccountId) => Option<(Perbill, BalanceOf<T>)>;
+
+		/// All slashing events on nominators, mapped by era to the highest slash value of the era.
+		NominatorSlashInEra:
+			double_map EraIndex, twox_128(T::AccountId) => Option<BalanceOf<T>>;
+
+		/// Slashing spans for stash accounts.
+		SlashingSpans: map T::AccountId => Option<slashing::SlashingSpans>;
+
+		/// Records information about the maximum slash of a stash within a slashing span,
+		/// as well as how much reward has been paid out.
+		SpanSlash: map (T::AccountId, slashing::SpanIndex) => slashing::SpanRecord<BalanceOf<T>>;
+
+		/// Status of the election window of the current era.
+		pub EraElectionStatus get(fn era_election_status): ElectionStatus<T::BlockNumber>;
+
+		/// True if the current session is the last one of the era, unless the era is forced.
+		pub IsCurrentSessionFinal get(fn is_current_session_final): bool = false;
+
+		/// The validator stashes that could be elected, as they stood when the election window
+		/// opened. Only set while the window is open.
+		pub SnapshotValidators get(fn snapshot_validators): Option<Vec<T::AccountId>>;
+
+		/// The nominator stashes, as they stood when the election window opened. Only set while
+		/// the window is open.
+		pub SnapshotNominators get(fn snapshot_nominators): Option<Vec<T::AccountId>>;
+
+		/// The best election solution submitted during the current window, enacted at the end
+		/// of the era instead of running the election on-chain.
+		pub QueuedElected get(fn queued_elected): Option<ElectionResult<T::AccountId>>;
+
+		/// The score of `QueuedElected`.
+		pub QueuedScore get(fn queued_score): Option<PhragmenScore>;
+
+		/// True if the ledgers of validators and nominators are stored with `claimed_rewards`.
+		LedgersMigrated build(|_| true): bool;
 	}
 	add_extra_genesis {
 		config(stakers):
@@ -652,13 +949,20 @@ decl_storage! {
 
 decl_event!(
 	pub enum Event<T> where Balance = BalanceOf<T>, <T as system::Trait>::AccountId {
-		/// All validators have been rewarded by the given balance.
-		Reward(Balance),
+		/// The staker payout of an era has been computed and can now be claimed through
+		/// `payout_stakers`.
+		EraPayout(EraIndex, Balance),
+		/// A staker has been rewarded by the given balance.
+		Reward(AccountId, Balance),
 		/// One validator (and its nominators) has been slashed by the given amount.
 		Slash(AccountId, Balance),
 		/// An old slashing report from a prior era was discarded because it could
 		/// not be processed.
 		OldSlashingReportDiscarded(SessionIndex),
+		/// A new validator set has been elected, in the given way.
+		StakingElection(ElectionCompute),
+		/// A better election solution has been stored, to be enacted at the end of the era.
+		SolutionStored(ElectionCompute),
 	}
 );
 
@@ -670,8 +974,55 @@ decl_module! {
 		/// Number of eras that staked funds must remain bonded for.
 		const BondingDuration: EraIndex = T::BondingDuration::get();
 
+		/// Number of eras that slashes are deferred by, after computation.
+		const SlashDeferDuration: EraIndex = T::SlashDeferDuration::get();
+
+		/// Number of past eras for which rewards can still be claimed.
+		const HistoryDepth: EraIndex = T::HistoryDepth::get();
+
+		/// The maximum number of nominators rewarded for each validator.
+		const MaxNominatorRewardedPerValidator: u32 = T::MaxNominatorRewardedPerValidator::get();
+
+		/// Number of blocks before the end of the era during which the election window is open.
+		const ElectionLookahead: T::BlockNumber = T::ElectionLookahead::get();
+
 		fn deposit_event() = default;
 
+		fn on_runtime_upgrade() -> Weight {
+			Self::migrate_ledgers()
+		}
+
+		fn on_initialize(now: T::BlockNumber) {
+			if Self::era_election_status().is_closed()
+				&& Self::is_current_session_final()
+				&& Self::force_era() != Forcing::ForceNone
+			{
+				let remaining = T::NextNewSession::estimate_next_session_rotation(now)
+					.and_then(|next_session| next_session.checked_sub(&now));
+				if let Some(remaining) = remaining {
+					// too late if the era ends in this very block.
+					if !remaining.is_zero() && remaining <= T::ElectionLookahead::get() {
+						Self::open_election_window(now);
+					}
+				}
+			}
+		}
+
+		fn offchain_worker(now: T::BlockNumber) {
+			// solutions are computed by validators when the window opens, and again every
+			// `OFFCHAIN_REPEAT` blocks for as long as none made it on chain.
+			if runtime_io::is_validator() && offchain_election::should_submit::<T>(now) {
+				debug::RuntimeLogger::init();
+				if let Err(e) = offchain_election::compute_and_submit_solution::<T>() {
+					debug::native::warn!(
+						target: "staking",
+						"Failed to submit an election solution: {:?}",
+						e,
+					);
+				}
+			}
+		}
+
 		fn on_finalize() {
 			// Set the start of the first era.
 			if !<CurrentEraStart<T>>::exists() {
@@ -701,31 +1052,8 @@ decl_module! {
 			payee: RewardDestination
 		) {
 			let stash = ensure_signed(origin)?;
-
-			if <Bonded<T>>::exists(&stash) {
-				return Err("stash already bonded")
-			}
-
 			let controller = T::Lookup::lookup(controller)?;
-
-			if <Ledger<T>>::exists(&controller) {
-				return Err("controller already paired")
-			}
-
-			// reject a bond which is considered to be _dust_.
-			if value < T::Currency::minimum_balance() {
-				return Err("can not bond with value less than minimum balance")
-			}
-
-			// You're auto-bonded forever, here. We might improve this by only bonding when
-			// you actually validate/nominate and remove once you unbond __everything__.
-			<Bonded<T>>::insert(&stash, &controller);
-			<Payee<T>>::insert(&stash, payee);
-
-			let stash_balance = T::Currency::free_balance(&stash);
-			let value = value.min(stash_balance);
-			let item = StakingLedger { stash, total: value, active: value, unlocking: vec![] };
-			Self::update_ledger(&controller, &item);
+			Self::do_bond(stash, controller, value, payee)?;
 		}
 
 		/// Add some extra amount that have appeared in the stash `free_balance` into the balance up
@@ -745,18 +1073,7 @@ decl_module! {
 		#[weight = SimpleDispatchInfo::FixedNormal(500_000)]
 		fn bond_extra(origin, #[compact] max_additional: BalanceOf<T>) {
 			let stash = ensure_signed(origin)?;
-
-			let controller = Self::bonded(&stash).ok_or("not a stash")?;
-			let mut ledger = Self::ledger(&controller).ok_or("not a controller")?;
-
-			let stash_balance = T::Currency::free_balance(&stash);
-
-			if let Some(extra) = stash_balance.checked_sub(&ledger.total) {
-				let extra = extra.min(max_additional);
-				ledger.total += extra;
-				ledger.active += extra;
-				Self::update_ledger(&controller, &ledger);
-			}
+			Self::do_bond_extra(&stash, max_additional)?;
 		}
 
 		/// Schedule a portion of the stash to be unlocked ready for transfer out after the bond
@@ -785,27 +1102,7 @@ decl_module! {
 		#[weight = SimpleDispatchInfo::FixedNormal(400_000)]
 		fn unbond(origin, #[compact] value: BalanceOf<T>) {
 			let controller = ensure_signed(origin)?;
-			let mut ledger = Self::ledger(&controller).ok_or("not a controller")?;
-			ensure!(
-				ledger.unlocking.len() < MAX_UNLOCKING_CHUNKS,
-				"can not schedule more unlock chunks"
-			);
-
-			let mut value = value.min(ledger.active);
-
-			if !value.is_zero() {
-				ledger.active -= value;
-
-				// Avoid there being a dust balance left in the staking system.
-				if ledger.active < T::Currency::minimum_balance() {
-					value += ledger.active;
-					ledger.active = Zero::zero();
-				}
-
-				let era = Self::current_era() + T::BondingDuration::get();
-				ledger.unlocking.push(UnlockChunk { value, era });
-				Self::update_ledger(&controller, &ledger);
-			}
+			Self::do_unbond(&controller, value, false)?;
 		}
 
 		/// Remove any unlocked chunks from the `unlocking` queue from our management.
@@ -827,22 +1124,7 @@ decl_module! {
 		#[weight = SimpleDispatchInfo::FixedNormal(400_000)]
 		fn withdraw_unbonded(origin) {
 			let controller = ensure_signed(origin)?;
-			let ledger = Self::ledger(&controller).ok_or("not a controller")?;
-			let ledger = ledger.consolidate_unlocked(Self::current_era());
-
-			if ledger.unlocking.is_empty() && ledger.active.is_zero() {
-				// This account must have called `unbond()` with some value that caused the active
-				// portion to fall below existential deposit + will have no more unlocking chunks
-				// left. We can now safely remove this.
-				let stash = ledger.stash;
-				// remove the lock.
-				T::Currency::remove_lock(STAKING_ID, &stash);
-				// remove all staking-related information.
-				Self::kill_stash(&stash);
-			} else {
-				// This was the consequence of a partial unbond. just update the ledger and move on.
-				Self::update_ledger(&controller, &ledger);
-			}
+			Self::do_withdraw_unbonded(&controller)?;
 		}
 
 		/// Declare the desire to validate for the origin controller.
@@ -859,6 +1141,7 @@ decl_module! {
 		#[weight = SimpleDispatchInfo::FixedNormal(750_000)]
 		fn validate(origin, prefs: ValidatorPrefs<BalanceOf<T>>) {
 			let controller = ensure_signed(origin)?;
+			Self::ensure_election_window_closed()?;
 			let ledger = Self::ledger(&controller).ok_or("not a controller")?;
 			let stash = &ledger.stash;
 			<Nominators<T>>::remove(stash);
@@ -879,16 +1162,11 @@ decl_module! {
 		#[weight = SimpleDispatchInfo::FixedNormal(750_000)]
 		fn nominate(origin, targets: Vec<<T::Lookup as StaticLookup>::Source>) {
 			let controller = ensure_signed(origin)?;
-			let ledger = Self::ledger(&controller).ok_or("not a controller")?;
-			let stash = &ledger.stash;
-			ensure!(!targets.is_empty(), "targets cannot be empty");
 			let targets = targets.into_iter()
 				.take(MAX_NOMINATIONS)
 				.map(|t| T::Lookup::lookup(t))
 				.collect::<result::Result<Vec<T::AccountId>, _>>()?;
-
-			<Validators<T>>::remove(stash);
-			<Nominators<T>>::insert(stash, targets);
+			Self::do_nominate(&controller, targets)?;
 		}
 
 		/// Declare no desire to either validate or nominate.
@@ -905,6 +1183,7 @@ decl_module! {
 		#[weight = SimpleDispatchInfo::FixedNormal(500_000)]
 		fn chill(origin) {
 			let controller = ensure_signed(origin)?;
+			Self::ensure_election_window_closed()?;
 			let ledger = Self::ledger(&controller).ok_or("not a controller")?;
 			let stash = &ledger.stash;
 			<Validators<T>>::remove(stash);
@@ -957,6 +1236,62 @@ decl_module! {
 			}
 		}
 
+		/// Pay out the rewards of a past era to a validator and all of the nominators that
+		/// backed it in that era.
+		///
+		/// Any account can call this on behalf of the stakers. The era must be over and no
+		/// older than `HistoryDepth` eras, and each validator can only be paid out once per era.
+		///
+		/// Only the `MaxNominatorRewardedPerValidator` nominators with the biggest stake behind
+		/// the validator are rewarded.
+		///
+		/// # <weight>
+		/// - Time complexity: O(N) where N is the number of rewarded nominators, at most
+		///   `MaxNominatorRewardedPerValidator`.
+		/// - Contains a limited number of reads, and one write per rewarded staker.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(1_000_000)]
+		fn payout_stakers(origin, validator_stash: T::AccountId, era: EraIndex) {
+			ensure_signed(origin)?;
+			Self::ensure_election_window_closed()?;
+			Self::do_payout_stakers(validator_stash, era)?;
+		}
+
+		/// Submit an election solution computed off-chain for the current era.
+		///
+		/// Only accepted while the election window is open, and if it is better than the
+		/// solution queued so far, if any. The solution is checked against the snapshot taken
+		/// when the window opened and is scored on-chain; the claimed `score` must match.
+		///
+		/// The dispatch origin for this call must be _None_. It is submitted by validators
+		/// through their offchain worker, and must be signed with the `KeyType` session key of
+		/// the validator at `validator_index` in the current validator set.
+		///
+		/// # <weight>
+		/// - Time complexity: O(E + W log W) where E is the number of edges of the solution
+		///   and W the number of winners.
+		/// - Reads the snapshots and the nominations of every nominator of the solution.
+		/// - Two writes.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000_000)]
+		fn submit_election_solution_unsigned(
+			origin,
+			winners: Vec<ValidatorIndex>,
+			compact: CompactAssignments,
+			score: PhragmenScore,
+			era: EraIndex,
+			validator_index: u32,
+			signature: <T::KeyType as RuntimeAppPublic>::Signature,
+		) {
+			ensure_none(origin)?;
+			Self::check_solution_claim(score, era)?;
+			Self::check_solution_signature(&winners, &compact, score, era, validator_index, &signature)?;
+			let result = Self::check_election_solution(winners, compact, score, era)?;
+			<QueuedElected<T>>::put(result);
+			QueuedScore::put(score);
+			Self::deposit_event(RawEvent::SolutionStored(ElectionCompute::Unsigned));
+		}
+
 		/// The ideal number of validators.
 		#[weight = SimpleDispatchInfo::FreeOperational]
 		fn set_validator_count(origin, #[compact] new: u32) {
@@ -975,6 +1310,8 @@ decl_module! {
 		fn force_no_eras(origin) {
 			ensure_root(origin)?;
 			ForceEra::put(Forcing::ForceNone);
+			// the era will not end, so the window must not stay open.
+			Self::close_election_window();
 		}
 
 		/// Force there to be a new era at the end of the next session. After this, it will be
@@ -1017,6 +1354,41 @@ decl_module! {
 			ensure_root(origin)?;
 			ForceEra::put(Forcing::ForceAlways);
 		}
+
+		/// Cancel enactment of a deferred slash. Can be called by either the root origin or
+		/// the `T::SlashCancelOrigin`, passing the era and indices of the slashes for that
+		/// era to kill.
+		///
+		/// # <weight>
+		/// - One storage write.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FreeOperational]
+		fn cancel_deferred_slash(origin, era: EraIndex, slash_indices: Vec<u32>) {
+			T::SlashCancelOrigin::try_origin(origin)
+				.map(|_| ())
+				.or_else(ensure_root)
+				.map_err(|_| "bad origin")?;
+
+			let mut slash_indices = slash_indices;
+			slash_indices.sort_unstable();
+			let mut unapplied = <Self as Store>::UnappliedSlashes::get(&era);
+
+			for (removed, index) in slash_indices.into_iter().enumerate() {
+				let index = index as usize;
+
+				// if `index` is not duplicate, `removed` must be <= index.
+				ensure!(removed <= index, "duplicate index");
+
+				// all prior removals were from before this index, since the
+				// list is sorted.
+				let index = index - removed;
+				ensure!(index < unapplied.len(), "slash record index out of bounds");
+
+				unapplied.remove(index);
+			}
+
+			<Self as Store>::UnappliedSlashes::insert(&era, &unapplied);
+		}
 	}
 }
 
@@ -1047,74 +1419,11 @@ impl<T: Trait> Module<T> {
 		<Ledger<T>>::insert(controller, ledger);
 	}
 
-	/// Slash a given validator by a specific amount with given (historical) exposure.
-	///
-	/// Removes the slash from the validator's balance by preference,
-	/// and reduces the nominators' balance if needed.
-	///
-	/// Returns the resulting `NegativeImbalance` to allow distributing the slashed amount and
-	/// pushes an entry onto the slash journal.
-	fn slash_validator(
-		stash: &T::AccountId,
-		slash: BalanceOf<T>,
-		exposure: &Exposure<T::AccountId, BalanceOf<T>>,
-		journal: &mut Vec<SlashJournalEntry<T::AccountId, BalanceOf<T>>>,
-	) -> NegativeImbalanceOf<T> {
-		// The amount we are actually going to slash (can't be bigger than the validator's total
-		// exposure)
-		let slash = slash.min(exposure.total);
-
-		// limit what we'll slash of the stash's own to only what's in
-		// the exposure.
-		//
-		// note: this is fine only because we limit reports of the current era.
-		// otherwise, these funds may have already been slashed due to something
-		// reported from a prior era.
-		let already_slashed_own = journal.iter()
-			.filter(|entry| &entry.who == stash)
-			.map(|entry| entry.own_slash)
-			.fold(<BalanceOf<T>>::zero(), |a, c| a.saturating_add(c));
-
-		let own_remaining = exposure.own.saturating_sub(already_slashed_own);
-
-		// The amount we'll slash from the validator's stash directly.
-		let own_slash = own_remaining.min(slash);
-		let (mut imbalance, missing) = T::Currency::slash(stash, own_slash);
-		let own_slash = own_slash - missing;
-		// The amount remaining that we can't slash from the validator,
-		// that must be taken from the nominators.
-		let rest_slash = slash - own_slash;
-		if !rest_slash.is_zero() {
-			// The total to be slashed from the nominators.
-			let total = exposure.total - exposure.own;
-			if !total.is_zero() {
-				for i in exposure.others.iter() {
-					let per_u64 = Perbill::from_rational_approximation(i.value, total);
-					// best effort - not much that can be done on fail.
-					imbalance.subsume(T::Currency::slash(&i.who, per_u64 * rest_slash).0)
-				}
-			}
-		}
-
-		journal.push(SlashJournalEntry {
-			who: stash.clone(),
-			own_slash: own_slash.clone(),
-			amount: slash,
-		});
-
-		// trigger the event
-		Self::deposit_event(
-			RawEvent::Slash(stash.clone(), slash)
-		);
-
-		imbalance
-	}
-
 	/// Actually make a payment to a staker. This uses the currency's reward function
 	/// to pay the right payee for the given staker account.
 	fn make_payout(stash: &T::AccountId, amount: BalanceOf<T>) -> Option<PositiveImbalanceOf<T>> {
 		let dest = Self::payee(stash);
-		match dest {
+		let imbalance = match dest {
 			RewardDestination::Controller => Self::bonded(stash)
 				.and_then(|controller|
 					T::Currency::deposit_into_existing(&controller, amount).ok()
@@ -1130,20 +1439,80 @@ impl<T: Trait> Module<T> {
 					Self::update_ledger(&controller, &l);
 					r
 				}),
+		};
+
+		if let Some(ref imbalance) = imbalance {
+			Self::deposit_event(RawEvent::Reward(stash.clone(), imbalance.peek()));
 		}
+		imbalance
+	}
+
+	/// Pay out the rewards of a past era to a validator and its nominators, and record the
+	/// claim in the validator's ledger.
+	fn do_payout_stakers(
+		validator_stash: T::AccountId,
+		era: EraIndex,
+	) -> result::Result<(), &'static str> {
+		let current_era = Self::current_era();
+		let oldest_claimable_era = current_era.saturating_sub(T::HistoryDepth::get());
+		ensure!(era < current_era, "era not yet finished");
+		ensure!(era >= oldest_claimable_era, "era is older than history depth");
+
+		// the payout of an era is only computed once it ends, so this can't fail for eras
+		// within the history depth.
+		let era_payout = Self::eras_validator_reward(&era).ok_or("invalid era to reward")?;
+
+		let controller = Self::bonded(&validator_stash).ok_or("not a stash")?;
+		let mut ledger = Self::ledger(&controller).ok_or("not a controller")?;
+
+		ledger.claimed_rewards.retain(|&x| x >= oldest_claimable_era);
+		match ledger.claimed_rewards.binary_search(&era) {
+			Ok(_) => return Err("reward already claimed"),
+			Err(pos) => ledger.claimed_rewards.insert(pos, era),
+		}
+
+		let era_reward_points = Self::eras_reward_points(&era);
+		let validator_points = era_reward_points.points_of(&validator_stash);
+
+		// nothing to pay out, but the claim is still recorded.
+		<Ledger<T>>::insert(&controller, &ledger);
+		if validator_points.is_zero() {
+			return Ok(())
+		}
+
+		let validator_total_payout = Perbill::from_rational_approximation(
+			validator_points,
+			era_reward_points.total,
+		) * era_payout;
+
+		let exposure = Self::eras_stakers(&era, &validator_stash);
+		let prefs = Self::eras_validator_prefs(&era, &validator_stash);
+		let imbalance = Self::reward_validator(
+			&validator_stash,
+			validator_total_payout,
+			&exposure,
+			&prefs,
+		);
+
+		T::Reward::on_unbalanced(imbalance);
+		Ok(())
 	}
 
 	/// Reward a given validator by a specific amount. Add the reward to the validator's, and its
 	/// nominators' balance, pro-rata based on their exposure, after having removed the validator's
 	/// pre-payout cut.
-	fn reward_validator(stash: &T::AccountId, reward: BalanceOf<T>) -> PositiveImbalanceOf<T> {
-		let off_the_table = reward.min(Self::validators(stash).validator_payment);
+	fn reward_validator(
+		stash: &T::AccountId,
+		reward: BalanceOf<T>,
+		exposure: &Exposure<T::AccountId, BalanceOf<T>>,
+		prefs: &ValidatorPrefs<BalanceOf<T>>,
+	) -> PositiveImbalanceOf<T> {
+		let off_the_table = reward.min(prefs.validator_payment);
 		let reward = reward - off_the_table;
 		let mut imbalance = <PositiveImbalanceOf<T>>::zero();
 		let validator_cut = if reward.is_zero() {
 			Zero::zero()
 		} else {
-			let exposure = Self::stakers(stash);
 			let total = exposure.total.max(One::one());
 
 			for i in &exposure.others {
@@ -1166,18 +1535,28 @@ impl<T: Trait> Module<T> {
 		-> Option<(Vec<T::AccountId>, Vec<(T::AccountId, Exposure<T::AccountId, BalanceOf<T>>)>)>
 	{
 		let era_length = session_index.checked_sub(Self::current_era_start_session_index()).unwrap_or(0);
-		match ForceEra::get() {
-			Forcing::ForceNew => ForceEra::kill(),
-			Forcing::ForceAlways => (),
-			Forcing::NotForcing if era_length >= T::SessionsPerEra::get() => (),
-			_ => return None,
-		}
-		let validators = T::SessionInterface::validators();
-		let prior = validators.into_iter()
-			.map(|v| { let e = Self::stakers(&v); (v, e) })
-			.collect();
+		let new_era = match ForceEra::get() {
+			Forcing::ForceNew => { ForceEra::kill(); true },
+			Forcing::ForceAlways => true,
+			Forcing::NotForcing if era_length >= T::SessionsPerEra::get() => true,
+			_ => false,
+		};
+		let result = if new_era {
+			let validators = T::SessionInterface::validators();
+			let prior = validators.into_iter()
+				.map(|v| { let e = Self::stakers(&v); (v, e) })
+				.collect();
+
+			Self::new_era(session_index).map(move |new| (new, prior))
+		} else {
+			None
+		};
+
+		let next_era_length = (session_index + 1)
+			.saturating_sub(Self::current_era_start_session_index());
+		IsCurrentSessionFinal::put(next_era_length >= T::SessionsPerEra::get());
 
-		Self::new_era(session_index).map(move |new| (new, prior))
+		result
 	}
 
 	/// The era has changed - enact new staking set.
@@ -1185,14 +1564,16 @@ impl<T: Trait> Module<T> {
 	/// NOTE: This always happens immediately before a session change to ensure that new validators
 	/// get a chance to set their session keys.
 	fn new_era(start_session_index: SessionIndex) -> Option<Vec<T::AccountId>> {
-		// Payout
+		// Record the payout of the era that just ended. It is paid out lazily to the stakers
+		// through `payout_stakers`.
+		let ending_era = Self::current_era();
 		let points = CurrentEraPointsEarned::take();
 		let now = T::Time::now();
 		let previous_era_start = <CurrentEraStart<T>>::mutate(|v| {
 			rstd::mem::replace(v, now)
 		});
 		let era_duration = now - previous_era_start;
-		if !era_duration.is_zero() {
+		if !era_duration.is_zero() && !points.total.is_zero() {
 			let validators = Self::current_elected();
 
 			let validator_len: BalanceOf<T> = (validators.len() as u32).into();
@@ -1206,82 +1587,138 @@ impl<T: Trait> Module<T> {
 				era_duration.saturated_into::<u64>(),
 			);
 
-			let mut total_imbalance = <PositiveImbalanceOf<T>>::zero();
-
-			for (v, p) in validators.iter().zip(points.individual.into_iter()) {
+			// Snapshot what is needed to compute the payout of each rewarded validator.
+			let mut individual = Vec::with_capacity(validators.len());
+			for (v, p) in validators.into_iter().zip(points.individual.into_iter()) {
 				if p != 0 {
-					let reward = Perbill::from_rational_approximation(p, points.total) * total_payout;
-					total_imbalance.subsume(Self::reward_validator(v, reward));
+					let mut exposure = Self::stakers(&v);
+					exposure.others.sort_unstable_by(|a, b| b.value.cmp(&a.value));
+					exposure.others.truncate(T::MaxNominatorRewardedPerValidator::get() as usize);
+					<ErasStakers<T>>::insert(&ending_era, &v, exposure);
+					<ErasValidatorPrefs<T>>::insert(&ending_era, &v, Self::validators(&v));
+					individual.push((v, p));
 				}
 			}
+			<ErasRewardPoints<T>>::insert(
+				&ending_era,
+				EraRewardPoints { total: points.total, individual },
+			);
+			<ErasValidatorReward<T>>::insert(&ending_era, total_payout);
 
-			let total_reward = total_imbalance.peek();
-			// assert!(total_reward <= total_payout)
-
-			Self::deposit_event(RawEvent::Reward(total_reward));
-			T::Reward::on_unbalanced(total_imbalance);
-			T::OnRewardMinted::on_dilution(total_reward, total_rewarded_stake);
+			Self::deposit_event(RawEvent::EraPayout(ending_era, total_payout));
+			T::OnRewardMinted::on_dilution(total_payout, total_rewarded_stake);
 		}
 
 		// Increment current era.
 		let current_era = CurrentEra::mutate(|s| { *s += 1; *s });
 
-		// prune journal for last era.
-		<EraSlashJournal<T>>::remove(current_era - 1);
-
 		CurrentEraStartSessionIndex::mutate(|v| {
 			*v = start_session_index;
 		});
 		let bonding_duration = T::BondingDuration::get();
 
-		if current_era > bonding_duration {
-			let first_kept = current_era - bonding_duration;
-			BondedEras::mutate(|bonded| {
-				bonded.push((current_era, start_session_index));
+		BondedEras::mutate(|bonded| {
+			bonded.push((current_era, start_session_index));
+
+			if current_era > bonding_duration {
+				let first_kept = current_era - bonding_duration;
 
 				// prune out everything that's from before the first-kept index.
 				let n_to_prune = bonded.iter()
 					.take_while(|&&(era_idx, _)| era_idx < first_kept)
 					.count();
 
-				bonded.drain(..n_to_prune);
+				// kill slashing metadata.
+				for (pruned_era, _) in bonded.drain(..n_to_prune) {
+					slashing::clear_era_metadata::<T>(pruned_era);
+				}
 
 				if let Some(&(_, first_session)) = bonded.first() {
 					T::SessionInterface::prune_historical_up_to(first_session);
 				}
-			})
+			}
+		});
+
+		// Forget the rewards of eras that can no longer be claimed.
+		if let Some(old_era) = current_era.checked_sub(T::HistoryDepth::get().saturating_add(1)) {
+			Self::clear_era_information(old_era);
 		}
 
+		// Apply any slashes whose deferral period has elapsed before electing new stakers.
+		Self::apply_unapplied_slashes(current_era);
+
 		// Reassign all Stakers.
 		let (_slot_stake, maybe_new_validators) = Self::select_validators();
 
 		maybe_new_validators
 	}
 
+	/// Store the ledgers of all validators and nominators in the current layout, if that has not
+	/// been done yet. Other ledgers are decoded in their former layout until they are updated.
+	fn migrate_ledgers() -> Weight {
+		if LedgersMigrated::get() {
+			return 0
+		}
+
+		let stashes = <Validators<T>>::enumerate().map(|(stash, _)| stash)
+			.chain(<Nominators<T>>::enumerate().map(|(stash, _)| stash))
+			.collect::<Vec<_>>();
+		for stash in stashes.iter() {
+			if let Some(controller) = Self::bonded(stash) {
+				if let Some(ledger) = Self::ledger(&controller) {
+					<Ledger<T>>::insert(&controller, ledger);
+				}
+			}
+		}
+		LedgersMigrated::put(true);
+
+		(stashes.len() as Weight).saturating_mul(LEDGER_MIGRATION_WEIGHT)
+	}
+
+	/// Clear all the reward information stored for the given era.
+	fn clear_era_information(era: EraIndex) {
+		<ErasStakers<T>>::remove_prefix(&era);
+		<ErasValidatorPrefs<T>>::remove_prefix(&era);
+		<ErasValidatorReward<T>>::remove(&era);
+		<ErasRewardPoints<T>>::remove(&era);
+	}
+
+	/// Apply previously-unapplied slashes on the beginning of a new era, after a delay.
+	fn apply_unapplied_slashes(current_era: EraIndex) {
+		let slash_defer_duration = T::SlashDeferDuration::get();
+		<Self as Store>::EarliestUnappliedSlash::mutate(|earliest| {
+			if let Some(ref mut earliest) = earliest {
+				let keep_from = current_era.saturating_sub(slash_defer_duration);
+				for era in (*earliest)..keep_from {
+					let era_slashes = <Self as Store>::UnappliedSlashes::take(&era);
+					for slash in era_slashes {
+						slashing::apply_slash::<T>(slash);
+					}
+				}
+
+				*earliest = (*earliest).max(keep_from)
+			}
+		})
+	}
+
 	/// Select a new validator set from the assembled stakers and their role preferences.
 	///
+	/// The solution queued during the election window is used if there is one, otherwise the
+	/// election is run on-chain. Either way, the election window is closed.
+	///
 	/// Returns the new `SlotStake` value and a set of newly selected _stash_ IDs.
 	fn select_validators() -> (BalanceOf<T>, Option<Vec<T::AccountId>>) {
-		let maybe_phragmen_result = elect::<_, _, _, T::CurrencyToVote>(
-			Self::validator_count() as usize,
-			Self::minimum_validator_count().max(1) as usize,
-			<Validators<T>>::enumerate().map(|(who, _)| who).collect::<Vec<T::AccountId>>(),
-			<Nominators<T>>::enumerate().collect(),
-			Self::slashable_balance_of,
-			true,
-		);
-
-		if let Some(phragmen_result) = maybe_phragmen_result {
-			let elected_stashes = phragmen_result.winners.iter()
-				.map(|(s, _)| s.clone())
-				.collect::<Vec<T::AccountId>>();
-			let assignments = phragmen_result.assignments;
+		let maybe_result = <QueuedElected<T>>::take().or_else(Self::do_phragmen);
+		Self::close_election_window();
 
+		if let Some(ElectionResult { elected_stashes, assignments, compute }) = maybe_result {
 			let to_votes = |b: BalanceOf<T>|
 				<T::CurrencyToVote as Convert<BalanceOf<T>, u64>>::convert(b) as ExtendedBalance;
 			let to_balance = |e: ExtendedBalance|
 				<T::CurrencyToVote as Convert<ExtendedBalance, BalanceOf<T>>>::convert(e);
 
+			// the supports are always rebuilt from the current stakes, which might have been
+			// slashed since a queued solution was submitted.
 			let mut supports = build_support_map::<_, _, _, T::CurrencyToVote>(
 				&elected_stashes,
 				&assignments,
@@ -1289,7 +1726,8 @@ impl<T: Trait> Module<T> {
 				true,
 			);
 
-			if cfg!(feature = "equalize") {
+			// a submitted solution is enacted as it was scored.
+			if cfg!(feature = "equalize") && compute == ElectionCompute::OnChain {
 				let mut staked_assignments
 					: Vec<(T::AccountId, Vec<PhragmenStakedAssignment<T::AccountId>>)>
 					= Vec::with_capacity(assignments.len());
@@ -1349,6 +1787,8 @@ impl<T: Trait> Module<T> {
 			// Set the new validator set in sessions.
 			<CurrentElected<T>>::put(&elected_stashes);
 
+			Self::deposit_event(RawEvent::StakingElection(compute));
+
 			// In order to keep the property required by `n_session_ending`
 			// that we must return the new validator set even if it's the same as the old,
 			// as long as any underlying economic conditions have changed, we don't attempt
@@ -1365,6 +1805,301 @@ impl<T: Trait> Module<T> {
 		}
 	}
 
+	/// Run the phragmén election on-chain, on the current stakers.
+	fn do_phragmen() -> Option<ElectionResult<T::AccountId>> {
+		elect::<_, _, _, T::CurrencyToVote>(
+			Self::validator_count() as usize,
+			Self::minimum_validator_count().max(1) as usize,
+			<Validators<T>>::enumerate().map(|(who, _)| who).collect::<Vec<T::AccountId>>(),
+			<Nominators<T>>::enumerate().collect(),
+			Self::slashable_balance_of,
+			true,
+		).map(|phragmen_result| ElectionResult {
+			elected_stashes: phragmen_result.winners.into_iter().map(|(s, _)| s).collect(),
+			assignments: phragmen_result.assignments,
+			compute: ElectionCompute::OnChain,
+		})
+	}
+
+	/// Ensure that the election window is closed, i.e. that the stakes and nominations can be
+	/// changed without invalidating the snapshot.
+	fn ensure_election_window_closed() -> result::Result<(), &'static str> {
+		ensure!(
+			Self::era_election_status().is_closed(),
+			"call not allowed while election window is open"
+		);
+		Ok(())
+	}
+
+	/// Bond `value` of `stash`'s free balance with `controller` as its controller.
+	fn do_bond(
+		stash: T::AccountId,
+		controller: T::AccountId,
+		value: BalanceOf<T>,
+		payee: RewardDestination,
+	) -> result::Result<(), &'static str> {
+		if <Bonded<T>>::exists(&stash) {
+			return Err("stash already bonded")
+		}
+
+		if <Ledger<T>>::exists(&controller) {
+			return Err("controller already paired")
+		}
+
+		// reject a bond which is considered to be _dust_.
+		if value < T::Currency::minimum_balance() {
+			return Err("can not bond with value less than minimum balance")
+		}
+
+		// You're auto-bonded forever, here. We might improve this by only bonding when
+		// you actually validate/nominate and remove once you unbond __everything__.
+		<Bonded<T>>::insert(&stash, &controller);
+		<Payee<T>>::insert(&stash, payee);
+
+		let stash_balance = T::Currency::free_balance(&stash);
+		let value = value.min(stash_balance);
+		// a fresh ledger can not claim rewards for eras in which it was not bonded.
+		let current_era = Self::current_era();
+		let last_reward_era = current_era.saturating_sub(T::HistoryDepth::get());
+		let item = StakingLedger {
+			stash,
+			total: value,
+			active: value,
+			unlocking: vec![],
+			claimed_rewards: (last_reward_era..current_era).collect(),
+		};
+		Self::update_ledger(&controller, &item);
+		Ok(())
+	}
+
+	/// Add up to `max_additional` of `stash`'s free balance to its bonded funds.
+	fn do_bond_extra(stash: &T::AccountId, max_additional: BalanceOf<T>) -> result::Result<(), &'static str> {
+		Self::ensure_election_window_closed()?;
+
+		let controller = Self::bonded(stash).ok_or("not a stash")?;
+		let mut ledger = Self::ledger(&controller).ok_or("not a controller")?;
+
+		let stash_balance = T::Currency::free_balance(stash);
+
+		if let Some(extra) = stash_balance.checked_sub(&ledger.total) {
+			let extra = extra.min(max_additional);
+			ledger.total += extra;
+			ledger.active += extra;
+			Self::update_ledger(&controller, &ledger);
+		}
+		Ok(())
+	}
+
+	/// Schedule up to `value` of `controller`'s active funds for withdrawal. With `merge`, funds
+	/// unbonded during the same era are added to the chunk already scheduled for that era.
+	fn do_unbond(
+		controller: &T::AccountId,
+		value: BalanceOf<T>,
+		merge: bool,
+	) -> result::Result<(), &'static str> {
+		Self::ensure_election_window_closed()?;
+		let mut ledger = Self::ledger(controller).ok_or("not a controller")?;
+		let era = Self::current_era() + T::BondingDuration::get();
+		let merges = merge && ledger.unlocking.last().map_or(false, |chunk| chunk.era == era);
+		ensure!(
+			merges || ledger.unlocking.len() < MAX_UNLOCKING_CHUNKS,
+			"can not schedule more unlock chunks"
+		);
+
+		let mut value = value.min(ledger.active);
+
+		if !value.is_zero() {
+			ledger.active -= value;
+
+			// Avoid there being a dust balance left in the staking system.
+			if ledger.active < T::Currency::minimum_balance() {
+				value += ledger.active;
+				ledger.active = Zero::zero();
+			}
+
+			match ledger.unlocking.last_mut() {
+				Some(chunk) if merges => chunk.value += value,
+				_ => ledger.unlocking.push(UnlockChunk { value, era }),
+			}
+			Self::update_ledger(controller, &ledger);
+		}
+		Ok(())
+	}
+
+	/// Release the unlocked chunks of `controller`, removing the stash altogether once nothing
+	/// is left bonded.
+	fn do_withdraw_unbonded(controller: &T::AccountId) -> result::Result<(), &'static str> {
+		Self::ensure_election_window_closed()?;
+		let ledger = Self::ledger(controller).ok_or("not a controller")?;
+		let ledger = ledger.consolidate_unlocked(Self::current_era());
+
+		if ledger.unlocking.is_empty() && ledger.active.is_zero() {
+			// This account must have called `unbond()` with some value that caused the active
+			// portion to fall below existential deposit + will have no more unlocking chunks
+			// left. We can now safely remove this.
+			let stash = ledger.stash;
+			// remove the lock.
+			T::Currency::remove_lock(STAKING_ID, &stash);
+			// remove all staking-related information.
+			Self::kill_stash(&stash);
+		} else {
+			// This was the consequence of a partial unbond. just update the ledger and move on.
+			Self::update_ledger(controller, &ledger);
+		}
+		Ok(())
+	}
+
+	/// Declare the stash of `controller` to nominate (at most `MAX_NOMINATIONS` of) `targets`.
+	fn do_nominate(controller: &T::AccountId, targets: Vec<T::AccountId>) -> result::Result<(), &'static str> {
+		Self::ensure_election_window_closed()?;
+		let ledger = Self::ledger(controller).ok_or("not a controller")?;
+		let stash = &ledger.stash;
+		ensure!(!targets.is_empty(), "targets cannot be empty");
+		let targets = targets.into_iter().take(MAX_NOMINATIONS).collect::<Vec<_>>();
+
+		<Validators<T>>::remove(stash);
+		<Nominators<T>>::insert(stash, targets);
+		Ok(())
+	}
+
+	/// Take the snapshot of the stakers and open the election window.
+	///
+	/// The window is not opened if there are too many stakers to refer to them by index.
+	fn open_election_window(now: T::BlockNumber) {
+		let validators = <Validators<T>>::enumerate().map(|(v, _)| v).collect::<Vec<_>>();
+		let nominators = <Nominators<T>>::enumerate().map(|(n, _)| n).collect::<Vec<_>>();
+		if validators.len() > offchain_election::MAX_VALIDATORS
+			|| nominators.len() > offchain_election::MAX_NOMINATORS
+		{
+			return
+		}
+
+		<SnapshotValidators<T>>::put(validators);
+		<SnapshotNominators<T>>::put(nominators);
+		<EraElectionStatus<T>>::put(ElectionStatus::Open(now));
+	}
+
+	/// Close the election window and remove its snapshot and queued solution.
+	fn close_election_window() {
+		<SnapshotValidators<T>>::kill();
+		<SnapshotNominators<T>>::kill();
+		<QueuedElected<T>>::kill();
+		QueuedScore::kill();
+		<EraElectionStatus<T>>::put(ElectionStatus::Closed);
+	}
+
+	/// Check that a solution claiming `claimed_score` for `era` could be queued right now. Cheap.
+	fn check_solution_claim(
+		claimed_score: PhragmenScore,
+		era: EraIndex,
+	) -> result::Result<(), &'static str> {
+		ensure!(Self::era_election_status().is_open(), "election window is not open");
+		ensure!(era == Self::current_era(), "solution is for another era");
+		if let Some(queued_score) = Self::queued_score() {
+			ensure!(
+				is_score_better(claimed_score, queued_score),
+				"solution is not better than the queued one"
+			);
+		}
+		Ok(())
+	}
+
+	/// Check that a solution is signed by the `KeyType` session key of the validator at
+	/// `validator_index` in the current validator set. Cheap compared to checking the solution.
+	fn check_solution_signature(
+		winners: &[ValidatorIndex],
+		compact: &CompactAssignments,
+		score: PhragmenScore,
+		era: EraIndex,
+		validator_index: u32,
+		signature: &<T::KeyType as RuntimeAppPublic>::Signature,
+	) -> result::Result<(), &'static str> {
+		let validator = T::SessionInterface::validators()
+			.get(validator_index as usize)
+			.cloned()
+			.ok_or("submitter is not a validator")?;
+		let key = T::SessionInterface::session_key::<T::KeyType>(&validator)
+			.ok_or("submitter has no session key")?;
+		let payload = offchain_election::signing_payload(winners, compact, score, era, validator_index);
+		ensure!(key.verify(&payload, signature), "invalid solution signature");
+		Ok(())
+	}
+
+	/// Check a submitted election solution against the snapshot of the current window and score
+	/// it. Returns the election result to queue if it is valid and better than the queued one.
+	fn check_election_solution(
+		winners: Vec<ValidatorIndex>,
+		compact: CompactAssignments,
+		claimed_score: PhragmenScore,
+		era: EraIndex,
+	) -> result::Result<ElectionResult<T::AccountId>, &'static str> {
+		Self::check_solution_claim(claimed_score, era)?;
+
+		let snapshot_validators = Self::snapshot_validators().ok_or("snapshot unavailable")?;
+		let snapshot_nominators = Self::snapshot_nominators().ok_or("snapshot unavailable")?;
+
+		// the same number of winners as the on-chain election would elect.
+		let desired_winners = (Self::validator_count() as usize).min(snapshot_validators.len());
+		ensure!(winners.len() == desired_winners, "wrong number of winners");
+		ensure!(
+			winners.len() >= Self::minimum_validator_count().max(1) as usize,
+			"not enough winners"
+		);
+
+		let elected_stashes = winners.into_iter()
+			.map(|i| snapshot_validators.get(i as usize).cloned().ok_or("invalid winner index"))
+			.collect::<result::Result<Vec<T::AccountId>, _>>()?;
+		let mut sorted_winners = elected_stashes.clone();
+		sorted_winners.sort();
+		sorted_winners.dedup();
+		ensure!(sorted_winners.len() == elected_stashes.len(), "duplicate winner");
+		ensure!(
+			sorted_winners.iter().all(|w| <Validators<T>>::exists(w)),
+			"winner is no longer a validator"
+		);
+
+		let mut assignments = Vec::with_capacity(compact.votes.len());
+		let mut last_nominator_index = None;
+		for (nominator_index, distribution) in compact.votes {
+			// sorted and without duplicates, so that no stake can be counted twice.
+			ensure!(
+				last_nominator_index.map_or(true, |last| nominator_index > last),
+				"nominators are not sorted"
+			);
+			last_nominator_index = Some(nominator_index);
+
+			let nominator = snapshot_nominators.get(nominator_index as usize)
+				.cloned()
+				.ok_or("invalid nominator index")?;
+			let targets = Self::nominators(&nominator);
+
+			let mut total_parts: u64 = 0;
+			let mut assignment = Vec::with_capacity(distribution.len());
+			for (validator_index, ratio) in distribution {
+				let target = snapshot_validators.get(validator_index as usize)
+					.cloned()
+					.ok_or("invalid validator index")?;
+				ensure!(sorted_winners.binary_search(&target).is_ok(), "target is not a winner");
+				ensure!(targets.contains(&target), "target was not nominated");
+				total_parts += ratio.deconstruct() as u64;
+				assignment.push((target, ratio));
+			}
+			ensure!(total_parts <= Perbill::accuracy() as u64, "nominator stake over-assigned");
+
+			assignments.push((nominator, assignment));
+		}
+
+		let supports = build_support_map::<_, _, _, T::CurrencyToVote>(
+			&elected_stashes,
+			&assignments,
+			Self::slashable_balance_of,
+			true,
+		);
+		ensure!(evaluate_support(&supports) == claimed_score, "wrong score");
+
+		Ok(ElectionResult { elected_stashes, assignments, compute: ElectionCompute::Unsigned })
+	}
+
 	/// Remove all associated data of a stash account from the staking system.
 	///
 	/// This is called :
@@ -1377,6 +2112,8 @@ impl<T: Trait> Module<T> {
 		<Payee<T>>::remove(stash);
 		<Validators<T>>::remove(stash);
 		<Nominators<T>>::remove(stash);
+
+		slashing::clear_stash_metadata::<T>(stash);
 	}
 
 	/// Add reward points to validators using their stash account ID.
@@ -1488,12 +2225,122 @@ impl<T: Trait> Convert<T::AccountId, Option<Exposure<T::AccountId, BalanceOf<T>>
 	}
 }
 
+impl<T: Trait> StakingInterface for Module<T> {
+	type AccountId = T::AccountId;
+	type Balance = BalanceOf<T>;
+
+	fn bonding_duration() -> EraIndex {
+		T::BondingDuration::get()
+	}
+
+	fn current_era() -> EraIndex {
+		Self::current_era()
+	}
+
+	fn election_ongoing() -> bool {
+		!Self::era_election_status().is_closed()
+	}
+
+	fn active_stake(controller: &T::AccountId) -> Option<BalanceOf<T>> {
+		Self::ledger(controller).map(|l| l.active)
+	}
+
+	fn bond(stash: &T::AccountId, controller: &T::AccountId, value: BalanceOf<T>) -> DispatchResult {
+		Self::do_bond(stash.clone(), controller.clone(), value, RewardDestination::Controller)
+	}
+
+	fn bond_extra(stash: &T::AccountId, max_additional: BalanceOf<T>) -> DispatchResult {
+		Self::do_bond_extra(stash, max_additional)
+	}
+
+	fn unbond(controller: &T::AccountId, value: BalanceOf<T>) -> DispatchResult {
+		Self::do_unbond(controller, value, true)
+	}
+
+	fn withdraw_unbonded(controller: &T::AccountId) -> DispatchResult {
+		Self::do_withdraw_unbonded(controller)
+	}
+
+	fn nominate(controller: &T::AccountId, targets: Vec<T::AccountId>) -> DispatchResult {
+		Self::do_nominate(controller, targets)
+	}
+}
+
 impl<T: Trait> SelectInitialValidators<T::AccountId> for Module<T> {
 	fn select_initial_validators() -> Option<Vec<T::AccountId>> {
 		<Module<T>>::select_validators().1
 	}
 }
 
+impl<T: Trait> support::unsigned::ValidateUnsigned for Module<T> {
+	type Call = Call<T>;
+
+	fn validate_unsigned(call: &Self::Call) -> TransactionValidity {
+		if let Call::submit_election_solution_unsigned(
+			winners,
+			compact,
+			score,
+			era,
+			validator_index,
+			signature,
+		) = call {
+			// the cheap checks go first, so that only solutions of a validator that could be
+			// queued are fully checked.
+			if let Err(e) = Self::check_solution_claim(*score, *era) {
+				debug::native::debug!(
+					target: "staking",
+					"Rejected unsigned election solution: {}",
+					e,
+				);
+				return InvalidTransaction::Stale.into();
+			}
+			if let Err(e) = Self::check_solution_signature(
+				winners,
+				compact,
+				*score,
+				*era,
+				*validator_index,
+				signature,
+			) {
+				debug::native::debug!(
+					target: "staking",
+					"Rejected unsigned election solution: {}",
+					e,
+				);
+				return InvalidTransaction::BadProof.into();
+			}
+
+			// the solution is fully checked here so that invalid ones never make it into a block.
+			if let Err(e) = Self::check_election_solution(
+				winners.clone(),
+				compact.clone(),
+				*score,
+				*era,
+			) {
+				debug::native::debug!(
+					target: "staking",
+					"Rejected unsigned election solution: {}",
+					e,
+				);
+				return InvalidTransaction::Stale.into();
+			}
+
+			Ok(ValidTransaction {
+				// the higher the minimum support, the better the solution.
+				priority: score[0].saturated_into::<TransactionPriority>(),
+				requires: vec![],
+				// only the best solution of an era is kept in the pool.
+				provides: vec![("StakingOffchain", era).encode()],
+				// the solution is useless once the window is closed.
+				longevity: T::ElectionLookahead::get().saturated_into::<u64>(),
+				propagate: true,
+			})
+		} else {
+			InvalidTransaction::Call.into()
+		}
+	}
+}
+
 /// This is intended to be used with `FilterHistoricalOffences`.
 impl <T: Trait> OnOffenceHandler<T::AccountId, session::historical::IdentificationTuple<T>> for Module<T> where
 	T: session::Trait<ValidatorId = <T as system::Trait>::AccountId>,
@@ -1510,11 +2357,18 @@ impl <T: Trait> OnOffenceHandler<T::AccountId, session::historical::Identificati
 		offenders: &[OffenceDetails<T::AccountId, session::historical::IdentificationTuple<T>>],
 		slash_fraction: &[Perbill],
 	) {
-		let mut remaining_imbalance = <NegativeImbalanceOf<T>>::zero();
-		let slash_reward_fraction = SlashRewardFraction::get();
+		let reward_proportion = SlashRewardFraction::get();
 
 		let era_now = Self::current_era();
-		let mut journal = Self::era_slash_journal(era_now);
+		let window_start = era_now.saturating_sub(T::BondingDuration::get());
+		let slash_defer_duration = T::SlashDeferDuration::get();
+
+		if slash_defer_duration != 0 {
+			<Self as Store>::EarliestUnappliedSlash::mutate(|earliest| if earliest.is_none() {
+				*earliest = Some(era_now)
+			});
+		}
+
 		for (details, slash_fraction) in offenders.iter().zip(slash_fraction) {
 			let stash = &details.offender.0;
 			let exposure = &details.offender.1;
@@ -1531,46 +2385,30 @@ impl <T: Trait> OnOffenceHandler<T::AccountId, session::historical::Identificati
 				Self::ensure_new_era();
 			}
 
-			// calculate the amount to slash
-			let slash_exposure = exposure.total;
-			let amount = *slash_fraction * slash_exposure;
-			// in some cases `slash_fraction` can be just `0`,
-			// which means we are not slashing this time.
-			if amount.is_zero() {
-				continue;
-			}
-
-			// make sure to disable validator till the end of this session
-			if T::SessionInterface::disable_validator(stash).unwrap_or(false) {
-				// force a new era, to select a new validator set
-				Self::ensure_new_era();
-			}
-			// actually slash the validator
-			let slashed_amount = Self::slash_validator(stash, amount, exposure, &mut journal);
-
-			// distribute the rewards according to the slash
-			let slash_reward = slash_reward_fraction * slashed_amount.peek();
-			if !slash_reward.is_zero() && !details.reporters.is_empty() {
-				let (mut reward, rest) = slashed_amount.split(slash_reward);
-				// split the reward between reporters equally. Division cannot fail because
-				// we guarded against it in the enclosing if.
-				let per_reporter = reward.peek() / (details.reporters.len() as u32).into();
-				for reporter in &details.reporters {
-					let (reporter_reward, rest) = reward.split(per_reporter);
-					reward = rest;
-					T::Currency::resolve_creating(reporter, reporter_reward);
+			let unapplied = slashing::compute_slash::<T>(slashing::SlashParams {
+				stash,
+				slash: *slash_fraction,
+				exposure,
+				slash_era: era_now,
+				window_start,
+				now: era_now,
+				reward_proportion,
+			});
+
+			if let Some(mut unapplied) = unapplied {
+				unapplied.reporters = details.reporters.clone();
+				if slash_defer_duration == 0 {
+					// apply right away.
+					slashing::apply_slash::<T>(unapplied);
+				} else {
+					// defer to end of some `slash_defer_duration` from now.
+					<Self as Store>::UnappliedSlashes::mutate(
+						era_now,
+						move |for_later| for_later.push(unapplied),
+					);
 				}
-				// The rest goes to the treasury.
-				remaining_imbalance.subsume(reward);
-				remaining_imbalance.subsume(rest);
-			} else {
-				remaining_imbalance.subsume(slashed_amount);
 			}
 		}
-		<EraSlashJournal<T>>::insert(era_now, journal);
-
-		// Handle the rest of imbalances
-		T::Slash::on_unbalanced(remaining_imbalance);
 	}
 }
 
@@ -20,7 +20,7 @@ use std::{collections::HashSet, cell::RefCell};
 use sr_primitives::{Perbill, KeyTypeId};
 use sr_primitives::curve::PiecewiseLinear;
 use sr_primitives::traits::{IdentityLookup, Convert, OpaqueKeys, OnInitialize, SaturatedConversion};
-use sr_primitives::testing::{Header, UintAuthorityId};
+use sr_primitives::testing::{Header, UintAuthorityId, TestXt};
 use sr_staking_primitives::SessionIndex;
 use primitives::{H256, crypto::key_types};
 use runtime_io;
@@ -28,7 +28,7 @@ use support::{assert_ok, impl_outer_origin, parameter_types, StorageLinkedMap};
 use support::traits::{Currency, Get, FindAuthor};
 use crate::{
 	EraIndex, GenesisConfig, Module, Trait, StakerStatus, ValidatorPrefs, RewardDestination,
-	Nominators, inflation
+	Nominators, Call, inflation
 };
 
 /// The AccountId alias in this test module.
@@ -48,6 +48,8 @@ impl Convert<u128, u64> for CurrencyToVoteHandler {
 thread_local! {
 	static SESSION: RefCell<(Vec<AccountId>, HashSet<AccountId>)> = RefCell::new(Default::default());
 	static EXISTENTIAL_DEPOSIT: RefCell<u64> = RefCell::new(0);
+	static SLASH_DEFER_DURATION: RefCell<EraIndex> = RefCell::new(0);
+	static MAX_NOMINATOR_REWARDED_PER_VALIDATOR: RefCell<u32> = RefCell::new(64);
 }
 
 pub struct TestSessionHandler;
@@ -87,6 +89,20 @@ impl Get<u64> for ExistentialDeposit {
 	}
 }
 
+pub struct SlashDeferDuration;
+impl Get<EraIndex> for SlashDeferDuration {
+	fn get() -> EraIndex {
+		SLASH_DEFER_DURATION.with(|v| *v.borrow())
+	}
+}
+
+pub struct MaxNominatorRewardedPerValidator;
+impl Get<u32> for MaxNominatorRewardedPerValidator {
+	fn get() -> u32 {
+		MAX_NOMINATOR_REWARDED_PER_VALIDATOR.with(|v| *v.borrow())
+	}
+}
+
 impl_outer_origin!{
 	pub enum Origin for Test {}
 }
@@ -191,8 +207,12 @@ srml_staking_reward_curve::build! {
 parameter_types! {
 	pub const SessionsPerEra: SessionIndex = 3;
 	pub const BondingDuration: EraIndex = 3;
+	pub const HistoryDepth: EraIndex = 84;
 	pub const RewardCurve: &'static PiecewiseLinear<'static> = &I_NPOS;
+	pub const ElectionLookahead: BlockNumber = 1;
 }
+pub type Extrinsic = TestXt<Call<Test>, ()>;
+type SubmitTransaction = system::offchain::TransactionSubmitter<(), Call<Test>, Extrinsic>;
 impl Trait for Test {
 	type Currency = balances::Module<Self>;
 	type Time = timestamp::Module<Self>;
@@ -203,8 +223,17 @@ impl Trait for Test {
 	type Reward = ();
 	type SessionsPerEra = SessionsPerEra;
 	type BondingDuration = BondingDuration;
+	type SlashDeferDuration = SlashDeferDuration;
+	type SlashCancelOrigin = system::EnsureRoot<Self::AccountId>;
+	type HistoryDepth = HistoryDepth;
+	type MaxNominatorRewardedPerValidator = MaxNominatorRewardedPerValidator;
 	type SessionInterface = Self;
 	type RewardCurve = RewardCurve;
+	type NextNewSession = session::PeriodicSessions<Period, Offset>;
+	type ElectionLookahead = ElectionLookahead;
+	type Call = Call<Test>;
+	type SubmitTransaction = SubmitTransaction;
+	type KeyType = UintAuthorityId;
 }
 
 pub struct ExtBuilder {
@@ -216,6 +245,8 @@ pub struct ExtBuilder {
 	fair: bool,
 	num_validators: Option<u32>,
 	invulnerables: Vec<u64>,
+	slash_defer_duration: EraIndex,
+	max_nominator_rewarded_per_validator: u32,
 }
 
 impl Default for ExtBuilder {
@@ -229,6 +260,8 @@ impl Default for ExtBuilder {
 			fair: true,
 			num_validators: None,
 			invulnerables: vec![],
+			slash_defer_duration: 0,
+			max_nominator_rewarded_per_validator: 64,
 		}
 	}
 }
@@ -266,8 +299,18 @@ impl ExtBuilder {
 		self.invulnerables = invulnerables;
 		self
 	}
+	pub fn slash_defer_duration(mut self, eras: EraIndex) -> Self {
+		self.slash_defer_duration = eras;
+		self
+	}
+	pub fn max_nominator_rewarded_per_validator(mut self, max: u32) -> Self {
+		self.max_nominator_rewarded_per_validator = max;
+		self
+	}
 	pub fn set_associated_consts(&self) {
 		EXISTENTIAL_DEPOSIT.with(|v| *v.borrow_mut() = self.existential_deposit);
+		SLASH_DEFER_DURATION.with(|v| *v.borrow_mut() = self.slash_defer_duration);
+		MAX_NOMINATOR_REWARDED_PER_VALIDATOR.with(|v| *v.borrow_mut() = self.max_nominator_rewarded_per_validator);
 	}
 	pub fn build(self) -> runtime_io::TestExternalities {
 		self.set_associated_consts();
@@ -302,7 +345,6 @@ impl ExtBuilder {
 					// This allow us to have a total_payout different from 0.
 					(999, 1_000_000_000_000),
 			],
-			vesting: vec![],
 		}.assimilate_storage(&mut storage);
 
 		let stake_21 = if self.fair { 1000 } else { 2000 };
@@ -426,6 +468,17 @@ pub fn start_session(session_index: SessionIndex) {
 	assert_eq!(Session::current_index(), session_index);
 }
 
+/// Run the blocks up to `n` included, with both the session and the staking module being
+/// initialized at each block.
+pub fn run_to_block(n: BlockNumber) {
+	for b in System::block_number() + 1..=n {
+		System::set_block_number(b);
+		Timestamp::set_timestamp(b * 1000);
+		Session::on_initialize(b);
+		Staking::on_initialize(b);
+	}
+}
+
 pub fn start_era(era_index: EraIndex) {
 	start_session((era_index * 3).into());
 	assert_eq!(Staking::current_era(), era_index);
@@ -450,6 +503,17 @@ pub fn reward_all_elected() {
 	<Module<Test>>::reward_by_ids(rewards)
 }
 
+/// Pay out the rewards of the given era to all rewarded validators and their nominators.
+pub fn make_all_reward_payment(era: EraIndex) {
+	let validators = Staking::eras_reward_points(&era).individual.into_iter()
+		.map(|(v, _)| v)
+		.collect::<Vec<_>>();
+
+	for validator in validators {
+		assert_ok!(Staking::payout_stakers(Origin::signed(1337), validator, era));
+	}
+}
+
 pub fn validator_controllers() -> Vec<AccountId> {
 	Session::validators().into_iter().map(|s| Staking::bonded(&s).expect("no controller for validator")).collect()
 }
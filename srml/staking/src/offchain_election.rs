@@ -0,0 +1,282 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Helpers for the off-chain phragmén election.
+//!
+//! When the election window opens, a snapshot of the validator and nominator stash accounts is
+//! stored on chain. Validators then run the election in their offchain worker against that
+//! snapshot and submit the result as an unsigned transaction. To keep the transaction small,
+//! the solution refers to stakers by their index in the snapshot rather than by account id.
+//!
+//! The solution is never trusted: the chain rebuilds the supports from the submitted ratios,
+//! scores them itself and only keeps the best solution of the window. Submissions are signed
+//! with a session key of the submitting validator, so that only validators can make the chain
+//! check a solution.
+
+use super::{
+	Module, Trait, Call, BalanceOf, Perbill, ExtendedBalance, PhragmenStakedAssignment, EraIndex,
+	ElectionStatus, SessionInterface,
+};
+use codec::{Encode, Decode};
+use support::metadata::TypeInfo;
+use phragmen::{
+	elect, equalize, build_support_map, evaluate_support, PhragmenResult, PhragmenScore,
+	PhragmenAssignment,
+};
+use rstd::{prelude::*, convert::TryInto};
+use primitives::offchain::StorageKind;
+use sr_primitives::{
+	RuntimeDebug, RuntimeAppPublic, helpers_128bit::multiply_by_rational, traits::{Convert, Zero},
+};
+use system::offchain::SubmitUnsignedTransaction;
+
+/// Index of a validator in the validator snapshot.
+pub type ValidatorIndex = u16;
+
+/// Index of a nominator in the nominator snapshot.
+pub type NominatorIndex = u32;
+
+/// The maximum number of validators that can be part of a snapshot.
+pub(crate) const MAX_VALIDATORS: usize = ValidatorIndex::max_value() as usize;
+
+/// The maximum number of nominators that can be part of a snapshot.
+pub(crate) const MAX_NOMINATORS: usize = NominatorIndex::max_value() as usize;
+
+/// The number of blocks after which a validator submits a solution again, if none was queued.
+pub(crate) const OFFCHAIN_REPEAT: u32 = 5;
+
+/// The offchain storage key of the block at which a solution was last submitted.
+const OFFCHAIN_LAST_SUBMISSION_DB: &[u8] = b"srml/staking/election-last-submission";
+
+/// The assignments of an election solution, referring to stakers by their index in the
+/// snapshots taken when the election window opened.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug, TypeInfo)]
+pub struct CompactAssignments {
+	/// For each nominator backing at least one winner, the winners it backs and the ratio of its
+	/// stake that goes to each of them. Must be sorted by nominator index, without duplicates.
+	pub votes: Vec<(NominatorIndex, Vec<(ValidatorIndex, Perbill)>)>,
+}
+
+/// Reasons for which an election solution could not be computed or submitted off-chain.
+#[derive(RuntimeDebug)]
+pub(crate) enum OffchainElectionError {
+	/// The snapshot of the election window is not available.
+	SnapshotUnavailable,
+	/// The election did not elect enough validators.
+	ElectionFailed,
+	/// An elected validator or a nominator could not be found in the snapshot.
+	NotInSnapshot,
+	/// None of the local keys is the session key of a current validator.
+	NoSigningKey,
+	/// The solution could not be signed.
+	FailedSigning,
+	/// The transaction could not be submitted to the pool.
+	PoolSubmissionFailed,
+}
+
+/// Run the election on the current snapshot, and return the winners and the assignments in their
+/// compact form, along with the score of the solution.
+pub(crate) fn prepare_submission<T: Trait>()
+	-> Result<(Vec<ValidatorIndex>, CompactAssignments, PhragmenScore), OffchainElectionError>
+{
+	let snapshot_validators = <Module<T>>::snapshot_validators()
+		.ok_or(OffchainElectionError::SnapshotUnavailable)?;
+	let snapshot_nominators = <Module<T>>::snapshot_nominators()
+		.ok_or(OffchainElectionError::SnapshotUnavailable)?;
+
+	let PhragmenResult { winners, assignments } = elect::<_, _, _, T::CurrencyToVote>(
+		<Module<T>>::validator_count() as usize,
+		<Module<T>>::minimum_validator_count().max(1) as usize,
+		snapshot_validators.clone(),
+		snapshot_nominators.iter().map(|n| (n.clone(), <Module<T>>::nominators(n))).collect(),
+		<Module<T>>::slashable_balance_of,
+		true,
+	).ok_or(OffchainElectionError::ElectionFailed)?;
+
+	let winners = winners.into_iter().map(|(w, _)| w).collect::<Vec<T::AccountId>>();
+
+	let assignments = if cfg!(feature = "equalize") {
+		equalize_assignments::<T>(&winners, assignments)
+	} else {
+		assignments
+	};
+
+	// score the solution exactly the way the chain will when it is submitted.
+	let supports = build_support_map::<_, _, _, T::CurrencyToVote>(
+		&winners,
+		&assignments,
+		<Module<T>>::slashable_balance_of,
+		true,
+	);
+	let score = evaluate_support(&supports);
+
+	let validator_index = |who: &T::AccountId| -> Result<ValidatorIndex, OffchainElectionError> {
+		snapshot_validators.iter()
+			.position(|v| v == who)
+			.and_then(|i| i.try_into().ok())
+			.ok_or(OffchainElectionError::NotInSnapshot)
+	};
+	let nominator_index = |who: &T::AccountId| -> Result<NominatorIndex, OffchainElectionError> {
+		snapshot_nominators.iter()
+			.position(|n| n == who)
+			.and_then(|i| i.try_into().ok())
+			.ok_or(OffchainElectionError::NotInSnapshot)
+	};
+
+	let winners = winners.iter().map(validator_index).collect::<Result<Vec<_>, _>>()?;
+
+	let mut votes = Vec::with_capacity(assignments.len());
+	for (nominator, distribution) in assignments {
+		let distribution = distribution.into_iter()
+			.filter(|(_, ratio)| !ratio.is_zero())
+			.map(|(target, ratio)| validator_index(&target).map(|i| (i, ratio)))
+			.collect::<Result<Vec<_>, _>>()?;
+		if !distribution.is_empty() {
+			votes.push((nominator_index(&nominator)?, distribution));
+		}
+	}
+	votes.sort_by_key(|(nominator, _)| *nominator);
+
+	Ok((winners, CompactAssignments { votes }, score))
+}
+
+/// The payload signed by the validator submitting a solution.
+pub(crate) fn signing_payload(
+	winners: &[ValidatorIndex],
+	compact: &CompactAssignments,
+	score: PhragmenScore,
+	era: EraIndex,
+	validator_index: u32,
+) -> Vec<u8> {
+	(winners, compact, score, era, validator_index).encode()
+}
+
+/// Whether a solution should be submitted at `now`: once in the block opening the window, and
+/// then every `OFFCHAIN_REPEAT` blocks for as long as no solution is queued, in case the previous
+/// one did not make it into a block.
+///
+/// Records `now` as the block of the last submission if it returns `true`.
+pub(crate) fn should_submit<T: Trait>(now: T::BlockNumber) -> bool {
+	let opened_at = match <Module<T>>::era_election_status() {
+		ElectionStatus::Open(opened_at) => opened_at,
+		ElectionStatus::Closed => return false,
+	};
+
+	let last_raw = runtime_io::local_storage_get(StorageKind::PERSISTENT, OFFCHAIN_LAST_SUBMISSION_DB);
+	let last = last_raw.as_ref().and_then(|raw| T::BlockNumber::decode(&mut &raw[..]).ok());
+	let due = match last {
+		// a previous submission of this window.
+		Some(last) if last >= opened_at =>
+			now >= last + T::BlockNumber::from(OFFCHAIN_REPEAT) && <Module<T>>::queued_elected().is_none(),
+		_ => true,
+	};
+
+	// another worker may be running for the same block.
+	due && runtime_io::local_storage_compare_and_set(
+		StorageKind::PERSISTENT,
+		OFFCHAIN_LAST_SUBMISSION_DB,
+		last_raw.as_ref().map(Vec::as_slice),
+		&now.encode(),
+	)
+}
+
+/// Compute a solution for the current election window, sign it with the session key of one of the
+/// current validators found in the local keystore and submit it to the transaction pool.
+pub(crate) fn compute_and_submit_solution<T: Trait>() -> Result<(), OffchainElectionError> {
+	let local_keys = T::KeyType::all();
+	let (validator_index, key) = T::SessionInterface::validators()
+		.iter()
+		.enumerate()
+		.find_map(|(index, validator)| {
+			T::SessionInterface::session_key::<T::KeyType>(validator)
+				.filter(|key| local_keys.contains(key))
+				.map(|key| (index as u32, key))
+		})
+		.ok_or(OffchainElectionError::NoSigningKey)?;
+
+	let (winners, compact, score) = prepare_submission::<T>()?;
+	let era = <Module<T>>::current_era();
+	let signature = key.sign(&signing_payload(&winners, &compact, score, era, validator_index))
+		.ok_or(OffchainElectionError::FailedSigning)?;
+	let call: Call<T> = Call::submit_election_solution_unsigned(
+		winners,
+		compact,
+		score,
+		era,
+		validator_index,
+		signature,
+	);
+	T::SubmitTransaction::submit_unsigned(call)
+		.map_err(|_| OffchainElectionError::PoolSubmissionFailed)
+}
+
+/// Run `equalize` on the given assignments and convert the result back into ratios of each
+/// nominator's stake.
+///
+/// Ratios are always rounded down, so that the ratios of a nominator never add up to more than
+/// one.
+fn equalize_assignments<T: Trait>(
+	winners: &Vec<T::AccountId>,
+	assignments: Vec<(T::AccountId, Vec<PhragmenAssignment<T::AccountId>>)>,
+) -> Vec<(T::AccountId, Vec<PhragmenAssignment<T::AccountId>>)> {
+	let to_votes = |b: BalanceOf<T>|
+		<T::CurrencyToVote as Convert<BalanceOf<T>, u64>>::convert(b) as ExtendedBalance;
+
+	let mut supports = build_support_map::<_, _, _, T::CurrencyToVote>(
+		winners,
+		&assignments,
+		<Module<T>>::slashable_balance_of,
+		true,
+	);
+
+	let staked_assignments = assignments.iter()
+		.map(|(n, assignment)| {
+			let nominator_stake = to_votes(<Module<T>>::slashable_balance_of(n));
+			let staked = assignment.iter()
+				.map(|(c, per_thing)| (c.clone(), *per_thing * nominator_stake))
+				.collect::<Vec<PhragmenStakedAssignment<T::AccountId>>>();
+			(n.clone(), staked)
+		})
+		.collect::<Vec<_>>();
+
+	equalize::<_, _, T::CurrencyToVote, _>(
+		staked_assignments,
+		&mut supports,
+		0,
+		2,
+		<Module<T>>::slashable_balance_of,
+	);
+
+	// `equalize` only updates the supports, so the edges are read back from there.
+	let mut equalized = assignments.into_iter()
+		.map(|(n, _)| (n, Vec::new()))
+		.collect::<Vec<(T::AccountId, Vec<PhragmenAssignment<T::AccountId>>)>>();
+	for (target, support) in supports.into_iter() {
+		for (nominator, stake) in support.others {
+			if let Some((_, distribution)) = equalized.iter_mut().find(|(n, _)| *n == nominator) {
+				let budget = to_votes(<Module<T>>::slashable_balance_of(&nominator));
+				if budget.is_zero() {
+					continue;
+				}
+				let parts = multiply_by_rational(stake, Perbill::accuracy().into(), budget)
+					.unwrap_or_default()
+					.min(Perbill::accuracy().into());
+				distribution.push((target.clone(), Perbill::from_parts(parts as u32)));
+			}
+		}
+	}
+	equalized
+}
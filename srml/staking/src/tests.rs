@@ -18,9 +18,15 @@
 
 use super::*;
 use mock::*;
-use sr_primitives::{assert_eq_error_rate, traits::OnInitialize};
+use sr_primitives::{assert_eq_error_rate, traits::{OnInitialize, OnRuntimeUpgrade}};
 use sr_staking_primitives::offence::{OffenceDetails, OnOffenceHandler};
-use support::{assert_ok, assert_noop, assert_eq_uvec, traits::{Currency, ReservableCurrency}};
+use support::{
+	assert_ok, assert_noop, assert_eq_uvec, StorageValue, StorageMap,
+	traits::{Currency, ReservableCurrency},
+};
+use offchain::testing::TestOffchainExt;
+use primitives::offchain::OffchainExt;
+use sr_primitives::testing::UintAuthorityId;
 
 #[test]
 fn force_unstake_works() {
@@ -58,12 +64,12 @@ fn basic_setup_works() {
 		// Account 10 controls the stash from account 11, which is 100 * balance_factor units
 		assert_eq!(
 			Staking::ledger(&10),
-			Some(StakingLedger { stash: 11, total: 1000, active: 1000, unlocking: vec![] })
+			Some(StakingLedger { stash: 11, total: 1000, active: 1000, unlocking: vec![], claimed_rewards: vec![] })
 		);
 		// Account 20 controls the stash from account 21, which is 200 * balance_factor units
 		assert_eq!(
 			Staking::ledger(&20),
-			Some(StakingLedger { stash: 21, total: 1000, active: 1000, unlocking: vec![] })
+			Some(StakingLedger { stash: 21, total: 1000, active: 1000, unlocking: vec![], claimed_rewards: vec![] })
 		);
 		// Account 1 does not control any stash
 		assert_eq!(Staking::ledger(&1), None);
@@ -77,7 +83,7 @@ fn basic_setup_works() {
 
 		assert_eq!(
 			Staking::ledger(100),
-			Some(StakingLedger { stash: 101, total: 500, active: 500, unlocking: vec![] })
+			Some(StakingLedger { stash: 101, total: 500, active: 500, unlocking: vec![], claimed_rewards: vec![] })
 		);
 		assert_eq!(Staking::nominators(101), vec![11, 21]);
 
@@ -219,6 +225,11 @@ fn rewards_should_work() {
 		assert_eq!(Staking::current_era(), 1);
 		assert_eq!(Session::current_index(), 3);
 
+		// Rewards are only paid out once claimed.
+		assert_eq!(Balances::total_balance(&2), init_balance_2);
+		assert_eq!(Balances::total_balance(&10), init_balance_10);
+		make_all_reward_payment(0);
+
 		// 11 validator has 2/3 of the total rewards and half half for it and its nominator
 		assert_eq_error_rate!(Balances::total_balance(&2), init_balance_2 + total_payout / 3, 1);
 		assert_eq_error_rate!(Balances::total_balance(&10), init_balance_10 + total_payout / 3, 1);
@@ -248,6 +259,7 @@ fn multi_era_reward_should_work() {
 		start_session(3);
 
 		assert_eq!(Staking::current_era(), 1);
+		make_all_reward_payment(0);
 		assert_eq!(Balances::total_balance(&10), init_balance_10 + total_payout_0);
 
 		start_session(4);
@@ -260,10 +272,120 @@ fn multi_era_reward_should_work() {
 		start_session(5);
 
 		// pay time
+		make_all_reward_payment(1);
 		assert_eq!(Balances::total_balance(&10), init_balance_10 + total_payout_0 + total_payout_1);
 	});
 }
 
+#[test]
+fn payout_stakers_pays_once_per_era() {
+	// Should check that:
+	// * only finished eras with a recorded payout can be claimed
+	// * each validator can only be paid out once per era
+	ExtBuilder::default().nominate(false).build().execute_with(|| {
+		let init_balance_11 = Balances::total_balance(&11);
+		let init_balance_21 = Balances::total_balance(&21);
+
+		let total_payout_0 = current_total_payout_for_duration(3000);
+		assert!(total_payout_0 > 10); // Test is meaningful if reward something
+		<Module<Test>>::reward_by_ids(vec![(11, 1)]);
+
+		// the current era can't be claimed yet.
+		assert_noop!(
+			Staking::payout_stakers(Origin::signed(1337), 11, 0),
+			"era not yet finished",
+		);
+
+		start_era(1);
+
+		// only stashes can be paid out.
+		assert_noop!(
+			Staking::payout_stakers(Origin::signed(1337), 10, 0),
+			"not a stash",
+		);
+
+		// anyone can trigger the payout, which goes to the stakers.
+		assert_ok!(Staking::payout_stakers(Origin::signed(1337), 11, 0));
+		assert_eq!(Balances::total_balance(&11), init_balance_11 + total_payout_0);
+		assert_eq!(Staking::ledger(&10).unwrap().claimed_rewards, vec![0]);
+
+		// but only once.
+		assert_noop!(
+			Staking::payout_stakers(Origin::signed(1337), 11, 0),
+			"reward already claimed",
+		);
+
+		// a validator without points gets nothing, but its claim is recorded.
+		assert_ok!(Staking::payout_stakers(Origin::signed(1337), 21, 0));
+		assert_eq!(Balances::total_balance(&21), init_balance_21);
+		assert_eq!(Staking::ledger(&20).unwrap().claimed_rewards, vec![0]);
+
+		// an era without any reward points has no payout.
+		start_era(2);
+		assert_noop!(
+			Staking::payout_stakers(Origin::signed(1337), 11, 1),
+			"invalid era to reward",
+		);
+	});
+}
+
+#[test]
+fn payout_stakers_only_rewards_the_biggest_nominators() {
+	ExtBuilder::default()
+		.nominate(false)
+		.max_nominator_rewarded_per_validator(1)
+		.build()
+		.execute_with(|| {
+			// rewards are paid to the controllers, which must exist.
+			let _ = Balances::make_free_balance_be(&1000, 1);
+			let _ = Balances::make_free_balance_be(&2000, 1);
+			bond_nominator(1000, 500, vec![11]);
+			bond_nominator(2000, 1000, vec![11]);
+			start_era(1);
+
+			<Module<Test>>::reward_by_ids(vec![(11, 1)]);
+			start_era(2);
+
+			let exposure = Staking::eras_stakers(&1, &11);
+			assert_eq!(exposure.others.len(), 1);
+			assert_eq!(exposure.others[0].who, 2001);
+
+			let init_balance_1000 = Balances::total_balance(&1000);
+			let init_balance_2000 = Balances::total_balance(&2000);
+			assert_ok!(Staking::payout_stakers(Origin::signed(1337), 11, 1));
+			assert_eq!(Balances::total_balance(&1000), init_balance_1000);
+			assert!(Balances::total_balance(&2000) > init_balance_2000);
+		});
+}
+
+#[test]
+fn ledgers_without_claimed_rewards_are_migrated() {
+	ExtBuilder::default().build().execute_with(|| {
+		let ledger = Staking::ledger(&10).unwrap();
+		let old_ledger = (
+			ledger.stash,
+			codec::Compact(ledger.total),
+			codec::Compact(ledger.active),
+			ledger.unlocking.clone(),
+		);
+		let key = <Ledger<Test>>::hashed_key_for(&10);
+		support::storage::unhashed::put_raw(&key, &old_ledger.encode());
+
+		// the former layout is still readable.
+		assert_eq!(Staking::ledger(&10), Some(StakingLedger { claimed_rewards: vec![], ..ledger.clone() }));
+
+		LedgersMigrated::put(false);
+		assert!(<Module<Test> as OnRuntimeUpgrade>::on_runtime_upgrade() > 0);
+		assert_eq!(support::storage::unhashed::get_raw(&key), Some(StakingLedger {
+			claimed_rewards: vec![],
+			..ledger
+		}.encode()));
+
+		// the migration only runs once.
+		assert_eq!(<Module<Test> as OnRuntimeUpgrade>::on_runtime_upgrade(), 0);
+	});
+}
+
 #[test]
 fn staking_should_work() {
 	// should test:
@@ -322,7 +444,7 @@ fn staking_should_work() {
 			// Note: the stashed value of 4 is still lock
 			assert_eq!(
 				Staking::ledger(&4),
-				Some(StakingLedger { stash: 3, total: 1500, active: 1500, unlocking: vec![] })
+				Some(StakingLedger { stash: 3, total: 1500, active: 1500, unlocking: vec![], claimed_rewards: vec![] })
 			);
 			// e.g. it cannot spend more than 500 that it has free from the total 2000
 			assert_noop!(Balances::reserve(&3, 501), "account liquidity restrictions prevent withdrawal");
@@ -467,6 +589,7 @@ fn nominating_and_rewards_should_work() {
 			assert_eq_uvec!(validator_controllers(), vec![20, 10]);
 
 			// OLD validators must have already received some rewards.
+			make_all_reward_payment(0);
 			assert_eq!(Balances::total_balance(&40), 1 + total_payout_0 / 2);
 			assert_eq!(Balances::total_balance(&30), 1 + total_payout_0 / 2);
 
@@ -537,6 +660,7 @@ fn nominating_and_rewards_should_work() {
 			<Module<Test>>::reward_by_ids(vec![(11, 1)]);
 
 			start_era(2);
+			make_all_reward_payment(1);
 
 			// nothing else will happen, era ends and rewards are paid again,
 			// it is expected that nominators will also be paid. See below
@@ -608,7 +732,7 @@ fn nominators_also_get_slashed() {
 	// Here is the breakdown of roles:
 	// 10 - is the controller of 11
 	// 11 - is the stash.
-	// 2 - is the nominator of 20, 10
+	// 1 - is the nominator of 21, 11 (controlled by 2)
 	ExtBuilder::default().nominate(false).build().execute_with(|| {
 		assert_eq!(Staking::validator_count(), 2);
 
@@ -621,10 +745,10 @@ fn nominators_also_get_slashed() {
 			let _ = Balances::make_free_balance_be(i, initial_balance);
 		}
 
-		// 2 will nominate for 10, 20
+		// 1 will nominate for 21, 11
 		let nominator_stake = 500;
 		assert_ok!(Staking::bond(Origin::signed(1), 2, nominator_stake, RewardDestination::default()));
-		assert_ok!(Staking::nominate(Origin::signed(2), vec![20, 10]));
+		assert_ok!(Staking::nominate(Origin::signed(2), vec![21, 11]));
 
 		let total_payout = current_total_payout_for_duration(3000);
 		assert!(total_payout > 100); // Test is meaningfull if reward something
@@ -632,9 +756,17 @@ fn nominators_also_get_slashed() {
 
 		// new era, pay rewards,
 		start_era(1);
+		make_all_reward_payment(0);
 
 		// Nominator stash didn't collect any.
-		assert_eq!(Balances::total_balance(&2), initial_balance);
+		assert_eq!(Balances::total_balance(&1), initial_balance);
+
+		let expo = Staking::stakers(11);
+		let nominator_exposure = expo.others.iter()
+			.find(|o| o.who == 1)
+			.map(|o| o.value)
+			.unwrap();
+		assert!(nominator_exposure > 0);
 
 		// 10 goes offline
 		Staking::on_offence(
@@ -647,15 +779,13 @@ fn nominators_also_get_slashed() {
 			}],
 			&[Perbill::from_percent(5)],
 		);
-		let expo = Staking::stakers(11);
-		let slash_value = 50;
-		let total_slash = expo.total.min(slash_value);
-		let validator_slash = expo.own.min(total_slash);
-		let nominator_slash = nominator_stake.min(total_slash - validator_slash);
+		// both the validator and the nominator lose the same proportion of their exposure.
+		let validator_slash = Perbill::from_percent(5) * expo.own;
+		let nominator_slash = Perbill::from_percent(5) * nominator_exposure;
 
 		// initial + first era reward + slash
 		assert_eq!(Balances::total_balance(&11), initial_balance - validator_slash);
-		assert_eq!(Balances::total_balance(&2), initial_balance - nominator_slash);
+		assert_eq!(Balances::total_balance(&1), initial_balance - nominator_slash);
 		check_exposure_all();
 		check_nominator_all();
 		// Because slashing happened.
@@ -883,6 +1013,7 @@ fn reward_destination_works() {
 			total: 1000,
 			active: 1000,
 			unlocking: vec![],
+			claimed_rewards: vec![],
 		}));
 
 		// Compute total payout now for whole duration as other parameter won't change
@@ -891,6 +1022,7 @@ fn reward_destination_works() {
 		<Module<Test>>::reward_by_ids(vec![(11, 1)]);
 
 		start_era(1);
+		make_all_reward_payment(0);
 
 		// Check that RewardDestination is Staked (default)
 		assert_eq!(Staking::payee(&11), RewardDestination::Staked);
@@ -902,6 +1034,7 @@ fn reward_destination_works() {
 			total: 1000 + total_payout_0,
 			active: 1000 + total_payout_0,
 			unlocking: vec![],
+			claimed_rewards: vec![0],
 		}));
 
 		//Change RewardDestination to Stash
@@ -913,6 +1046,7 @@ fn reward_destination_works() {
 		<Module<Test>>::reward_by_ids(vec![(11, 1)]);
 
 		start_era(2);
+		make_all_reward_payment(1);
 
 		// Check that RewardDestination is Stash
 		assert_eq!(Staking::payee(&11), RewardDestination::Stash);
@@ -926,6 +1060,7 @@ fn reward_destination_works() {
 			total: 1000 + total_payout_0,
 			active: 1000 + total_payout_0,
 			unlocking: vec![],
+			claimed_rewards: vec![0, 1],
 		}));
 
 		// Change RewardDestination to Controller
@@ -940,6 +1075,7 @@ fn reward_destination_works() {
 		<Module<Test>>::reward_by_ids(vec![(11, 1)]);
 
 		start_era(3);
+		make_all_reward_payment(2);
 
 		// Check that RewardDestination is Controller
 		assert_eq!(Staking::payee(&11), RewardDestination::Controller);
@@ -951,6 +1087,7 @@ fn reward_destination_works() {
 			total: 1000 + total_payout_0,
 			active: 1000 + total_payout_0,
 			unlocking: vec![],
+			claimed_rewards: vec![0, 1, 2],
 		}));
 		// Check that amount in staked account is NOT increased.
 		assert_eq!(Balances::free_balance(&11), recorded_stash_balance);
@@ -991,6 +1128,7 @@ fn validator_payment_prefs_work() {
 		<Module<Test>>::reward_by_ids(vec![(11, 1)]);
 
 		start_era(1);
+		make_all_reward_payment(0);
 
 		// whats left to be shared is the sum of 3 rounds minus the validator's cut.
 		let shared_cut = total_payout_0 - validator_cut;
@@ -1023,6 +1161,7 @@ fn bond_extra_works() {
 			total: 1000,
 			active: 1000,
 			unlocking: vec![],
+			claimed_rewards: vec![],
 		}));
 
 		// Give account 11 some large free balance greater than total
@@ -1036,6 +1175,7 @@ fn bond_extra_works() {
 			total: 1000 + 100,
 			active: 1000 + 100,
 			unlocking: vec![],
+			claimed_rewards: vec![],
 		}));
 
 		// Call the bond_extra function with a large number, should handle it
@@ -1046,6 +1186,7 @@ fn bond_extra_works() {
 			total: 1000000,
 			active: 1000000,
 			unlocking: vec![],
+			claimed_rewards: vec![],
 		}));
 	});
 }
@@ -1080,6 +1221,7 @@ fn bond_extra_and_withdraw_unbonded_works() {
 			total: 1000,
 			active: 1000,
 			unlocking: vec![],
+			claimed_rewards: vec![],
 		}));
 		assert_eq!(Staking::stakers(&11), Exposure { total: 1000, own: 1000, others: vec![] });
 
@@ -1091,6 +1233,7 @@ fn bond_extra_and_withdraw_unbonded_works() {
 			total: 1000 + 100,
 			active: 1000 + 100,
 			unlocking: vec![],
+			claimed_rewards: vec![],
 		}));
 		// Exposure is a snapshot! only updated after the next era update.
 		assert_ne!(Staking::stakers(&11), Exposure { total: 1000 + 100, own: 1000 + 100, others: vec![] });
@@ -1106,6 +1249,7 @@ fn bond_extra_and_withdraw_unbonded_works() {
 			total: 1000 + 100,
 			active: 1000 + 100,
 			unlocking: vec![],
+			claimed_rewards: vec![],
 		}));
 		// Exposure is now updated.
 		assert_eq!(Staking::stakers(&11), Exposure { total: 1000 + 100, own: 1000 + 100, others: vec![] });
@@ -1113,13 +1257,15 @@ fn bond_extra_and_withdraw_unbonded_works() {
 		// Unbond almost all of the funds in stash.
 		Staking::unbond(Origin::signed(10), 1000).unwrap();
 		assert_eq!(Staking::ledger(&10), Some(StakingLedger {
-			stash: 11, total: 1000 + 100, active: 100, unlocking: vec![UnlockChunk{ value: 1000, era: 2 + 3}] })
+			stash: 11, total: 1000 + 100, active: 100, unlocking: vec![UnlockChunk{ value: 1000, era: 2 + 3}],
+			claimed_rewards: vec![] })
 		);
 
 		// Attempting to free the balances now will fail. 2 eras need to pass.
 		Staking::withdraw_unbonded(Origin::signed(10)).unwrap();
 		assert_eq!(Staking::ledger(&10), Some(StakingLedger {
-			stash: 11, total: 1000 + 100, active: 100, unlocking: vec![UnlockChunk{ value: 1000, era: 2 + 3}] }));
+			stash: 11, total: 1000 + 100, active: 100, unlocking: vec![UnlockChunk{ value: 1000, era: 2 + 3}],
+			claimed_rewards: vec![] }));
 
 		// trigger next era.
 		start_era(3);
@@ -1127,7 +1273,8 @@ fn bond_extra_and_withdraw_unbonded_works() {
 		// nothing yet
 		Staking::withdraw_unbonded(Origin::signed(10)).unwrap();
 		assert_eq!(Staking::ledger(&10), Some(StakingLedger {
-			stash: 11, total: 1000 + 100, active: 100, unlocking: vec![UnlockChunk{ value: 1000, era: 2 + 3}] }));
+			stash: 11, total: 1000 + 100, active: 100, unlocking: vec![UnlockChunk{ value: 1000, era: 2 + 3}],
+			claimed_rewards: vec![] }));
 
 		// trigger next era.
 		start_era(5);
@@ -1135,7 +1282,8 @@ fn bond_extra_and_withdraw_unbonded_works() {
 		Staking::withdraw_unbonded(Origin::signed(10)).unwrap();
 		// Now the value is free and the staking ledger is updated.
 		assert_eq!(Staking::ledger(&10), Some(StakingLedger {
-			stash: 11, total: 100, active: 100, unlocking: vec![] }));
+			stash: 11, total: 100, active: 100, unlocking: vec![],
+			claimed_rewards: vec![] }));
 	})
 }
 
@@ -1166,6 +1314,34 @@ fn too_many_unbond_calls_should_not_work() {
 	})
 }
 
+#[test]
+fn staking_interface_unbond_merges_chunks_of_the_same_era() {
+	ExtBuilder::default().build().execute_with(|| {
+		// locked at era 0 until 3, all in a single chunk.
+		for _ in 0..MAX_UNLOCKING_CHUNKS {
+			assert_ok!(<Staking as StakingInterface>::unbond(&10, 1));
+		}
+		assert_eq!(
+			Staking::ledger(&10).unwrap().unlocking,
+			vec![UnlockChunk { value: MAX_UNLOCKING_CHUNKS as Balance, era: 3 }],
+		);
+
+		// a new era needs a new chunk, as long as there is room for it.
+		for i in 1..MAX_UNLOCKING_CHUNKS {
+			start_era(i as EraIndex);
+			assert_ok!(<Staking as StakingInterface>::unbond(&10, 1));
+		}
+		assert_eq!(Staking::ledger(&10).unwrap().unlocking.len(), MAX_UNLOCKING_CHUNKS);
+		assert_ok!(<Staking as StakingInterface>::unbond(&10, 1));
+
+		start_era(MAX_UNLOCKING_CHUNKS as EraIndex);
+		assert_noop!(
+			<Staking as StakingInterface>::unbond(&10, 1),
+			"can not schedule more unlock chunks"
+		);
+	})
+}
+
 #[test]
 fn slot_stake_is_least_staked_validator_and_exposure_defines_maximum_punishment() {
 	// Test that slot_stake is determined by the least staked validator
@@ -1189,7 +1365,7 @@ fn slot_stake_is_least_staked_validator_and_exposure_defines_maximum_punishment(
 		// Now lets lower account 20 stake
 		<Stakers<Test>>::insert(&21, Exposure { total: 69, own: 69, others: vec![] });
 		assert_eq!(Staking::stakers(&21).total, 69);
-		<Ledger<Test>>::insert(&20, StakingLedger { stash: 22, total: 69, active: 69, unlocking: vec![] });
+		<Ledger<Test>>::insert(&20, StakingLedger { stash: 22, total: 69, active: 69, unlocking: vec![], claimed_rewards: vec![] });
 
 		// Compute total payout now for whole duration as other parameter won't change
 		let total_payout_0 = current_total_payout_for_duration(3000);
@@ -1197,16 +1373,24 @@ fn slot_stake_is_least_staked_validator_and_exposure_defines_maximum_punishment(
 		<Module<Test>>::reward_by_ids(vec![(11, 1)]);
 		<Module<Test>>::reward_by_ids(vec![(21, 1)]);
 
-		// New era --> rewards are paid --> stakes are changed
+		// New era --> rewards are paid
 		start_era(1);
+		make_all_reward_payment(0);
 
 		// -- new balances + reward
-		assert_eq!(Staking::stakers(&11).total, 1000 + total_payout_0 / 2);
-		assert_eq!(Staking::stakers(&21).total, 69 + total_payout_0 / 2);
-
 		let _11_balance = Balances::free_balance(&11);
 		assert_eq!(_11_balance, 1000 + total_payout_0 / 2);
 
+		// -- rewards are paid after the election, so stakes only change on the next era.
+		assert_eq!(Staking::stakers(&11).total, 1000);
+		assert_eq!(Staking::slot_stake(), 69);
+
+		start_era(2);
+
+		// -- new stakes include the reward.
+		assert_eq!(Staking::stakers(&11).total, 1000 + total_payout_0 / 2);
+		assert_eq!(Staking::stakers(&21).total, 69 + total_payout_0 / 2);
+
 		// -- slot stake should also be updated.
 		assert_eq!(Staking::slot_stake(), 69 + total_payout_0 / 2);
 
@@ -1436,7 +1620,8 @@ fn bond_with_no_staked_value() {
 					stash: 1,
 					active: 0,
 					total: 5,
-					unlocking: vec![UnlockChunk {value: 5, era: 3}]
+					unlocking: vec![UnlockChunk {value: 5, era: 3}],
+					claimed_rewards: vec![],
 				})
 			);
 
@@ -1481,6 +1666,7 @@ fn bond_with_little_staked_value_bounded_by_slot_stake() {
 			assert!(total_payout_0 > 100); // Test is meaningfull if reward something
 			reward_all_elected();
 			start_era(1);
+			make_all_reward_payment(0);
 
 			// 2 is elected.
 			// and fucks up the slot stake.
@@ -1496,6 +1682,7 @@ fn bond_with_little_staked_value_bounded_by_slot_stake() {
 			assert!(total_payout_1 > 100); // Test is meaningfull if reward something
 			reward_all_elected();
 			start_era(2);
+			make_all_reward_payment(1);
 
 			assert_eq_uvec!(validator_controllers(), vec![20, 10, 2]);
 			assert_eq!(Staking::slot_stake(), 1);
@@ -1654,18 +1841,36 @@ fn reward_validator_slashing_validator_doesnt_overflow() {
 		<Stakers<Test>>::insert(&11, Exposure { total: stake, own: stake, others: vec![] });
 
 		// Check reward
-		let _ = Staking::reward_validator(&11, reward_slash);
+		let _ = Staking::reward_validator(
+			&11,
+			reward_slash,
+			&Staking::stakers(&11),
+			&Staking::validators(&11),
+		);
 		assert_eq!(Balances::total_balance(&11), stake * 2);
 
 		// Set staker
 		let _ = Balances::make_free_balance_be(&11, stake);
 		let _ = Balances::make_free_balance_be(&2, stake);
+
+		// only slashes out of bonded stake are applied. without this line,
+		// it is 0.
+		Staking::bond(Origin::signed(2), 20000, stake - 1, RewardDestination::default()).unwrap();
 		<Stakers<Test>>::insert(&11, Exposure { total: stake, own: 1, others: vec![
 			IndividualExposure { who: 2, value: stake - 1 }
 		]});
 
 		// Check slashing
-		let _ = Staking::slash_validator(&11, reward_slash, &Staking::stakers(&11), &mut Vec::new());
+		Staking::on_offence(
+			&[
+				OffenceDetails {
+					offender: (11, Staking::stakers(&11)),
+					reporters: vec![],
+				},
+			],
+			&[Perbill::from_percent(100)],
+		);
+
 		assert_eq!(Balances::total_balance(&11), stake - 1);
 		assert_eq!(Balances::total_balance(&2), 1);
 	})
@@ -1866,10 +2071,12 @@ fn reporters_receive_their_slice() {
 			&[Perbill::from_percent(50)],
 		);
 
-		// initial_balance x 50% (slash fraction) x 10% (rewards slice)
-		let reward = initial_balance / 20 / 2;
-		assert_eq!(Balances::free_balance(&1), 10 + reward);
-		assert_eq!(Balances::free_balance(&2), 20 + reward);
+		// F1 * (reward_proportion * slash - 0)
+		// 50% * (10% * initial_balance / 2)
+		let reward = (initial_balance / 20) / 2;
+		let reward_each = reward / 2; // split into two pieces.
+		assert_eq!(Balances::free_balance(&1), 10 + reward_each);
+		assert_eq!(Balances::free_balance(&2), 20 + reward_each);
 	});
 }
 
@@ -1884,7 +2091,12 @@ fn invulnerables_are_not_slashed() {
 
 		assert_eq!(Balances::free_balance(&11), 1000);
 		assert_eq!(Balances::free_balance(&21), 2000);
-		assert_eq!(Staking::stakers(&21).total, initial_balance);
+
+		let exposure = Staking::stakers(&21);
+		assert_eq!(exposure.total, initial_balance);
+
+		let nominator_balances: Vec<_> = exposure.others
+			.iter().map(|o| Balances::free_balance(&o.who)).collect();
 
 		Staking::on_offence(
 			&[
@@ -1902,8 +2114,16 @@ fn invulnerables_are_not_slashed() {
 
 		// The validator 11 hasn't been slashed, but 21 has been.
 		assert_eq!(Balances::free_balance(&11), 1000);
-		// 2000 - (0.2 * initial_balance)
-		assert_eq!(Balances::free_balance(&21), 2000 - (2 * initial_balance / 10));
+		// 2000 - (0.2 * 1000)
+		assert_eq!(Balances::free_balance(&21), 1800);
+
+		// ensure that nominators were slashed as well.
+		for (initial_balance, other) in nominator_balances.into_iter().zip(exposure.others) {
+			assert_eq!(
+				Balances::free_balance(&other.who),
+				initial_balance - (2 * other.value / 10),
+			);
+		}
 	});
 }
 
@@ -1928,3 +2148,410 @@ fn dont_slash_if_fraction_is_zero() {
 		assert_eq!(Balances::free_balance(&11), 1000);
 	});
 }
+
+#[test]
+fn only_slash_for_max_in_era() {
+	// A validator that is reported several times in the same era only pays for the worst offence.
+	ExtBuilder::default().build().execute_with(|| {
+		start_era(1);
+
+		assert_eq!(Balances::free_balance(&11), 1000);
+
+		Staking::on_offence(
+			&[
+				OffenceDetails {
+					offender: (11, Staking::stakers(&11)),
+					reporters: vec![],
+				},
+			],
+			&[Perbill::from_percent(50)],
+		);
+
+		// The validator has been slashed and has been force-chilled.
+		assert_eq!(Balances::free_balance(&11), 500);
+		assert_eq!(Staking::force_era(), Forcing::ForceNew);
+
+		Staking::on_offence(
+			&[
+				OffenceDetails {
+					offender: (11, Staking::stakers(&11)),
+					reporters: vec![],
+				},
+			],
+			&[Perbill::from_percent(25)],
+		);
+
+		// The validator has not been slashed additionally.
+		assert_eq!(Balances::free_balance(&11), 500);
+
+		Staking::on_offence(
+			&[
+				OffenceDetails {
+					offender: (11, Staking::stakers(&11)),
+					reporters: vec![],
+				},
+			],
+			&[Perbill::from_percent(60)],
+		);
+
+		// The validator got slashed 10% more.
+		assert_eq!(Balances::free_balance(&11), 400);
+	})
+}
+
+#[test]
+fn deferred_slashes_are_deferred() {
+	ExtBuilder::default().slash_defer_duration(2).build().execute_with(|| {
+		start_era(1);
+
+		assert_eq!(Balances::free_balance(&11), 1000);
+
+		let exposure = Staking::stakers(&11);
+		assert_eq!(Balances::free_balance(&101), 2000);
+		let nominated_value = exposure.others.iter().find(|o| o.who == 101).unwrap().value;
+
+		Staking::on_offence(
+			&[
+				OffenceDetails {
+					offender: (11, Staking::stakers(&11)),
+					reporters: vec![],
+				},
+			],
+			&[Perbill::from_percent(10)],
+		);
+
+		assert_eq!(Balances::free_balance(&11), 1000);
+		assert_eq!(Balances::free_balance(&101), 2000);
+
+		start_era(2);
+
+		assert_eq!(Balances::free_balance(&11), 1000);
+		assert_eq!(Balances::free_balance(&101), 2000);
+
+		start_era(3);
+
+		assert_eq!(Balances::free_balance(&11), 1000);
+		assert_eq!(Balances::free_balance(&101), 2000);
+
+		// at the start of era 4, slashes from era 1 are processed,
+		// after being deferred for at least 2 full eras.
+		start_era(4);
+
+		assert_eq!(Balances::free_balance(&11), 900);
+		assert_eq!(Balances::free_balance(&101), 2000 - (nominated_value / 10));
+	})
+}
+
+#[test]
+fn remove_deferred() {
+	ExtBuilder::default().slash_defer_duration(2).build().execute_with(|| {
+		start_era(1);
+
+		assert_eq!(Balances::free_balance(&11), 1000);
+		assert_eq!(Balances::free_balance(&21), 2000);
+
+		Staking::on_offence(
+			&[
+				OffenceDetails {
+					offender: (11, Staking::stakers(&11)),
+					reporters: vec![],
+				},
+				OffenceDetails {
+					offender: (21, Staking::stakers(&21)),
+					reporters: vec![],
+				},
+			],
+			&[Perbill::from_percent(10), Perbill::from_percent(10)],
+		);
+
+		assert_eq!(<Staking as Store>::UnappliedSlashes::get(&1).len(), 2);
+
+		// only root or the cancel origin can remove deferred slashes.
+		assert_noop!(
+			Staking::cancel_deferred_slash(Origin::signed(10), 1, vec![0]),
+			"bad origin",
+		);
+
+		// indices must be unique and within bounds.
+		assert_noop!(
+			Staking::cancel_deferred_slash(Origin::ROOT, 1, vec![0, 0]),
+			"duplicate index",
+		);
+		assert_noop!(
+			Staking::cancel_deferred_slash(Origin::ROOT, 1, vec![2]),
+			"slash record index out of bounds",
+		);
+
+		// cancel the slash of validator 11.
+		assert_ok!(Staking::cancel_deferred_slash(Origin::ROOT, 1, vec![0]));
+		assert_eq!(<Staking as Store>::UnappliedSlashes::get(&1).len(), 1);
+
+		start_era(2);
+		start_era(3);
+		start_era(4);
+
+		// 11 was spared, while 21 has been slashed 10% of its own stake.
+		assert_eq!(Balances::free_balance(&11), 1000);
+		assert_eq!(Balances::free_balance(&21), 1900);
+		assert!(<Staking as Store>::UnappliedSlashes::get(&1).is_empty());
+	})
+}
+
+#[test]
+fn election_window_opens_before_era_end_and_closes_with_it() {
+	ExtBuilder::default().build().execute_with(|| {
+		run_to_block(1);
+		assert!(!Staking::is_current_session_final());
+		assert_eq!(Staking::era_election_status(), ElectionStatus::Closed);
+		assert!(Staking::snapshot_validators().is_none());
+
+		// the next session change ends the era: the window opens and the stakers are snapshotted.
+		run_to_block(2);
+		assert!(Staking::is_current_session_final());
+		assert_eq!(Staking::era_election_status(), ElectionStatus::Open(2));
+		assert_eq_uvec!(Staking::snapshot_validators().unwrap(), vec![11, 21, 31]);
+		assert_eq!(Staking::snapshot_nominators(), Some(vec![101]));
+
+		// stakes and nominations are frozen while the window is open.
+		assert_noop!(
+			Staking::chill(Origin::signed(10)),
+			"call not allowed while election window is open"
+		);
+		assert_noop!(
+			Staking::bond_extra(Origin::signed(11), 10),
+			"call not allowed while election window is open"
+		);
+		assert_noop!(
+			Staking::nominate(Origin::signed(100), vec![31]),
+			"call not allowed while election window is open"
+		);
+
+		// no solution was submitted: the election is run on-chain at the end of the era.
+		run_to_block(3);
+		assert_eq!(Staking::current_era(), 1);
+		assert_eq!(Staking::era_election_status(), ElectionStatus::Closed);
+		assert!(Staking::snapshot_validators().is_none());
+		assert!(Staking::snapshot_nominators().is_none());
+		assert_eq_uvec!(Staking::current_elected(), vec![11, 21]);
+		assert_ok!(Staking::chill(Origin::signed(10)));
+	});
+}
+
+/// Submit an election solution signed by the first validator of the current validator set.
+fn submit_solution(
+	winners: Vec<ValidatorIndex>,
+	compact: CompactAssignments,
+	score: PhragmenScore,
+	era: EraIndex,
+) -> support::dispatch::Result {
+	let signature = sign_solution(&winners, &compact, score, era, 0);
+	Staking::submit_election_solution_unsigned(Origin::NONE, winners, compact, score, era, 0, signature)
+}
+
+/// Sign an election solution with the session key of the validator at `validator_index`.
+fn sign_solution(
+	winners: &[ValidatorIndex],
+	compact: &CompactAssignments,
+	score: PhragmenScore,
+	era: EraIndex,
+	validator_index: u32,
+) -> u64 {
+	let validator = Session::validators()[validator_index as usize];
+	let payload = offchain_election::signing_payload(winners, compact, score, era, validator_index);
+	UintAuthorityId(validator).sign(&payload).unwrap()
+}
+
+#[test]
+fn offchain_election_solution_is_enacted_at_era_end() {
+	ExtBuilder::default().build().execute_with(|| {
+		run_to_block(2);
+
+		let (winners, compact, score) = offchain_election::prepare_submission::<Test>().unwrap();
+		assert_eq!(winners.len(), 2);
+		assert_ok!(submit_solution(winners.clone(), compact.clone(), score, 0));
+		assert_eq!(Staking::queued_score(), Some(score));
+		assert_eq!(
+			Staking::queued_elected().map(|r| r.compute),
+			Some(ElectionCompute::Unsigned),
+		);
+
+		// a solution must be strictly better than the queued one.
+		assert_noop!(
+			submit_solution(winners, compact, score, 0),
+			"solution is not better than the queued one"
+		);
+
+		run_to_block(3);
+		assert_eq!(Staking::current_era(), 1);
+		assert_eq_uvec!(Staking::current_elected(), vec![11, 21]);
+		assert!(Staking::queued_elected().is_none());
+		assert!(Staking::queued_score().is_none());
+		check_exposure_all();
+		check_nominator_all();
+	});
+}
+
+#[test]
+fn invalid_election_solutions_are_rejected() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_noop!(
+			submit_solution(vec![], Default::default(), [0; 3], 0),
+			"election window is not open"
+		);
+
+		run_to_block(2);
+		let (winners, compact, score) = offchain_election::prepare_submission::<Test>().unwrap();
+		let submit = submit_solution;
+
+		assert_noop!(
+			Staking::submit_election_solution_unsigned(
+				Origin::signed(10),
+				winners.clone(),
+				compact.clone(),
+				score,
+				0,
+				0,
+				sign_solution(&winners, &compact, score, 0, 0),
+			),
+			"RequireNoOrigin"
+		);
+		assert_noop!(
+			submit(winners.clone(), compact.clone(), score, 1),
+			"solution is for another era"
+		);
+		assert_noop!(
+			submit(winners.clone(), compact.clone(), [score[0] + 1, score[1], score[2]], 0),
+			"wrong score"
+		);
+		assert_noop!(
+			submit(vec![winners[0]], compact.clone(), score, 0),
+			"wrong number of winners"
+		);
+		assert_noop!(
+			submit(vec![winners[0], winners[0]], compact.clone(), score, 0),
+			"duplicate winner"
+		);
+		assert_noop!(
+			submit(vec![winners[0], 99], compact.clone(), score, 0),
+			"invalid winner index"
+		);
+
+		// 101 backs both winners, it can not give the whole of its stake to each of them.
+		let mut over_assigned = compact.clone();
+		over_assigned.votes[0].1.iter_mut().for_each(|(_, ratio)| *ratio = Perbill::one());
+		assert_noop!(
+			submit(winners.clone(), over_assigned, score, 0),
+			"nominator stake over-assigned"
+		);
+
+		// the pool gets the same answer.
+		let bad_score = [score[0] + 1, score[1], score[2]];
+		let bad_call = Call::submit_election_solution_unsigned(
+			winners.clone(),
+			compact.clone(),
+			bad_score,
+			0,
+			0,
+			sign_solution(&winners, &compact, bad_score, 0, 0),
+		);
+		assert_eq!(
+			<Staking as support::unsigned::ValidateUnsigned>::validate_unsigned(&bad_call),
+			InvalidTransaction::Stale.into(),
+		);
+		let signature = sign_solution(&winners, &compact, score, 0, 0);
+		let call = Call::submit_election_solution_unsigned(winners, compact, score, 0, 0, signature);
+		assert!(<Staking as support::unsigned::ValidateUnsigned>::validate_unsigned(&call).is_ok());
+	});
+}
+
+#[test]
+fn election_solutions_must_be_signed_by_a_validator() {
+	ExtBuilder::default().build().execute_with(|| {
+		run_to_block(2);
+		let (winners, compact, score) = offchain_election::prepare_submission::<Test>().unwrap();
+		let signature = sign_solution(&winners, &compact, score, 0, 0);
+		let validate = |call: Call<Test>| <Staking as support::unsigned::ValidateUnsigned>::validate_unsigned(&call);
+
+		assert_noop!(
+			Staking::submit_election_solution_unsigned(
+				Origin::NONE,
+				winners.clone(),
+				compact.clone(),
+				score,
+				0,
+				0,
+				signature + 1,
+			),
+			"invalid solution signature"
+		);
+		assert_noop!(
+			Staking::submit_election_solution_unsigned(
+				Origin::NONE,
+				winners.clone(),
+				compact.clone(),
+				score,
+				0,
+				Session::validators().len() as u32,
+				signature,
+			),
+			"submitter is not a validator"
+		);
+
+		// the signature is checked before the solution itself, but after its claim.
+		assert_eq!(
+			validate(Call::submit_election_solution_unsigned(
+				vec![],
+				Default::default(),
+				score,
+				0,
+				0,
+				signature,
+			)),
+			InvalidTransaction::BadProof.into(),
+		);
+		assert_eq!(
+			validate(Call::submit_election_solution_unsigned(
+				winners.clone(),
+				compact.clone(),
+				score,
+				1,
+				0,
+				signature + 1,
+			)),
+			InvalidTransaction::Stale.into(),
+		);
+		assert!(validate(Call::submit_election_solution_unsigned(
+			winners,
+			compact,
+			score,
+			0,
+			0,
+			signature,
+		)).is_ok());
+	});
+}
+
+#[test]
+fn offchain_worker_submits_again_until_a_solution_is_queued() {
+	let mut ext = ExtBuilder::default().build();
+	let (offchain, _state) = TestOffchainExt::new();
+	ext.register_extension(OffchainExt::new(offchain));
+	ext.execute_with(|| {
+		let should_submit = offchain_election::should_submit::<Test>;
+		let repeat = offchain_election::OFFCHAIN_REPEAT as u64;
+
+		run_to_block(1);
+		assert!(!should_submit(1));
+
+		run_to_block(2);
+		assert!(should_submit(2));
+		assert!(!should_submit(2));
+		assert!(!should_submit(2 + repeat - 1));
+		assert!(should_submit(2 + repeat));
+
+		// no need to try again once a solution is queued.
+		let (winners, compact, score) = offchain_election::prepare_submission::<Test>().unwrap();
+		assert_ok!(submit_solution(winners, compact, score, 0));
+		assert!(!should_submit(2 + 2 * repeat));
+	});
+}
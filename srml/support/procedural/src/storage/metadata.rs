@@ -14,7 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
-//! Implementation of `storage_metadata` on module structure, used by construct_runtime.
+//! Implementation of `storage_metadata` and `storage_metadata_v9` on module structure, used by
+//! construct_runtime.
 
 use srml_support_procedural_tools::clean_type_string;
 use proc_macro2::TokenStream;
@@ -78,6 +79,63 @@ fn storage_line_metadata_type(scrate: &TokenStream, line: &StorageLineDefExt) ->
 	}
 }
 
+fn storage_line_metadata_type_v9(scrate: &TokenStream, line: &StorageLineDefExt) -> TokenStream {
+	let value_type = &line.value_type;
+	match &line.storage_type {
+		StorageLineTypeDef::Simple(_) => {
+			quote!{
+				#scrate::metadata::StorageEntryTypeV9::Plain(registry.register::<#value_type>())
+			}
+		},
+		StorageLineTypeDef::Map(map) | StorageLineTypeDef::LinkedMap(map) => {
+			let hasher = map.hasher.into_metadata();
+			let key = &map.key;
+			let is_linked = if let StorageLineTypeDef::LinkedMap(_) = line.storage_type {
+				true
+			} else {
+				false
+			};
+			quote!{
+				#scrate::metadata::StorageEntryTypeV9::Map {
+					hasher: #scrate::metadata::#hasher,
+					key: registry.register::<#key>(),
+					value: registry.register::<#value_type>(),
+					is_linked: #is_linked,
+				}
+			}
+		},
+		StorageLineTypeDef::DoubleMap(map) => {
+			let hasher1 = map.hasher1.into_metadata();
+			let hasher2 = map.hasher2.into_metadata();
+			let key1 = &map.key1;
+			let key2 = &map.key2;
+			quote!{
+				#scrate::metadata::StorageEntryTypeV9::DoubleMap {
+					hasher: #scrate::metadata::#hasher1,
+					key1: registry.register::<#key1>(),
+					key2: registry.register::<#key2>(),
+					value: registry.register::<#value_type>(),
+					key2_hasher: #scrate::metadata::#hasher2,
+				}
+			}
+		},
+	}
+}
+
+/// The types of a storage line that are described in the metadata.
+fn storage_line_types(line: &StorageLineDefExt) -> Vec<&syn::Type> {
+	let mut types = vec![&line.value_type];
+	match &line.storage_type {
+		StorageLineTypeDef::Simple(_) => (),
+		StorageLineTypeDef::Map(map) | StorageLineTypeDef::LinkedMap(map) => types.push(&map.key),
+		StorageLineTypeDef::DoubleMap(map) => {
+			types.push(&map.key1);
+			types.push(&map.key2);
+		},
+	}
+	types
+}
+
 fn default_byte_getter(
 	scrate: &TokenStream,
 	line: &StorageLineDefExt,
@@ -154,7 +212,10 @@ fn default_byte_getter(
 
 pub fn impl_metadata(scrate: &TokenStream, def: &DeclStorageDefExt) -> TokenStream {
 	let mut entries = TokenStream::new();
+	let mut entries_v9 = TokenStream::new();
 	let mut default_byte_getter_struct_defs = TokenStream::new();
+	let mut where_clause_v9 = def.where_clause.clone()
+		.unwrap_or_else(|| syn::parse_quote!( where ));
 
 	for line in def.storage_lines.iter() {
 		let str_name = line.name.to_string();
@@ -166,6 +227,10 @@ pub fn impl_metadata(scrate: &TokenStream, def: &DeclStorageDefExt) -> TokenStre
 		};
 
 		let ty = storage_line_metadata_type(scrate, line);
+		let ty_v9 = storage_line_metadata_type_v9(scrate, line);
+		for ty in storage_line_types(line) {
+			where_clause_v9.predicates.push(syn::parse_quote!( #ty: #scrate::metadata::TypeInfo ));
+		}
 
 		let (
 			default_byte_getter_struct_def,
@@ -173,11 +238,13 @@ pub fn impl_metadata(scrate: &TokenStream, def: &DeclStorageDefExt) -> TokenStre
 		) = default_byte_getter(scrate, line, def);
 
 		let mut docs = TokenStream::new();
+		let mut docs_v9 = TokenStream::new();
 		for attr in line.attrs.iter().filter_map(|v| v.parse_meta().ok()) {
 			if let syn::Meta::NameValue(meta) = attr {
 				if meta.ident == "doc" {
 					let lit = meta.lit;
 					docs.extend(quote!(#lit,));
+					docs_v9.extend(quote!(#scrate::metadata::DecodeDifferent::Encode(#lit),));
 				}
 			}
 		}
@@ -194,8 +261,21 @@ pub fn impl_metadata(scrate: &TokenStream, def: &DeclStorageDefExt) -> TokenStre
 			},
 		};
 
+		let entry_v9 = quote! {
+			#scrate::metadata::StorageEntryMetadataV9 {
+				name: #scrate::metadata::DecodeDifferent::Encode(#str_name),
+				modifier: #modifier,
+				ty: #ty_v9,
+				default: #scrate::metadata::DecodeDifferent::Encode(
+					#scrate::metadata::DefaultByteGetter(&#default_byte_getter_struct_instance)
+				),
+				documentation: <[_]>::to_vec(&[ #docs_v9 ]),
+			},
+		};
+
 		default_byte_getter_struct_defs.extend(default_byte_getter_struct_def);
 		entries.extend(entry);
+		entries_v9.extend(entry_v9);
 	}
 
 	let prefix = if let Some(instance) = &def.module_instance {
@@ -226,5 +306,18 @@ pub fn impl_metadata(scrate: &TokenStream, def: &DeclStorageDefExt) -> TokenStre
 				#store_metadata
 			}
 		}
+
+		impl#module_impl #module_struct #where_clause_v9 {
+			#[doc(hidden)]
+			pub fn storage_metadata_v9(
+				registry: &mut #scrate::metadata::Registry,
+			) -> #scrate::metadata::StorageMetadataV9 {
+				let _ = &registry;
+				#scrate::metadata::StorageMetadataV9 {
+					prefix: #scrate::metadata::DecodeDifferent::Encode(#prefix),
+					entries: <[_]>::to_vec(&[ #entries_v9 ]),
+				}
+			}
+		}
 	)
 }
@@ -205,6 +205,13 @@ impl<T> Parameter for T where T: Codec + EncodeLike + Clone + Eq + fmt::Debug {}
 /// * `offchain_worker`: Executes at the beginning of a block and produces extrinsics for a future block
 /// upon completion. Using this function will implement the
 /// [`OffchainWorker`](../sr_primitives/traits/trait.OffchainWorker.html) trait.
+///
+/// The following reserved function takes no input and must return the weight it consumed:
+///
+/// * `on_runtime_upgrade`: Executes once, at the beginning of the first block executed after the
+/// runtime's `spec_version` changed, before `on_initialize`. This is the place to migrate storage
+/// to a new layout. Using this function will implement the
+/// [`OnRuntimeUpgrade`](../sr_primitives/traits/trait.OnRuntimeUpgrade.html) trait.
 #[macro_export]
 macro_rules! decl_module {
 	// Entry point #1.
@@ -231,6 +238,7 @@ macro_rules! decl_module {
 			{}
 			{}
 			{}
+			{}
 			[]
 			$($t)*
 		);
@@ -263,6 +271,7 @@ macro_rules! decl_module {
 			{}
 			{}
 			{}
+			{}
 			[]
 			$($t)*
 		);
@@ -276,6 +285,7 @@ macro_rules! decl_module {
 		{ $( $other_where_bounds:tt )* }
 		{}
 		{ $( $on_initialize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $on_finalize:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
@@ -292,6 +302,7 @@ macro_rules! decl_module {
 			{ $( $other_where_bounds )* }
 			{ $vis fn deposit_event() = default; }
 			{ $( $on_initialize )* }
+			{ $( $on_runtime_upgrade )* }
 			{ $( $on_finalize )* }
 			{ $( $offchain )* }
 			{ $( $constants )* }
@@ -307,6 +318,7 @@ macro_rules! decl_module {
 		{ $( $other_where_bounds:tt )* }
 		{}
 		{ $( $on_initialize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $on_finalize:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
@@ -327,6 +339,7 @@ macro_rules! decl_module {
 		{ $( $other_where_bounds:tt )* }
 		{ $( $deposit_event:tt )* }
 		{ $( $on_initialize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{}
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
@@ -343,6 +356,7 @@ macro_rules! decl_module {
 			{ $( $other_where_bounds )* }
 			{ $( $deposit_event )* }
 			{ $( $on_initialize )* }
+			{ $( $on_runtime_upgrade )* }
 			{ fn on_finalize( $( $param_name : $param ),* ) { $( $impl )* } }
 			{ $( $offchain )* }
 			{ $( $constants )* }
@@ -358,6 +372,7 @@ macro_rules! decl_module {
 		{ $( $other_where_bounds:tt )* }
 		{ $( $deposit_event:tt )* }
 		{}
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $on_finalize:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
@@ -374,6 +389,40 @@ macro_rules! decl_module {
 			{ $( $other_where_bounds )* }
 			{ $( $deposit_event )* }
 			{ fn on_initialize( $( $param_name : $param ),* ) { $( $impl )* } }
+			{ $( $on_runtime_upgrade )* }
+			{ $( $on_finalize )* }
+			{ $( $offchain )* }
+			{ $( $constants )* }
+			{ $( $error_type )* }
+			[ $( $dispatchables )* ]
+			$($rest)*
+		);
+	};
+	(@normalize
+		$(#[$attr:meta])*
+		pub struct $mod_type:ident<$trait_instance:ident: $trait_name:ident$(<I>, I: $instantiable:path $(= $module_default_instance:path)?)?>
+		for enum $call_type:ident where origin: $origin_type:ty, system = $system:ident
+		{ $( $other_where_bounds:tt )* }
+		{ $( $deposit_event:tt )* }
+		{ $( $on_initialize:tt )* }
+		{}
+		{ $( $on_finalize:tt )* }
+		{ $( $offchain:tt )* }
+		{ $( $constants:tt )* }
+		{ $( $error_type:tt )* }
+		[ $( $dispatchables:tt )* ]
+		$(#[doc = $doc_attr:tt])*
+		fn on_runtime_upgrade() -> $return:ty { $( $impl:tt )* }
+		$($rest:tt)*
+	) => {
+		$crate::decl_module!(@normalize
+			$(#[$attr])*
+			pub struct $mod_type<$trait_instance: $trait_name$(<I>, I: $instantiable $(= $module_default_instance)?)?>
+			for enum $call_type where origin: $origin_type, system = $system
+			{ $( $other_where_bounds )* }
+			{ $( $deposit_event )* }
+			{ $( $on_initialize )* }
+			{ fn on_runtime_upgrade() -> $return { $( $impl )* } }
 			{ $( $on_finalize )* }
 			{ $( $offchain )* }
 			{ $( $constants )* }
@@ -382,6 +431,27 @@ macro_rules! decl_module {
 			$($rest)*
 		);
 	};
+	(@normalize
+		$(#[$attr:meta])*
+		pub struct $mod_type:ident<$trait_instance:ident: $trait_name:ident$(<I>, I: $instantiable:path $(= $module_default_instance:path)?)?>
+		for enum $call_type:ident where origin: $origin_type:ty, system = $system:ident
+		{ $( $other_where_bounds:tt )* }
+		{ $( $deposit_event:tt )* }
+		{ $( $on_initialize:tt )* }
+		{}
+		{ $( $on_finalize:tt )* }
+		{ $( $offchain:tt )* }
+		{ $( $constants:tt )* }
+		{ $( $error_type:tt )* }
+		[ $( $dispatchables:tt )* ]
+		$(#[doc = $doc_attr:tt])*
+		fn on_runtime_upgrade( $( $param_name:ident : $param:ty ),* $(,)? ) $( -> $return:ty )? { $( $impl:tt )* }
+		$($rest:tt)*
+	) => {
+		compile_error!(
+			"`on_runtime_upgrade` is reserved and must follow the syntax: `fn on_runtime_upgrade() -> Weight { ... }`"
+		);
+	};
 	(@normalize
 		$(#[$attr:meta])*
 		pub struct $mod_type:ident<
@@ -392,6 +462,7 @@ macro_rules! decl_module {
 		{ $( $other_where_bounds:tt )* }
 		{ $( $deposit_event:tt )* }
 		{ $( $on_initialize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $on_finalize:tt )* }
 		{ }
 		{ $( $constants:tt )* }
@@ -410,6 +481,7 @@ macro_rules! decl_module {
 			{ $( $other_where_bounds )* }
 			{ $( $deposit_event )* }
 			{ $( $on_initialize )* }
+			{ $( $on_runtime_upgrade )* }
 			{ $( $on_finalize )* }
 			{ fn offchain_worker( $( $param_name : $param ),* ) { $( $impl )* } }
 			{ $( $constants )* }
@@ -430,6 +502,7 @@ macro_rules! decl_module {
 		{ $( $other_where_bounds:tt )* }
 		{ $( $deposit_event:tt )* }
 		{ $( $on_initialize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $on_finalize:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
@@ -449,6 +522,7 @@ macro_rules! decl_module {
 			{ $( $other_where_bounds )* }
 			{ $( $deposit_event )* }
 			{ $( $on_initialize )* }
+			{ $( $on_runtime_upgrade )* }
 			{ $( $on_finalize )* }
 			{ $( $offchain )* }
 			{
@@ -473,6 +547,7 @@ macro_rules! decl_module {
 		{ $( $other_where_bounds:tt )* }
 		{ $( $deposit_event:tt )* }
 		{ $( $on_initialize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $on_finalize:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
@@ -491,6 +566,7 @@ macro_rules! decl_module {
 			{ $( $other_where_bounds )* }
 			{ $( $deposit_event )* }
 			{ $( $on_initialize )* }
+			{ $( $on_runtime_upgrade )* }
 			{ $( $on_finalize )* }
 			{ $( $offchain )* }
 			{ $( $constants )* }
@@ -510,6 +586,7 @@ macro_rules! decl_module {
 		{ $( $other_where_bounds:tt )* }
 		{ $( $deposit_event:tt )* }
 		{ $( $on_initialize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $on_finalize:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
@@ -526,6 +603,7 @@ macro_rules! decl_module {
 			{ $( $other_where_bounds )* }
 			{ $( $deposit_event )* }
 			{ $( $on_initialize )* }
+			{ $( $on_runtime_upgrade )* }
 			{ $( $on_finalize )* }
 			{ $( $offchain )* }
 			{ $( $constants )* }
@@ -546,6 +624,7 @@ macro_rules! decl_module {
 		{ $( $other_where_bounds:tt )* }
 		{ $( $deposit_event:tt )* }
 		{ $( $on_initialize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $on_finalize:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
@@ -567,6 +646,7 @@ macro_rules! decl_module {
 			{ $( $other_where_bounds )* }
 			{ $( $deposit_event )* }
 			{ $( $on_initialize )* }
+			{ $( $on_runtime_upgrade )* }
 			{ $( $on_finalize )* }
 			{ $( $offchain )* }
 			{ $( $constants )* }
@@ -594,6 +674,7 @@ macro_rules! decl_module {
 		{ $( $other_where_bounds:tt )* }
 		{ $( $deposit_event:tt )* }
 		{ $( $on_initialize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $on_finalize:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
@@ -614,6 +695,7 @@ macro_rules! decl_module {
 			{ $( $other_where_bounds )* }
 			{ $( $deposit_event )* }
 			{ $( $on_initialize )* }
+			{ $( $on_runtime_upgrade )* }
 			{ $( $on_finalize )* }
 			{ $( $offchain )* }
 			{ $( $constants )* }
@@ -635,6 +717,7 @@ macro_rules! decl_module {
 		{ $( $other_where_bounds:tt )* }
 		{ $( $deposit_event:tt )* }
 		{ $( $on_initialize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $on_finalize:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
@@ -660,6 +743,7 @@ macro_rules! decl_module {
 		{ $( $other_where_bounds:tt )* }
 		{ $( $deposit_event:tt )* }
 		{ $( $on_initialize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $on_finalize:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
@@ -685,6 +769,7 @@ macro_rules! decl_module {
 		{ $( $other_where_bounds:tt )* }
 		{ $( $deposit_event:tt )* }
 		{ $( $on_initialize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $on_finalize:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
@@ -711,6 +796,7 @@ macro_rules! decl_module {
 		{ $( $other_where_bounds:tt )* }
 		{ $( $deposit_event:tt )* }
 		{ $( $on_initialize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $on_finalize:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
@@ -726,6 +812,7 @@ macro_rules! decl_module {
 			{ $( $other_where_bounds )* }
 			{ $( $deposit_event )* }
 			{ $( $on_initialize )* }
+			{ $( $on_runtime_upgrade )* }
 			{ $( $on_finalize )* }
 			{ $( $offchain )* }
 			{ $( $constants )* }
@@ -803,6 +890,29 @@ macro_rules! decl_module {
 		{}
 	};
 
+	(@impl_on_runtime_upgrade
+		$module:ident<$trait_instance:ident: $trait_name:ident$(<I>, $instance:ident: $instantiable:path)?>;
+		{ $( $other_where_bounds:tt )* }
+		fn on_runtime_upgrade() -> $return:ty { $( $impl:tt )* }
+	) => {
+		impl<$trait_instance: $trait_name$(<I>, $instance: $instantiable)?>
+			$crate::sr_primitives::traits::OnRuntimeUpgrade
+			for $module<$trait_instance$(, $instance)?> where $( $other_where_bounds )*
+		{
+			fn on_runtime_upgrade() -> $return { $( $impl )* }
+		}
+	};
+
+	(@impl_on_runtime_upgrade
+		$module:ident<$trait_instance:ident: $trait_name:ident$(<I>, $instance:ident: $instantiable:path)?>;
+		{ $( $other_where_bounds:tt )* }
+	) => {
+		impl<$trait_instance: $trait_name$(<I>, $instance: $instantiable)?>
+			$crate::sr_primitives::traits::OnRuntimeUpgrade
+			for $module<$trait_instance$(, $instance)?> where $( $other_where_bounds )*
+		{}
+	};
+
 	(@impl_on_finalize
 		$module:ident<$trait_instance:ident: $trait_name:ident$(<I>, $instance:ident: $instantiable:path)?>;
 		{ $( $other_where_bounds:tt )* }
@@ -1054,6 +1164,7 @@ macro_rules! decl_module {
 		{ $( $other_where_bounds:tt )* }
 		{ $( $deposit_event:tt )* }
 		{ $( $on_initialize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $on_finalize:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
@@ -1076,6 +1187,13 @@ macro_rules! decl_module {
 			$( $on_initialize )*
 		}
 
+		$crate::decl_module! {
+			@impl_on_runtime_upgrade
+			$mod_type<$trait_instance: $trait_name $(<I>, $instance: $instantiable)?>;
+			{ $( $other_where_bounds )* }
+			$( $on_runtime_upgrade )*
+		}
+
 		$crate::decl_module! {
 			@impl_on_finalize
 			$mod_type<$trait_instance: $trait_name $(<I>, $instance: $instantiable)?>;
@@ -1306,6 +1424,7 @@ pub trait IsSubType<T: Callable<R>, R> {
 #[macro_export]
 macro_rules! impl_outer_dispatch {
 	(
+		$( @$type_info:ident )?
 		$(#[$attr:meta])*
 		pub enum $call_type:ident for $runtime:ident where origin: $origin:ty {
 			$(
@@ -1369,6 +1488,40 @@ macro_rules! impl_outer_dispatch {
 				}
 			}
 		)*
+		$crate::impl_outer_dispatch! {
+			@TYPE_INFO
+			$( $type_info )?;
+			$call_type;
+			$runtime;
+			$( $camelcase ),*
+		}
+	};
+	(@TYPE_INFO
+		;
+		$( $rest:tt )*
+	) => {};
+	(@TYPE_INFO
+		type_info;
+		$call_type:ident;
+		$runtime:ident;
+		$( $camelcase:ident ),*
+	) => {
+		impl $crate::metadata::TypeInfo for $call_type {
+			fn path() -> $crate::dispatch::Vec<&'static str> {
+				module_path!().split("::").chain($crate::rstd::iter::once(stringify!($call_type))).collect()
+			}
+
+			fn type_def(registry: &mut $crate::metadata::Registry) -> $crate::metadata::TypeDef {
+				$crate::metadata::TypeDef::Variant($crate::metadata::outer_variants(<[_]>::to_vec(&[
+					$(
+						(
+							stringify!($camelcase),
+							registry.register::<$crate::dispatch::CallableCallFor<$camelcase, $runtime>>(),
+						),
+					)*
+				])))
+			}
+		}
 	};
 	(@DISPATCH_MATCH
 		$self:ident
@@ -1416,14 +1569,80 @@ macro_rules! __dispatch_impl_metadata {
 	(
 		$mod_type:ident<$trait_instance:ident: $trait_name:ident$(<I>, $instance:ident: $instantiable:path)?>
 		{ $( $other_where_bounds:tt )* }
-		$($rest:tt)*
+		$call_type:ident $origin_type:ty
+		{
+			$(
+				$(#[doc = $doc_attr:tt])*
+				fn $fn_name:ident($from:ident $(, $(#[$codec_attr:ident])* $param_name:ident : $param:ty )*);
+			)*
+		}
 	) => {
 		impl<$trait_instance: $trait_name $(<I>, $instance: $instantiable)?> $mod_type<$trait_instance $(, $instance)?>
 			where $( $other_where_bounds )*
 		{
 			#[doc(hidden)]
 			pub fn call_functions() -> &'static [$crate::dispatch::FunctionMetadata] {
-				$crate::__call_to_functions!($($rest)*)
+				$crate::__call_to_functions!(
+					$call_type $origin_type
+					{
+						$(
+							$(#[doc = $doc_attr])*
+							fn $fn_name($from $(, $(#[$codec_attr])* $param_name : $param )*);
+						)*
+					}
+				)
+			}
+		}
+
+		impl<$trait_instance: $trait_name $(<I>, $instance: $instantiable)?> $mod_type<$trait_instance $(, $instance)?>
+			where
+				$( $( $param: $crate::metadata::TypeInfo, )* )*
+				$trait_instance: 'static, $( $instance: 'static, )?
+				$( $other_where_bounds )*
+		{
+			#[doc(hidden)]
+			pub fn call_functions_v9(
+				registry: &mut $crate::metadata::Registry,
+			) -> $crate::dispatch::Vec<$crate::metadata::FunctionMetadataV9> {
+				let _ = &registry;
+				<[_]>::to_vec(&[
+					$(
+						$crate::metadata::FunctionMetadataV9 {
+							name: $crate::dispatch::DecodeDifferent::Encode(stringify!($fn_name)),
+							arguments: <[_]>::to_vec(&[
+								$(
+									$crate::metadata::FunctionArgumentMetadataV9 {
+										name: $crate::dispatch::DecodeDifferent::Encode(stringify!($param_name)),
+										ty: $crate::__function_to_metadata!(@register
+											registry; $(#[$codec_attr])* $param_name: $param
+										),
+									}
+								),*
+							]),
+							documentation: <[_]>::to_vec(&[
+								$( $crate::dispatch::DecodeDifferent::Encode($doc_attr) ),*
+							]),
+						}
+					),*
+				])
+			}
+		}
+
+		impl<$trait_instance: $trait_name $(<I>, $instance: $instantiable)?> $crate::metadata::TypeInfo
+			for $call_type<$trait_instance $(, $instance)?>
+			where
+				$( $( $param: $crate::metadata::TypeInfo, )* )*
+				$trait_instance: 'static, $( $instance: 'static, )?
+				$( $other_where_bounds )*
+		{
+			fn path() -> $crate::dispatch::Vec<&'static str> {
+				module_path!().split("::").chain($crate::rstd::iter::once(stringify!($call_type))).collect()
+			}
+
+			fn type_def(registry: &mut $crate::metadata::Registry) -> $crate::metadata::TypeDef {
+				$crate::metadata::TypeDef::Variant($crate::metadata::call_variants(
+					$mod_type::<$trait_instance $(, $instance)?>::call_functions_v9(registry)
+				))
 			}
 		}
 	}
@@ -1548,6 +1767,34 @@ macro_rules! __impl_module_constants_metadata {
 				]
 			}
 		}
+
+		impl<$trait_instance: 'static + $trait_name $(<I>, $instance: $instantiable)?>
+			$mod_type<$trait_instance $(, $instance)?>
+			where $( $type: $crate::metadata::TypeInfo, )* $( $other_where_bounds )*
+		{
+			#[doc(hidden)]
+			pub fn module_constants_metadata_v9(
+				registry: &mut $crate::metadata::Registry,
+			) -> $crate::dispatch::Vec<$crate::metadata::ModuleConstantMetadataV9> {
+				let _ = &registry;
+				let types: $crate::dispatch::Vec<$crate::metadata::TypeId> =
+					<[_]>::to_vec(&[ $( registry.register::<$type>() ),* ]);
+				let documentation: &[&[&'static str]] = &[ $( &[ $( $doc_attr ),* ] ),* ];
+
+				Self::module_constants_metadata().iter()
+					.zip(types)
+					.zip(documentation)
+					.map(|((constant, ty), documentation)| $crate::metadata::ModuleConstantMetadataV9 {
+						name: constant.name.clone(),
+						ty,
+						value: constant.value.clone(),
+						documentation: documentation.iter()
+							.map(|doc| $crate::dispatch::DecodeDifferent::Encode(*doc))
+							.collect(),
+					})
+					.collect()
+			}
+		}
 	}
 }
 
@@ -1644,6 +1891,12 @@ macro_rules! __function_to_metadata {
 
 	(@stringify_expand_attr $param_name:ident : $param:ty) => { stringify!($param) };
 
+	(@register $registry:ident; #[compact] $param_name:ident : $param:ty) => {
+		$registry.register_compact::<$param>()
+	};
+
+	(@register $registry:ident; $param_name:ident : $param:ty) => { $registry.register::<$param>() };
+
 	(@stringify_expand_attr $(#[codec_attr:ident])* $param_name:ident : $param:ty) => {
 		compile_error!(concat!(
 			"Invalid attribute for parameter `", stringify!($param_name),
@@ -1673,6 +1926,9 @@ macro_rules! __check_reserved_fn_name {
 	(offchain_worker $( $rest:ident )*) => {
 		$crate::__check_reserved_fn_name!(@compile_error offchain_worker);
 	};
+	(on_runtime_upgrade $( $rest:ident )*) => {
+		$crate::__check_reserved_fn_name!(@compile_error on_runtime_upgrade);
+	};
 	($t:ident $( $rest:ident )*) => {
 		$crate::__check_reserved_fn_name!($( $rest )*);
 	};
@@ -1706,8 +1962,8 @@ macro_rules! __check_reserved_fn_name {
 #[allow(dead_code)]
 mod tests {
 	use super::*;
-	use crate::sr_primitives::traits::{OnInitialize, OnFinalize};
-	use sr_primitives::weights::{DispatchInfo, DispatchClass};
+	use crate::sr_primitives::traits::{OnInitialize, OnFinalize, OnRuntimeUpgrade};
+	use sr_primitives::weights::{DispatchInfo, DispatchClass, Weight};
 
 	pub trait Trait: system::Trait + Sized where Self::AccountId: From<u32> {
 		type Origin;
@@ -1739,6 +1995,7 @@ mod tests {
 			fn aux_5(_origin, _data: i32, #[compact] _data2: u32,) -> Result { unreachable!() }
 
 			fn on_initialize(n: T::BlockNumber,) { if n.into() == 42 { panic!("on_initialize") } }
+			fn on_runtime_upgrade() -> Weight { 10 }
 			fn on_finalize(n: T::BlockNumber) { if n.into() == 42 { panic!("on_finalize") } }
 			fn offchain_worker() {}
 
@@ -1875,6 +2132,11 @@ mod tests {
 		<Module<TraitImpl> as OnInitialize<u32>>::on_initialize(42);
 	}
 
+	#[test]
+	fn on_runtime_upgrade_should_work() {
+		assert_eq!(<Module<TraitImpl> as OnRuntimeUpgrade>::on_runtime_upgrade(), 10);
+	}
+
 	#[test]
 	#[should_panic(expected = "on_finalize")]
 	fn on_finalize_should_work() {
@@ -17,7 +17,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
-pub use srml_metadata::{EventMetadata, DecodeDifferent, OuterEventMetadata, FnEncode};
+pub use srml_metadata::{EventMetadata, EventMetadataV9, DecodeDifferent, OuterEventMetadata, FnEncode};
 
 /// Implement the `Event` for a module.
 ///
@@ -144,6 +144,23 @@ macro_rules! decl_event {
 			pub fn metadata() -> &'static [ $crate::event::EventMetadata ] {
 				$crate::__events_to_metadata!(; $( $events )* )
 			}
+
+			#[allow(dead_code)]
+			#[doc(hidden)]
+			pub fn metadata_v9(
+				registry: &mut $crate::metadata::Registry,
+			) -> $crate::rstd::vec::Vec<$crate::event::EventMetadataV9> {
+				$crate::__events_to_metadata_v9!(registry;; $( $events )* )
+			}
+		}
+		impl $crate::metadata::TypeInfo for Event {
+			fn path() -> $crate::rstd::vec::Vec<&'static str> {
+				module_path!().split("::").chain($crate::rstd::iter::once("Event")).collect()
+			}
+
+			fn type_def(registry: &mut $crate::metadata::Registry) -> $crate::metadata::TypeDef {
+				$crate::metadata::TypeDef::Variant($crate::metadata::event_variants(Self::metadata_v9(registry)))
+			}
 		}
 	}
 }
@@ -292,6 +309,35 @@ macro_rules! __decl_generic_event {
 				$crate::__events_to_metadata!(; $( $events )* )
 			}
 		}
+		impl<$( $generic_param ),* $(, $instance)?> RawEvent<$( $generic_param ),* $(, $instance)?>
+			where $( $generic_param: $crate::metadata::TypeInfo, )* $( $instance: 'static )?
+		{
+			#[allow(dead_code)]
+			#[doc(hidden)]
+			pub fn metadata_v9(
+				registry: &mut $crate::metadata::Registry,
+			) -> $crate::rstd::vec::Vec<$crate::event::EventMetadataV9> {
+				$crate::__events_to_metadata_v9!(registry;; $( $events )* )
+			}
+		}
+		impl<$( $generic_param ),* $(, $instance)?> $crate::metadata::TypeInfo
+			for RawEvent<$( $generic_param ),* $(, $instance)?>
+			where $( $generic_param: $crate::metadata::TypeInfo, )* $( $instance: 'static )?
+		{
+			fn path() -> $crate::rstd::vec::Vec<&'static str> {
+				module_path!().split("::").chain($crate::rstd::iter::once("RawEvent")).collect()
+			}
+
+			fn params(
+				registry: &mut $crate::metadata::Registry,
+			) -> $crate::rstd::vec::Vec<$crate::metadata::TypeId> {
+				<[_]>::to_vec(&[ $( registry.register::<$generic_param>() ),* ])
+			}
+
+			fn type_def(registry: &mut $crate::metadata::Registry) -> $crate::metadata::TypeDef {
+				$crate::metadata::TypeDef::Variant($crate::metadata::event_variants(Self::metadata_v9(registry)))
+			}
+		}
 	};
 	(@cannot_parse $ty:ty) => {
 		compile_error!(concat!("The type `", stringify!($ty), "` can't be parsed as an unnamed one, please name it `Name = ", stringify!($ty), "`"));
@@ -328,6 +374,39 @@ macro_rules! __events_to_metadata {
 	}
 }
 
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __events_to_metadata_v9 {
+	(
+		$registry:ident;
+		$( $metadata:expr ),*;
+		$( #[doc = $doc_attr:tt] )*
+		$event:ident $( ( $( $param:path ),* ) )*,
+		$( $rest:tt )*
+	) => {
+		$crate::__events_to_metadata_v9!(
+			$registry;
+			$( $metadata, )*
+			$crate::event::EventMetadataV9 {
+				name: $crate::event::DecodeDifferent::Encode(stringify!($event)),
+				arguments: <[_]>::to_vec(&[
+					$( $( $registry.register::<$param>() ),* )*
+				]),
+				documentation: <[_]>::to_vec(&[
+					$( $crate::event::DecodeDifferent::Encode($doc_attr) ),*
+				]),
+			};
+			$( $rest )*
+		)
+	};
+	(
+		$registry:ident;
+		$( $metadata:expr ),*;
+	) => {
+		<[_]>::to_vec(&[ $( $metadata ),* ])
+	}
+}
+
 /// Constructs an Event type for a runtime. This is usually called automatically by the
 /// construct_runtime macro.
 #[macro_export]
@@ -335,6 +414,7 @@ macro_rules! impl_outer_event {
 	// Macro transformations (to convert invocations with incomplete parameters to the canonical
 	// form)
 	(
+		$( @$type_info:ident )?
 		$(#[$attr:meta])*
 		pub enum $name:ident for $runtime:ident {
 			$( $rest_event_without_system:tt )*
@@ -345,11 +425,13 @@ macro_rules! impl_outer_event {
 			$name;
 			$runtime;
 			system;
+			{ $( $type_info )? };
 			Modules { $( $rest_event_without_system )* };
 			;
 		);
 	};
 	(
+		$( @$type_info:ident )?
 		$(#[$attr:meta])*
 		pub enum $name:ident for $runtime:ident where system = $system:ident {
 			$( $rest_event_with_system:tt )*
@@ -360,6 +442,7 @@ macro_rules! impl_outer_event {
 			$name;
 			$runtime;
 			$system;
+			{ $( $type_info )? };
 			Modules { $( $rest_event_with_system )* };
 			;
 		);
@@ -370,6 +453,7 @@ macro_rules! impl_outer_event {
 		$name:ident;
 		$runtime:ident;
 		$system:ident;
+		{ $( $type_info:ident )? };
 		Modules {
 			$module:ident $instance:ident<T>,
 			$( $rest_event_generic_instance:tt )*
@@ -381,6 +465,7 @@ macro_rules! impl_outer_event {
 			$name;
 			$runtime;
 			$system;
+			{ $( $type_info )? };
 			Modules { $( $rest_event_generic_instance )* };
 			$( $module_name::Event $( <$generic_param> )? $( { $generic_instance } )?, )* $module::Event<$runtime>{ $instance },;
 		);
@@ -391,6 +476,7 @@ macro_rules! impl_outer_event {
 		$name:ident;
 		$runtime:ident;
 		$system:ident;
+		{ $( $type_info:ident )? };
 		Modules {
 			$module:ident $instance:ident,
 			$( $rest_event_instance:tt )*
@@ -402,6 +488,7 @@ macro_rules! impl_outer_event {
 			$name;
 			$runtime;
 			$system;
+			{ $( $type_info )? };
 			Modules { $( $rest_event_instance )* };
 			$( $module_name::Event $( <$generic_param> )* $( { $generic_instance } )?, )* $module::Event { $instance },;
 		);
@@ -412,6 +499,7 @@ macro_rules! impl_outer_event {
 		$name:ident;
 		$runtime:ident;
 		$system:ident;
+		{ $( $type_info:ident )? };
 		Modules {
 			$module:ident<T>,
 			$( $rest_event_generic:tt )*
@@ -423,6 +511,7 @@ macro_rules! impl_outer_event {
 			$name;
 			$runtime;
 			$system;
+			{ $( $type_info )? };
 			Modules { $( $rest_event_generic )* };
 			$( $module_name::Event $( <$generic_param> )? $( { $generic_instance } )?, )* $module::Event<$runtime>,;
 		);
@@ -433,6 +522,7 @@ macro_rules! impl_outer_event {
 		$name:ident;
 		$runtime:ident;
 		$system:ident;
+		{ $( $type_info:ident )? };
 		Modules {
 			$module:ident,
 			$( $rest_event_no_generic_no_instance:tt )*
@@ -444,6 +534,7 @@ macro_rules! impl_outer_event {
 			$name;
 			$runtime;
 			$system;
+			{ $( $type_info )? };
 			Modules { $( $rest_event_no_generic_no_instance )* };
 			$( $module_name::Event $( <$generic_param> )? $( { $generic_instance } )?, )* $module::Event,;
 		);
@@ -455,6 +546,7 @@ macro_rules! impl_outer_event {
 		$name:ident;
 		$runtime:ident;
 		$system:ident;
+		{ $( $type_info:ident )? };
 		Modules {};
 		$( $module_name:ident::Event $( <$generic_param:ident> )? $( { $generic_instance:ident } )?, )*;
 	) => {
@@ -512,6 +604,17 @@ macro_rules! impl_outer_event {
 				$( $generic_instance )?,
 			)*;
 		);
+		$crate::__impl_outer_event_type_info!(
+			$( $type_info )?;
+			$runtime;
+			$name;
+			$system;
+			$(
+				$module_name::Event
+				< $( $generic_param )? $(, $module_name::$generic_instance )? >
+				$( $generic_instance )?,
+			)*;
+		);
 	}
 }
 
@@ -561,6 +664,60 @@ macro_rules! __impl_outer_event_json_metadata {
 	}
 }
 
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __impl_outer_event_type_info {
+	(
+		;
+		$( $rest:tt )*
+	) => {};
+	(
+		type_info;
+		$runtime:ident;
+		$event_name:ident;
+		$system:ident;
+		$( $module_name:ident::Event < $( $generic_params:path ),* > $( $instance:ident )?, )*;
+	) => {
+		impl $crate::metadata::TypeInfo for $event_name {
+			fn path() -> $crate::rstd::vec::Vec<&'static str> {
+				module_path!().split("::").chain($crate::rstd::iter::once(stringify!($event_name))).collect()
+			}
+
+			fn type_def(registry: &mut $crate::metadata::Registry) -> $crate::metadata::TypeDef {
+				$crate::metadata::TypeDef::Variant($crate::metadata::outer_variants(<[_]>::to_vec(&[
+					("system", registry.register::<$system::Event>()),
+					$(
+						(
+							concat!(stringify!($module_name) $(, "_", stringify!($instance) )?),
+							registry.register::<$module_name::Event < $( $generic_params ),* >>(),
+						),
+					)*
+				])))
+			}
+		}
+
+		impl $runtime {
+			#[allow(dead_code)]
+			pub fn __module_events_v9_system(
+				registry: &mut $crate::metadata::Registry,
+			) -> $crate::rstd::vec::Vec<$crate::event::EventMetadataV9> {
+				$system::Event::metadata_v9(registry)
+			}
+
+			$crate::paste::item! {
+				$(
+					#[allow(dead_code)]
+					pub fn [< __module_events_v9_ $module_name $( _ $instance )? >](
+						registry: &mut $crate::metadata::Registry,
+					) -> $crate::rstd::vec::Vec<$crate::event::EventMetadataV9> {
+						$module_name::Event ::< $( $generic_params ),* > ::metadata_v9(registry)
+					}
+				)*
+			}
+		}
+	}
+}
+
 #[cfg(test)]
 #[allow(dead_code)]
 mod tests {
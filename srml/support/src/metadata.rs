@@ -17,8 +17,44 @@
 pub use srml_metadata::{
 	DecodeDifferent, FnEncode, RuntimeMetadata, ModuleMetadata, RuntimeMetadataLastVersion,
 	DefaultByteGetter, RuntimeMetadataPrefixed, StorageEntryMetadata, StorageMetadata,
-	StorageEntryType, StorageEntryModifier, DefaultByte, StorageHasher, ModuleErrorMetadata
+	StorageEntryType, StorageEntryModifier, DefaultByte, StorageHasher, ModuleErrorMetadata,
+	TypeInfo, Registry, Type, TypeDef, TypeDefPrimitive, TypeId, Field, Variant,
+	ModuleMetadataV9, StorageMetadataV9, StorageEntryMetadataV9, StorageEntryTypeV9,
+	FunctionMetadataV9, FunctionArgumentMetadataV9, EventMetadataV9, ModuleConstantMetadataV9,
 };
+#[doc(hidden)]
+pub use srml_metadata::rstd;
+use crate::rstd::prelude::*;
+
+/// The variants of a module event enum, indexed by position like the codec derive does.
+#[doc(hidden)]
+pub fn event_variants(events: Vec<EventMetadataV9>) -> Vec<Variant> {
+	events.into_iter().enumerate().map(|(index, event)| Variant {
+		name: event.name,
+		index: index as u8,
+		fields: event.arguments.into_iter().map(Field::unnamed).collect(),
+	}).collect()
+}
+
+/// The variants of a module call enum, indexed by position like the codec derive does.
+#[doc(hidden)]
+pub fn call_variants(calls: Vec<FunctionMetadataV9>) -> Vec<Variant> {
+	calls.into_iter().enumerate().map(|(index, call)| Variant {
+		name: call.name,
+		index: index as u8,
+		fields: call.arguments.into_iter()
+			.map(|argument| Field { name: Some(argument.name), ty: argument.ty })
+			.collect(),
+	}).collect()
+}
+
+/// The variants of an outer enum wrapping one type per module, indexed by position.
+#[doc(hidden)]
+pub fn outer_variants(modules: Vec<(&'static str, TypeId)>) -> Vec<Variant> {
+	modules.into_iter().enumerate()
+		.map(|(index, (name, ty))| Variant::new(name, index as u8, vec![Field::unnamed(ty)]))
+		.collect()
+}
 
 /// Implements the metadata support for the given runtime and all its modules.
 ///
@@ -62,9 +98,9 @@ macro_rules! impl_runtime_metadata {
 	) => {
 		impl $runtime {
 			pub fn metadata() -> $crate::metadata::RuntimeMetadataPrefixed {
-				$crate::metadata::RuntimeMetadataLastVersion {
-						modules: $crate::__runtime_modules_to_metadata!($runtime;; $( $rest )*),
-				}.into()
+				let mut registry = $crate::metadata::Registry::new();
+				let modules = $crate::__runtime_modules_to_metadata!($runtime; registry;; $( $rest )*);
+				$crate::metadata::RuntimeMetadataLastVersion::new(registry, modules).into()
 			}
 		}
 	}
@@ -75,42 +111,44 @@ macro_rules! impl_runtime_metadata {
 macro_rules! __runtime_modules_to_metadata {
 	(
 		$runtime: ident;
+		$registry: ident;
 		$( $metadata:expr ),*;
 		$mod:ident::$module:ident $( < $instance:ident > )? as $name:ident $(with)+ $($kw:ident)*,
 		$( $rest:tt )*
 	) => {
 		$crate::__runtime_modules_to_metadata!(
 			$runtime;
-			$( $metadata, )* $crate::metadata::ModuleMetadata {
+			$registry;
+			$( $metadata, )* $crate::metadata::ModuleMetadataV9 {
 				name: $crate::metadata::DecodeDifferent::Encode(stringify!($name)),
 				storage: $crate::__runtime_modules_to_metadata_calls_storage!(
-					$mod, $module $( <$instance> )?, $runtime, $(with $kw)*
+					$mod, $module $( <$instance> )?, $runtime, $registry, $(with $kw)*
 				),
 				calls: $crate::__runtime_modules_to_metadata_calls_call!(
-					$mod, $module $( <$instance> )?, $runtime, $(with $kw)*
+					$mod, $module $( <$instance> )?, $runtime, $registry, $(with $kw)*
 				),
 				event: $crate::__runtime_modules_to_metadata_calls_event!(
-					$mod, $module $( <$instance> )?, $runtime, $(with $kw)*
+					$mod, $module $( <$instance> )?, $runtime, $registry, $(with $kw)*
 				),
-				constants: $crate::metadata::DecodeDifferent::Encode(
-					$crate::metadata::FnEncode(
-						$mod::$module::<$runtime $(, $mod::$instance )?>::module_constants_metadata
-					)
+				constants: $mod::$module::<$runtime $(, $mod::$instance )?>::module_constants_metadata_v9(
+					&mut $registry
+				),
+				errors: <[_]>::to_vec(
+					<
+						$mod::$module::<$runtime $(, $mod::$instance )?>
+						as $crate::metadata::ModuleErrorMetadata
+					>::metadata()
 				),
-				errors: $crate::metadata::DecodeDifferent::Encode(
-					$crate::metadata::FnEncode(
-						<$mod::$module::<$runtime $(, $mod::$instance )?> as $crate::metadata::ModuleErrorMetadata>::metadata
-					)
-				)
 			};
 			$( $rest )*
 		)
 	};
 	(
 		$runtime:ident;
+		$registry: ident;
 		$( $metadata:expr ),*;
 	) => {
-		$crate::metadata::DecodeDifferent::Encode(&[ $( $metadata ),* ])
+		<[_]>::to_vec(&[ $( $metadata ),* ])
 	};
 }
 
@@ -121,30 +159,29 @@ macro_rules! __runtime_modules_to_metadata_calls_call {
 		$mod: ident,
 		$module: ident $( <$instance:ident> )?,
 		$runtime: ident,
+		$registry: ident,
 		with Call
 		$(with $kws:ident)*
 	) => {
-		Some($crate::metadata::DecodeDifferent::Encode(
-			$crate::metadata::FnEncode(
-				$mod::$module::<$runtime $(, $mod::$instance )?>::call_functions
-			)
-		))
+		Some($mod::$module::<$runtime $(, $mod::$instance )?>::call_functions_v9(&mut $registry))
 	};
 	(
 		$mod: ident,
 		$module: ident $( <$instance:ident> )?,
 		$runtime: ident,
+		$registry: ident,
 		with $_:ident
 		$(with $kws:ident)*
 	) => {
 		$crate::__runtime_modules_to_metadata_calls_call! {
-			$mod, $module $( <$instance> )?, $runtime, $(with $kws)*
+			$mod, $module $( <$instance> )?, $runtime, $registry, $(with $kws)*
 		};
 	};
 	(
 		$mod: ident,
 		$module: ident $( <$instance:ident> )?,
 		$runtime: ident,
+		$registry: ident,
 	) => {
 		None
 	};
@@ -158,30 +195,31 @@ macro_rules! __runtime_modules_to_metadata_calls_event {
 		$mod: ident,
 		$module: ident $( <$instance:ident> )?,
 		$runtime: ident,
+		$registry: ident,
 		with Event
 		$(with $kws:ident)*
 	) => {
-		Some($crate::metadata::DecodeDifferent::Encode(
-			$crate::metadata::FnEncode(
-				$crate::paste::expr!{
-					$runtime:: [< __module_events_ $mod $(_ $instance)?>]
-				}
-			)
-		))
+		Some($crate::paste::expr!{
+			$runtime:: [< __module_events_v9_ $mod $(_ $instance)?>](&mut $registry)
+		})
 	};
 	(
 		$mod: ident,
 		$module: ident $( <$instance:ident> )?,
 		$runtime: ident,
+		$registry: ident,
 		with $_:ident
 		$(with $kws:ident)*
 	) => {
-		$crate::__runtime_modules_to_metadata_calls_event!( $mod, $module $( <$instance> )?, $runtime, $(with $kws)* );
+		$crate::__runtime_modules_to_metadata_calls_event!(
+			$mod, $module $( <$instance> )?, $runtime, $registry, $(with $kws)*
+		);
 	};
 	(
 		$mod: ident,
 		$module: ident $( <$instance:ident> )?,
 		$runtime: ident,
+		$registry: ident,
 	) => {
 		None
 	};
@@ -194,30 +232,29 @@ macro_rules! __runtime_modules_to_metadata_calls_storage {
 		$mod: ident,
 		$module: ident $( <$instance:ident> )?,
 		$runtime: ident,
+		$registry: ident,
 		with Storage
 		$(with $kws:ident)*
 	) => {
-		Some($crate::metadata::DecodeDifferent::Encode(
-			$crate::metadata::FnEncode(
-				$mod::$module::<$runtime $(, $mod::$instance )?>::storage_metadata
-			)
-		))
+		Some($mod::$module::<$runtime $(, $mod::$instance )?>::storage_metadata_v9(&mut $registry))
 	};
 	(
 		$mod: ident,
 		$module: ident $( <$instance:ident> )?,
 		$runtime: ident,
+		$registry: ident,
 		with $_:ident
 		$(with $kws:ident)*
 	) => {
 		$crate::__runtime_modules_to_metadata_calls_storage! {
-			$mod, $module $( <$instance> )?, $runtime, $(with $kws)*
+			$mod, $module $( <$instance> )?, $runtime, $registry, $(with $kws)*
 		};
 	};
 	(
 		$mod: ident,
 		$module: ident $( <$instance:ident> )?,
 		$runtime: ident,
+		$registry: ident,
 	) => {
 		None
 	};
@@ -229,11 +266,7 @@ macro_rules! __runtime_modules_to_metadata_calls_storage {
 #[allow(dead_code)]
 mod tests {
 	use super::*;
-	use srml_metadata::{
-		EventMetadata, StorageEntryModifier, StorageEntryType, FunctionMetadata, StorageEntryMetadata,
-		ModuleMetadata, RuntimeMetadataPrefixed, DefaultByte, ModuleConstantMetadata, DefaultByteGetter,
-		ErrorMetadata,
-	};
+	use srml_metadata::ErrorMetadata;
 	use codec::{Encode, Decode};
 	use crate::traits::Get;
 
@@ -354,6 +387,7 @@ mod tests {
 	pub struct TestRuntime;
 
 	impl_outer_event! {
+		@type_info
 		pub enum TestEvent for TestRuntime {
 			event_module<T>,
 			event_module2<T>,
@@ -365,6 +399,7 @@ mod tests {
 	}
 
 	impl_outer_dispatch! {
+		@type_info
 		pub enum Call for TestRuntime where origin: Origin {
 			event_module::EventModule,
 			event_module2::EventModule2,
@@ -422,73 +457,64 @@ mod tests {
 		}
 	}
 
-	const EXPECTED_METADATA: RuntimeMetadataLastVersion = RuntimeMetadataLastVersion {
-		modules: DecodeDifferent::Encode(&[
-			ModuleMetadata {
+	fn expected_metadata() -> RuntimeMetadataLastVersion {
+		let mut registry = Registry::new();
+		let u32_ty = registry.register::<u32>();
+		let u64_ty = registry.register::<u64>();
+
+		RuntimeMetadataLastVersion::new(registry, vec![
+			ModuleMetadataV9 {
 				name: DecodeDifferent::Encode("System"),
 				storage: None,
 				calls: None,
-				event: Some(DecodeDifferent::Encode(
-					FnEncode(||&[
-						EventMetadata {
-							name: DecodeDifferent::Encode("SystemEvent"),
-							arguments: DecodeDifferent::Encode(&[]),
-							documentation: DecodeDifferent::Encode(&[])
-						}
-					])
-				)),
-				constants: DecodeDifferent::Encode(
-					FnEncode(|| &[
-						ModuleConstantMetadata {
-							name: DecodeDifferent::Encode("BlockNumber"),
-							ty: DecodeDifferent::Encode("T::BlockNumber"),
-							value: DecodeDifferent::Encode(
-								DefaultByteGetter(&ConstantBlockNumberByteGetter)
-							),
-							documentation: DecodeDifferent::Encode(&[" Hi, I am a comment."]),
-						},
-						ModuleConstantMetadata {
-							name: DecodeDifferent::Encode("GetType"),
-							ty: DecodeDifferent::Encode("T::AccountId"),
-							value: DecodeDifferent::Encode(
-								DefaultByteGetter(&ConstantGetTypeByteGetter)
-							),
-							documentation: DecodeDifferent::Encode(&[]),
-						},
-						ModuleConstantMetadata {
-							name: DecodeDifferent::Encode("ASSOCIATED_CONST"),
-							ty: DecodeDifferent::Encode("u64"),
-							value: DecodeDifferent::Encode(
-								DefaultByteGetter(&ConstantAssociatedConstByteGetter)
-							),
-							documentation: DecodeDifferent::Encode(&[]),
-						}
-					])
-				),
-				errors: DecodeDifferent::Encode(FnEncode(|| &[])),
+				event: Some(vec![
+					EventMetadataV9 {
+						name: DecodeDifferent::Encode("SystemEvent"),
+						arguments: vec![],
+						documentation: vec![],
+					}
+				]),
+				constants: vec![
+					ModuleConstantMetadataV9 {
+						name: DecodeDifferent::Encode("BlockNumber"),
+						ty: u32_ty,
+						value: DecodeDifferent::Encode(DefaultByteGetter(&ConstantBlockNumberByteGetter)),
+						documentation: vec![DecodeDifferent::Encode(" Hi, I am a comment.")],
+					},
+					ModuleConstantMetadataV9 {
+						name: DecodeDifferent::Encode("GetType"),
+						ty: u32_ty,
+						value: DecodeDifferent::Encode(DefaultByteGetter(&ConstantGetTypeByteGetter)),
+						documentation: vec![],
+					},
+					ModuleConstantMetadataV9 {
+						name: DecodeDifferent::Encode("ASSOCIATED_CONST"),
+						ty: u64_ty,
+						value: DecodeDifferent::Encode(DefaultByteGetter(&ConstantAssociatedConstByteGetter)),
+						documentation: vec![],
+					}
+				],
+				errors: vec![],
 			},
-			ModuleMetadata {
+			ModuleMetadataV9 {
 				name: DecodeDifferent::Encode("Module"),
 				storage: None,
-				calls: Some(
-					DecodeDifferent::Encode(FnEncode(|| &[
-						FunctionMetadata {
-							name: DecodeDifferent::Encode("aux_0"),
-							arguments: DecodeDifferent::Encode(&[]),
-							documentation: DecodeDifferent::Encode(&[]),
-						}
-					]))),
-				event: Some(DecodeDifferent::Encode(
-					FnEncode(||&[
-						EventMetadata {
-							name: DecodeDifferent::Encode("TestEvent"),
-							arguments: DecodeDifferent::Encode(&["Balance"]),
-							documentation: DecodeDifferent::Encode(&[" Hi, I am a comment."])
-						}
-					])
-				)),
-				constants: DecodeDifferent::Encode(FnEncode(|| &[])),
-				errors: DecodeDifferent::Encode(FnEncode(|| &[
+				calls: Some(vec![
+					FunctionMetadataV9 {
+						name: DecodeDifferent::Encode("aux_0"),
+						arguments: vec![],
+						documentation: vec![],
+					}
+				]),
+				event: Some(vec![
+					EventMetadataV9 {
+						name: DecodeDifferent::Encode("TestEvent"),
+						arguments: vec![u32_ty],
+						documentation: vec![DecodeDifferent::Encode(" Hi, I am a comment.")],
+					}
+				]),
+				constants: vec![],
+				errors: vec![
 					ErrorMetadata {
 						name: DecodeDifferent::Encode("UserInputError"),
 						documentation: DecodeDifferent::Encode(&[" Some user input error"]),
@@ -500,54 +526,65 @@ mod tests {
 							" this could be due to many reasons",
 						]),
 					},
-				])),
+				],
 			},
-			ModuleMetadata {
+			ModuleMetadataV9 {
 				name: DecodeDifferent::Encode("Module2"),
-				storage: Some(DecodeDifferent::Encode(
-					FnEncode(|| StorageMetadata {
-						prefix: DecodeDifferent::Encode("TestStorage"),
-						entries: DecodeDifferent::Encode(
-							&[
-								StorageEntryMetadata {
-									name: DecodeDifferent::Encode("StorageMethod"),
-									modifier: StorageEntryModifier::Optional,
-									ty: StorageEntryType::Plain(DecodeDifferent::Encode("u32")),
-									default: DecodeDifferent::Encode(
-										DefaultByteGetter(
-											&event_module2::__GetByteStructStorageMethod(
-												std::marker::PhantomData::<TestRuntime>
-											)
-										)
-									),
-									documentation: DecodeDifferent::Encode(&[]),
-								}
-							]
-						)
-					}),
-				)),
-				calls: Some(DecodeDifferent::Encode(FnEncode(|| &[]))),
-				event: Some(DecodeDifferent::Encode(
-					FnEncode(||&[
-						EventMetadata {
-							name: DecodeDifferent::Encode("TestEvent"),
-							arguments: DecodeDifferent::Encode(&["Balance"]),
-							documentation: DecodeDifferent::Encode(&[])
+				storage: Some(StorageMetadataV9 {
+					prefix: DecodeDifferent::Encode("TestStorage"),
+					entries: vec![
+						StorageEntryMetadataV9 {
+							name: DecodeDifferent::Encode("StorageMethod"),
+							modifier: StorageEntryModifier::Optional,
+							ty: StorageEntryTypeV9::Plain(u32_ty),
+							default: DecodeDifferent::Encode(
+								DefaultByteGetter(
+									&event_module2::__GetByteStructStorageMethod(
+										std::marker::PhantomData::<TestRuntime>
+									)
+								)
+							),
+							documentation: vec![],
 						}
-					])
-				)),
-				constants: DecodeDifferent::Encode(FnEncode(|| &[])),
-				errors: DecodeDifferent::Encode(FnEncode(|| &[])),
+					],
+				}),
+				calls: Some(vec![]),
+				event: Some(vec![
+					EventMetadataV9 {
+						name: DecodeDifferent::Encode("TestEvent"),
+						arguments: vec![u32_ty],
+						documentation: vec![],
+					}
+				]),
+				constants: vec![],
+				errors: vec![],
 			},
 		])
-	};
+	}
 
 	#[test]
 	fn runtime_metadata() {
 		let metadata_encoded = TestRuntime::metadata().encode();
 		let metadata_decoded = RuntimeMetadataPrefixed::decode(&mut &metadata_encoded[..]);
-		let expected_metadata: RuntimeMetadataPrefixed = EXPECTED_METADATA.into();
+		let expected_metadata: RuntimeMetadataPrefixed = expected_metadata().into();
 
 		pretty_assertions::assert_eq!(expected_metadata, metadata_decoded.unwrap());
 	}
+
+	#[test]
+	fn outer_event_has_a_variant_per_module() {
+		let mut registry = Registry::new();
+		let id = registry.register::<TestEvent>();
+		let types = registry.into_types();
+
+		let names = match &types[id.0 as usize].def {
+			TypeDef::Variant(variants) => variants.iter().map(|v| v.name.clone()).collect::<Vec<_>>(),
+			def => panic!("outer event described as {:?}", def),
+		};
+		assert_eq!(names, vec![
+			DecodeDifferent::Encode("system"),
+			DecodeDifferent::Encode("event_module"),
+			DecodeDifferent::Encode("event_module2"),
+		]);
+	}
 }
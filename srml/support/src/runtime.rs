@@ -268,7 +268,7 @@ macro_rules! construct_runtime {
 
 /// A macro that generates a "__decl" private macro that transforms parts of the runtime definition
 /// to feed them into a public "impl" macro which accepts the format
-/// "pub enum $name for $runtime where system = $system".
+/// "pub enum $name for $runtime where system = $system", optionally preceded by the given prefix.
 ///
 /// Used to define Event and Origin associated types.
 #[macro_export]
@@ -278,6 +278,7 @@ macro_rules! __create_decl_macro {
 		// Parameter $d is a hack for the following issue:
 		// https://github.com/rust-lang/rust/issues/35853
 		$macro_name:ident, $macro_outer_name:ident, $macro_enum_name:ident, $d:tt
+		$(, $( $outer_prefix:tt )+ )?
 	) => {
 		#[macro_export]
 		#[doc(hidden)]
@@ -412,6 +413,7 @@ macro_rules! __create_decl_macro {
 				{ $d( $parsed_modules:ident $d( $instance:ident )? $d( <$parsed_generic:ident> )? ,)* };
 			) => {
 				$d crate::$macro_outer_name! {
+					$( $( $outer_prefix )+ )?
 					pub enum $macro_enum_name for $runtime where system = $system {
 						$d(
 							$parsed_modules $d( $instance )? $d( <$parsed_generic> )?,
@@ -423,7 +425,7 @@ macro_rules! __create_decl_macro {
 	}
 }
 
-__create_decl_macro!(__decl_outer_event, impl_outer_event, Event, $);
+__create_decl_macro!(__decl_outer_event, impl_outer_event, Event, $, @type_info);
 __create_decl_macro!(__decl_outer_origin, impl_outer_origin, Origin, $);
 
 /// A macro that defines all modules as an associated types of the Runtime type.
@@ -600,6 +602,7 @@ macro_rules! __decl_outer_dispatch {
 		;
 	) => {
 		$crate::impl_outer_dispatch!(
+			@type_info
 			pub enum Call for $runtime where origin: Origin {
 				$( $parsed_modules::$parsed_name, )*
 			}
@@ -346,6 +346,22 @@ where
 		read_head::<_, _, G>()
 	}
 
+	fn translate_values<O: Decode, F: FnMut(O) -> V>(mut f: F) -> Result<(), K> {
+		let mut next = read_head::<_, _, G>();
+		while let Some(key) = next {
+			let final_key = G::storage_linked_map_final_key(&key);
+			let full_value = unhashed::get_raw(final_key.as_ref())
+				.and_then(|data| <(O, Linkage<K>)>::decode(&mut &data[..]).ok());
+			let (old, linkage) = match full_value {
+				Some(full_value) => full_value,
+				None => return Err(key),
+			};
+			unhashed::put(final_key.as_ref(), &(f(old), &linkage));
+			next = linkage.next;
+		}
+		Ok(())
+	}
+
 	fn decode_len<KeyArg: EncodeLike<K>>(key: KeyArg) -> Result<usize, &'static str>
 		where V: codec::DecodeLength + Len
 	{
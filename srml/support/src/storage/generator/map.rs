@@ -17,7 +17,7 @@
 #[cfg(not(feature = "std"))]
 use rstd::prelude::*;
 use rstd::borrow::Borrow;
-use codec::{FullCodec, FullEncode, Encode, EncodeLike, Ref, EncodeAppend};
+use codec::{FullCodec, FullEncode, Decode, Encode, EncodeLike, Ref, EncodeAppend};
 use crate::{storage::{self, unhashed}, hash::StorageHasher, traits::Len};
 
 /// Generator for `StorageMap` used by `decl_storage`.
@@ -116,6 +116,26 @@ impl<K: FullEncode, V: FullCodec, G: StorageMap<K, V>> storage::StorageMap<K, V>
 		G::from_optional_value_to_query(value)
 	}
 
+	fn translate<KeyArg, O, F>(key: KeyArg, f: F) -> Result<Option<V>, ()>
+	where
+		KeyArg: EncodeLike<K>,
+		O: Decode,
+		F: FnOnce(Option<O>) -> Option<V>,
+	{
+		let final_key = Self::storage_map_final_key(key);
+
+		let maybe_old = match unhashed::get_raw(final_key.as_ref()) {
+			Some(old_data) => Some(O::decode(&mut &old_data[..]).map_err(|_| ())?),
+			None => None,
+		};
+		let maybe_new = f(maybe_old);
+		match maybe_new.as_ref() {
+			Some(new) => unhashed::put(final_key.as_ref(), new),
+			None => unhashed::kill(final_key.as_ref()),
+		}
+		Ok(maybe_new)
+	}
+
 	fn append<Items, Item, EncodeLikeItem, KeyArg>(key: KeyArg, items: Items) -> Result<(), &'static str>
 	where
 		KeyArg: EncodeLike<K>,
@@ -36,8 +36,8 @@ pub use value::StorageValue;
 #[allow(dead_code)]
 mod tests {
 	use runtime_io::TestExternalities;
-	use codec::Encode;
-	use crate::storage::{unhashed, generator::StorageValue};
+	use codec::{Encode, Decode};
+	use crate::storage::{unhashed, generator::{StorageValue, StorageMap, StorageLinkedMap, Linkage}};
 
 	struct Runtime {}
 	pub trait Trait {
@@ -57,6 +57,8 @@ mod tests {
 	crate::decl_storage! {
 		trait Store for Module<T: Trait> as Runtime {
 			Value get(fn value) config(): (u64, u64);
+			NumberMap get(fn number_map): map u32 => (u64, u64);
+			NumberLinkedMap get(fn number_linked_map): linked_map u32 => (u64, u64);
 		}
 	}
 
@@ -78,4 +80,56 @@ mod tests {
 			assert_eq!(Value::get(), (1111, 2222));
 		})
 	}
+
+	#[test]
+	fn map_translate_works() {
+		let t = GenesisConfig::default().build_storage().unwrap();
+		TestExternalities::new(t).execute_with(|| {
+			// put the old value `1111u32` in the storage.
+			let key = NumberMap::storage_map_final_key(1);
+			unhashed::put_raw(key.as_ref(), &1111u32.encode());
+
+			// translate
+			let translate_fn = |old: Option<u32>| -> Option<(u64, u64)> {
+				old.map(|o| (o.into(), (o*2).into()))
+			};
+			assert_eq!(NumberMap::translate(1, translate_fn), Ok(Some((1111, 2222))));
+			assert_eq!(NumberMap::translate(2, translate_fn), Ok(None));
+
+			// new storage should be `(1111, 1111 * 2)`
+			assert_eq!(NumberMap::get(1), (1111, 2222));
+			assert!(!NumberMap::exists(2));
+
+			// the new value can't be interpreted as the old one anymore.
+			assert_eq!(NumberMap::translate(1, |old: Option<Vec<u64>>| old.map(|_| (0, 0))), Err(()));
+		})
+	}
+
+	#[test]
+	fn linked_map_translate_values_works() {
+		let t = GenesisConfig::default().build_storage().unwrap();
+		TestExternalities::new(t).execute_with(|| {
+			// put some values, then overwrite them with old values, keeping the linkage.
+			for i in 0u32..4 {
+				NumberLinkedMap::insert(i, (0, 0));
+				let key = NumberLinkedMap::storage_linked_map_final_key(i);
+				let linkage: Linkage<u32> = unhashed::get_raw(key.as_ref())
+					.map(|data| <((u64, u64), Linkage<u32>)>::decode(&mut &data[..]).unwrap().1)
+					.unwrap();
+				unhashed::put(key.as_ref(), &(i * 1000, linkage));
+			}
+
+			// translate
+			assert_eq!(
+				NumberLinkedMap::translate_values(|o: u32| (o.into(), (o*2).into())),
+				Ok(()),
+			);
+
+			// new storage should be `(o, o * 2)`, enumerated in the same order.
+			assert_eq!(
+				NumberLinkedMap::enumerate().collect::<Vec<_>>(),
+				vec![(3, (3000, 6000)), (2, (2000, 4000)), (1, (1000, 2000)), (0, (0, 0))],
+			);
+		})
+	}
 }
@@ -59,9 +59,8 @@ pub trait StorageValue<T: FullCodec> {
 	///
 	/// # Usage
 	///
-	/// This would typically be called inside the module implementation of on_initialize, while
-	/// ensuring **no usage of this storage are made before the call to `on_initialize`**. (More
-	/// precisely prior initialized modules doesn't make use of this storage).
+	/// This would typically be called inside the module implementation of `on_runtime_upgrade`,
+	/// which is executed before any `on_initialize`.
 	fn translate<O: Decode, F: FnOnce(Option<O>) -> Option<T>>(f: F) -> Result<Option<T>, ()>;
 
 	/// Store a value under this key into the provided storage instance.
@@ -145,6 +144,32 @@ pub trait StorageMap<K: FullEncode, V: FullCodec> {
 	/// Take the value under a key.
 	fn take<KeyArg: EncodeLike<K>>(key: KeyArg) -> Self::Query;
 
+	/// Translate the value under a key from some previous type (`O`) to the current type.
+	///
+	/// `f: F` is the translation function.
+	///
+	/// Returns `Err` if the storage item could not be interpreted as the old type, and Ok, along
+	/// with the new value if it could.
+	///
+	/// NOTE: This operates from and to `Option<_>` types; no effort is made to respect the default
+	/// value of the original type.
+	///
+	/// # Warning
+	///
+	/// Keys of a map are hashed, so this can only be used if all the keys to translate are known.
+	/// Before being updated the storage still contains the old type, thus other calls (such as
+	/// `get`) will fail at decoding it.
+	///
+	/// # Usage
+	///
+	/// This would typically be called inside the module implementation of `on_runtime_upgrade`,
+	/// which is executed before any `on_initialize`.
+	fn translate<KeyArg, O, F>(key: KeyArg, f: F) -> Result<Option<V>, ()>
+	where
+		KeyArg: EncodeLike<K>,
+		O: Decode,
+		F: FnOnce(Option<O>) -> Option<V>;
+
 	/// Append the given items to the value in the storage.
 	///
 	/// `V` is required to implement `codec::EncodeAppend`.
@@ -221,6 +246,24 @@ pub trait StorageLinkedMap<K: FullCodec, V: FullCodec> {
 	/// Enumerate all elements in the map.
 	fn enumerate() -> Self::Enumerator;
 
+	/// Translate all the values of the map from some previous type (`O`) to the current type.
+	///
+	/// `f: F` is the translation function, keys and order of enumeration are kept.
+	///
+	/// Returns `Err` with the key of the first value which could not be interpreted as the old
+	/// type, values enumerated before it are already translated.
+	///
+	/// # Warning
+	///
+	/// This function must be used with care, before being updated the storage still contains the
+	/// old type, thus other calls (such as `get` or `enumerate`) will fail at decoding it.
+	///
+	/// # Usage
+	///
+	/// This would typically be called inside the module implementation of `on_runtime_upgrade`,
+	/// which is executed before any `on_initialize`.
+	fn translate_values<O: Decode, F: FnMut(O) -> V>(f: F) -> Result<(), K>;
+
 	/// Read the length of the value in a fast way, without decoding the entire value.
 	///
 	/// `T` is required to implement `Codec::DecodeLength`.
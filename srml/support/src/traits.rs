@@ -62,6 +62,12 @@ impl<V: PartialEq, T: Get<V>> Contains<V> for T {
 	}
 }
 
+/// A `Contains` implementation that also knows how many values it contains.
+pub trait ContainsCount<T>: Contains<T> {
+	/// Return the number of items that this contains.
+	fn count() -> usize;
+}
+
 /// The account with the given id was killed.
 #[impl_trait_for_tuples::impl_for_tuples(30)]
 pub trait OnFreeBalanceZero<AccountId> {
@@ -644,6 +650,17 @@ impl WithdrawReasons {
 	}
 }
 
+// The mask is encoded as its underlying integer, the flags are the bits of `WithdrawReason`.
+impl crate::metadata::TypeInfo for WithdrawReasons {
+	fn path() -> Vec<&'static str> {
+		module_path!().split("::").chain(rstd::iter::once("WithdrawReasons")).collect()
+	}
+
+	fn type_def(registry: &mut crate::metadata::Registry) -> crate::metadata::TypeDef {
+		crate::metadata::TypeDef::Composite(vec![crate::metadata::Field::unnamed(registry.register::<i8>())])
+	}
+}
+
 /// Trait for type that can handle incremental changes to a set of account IDs.
 pub trait ChangeMembers<AccountId: Clone + Ord> {
 	/// A number of members `incoming` just joined the set and replaced some `outgoing` ones. The
@@ -705,12 +722,17 @@ pub trait ChangeMembers<AccountId: Clone + Ord> {
 		}
 		(incoming, outgoing)
 	}
+
+	/// Set the prime member, if any. The prime member's vote is used as the default vote of the
+	/// members that abstained.
+	fn set_prime(_prime: Option<AccountId>) {}
 }
 
 impl<T: Clone + Ord> ChangeMembers<T> for () {
 	fn change_members(_: &[T], _: &[T], _: Vec<T>) {}
 	fn change_members_sorted(_: &[T], _: &[T], _: &[T]) {}
 	fn set_members_sorted(_: &[T], _: &[T]) {}
+	fn set_prime(_: Option<T>) {}
 }
 
 /// Trait for type that can handle the initialization of account IDs at genesis.
@@ -742,3 +764,214 @@ pub trait Randomness<Output> {
 		Self::random(&[][..])
 	}
 }
+
+/// Determine whether or not a given value passes a filter defined by an instance of the
+/// implementing type.
+pub trait InstanceFilter<T>: Sized + Send + Sync {
+	/// Determine if a given value should be allowed through the filter (returns `true`) or not.
+	fn filter(&self, _: &T) -> bool;
+
+	/// Determines whether `self` matches at least everything that `_o` does.
+	fn is_superset(&self, _o: &Self) -> bool { false }
+}
+
+impl<T> InstanceFilter<T> for () {
+	fn filter(&self, _: &T) -> bool { true }
+	fn is_superset(&self, _o: &Self) -> bool { true }
+}
+
+/// Traits for scheduling the dispatch of calls at a future block.
+pub mod schedule {
+	use super::*;
+
+	/// Information relating to the period of a scheduled task. First item is the length of the
+	/// period and the second is the number of times it should be executed in total before the task
+	/// is considered finished and removed.
+	pub type Period<BlockNumber> = (BlockNumber, u32);
+
+	/// Priority with which a call is scheduled. It's just a linear amount with lowest values meaning
+	/// higher priority.
+	pub type Priority = u8;
+
+	/// The highest priority. We invert the value so that normal sorting will place the highest
+	/// priority at the beginning of the list.
+	pub const HIGHEST_PRIORITY: Priority = 0;
+	/// Anything of this value or lower will definitely be scheduled on the block that they ask for,
+	/// even if it breaches the `MaximumWeight` limitation.
+	pub const HARD_DEADLINE: Priority = 63;
+	/// The lowest priority. Most stuff should be around here.
+	pub const LOWEST_PRIORITY: Priority = 255;
+
+	/// A scheduled call, either given as is or by the hash of its preimage. Calls given by hash
+	/// are looked up when they are dispatched, so that large calls need not be stored twice.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, sr_primitives::RuntimeDebug, crate::metadata::TypeInfo)]
+	pub enum MaybeHashed<Call, Hash> {
+		/// The call itself.
+		Value(Call),
+		/// The hash of the encoded call.
+		Hash(Hash),
+	}
+
+	impl<Call, Hash> From<Call> for MaybeHashed<Call, Hash> {
+		fn from(call: Call) -> Self {
+			MaybeHashed::Value(call)
+		}
+	}
+
+	/// A type that can be used as a scheduler.
+	pub trait Anon<BlockNumber, Call> {
+		/// An address which can be used for removing a scheduled task.
+		type Address: Codec + Clone + Eq + Debug;
+
+		/// Schedule a one-off dispatch to happen at the beginning of some block in the future.
+		///
+		/// This is not named.
+		///
+		/// Infallible.
+		fn schedule(
+			when: BlockNumber,
+			maybe_periodic: Option<Period<BlockNumber>>,
+			priority: Priority,
+			call: Call,
+		) -> Self::Address;
+
+		/// Cancel a scheduled task. If periodic, then it will cancel all further instances of that,
+		/// also.
+		///
+		/// Will return an error if the `address` is invalid.
+		///
+		/// NOTE: This guaranteed to work only *before* the point that it is due to be executed.
+		/// If it ends up being delayed beyond the point of execution, then it cannot be cancelled.
+		fn cancel(address: Self::Address) -> result::Result<(), ()>;
+	}
+
+	/// A type that can be used as a scheduler.
+	pub trait Named<BlockNumber, Call> {
+		/// An address which can be used for removing a scheduled task.
+		type Address: Codec + Clone + Eq + Debug;
+
+		/// Schedule a one-off dispatch to happen at the beginning of some block in the future.
+		///
+		/// Fails if a task with the same `id` is already scheduled.
+		fn schedule_named(
+			id: Vec<u8>,
+			when: BlockNumber,
+			maybe_periodic: Option<Period<BlockNumber>>,
+			priority: Priority,
+			call: Call,
+		) -> result::Result<Self::Address, ()>;
+
+		/// Cancel a scheduled, named task. If periodic, then it will cancel all further instances
+		/// of that, also.
+		///
+		/// Will return an error if the `id` is invalid.
+		///
+		/// NOTE: This guaranteed to work only *before* the point that it is due to be executed.
+		/// If it ends up being delayed beyond the point of execution, then it cannot be cancelled.
+		fn cancel_named(id: Vec<u8>) -> result::Result<(), ()>;
+	}
+}
+
+/// Traits for keeping the preimages of hashes on chain.
+pub mod preimage {
+	use super::*;
+
+	/// A type which can provide the preimages of hashes, and keep them available for as long as
+	/// they are requested.
+	pub trait PreimageProvider<Hash> {
+		/// Returns whether the preimage of `hash` is available.
+		fn have_preimage(hash: &Hash) -> bool;
+
+		/// Returns the preimage of `hash`, if it is available.
+		fn get_preimage(hash: &Hash) -> Option<Vec<u8>>;
+
+		/// Returns whether the preimage of `hash` is requested.
+		fn preimage_requested(hash: &Hash) -> bool;
+
+		/// Request that the preimage of `hash` be kept available, or made available once noted.
+		///
+		/// Requests are counted, so that several consumers may request the same preimage. Noting
+		/// a requested preimage is free.
+		fn request_preimage(hash: &Hash);
+
+		/// Cancel a previous request for the preimage of `hash`. Once it is not requested anymore,
+		/// the preimage may be removed.
+		///
+		/// Does nothing if the preimage is not requested.
+		fn unrequest_preimage(hash: &Hash);
+	}
+
+	impl<Hash> PreimageProvider<Hash> for () {
+		fn have_preimage(_: &Hash) -> bool { false }
+		fn get_preimage(_: &Hash) -> Option<Vec<u8>> { None }
+		fn preimage_requested(_: &Hash) -> bool { false }
+		fn request_preimage(_: &Hash) {}
+		fn unrequest_preimage(_: &Hash) {}
+	}
+
+	/// A type which can also store preimages on behalf of other modules, without a deposit.
+	pub trait PreimageRecipient<Hash>: PreimageProvider<Hash> {
+		/// Store `bytes` as the preimage of their hash, unless it is stored already.
+		///
+		/// The preimage is only kept for as long as it is requested, so it should be requested as
+		/// well. Fails if the preimage cannot be stored, for instance because it is too large.
+		fn note_preimage(bytes: Vec<u8>) -> result::Result<(), ()>;
+	}
+
+	impl<Hash> PreimageRecipient<Hash> for () {
+		fn note_preimage(_: Vec<u8>) -> result::Result<(), ()> { Err(()) }
+	}
+}
+
+/// Traits for staking funds on behalf of accounts managed by other modules.
+pub mod staking {
+	use super::*;
+
+	/// Counter for the number of eras that have passed.
+	pub type EraIndex = u32;
+
+	/// A staking system, through which a module may bond and nominate with accounts it manages.
+	///
+	/// Unlike the dispatchable functions of the staking system, these functions act on behalf of
+	/// the given accounts without any origin check: the calling module is responsible for them.
+	pub trait StakingInterface {
+		/// The account identifier type.
+		type AccountId;
+
+		/// The balance type.
+		type Balance;
+
+		/// The number of eras that unbonded funds stay locked for.
+		fn bonding_duration() -> EraIndex;
+
+		/// The index of the current era.
+		fn current_era() -> EraIndex;
+
+		/// Whether an election is ongoing, during which stakes cannot be changed.
+		fn election_ongoing() -> bool;
+
+		/// The active stake of the ledger controlled by `controller`, if there is one.
+		fn active_stake(controller: &Self::AccountId) -> Option<Self::Balance>;
+
+		/// Bond `value` of `stash`, with `controller` as its controller, which also receives its
+		/// rewards.
+		fn bond(
+			stash: &Self::AccountId,
+			controller: &Self::AccountId,
+			value: Self::Balance,
+		) -> crate::dispatch::Result;
+
+		/// Bond up to `max_additional` more of the free balance of a bonded `stash`.
+		fn bond_extra(stash: &Self::AccountId, max_additional: Self::Balance) -> crate::dispatch::Result;
+
+		/// Unbond `value` of the active stake of the ledger controlled by `controller`. Funds
+		/// unbonded during the same era are merged into a single unlocking chunk.
+		fn unbond(controller: &Self::AccountId, value: Self::Balance) -> crate::dispatch::Result;
+
+		/// Withdraw the unlocked funds of the ledger controlled by `controller`.
+		fn withdraw_unbonded(controller: &Self::AccountId) -> crate::dispatch::Result;
+
+		/// Nominate `targets` with the stash of the ledger controlled by `controller`.
+		fn nominate(controller: &Self::AccountId, targets: Vec<Self::AccountId>) -> crate::dispatch::Result;
+	}
+}
@@ -17,6 +17,7 @@
 use support::sr_primitives::generic;
 use support::sr_primitives::traits::{BlakeTwo256, Block as _, Verify};
 use support::codec::{Encode, Decode};
+use support::metadata::TypeInfo;
 use primitives::{H256, sr25519};
 use serde::{Serialize, Deserialize};
 
@@ -32,12 +33,12 @@ mod module {
 	);
 	pub type Requests<T> = Vec<Request<T>>;
 
-	#[derive(Encode, Decode, Copy, Clone, Eq, PartialEq, Debug)]
+	#[derive(Encode, Decode, TypeInfo, Copy, Clone, Eq, PartialEq, Debug)]
 	pub enum Role {
 		Storage,
 	}
 
-	#[derive(Encode, Decode, Copy, Clone, Eq, PartialEq, Debug)]
+	#[derive(Encode, Decode, TypeInfo, Copy, Clone, Eq, PartialEq, Debug)]
 	pub struct RoleParameters<T: Trait> {
 		// minimum actors to maintain - if role is unstaking
 		// and remaining actors would be less that this value - prevent or punish for unstaking
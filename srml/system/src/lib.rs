@@ -113,7 +113,7 @@ use sr_primitives::{
 
 use primitives::storage::well_known_keys;
 use support::{
-	decl_module, decl_event, decl_storage, decl_error, storage, Parameter,
+	decl_module, decl_event, decl_storage, decl_error, storage, Parameter, metadata::TypeInfo,
 	traits::{Contains, Get},
 };
 use codec::{Encode, Decode};
@@ -286,7 +286,7 @@ decl_module! {
 }
 
 /// A phase of a block's execution.
-#[derive(Encode, Decode, RuntimeDebug)]
+#[derive(Encode, Decode, RuntimeDebug, TypeInfo)]
 #[cfg_attr(feature = "std", derive(Serialize, PartialEq, Eq, Clone))]
 pub enum Phase {
 	/// Applying an extrinsic.
@@ -296,7 +296,7 @@ pub enum Phase {
 }
 
 /// Record of an event happening.
-#[derive(Encode, Decode, RuntimeDebug)]
+#[derive(Encode, Decode, RuntimeDebug, TypeInfo)]
 #[cfg_attr(feature = "std", derive(Serialize, PartialEq, Eq, Clone))]
 pub struct EventRecord<E: Parameter + Member, T> {
 	/// The phase of the block it happened in.
@@ -415,6 +415,13 @@ decl_storage! {
 		/// no notification will be triggered thus the event might be lost.
 		EventTopics get(fn event_topics): double_map hasher(blake2_256) (), blake2_256(T::Hash)
 			=> Vec<(T::BlockNumber, EventIndex)>;
+
+		/// The `spec_version` of the runtime that executed the last block, recorded at genesis.
+		///
+		/// Used to detect runtime upgrades, so that `on_runtime_upgrade` is executed exactly once.
+		/// It is missing on the chains created before it was introduced.
+		pub LastRuntimeUpgrade get(fn last_runtime_upgrade)
+			build(|_| T::Version::get().spec_version): Option<u32>;
 	}
 	add_extra_genesis {
 		config(changes_trie_config): Option<ChangesTrieConfiguration>;
@@ -606,6 +613,17 @@ impl<T: Trait> Module<T> {
 		AllExtrinsicsLen::get().unwrap_or_default()
 	}
 
+	/// Inform the system module of some additional weight that should be accounted for, in the
+	/// current block.
+	///
+	/// NOTE: the weight is added unchecked: it can push the block past its weight limit. This is
+	/// only meant for things that must happen regardless of the block's fullness, such as
+	/// storage migrations executed on a runtime upgrade.
+	pub fn register_extra_weight_unchecked(weight: Weight) {
+		let current_weight = Self::all_extrinsics_weight();
+		AllExtrinsicsWeight::put(current_weight.saturating_add(weight));
+	}
+
 	/// Start the execution of a particular block.
 	pub fn initialize(
 		number: &T::BlockNumber,
@@ -41,6 +41,24 @@
 //! respectively.
 //! - **Pot:** Unspent funds accumulated by the treasury module.
 //!
+//! Tipping protocol:
+//! - **Tipping:** The process of gathering declarations of amounts to tip and taking the median
+//!   amount to be transferred from the treasury to a beneficiary account.
+//! - **Tip Reason:** The reason for a tip; generally a URL which embodies or explains why a
+//!   particular individual (identified by an account ID) is worthy of a recognition by the
+//!   treasury.
+//! - **Finder:** The original public reporter of some reason for tipping.
+//! - **Finders Fee:** Some proportion of the tip amount that is paid to the reporter of the tip,
+//!   rather than the main beneficiary.
+//!
+//! Bounty:
+//! - **Bounty spending proposal:** A proposal to reward a predefined body of work upon completion
+//!   by the treasury.
+//! - **Curator:** An account managing the bounty and assigning a payout address receiving the
+//!   reward for the completion of work.
+//! - **Curator fee:** The reserved upfront payment for a curator for work related to the bounty.
+//! - **Payout address:** The account receiving the bounty reward, once the bounty is awarded.
+//!
 //! ### Implementations
 //!
 //! The treasury module provides an implementation for the following trait:
@@ -61,6 +79,26 @@
 //! - `reject_proposal` - Reject a proposal, slashing the deposit.
 //! - `approve_proposal` - Accept the proposal, returning the deposit.
 //!
+//! Tipping protocol:
+//! - `report_awesome` - Report something worthy of a tip and register for a finders fee.
+//! - `retract_tip` - Retract a previous (finders fee registered) report.
+//! - `tip_new` - Report an item worthy of a tip and declare a specific amount to tip.
+//! - `tip` - Declare or redeclare an amount to tip for a particular reason.
+//! - `close_tip` - Close and pay out a tip.
+//!
+//! Bounty protocol:
+//! - `propose_bounty` - Propose a specific treasury amount to be earmarked for a predefined set of
+//!   tasks and stake the required deposit.
+//! - `approve_bounty` - Accept a specific treasury amount to be earmarked for a predefined body of
+//!   work.
+//! - `propose_curator` - Assign an account to a bounty as candidate curator.
+//! - `accept_curator` - Accept a bounty assignment from the council as a curator.
+//! - `unassign_curator` - Unassign an accepted or proposed curator from a bounty.
+//! - `award_bounty` - Close and pay out the specified amount for the completed work.
+//! - `claim_bounty` - Claim a specific bounty amount from the payout address. Awarded bounties are
+//!   also claimed by `T::Scheduler` once their payout delay has passed.
+//! - `close_bounty` - Cancel the earmark for a specific treasury amount and close the bounty.
+//!
 //! ## GenesisConfig
 //!
 //! The Treasury module depends on the [`GenesisConfig`](./struct.GenesisConfig.html).
@@ -70,14 +108,14 @@
 #[cfg(feature = "std")]
 use serde::{Serialize, Deserialize};
 use rstd::prelude::*;
-use support::{decl_module, decl_storage, decl_event, ensure, print};
+use support::{decl_module, decl_storage, decl_event, ensure, print, metadata::TypeInfo};
 use support::traits::{
-	Currency, ExistenceRequirement, Get, Imbalance, OnDilution, OnUnbalanced,
-	ReservableCurrency, WithdrawReason
+	ContainsCount, Currency, ExistenceRequirement, Get, Imbalance, OnDilution, OnUnbalanced,
+	ReservableCurrency, WithdrawReason, schedule,
 };
-use sr_primitives::{Permill, Perbill, ModuleId};
+use sr_primitives::{Permill, Perbill, Percent, ModuleId};
 use sr_primitives::traits::{
-	Zero, EnsureOrigin, StaticLookup, AccountIdConversion, CheckedSub, Saturating
+	Zero, EnsureOrigin, StaticLookup, AccountIdConversion, CheckedSub, Saturating, Hash,
 };
 use sr_primitives::weights::SimpleDispatchInfo;
 use codec::{Encode, Decode};
@@ -117,10 +155,46 @@ pub trait Trait: system::Trait {
 
 	/// Percentage of spare funds (if any) that are burnt per spend period.
 	type Burn: Get<Permill>;
+
+	/// The accounts which may declare tip amounts, typically the members of the council.
+	type Tippers: ContainsCount<Self::AccountId>;
+
+	/// The period for which a tip remains open after it has achieved the threshold of tippers.
+	type TipCountdown: Get<Self::BlockNumber>;
+
+	/// The percent of the final tip which goes to the original reporter of the tip.
+	type TipFindersFee: Get<Percent>;
+
+	/// The amount held on deposit for placing a tip report.
+	type TipReportDepositBase: Get<BalanceOf<Self>>;
+
+	/// The amount held on deposit per byte within a tip report reason or bounty description.
+	type DataDepositPerByte: Get<BalanceOf<Self>>;
+
+	/// Maximum acceptable length of a tip reason or bounty description.
+	type MaximumReasonLength: Get<u32>;
+
+	/// The amount held on deposit for placing a bounty proposal.
+	type BountyDepositBase: Get<BalanceOf<Self>>;
+
+	/// The delay period for which a bounty beneficiary needs to wait before claiming the payout.
+	type BountyDepositPayoutDelay: Get<Self::BlockNumber>;
+
+	/// Minimum value for a bounty.
+	type BountyValueMinimum: Get<BalanceOf<Self>>;
+
+	/// The aggregated call type.
+	type Call: From<Call<Self>>;
+
+	/// The scheduler with which awarded bounties are paid out once their payout delay has passed.
+	type Scheduler: schedule::Anon<Self::BlockNumber, <Self as Trait>::Call>;
 }
 
 type ProposalIndex = u32;
 
+/// An index of a bounty.
+pub type BountyIndex = u32;
+
 decl_module! {
 	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
 		/// Fraction of a proposal's value that should be bonded in order to place the proposal.
@@ -136,6 +210,31 @@ decl_module! {
 		/// Percentage of spare funds (if any) that are burnt per spend period.
 		const Burn: Permill = T::Burn::get();
 
+		/// The period for which a tip remains open after it has achieved the threshold of tippers.
+		const TipCountdown: T::BlockNumber = T::TipCountdown::get();
+
+		/// The amount of the final tip which goes to the original reporter of the tip.
+		const TipFindersFee: Percent = T::TipFindersFee::get();
+
+		/// The amount held on deposit for placing a tip report.
+		const TipReportDepositBase: BalanceOf<T> = T::TipReportDepositBase::get();
+
+		/// The amount held on deposit per byte within a tip report reason or bounty description.
+		const DataDepositPerByte: BalanceOf<T> = T::DataDepositPerByte::get();
+
+		/// Maximum acceptable length of a tip reason or bounty description.
+		const MaximumReasonLength: u32 = T::MaximumReasonLength::get();
+
+		/// The amount held on deposit for placing a bounty proposal.
+		const BountyDepositBase: BalanceOf<T> = T::BountyDepositBase::get();
+
+		/// The delay period for which a bounty beneficiary needs to wait before claiming the
+		/// payout.
+		const BountyDepositPayoutDelay: T::BlockNumber = T::BountyDepositPayoutDelay::get();
+
+		/// Minimum value for a bounty.
+		const BountyValueMinimum: BalanceOf<T> = T::BountyValueMinimum::get();
+
 		fn deposit_event() = default;
 		/// Put forward a suggestion for spending. A deposit proportional to the value
 		/// is reserved and slashed if the proposal is rejected. It is returned once the
@@ -200,6 +299,495 @@ decl_module! {
 			Approvals::mutate(|v| v.push(proposal_id));
 		}
 
+		/// Report something `reason` that deserves a tip and claim any eventual finder's fee.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// Payment: `TipReportDepositBase` will be reserved from the origin account, as well as
+		/// `DataDepositPerByte` for each byte in `reason`.
+		///
+		/// - `reason`: The reason for, or the thing that deserves, the tip; generally this will be
+		///   a UTF-8-encoded URL.
+		/// - `who`: The account which should be credited for the tip.
+		///
+		/// Emits `NewTip` if successful.
+		///
+		/// # <weight>
+		/// - `O(R)` where `R` length of `reason`.
+		/// - One balance operation.
+		/// - One storage mutation (codec `O(R)`).
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		fn report_awesome(origin, reason: Vec<u8>, who: T::AccountId) {
+			let finder = ensure_signed(origin)?;
+
+			ensure!(
+				reason.len() <= T::MaximumReasonLength::get() as usize,
+				"reason too big",
+			);
+
+			let reason_hash = T::Hashing::hash(&reason[..]);
+			ensure!(!Reasons::<T>::exists(&reason_hash), "tip reason already exists");
+			let hash = T::Hashing::hash_of(&(&reason_hash, &who));
+
+			let deposit = T::TipReportDepositBase::get()
+				+ T::DataDepositPerByte::get() * (reason.len() as u32).into();
+			T::Currency::reserve(&finder, deposit)
+				.map_err(|_| "Finder's balance too low")?;
+
+			Reasons::<T>::insert(&reason_hash, &reason);
+			let finder = Some((finder, deposit));
+			let tip = OpenTip { reason: reason_hash, who, finder, closes: None, tips: vec![] };
+			Tips::<T>::insert(&hash, tip);
+			Self::deposit_event(RawEvent::NewTip(hash));
+		}
+
+		/// Retract a prior tip-report from `report_awesome`, and cancel the process of tipping.
+		///
+		/// If successful, the original deposit will be unreserved.
+		///
+		/// The dispatch origin for this call must be _Signed_ and the tip identified by `hash`
+		/// must have been reported by the signing account through `report_awesome` (and not
+		/// through `tip_new`).
+		///
+		/// - `hash`: The identity of the open tip for which a tip value is declared. This is formed
+		///   as the hash of the tuple of the original tip `reason` and the beneficiary account ID.
+		///
+		/// Emits `TipRetracted` if successful.
+		///
+		/// # <weight>
+		/// - `O(T)`
+		/// - One balance operation.
+		/// - Two storage removals (one read, codec `O(T)`).
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(50_000)]
+		fn retract_tip(origin, hash: T::Hash) {
+			let who = ensure_signed(origin)?;
+			let tip = Tips::<T>::get(&hash).ok_or("tip hash unknown")?;
+			let (finder, deposit) = tip.finder.ok_or("not finder")?;
+			ensure!(finder == who, "not finder");
+
+			Reasons::<T>::remove(&tip.reason);
+			Tips::<T>::remove(&hash);
+			let _ = T::Currency::unreserve(&who, deposit);
+			Self::deposit_event(RawEvent::TipRetracted(hash));
+		}
+
+		/// Give a tip for something new; no finder's fee will be taken.
+		///
+		/// The dispatch origin for this call must be _Signed_ and the signing account must be a
+		/// member of the `Tippers` set.
+		///
+		/// - `reason`: The reason for, or the thing that deserves, the tip; generally this will be
+		///   a UTF-8-encoded URL.
+		/// - `who`: The account which should be credited for the tip.
+		/// - `tip_value`: The amount of tip that the sender would like to give. The median tip
+		///   value of active tippers will be given to the `who`.
+		///
+		/// Emits `NewTip` if successful.
+		///
+		/// # <weight>
+		/// - `O(R + T)` where `R` length of `reason`, `T` is the number of tippers.
+		/// - Two storage insertions (codecs `O(R)`, `O(T)`), one read `O(1)`.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(150_000)]
+		fn tip_new(origin, reason: Vec<u8>, who: T::AccountId, #[compact] tip_value: BalanceOf<T>) {
+			let tipper = ensure_signed(origin)?;
+			ensure!(T::Tippers::contains(&tipper), "not a tipper");
+			ensure!(
+				reason.len() <= T::MaximumReasonLength::get() as usize,
+				"reason too big",
+			);
+
+			let reason_hash = T::Hashing::hash(&reason[..]);
+			ensure!(!Reasons::<T>::exists(&reason_hash), "tip reason already exists");
+			let hash = T::Hashing::hash_of(&(&reason_hash, &who));
+
+			Reasons::<T>::insert(&reason_hash, &reason);
+			let tips = vec![(tipper, tip_value)];
+			let tip = OpenTip { reason: reason_hash, who, finder: None, closes: None, tips };
+			Tips::<T>::insert(&hash, tip);
+			Self::deposit_event(RawEvent::NewTip(hash));
+		}
+
+		/// Declare a tip value for an already-open tip.
+		///
+		/// The dispatch origin for this call must be _Signed_ and the signing account must be a
+		/// member of the `Tippers` set.
+		///
+		/// - `hash`: The identity of the open tip for which a tip value is declared. This is formed
+		///   as the hash of the tuple of the hash of the original tip `reason` and the beneficiary
+		///   account ID.
+		/// - `tip_value`: The amount of tip that the sender would like to give. The median tip
+		///   value of active tippers will be given to the `who`.
+		///
+		/// Emits `TipClosing` if the threshold of tippers has been reached and the countdown period
+		/// has started.
+		///
+		/// # <weight>
+		/// - `O(T)`
+		/// - One storage mutation (codec `O(T)`), one storage read `O(1)`.
+		/// - Up to one event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(50_000)]
+		fn tip(origin, hash: T::Hash, #[compact] tip_value: BalanceOf<T>) {
+			let tipper = ensure_signed(origin)?;
+			ensure!(T::Tippers::contains(&tipper), "not a tipper");
+
+			let mut tip = Tips::<T>::get(&hash).ok_or("tip hash unknown")?;
+			if Self::insert_tip_and_check_closing(&mut tip, tipper, tip_value) {
+				Self::deposit_event(RawEvent::TipClosing(hash));
+			}
+			Tips::<T>::insert(&hash, tip);
+		}
+
+		/// Close and payout a tip.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// The tip identified by `hash` must have finished its countdown period.
+		///
+		/// - `hash`: The identity of the open tip for which a tip value is declared. This is formed
+		///   as the hash of the tuple of the original tip `reason` and the beneficiary account ID.
+		///
+		/// Emits `TipClosed` if successful.
+		///
+		/// # <weight>
+		/// - `O(T)`
+		/// - One storage retrieval (codec `O(T)`) and two removals.
+		/// - Up to three balance operations.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(50_000)]
+		fn close_tip(origin, hash: T::Hash) {
+			ensure_signed(origin)?;
+
+			let tip = Tips::<T>::get(&hash).ok_or("tip hash unknown")?;
+			let n = tip.closes.as_ref().ok_or("tip still open")?;
+			ensure!(<system::Module<T>>::block_number() >= *n, "tip countdown not yet over");
+			// closed.
+			Reasons::<T>::remove(&tip.reason);
+			Tips::<T>::remove(&hash);
+			Self::payout_tip(hash, tip);
+		}
+
+		/// Propose a new bounty.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// Payment: `BountyDepositBase` will be reserved from the origin account, as well as
+		/// `DataDepositPerByte` for each byte in `description`. It will be unreserved upon
+		/// approval, or slashed when rejected.
+		///
+		/// - `value`: The total payment amount of this bounty, curator fee included.
+		/// - `description`: The description of this bounty.
+		///
+		/// Emits `BountyProposed` if successful.
+		///
+		/// # <weight>
+		/// - `O(D)` where `D` is the length of `description`.
+		/// - One balance operation.
+		/// - Three storage insertions.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(500_000)]
+		fn propose_bounty(origin, #[compact] value: BalanceOf<T>, description: Vec<u8>) {
+			let proposer = ensure_signed(origin)?;
+
+			ensure!(
+				description.len() <= T::MaximumReasonLength::get() as usize,
+				"reason too big",
+			);
+			ensure!(value >= T::BountyValueMinimum::get(), "invalid bounty value");
+
+			let bond = T::BountyDepositBase::get()
+				+ T::DataDepositPerByte::get() * (description.len() as u32).into();
+			T::Currency::reserve(&proposer, bond)
+				.map_err(|_| "Proposer's balance too low")?;
+
+			let index = Self::bounty_count();
+			BountyCount::put(index + 1);
+			let bounty = Bounty {
+				proposer,
+				value,
+				fee: Zero::zero(),
+				bond,
+				status: BountyStatus::Proposed,
+			};
+			<Bounties<T>>::insert(index, bounty);
+			BountyDescriptions::insert(index, description);
+
+			Self::deposit_event(RawEvent::BountyProposed(index));
+		}
+
+		/// Approve a bounty proposal. At a later time, the bounty will be funded and become active
+		/// and the original deposit will be returned.
+		///
+		/// The dispatch origin for this call must be `ApproveOrigin`.
+		///
+		/// # <weight>
+		/// - O(1).
+		/// - Limited storage reads.
+		/// - One DB change.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedOperational(100_000)]
+		fn approve_bounty(origin, #[compact] bounty_id: BountyIndex) {
+			T::ApproveOrigin::ensure_origin(origin)?;
+
+			let mut bounty = Self::bounties(bounty_id).ok_or("No bounty at that index")?;
+			ensure!(bounty.status == BountyStatus::Proposed, "bounty is not proposed");
+
+			bounty.status = BountyStatus::Approved;
+			<Bounties<T>>::insert(bounty_id, bounty);
+			BountyApprovals::mutate(|v| v.push(bounty_id));
+		}
+
+		/// Assign a curator to a funded bounty. The curator receives `fee` out of the bounty value
+		/// once the bounty is claimed.
+		///
+		/// The dispatch origin for this call must be `ApproveOrigin`.
+		///
+		/// # <weight>
+		/// - O(1).
+		/// - Limited storage reads.
+		/// - One DB change.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedOperational(100_000)]
+		fn propose_curator(
+			origin,
+			#[compact] bounty_id: BountyIndex,
+			curator: <T::Lookup as StaticLookup>::Source,
+			#[compact] fee: BalanceOf<T>,
+		) {
+			T::ApproveOrigin::ensure_origin(origin)?;
+			let curator = T::Lookup::lookup(curator)?;
+
+			let mut bounty = Self::bounties(bounty_id).ok_or("No bounty at that index")?;
+			ensure!(bounty.status == BountyStatus::Funded, "bounty is not funded");
+			ensure!(fee < bounty.value, "curator fee must be less than the bounty value");
+
+			bounty.status = BountyStatus::CuratorProposed { curator };
+			bounty.fee = fee;
+			<Bounties<T>>::insert(bounty_id, bounty);
+		}
+
+		/// Accept the curator role for a bounty.
+		///
+		/// The dispatch origin for this call must be the curator proposed for this bounty.
+		///
+		/// # <weight>
+		/// - O(1).
+		/// - Limited storage reads.
+		/// - One DB change.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		fn accept_curator(origin, #[compact] bounty_id: BountyIndex) {
+			let signer = ensure_signed(origin)?;
+
+			let mut bounty = Self::bounties(bounty_id).ok_or("No bounty at that index")?;
+			match bounty.status {
+				BountyStatus::CuratorProposed { ref curator } if curator == &signer => {},
+				_ => return Err("not the proposed curator"),
+			}
+
+			bounty.status = BountyStatus::Active { curator: signer };
+			<Bounties<T>>::insert(bounty_id, bounty);
+		}
+
+		/// Unassign the curator of a bounty, so that a new one may be proposed.
+		///
+		/// The dispatch origin for this call must be either `RejectOrigin` or the curator itself.
+		/// A bounty that was already awarded cannot have its curator unassigned.
+		///
+		/// # <weight>
+		/// - O(1).
+		/// - Limited storage reads.
+		/// - One DB change.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedOperational(100_000)]
+		fn unassign_curator(origin, #[compact] bounty_id: BountyIndex) {
+			let maybe_sender = T::RejectOrigin::try_origin(origin)
+				.map(|_| None)
+				.or_else(|origin| ensure_signed(origin).map(Some))
+				.map_err(|_| "bad origin")?;
+
+			let mut bounty = Self::bounties(bounty_id).ok_or("No bounty at that index")?;
+			match bounty.status {
+				BountyStatus::CuratorProposed { ref curator } |
+				BountyStatus::Active { ref curator } => if let Some(ref sender) = maybe_sender {
+					ensure!(sender == curator, "bad origin");
+				},
+				_ => return Err("bounty has no curator to unassign"),
+			}
+
+			bounty.status = BountyStatus::Funded;
+			bounty.fee = Zero::zero();
+			<Bounties<T>>::insert(bounty_id, bounty);
+		}
+
+		/// Award an active bounty to a beneficiary. The bounty is paid out after a delay, when it
+		/// is claimed by `T::Scheduler`.
+		///
+		/// The dispatch origin for this call must be the curator of this bounty.
+		///
+		/// - `bounty_id`: Bounty ID to award.
+		/// - `beneficiary`: The beneficiary account whom will receive the payout.
+		///
+		/// Emits `BountyAwarded` if successful.
+		///
+		/// # <weight>
+		/// - O(1).
+		/// - Limited storage reads.
+		/// - One DB change.
+		/// - One scheduled call.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		fn award_bounty(
+			origin,
+			#[compact] bounty_id: BountyIndex,
+			beneficiary: <T::Lookup as StaticLookup>::Source,
+		) {
+			let signer = ensure_signed(origin)?;
+			let beneficiary = T::Lookup::lookup(beneficiary)?;
+
+			let mut bounty = Self::bounties(bounty_id).ok_or("No bounty at that index")?;
+			let curator = match bounty.status {
+				BountyStatus::Active { ref curator } if curator == &signer => signer,
+				_ => return Err("not the curator of an active bounty"),
+			};
+
+			let now = <system::Module<T>>::block_number();
+			let unlock_at = now + T::BountyDepositPayoutDelay::get();
+			bounty.status = BountyStatus::PendingPayout {
+				curator,
+				beneficiary: beneficiary.clone(),
+				unlock_at,
+			};
+			<Bounties<T>>::insert(bounty_id, bounty);
+			T::Scheduler::schedule(
+				unlock_at,
+				None,
+				schedule::LOWEST_PRIORITY,
+				Call::claim_bounty(bounty_id).into(),
+			);
+
+			Self::deposit_event(RawEvent::BountyAwarded(bounty_id, beneficiary));
+		}
+
+		/// Claim the payout of an awarded bounty after the payout delay. The curator fee is paid to
+		/// the curator and the rest of the bounty value to the beneficiary.
+		///
+		/// The dispatch origin for this call must be _Signed_, or _Root_ when it is dispatched by
+		/// `T::Scheduler`.
+		///
+		/// - `bounty_id`: Bounty ID to claim.
+		///
+		/// Emits `BountyClaimed` if successful.
+		///
+		/// # <weight>
+		/// - O(1).
+		/// - Limited storage reads.
+		/// - Two DB removals.
+		/// - Up to three balance operations.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		fn claim_bounty(origin, #[compact] bounty_id: BountyIndex) {
+			system::EnsureRoot::<T::AccountId>::try_origin(origin)
+				.map(|_| ())
+				.or_else(|origin| ensure_signed(origin).map(|_| ()))
+				.map_err(|_| "bad origin")?;
+
+			let bounty = Self::bounties(bounty_id).ok_or("No bounty at that index")?;
+			let (curator, beneficiary) = match bounty.status {
+				BountyStatus::PendingPayout { curator, beneficiary, unlock_at } => {
+					ensure!(
+						<system::Module<T>>::block_number() >= unlock_at,
+						"bounty payout is not yet unlocked",
+					);
+					(curator, beneficiary)
+				},
+				_ => return Err("bounty is not awarded"),
+			};
+
+			let bounty_account = Self::bounty_account_id(bounty_id);
+			let balance = T::Currency::free_balance(&bounty_account);
+			let imbalance = T::Currency::withdraw(
+				&bounty_account,
+				balance,
+				WithdrawReason::Transfer.into(),
+				ExistenceRequirement::AllowDeath,
+			)?;
+			let (fee, payout) = imbalance.split(bounty.fee);
+			let payout_value = payout.peek();
+			T::Currency::resolve_creating(&curator, fee);
+			T::Currency::resolve_creating(&beneficiary, payout);
+
+			<Bounties<T>>::remove(bounty_id);
+			BountyDescriptions::remove(bounty_id);
+
+			Self::deposit_event(RawEvent::BountyClaimed(bounty_id, payout_value, beneficiary));
+		}
+
+		/// Cancel a proposed or active bounty. A proposed bounty is rejected and its deposit
+		/// slashed, while the funds of an active bounty are returned to the treasury pot. A bounty
+		/// that is approved but not yet funded, or already awarded, cannot be closed.
+		///
+		/// The dispatch origin for this call must be `RejectOrigin`.
+		///
+		/// - `bounty_id`: Bounty ID to cancel.
+		///
+		/// Emits `BountyRejected` or `BountyCanceled` if successful.
+		///
+		/// # <weight>
+		/// - O(1).
+		/// - Limited storage reads.
+		/// - Two DB removals.
+		/// - Up to two balance operations.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedOperational(100_000)]
+		fn close_bounty(origin, #[compact] bounty_id: BountyIndex) {
+			T::RejectOrigin::ensure_origin(origin)?;
+
+			let bounty = Self::bounties(bounty_id).ok_or("No bounty at that index")?;
+			match bounty.status {
+				BountyStatus::Proposed => {
+					<Bounties<T>>::remove(bounty_id);
+					BountyDescriptions::remove(bounty_id);
+
+					let imbalance = T::Currency::slash_reserved(&bounty.proposer, bounty.bond).0;
+					T::ProposalRejection::on_unbalanced(imbalance);
+
+					Self::deposit_event(RawEvent::BountyRejected(bounty_id, bounty.bond));
+				},
+				BountyStatus::Funded |
+				BountyStatus::CuratorProposed { .. } |
+				BountyStatus::Active { .. } => {
+					let bounty_account = Self::bounty_account_id(bounty_id);
+					let balance = T::Currency::free_balance(&bounty_account);
+					let imbalance = T::Currency::withdraw(
+						&bounty_account,
+						balance,
+						WithdrawReason::Transfer.into(),
+						ExistenceRequirement::AllowDeath,
+					)?;
+					Self::on_unbalanced(imbalance);
+
+					<Bounties<T>>::remove(bounty_id);
+					BountyDescriptions::remove(bounty_id);
+
+					Self::deposit_event(RawEvent::BountyCanceled(bounty_id));
+				},
+				BountyStatus::Approved => return Err("bounty is not yet funded"),
+				BountyStatus::PendingPayout { .. } => return Err("bounty is pending payout"),
+			}
+		}
+
 		fn on_finalize(n: T::BlockNumber) {
 			// Check to see if we should spend some funds!
 			if (n % T::SpendPeriod::get()).is_zero() {
@@ -211,7 +799,7 @@ decl_module! {
 
 /// A spending proposal.
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
-#[derive(Encode, Decode, Clone, PartialEq, Eq, sr_primitives::RuntimeDebug)]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, sr_primitives::RuntimeDebug, TypeInfo)]
 pub struct Proposal<AccountId, Balance> {
 	proposer: AccountId,
 	value: Balance,
@@ -219,6 +807,74 @@ pub struct Proposal<AccountId, Balance> {
 	bond: Balance,
 }
 
+/// An open tipping "motion". Retains all details of a tip including information on the finder
+/// and the members who have voted.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, sr_primitives::RuntimeDebug, TypeInfo)]
+pub struct OpenTip<AccountId, Balance, BlockNumber, Hash> {
+	/// The hash of the reason for the tip. The reason should be a human-readable UTF-8 encoded
+	/// string. A URL would be sensible.
+	reason: Hash,
+	/// The account to be tipped.
+	who: AccountId,
+	/// The account who began this tip and the amount held on deposit, if it was reported through
+	/// `report_awesome`.
+	finder: Option<(AccountId, Balance)>,
+	/// The block number at which this tip will close if `Some`. If `None`, then no closing is
+	/// scheduled.
+	closes: Option<BlockNumber>,
+	/// The members who have voted for this tip, sorted by account ID.
+	tips: Vec<(AccountId, Balance)>,
+}
+
+/// A bounty proposal.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, sr_primitives::RuntimeDebug, TypeInfo)]
+pub struct Bounty<AccountId, Balance, BlockNumber> {
+	/// The account proposing it.
+	proposer: AccountId,
+	/// The (total) amount that should be paid if the bounty is rewarded.
+	value: Balance,
+	/// The curator fee. Included in value.
+	fee: Balance,
+	/// The amount held on deposit (reserved) for making this proposal.
+	bond: Balance,
+	/// The status of this bounty.
+	status: BountyStatus<AccountId, BlockNumber>,
+}
+
+/// The status of a bounty proposal.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, sr_primitives::RuntimeDebug, TypeInfo)]
+pub enum BountyStatus<AccountId, BlockNumber> {
+	/// The bounty is proposed and waiting for approval.
+	Proposed,
+	/// The bounty is approved and waiting to become active at next spend period.
+	Approved,
+	/// The bounty is funded and waiting for curator assignment.
+	Funded,
+	/// A curator has been proposed by the `ApproveOrigin`. Waiting for acceptance from the
+	/// curator.
+	CuratorProposed {
+		/// The assigned curator of this bounty.
+		curator: AccountId,
+	},
+	/// The bounty is active and waiting to be awarded.
+	Active {
+		/// The curator of this bounty.
+		curator: AccountId,
+	},
+	/// The bounty is awarded and waiting to be released after a delay.
+	PendingPayout {
+		/// The curator of this bounty.
+		curator: AccountId,
+		/// The beneficiary of the bounty.
+		beneficiary: AccountId,
+		/// When the bounty can be claimed.
+		unlock_at: BlockNumber,
+	},
+}
+
 decl_storage! {
 	trait Store for Module<T: Trait> as Treasury {
 		/// Number of proposals that have been made.
@@ -229,6 +885,29 @@ decl_storage! {
 
 		/// Proposal indices that have been approved but not yet awarded.
 		Approvals get(fn approvals): Vec<ProposalIndex>;
+
+		/// Tips that are not yet completed. Keyed by the hash of `(reason, who)` from the value.
+		/// This has the insecure enumerable hash function since the key itself is already
+		/// guaranteed to be a secure hash.
+		pub Tips get(fn tips): map hasher(twox_64_concat) T::Hash
+			=> Option<OpenTip<T::AccountId, BalanceOf<T>, T::BlockNumber, T::Hash>>;
+
+		/// Simple preimage lookup from the reason's hash to the original data. Again, has an
+		/// insecure enumerable hash since the key is guaranteed to be the result of a secure hash.
+		pub Reasons get(fn reasons): map hasher(twox_64_concat) T::Hash => Option<Vec<u8>>;
+
+		/// Number of bounty proposals that have been made.
+		pub BountyCount get(fn bounty_count): BountyIndex;
+
+		/// Bounties that have been made.
+		pub Bounties get(fn bounties):
+			map BountyIndex => Option<Bounty<T::AccountId, BalanceOf<T>, T::BlockNumber>>;
+
+		/// The description of each bounty.
+		pub BountyDescriptions get(fn bounty_descriptions): map BountyIndex => Option<Vec<u8>>;
+
+		/// Bounty indices that have been approved but not yet funded.
+		pub BountyApprovals get(fn bounty_approvals): Vec<BountyIndex>;
 	}
 	add_extra_genesis {
 		build(|_config| {
@@ -245,7 +924,8 @@ decl_event!(
 	pub enum Event<T>
 	where
 		Balance = BalanceOf<T>,
-		<T as system::Trait>::AccountId
+		<T as system::Trait>::AccountId,
+		<T as system::Trait>::Hash,
 	{
 		/// New proposal.
 		Proposed(ProposalIndex),
@@ -257,6 +937,26 @@ decl_event!(
 		Burnt(Balance),
 		/// Spending has finished; this is the amount that rolls over until next spend.
 		Rollover(Balance),
+		/// A new tip suggestion has been opened.
+		NewTip(Hash),
+		/// A tip suggestion has reached threshold and is closing.
+		TipClosing(Hash),
+		/// A tip suggestion has been closed.
+		TipClosed(Hash, AccountId, Balance),
+		/// A tip suggestion has been retracted.
+		TipRetracted(Hash),
+		/// New bounty proposal.
+		BountyProposed(BountyIndex),
+		/// A bounty proposal was rejected; funds were slashed.
+		BountyRejected(BountyIndex, Balance),
+		/// A bounty proposal is funded and became active.
+		BountyBecameActive(BountyIndex),
+		/// A bounty is awarded to a beneficiary.
+		BountyAwarded(BountyIndex, AccountId),
+		/// A bounty is claimed by beneficiary.
+		BountyClaimed(BountyIndex, Balance, AccountId),
+		/// A bounty is cancelled.
+		BountyCanceled(BountyIndex),
 	}
 );
 
@@ -271,11 +971,98 @@ impl<T: Trait> Module<T> {
 		MODULE_ID.into_account()
 	}
 
+	/// The account ID of a bounty account.
+	pub fn bounty_account_id(id: BountyIndex) -> T::AccountId {
+		// only use two byte prefix to support 16 byte account id (used by test)
+		// "modl" ++ "py/trsry" ++ "bt" is 14 bytes, and two bytes remaining for bounty index
+		MODULE_ID.into_sub_account(("bt", id))
+	}
+
 	/// The needed bond for a proposal whose spend is `value`.
 	fn calculate_bond(value: BalanceOf<T>) -> BalanceOf<T> {
 		T::ProposalBondMinimum::get().max(T::ProposalBond::get() * value)
 	}
 
+	/// Given a mutable reference to an `OpenTip`, insert the tip into it and check whether it
+	/// closes, if so, then set closing accordingly and return `true`.
+	///
+	/// `O(T)` and one storage access.
+	fn insert_tip_and_check_closing(
+		tip: &mut OpenTip<T::AccountId, BalanceOf<T>, T::BlockNumber, T::Hash>,
+		tipper: T::AccountId,
+		tip_value: BalanceOf<T>,
+	) -> bool {
+		match tip.tips.binary_search_by_key(&&tipper, |x| &x.0) {
+			Ok(pos) => tip.tips[pos] = (tipper, tip_value),
+			Err(pos) => tip.tips.insert(pos, (tipper, tip_value)),
+		}
+		Self::retain_active_tips(&mut tip.tips);
+		let threshold = (T::Tippers::count() + 1) / 2;
+		if tip.tips.len() >= threshold && tip.closes.is_none() {
+			tip.closes = Some(<system::Module<T>>::block_number() + T::TipCountdown::get());
+			true
+		} else {
+			false
+		}
+	}
+
+	/// Remove any non-members of `Tippers` from a `tips` vector. `O(T)`.
+	fn retain_active_tips(tips: &mut Vec<(T::AccountId, BalanceOf<T>)>) {
+		tips.retain(|(who, _)| T::Tippers::contains(who));
+	}
+
+	/// Execute the payout of a tip.
+	///
+	/// Up to three balance operations.
+	/// Plus `O(T)` (`T` is Tippers length).
+	fn payout_tip(
+		hash: T::Hash,
+		tip: OpenTip<T::AccountId, BalanceOf<T>, T::BlockNumber, T::Hash>,
+	) {
+		let mut tips = tip.tips;
+		Self::retain_active_tips(&mut tips);
+		tips.sort_by_key(|i| i.1);
+		let treasury = Self::account_id();
+		let max_payout = Self::pot();
+		let payout = tips.get(tips.len() / 2)
+			.map_or_else(Zero::zero, |t| t.1)
+			.min(max_payout);
+		let mut finders_fee = None;
+		if let Some((finder, deposit)) = tip.finder {
+			let _ = T::Currency::unreserve(&finder, deposit);
+			if finder != tip.who {
+				finders_fee = Some((finder, T::TipFindersFee::get() * payout));
+			}
+		}
+
+		// this should go through given we checked it's at most the pot, but still we only make a
+		// best-effort.
+		let withdrawn = T::Currency::withdraw(
+			&treasury,
+			payout,
+			WithdrawReason::Transfer.into(),
+			ExistenceRequirement::KeepAlive,
+		);
+		let payout = match withdrawn {
+			Ok(mut imbalance) => {
+				// pay out the finder's fee.
+				if let Some((finder, fee)) = finders_fee {
+					let (fee, rest) = imbalance.split(fee);
+					T::Currency::resolve_creating(&finder, fee);
+					imbalance = rest;
+				}
+				let payout = imbalance.peek();
+				T::Currency::resolve_creating(&tip.who, imbalance);
+				payout
+			},
+			Err(_) => {
+				print("Inconsistent state - couldn't pay out tip from treasury");
+				Zero::zero()
+			},
+		};
+		Self::deposit_event(RawEvent::TipClosed(hash, tip.who, payout));
+	}
+
 	// Spend some money!
 	fn spend_funds() {
 		let mut budget_remaining = Self::pot();
@@ -309,6 +1096,37 @@ impl<T: Trait> Module<T> {
 			});
 		});
 
+		BountyApprovals::mutate(|v| {
+			v.retain(|&index| {
+				<Bounties<T>>::mutate(index, |bounty| {
+					// Should always be true, but shouldn't panic if false or we're screwed.
+					if let Some(bounty) = bounty {
+						if bounty.value <= budget_remaining {
+							budget_remaining -= bounty.value;
+
+							bounty.status = BountyStatus::Funded;
+
+							// return their deposit.
+							let _ = T::Currency::unreserve(&bounty.proposer, bounty.bond);
+
+							// fund the bounty account.
+							let bounty_account = Self::bounty_account_id(index);
+							let funds = T::Currency::deposit_creating(&bounty_account, bounty.value);
+							imbalance.subsume(funds);
+
+							Self::deposit_event(RawEvent::BountyBecameActive(index));
+							false
+						} else {
+							missed_any = true;
+							true
+						}
+					} else {
+						false
+					}
+				})
+			});
+		});
+
 		if !missed_any {
 			// burn some proportion of the remaining budget if we run a surplus.
 			let burn = (T::Burn::get() * budget_remaining).min(budget_remaining);
@@ -376,16 +1194,26 @@ impl<T: Trait> OnDilution<BalanceOf<T>> for Module<T> {
 mod tests {
 	use super::*;
 
-	use support::{assert_noop, assert_ok, impl_outer_origin, parameter_types};
+	use support::{
+		assert_noop, assert_ok, impl_outer_origin, impl_outer_dispatch, parameter_types,
+		traits::Contains,
+	};
 	use primitives::H256;
 	use sr_primitives::{
 		traits::{BlakeTwo256, OnFinalize, IdentityLookup}, testing::Header, assert_eq_error_rate,
+		weights::Weight,
 	};
 
 	impl_outer_origin! {
 		pub enum Origin for Test {}
 	}
 
+	impl_outer_dispatch! {
+		pub enum Call for Test where origin: Origin {
+			treasury::Treasury,
+		}
+	}
+
 	#[derive(Clone, Eq, PartialEq)]
 	pub struct Test;
 	parameter_types! {
@@ -427,11 +1255,40 @@ mod tests {
 		type TransferFee = TransferFee;
 		type CreationFee = CreationFee;
 	}
+	parameter_types! {
+		pub const MaximumWeight: Weight = 1_000_000;
+	}
+	impl scheduler::Trait for Test {
+		type Event = ();
+		type Call = Call;
+		type MaximumWeight = MaximumWeight;
+		type ScheduleOrigin = system::EnsureRoot<u64>;
+		type Preimages = ();
+	}
 	parameter_types! {
 		pub const ProposalBond: Permill = Permill::from_percent(5);
 		pub const ProposalBondMinimum: u64 = 1;
 		pub const SpendPeriod: u64 = 2;
 		pub const Burn: Permill = Permill::from_percent(50);
+		pub const TipCountdown: u64 = 1;
+		pub const TipFindersFee: Percent = Percent::from_percent(20);
+		pub const TipReportDepositBase: u64 = 1;
+		pub const DataDepositPerByte: u64 = 1;
+		pub const MaximumReasonLength: u32 = 16384;
+		pub const BountyDepositBase: u64 = 80;
+		pub const BountyDepositPayoutDelay: u64 = 3;
+		pub const BountyValueMinimum: u64 = 1;
+	}
+	pub struct TenToFourteen;
+	impl Contains<u64> for TenToFourteen {
+		fn contains(n: &u64) -> bool {
+			*n >= 10 && *n <= 14
+		}
+	}
+	impl ContainsCount<u64> for TenToFourteen {
+		fn count() -> usize {
+			5
+		}
 	}
 	impl Trait for Test {
 		type Currency = balances::Module<Test>;
@@ -443,8 +1300,21 @@ mod tests {
 		type ProposalBondMinimum = ProposalBondMinimum;
 		type SpendPeriod = SpendPeriod;
 		type Burn = Burn;
+		type Tippers = TenToFourteen;
+		type TipCountdown = TipCountdown;
+		type TipFindersFee = TipFindersFee;
+		type TipReportDepositBase = TipReportDepositBase;
+		type DataDepositPerByte = DataDepositPerByte;
+		type MaximumReasonLength = MaximumReasonLength;
+		type BountyDepositBase = BountyDepositBase;
+		type BountyDepositPayoutDelay = BountyDepositPayoutDelay;
+		type BountyValueMinimum = BountyValueMinimum;
+		type Call = Call;
+		type Scheduler = Scheduler;
 	}
+	type System = system::Module<Test>;
 	type Balances = balances::Module<Test>;
+	type Scheduler = scheduler::Module<Test>;
 	type Treasury = Module<Test>;
 
 	fn new_test_ext() -> runtime_io::TestExternalities {
@@ -452,7 +1322,6 @@ mod tests {
 		balances::GenesisConfig::<Test>{
 			// Total issuance will be 200 with treasury account initialized at ED.
 			balances: vec![(0, 100), (1, 98), (2, 1)],
-			vesting: vec![],
 		}.assimilate_storage(&mut t).unwrap();
 		GenesisConfig::default().assimilate_storage::<Test>(&mut t).unwrap();
 		t.into()
@@ -668,7 +1537,6 @@ mod tests {
 		let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
 		balances::GenesisConfig::<Test>{
 			balances: vec![(0, 100), (1, 99), (2, 1)],
-			vesting: vec![],
 		}.assimilate_storage(&mut t).unwrap();
 		// Treasury genesis config is not build thus treasury account does not exist
 		let mut t: runtime_io::TestExternalities = t.into();
@@ -695,4 +1563,476 @@ mod tests {
 			assert_eq!(Balances::free_balance(&3), 99); // Balance of `3` has changed
 		});
 	}
+
+	fn tip_hash() -> H256 {
+		BlakeTwo256::hash_of(&(BlakeTwo256::hash(b"awesome.dot"), 3u64))
+	}
+
+	#[test]
+	fn tip_new_cannot_be_used_twice() {
+		new_test_ext().execute_with(|| {
+			Treasury::on_dilution(100, 100);
+			assert_ok!(Treasury::tip_new(Origin::signed(10), b"awesome.dot".to_vec(), 3, 10));
+			assert_noop!(
+				Treasury::tip_new(Origin::signed(11), b"awesome.dot".to_vec(), 3, 10),
+				"tip reason already exists",
+			);
+		});
+	}
+
+	#[test]
+	fn tip_new_requires_a_tipper() {
+		new_test_ext().execute_with(|| {
+			assert_noop!(
+				Treasury::tip_new(Origin::signed(0), b"awesome.dot".to_vec(), 3, 10),
+				"not a tipper",
+			);
+		});
+	}
+
+	#[test]
+	fn report_awesome_and_tip_works() {
+		new_test_ext().execute_with(|| {
+			Treasury::on_dilution(100, 100);
+			assert_ok!(Treasury::report_awesome(Origin::signed(0), b"awesome.dot".to_vec(), 3));
+			assert_eq!(Balances::reserved_balance(&0), 12);
+			assert_eq!(Balances::free_balance(&0), 88);
+
+			// other reports don't count.
+			assert_noop!(
+				Treasury::report_awesome(Origin::signed(1), b"awesome.dot".to_vec(), 3),
+				"tip reason already exists",
+			);
+
+			let h = tip_hash();
+			assert_ok!(Treasury::tip(Origin::signed(10), h.clone(), 10));
+			assert_ok!(Treasury::tip(Origin::signed(11), h.clone(), 10));
+			assert_ok!(Treasury::tip(Origin::signed(12), h.clone(), 10));
+			assert_noop!(Treasury::tip(Origin::signed(9), h.clone(), 10), "not a tipper");
+			System::set_block_number(2);
+			assert_ok!(Treasury::close_tip(Origin::signed(100), h.into()));
+			assert_eq!(Balances::reserved_balance(&0), 0);
+			assert_eq!(Balances::free_balance(&0), 102);
+			assert_eq!(Balances::free_balance(&3), 8);
+			assert_eq!(Treasury::tips(h), None);
+			assert_eq!(Treasury::reasons(BlakeTwo256::hash(b"awesome.dot")), None);
+		});
+	}
+
+	#[test]
+	fn report_awesome_from_beneficiary_and_tip_works() {
+		new_test_ext().execute_with(|| {
+			Treasury::on_dilution(100, 100);
+			assert_ok!(Treasury::report_awesome(Origin::signed(0), b"awesome.dot".to_vec(), 0));
+			assert_eq!(Balances::reserved_balance(&0), 12);
+			assert_eq!(Balances::free_balance(&0), 88);
+			let h = BlakeTwo256::hash_of(&(BlakeTwo256::hash(b"awesome.dot"), 0u64));
+			assert_ok!(Treasury::tip(Origin::signed(10), h.clone(), 10));
+			assert_ok!(Treasury::tip(Origin::signed(11), h.clone(), 10));
+			assert_ok!(Treasury::tip(Origin::signed(12), h.clone(), 10));
+			System::set_block_number(2);
+			assert_ok!(Treasury::close_tip(Origin::signed(100), h.into()));
+			assert_eq!(Balances::reserved_balance(&0), 0);
+			assert_eq!(Balances::free_balance(&0), 110);
+		});
+	}
+
+	#[test]
+	fn close_tip_works() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			Treasury::on_dilution(100, 100);
+			assert_eq!(Treasury::pot(), 100);
+
+			assert_ok!(Treasury::tip_new(Origin::signed(10), b"awesome.dot".to_vec(), 3, 10));
+			let h = tip_hash();
+			assert_ok!(Treasury::tip(Origin::signed(11), h.clone(), 10));
+			assert_noop!(Treasury::close_tip(Origin::signed(0), h.into()), "tip still open");
+
+			assert_ok!(Treasury::tip(Origin::signed(12), h.clone(), 10));
+			assert_noop!(
+				Treasury::close_tip(Origin::signed(0), h.into()),
+				"tip countdown not yet over",
+			);
+
+			System::set_block_number(2);
+			assert_noop!(Treasury::close_tip(Origin::NONE, h.into()), "RequireSignedOrigin");
+			assert_ok!(Treasury::close_tip(Origin::signed(0), h.into()));
+			assert_eq!(Balances::free_balance(&3), 10);
+			assert_eq!(Treasury::pot(), 90);
+
+			assert_noop!(Treasury::close_tip(Origin::signed(100), h.into()), "tip hash unknown");
+		});
+	}
+
+	#[test]
+	fn tip_median_calculation_works() {
+		new_test_ext().execute_with(|| {
+			Treasury::on_dilution(100, 100);
+			assert_ok!(Treasury::tip_new(Origin::signed(10), b"awesome.dot".to_vec(), 3, 0));
+			let h = tip_hash();
+			assert_ok!(Treasury::tip(Origin::signed(11), h.clone(), 10));
+			assert_ok!(Treasury::tip(Origin::signed(12), h.clone(), 1000000));
+			System::set_block_number(2);
+			assert_ok!(Treasury::close_tip(Origin::signed(0), h.into()));
+			assert_eq!(Balances::free_balance(&3), 10);
+		});
+	}
+
+	#[test]
+	fn tip_changing_works() {
+		new_test_ext().execute_with(|| {
+			Treasury::on_dilution(100, 100);
+			assert_ok!(Treasury::tip_new(Origin::signed(10), b"awesome.dot".to_vec(), 3, 10000));
+			let h = tip_hash();
+			assert_ok!(Treasury::tip(Origin::signed(11), h.clone(), 10000));
+			assert_ok!(Treasury::tip(Origin::signed(12), h.clone(), 10000));
+			assert_ok!(Treasury::tip(Origin::signed(13), h.clone(), 0));
+			assert_ok!(Treasury::tip(Origin::signed(14), h.clone(), 0));
+			assert_ok!(Treasury::tip(Origin::signed(12), h.clone(), 1000));
+			assert_ok!(Treasury::tip(Origin::signed(11), h.clone(), 100));
+			assert_ok!(Treasury::tip(Origin::signed(10), h.clone(), 10));
+			System::set_block_number(2);
+			assert_ok!(Treasury::close_tip(Origin::signed(0), h.into()));
+			assert_eq!(Balances::free_balance(&3), 10);
+		});
+	}
+
+	#[test]
+	fn retract_tip_works() {
+		new_test_ext().execute_with(|| {
+			Treasury::on_dilution(100, 100);
+			assert_ok!(Treasury::report_awesome(Origin::signed(0), b"awesome.dot".to_vec(), 3));
+			let h = tip_hash();
+			assert_ok!(Treasury::tip(Origin::signed(10), h.clone(), 10));
+			assert_ok!(Treasury::tip(Origin::signed(11), h.clone(), 10));
+			assert_ok!(Treasury::tip(Origin::signed(12), h.clone(), 10));
+			assert_noop!(Treasury::retract_tip(Origin::signed(10), h.clone()), "not finder");
+			assert_ok!(Treasury::retract_tip(Origin::signed(0), h.clone()));
+			assert_eq!(Balances::reserved_balance(&0), 0);
+			assert_eq!(Balances::free_balance(&0), 100);
+			System::set_block_number(2);
+			assert_noop!(Treasury::close_tip(Origin::signed(0), h.into()), "tip hash unknown");
+		});
+	}
+}
+
+/// Bounty accounts are derived from the module id with a sub-account suffix that does not fit
+/// into the `u64` account ids used by `tests`, so bounties get a mock with `u128` accounts.
+#[cfg(test)]
+mod bounty_tests {
+	use super::*;
+	use super::tests::{
+		BlockHashCount, MaximumBlockWeight, MaximumBlockLength, AvailableBlockRatio,
+		ExistentialDeposit, TransferFee, CreationFee, MaximumWeight, ProposalBond,
+		ProposalBondMinimum, SpendPeriod, Burn, TipCountdown, TipFindersFee, TipReportDepositBase,
+		DataDepositPerByte, MaximumReasonLength, BountyDepositBase, BountyDepositPayoutDelay,
+		BountyValueMinimum,
+	};
+
+	use support::{
+		assert_noop, assert_ok, impl_outer_origin, impl_outer_dispatch,
+		traits::{Contains, OnInitialize},
+	};
+	use primitives::H256;
+	use sr_primitives::traits::{BlakeTwo256, OnFinalize, IdentityLookup};
+	use sr_primitives::testing::Header;
+
+	impl_outer_origin! {
+		pub enum Origin for Test {}
+	}
+
+	impl_outer_dispatch! {
+		pub enum Call for Test where origin: Origin {
+			treasury::Treasury,
+		}
+	}
+
+	#[derive(Clone, Eq, PartialEq)]
+	pub struct Test;
+	impl system::Trait for Test {
+		type Origin = Origin;
+		type Index = u64;
+		type BlockNumber = u64;
+		type Call = ();
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type AccountId = u128;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = Header;
+		type Event = ();
+		type BlockHashCount = BlockHashCount;
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type MaximumBlockLength = MaximumBlockLength;
+		type Version = ();
+	}
+	impl balances::Trait for Test {
+		type Balance = u64;
+		type OnNewAccount = ();
+		type OnFreeBalanceZero = ();
+		type Event = ();
+		type TransferPayment = ();
+		type DustRemoval = ();
+		type ExistentialDeposit = ExistentialDeposit;
+		type TransferFee = TransferFee;
+		type CreationFee = CreationFee;
+	}
+	impl scheduler::Trait for Test {
+		type Event = ();
+		type Call = Call;
+		type MaximumWeight = MaximumWeight;
+		type ScheduleOrigin = system::EnsureRoot<u128>;
+		type Preimages = ();
+	}
+	pub struct TenToFourteen;
+	impl Contains<u128> for TenToFourteen {
+		fn contains(n: &u128) -> bool {
+			*n >= 10 && *n <= 14
+		}
+	}
+	impl ContainsCount<u128> for TenToFourteen {
+		fn count() -> usize {
+			5
+		}
+	}
+	impl Trait for Test {
+		type Currency = balances::Module<Test>;
+		type ApproveOrigin = system::EnsureRoot<u128>;
+		type RejectOrigin = system::EnsureRoot<u128>;
+		type Event = ();
+		type ProposalRejection = ();
+		type ProposalBond = ProposalBond;
+		type ProposalBondMinimum = ProposalBondMinimum;
+		type SpendPeriod = SpendPeriod;
+		type Burn = Burn;
+		type Tippers = TenToFourteen;
+		type TipCountdown = TipCountdown;
+		type TipFindersFee = TipFindersFee;
+		type TipReportDepositBase = TipReportDepositBase;
+		type DataDepositPerByte = DataDepositPerByte;
+		type MaximumReasonLength = MaximumReasonLength;
+		type BountyDepositBase = BountyDepositBase;
+		type BountyDepositPayoutDelay = BountyDepositPayoutDelay;
+		type BountyValueMinimum = BountyValueMinimum;
+		type Call = Call;
+		type Scheduler = Scheduler;
+	}
+	type System = system::Module<Test>;
+	type Balances = balances::Module<Test>;
+	type Scheduler = scheduler::Module<Test>;
+	type Treasury = Module<Test>;
+
+	fn new_test_ext() -> runtime_io::TestExternalities {
+		let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+		balances::GenesisConfig::<Test>{
+			balances: vec![(0, 100), (1, 98), (2, 1)],
+		}.assimilate_storage(&mut t).unwrap();
+		GenesisConfig::default().assimilate_storage::<Test>(&mut t).unwrap();
+		t.into()
+	}
+
+	#[test]
+	fn propose_bounty_works() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			Treasury::on_dilution(100, 100);
+			assert_eq!(Treasury::pot(), 100);
+
+			assert_ok!(Treasury::propose_bounty(Origin::signed(0), 10, b"1234567890".to_vec()));
+
+			let deposit: u64 = 80 + 10;
+			assert_eq!(Balances::reserved_balance(&0), deposit);
+			assert_eq!(Balances::free_balance(&0), 100 - deposit);
+
+			assert_eq!(Treasury::bounties(0).unwrap(), Bounty {
+				proposer: 0,
+				fee: 0,
+				value: 10,
+				bond: deposit,
+				status: BountyStatus::Proposed,
+			});
+			assert_eq!(Treasury::bounty_descriptions(0).unwrap(), b"1234567890".to_vec());
+			assert_eq!(Treasury::bounty_count(), 1);
+		});
+	}
+
+	#[test]
+	fn propose_bounty_validation_works() {
+		new_test_ext().execute_with(|| {
+			assert_noop!(
+				Treasury::propose_bounty(Origin::signed(1), 0, Vec::new()),
+				"invalid bounty value",
+			);
+			assert_noop!(
+				Treasury::propose_bounty(Origin::signed(1), 10, [0u8; 16385].to_vec()),
+				"reason too big",
+			);
+			assert_noop!(
+				Treasury::propose_bounty(Origin::signed(2), 10, b"12345678901234567890".to_vec()),
+				"Proposer's balance too low",
+			);
+		});
+	}
+
+	#[test]
+	fn close_bounty_rejects_proposed_bounty() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(Treasury::propose_bounty(Origin::signed(0), 10, b"12345".to_vec()));
+			assert_noop!(Treasury::close_bounty(Origin::signed(0), 0), "Invalid origin");
+			assert_ok!(Treasury::close_bounty(Origin::ROOT, 0));
+
+			assert_eq!(Balances::reserved_balance(&0), 0);
+			assert_eq!(Balances::free_balance(&0), 100 - 85);
+			assert_eq!(Treasury::bounties(0), None);
+			assert_eq!(Treasury::bounty_descriptions(0), None);
+			assert_noop!(Treasury::close_bounty(Origin::ROOT, 0), "No bounty at that index");
+		});
+	}
+
+	#[test]
+	fn approve_bounty_works() {
+		new_test_ext().execute_with(|| {
+			Treasury::on_dilution(100, 100);
+			assert_noop!(Treasury::approve_bounty(Origin::ROOT, 0), "No bounty at that index");
+
+			assert_ok!(Treasury::propose_bounty(Origin::signed(0), 50, b"12345".to_vec()));
+			assert_ok!(Treasury::approve_bounty(Origin::ROOT, 0));
+			assert_noop!(Treasury::approve_bounty(Origin::ROOT, 0), "bounty is not proposed");
+			assert_noop!(Treasury::close_bounty(Origin::ROOT, 0), "bounty is not yet funded");
+			assert_eq!(Treasury::bounty_approvals(), vec![0]);
+
+			// deposit not returned yet
+			assert_eq!(Balances::reserved_balance(&0), 85);
+
+			<Treasury as OnFinalize<u64>>::on_finalize(2);
+
+			// return deposit and fund the bounty account
+			assert_eq!(Balances::reserved_balance(&0), 0);
+			assert_eq!(Balances::free_balance(&0), 100);
+			assert_eq!(Treasury::bounties(0).unwrap().status, BountyStatus::Funded);
+			assert_eq!(Treasury::bounty_approvals(), Vec::<BountyIndex>::new());
+			assert_eq!(Balances::free_balance(&Treasury::bounty_account_id(0)), 50);
+			// 50 spent on the bounty, half of the remaining 50 burnt.
+			assert_eq!(Treasury::pot(), 25);
+		});
+	}
+
+	#[test]
+	fn bounty_award_and_claim_works() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			Treasury::on_dilution(100, 100);
+			assert_ok!(Treasury::propose_bounty(Origin::signed(0), 50, b"12345".to_vec()));
+			assert_noop!(Treasury::propose_curator(Origin::ROOT, 0, 4, 4), "bounty is not funded");
+			assert_ok!(Treasury::approve_bounty(Origin::ROOT, 0));
+			<Treasury as OnFinalize<u64>>::on_finalize(2);
+
+			assert_noop!(
+				Treasury::propose_curator(Origin::ROOT, 0, 4, 50),
+				"curator fee must be less than the bounty value",
+			);
+			assert_ok!(Treasury::propose_curator(Origin::ROOT, 0, 4, 4));
+			assert_noop!(
+				Treasury::accept_curator(Origin::signed(1), 0),
+				"not the proposed curator",
+			);
+			assert_ok!(Treasury::accept_curator(Origin::signed(4), 0));
+
+			assert_noop!(
+				Treasury::award_bounty(Origin::signed(1), 0, 3),
+				"not the curator of an active bounty",
+			);
+			assert_ok!(Treasury::award_bounty(Origin::signed(4), 0, 3));
+			assert_eq!(Treasury::bounties(0).unwrap().status, BountyStatus::PendingPayout {
+				curator: 4,
+				beneficiary: 3,
+				unlock_at: 4,
+			});
+			assert_noop!(
+				Treasury::unassign_curator(Origin::ROOT, 0),
+				"bounty has no curator to unassign",
+			);
+			assert_noop!(Treasury::close_bounty(Origin::ROOT, 0), "bounty is pending payout");
+
+			System::set_block_number(3);
+			assert_noop!(
+				Treasury::claim_bounty(Origin::signed(1), 0),
+				"bounty payout is not yet unlocked",
+			);
+
+			System::set_block_number(4);
+			assert_ok!(Treasury::claim_bounty(Origin::signed(1), 0));
+
+			assert_eq!(Balances::free_balance(&4), 4); // curator fee
+			assert_eq!(Balances::free_balance(&3), 46);
+			assert_eq!(Balances::free_balance(&Treasury::bounty_account_id(0)), 0);
+			assert_eq!(Treasury::bounties(0), None);
+			assert_eq!(Treasury::bounty_descriptions(0), None);
+		});
+	}
+
+	#[test]
+	fn awarded_bounty_is_claimed_by_the_scheduler() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			Treasury::on_dilution(100, 100);
+			assert_ok!(Treasury::propose_bounty(Origin::signed(0), 50, b"12345".to_vec()));
+			assert_ok!(Treasury::approve_bounty(Origin::ROOT, 0));
+			<Treasury as OnFinalize<u64>>::on_finalize(2);
+			assert_ok!(Treasury::propose_curator(Origin::ROOT, 0, 4, 4));
+			assert_ok!(Treasury::accept_curator(Origin::signed(4), 0));
+			assert_ok!(Treasury::award_bounty(Origin::signed(4), 0, 3));
+
+			System::set_block_number(3);
+			Scheduler::on_initialize(3);
+			assert!(Treasury::bounties(0).is_some());
+
+			System::set_block_number(4);
+			Scheduler::on_initialize(4);
+			assert_eq!(Balances::free_balance(&4), 4);
+			assert_eq!(Balances::free_balance(&3), 46);
+			assert_eq!(Treasury::bounties(0), None);
+		});
+	}
+
+	#[test]
+	fn unassign_curator_works() {
+		new_test_ext().execute_with(|| {
+			Treasury::on_dilution(100, 100);
+			assert_ok!(Treasury::propose_bounty(Origin::signed(0), 50, b"12345".to_vec()));
+			assert_ok!(Treasury::approve_bounty(Origin::ROOT, 0));
+			<Treasury as OnFinalize<u64>>::on_finalize(2);
+
+			assert_ok!(Treasury::propose_curator(Origin::ROOT, 0, 4, 4));
+			assert_noop!(Treasury::unassign_curator(Origin::signed(1), 0), "bad origin");
+			assert_ok!(Treasury::unassign_curator(Origin::signed(4), 0));
+			assert_eq!(Treasury::bounties(0).unwrap().status, BountyStatus::Funded);
+
+			assert_ok!(Treasury::propose_curator(Origin::ROOT, 0, 4, 4));
+			assert_ok!(Treasury::accept_curator(Origin::signed(4), 0));
+			assert_ok!(Treasury::unassign_curator(Origin::ROOT, 0));
+			assert_eq!(Treasury::bounties(0).unwrap().status, BountyStatus::Funded);
+			assert_eq!(Treasury::bounties(0).unwrap().fee, 0);
+		});
+	}
+
+	#[test]
+	fn close_bounty_returns_funds_of_active_bounty() {
+		new_test_ext().execute_with(|| {
+			Treasury::on_dilution(100, 100);
+			assert_ok!(Treasury::propose_bounty(Origin::signed(0), 50, b"12345".to_vec()));
+			assert_ok!(Treasury::approve_bounty(Origin::ROOT, 0));
+			<Treasury as OnFinalize<u64>>::on_finalize(2);
+			assert_eq!(Treasury::pot(), 25);
+
+			assert_ok!(Treasury::propose_curator(Origin::ROOT, 0, 4, 4));
+			assert_ok!(Treasury::accept_curator(Origin::signed(4), 0));
+			assert_ok!(Treasury::close_bounty(Origin::ROOT, 0));
+
+			assert_eq!(Treasury::pot(), 75);
+			assert_eq!(Balances::free_balance(&Treasury::bounty_account_id(0)), 0);
+			assert_eq!(Treasury::bounties(0), None);
+		});
+	}
 }
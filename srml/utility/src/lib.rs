@@ -16,28 +16,147 @@
 
 //! # Utility Module
 //! A module full of useful helpers for practical chain management.
+//!
+//! - [`utility::Trait`](./trait.Trait.html)
+//! - [`Call`](./enum.Call.html)
+//!
+//! ## Overview
+//!
+//! This module contains two basic pieces of functionality:
+//! - Batch dispatch: A stateless operation, allowing any origin to execute multiple calls in a
+//!   single dispatch.
+//! - Multisig dispatch: A stateful operation, allowing multiple signed origins (accounts) to
+//!   coordinate and dispatch a call from a well-known origin, derivable deterministically from
+//!   the set of account IDs and the threshold number of accounts from the set that must approve
+//!   it. In the case that the threshold is just one then this is a stateless operation.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! * `batch` - Dispatch multiple calls from the root origin.
+//! * `as_multi` - Approve and if possible dispatch a call from a composite origin formed from a
+//!   number of signed origins.
+//! * `approve_as_multi` - Approve a call from a composite origin.
+//! * `cancel_as_multi` - Cancel a call from a composite origin.
+//!
+//! [`Call`]: ./enum.Call.html
+//! [`Trait`]: ./trait.Trait.html
 
 // Ensure we're `no_std` when compiling for Wasm.
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use rstd::prelude::*;
-use support::{decl_module, decl_event, Parameter};
-use system::ensure_root;
-use sr_primitives::{traits::Dispatchable, weights::SimpleDispatchInfo, DispatchError};
+use codec::{Encode, Decode};
+use support::{decl_module, decl_event, decl_storage, ensure, Parameter, metadata::TypeInfo};
+use support::traits::{Currency, ReservableCurrency, Get};
+use system::{ensure_root, ensure_signed};
+use sr_primitives::{traits::Dispatchable, DispatchError, RuntimeDebug};
+use sr_primitives::weights::{
+	SimpleDispatchInfo, GetDispatchInfo, WeighData, ClassifyDispatch, DispatchClass, Weight,
+};
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
 
 /// Configuration trait.
 pub trait Trait: system::Trait {
 	/// The overarching event type.
-	type Event: From<Event> + Into<<Self as system::Trait>::Event>;
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
 
 	/// The overarching call type.
-	type Call: Parameter + Dispatchable<Origin=Self::Origin>;
+	type Call: Parameter + Dispatchable<Origin=Self::Origin> + GetDispatchInfo;
+
+	/// The currency mechanism.
+	type Currency: ReservableCurrency<Self::AccountId>;
+
+	/// The base amount of currency needed to reserve for creating a multisig execution.
+	///
+	/// This is held for an additional storage item whose value size is
+	/// `4 + sizeof((BlockNumber, Balance, AccountId))` bytes.
+	type MultisigDepositBase: Get<BalanceOf<Self>>;
+
+	/// The amount of currency needed per unit threshold when creating a multisig execution.
+	///
+	/// This is held for adding 32 bytes more into a pre-existing storage value.
+	type MultisigDepositFactor: Get<BalanceOf<Self>>;
+
+	/// The maximum amount of signatories allowed in the multisig.
+	type MaxSignatories: Get<u16>;
+}
+
+/// A global extrinsic index, formed as the extrinsic index within a block, together with that
+/// block's height. This allows a transaction in which a multisig operation of a particular
+/// composite was created to be uniquely identified.
+#[derive(Copy, Clone, Eq, PartialEq, Encode, Decode, Default, RuntimeDebug, TypeInfo)]
+pub struct Timepoint<BlockNumber> {
+	/// The height of the chain at the point in time.
+	pub height: BlockNumber,
+	/// The index of the extrinsic at the point in time.
+	pub index: u32,
+}
+
+/// An open multisig operation.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, Default, RuntimeDebug, TypeInfo)]
+pub struct Multisig<BlockNumber, Balance, AccountId> {
+	/// The extrinsic when the multisig operation was opened.
+	when: Timepoint<BlockNumber>,
+	/// The amount held in reserve of the `depositor`, to be returned once the operation ends.
+	deposit: Balance,
+	/// The account who opened it (i.e. the first to approve it).
+	depositor: AccountId,
+	/// The approvals achieved so far, including the depositor. Always sorted.
+	approvals: Vec<AccountId>,
+}
+
+/// Weight of `as_multi`: a fixed base weight, plus the weight of the call, which is dispatched
+/// once enough approvals have been given.
+struct WeightWithCall(Weight);
+
+impl<AccountId, BlockNumber, Call: GetDispatchInfo>
+	WeighData<(&u16, &Vec<AccountId>, &Option<Timepoint<BlockNumber>>, &Box<Call>)> for WeightWithCall
+{
+	fn weigh_data(
+		&self,
+		(_, _, _, call): (&u16, &Vec<AccountId>, &Option<Timepoint<BlockNumber>>, &Box<Call>),
+	) -> Weight {
+		self.0.saturating_add(call.get_dispatch_info().weight)
+	}
+}
+
+impl<T> ClassifyDispatch<T> for WeightWithCall {
+	fn classify_dispatch(&self, _: T) -> DispatchClass {
+		DispatchClass::Normal
+	}
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Utility {
+		/// The set of open multisig operations.
+		pub Multisigs get(fn multisigs): double_map hasher(twox_64_concat) T::AccountId, blake2_256([u8; 32])
+			=> Option<Multisig<T::BlockNumber, BalanceOf<T>, T::AccountId>>;
+	}
 }
 
 decl_event!(
 	/// Events type.
-	pub enum Event {
+	pub enum Event<T> where
+		AccountId = <T as system::Trait>::AccountId,
+		BlockNumber = <T as system::Trait>::BlockNumber
+	{
+		/// Batch of dispatches completed, with the result of each of them.
 		BatchExecuted(Vec<Result<(), DispatchError>>),
+		/// A new multisig operation has begun. First param is the account that is approving,
+		/// second is the multisig account.
+		NewMultisig(AccountId, AccountId),
+		/// A multisig operation has been approved by someone. First param is the account that is
+		/// approving, third is the multisig account.
+		MultisigApproval(AccountId, Timepoint<BlockNumber>, AccountId),
+		/// A multisig operation has been executed. First param is the account that is
+		/// approving, third is the multisig account.
+		MultisigExecuted(AccountId, Timepoint<BlockNumber>, AccountId, Result<(), DispatchError>),
+		/// A multisig operation has been cancelled. First param is the account that is
+		/// cancelling, third is the multisig account.
+		MultisigCancelled(AccountId, Timepoint<BlockNumber>, AccountId),
 	}
 );
 
@@ -46,6 +165,15 @@ decl_module! {
 		/// Deposit one of this module's events by using the default implementation.
 		fn deposit_event() = default;
 
+		/// The base deposit reserved when opening a multisig operation.
+		const MultisigDepositBase: BalanceOf<T> = T::MultisigDepositBase::get();
+
+		/// The additional deposit reserved per unit of threshold.
+		const MultisigDepositFactor: BalanceOf<T> = T::MultisigDepositFactor::get();
+
+		/// The maximum amount of signatories allowed in a multisig.
+		const MaxSignatories: u16 = T::MaxSignatories::get();
+
 		/// Send a batch of dispatch calls (only root).
 		#[weight = SimpleDispatchInfo::FreeOperational]
 		fn batch(origin, calls: Vec<<T as Trait>::Call>) {
@@ -54,11 +182,267 @@ decl_module! {
 				.map(|call| call.dispatch(system::RawOrigin::Root.into()))
 				.map(|res| res.map_err(Into::into))
 				.collect::<Vec<_>>();
-			Self::deposit_event(Event::BatchExecuted(results));
+			Self::deposit_event(RawEvent::BatchExecuted(results));
+		}
+
+		/// Register approval for a dispatch to be made from a deterministic composite account if
+		/// approved by a total of `threshold - 1` of `other_signatories`.
+		///
+		/// If there are enough, then dispatch the call.
+		///
+		/// Payment: `MultisigDepositBase` will be reserved if this is the first approval, plus
+		/// `threshold` times `MultisigDepositFactor`. It is returned once this dispatch happens or
+		/// is cancelled.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// - `threshold`: The total number of approvals for this dispatch before it is executed.
+		/// - `other_signatories`: The accounts (other than the sender) who can approve this
+		/// dispatch. May not be empty.
+		/// - `maybe_timepoint`: If this is the first approval, then this must be `None`. If it is
+		/// not the first approval, then it must be `Some`, with the timepoint (block number and
+		/// transaction index) of the first approval transaction.
+		/// - `call`: The call to be executed.
+		///
+		/// NOTE: Unless this is the final approval, you will generally want to use
+		/// `approve_as_multi` instead, since it only requires a hash of the call.
+		///
+		/// On success, result is `Ok` and the result from the interior call, if it was executed,
+		/// may be found in the deposited `MultisigExecuted` event.
+		///
+		/// # <weight>
+		/// - `O(S + Z + Call)`.
+		/// - Up to one balance-reserve or unreserve operation.
+		/// - One passthrough operation, one insert, both `O(S)` where `S` is the number of
+		///   signatories. `S` is capped by `MaxSignatories`, with weight being proportional.
+		/// - One call encode & hash, both of complexity `O(Z)` where `Z` is tx-len.
+		/// - One encode & hash, both of complexity `O(S)`.
+		/// - Up to one binary search and insert (`O(logS + S)`).
+		/// - I/O: 1 read `O(S)`, up to 1 mutate `O(S)`. Up to one remove.
+		/// - One event.
+		/// - The weight of the `call`.
+		/// # </weight>
+		#[weight = WeightWithCall(1_000_000)]
+		fn as_multi(origin,
+			threshold: u16,
+			other_signatories: Vec<T::AccountId>,
+			maybe_timepoint: Option<Timepoint<T::BlockNumber>>,
+			call: Box<<T as Trait>::Call>,
+		) {
+			let who = ensure_signed(origin)?;
+			ensure!(threshold >= 1, "threshold too low");
+			let max_sigs = T::MaxSignatories::get() as usize;
+			ensure!(!other_signatories.is_empty(), "too few signatories");
+			ensure!(other_signatories.len() < max_sigs, "too many signatories");
+			let signatories = Self::ensure_sorted_and_insert(other_signatories, who.clone())?;
+
+			let id = Self::multi_account_id(&signatories, threshold);
+			let call_hash = call.using_encoded(runtime_io::blake2_256);
+
+			if let Some(mut m) = <Multisigs<T>>::get(&id, &call_hash) {
+				let timepoint = maybe_timepoint.ok_or("no timepoint")?;
+				ensure!(m.when == timepoint, "wrong timepoint");
+				if let Err(pos) = m.approvals.binary_search(&who) {
+					// we know threshold is greater than zero from the above ensure.
+					if (m.approvals.len() as u16) < threshold - 1 {
+						m.approvals.insert(pos, who.clone());
+						<Multisigs<T>>::insert(&id, &call_hash, m);
+						Self::deposit_event(RawEvent::MultisigApproval(who, timepoint, id));
+						return Ok(())
+					}
+				} else {
+					ensure!((m.approvals.len() as u16) >= threshold, "already approved");
+				}
+
+				let result = call.dispatch(system::RawOrigin::Signed(id.clone()).into());
+				let _ = T::Currency::unreserve(&m.depositor, m.deposit);
+				<Multisigs<T>>::remove(&id, &call_hash);
+				Self::deposit_event(RawEvent::MultisigExecuted(
+					who, timepoint, id, result.map_err(Into::into),
+				));
+			} else {
+				ensure!(maybe_timepoint.is_none(), "unexpected timepoint");
+				if threshold == 1 {
+					let result = call.dispatch(system::RawOrigin::Signed(id.clone()).into());
+					Self::deposit_event(RawEvent::MultisigExecuted(
+						who, Self::timepoint(), id, result.map_err(Into::into),
+					));
+				} else {
+					let deposit = T::MultisigDepositBase::get()
+						+ T::MultisigDepositFactor::get() * threshold.into();
+					T::Currency::reserve(&who, deposit)?;
+					<Multisigs<T>>::insert(&id, call_hash, Multisig {
+						when: Self::timepoint(),
+						deposit,
+						depositor: who.clone(),
+						approvals: vec![who.clone()],
+					});
+					Self::deposit_event(RawEvent::NewMultisig(who, id));
+				}
+			}
+		}
+
+		/// Register approval for a dispatch to be made from a deterministic composite account if
+		/// approved by a total of `threshold - 1` of `other_signatories`.
+		///
+		/// Payment: `MultisigDepositBase` will be reserved if this is the first approval, plus
+		/// `threshold` times `MultisigDepositFactor`. It is returned once this dispatch happens or
+		/// is cancelled.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// - `threshold`: The total number of approvals for this dispatch before it is executed.
+		/// - `other_signatories`: The accounts (other than the sender) who can approve this
+		/// dispatch. May not be empty.
+		/// - `maybe_timepoint`: If this is the first approval, then this must be `None`. If it is
+		/// not the first approval, then it must be `Some`, with the timepoint (block number and
+		/// transaction index) of the first approval transaction.
+		/// - `call_hash`: The hash of the call to be executed.
+		///
+		/// NOTE: If this is the final approval, you will want to use `as_multi` instead.
+		///
+		/// # <weight>
+		/// - `O(S)`.
+		/// - Up to one balance-reserve or unreserve operation.
+		/// - One passthrough operation, one insert, both `O(S)` where `S` is the number of
+		///   signatories. `S` is capped by `MaxSignatories`, with weight being proportional.
+		/// - One encode & hash, both of complexity `O(S)`.
+		/// - Up to one binary search and insert (`O(logS + S)`).
+		/// - I/O: 1 read `O(S)`, up to 1 mutate `O(S)`. Up to one remove.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		fn approve_as_multi(origin,
+			threshold: u16,
+			other_signatories: Vec<T::AccountId>,
+			maybe_timepoint: Option<Timepoint<T::BlockNumber>>,
+			call_hash: [u8; 32],
+		) {
+			let who = ensure_signed(origin)?;
+			ensure!(threshold >= 2, "threshold too low");
+			let max_sigs = T::MaxSignatories::get() as usize;
+			ensure!(!other_signatories.is_empty(), "too few signatories");
+			ensure!(other_signatories.len() < max_sigs, "too many signatories");
+			let signatories = Self::ensure_sorted_and_insert(other_signatories, who.clone())?;
+
+			let id = Self::multi_account_id(&signatories, threshold);
+
+			if let Some(mut m) = <Multisigs<T>>::get(&id, call_hash) {
+				let timepoint = maybe_timepoint.ok_or("no timepoint")?;
+				ensure!(m.when == timepoint, "wrong timepoint");
+				ensure!(m.approvals.len() < threshold as usize, "already approved");
+				if let Err(pos) = m.approvals.binary_search(&who) {
+					m.approvals.insert(pos, who.clone());
+					<Multisigs<T>>::insert(&id, call_hash, m);
+					Self::deposit_event(RawEvent::MultisigApproval(who, timepoint, id));
+				} else {
+					return Err("already approved")
+				}
+			} else {
+				ensure!(maybe_timepoint.is_none(), "unexpected timepoint");
+				let deposit = T::MultisigDepositBase::get()
+					+ T::MultisigDepositFactor::get() * threshold.into();
+				T::Currency::reserve(&who, deposit)?;
+				<Multisigs<T>>::insert(&id, call_hash, Multisig {
+					when: Self::timepoint(),
+					deposit,
+					depositor: who.clone(),
+					approvals: vec![who.clone()],
+				});
+				Self::deposit_event(RawEvent::NewMultisig(who, id));
+			}
+		}
+
+		/// Cancel a pre-existing, on-going multisig transaction. Any deposit reserved previously
+		/// for this operation will be unreserved on success.
+		///
+		/// The dispatch origin for this call must be _Signed_ and must be the account which opened
+		/// the operation.
+		///
+		/// - `threshold`: The total number of approvals for this dispatch before it is executed.
+		/// - `other_signatories`: The accounts (other than the sender) who can approve this
+		/// dispatch. May not be empty.
+		/// - `timepoint`: The timepoint (block number and transaction index) of the first approval
+		/// transaction for this dispatch.
+		/// - `call_hash`: The hash of the call to be executed.
+		///
+		/// # <weight>
+		/// - `O(S)`.
+		/// - Up to one balance-reserve or unreserve operation.
+		/// - One passthrough operation, one insert, both `O(S)` where `S` is the number of
+		///   signatories. `S` is capped by `MaxSignatories`, with weight being proportional.
+		/// - One encode & hash, both of complexity `O(S)`.
+		/// - One event.
+		/// - I/O: 1 read `O(S)`, one remove.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		fn cancel_as_multi(origin,
+			threshold: u16,
+			other_signatories: Vec<T::AccountId>,
+			timepoint: Timepoint<T::BlockNumber>,
+			call_hash: [u8; 32],
+		) {
+			let who = ensure_signed(origin)?;
+			ensure!(threshold >= 2, "threshold too low");
+			let max_sigs = T::MaxSignatories::get() as usize;
+			ensure!(!other_signatories.is_empty(), "too few signatories");
+			ensure!(other_signatories.len() < max_sigs, "too many signatories");
+			let signatories = Self::ensure_sorted_and_insert(other_signatories, who.clone())?;
+
+			let id = Self::multi_account_id(&signatories, threshold);
+
+			let m = <Multisigs<T>>::get(&id, call_hash).ok_or("not found")?;
+			ensure!(m.when == timepoint, "wrong timepoint");
+			ensure!(m.depositor == who, "not owner");
+
+			let _ = T::Currency::unreserve(&m.depositor, m.deposit);
+			<Multisigs<T>>::remove(&id, call_hash);
+
+			Self::deposit_event(RawEvent::MultisigCancelled(who, timepoint, id));
 		}
 	}
 }
 
+impl<T: Trait> Module<T> {
+	/// Derive a multi-account ID from the sorted list of accounts and the threshold that are
+	/// required.
+	///
+	/// NOTE: `who` must be sorted. If it is not, then you'll get the wrong answer.
+	pub fn multi_account_id(who: &[T::AccountId], threshold: u16) -> T::AccountId {
+		let entropy = (b"modlpy/utilisuba", who, threshold).using_encoded(runtime_io::blake2_256);
+		T::AccountId::decode(&mut &entropy[..]).unwrap_or_default()
+	}
+
+	/// The current `Timepoint`.
+	pub fn timepoint() -> Timepoint<T::BlockNumber> {
+		Timepoint {
+			height: <system::Module<T>>::block_number(),
+			index: <system::Module<T>>::extrinsic_index().unwrap_or_default(),
+		}
+	}
+
+	/// Check that signatories is sorted and doesn't contain sender, then insert sender.
+	fn ensure_sorted_and_insert(other_signatories: Vec<T::AccountId>, who: T::AccountId)
+		-> Result<Vec<T::AccountId>, &'static str>
+	{
+		let mut signatories = other_signatories;
+		let mut maybe_last = None;
+		let mut index = 0;
+		for item in signatories.iter() {
+			if let Some(last) = maybe_last {
+				ensure!(last < item, "signatories not sorted");
+			}
+			if item <= &who {
+				ensure!(item != &who, "sender in signatories");
+				index += 1;
+			}
+			maybe_last = Some(item);
+		}
+		signatories.insert(index, who);
+		Ok(signatories)
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -122,9 +506,18 @@ mod tests {
 		type TransferFee = TransferFee;
 		type CreationFee = CreationFee;
 	}
+	parameter_types! {
+		pub const MultisigDepositBase: u64 = 1;
+		pub const MultisigDepositFactor: u64 = 1;
+		pub const MaxSignatories: u16 = 3;
+	}
 	impl Trait for Test {
 		type Event = ();
 		type Call = Call;
+		type Currency = Balances;
+		type MultisigDepositBase = MultisigDepositBase;
+		type MultisigDepositFactor = MultisigDepositFactor;
+		type MaxSignatories = MaxSignatories;
 	}
 	type Balances = balances::Module<Test>;
 	type Utility = Module<Test>;
@@ -132,8 +525,7 @@ mod tests {
 	fn new_test_ext() -> runtime_io::TestExternalities {
 		let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
 		balances::GenesisConfig::<Test> {
-			balances: vec![(1, 10), (2, 0)],
-			vesting: vec![],
+			balances: vec![(1, 10), (2, 10), (3, 10), (4, 10), (5, 10)],
 		}.assimilate_storage(&mut t).unwrap();
 		t.into()
 	}
@@ -142,7 +534,7 @@ mod tests {
 	fn batch_works() {
 		new_test_ext().execute_with(|| {
 			assert_eq!(Balances::free_balance(1), 10);
-			assert_eq!(Balances::free_balance(2), 0);
+			assert_eq!(Balances::free_balance(2), 10);
 			assert_noop!(Utility::batch(Origin::signed(1), vec![
 				Call::Balances(balances::Call::force_transfer(1, 2, 5)),
 				Call::Balances(balances::Call::force_transfer(1, 2, 5))
@@ -152,7 +544,186 @@ mod tests {
 				Call::Balances(balances::Call::force_transfer(1, 2, 5))
 			]));
 			assert_eq!(Balances::free_balance(1), 0);
-			assert_eq!(Balances::free_balance(2), 10);
+			assert_eq!(Balances::free_balance(2), 20);
 		});
 	}
+
+	fn now() -> Timepoint<u64> {
+		Utility::timepoint()
+	}
+
+	#[test]
+	fn multisig_deposit_is_taken_and_returned() {
+		new_test_ext().execute_with(|| {
+			let multi = Utility::multi_account_id(&[1, 2, 3][..], 2);
+			assert_ok!(Balances::transfer(Origin::signed(1), multi, 5));
+			assert_ok!(Balances::transfer(Origin::signed(2), multi, 5));
+			assert_ok!(Balances::transfer(Origin::signed(3), multi, 5));
+
+			let call = Box::new(Call::Balances(balances::Call::transfer(6, 15)));
+			assert_ok!(Utility::as_multi(Origin::signed(1), 2, vec![2, 3], None, call.clone()));
+			assert_eq!(Balances::free_balance(1), 2);
+			assert_eq!(Balances::reserved_balance(1), 3);
+
+			assert_ok!(Utility::as_multi(Origin::signed(2), 2, vec![1, 3], Some(now()), call));
+			assert_eq!(Balances::free_balance(1), 5);
+			assert_eq!(Balances::reserved_balance(1), 0);
+		});
+	}
+
+	#[test]
+	fn cancel_multisig_returns_deposit() {
+		new_test_ext().execute_with(|| {
+			let call = Box::new(Call::Balances(balances::Call::transfer(6, 15)));
+			let hash = call.using_encoded(runtime_io::blake2_256);
+			assert_ok!(Utility::approve_as_multi(Origin::signed(1), 3, vec![2, 3], None, hash));
+			assert_ok!(Utility::approve_as_multi(Origin::signed(2), 3, vec![1, 3], Some(now()), hash));
+			assert_eq!(Balances::free_balance(1), 6);
+			assert_eq!(Balances::reserved_balance(1), 4);
+
+			assert_noop!(
+				Utility::cancel_as_multi(Origin::signed(2), 3, vec![1, 3], now(), hash),
+				"not owner",
+			);
+			assert_ok!(Utility::cancel_as_multi(Origin::signed(1), 3, vec![2, 3], now(), hash));
+			assert_eq!(Balances::free_balance(1), 10);
+			assert_eq!(Balances::reserved_balance(1), 0);
+		});
+	}
+
+	#[test]
+	fn multisig_2_of_3_works() {
+		new_test_ext().execute_with(|| {
+			let multi = Utility::multi_account_id(&[1, 2, 3][..], 2);
+			assert_ok!(Balances::transfer(Origin::signed(1), multi, 5));
+			assert_ok!(Balances::transfer(Origin::signed(2), multi, 5));
+			assert_ok!(Balances::transfer(Origin::signed(3), multi, 5));
+
+			let call = Box::new(Call::Balances(balances::Call::transfer(6, 15)));
+			let hash = call.using_encoded(runtime_io::blake2_256);
+			assert_ok!(Utility::approve_as_multi(Origin::signed(1), 2, vec![2, 3], None, hash));
+			assert_eq!(Balances::free_balance(6), 0);
+
+			assert_ok!(Utility::as_multi(Origin::signed(2), 2, vec![1, 3], Some(now()), call));
+			assert_eq!(Balances::free_balance(6), 15);
+			assert_eq!(Utility::multisigs(&multi, &hash), None);
+		});
+	}
+
+	#[test]
+	fn multisig_3_of_3_works() {
+		new_test_ext().execute_with(|| {
+			let multi = Utility::multi_account_id(&[1, 2, 3][..], 3);
+			assert_ok!(Balances::transfer(Origin::signed(1), multi, 5));
+			assert_ok!(Balances::transfer(Origin::signed(2), multi, 5));
+			assert_ok!(Balances::transfer(Origin::signed(3), multi, 5));
+
+			let call = Box::new(Call::Balances(balances::Call::transfer(6, 15)));
+			let hash = call.using_encoded(runtime_io::blake2_256);
+			assert_ok!(Utility::approve_as_multi(Origin::signed(1), 3, vec![2, 3], None, hash));
+			assert_ok!(Utility::approve_as_multi(Origin::signed(2), 3, vec![1, 3], Some(now()), hash));
+			assert_eq!(Balances::free_balance(6), 0);
+
+			assert_ok!(Utility::as_multi(Origin::signed(3), 3, vec![1, 2], Some(now()), call));
+			assert_eq!(Balances::free_balance(6), 15);
+		});
+	}
+
+	#[test]
+	fn multisig_1_of_3_works() {
+		new_test_ext().execute_with(|| {
+			let multi = Utility::multi_account_id(&[1, 2, 3][..], 1);
+			assert_ok!(Balances::transfer(Origin::signed(1), multi, 5));
+			assert_ok!(Balances::transfer(Origin::signed(2), multi, 5));
+			assert_ok!(Balances::transfer(Origin::signed(3), multi, 5));
+
+			let call = Box::new(Call::Balances(balances::Call::transfer(6, 15)));
+			let hash = call.using_encoded(runtime_io::blake2_256);
+			assert_noop!(
+				Utility::approve_as_multi(Origin::signed(1), 1, vec![2, 3], None, hash),
+				"threshold too low",
+			);
+			assert_ok!(Utility::as_multi(Origin::signed(1), 1, vec![2, 3], None, call));
+			assert_eq!(Balances::free_balance(6), 15);
+			assert_eq!(Balances::reserved_balance(1), 0);
+		});
+	}
+
+	#[test]
+	fn multisig_requires_timepoint_of_first_approval() {
+		new_test_ext().execute_with(|| {
+			let call = Box::new(Call::Balances(balances::Call::transfer(6, 15)));
+			let hash = call.using_encoded(runtime_io::blake2_256);
+			assert_noop!(
+				Utility::approve_as_multi(Origin::signed(1), 2, vec![2, 3], Some(now()), hash),
+				"unexpected timepoint",
+			);
+			assert_ok!(Utility::approve_as_multi(Origin::signed(1), 2, vec![2, 3], None, hash));
+			assert_noop!(
+				Utility::as_multi(Origin::signed(2), 2, vec![1, 3], None, call.clone()),
+				"no timepoint",
+			);
+			let later = Timepoint { height: 2, .. now() };
+			assert_noop!(
+				Utility::as_multi(Origin::signed(2), 2, vec![1, 3], Some(later), call),
+				"wrong timepoint",
+			);
+		});
+	}
+
+	#[test]
+	fn multisig_checks_signatories() {
+		new_test_ext().execute_with(|| {
+			let call = Box::new(Call::Balances(balances::Call::transfer(6, 15)));
+			assert_noop!(
+				Utility::as_multi(Origin::signed(1), 2, vec![], None, call.clone()),
+				"too few signatories",
+			);
+			assert_noop!(
+				Utility::as_multi(Origin::signed(1), 2, vec![2, 3, 4], None, call.clone()),
+				"too many signatories",
+			);
+			assert_noop!(
+				Utility::as_multi(Origin::signed(1), 2, vec![3, 2], None, call.clone()),
+				"signatories not sorted",
+			);
+			assert_noop!(
+				Utility::as_multi(Origin::signed(1), 2, vec![1, 2], None, call),
+				"sender in signatories",
+			);
+		});
+	}
+
+	#[test]
+	fn duplicate_approvals_are_ignored() {
+		new_test_ext().execute_with(|| {
+			let call = Box::new(Call::Balances(balances::Call::transfer(6, 15)));
+			let hash = call.using_encoded(runtime_io::blake2_256);
+			assert_ok!(Utility::approve_as_multi(Origin::signed(1), 3, vec![2, 3], None, hash));
+			assert_ok!(Utility::approve_as_multi(Origin::signed(2), 3, vec![1, 3], Some(now()), hash));
+			assert_noop!(
+				Utility::approve_as_multi(Origin::signed(2), 3, vec![1, 3], Some(now()), hash),
+				"already approved",
+			);
+			assert_noop!(
+				Utility::as_multi(Origin::signed(2), 3, vec![1, 3], Some(now()), call),
+				"already approved",
+			);
+		});
+	}
+
+	#[test]
+	fn multi_account_id_depends_on_signatories_and_threshold() {
+		assert_ne!(Utility::multi_account_id(&[1, 2, 3][..], 2), Utility::multi_account_id(&[1, 2, 3][..], 3));
+		assert_ne!(Utility::multi_account_id(&[1, 2, 3][..], 2), Utility::multi_account_id(&[1, 2, 4][..], 2));
+		assert_eq!(Utility::multi_account_id(&[1, 2, 3][..], 2), Utility::multi_account_id(&[1, 2, 3][..], 2));
+	}
+
+	#[test]
+	fn as_multi_weight_includes_the_call() {
+		let call = Box::new(Call::Balances(balances::Call::transfer(6, 15)));
+		let call_weight = call.get_dispatch_info().weight;
+		let as_multi = Call::Utility(crate::Call::as_multi(3, vec![1, 2], None, call));
+		assert_eq!(as_multi.get_dispatch_info().weight, 1_000_000 + call_weight);
+	}
 }
@@ -0,0 +1,682 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Vesting Module
+//!
+//! - [`vesting::Trait`](./trait.Trait.html)
+//! - [`Call`](./enum.Call.html)
+//!
+//! ## Overview
+//!
+//! A simple module providing a means of placing a linear curve on an account's locked balance. This
+//! module ensures that there is a lock in place preventing the balance to drop below the *unvested*
+//! amount for any reason other than transaction fee payment.
+//!
+//! As the amount vested increases over time, the amount unvested reduces. However, locks remain in
+//! place and explicit action is needed on behalf of the user to ensure that the amount locked is
+//! equivalent to the amount remaining to be vested. This is done through a dispatchable function,
+//! either `vest` (in typical case where the sender is calling on their own behalf) or `vest_other`
+//! in case the sender is calling on another account's behalf.
+//!
+//! An account may have several vesting schedules running at the same time, up to
+//! `MaxVestingSchedules`. The amount locked is the sum of what remains unvested under each of them.
+//!
+//! Vesting schedules used to be kept by the balances module. The schedules of the accounts given
+//! by `LegacyVestingAccounts` are moved to this module, and their funds locked, on runtime upgrade.
+//!
+//! ## Interface
+//!
+//! This module implements the `Call` dispatchables listed below, and exposes `vesting_balance` for
+//! other modules and RPCs to query the amount still locked.
+//!
+//! ### Dispatchable Functions
+//!
+//! - `vest` - Update the lock, reducing it in line with the amount "vested" so far.
+//! - `vest_other` - Update the lock of another account, reducing it in line with the amount
+//!   "vested" so far.
+//! - `vested_transfer` - Transfer funds to another account under a new vesting schedule.
+//!
+//! [`Call`]: ./enum.Call.html
+//! [`Trait`]: ./trait.Trait.html
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use rstd::prelude::*;
+use codec::{Encode, Decode};
+use sr_primitives::{
+	RuntimeDebug,
+	traits::{
+		StaticLookup, Zero, SimpleArithmetic, Saturating, Convert, Bounded,
+	},
+	weights::{Weight, SimpleDispatchInfo},
+};
+use support::{
+	decl_module, decl_event, decl_storage, ensure, storage, metadata::TypeInfo,
+	traits::{Currency, LockableCurrency, WithdrawReason, WithdrawReasons, LockIdentifier, Get},
+};
+use system::ensure_signed;
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+
+pub trait Trait: system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+	/// The currency trait.
+	type Currency: LockableCurrency<Self::AccountId, Moment=Self::BlockNumber>;
+
+	/// Convert the block number into a balance.
+	type BlockNumberToBalance: Convert<Self::BlockNumber, BalanceOf<Self>>;
+
+	/// The minimum amount to be transferred to create a new vesting schedule.
+	type MinVestedTransfer: Get<BalanceOf<Self>>;
+
+	/// The maximum number of vesting schedules an account may have at a given moment.
+	type MaxVestingSchedules: Get<u32>;
+
+	/// The accounts which may still have a vesting schedule in the storage of the balances
+	/// module, typically those given one at genesis. Their schedules are migrated on runtime
+	/// upgrade.
+	type LegacyVestingAccounts: Get<Vec<Self::AccountId>>;
+}
+
+const VESTING_ID: LockIdentifier = *b"vesting ";
+
+/// The prefix of the vesting schedules formerly kept by the balances module.
+const LEGACY_VESTING_PREFIX: &[u8] = b"Balances Vesting";
+
+/// The weight of migrating the vesting schedule of one account.
+const MIGRATION_WEIGHT_PER_ACCOUNT: Weight = 50_000;
+
+/// Struct to encode the vesting schedule of an individual account.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct VestingInfo<Balance, BlockNumber> {
+	/// Locked amount at genesis.
+	pub locked: Balance,
+	/// Amount that gets unlocked every block after `starting_block`.
+	pub per_block: Balance,
+	/// Starting block for unlocking(vesting).
+	pub starting_block: BlockNumber,
+}
+
+impl<
+	Balance: SimpleArithmetic + Copy,
+	BlockNumber: SimpleArithmetic + Copy,
+> VestingInfo<Balance, BlockNumber> {
+	/// Amount locked at block `n`.
+	pub fn locked_at<
+		BlockNumberToBalance: Convert<BlockNumber, Balance>
+	>(&self, n: BlockNumber) -> Balance {
+		// Number of blocks that count toward vesting
+		// Saturating to 0 when n < starting_block
+		let vested_block_count = n.saturating_sub(self.starting_block);
+		let vested_block_count = BlockNumberToBalance::convert(vested_block_count);
+		// Return amount that is still locked in vesting
+		if let Some(x) = vested_block_count.checked_mul(&self.per_block) {
+			self.locked.max(x) - x
+		} else {
+			Zero::zero()
+		}
+	}
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Vesting {
+		/// Information regarding the vesting of a given account.
+		pub Vesting get(fn vesting): map T::AccountId => Vec<VestingInfo<BalanceOf<T>, T::BlockNumber>>;
+	}
+	add_extra_genesis {
+		config(vesting): Vec<(T::AccountId, T::BlockNumber, T::BlockNumber, BalanceOf<T>)>;
+		// ^^ begin, length, amount liquid at genesis
+		build(|config: &GenesisConfig<T>| {
+			// Generate initial vesting configuration
+			// * who - Account which we are generating vesting configuration for
+			// * begin - Block when the account will start to vest
+			// * length - Number of blocks from `begin` until fully vested
+			// * liquid - Number of units which can be spent before vesting begins
+			for &(ref who, begin, length, liquid) in config.vesting.iter() {
+				let balance = T::Currency::free_balance(who);
+				assert!(!balance.is_zero(), "Currencies must be init'd before vesting");
+				// Total genesis `balance` minus `liquid` equals funds locked for vesting
+				let locked = balance.saturating_sub(liquid);
+				let length_as_balance = T::BlockNumberToBalance::convert(length);
+				// Number of units unlocked per block after `begin`
+				let per_block = locked / length_as_balance.max(sr_primitives::traits::One::one());
+
+				let total_locked = <Vesting<T>>::mutate(who, |v| {
+					v.push(VestingInfo { locked, per_block, starting_block: begin });
+					v.iter().fold(Zero::zero(), |acc: BalanceOf<T>, v| acc.saturating_add(v.locked))
+				});
+				let reasons = WithdrawReasons::except(WithdrawReason::TransactionPayment);
+				T::Currency::set_lock(VESTING_ID, who, total_locked, T::BlockNumber::max_value(), reasons);
+			}
+		})
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where AccountId = <T as system::Trait>::AccountId, Balance = BalanceOf<T> {
+		/// The amount vested has been updated. This could indicate more funds are available. The
+		/// balance given is the amount which is left unvested (and thus locked).
+		VestingUpdated(AccountId, Balance),
+		/// An account (given) has become fully vested. No further vesting can happen.
+		VestingCompleted(AccountId),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		fn deposit_event() = default;
+
+		/// The minimum amount to be transferred to create a new vesting schedule.
+		const MinVestedTransfer: BalanceOf<T> = T::MinVestedTransfer::get();
+
+		/// The maximum number of vesting schedules an account may have at a given moment.
+		const MaxVestingSchedules: u32 = T::MaxVestingSchedules::get();
+
+		fn on_runtime_upgrade() -> Weight {
+			Self::migrate_legacy_vesting()
+		}
+
+		/// Unlock any vested funds of the sender account.
+		///
+		/// The dispatch origin for this call must be _Signed_ and the sender must have funds still
+		/// locked under this module.
+		///
+		/// Emits either `VestingCompleted` or `VestingUpdated`.
+		///
+		/// # <weight>
+		/// - `O(S)` where `S` is the number of vesting schedules (bounded by `MaxVestingSchedules`).
+		/// - One storage read and one storage write.
+		/// - One balance-lock operation.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(50_000)]
+		fn vest(origin) {
+			let who = ensure_signed(origin)?;
+			Self::update_lock(who)?;
+		}
+
+		/// Unlock any vested funds of a `target` account.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// - `target`: The account whose vested funds should be unlocked. Must have funds still
+		///   locked under this module.
+		///
+		/// Emits either `VestingCompleted` or `VestingUpdated`.
+		///
+		/// # <weight>
+		/// - `O(S)` where `S` is the number of vesting schedules (bounded by `MaxVestingSchedules`).
+		/// - Up to one account-lookup operation.
+		/// - One storage read and one storage write.
+		/// - One balance-lock operation.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(50_000)]
+		fn vest_other(origin, target: <T::Lookup as StaticLookup>::Source) {
+			ensure_signed(origin)?;
+			Self::update_lock(T::Lookup::lookup(target)?)?;
+		}
+
+		/// Create a vested transfer.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// - `target`: The account that should be transferred the vested funds.
+		/// - `schedule`: The vesting schedule attached to the transfer. `schedule.locked` is
+		///   transferred and must be at least `MinVestedTransfer`.
+		///
+		/// Emits `VestingUpdated`.
+		///
+		/// # <weight>
+		/// - `O(S)` where `S` is the number of vesting schedules (bounded by `MaxVestingSchedules`).
+		/// - Up to one account-lookup operation.
+		/// - One balance-transfer operation.
+		/// - One storage read and one storage write.
+		/// - One balance-lock operation.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(1_000_000)]
+		fn vested_transfer(
+			origin,
+			target: <T::Lookup as StaticLookup>::Source,
+			schedule: VestingInfo<BalanceOf<T>, T::BlockNumber>,
+		) {
+			let transactor = ensure_signed(origin)?;
+			ensure!(schedule.locked >= T::MinVestedTransfer::get(), "amount low");
+			ensure!(!schedule.per_block.is_zero(), "invalid schedule");
+
+			let who = T::Lookup::lookup(target)?;
+			ensure!(
+				(Self::vesting(&who).len() as u32) < T::MaxVestingSchedules::get(),
+				"at max vesting schedules",
+			);
+
+			T::Currency::transfer(&transactor, &who, schedule.locked)?;
+
+			<Vesting<T>>::mutate(&who, |v| v.push(schedule));
+			Self::update_lock(who)?;
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// Get the amount that is currently being vested and cannot be transferred out of this account.
+	///
+	/// Returns `None` if the account has no vesting schedule.
+	pub fn vesting_balance(who: &T::AccountId) -> Option<BalanceOf<T>> {
+		let vesting = Self::vesting(who);
+		if vesting.is_empty() {
+			return None
+		}
+
+		let now = <system::Module<T>>::block_number();
+		let locked = vesting.iter()
+			.map(|v| v.locked_at::<T::BlockNumberToBalance>(now))
+			.fold(Zero::zero(), |acc: BalanceOf<T>, l| acc.saturating_add(l));
+		Some(T::Currency::free_balance(who).min(locked))
+	}
+
+	/// Move the vesting schedules of `LegacyVestingAccounts` out of the storage of the balances
+	/// module, which no longer enforces them, and lock the funds they have yet to vest.
+	fn migrate_legacy_vesting() -> Weight {
+		let accounts = T::LegacyVestingAccounts::get();
+		for who in accounts.iter() {
+			let mut key = LEGACY_VESTING_PREFIX.to_vec();
+			who.encode_to(&mut key);
+			let legacy: Option<VestingInfo<BalanceOf<T>, T::BlockNumber>> =
+				storage::hashed::take(&runtime_io::blake2_256, &key);
+			if let Some(schedule) = legacy {
+				<Vesting<T>>::mutate(who, |v| v.push(schedule));
+				// Fails only if the account has no schedule, which it has been given above.
+				let _ = Self::update_lock(who.clone());
+			}
+		}
+		(accounts.len() as Weight).saturating_mul(MIGRATION_WEIGHT_PER_ACCOUNT)
+	}
+
+	/// (Re)set or remove the module's currency lock on `who`'s account in accordance with their
+	/// current unvested amount. Fully vested schedules are dropped.
+	fn update_lock(who: T::AccountId) -> Result<(), &'static str> {
+		let vesting = Self::vesting(&who);
+		ensure!(!vesting.is_empty(), "not vesting");
+
+		let now = <system::Module<T>>::block_number();
+		let mut locked_now: BalanceOf<T> = Zero::zero();
+		let still_vesting = vesting.into_iter()
+			.filter(|v| {
+				let locked = v.locked_at::<T::BlockNumberToBalance>(now);
+				locked_now = locked_now.saturating_add(locked);
+				!locked.is_zero()
+			})
+			.collect::<Vec<_>>();
+
+		if locked_now.is_zero() {
+			T::Currency::remove_lock(VESTING_ID, &who);
+			<Vesting<T>>::remove(&who);
+			Self::deposit_event(RawEvent::VestingCompleted(who));
+		} else {
+			let reasons = WithdrawReasons::except(WithdrawReason::TransactionPayment);
+			T::Currency::set_lock(VESTING_ID, &who, locked_now, T::BlockNumber::max_value(), reasons);
+			<Vesting<T>>::insert(&who, still_vesting);
+			Self::deposit_event(RawEvent::VestingUpdated(who, locked_now));
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use support::{assert_ok, assert_noop, impl_outer_origin, parameter_types};
+	use primitives::H256;
+	// The testing primitives are very useful for avoiding having to work with signatures
+	// or public keys. `u64` is used as the `AccountId` and no `Signature`s are required.
+	use sr_primitives::{
+		Perbill, testing::Header, traits::{BlakeTwo256, IdentityLookup, ConvertInto, OnRuntimeUpgrade},
+	};
+
+	impl_outer_origin! {
+		pub enum Origin for Test {}
+	}
+
+	// For testing the module, we construct most of a mock runtime. This means
+	// first constructing a configuration type (`Test`) which `impl`s each of the
+	// configuration traits of modules we want to use.
+	#[derive(Clone, Eq, PartialEq)]
+	pub struct Test;
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const MaximumBlockWeight: u32 = 1024;
+		pub const MaximumBlockLength: u32 = 2 * 1024;
+		pub const AvailableBlockRatio: Perbill = Perbill::one();
+	}
+	impl system::Trait for Test {
+		type Origin = Origin;
+		type Index = u64;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Call = ();
+		type Hashing = BlakeTwo256;
+		type AccountId = u64;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = Header;
+		type Event = ();
+		type BlockHashCount = BlockHashCount;
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type MaximumBlockLength = MaximumBlockLength;
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type Version = ();
+	}
+	parameter_types! {
+		pub const ExistentialDeposit: u64 = 0;
+		pub const TransferFee: u64 = 0;
+		pub const CreationFee: u64 = 0;
+	}
+	impl balances::Trait for Test {
+		type Balance = u64;
+		type OnFreeBalanceZero = ();
+		type OnNewAccount = ();
+		type Event = ();
+		type TransferPayment = ();
+		type DustRemoval = ();
+		type ExistentialDeposit = ExistentialDeposit;
+		type TransferFee = TransferFee;
+		type CreationFee = CreationFee;
+	}
+	parameter_types! {
+		pub const MinVestedTransfer: u64 = 10;
+		pub const MaxVestingSchedules: u32 = 3;
+	}
+	pub struct LegacyVestingAccounts;
+	impl Get<Vec<u64>> for LegacyVestingAccounts {
+		fn get() -> Vec<u64> {
+			vec![3, 4]
+		}
+	}
+	impl Trait for Test {
+		type Event = ();
+		type Currency = Balances;
+		type BlockNumberToBalance = ConvertInto;
+		type MinVestedTransfer = MinVestedTransfer;
+		type MaxVestingSchedules = MaxVestingSchedules;
+		type LegacyVestingAccounts = LegacyVestingAccounts;
+	}
+	type System = system::Module<Test>;
+	type Balances = balances::Module<Test>;
+	type Vesting = Module<Test>;
+
+	// This function basically just builds a genesis storage key/value store according to
+	// our desired mockup.
+	fn new_test_ext() -> runtime_io::TestExternalities {
+		let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+		balances::GenesisConfig::<Test> {
+			balances: vec![
+				(1, 100),
+				(2, 200),
+				(3, 300),
+				(4, 400),
+				(12, 100),
+			],
+		}.assimilate_storage(&mut t).unwrap();
+		GenesisConfig::<Test> {
+			vesting: vec![
+				(1, 0, 10, 50),
+				(2, 10, 20, 0),
+				(12, 10, 20, 50),
+			],
+		}.assimilate_storage(&mut t).unwrap();
+		let mut ext = runtime_io::TestExternalities::new(t);
+		ext.execute_with(|| System::set_block_number(1));
+		ext
+	}
+
+	#[test]
+	fn check_vesting_status() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(Vesting::vesting(&1), vec![VestingInfo {
+				locked: 50,
+				per_block: 5, // Vesting over 10 blocks
+				starting_block: 0,
+			}]);
+			assert_eq!(Vesting::vesting(&2), vec![VestingInfo {
+				locked: 200,
+				per_block: 10, // Vesting over 20 blocks
+				starting_block: 10,
+			}]);
+			assert_eq!(Vesting::vesting(&12), vec![VestingInfo {
+				locked: 50,
+				per_block: 2, // Vesting over 25 blocks
+				starting_block: 10,
+			}]);
+			assert_eq!(Vesting::vesting_balance(&3), None);
+
+			// Account 1 has only 5 units vested from their illiquid 50 units at block 1
+			assert_eq!(Vesting::vesting_balance(&1), Some(45));
+			// Account 2 has their full balance locked
+			assert_eq!(Vesting::vesting_balance(&2), Some(200));
+			// Account 12 has only their illiquid funds locked
+			assert_eq!(Vesting::vesting_balance(&12), Some(50));
+
+			System::set_block_number(10);
+
+			// Account 1 has fully vested by block 10
+			assert_eq!(Vesting::vesting_balance(&1), Some(0));
+			// Account 2 has started vesting by block 10
+			assert_eq!(Vesting::vesting_balance(&2), Some(200));
+			// Account 12 has started vesting by block 10
+			assert_eq!(Vesting::vesting_balance(&12), Some(50));
+
+			System::set_block_number(30);
+
+			assert_eq!(Vesting::vesting_balance(&1), Some(0)); // Account 1 is still fully vested, and not negative
+			assert_eq!(Vesting::vesting_balance(&2), Some(0)); // Account 2 has fully vested by block 30
+			// Account 12's per-block amount was rounded down, so a little is still locked
+			assert_eq!(Vesting::vesting_balance(&12), Some(10));
+
+			System::set_block_number(35);
+			assert_eq!(Vesting::vesting_balance(&12), Some(0)); // Account 12 has fully vested by block 35
+		});
+	}
+
+	#[test]
+	fn unvested_balance_should_not_transfer() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(Balances::free_balance(&1), 100);
+			// Account 1 has only 5 units vested at block 1 (plus 50 unvested)
+			assert_eq!(Vesting::vesting_balance(&1), Some(45));
+			assert_noop!(
+				Balances::transfer(Some(1).into(), 2, 56),
+				"account liquidity restrictions prevent withdrawal",
+			); // Account 1 cannot send more than vested amount
+		});
+	}
+
+	#[test]
+	fn vested_balance_should_transfer() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(Balances::free_balance(&1), 100);
+			// Account 1 has only 5 units vested at block 1 (plus 50 unvested)
+			assert_eq!(Vesting::vesting_balance(&1), Some(45));
+			assert_ok!(Vesting::vest(Some(1).into()));
+			assert_ok!(Balances::transfer(Some(1).into(), 2, 55));
+		});
+	}
+
+	#[test]
+	fn vested_balance_should_transfer_using_vest_other() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(Vesting::vesting_balance(&1), Some(45));
+			assert_ok!(Vesting::vest_other(Some(2).into(), 1));
+			assert_ok!(Balances::transfer(Some(1).into(), 2, 55));
+			assert_noop!(Vesting::vest_other(Some(2).into(), 3), "not vesting");
+		});
+	}
+
+	#[test]
+	fn fully_vested_account_should_have_lock_removed() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(10);
+			assert_ok!(Vesting::vest(Some(1).into()));
+			assert_eq!(Vesting::vesting(&1), vec![]);
+			assert_eq!(Vesting::vesting_balance(&1), None);
+			assert_eq!(Balances::locks(&1), vec![]);
+			assert_ok!(Balances::transfer(Some(1).into(), 2, 100));
+			assert_noop!(Vesting::vest(Some(1).into()), "not vesting");
+		});
+	}
+
+	#[test]
+	fn extra_balance_should_transfer() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(Balances::transfer(Some(3).into(), 1, 100));
+			assert_ok!(Balances::transfer(Some(3).into(), 2, 100));
+
+			assert_eq!(Balances::free_balance(&1), 200); // Account 1 has 100 more free balance than normal
+			assert_eq!(Balances::free_balance(&2), 300); // Account 2 has 100 more free balance than normal
+
+			// Account 1 has only 5 units vested at block 1 (plus 150 unvested)
+			assert_eq!(Vesting::vesting_balance(&1), Some(45));
+			assert_ok!(Vesting::vest(Some(1).into()));
+			assert_ok!(Balances::transfer(Some(1).into(), 3, 155)); // Account 1 can send extra units gained
+
+			// Account 2 has no units vested at block 1, but gained 100
+			assert_eq!(Vesting::vesting_balance(&2), Some(200));
+			assert_ok!(Vesting::vest(Some(2).into()));
+			assert_ok!(Balances::transfer(Some(2).into(), 3, 100)); // Account 2 can send extra units gained
+		});
+	}
+
+	#[test]
+	fn liquid_funds_should_transfer_with_delayed_vesting() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(Balances::free_balance(&12), 100);
+			// Account 12 has liquid funds
+			assert_eq!(Vesting::vesting_balance(&12), Some(50));
+
+			// Account 12 can still send liquid funds
+			assert_ok!(Balances::transfer(Some(12).into(), 3, 50));
+		});
+	}
+
+	#[test]
+	fn vested_transfer_works() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(Vesting::vesting(&4), vec![]);
+			let new_vesting_schedule = VestingInfo {
+				locked: 100,
+				per_block: 10, // Vesting over 10 blocks
+				starting_block: 10,
+			};
+			assert_ok!(Vesting::vested_transfer(Some(3).into(), 4, new_vesting_schedule));
+			assert_eq!(Balances::free_balance(&3), 200);
+			assert_eq!(Balances::free_balance(&4), 500);
+			assert_eq!(Vesting::vesting(&4), vec![new_vesting_schedule]);
+			assert_eq!(Vesting::vesting_balance(&4), Some(100));
+			assert_noop!(
+				Balances::transfer(Some(4).into(), 3, 401),
+				"account liquidity restrictions prevent withdrawal",
+			);
+
+			// Half way through, half the funds are unlocked.
+			System::set_block_number(15);
+			assert_eq!(Vesting::vesting_balance(&4), Some(50));
+			assert_ok!(Vesting::vest(Some(4).into()));
+			assert_ok!(Balances::transfer(Some(4).into(), 3, 450));
+
+			System::set_block_number(20);
+			assert_ok!(Vesting::vest(Some(4).into()));
+			assert_eq!(Vesting::vesting_balance(&4), None);
+			assert_eq!(Balances::locks(&4), vec![]);
+		});
+	}
+
+	#[test]
+	fn vested_transfer_correctly_fails() {
+		new_test_ext().execute_with(|| {
+			let schedule = VestingInfo { locked: 100, per_block: 10, starting_block: 10 };
+
+			// Too little to be worth a schedule.
+			assert_noop!(
+				Vesting::vested_transfer(Some(3).into(), 4, VestingInfo { locked: 9, .. schedule }),
+				"amount low",
+			);
+			// A schedule which never vests.
+			assert_noop!(
+				Vesting::vested_transfer(Some(3).into(), 4, VestingInfo { per_block: 0, .. schedule }),
+				"invalid schedule",
+			);
+			// Not enough free funds; no schedule is left behind.
+			assert_noop!(
+				Vesting::vested_transfer(Some(3).into(), 4, VestingInfo { locked: 301, .. schedule }),
+				"balance too low to send value",
+			);
+			assert_eq!(Vesting::vesting(&4), vec![]);
+		});
+	}
+
+	#[test]
+	fn multiple_schedules_are_summed() {
+		new_test_ext().execute_with(|| {
+			let schedule = VestingInfo { locked: 20, per_block: 1, starting_block: 1 };
+			assert_ok!(Vesting::vested_transfer(Some(3).into(), 1, schedule));
+			assert_ok!(Vesting::vested_transfer(Some(3).into(), 1, schedule));
+			assert_eq!(Vesting::vesting(&1).len(), 3);
+			assert_noop!(
+				Vesting::vested_transfer(Some(3).into(), 1, schedule),
+				"at max vesting schedules",
+			);
+
+			// 45 from genesis plus 20 from each transfer.
+			assert_eq!(Vesting::vesting_balance(&1), Some(85));
+
+			// The genesis schedule has vested entirely by block 10, the others have 9 left each.
+			System::set_block_number(10);
+			assert_eq!(Vesting::vesting_balance(&1), Some(22));
+			assert_ok!(Vesting::vest(Some(1).into()));
+			assert_eq!(Vesting::vesting(&1), vec![schedule, schedule]);
+			assert_ok!(Balances::transfer(Some(1).into(), 2, 140 - 22));
+			assert_noop!(
+				Balances::transfer(Some(1).into(), 2, 1),
+				"account liquidity restrictions prevent withdrawal",
+			);
+		});
+	}
+
+	#[test]
+	fn legacy_vesting_is_migrated_on_runtime_upgrade() {
+		new_test_ext().execute_with(|| {
+			let schedule = VestingInfo { locked: 300, per_block: 10, starting_block: 1 };
+			let mut key = LEGACY_VESTING_PREFIX.to_vec();
+			3u64.encode_to(&mut key);
+			storage::hashed::put(&runtime_io::blake2_256, &key, &schedule);
+			assert_ok!(Balances::transfer(Some(3).into(), 1, 10));
+
+			assert_eq!(<Module<Test> as OnRuntimeUpgrade>::on_runtime_upgrade(), 100_000);
+
+			assert!(!storage::hashed::exists(&runtime_io::blake2_256, &key));
+			assert_eq!(Vesting::vesting(&3), vec![schedule]);
+			assert_eq!(Vesting::vesting(&4), vec![]);
+			assert_eq!(Vesting::vesting_balance(&3), Some(290));
+			assert_noop!(
+				Balances::transfer(Some(3).into(), 1, 1),
+				"account liquidity restrictions prevent withdrawal",
+			);
+		});
+	}
+}
@@ -194,6 +194,7 @@ fn import_block<Backend, Exec, Block, RtApi>(
 		justification: None,
 		auxiliary: Vec::new(),
 		fork_choice: ForkChoiceStrategy::LongestChain,
+		skip_execution: false,
 	};
 	(&**client).import_block(import, HashMap::new()).expect("Failed to import block");
 }